@@ -0,0 +1,142 @@
+//! Generate a large introspection result and time loading it, the old way (through a
+//! `serde_json::Value`) against the typed pass `load_schema_file` uses now.
+//!
+//! ```bash
+//! cargo run --release --example large_schema                  # 1500 types, 25 fields each
+//! cargo run --release --example large_schema -- 3000 big.json # and keep the file
+//! time ./target/release/gqlmap schema wordlist -s big.json -o /dev/null
+//! ```
+
+use gqlmap::schema::{parse_schema_json, parse_schema_str, schema_wordlist};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+const FIELDS_PER_TYPE: usize = 25;
+const RUNS: usize = 5;
+
+fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let types: usize = match args.next() {
+        Some(count) => count.parse()?,
+        None => 1500,
+    };
+    let output = args.next();
+
+    let content = serde_json::to_string(&introspection(types))?;
+    println!(
+        "{} object types, {} fields each: {:.1}MB",
+        types,
+        FIELDS_PER_TYPE,
+        content.len() as f64 / 1_000_000.0
+    );
+    if let Some(path) = &output {
+        std::fs::write(path, &content)?;
+        println!("written to {}", path);
+    }
+
+    let through_value = best_of(|| {
+        let value: Value = serde_json::from_str(&content).unwrap();
+        parse_schema_json(value).unwrap();
+    });
+    let typed = best_of(|| {
+        parse_schema_str(&content).unwrap();
+    });
+    let schema = parse_schema_str(&content)?;
+    let wordlist = best_of(|| {
+        schema_wordlist(&schema, true);
+    });
+
+    println!("parse through a Value: {:>6}ms", through_value.as_millis());
+    println!("parse in one pass:     {:>6}ms", typed.as_millis());
+    println!("schema wordlist:       {:>6}ms", wordlist.as_millis());
+    Ok(())
+}
+
+/// Fastest of [`RUNS`] runs of `f`
+fn best_of(mut f: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// `{"data": {"__schema": ...}}` with `types` object types, each pointing at the next
+fn introspection(types: usize) -> Value {
+    let mut all: Vec<Value> = (0..types).map(|i| object_type(i, types)).collect();
+    all.push(json!({
+        "kind": "OBJECT",
+        "name": "Query",
+        "description": null,
+        "fields": (0..types.min(100)).map(|i| field(&format!("type{}", i), i, "OBJECT", &type_name(i))).collect::<Vec<_>>(),
+        "inputFields": null,
+        "interfaces": [],
+        "enumValues": null,
+        "possibleTypes": null,
+    }));
+    for scalar in ["String", "Int", "Boolean", "ID"] {
+        all.push(json!({
+            "kind": "SCALAR",
+            "name": scalar,
+            "description": null,
+            "fields": null,
+            "inputFields": null,
+            "interfaces": null,
+            "enumValues": null,
+            "possibleTypes": null,
+        }));
+    }
+
+    json!({
+        "data": {
+            "__schema": {
+                "queryType": {"name": "Query"},
+                "mutationType": null,
+                "subscriptionType": null,
+                "types": all,
+                "directives": [],
+            }
+        }
+    })
+}
+
+fn object_type(index: usize, types: usize) -> Value {
+    let fields: Vec<Value> = (0..FIELDS_PER_TYPE)
+        .map(|f| match f {
+            0 => field("id", f, "SCALAR", "ID"),
+            1 => field("next", f, "OBJECT", &type_name((index + 1) % types)),
+            _ => field(&format!("field{}Value", f), f, "SCALAR", ["String", "Int", "Boolean"][f % 3]),
+        })
+        .collect();
+    json!({
+        "kind": "OBJECT",
+        "name": type_name(index),
+        "description": format!("Generated object type number {}", index),
+        "fields": fields,
+        "inputFields": null,
+        "interfaces": [],
+        "enumValues": null,
+        "possibleTypes": null,
+    })
+}
+
+/// A non-null field with an optional argument
+fn field(name: &str, index: usize, kind: &str, type_name: &str) -> Value {
+    json!({
+        "name": name,
+        "description": format!("Generated field number {}", index),
+        "args": [
+            {"name": "first", "description": null, "type": {"kind": "SCALAR", "name": "Int", "ofType": null}, "defaultValue": null},
+        ],
+        "type": {"kind": "NON_NULL", "name": null, "ofType": {"kind": kind, "name": type_name, "ofType": null}},
+        "isDeprecated": false,
+        "deprecationReason": null,
+    })
+}
+
+fn type_name(index: usize) -> String {
+    format!("GeneratedType{}", index)
+}
@@ -0,0 +1,265 @@
+use super::{classify_error, ErrorClass};
+use crate::http::GraphQLResponse;
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::time::Duration;
+
+/// Extra time over the baseline that counts as a slow response
+const SLOW_THRESHOLD: Duration = Duration::from_secs(4);
+/// ...and how many times the baseline it has to be
+const SLOW_FACTOR: u32 = 3;
+/// Length buckets are powers of two; growing by this many (4x) is worth a look
+const LENGTH_BUCKET_JUMP: u32 = 2;
+/// How deep into `data` and `extensions` the structure goes
+const MAX_SHAPE_DEPTH: usize = 6;
+
+/// What a response looked like, reduced to what can be compared between requests
+#[derive(Debug, Clone)]
+pub struct ResponseProfile {
+    /// None when the request failed outright
+    pub status: Option<u16>,
+    /// Why the request failed, when it did
+    pub failure: Option<String>,
+    /// Classes of the errors returned, with the first message of each
+    pub errors: BTreeMap<ErrorClass, String>,
+    /// Key paths in `data` and `extensions`, e.g. `data.users[].email`
+    pub shape: BTreeSet<String>,
+    /// Size of `data` (or the raw body when there is none) in bytes
+    pub length: usize,
+    pub elapsed: Duration,
+}
+
+impl ResponseProfile {
+    pub fn new(response: &GraphQLResponse, elapsed: Duration) -> Self {
+        let mut errors = BTreeMap::new();
+        for error in response.get_errors().and_then(|e| e.as_array()).into_iter().flatten() {
+            let message = error.get("message").and_then(|m| m.as_str()).unwrap_or_default();
+            errors.entry(classify_error(error)).or_insert_with(|| message.to_string());
+        }
+
+        let mut shape = BTreeSet::new();
+        for key in ["data", "extensions"] {
+            if let Some(value) = response.body.get(key) {
+                collect_shape(value, key.to_string(), 0, &mut shape);
+            }
+        }
+
+        let length = match response.get_data() {
+            Some(data) if !data.is_null() => data.to_string().len(),
+            _ => response.text.len(),
+        };
+
+        Self {
+            status: Some(response.status),
+            failure: None,
+            errors,
+            shape,
+            length,
+            elapsed,
+        }
+    }
+
+    /// Profile of a request that got no response at all
+    pub fn failed(error: &anyhow::Error, elapsed: Duration) -> Self {
+        Self {
+            status: None,
+            failure: Some(error.to_string()),
+            errors: BTreeMap::new(),
+            shape: BTreeSet::new(),
+            length: 0,
+            elapsed,
+        }
+    }
+
+    pub fn from_result(response: &Result<GraphQLResponse>, elapsed: Duration) -> Self {
+        match response {
+            Ok(response) => Self::new(response, elapsed),
+            Err(e) => Self::failed(e, elapsed),
+        }
+    }
+
+    /// Order of magnitude of `length`, in powers of two
+    pub fn length_bucket(&self) -> u32 {
+        usize::BITS - self.length.leading_zeros()
+    }
+}
+
+/// Ways a response can deviate from its baseline, most telling first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AnomalyKind {
+    NoResponse,
+    ServerError,
+    /// Errors of a class the baseline didn't have
+    NewErrors(ErrorClass),
+    /// The baseline's errors went away and data came back
+    ErrorsGone,
+    Slow,
+    /// Keys the baseline didn't have
+    StructureChange,
+    /// Data several times the size of the baseline's
+    LengthChange,
+    StatusChange,
+}
+
+impl AnomalyKind {
+    /// Whether this on its own points at a bug, rather than at the server handling an
+    /// odd value the way it should (a 400, a "not found", a validation error)
+    pub fn is_notable(&self) -> bool {
+        match self {
+            AnomalyKind::NewErrors(class) => class.is_failure(),
+            AnomalyKind::StatusChange => false,
+            _ => true,
+        }
+    }
+}
+
+impl fmt::Display for AnomalyKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnomalyKind::NoResponse => write!(f, "no response"),
+            AnomalyKind::ServerError => write!(f, "server error"),
+            AnomalyKind::NewErrors(class) => write!(f, "{} error", class),
+            AnomalyKind::ErrorsGone => write!(f, "errors gone"),
+            AnomalyKind::Slow => write!(f, "slow response"),
+            AnomalyKind::StructureChange => write!(f, "structure change"),
+            AnomalyKind::LengthChange => write!(f, "length change"),
+            AnomalyKind::StatusChange => write!(f, "status change"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Anomaly {
+    pub kind: AnomalyKind,
+    pub detail: String,
+}
+
+impl Anomaly {
+    fn new(kind: AnomalyKind, detail: impl Into<String>) -> Self {
+        Self {
+            kind,
+            detail: detail.into(),
+        }
+    }
+
+    pub fn is_notable(&self) -> bool {
+        self.kind.is_notable()
+    }
+}
+
+/// Compares responses against a baseline one for the same operation
+#[derive(Debug, Clone)]
+pub struct Differ {
+    baseline: ResponseProfile,
+}
+
+impl Differ {
+    pub fn new(baseline: ResponseProfile) -> Self {
+        Self { baseline }
+    }
+
+    pub fn baseline(&self) -> &ResponseProfile {
+        &self.baseline
+    }
+
+    /// Every way `profile` deviates from the baseline, most telling first
+    pub fn compare(&self, profile: &ResponseProfile) -> Vec<Anomaly> {
+        let baseline = &self.baseline;
+        let mut anomalies = Vec::new();
+
+        let status = match profile.status {
+            Some(status) => status,
+            None => {
+                let failure = profile.failure.clone().unwrap_or_default();
+                return vec![Anomaly::new(AnomalyKind::NoResponse, failure)];
+            }
+        };
+        let baseline_status = baseline.status.unwrap_or_default();
+        if status >= 500 && baseline_status < 500 {
+            anomalies.push(Anomaly::new(
+                AnomalyKind::ServerError,
+                format!("HTTP {} (baseline {})", status, baseline_status),
+            ));
+        } else if status != baseline_status {
+            anomalies.push(Anomaly::new(
+                AnomalyKind::StatusChange,
+                format!("HTTP {} (baseline {})", status, baseline_status),
+            ));
+        }
+
+        for (class, message) in &profile.errors {
+            if !baseline.errors.contains_key(class) {
+                anomalies.push(Anomaly::new(AnomalyKind::NewErrors(*class), message.clone()));
+            }
+        }
+        if !baseline.errors.is_empty() && profile.errors.is_empty() && profile.shape.len() > 1 {
+            let classes: Vec<String> = baseline.errors.keys().map(|c| c.to_string()).collect();
+            anomalies.push(Anomaly::new(
+                AnomalyKind::ErrorsGone,
+                format!("baseline had {} errors", classes.join(", ")),
+            ));
+        }
+
+        if profile.elapsed > baseline.elapsed * SLOW_FACTOR && profile.elapsed > baseline.elapsed + SLOW_THRESHOLD {
+            anomalies.push(Anomaly::new(
+                AnomalyKind::Slow,
+                format!("{} ms (baseline {} ms)", profile.elapsed.as_millis(), baseline.elapsed.as_millis()),
+            ));
+        }
+
+        let added: Vec<&String> = profile.shape.difference(&baseline.shape).collect();
+        if !added.is_empty() {
+            let shown: Vec<&str> = added.iter().take(5).map(|s| s.as_str()).collect();
+            let more = if added.len() > shown.len() {
+                format!(" (+{} more)", added.len() - shown.len())
+            } else {
+                String::new()
+            };
+            anomalies.push(Anomaly::new(
+                AnomalyKind::StructureChange,
+                format!("new keys {}{}", shown.join(", "), more),
+            ));
+        }
+
+        if profile.length_bucket() >= baseline.length_bucket() + LENGTH_BUCKET_JUMP {
+            anomalies.push(Anomaly::new(
+                AnomalyKind::LengthChange,
+                format!("{} bytes (baseline {})", profile.length, baseline.length),
+            ));
+        }
+
+        anomalies.sort_by_key(|a| a.kind);
+        anomalies
+    }
+
+    /// The most telling deviation that points at a bug, if any
+    pub fn classify(&self, profile: &ResponseProfile) -> Option<Anomaly> {
+        self.compare(profile).into_iter().find(Anomaly::is_notable)
+    }
+}
+
+/// Key paths under `value`, with array items folded into `[]`
+fn collect_shape(value: &Value, path: String, depth: usize, shape: &mut BTreeSet<String>) {
+    if depth > MAX_SHAPE_DEPTH {
+        return;
+    }
+    match value {
+        Value::Object(map) => {
+            for (key, item) in map {
+                let path = format!("{}.{}", path, key);
+                collect_shape(item, path.clone(), depth + 1, shape);
+                shape.insert(path);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_shape(item, format!("{}[]", path), depth + 1, shape);
+            }
+        }
+        _ => {}
+    }
+}
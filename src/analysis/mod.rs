@@ -0,0 +1,7 @@
+mod differ;
+mod outcome;
+mod taxonomy;
+
+pub use differ::*;
+pub use outcome::*;
+pub use taxonomy::*;
@@ -0,0 +1,162 @@
+use super::{classify_error, ErrorClass};
+use crate::http::GraphQLResponse;
+use crate::schema::Schema;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// What requests through an operation got, weakest first: an operation keeps the
+/// strongest outcome any request got
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FieldOutcome {
+    /// No request reached it
+    Untested,
+    /// Requests never got past validation
+    ValidationError,
+    /// Requests were turned away for lack of credentials or permissions
+    AuthDenied,
+    /// The resolver ran
+    Success,
+    /// Something was reported for it
+    Anomaly,
+}
+
+impl FieldOutcome {
+    pub const ALL: [FieldOutcome; 5] = [
+        FieldOutcome::Anomaly,
+        FieldOutcome::Success,
+        FieldOutcome::AuthDenied,
+        FieldOutcome::ValidationError,
+        FieldOutcome::Untested,
+    ];
+
+    /// Outcome of one request selecting root field `field`; None when it says nothing
+    /// (no response, an error that isn't about validation or access)
+    pub fn of_response(response: &GraphQLResponse, field: &str) -> Option<Self> {
+        let classes: Vec<ErrorClass> = response
+            .get_errors()
+            .and_then(|e| e.as_array())
+            .into_iter()
+            .flatten()
+            .map(classify_error)
+            .collect();
+        // A null field with no errors at all still means the resolver ran
+        let resolved = match response.get_data().and_then(|d| d.get(field)) {
+            Some(Value::Null) => classes.is_empty(),
+            Some(_) => true,
+            None => false,
+        };
+
+        if resolved && response.status < 400 {
+            Some(FieldOutcome::Success)
+        } else if matches!(response.status, 401 | 403) || classes.contains(&ErrorClass::Authorization) {
+            Some(FieldOutcome::AuthDenied)
+        } else if classes.iter().any(|c| matches!(c, ErrorClass::Validation | ErrorClass::BadInput | ErrorClass::QueryLimit)) {
+            Some(FieldOutcome::ValidationError)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Display for FieldOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            FieldOutcome::Untested => "untested",
+            FieldOutcome::ValidationError => "validation-error",
+            FieldOutcome::AuthDenied => "auth-denied",
+            FieldOutcome::Success => "success",
+            FieldOutcome::Anomaly => "anomaly",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FieldRecord {
+    /// query or mutation
+    pub kind: String,
+    pub name: String,
+    pub outcome: FieldOutcome,
+    pub requests: usize,
+}
+
+/// Which root fields a run exercised, and how far it got with each
+#[derive(Debug, Clone, Serialize)]
+pub struct OutcomeReport {
+    pub total: usize,
+    pub counts: BTreeMap<FieldOutcome, usize>,
+    pub fields: Vec<FieldRecord>,
+}
+
+impl OutcomeReport {
+    pub fn count(&self, outcome: FieldOutcome) -> usize {
+        self.counts.get(&outcome).copied().unwrap_or(0)
+    }
+
+    /// Fields at least one request reached
+    pub fn exercised(&self) -> usize {
+        self.total - self.count(FieldOutcome::Untested)
+    }
+}
+
+/// Keeps the outcome of every query and mutation field over a run
+#[derive(Debug, Clone, Default)]
+pub struct OutcomeTracker {
+    fields: BTreeMap<(String, String), FieldRecord>,
+}
+
+impl OutcomeTracker {
+    /// Every query and mutation field, untested
+    pub fn new(schema: &Schema) -> Self {
+        let mut fields = BTreeMap::new();
+        for (kind, root) in [("query", schema.get_query_type()), ("mutation", schema.get_mutation_type())] {
+            for field in root.and_then(|t| t.fields.as_ref()).into_iter().flatten() {
+                if field.name.starts_with("__") {
+                    continue;
+                }
+                let record = FieldRecord {
+                    kind: kind.to_string(),
+                    name: field.name.clone(),
+                    outcome: FieldOutcome::Untested,
+                    requests: 0,
+                };
+                fields.insert((kind.to_string(), field.name.clone()), record);
+            }
+        }
+        Self { fields }
+    }
+
+    /// Count a request to `kind` field `name`, keeping `outcome` if it's stronger than
+    /// what the field had
+    pub fn record(&mut self, kind: &str, name: &str, outcome: Option<FieldOutcome>) {
+        if let Some(record) = self.fields.get_mut(&(kind.to_string(), name.to_string())) {
+            record.requests += 1;
+            if let Some(outcome) = outcome {
+                record.outcome = record.outcome.max(outcome);
+            }
+        }
+    }
+
+    /// Mark a field as having had something reported, without counting a request
+    pub fn flag(&mut self, kind: &str, name: &str) {
+        if let Some(record) = self.fields.get_mut(&(kind.to_string(), name.to_string())) {
+            record.outcome = FieldOutcome::Anomaly;
+        }
+    }
+
+    pub fn report(&self) -> OutcomeReport {
+        let fields: Vec<FieldRecord> = self.fields.values().cloned().collect();
+        let mut counts = BTreeMap::new();
+        for record in &fields {
+            *counts.entry(record.outcome).or_insert(0) += 1;
+        }
+        OutcomeReport {
+            total: fields.len(),
+            counts,
+            fields,
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// What kind of failure a GraphQL error reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorClass {
+    /// A depth, complexity or cost limit turned the query down
+    QueryLimit,
+    /// Turned away for how the request was sent: a mutation over GET, a CSRF guard
+    Transport,
+    /// Database driver errors leaking through
+    Database,
+    /// Unhandled exceptions, stack traces
+    Internal,
+    Timeout,
+    RateLimit,
+    Authorization,
+    NotFound,
+    /// The resolver rejected a value
+    BadInput,
+    /// The document or variables didn't validate
+    Validation,
+    Other,
+}
+
+impl ErrorClass {
+    /// Whether this class showing up where it didn't before means something broke,
+    /// rather than the server turning a value down
+    pub fn is_failure(&self) -> bool {
+        matches!(self, ErrorClass::Database | ErrorClass::Internal | ErrorClass::Timeout)
+    }
+}
+
+impl fmt::Display for ErrorClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorClass::QueryLimit => "query limit",
+            ErrorClass::Transport => "transport",
+            ErrorClass::Database => "database",
+            ErrorClass::Internal => "internal",
+            ErrorClass::Timeout => "timeout",
+            ErrorClass::RateLimit => "rate limit",
+            ErrorClass::Authorization => "authorization",
+            ErrorClass::NotFound => "not found",
+            ErrorClass::BadInput => "bad input",
+            ErrorClass::Validation => "validation",
+            ErrorClass::Other => "other",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// Apollo-style extension codes
+const CODES: &[(&str, ErrorClass)] = &[
+    ("GRAPHQL_VALIDATION_FAILED", ErrorClass::Validation),
+    ("GRAPHQL_PARSE_FAILED", ErrorClass::Validation),
+    ("BAD_USER_INPUT", ErrorClass::BadInput),
+    ("UNAUTHENTICATED", ErrorClass::Authorization),
+    ("FORBIDDEN", ErrorClass::Authorization),
+    ("NOT_FOUND", ErrorClass::NotFound),
+    ("INTERNAL_SERVER_ERROR", ErrorClass::Internal),
+];
+
+/// How engines and limit plugins word a query turned down for its depth, complexity or
+/// cost. Apollo codes these GRAPHQL_VALIDATION_FAILED, so they're matched first.
+const LIMIT_PATTERNS: &[&str] = &[
+    // graphql-depth-limit, Strawberry: 'deep' exceeds maximum operation depth of 10
+    r"exceeds maximum operation depth",
+    // graphql-query-complexity, graphql-cost-analysis: The query exceeds the maximum complexity of 1000
+    r"exceeds the maximum (complexity|cost)",
+    // GraphQL Armor: Syntax Error: Query depth limit of 6 exceeded, found 7.
+    r"(depth|cost|token|alias(es)?|directives?) limit of \d+ exceeded",
+    // graphql-java: maximum query depth exceeded 11 > 10
+    r"maximum query (depth|complexity) exceeded",
+    // graphql-ruby: Query has depth of 15, which exceeds max depth of 13
+    r"exceeds max (depth|complexity)",
+    // Absinthe: Operation users is too complex: complexity is 3000 and maximum is 100
+    r"is too complex: complexity is",
+    // async-graphql: Query is nested too deep. / Query is too complex.
+    r"query is (nested too deep|too complex)",
+    // gqlgen: operation has complexity 1500, which exceeds the limit of 1000
+    r"has complexity \d+, which exceeds the limit",
+    // graphql-php, Lighthouse: Max query depth should be 10 but got 12.
+    r"max query (depth|complexity) should be",
+    // Hot Chocolate: ...has an execution depth of 20 which exceeds the max allowed execution depth of 15.
+    r"max allowed execution depth|maximum allowed operation complexity",
+    // Anything else naming a limit, and the same in Spanish, Portuguese, German and French
+    r"(max(imum)?|query|operation) (depth|complexity|cost)\b|too (deep|complex)",
+    r"profundidad|profundidade|abfragetiefe|profondeur|complejidad|complexidade|komplexität|complexité",
+];
+
+/// How servers word a request turned away for its method or for looking like CSRF
+const TRANSPORT_PATTERNS: &[&str] = &[
+    // graphql-http, express-graphql, Yoga: Can only perform a mutation operation from a POST request.
+    r"only perform a \w+ operation from a POST",
+    // Apollo Server: GET supports only query operation
+    r"GET supports only query",
+    // Apollo Server: This operation has been blocked as a potential Cross-Site Request Forgery (CSRF).
+    r"potential Cross-Site Request Forgery",
+    // Strawberry: mutations are not allowed when using GET
+    r"not allowed when using GET",
+    // Hot Chocolate: The specified operation kind is not allowed.
+    r"operation kind is not allowed",
+    r"\b(mutations?|method)\b.*\bnot (allowed|supported)|not (allowed|supported) (over|via|with|using|for) GET\b",
+];
+
+/// Checked in order, so database errors win over the generic "syntax error" of validation
+const MESSAGE_PATTERNS: &[(ErrorClass, &str)] = &[
+    (
+        ErrorClass::Database,
+        r"(?i)(sql syntax|sqlstate|syntax error at or near|unterminated quoted string|ORA-\d{5}|SQLITE_|pg_query|mysql|postgres|unclosed quotation mark|MongoError|MongoServerError|CastError|BSON|duplicate key)",
+    ),
+    (
+        ErrorClass::Internal,
+        r#"(?i)(internal server error|internal error|unexpected error|exception|traceback|stacktrace|undefined method|cannot read propert|is not a function|null pointer|panicked at|\bat [\w$.<>]+ \([^)]*:\d+:\d+\)|\.java:\d+\)|\.rb:\d+:in|\.py", line \d+)"#,
+    ),
+    (ErrorClass::Timeout, r"(?i)(timed? ?out|deadline exceeded)"),
+    (ErrorClass::RateLimit, r"(?i)(rate limit|too many requests|throttl)"),
+    (
+        ErrorClass::Authorization,
+        r"(?i)(not authori[sz]ed|unauthori[sz]ed|forbidden|permission|access denied|not allowed|must be (logged in|authenticated)|authentication required|invalid token)",
+    ),
+    (ErrorClass::NotFound, r"(?i)(not found|does not exist|doesn't exist|no such|could not find)"),
+    (
+        ErrorClass::Validation,
+        r#"(?i)(cannot query field|unknown argument|unknown type|syntax error|expected type|got invalid value|of required type|was not provided|cannot represent|selection of subfields)"#,
+    ),
+    (ErrorClass::BadInput, r"(?i)(invalid|must be|is required|malformed|too long|too short|out of range|not a valid)"),
+];
+
+fn message_patterns() -> &'static [(ErrorClass, Regex)] {
+    static PATTERNS: OnceLock<Vec<(ErrorClass, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let one_of = |patterns: &[&str]| Regex::new(&format!("(?i)({})", patterns.join("|"))).unwrap();
+        let mut patterns = vec![
+            (ErrorClass::QueryLimit, one_of(LIMIT_PATTERNS)),
+            (ErrorClass::Transport, one_of(TRANSPORT_PATTERNS)),
+        ];
+        patterns.extend(
+            MESSAGE_PATTERNS
+                .iter()
+                .map(|(class, pattern)| (*class, Regex::new(pattern).unwrap())),
+        );
+        patterns
+    })
+}
+
+/// Class of one entry of a response's `errors`: its message first (a leaked driver
+/// error or a limit plugin says more than a generic code), then its `extensions.code`
+pub fn classify_error(error: &Value) -> ErrorClass {
+    let message = error.get("message").and_then(|m| m.as_str()).unwrap_or_default();
+    let extensions = error.get("extensions").map(|e| e.to_string()).unwrap_or_default();
+    let text = format!("{} {}", message, extensions);
+
+    for (class, regex) in message_patterns() {
+        let matched = match class {
+            // Apollo codes everything unhandled INTERNAL_SERVER_ERROR; only the text says database
+            ErrorClass::Database | ErrorClass::Internal => regex.is_match(&text),
+            // Limits and method checks come coded as validation or bad requests
+            ErrorClass::QueryLimit | ErrorClass::Transport => regex.is_match(message),
+            _ => false,
+        };
+        if matched {
+            return *class;
+        }
+    }
+    let code = error
+        .get("extensions")
+        .and_then(|e| e.get("code"))
+        .and_then(|c| c.as_str())
+        .unwrap_or_default();
+    if let Some((_, class)) = CODES.iter().find(|(c, _)| *c == code) {
+        return *class;
+    }
+    message_patterns()
+        .iter()
+        .find(|(_, regex)| regex.is_match(message))
+        .map(|(class, _)| *class)
+        .unwrap_or(ErrorClass::Other)
+}
@@ -0,0 +1,228 @@
+use crate::http::{GraphQLResponse, HttpClient};
+use crate::oob::random_label;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// Messages and codes servers answer an unknown hash with
+const NOT_FOUND: &[&str] = &["persistedquerynotfound", "persisted_query_not_found"];
+/// ... and what they say when they don't do APQ at all
+const NOT_SUPPORTED: &[&str] = &[
+    "persistedquerynotsupported",
+    "persisted_query_not_supported",
+    "must provide query",
+    "query string",
+    "no query",
+    "query is required",
+];
+
+/// The sha256 an APQ client sends for `query`: of the exact text, trailing whitespace
+/// aside (files end in a newline, printed documents don't)
+pub fn apq_hash(query: &str) -> String {
+    openssl::sha::sha256(query.trim_end().as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// The `extensions` object that asks for the persisted query `hash`
+pub fn apq_extensions(hash: &str) -> Value {
+    json!({"persistedQuery": {"version": 1, "sha256Hash": hash}})
+}
+
+/// What the server made of a hash
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PersistedStatus {
+    /// It found a document for it: data, or errors from running it (missing variables,
+    /// auth)
+    Registered,
+    /// PersistedQueryNotFound
+    NotFound,
+    /// The server ignores APQ, or has it turned off
+    Unsupported,
+    /// No response, or one that's no GraphQL
+    Unknown,
+}
+
+impl std::fmt::Display for PersistedStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PersistedStatus::Registered => "registered",
+            PersistedStatus::NotFound => "not-found",
+            PersistedStatus::Unsupported => "unsupported",
+            PersistedStatus::Unknown => "unknown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A hash to try, with the operation it's known to belong to
+#[derive(Debug, Clone)]
+pub struct KnownHash {
+    pub hash: String,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PersistedProbe {
+    pub hash: String,
+    /// Operation name, when the hash came from a harvested document
+    pub label: Option<String>,
+    pub status: PersistedStatus,
+    pub http_status: Option<u16>,
+    /// First error message, or the data that came back, cut short
+    pub detail: Option<String>,
+    pub curl_command: Option<String>,
+}
+
+/// Sends bare persisted-query hashes to see which the server knows
+pub struct PersistedQueryProber {
+    client: HttpClient,
+    url: String,
+    get: bool,
+    requests: usize,
+}
+
+impl PersistedQueryProber {
+    pub fn new(client: HttpClient, url: &str) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            get: false,
+            requests: 0,
+        }
+    }
+
+    /// Send hashes as GET query strings, the way CDN-cached APQ setups expect
+    pub fn with_get(mut self, get: bool) -> Self {
+        self.get = get;
+        self
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    /// How the server answers a hash nobody registered: NotFound means APQ is on and
+    /// the answers to real hashes mean something
+    pub async fn check_support(&mut self) -> PersistedStatus {
+        let hash = apq_hash(&random_label(32));
+        self.probe(&KnownHash { hash, label: None }).await.status
+    }
+
+    pub async fn run(
+        &mut self,
+        hashes: &[KnownHash],
+        callback: Option<&dyn Fn(&PersistedProbe)>,
+    ) -> Vec<PersistedProbe> {
+        let mut probes = Vec::new();
+        for known in hashes {
+            let probe = self.probe(known).await;
+            if let Some(cb) = callback {
+                cb(&probe);
+            }
+            probes.push(probe);
+        }
+        probes
+    }
+
+    async fn probe(&mut self, known: &KnownHash) -> PersistedProbe {
+        self.requests += 1;
+        let extensions = apq_extensions(&known.hash);
+        let response = if self.get {
+            let params = [("extensions", extensions.to_string())];
+            self.client.get_params(&self.url, &params, Some("apq")).await
+        } else {
+            self.client
+                .post_json(&self.url, &json!({"extensions": extensions}), Some("apq"))
+                .await
+        };
+
+        let (status, detail) = match &response {
+            Ok(response) => classify(response),
+            Err(e) => (PersistedStatus::Unknown, Some(e.to_string())),
+        };
+        let response = response.ok();
+        PersistedProbe {
+            hash: known.hash.clone(),
+            label: known.label.clone(),
+            status,
+            http_status: response.as_ref().map(|r| r.status),
+            detail,
+            curl_command: response.map(|r| r.curl_command),
+        }
+    }
+}
+
+fn classify(response: &GraphQLResponse) -> (PersistedStatus, Option<String>) {
+    let errors: Vec<&Value> = response.get_errors().and_then(|e| e.as_array()).into_iter().flatten().collect();
+    let mentions = |needles: &[&str]| {
+        errors.iter().any(|e| {
+            let text = e.to_string().to_lowercase();
+            needles.iter().any(|n| text.contains(n))
+        })
+    };
+    let message = response.get_first_error_message();
+
+    if mentions(NOT_FOUND) {
+        (PersistedStatus::NotFound, message)
+    } else if mentions(NOT_SUPPORTED) {
+        (PersistedStatus::Unsupported, message)
+    } else if let Some(data) = response.get_data().filter(|d| !d.is_null()) {
+        let data = data.to_string();
+        let end = data.floor_char_boundary(120);
+        (PersistedStatus::Registered, Some(data[..end].to_string()))
+    } else if !errors.is_empty() {
+        (PersistedStatus::Registered, message)
+    } else {
+        (PersistedStatus::Unknown, Some(format!("HTTP {}", response.status)))
+    }
+}
+
+/// Hashes to try from a file: `discover --operations` output (its persisted hashes, and
+/// the hashes of its documents), or one hash per line with an optional label after it
+pub fn load_hashes(path: &Path) -> Result<Vec<KnownHash>> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut hashes: Vec<KnownHash> = Vec::new();
+    let mut push = |hash: String, label: Option<String>| {
+        if !hashes.iter().any(|h| h.hash == hash) {
+            hashes.push(KnownHash { hash, label });
+        }
+    };
+
+    if let Ok(saved) = serde_json::from_str::<Value>(&content) {
+        let strings = |key: &str| -> Vec<String> {
+            saved
+                .get(key)
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        };
+        for hash in strings("persisted_hashes") {
+            push(hash.to_lowercase(), None);
+        }
+        let name_regex = Regex::new(r"\b(?:query|mutation|subscription)\s+([A-Za-z_]\w*)").unwrap();
+        for query in strings("queries") {
+            let label = name_regex.captures(&query).map(|c| c[1].to_string());
+            push(apq_hash(&query), label);
+        }
+        return Ok(hashes);
+    }
+
+    for line in content.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.split_whitespace();
+        let hash = parts.next().unwrap_or_default().to_lowercase();
+        let label = parts.next().map(String::from);
+        push(hash, label);
+    }
+    Ok(hashes)
+}
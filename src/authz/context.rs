@@ -0,0 +1,56 @@
+use crate::http::interpolate;
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// What `--context` takes for a request without credentials
+pub const ANONYMOUS: &str = "anon";
+
+/// A named set of headers to send requests as, e.g. an admin session
+#[derive(Debug, Clone)]
+pub struct AuthContext {
+    pub name: String,
+    pub headers: HashMap<String, String>,
+}
+
+impl AuthContext {
+    pub fn anonymous() -> Self {
+        Self {
+            name: ANONYMOUS.to_string(),
+            headers: HashMap::new(),
+        }
+    }
+
+    /// `anon`, or a file of `Name: value` header lines named after the context
+    /// (`admin.headers` is `admin`). Blank lines and `#` comments are skipped, and
+    /// `{{env:NAME}}` / `{{file:PATH}}` in values are filled in.
+    pub fn load(spec: &str) -> Result<Self> {
+        if spec == ANONYMOUS {
+            return Ok(Self::anonymous());
+        }
+
+        let path = Path::new(spec);
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .with_context(|| format!("{}: no context name", spec))?
+            .to_string();
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", spec))?;
+
+        let mut headers = HashMap::new();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match line.split_once(':') {
+                Some((key, value)) => {
+                    let value = interpolate(value.trim()).with_context(|| format!("{}: {}", spec, key.trim()))?;
+                    headers.insert(key.trim().to_string(), value);
+                }
+                None => bail!("{}: invalid header line: {}", spec, line),
+            }
+        }
+
+        Ok(Self { name, headers })
+    }
+}
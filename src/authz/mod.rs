@@ -0,0 +1,262 @@
+mod context;
+
+pub use context::*;
+
+use crate::analysis::{classify_error, ErrorClass, FieldOutcome, OutcomeReport, OutcomeTracker};
+use crate::exec::VariableOverrides;
+use crate::export::{CatalogExporter, OperationFilter};
+use crate::guard::{MutationClass, MutationGuard};
+use crate::http::{GraphQLResponse, HttpClient};
+use crate::schema::Schema;
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+/// What a context got back for an operation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Access {
+    /// The resolver ran and returned (possibly partial) data
+    Allowed,
+    /// 401/403, or an authentication or authorization error
+    Denied,
+    /// Anything else: validation errors, not found, no response
+    Error,
+}
+
+impl std::fmt::Display for Access {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Access::Allowed => "allowed",
+            Access::Denied => "denied",
+            Access::Error => "error",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessCell {
+    pub context: String,
+    pub access: Access,
+    pub status: Option<u16>,
+    /// First error message, or why there was no response
+    pub detail: Option<String>,
+    /// A less privileged context got in where it likely shouldn't have
+    pub unexpected: bool,
+    #[serde(skip)]
+    data: Option<Value>,
+}
+
+/// One operation run under every context, in the order they were given
+#[derive(Debug, Clone, Serialize)]
+pub struct AccessRow {
+    pub operation: String,
+    /// query or mutation
+    pub kind: String,
+    pub cells: Vec<AccessCell>,
+}
+
+impl AccessRow {
+    pub fn has_unexpected(&self) -> bool {
+        self.cells.iter().any(|c| c.unexpected)
+    }
+}
+
+/// Runs every operation once per auth context and tabulates who got in. Contexts go from
+/// most to least privileged.
+pub struct AccessMatrix {
+    url: String,
+    schema: Schema,
+    contexts: Vec<(String, HttpClient)>,
+    filter: OperationFilter,
+    mutations: bool,
+    guard: MutationGuard,
+    variables: VariableOverrides,
+    held_back: Vec<(String, MutationClass)>,
+    outcomes: OutcomeTracker,
+    requests: usize,
+}
+
+impl AccessMatrix {
+    pub fn new(url: &str, schema: Schema) -> Self {
+        Self {
+            url: url.to_string(),
+            outcomes: OutcomeTracker::new(&schema),
+            schema,
+            contexts: Vec::new(),
+            filter: OperationFilter::default(),
+            mutations: false,
+            guard: MutationGuard::default(),
+            variables: VariableOverrides::default(),
+            held_back: Vec::new(),
+            requests: 0,
+        }
+    }
+
+    /// Add a context, less privileged than the ones before it. `client` sends its headers.
+    pub fn with_context(mut self, name: &str, client: HttpClient) -> Self {
+        self.contexts.push((name.to_string(), client));
+        self
+    }
+
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Run mutations too, those `guard` lets through. They really run, once per context.
+    pub fn with_mutations(mut self, mutations: bool, guard: MutationGuard) -> Self {
+        self.mutations = mutations;
+        self.guard = guard;
+        self
+    }
+
+    /// Values to send instead of the generated placeholders
+    pub fn with_variables(mut self, variables: VariableOverrides) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Mutations the guard kept from running, with their class
+    pub fn held_back(&self) -> &[(String, MutationClass)] {
+        &self.held_back
+    }
+
+    /// Context names, most privileged first
+    pub fn context_names(&self) -> Vec<&str> {
+        self.contexts.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    /// How far requests got with each query and mutation field so far, over every context
+    pub fn outcomes(&self) -> OutcomeReport {
+        self.outcomes.report()
+    }
+
+    pub async fn run(&mut self, callback: Option<&dyn Fn(&AccessRow)>) -> Result<Vec<AccessRow>> {
+        let catalog = CatalogExporter::new(self.schema.clone())
+            .with_filter(self.filter.clone())
+            .export();
+
+        let mut rows = Vec::new();
+        for operation in catalog.operations {
+            if operation.kind != "query" && !(self.mutations && operation.kind == "mutation") {
+                continue;
+            }
+            if operation.kind == "mutation" {
+                let field = self
+                    .schema
+                    .get_mutation_type()
+                    .and_then(|t| t.fields.as_ref())
+                    .and_then(|fields| fields.iter().find(|f| f.name == operation.name));
+                if let Some(field) = field {
+                    let (class, allowed) = self.guard.check(field);
+                    if !allowed {
+                        self.held_back.push((operation.name, class));
+                        continue;
+                    }
+                }
+            }
+
+            let declared: Vec<&str> = operation.arguments.iter().map(|a| a.name.as_str()).collect();
+            let mut variables = operation.example.variables.clone();
+            self.variables.apply(&operation.name, &declared, &mut variables);
+
+            let mut cells = Vec::new();
+            for (name, client) in &self.contexts {
+                self.requests += 1;
+                let response = client
+                    .post_graphql(&self.url, &operation.example.query, Some(variables.clone()), Some("authz"))
+                    .await;
+                let outcome = response.as_ref().ok().and_then(|r| FieldOutcome::of_response(r, &operation.name));
+                self.outcomes.record(&operation.kind, &operation.name, outcome);
+                cells.push(access_cell(name, &operation.name, &response));
+            }
+            flag_unexpected(&mut cells);
+            if cells.iter().any(|c| c.unexpected) {
+                self.outcomes.flag(&operation.kind, &operation.name);
+            }
+
+            let row = AccessRow {
+                operation: operation.name,
+                kind: operation.kind,
+                cells,
+            };
+            if let Some(cb) = callback {
+                cb(&row);
+            }
+            rows.push(row);
+        }
+
+        Ok(rows)
+    }
+}
+
+fn access_cell(context: &str, field: &str, response: &Result<GraphQLResponse>) -> AccessCell {
+    let response = match response {
+        Ok(r) => r,
+        Err(e) => {
+            return AccessCell {
+                context: context.to_string(),
+                access: Access::Error,
+                status: None,
+                detail: Some(e.to_string()),
+                unexpected: false,
+                data: None,
+            }
+        }
+    };
+
+    let errors: Vec<&Value> = response.get_errors().and_then(|e| e.as_array()).into_iter().flatten().collect();
+    let data = response.get_data().and_then(|d| d.get(field)).cloned();
+    let denied = matches!(response.status, 401 | 403)
+        || errors.iter().any(|e| classify_error(e) == ErrorClass::Authorization);
+    // A null field with no errors at all still means the resolver ran
+    let resolved = match &data {
+        Some(Value::Null) => errors.is_empty(),
+        Some(_) => true,
+        None => false,
+    };
+
+    let access = if denied && data.as_ref().is_none_or(|d| d.is_null()) {
+        Access::Denied
+    } else if resolved && response.status < 400 {
+        Access::Allowed
+    } else {
+        Access::Error
+    };
+
+    AccessCell {
+        context: context.to_string(),
+        access,
+        status: Some(response.status),
+        detail: response.get_first_error_message(),
+        unexpected: false,
+        data: data.filter(|d| !d.is_null()),
+    }
+}
+
+/// Flag allowed cells that look like broken access control: a context getting in where
+/// a more privileged one was turned away, or getting exactly the most privileged
+/// context's data for an operation some context is denied
+fn flag_unexpected(cells: &mut [AccessCell]) {
+    let protected = cells.iter().any(|c| c.access == Access::Denied);
+    let top_data = cells
+        .first()
+        .filter(|c| c.access == Access::Allowed)
+        .and_then(|c| c.data.clone());
+
+    for i in 1..cells.len() {
+        if cells[i].access != Access::Allowed {
+            continue;
+        }
+        let inverted = cells[..i].iter().any(|c| c.access == Access::Denied);
+        let same_as_top = protected && cells[i].data.is_some() && cells[i].data == top_data;
+        cells[i].unexpected = inverted || same_as_top;
+    }
+}
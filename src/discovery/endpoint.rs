@@ -1,6 +1,13 @@
-use crate::http::HttpClient;
-use crate::tests::is_graphql_endpoint;
+use crate::http::{ws_url, GraphQLSocket, HttpClient};
+use crate::schema::EngineProfile;
+use crate::tests::{
+    batch_signal, confidence, engine_hint, looks_like_graphql, page_signals, response_signals, Signal, Transport, DETECTION_QUERY,
+    GRAPHQL_THRESHOLD,
+};
 use anyhow::Result;
+use serde::Serialize;
+use serde_json::json;
+use std::time::Duration;
 use url::Url;
 
 const DEFAULT_PATHS: &[&str] = &[
@@ -20,9 +27,127 @@ const DEFAULT_PATHS: &[&str] = &[
     "/api",
 ];
 
+/// Where each platform puts its GraphQL endpoint, for `--paths`
+const PATH_PACKS: &[(&str, &[&str])] = &[
+    ("hasura", &["/v1/graphql", "/v1beta1/relay", "/v1/relay", "/v1alpha1/graphql", "/console"]),
+    ("wordpress", &["/graphql", "/wp/graphql", "/index.php?graphql", "/?graphql", "/wp-json/graphql"]),
+    (
+        "shopify",
+        &[
+            "/api/graphql.json",
+            "/api/unstable/graphql.json",
+            "/api/2024-01/graphql.json",
+            "/api/2024-04/graphql.json",
+            "/api/2024-07/graphql.json",
+            "/api/2024-10/graphql.json",
+            "/api/2025-01/graphql.json",
+            "/admin/api/graphql.json",
+            "/admin/api/2024-10/graphql.json",
+            "/admin/api/2025-01/graphql.json",
+        ],
+    ),
+    ("saleor", &["/graphql/", "/api/graphql/", "/dashboard/graphql/"]),
+    ("magento", &["/graphql", "/pub/graphql", "/api/graphql", "/default/graphql"]),
+    ("appsync", &["/graphql", "/graphql/realtime"]),
+    ("drupal", &["/graphql", "/graphql/explorer", "/graphql/voyager"]),
+    ("craft", &["/api", "/actions/graphql/api", "/index.php?action=graphql/api"]),
+    ("strapi", &["/graphql", "/api/graphql"]),
+    ("directus", &["/graphql", "/graphql/system"]),
+    ("keystone", &["/api/graphql", "/admin/api"]),
+    ("gitlab", &["/api/graphql", "/-/graphql-explorer"]),
+];
+
+/// Names `--paths` accepts
+pub fn path_pack_names() -> Vec<&'static str> {
+    PATH_PACKS.iter().map(|(name, _)| *name).collect()
+}
+
+/// The paths of a named pack (case-insensitive)
+pub fn path_pack(name: &str) -> Option<&'static [&'static str]> {
+    PATH_PACKS
+        .iter()
+        .find(|(pack, _)| pack.eq_ignore_ascii_case(name))
+        .map(|(_, paths)| *paths)
+}
+
+/// Where an API gets mounted, for `--combine`; `services/<name>` adds to these
+const COMBINE_PREFIXES: &[&str] = &["api", "internal", "private", "public", "admin", "gateway", "backend"];
+const COMBINE_SERVICES: &[&str] = &["api", "graphql", "gateway", "core", "auth", "users", "accounts", "search"];
+const COMBINE_VERSIONS: &[&str] = &["v1", "v2", "v3", "v4"];
+const COMBINE_SUFFIXES: &[&str] = &["graphql", "gql", "graph", "query", "graphiql", "playground"];
+
+/// `[prefix/][version/]suffix` for every prefix, version and suffix, shortest first and
+/// capped at `max_paths`. `service` (the target's first host label) joins the
+/// `services/<name>` prefixes.
+pub fn combined_paths(service: Option<&str>, max_paths: usize) -> Vec<String> {
+    let mut prefixes: Vec<String> = vec![String::new()];
+    prefixes.extend(COMBINE_PREFIXES.iter().map(|p| format!("/{}", p)));
+    let services = COMBINE_SERVICES.iter().copied().chain(service.filter(|s| !COMBINE_SERVICES.contains(s)));
+    prefixes.extend(services.map(|s| format!("/services/{}", s)));
+
+    let mut versions: Vec<String> = vec![String::new()];
+    versions.extend(COMBINE_VERSIONS.iter().map(|v| format!("/{}", v)));
+
+    let mut paths = Vec::new();
+    for version in &versions {
+        for prefix in &prefixes {
+            for suffix in COMBINE_SUFFIXES {
+                paths.push(format!("{}{}/{}", prefix, version, suffix));
+            }
+        }
+    }
+    paths.sort_by_key(|p| p.matches('/').count());
+    paths.truncate(max_paths);
+    paths
+}
+
+/// Where subscription servers usually listen
+const WEBSOCKET_PATHS: &[&str] = &["/graphql", "/subscriptions", "/socket", "/ws", "/graphql/ws", "/graphql/realtime"];
+
+/// How long a socket gets to acknowledge `connection_init`
+const WEBSOCKET_ACK_WAIT: Duration = Duration::from_secs(5);
+
+/// Ports `--ports` tries by default: Apollo/Express, Hasura, Flask, alt HTTP(S) and friends
+pub const DEV_PORTS: &[u16] = &[3000, 4000, 5000, 8000, 8080, 8443, 9000];
+
+// Selects a field no schema has, so the error wording gives the engine away
+const FINGERPRINT_QUERY: &str = "query { gqlmapFingerprint }";
+
+/// What one candidate URL answered
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointCandidate {
+    pub url: String,
+    /// HTTP status, `None` when the request failed
+    pub status: Option<u16>,
+    pub graphql: bool,
+    /// Root type name `__typename` returned, when the query ran
+    pub root_type: Option<String>,
+    /// Engine its error messages look like, or failing that its headers
+    pub engine: Option<String>,
+    /// How sure it's GraphQL, 0-100 (see [`confidence`])
+    pub confidence: u8,
+    /// Where the URL came from: `path` (wordlist), `bundle` (the site's JavaScript) or
+    /// `websocket` (subscription paths)
+    pub source: String,
+    /// Transports that got a GraphQL answer, in the order they were tried
+    pub transports: Vec<Transport>,
+    /// GraphQL WebSocket subprotocol the server picked, for `ws(s)://` candidates
+    pub subprotocol: Option<String>,
+    /// Why it looks like GraphQL (or not quite)
+    pub signals: Vec<Signal>,
+}
+
 pub struct EndpointDiscovery {
     base_url: Url,
     paths: Vec<String>,
+    /// More base URLs to try the paths on, e.g. live subdomains
+    hosts: Vec<Url>,
+    /// Full URLs to try after the paths
+    urls: Vec<String>,
+    /// Other ports to try every host on
+    ports: Vec<u16>,
+    /// Also try GraphQL WebSocket upgrades
+    websockets: bool,
 }
 
 impl EndpointDiscovery {
@@ -34,27 +159,240 @@ impl EndpointDiscovery {
             None => DEFAULT_PATHS.iter().map(|s| s.to_string()).collect(),
         };
 
-        Ok(Self { base_url, paths })
+        Ok(Self {
+            base_url,
+            paths,
+            hosts: Vec::new(),
+            urls: Vec::new(),
+            ports: Vec::new(),
+            websockets: false,
+        })
+    }
+
+    /// Also try every path on these base URLs
+    pub fn with_hosts(mut self, hosts: Vec<Url>) -> Self {
+        self.hosts = hosts;
+        self
+    }
+
+    /// Also try these paths, e.g. from a platform's path pack
+    pub fn with_paths<S: AsRef<str>>(mut self, paths: &[S]) -> Self {
+        for path in paths {
+            let path = path.as_ref();
+            if !self.paths.iter().any(|p| p == path) {
+                self.paths.push(path.to_string());
+            }
+        }
+        self
+    }
+
+    /// Also try base segments combined with GraphQL suffixes (see [`combined_paths`])
+    pub fn with_combinations(self, max_paths: usize) -> Self {
+        let service = match self.base_url.host() {
+            Some(url::Host::Domain(domain)) => domain.split('.').next().map(String::from),
+            _ => None,
+        };
+        // The cap is on new paths, not on ones the list already has
+        let paths: Vec<String> = combined_paths(service.as_deref(), usize::MAX)
+            .into_iter()
+            .filter(|p| !self.paths.contains(p))
+            .take(max_paths)
+            .collect();
+        self.with_paths(&paths)
+    }
+
+    /// Also try every host on these ports
+    pub fn with_ports(mut self, ports: Vec<u16>) -> Self {
+        self.ports = ports;
+        self
+    }
+
+    /// Also try upgrading the usual subscription paths to graphql-ws on every host
+    pub fn with_websockets(mut self, websockets: bool) -> Self {
+        self.websockets = websockets;
+        self
+    }
+
+    /// Also try these full URLs, e.g. endpoints found in the site's JavaScript
+    pub fn with_urls(mut self, urls: Vec<String>) -> Self {
+        self.urls = urls;
+        self
     }
 
+    /// GraphQL endpoints found, most certain first
     pub async fn discover(&self, client: &HttpClient) -> Vec<String> {
-        let mut found = Vec::new();
+        let mut found: Vec<EndpointCandidate> = self.probe(client).await.into_iter().filter(|c| c.graphql).collect();
+        found.sort_by_key(|c| std::cmp::Reverse(c.confidence));
+        found.into_iter().map(|c| c.url).collect()
+    }
+
+    /// Every candidate path with what it answered, GraphQL or not
+    pub async fn probe(&self, client: &HttpClient) -> Vec<EndpointCandidate> {
+        let mut candidates: Vec<EndpointCandidate> = Vec::new();
 
-        for path in &self.paths {
-            let mut url = self.base_url.clone();
-            url.set_path(path);
-            let url_str = url.to_string();
+        let mut bases: Vec<Url> = std::iter::once(&self.base_url).chain(&self.hosts).cloned().collect();
+        for host in bases.clone() {
+            for &port in &self.ports {
+                if let Some(base) = on_port(&host, port) {
+                    // A closed or filtered port would cost a timeout per path
+                    if client.get_html(base.as_str(), Some("discovery")).await.is_ok() && !bases.contains(&base) {
+                        bases.push(base);
+                    }
+                }
+            }
+        }
 
-            match is_graphql_endpoint(client, &url_str).await {
-                Ok(true) => {
-                    found.push(url_str);
+        for base in &bases {
+            for path in &self.paths {
+                let mut url = base.clone();
+                match path.split_once('?') {
+                    Some((path, query)) => {
+                        url.set_path(path);
+                        url.set_query(Some(query));
+                    }
+                    None => url.set_path(path),
                 }
-                _ => {}
+                candidates.push(probe_endpoint(client, url.as_str()).await);
+            }
+        }
+        if self.websockets {
+            for base in &bases {
+                for path in WEBSOCKET_PATHS {
+                    let mut url = base.clone();
+                    url.set_path(path);
+                    candidates.push(probe_websocket(client, url.as_str()).await);
+                }
+            }
+        }
+        for url in &self.urls {
+            if candidates.iter().any(|c| &c.url == url) {
+                continue;
+            }
+            let mut candidate = probe_endpoint(client, url).await;
+            candidate.source = "bundle".to_string();
+            candidates.push(candidate);
+        }
+
+        candidates
+    }
+}
+
+/// `base` on another port. Dev servers speak plain HTTP unless the port says otherwise.
+fn on_port(base: &Url, port: u16) -> Option<Url> {
+    let scheme = if port % 1000 == 443 { "https" } else { "http" };
+    Url::parse(&format!("{}://{}:{}/", scheme, base.host_str()?, port)).ok()
+}
+
+/// Send the detection query to `url` over every transport, and fingerprint the engine
+/// when one answers like GraphQL
+pub async fn probe_endpoint(client: &HttpClient, url: &str) -> EndpointCandidate {
+    let mut candidate = EndpointCandidate {
+        url: url.to_string(),
+        status: None,
+        graphql: false,
+        root_type: None,
+        engine: None,
+        confidence: 0,
+        source: "path".to_string(),
+        transports: Vec::new(),
+        subprotocol: None,
+        signals: Vec::new(),
+    };
+
+    for transport in Transport::ALL {
+        // Neither POST nor GET got through, the host is down or refusing connections
+        if candidate.status.is_none() && !matches!(transport, Transport::Json | Transport::Get) {
+            break;
+        }
+        let response = match transport.send(client, url, DETECTION_QUERY, Some("discovery")).await {
+            Ok(r) => r,
+            Err(_) => continue,
+        };
+        if candidate.status.is_none() {
+            candidate.status = Some(response.status);
+        }
+        candidate.signals.extend(response_signals(&response));
+        if candidate.engine.is_none() {
+            candidate.engine = engine_hint(&response.headers).map(String::from);
+        }
+        if !looks_like_graphql(&response) {
+            continue;
+        }
+        if !candidate.graphql {
+            candidate.graphql = true;
+            candidate.status = Some(response.status);
+            candidate.root_type = response
+                .get_data()
+                .and_then(|d| d.get("__typename"))
+                .and_then(|t| t.as_str())
+                .map(String::from);
+        }
+        candidate.transports.push(transport);
+    }
+
+    // Nothing answered the query itself; an IDE page, a batch reply or the errors and
+    // headers seen so far can still add up
+    if !candidate.graphql && candidate.status.is_some() {
+        if let Ok(page) = client.get_html(url, Some("discovery")).await {
+            candidate.signals.extend(page_signals(&page));
+        }
+        if !candidate.signals.is_empty() {
+            candidate.signals.extend(batch_signal(client, url).await);
+        }
+        candidate.graphql = confidence(&candidate.signals) >= GRAPHQL_THRESHOLD;
+    }
+    let mut seen = std::collections::HashSet::new();
+    candidate.signals.retain(|s| seen.insert(s.name.clone()));
+
+    if let Some(transport) = candidate.transports.first() {
+        if let Ok(response) = transport.send(client, url, FINGERPRINT_QUERY, Some("discovery")).await {
+            let messages: Vec<String> = response
+                .get_errors()
+                .and_then(|e| e.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+                .map(String::from)
+                .collect();
+            if let Some(engine) = EngineProfile::detect(&messages) {
+                candidate.engine = Some(engine.name);
             }
         }
+    }
+    candidate.confidence = confidence(&candidate.signals);
 
-        found
+    candidate
+}
+
+/// Upgrade `url` with the GraphQL subprotocols and see whether `connection_init` is
+/// acknowledged. The candidate carries the `ws(s)://` URL.
+pub async fn probe_websocket(client: &HttpClient, url: &str) -> EndpointCandidate {
+    let mut candidate = EndpointCandidate {
+        url: ws_url(url),
+        status: None,
+        graphql: false,
+        root_type: None,
+        engine: None,
+        confidence: 0,
+        source: "websocket".to_string(),
+        transports: Vec::new(),
+        subprotocol: None,
+        signals: Vec::new(),
+    };
+
+    let mut socket = match GraphQLSocket::connect(client, url, Some("discovery")).await {
+        Ok(socket) => socket,
+        Err(_) => return candidate,
+    };
+    candidate.status = Some(101);
+    candidate.graphql = socket.init(json!({}), WEBSOCKET_ACK_WAIT).await.unwrap_or(false);
+    if candidate.graphql {
+        candidate.signals.push(Signal::new(format!("{} connection_ack", socket.protocol), 100));
     }
+    candidate.confidence = confidence(&candidate.signals);
+    candidate.subprotocol = Some(socket.protocol);
+
+    candidate
 }
 
 pub fn load_wordlist(path: &str) -> Result<Vec<String>> {
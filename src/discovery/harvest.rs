@@ -0,0 +1,248 @@
+use crate::http::HttpClient;
+use anyhow::Result;
+use regex::Regex;
+use std::collections::{HashSet, VecDeque};
+use url::Url;
+
+/// Scripts fetched per run, bundles and the chunks they reference together
+const MAX_SCRIPTS: usize = 50;
+/// Identifier-like strings kept from a single script, after anything from documents
+const MAX_TOKENS_PER_SCRIPT: usize = 2000;
+
+const KEYWORDS: &[&str] = &[
+    "query", "mutation", "subscription", "fragment", "on", "true", "false", "null",
+];
+
+/// Words pulled out of a site's HTML and JavaScript
+#[derive(Debug, Default)]
+pub struct Harvest {
+    /// Field, argument and type names from GraphQL documents, then operation names,
+    /// then identifier-like string literals
+    pub words: Vec<String>,
+    pub operations: Vec<String>,
+    pub documents: usize,
+    pub scripts: usize,
+    /// URLs in string literals that look like GraphQL endpoints, resolved against the origin
+    pub endpoints: Vec<String>,
+    /// Plain-text documents, unescaped
+    pub queries: Vec<String>,
+    /// sha256 hashes of persisted queries (Apollo APQ) and Relay persisted document ids
+    pub persisted_hashes: Vec<String>,
+}
+
+/// Fetch the target origin's front page and the scripts it loads, and collect every
+/// name the client code uses: fields in embedded queries (plain text or compiled
+/// `gql` ASTs), operation names and identifier-looking string literals. Endpoint URLs,
+/// query documents and persisted-query hashes come along for discovery.
+pub async fn harvest_words(client: &HttpClient, target: &str) -> Result<Harvest> {
+    let target = Url::parse(target)?;
+    let origin = target.join("/")?;
+
+    let document_regex =
+        Regex::new(r"\b(query|mutation|subscription|fragment)\b(\s+\w+)?(\s+on\s+\w+)?\s*(\([^)]*\))?\s*\{").unwrap();
+    let operation_regex = Regex::new(r"\b(?:query|mutation|subscription)\s+([A-Za-z_]\w*)\s*[({]").unwrap();
+    // Compiled documents: {kind:"Name",value:"user"} (keys quoted or not)
+    let ast_name_regex = Regex::new(r#"kind"?\s*:\s*"Name"\s*,\s*"?value"?\s*:\s*"([_A-Za-z]\w*)""#).unwrap();
+    let ast_operation_regex =
+        Regex::new(r#"kind"?\s*:\s*"OperationDefinition"[^}]{0,200}?"?value"?\s*:\s*"([_A-Za-z]\w*)""#).unwrap();
+    let string_regex = Regex::new(r#"["'`]([A-Za-z_][A-Za-z0-9_]{2,39})["'`]"#).unwrap();
+    let identifier_regex = Regex::new(r"\$?[_A-Za-z]\w*").unwrap();
+    let url_regex = Regex::new(r#"["'`]((?:https?:)?//[^"'`\s]+|/[^"'`\s]*)["'`]"#).unwrap();
+    // {sha256Hash:"..."} in APQ extensions, params:{id:"...",...,operationKind:"query"} in Relay
+    let apq_hash_regex = Regex::new(r#"sha256Hash"?\s*:\s*"([0-9a-fA-F]{64})""#).unwrap();
+    let relay_id_regex =
+        Regex::new(r#"\bid"?\s*:\s*"([0-9a-fA-F]{32,64})"[^}]{0,200}?operationKind"#).unwrap();
+
+    let mut harvest = Harvest::default();
+    let mut document_words = Vec::new();
+    let mut tokens = Vec::new();
+
+    let sources = fetch_client_code(client, &target, "harvest").await?;
+    harvest.scripts = sources.iter().filter(|s| !s.inline).count();
+    let sources: Vec<String> = sources.into_iter().map(|s| s.body).collect();
+
+    for source in &sources {
+        for m in document_regex.find_iter(source) {
+            // `function query(a) { ... }` matches too; selections have no statements
+            let body = match balanced_braces(&source[m.end() - 1..]) {
+                Some(body) if !body.contains(';') && !body.contains('=') => body,
+                _ => continue,
+            };
+            harvest.documents += 1;
+            let document = format!("{}{}", &source[m.start()..m.end() - 1], body);
+            harvest.queries.push(unescape(&document));
+            for word in identifier_regex.find_iter(&document) {
+                let word = word.as_str();
+                if !word.starts_with('$') && !KEYWORDS.contains(&word) {
+                    document_words.push(word.to_string());
+                }
+            }
+        }
+        for cap in operation_regex.captures_iter(source) {
+            harvest.operations.push(cap[1].to_string());
+        }
+
+        let ast_names: Vec<String> = ast_name_regex.captures_iter(source).map(|c| c[1].to_string()).collect();
+        if !ast_names.is_empty() {
+            harvest.documents += ast_operation_regex.find_iter(source).count();
+            for cap in ast_operation_regex.captures_iter(source) {
+                harvest.operations.push(cap[1].to_string());
+            }
+            document_words.extend(ast_names);
+        }
+
+        for cap in url_regex.captures_iter(source) {
+            if is_endpoint_like(&cap[1]) {
+                if let Ok(url) = origin.join(&cap[1]) {
+                    harvest.endpoints.push(url.to_string());
+                }
+            }
+        }
+        for cap in apq_hash_regex.captures_iter(source).chain(relay_id_regex.captures_iter(source)) {
+            harvest.persisted_hashes.push(cap[1].to_lowercase());
+        }
+
+        tokens.extend(
+            string_regex
+                .captures_iter(source)
+                .map(|c| c[1].to_string())
+                .take(MAX_TOKENS_PER_SCRIPT),
+        );
+    }
+
+    harvest.operations.sort();
+    harvest.operations.dedup();
+    harvest.endpoints.sort();
+    harvest.endpoints.dedup();
+    harvest.persisted_hashes.sort();
+    harvest.persisted_hashes.dedup();
+    let mut seen_queries = HashSet::new();
+    harvest.queries.retain(|q| seen_queries.insert(q.clone()));
+
+    let mut seen = HashSet::new();
+    let operation_words = harvest.operations.iter().flat_map(|op| [op.clone(), lower_first(op)]);
+    harvest.words = document_words
+        .into_iter()
+        .chain(operation_words)
+        .chain(tokens)
+        .filter(|w| seen.insert(w.clone()))
+        .collect();
+
+    Ok(harvest)
+}
+
+/// A page or script of the target's front end
+#[derive(Debug, Clone)]
+pub struct ClientSource {
+    /// Where it came from; the page's URL for inline scripts
+    pub url: String,
+    pub body: String,
+    /// An inline `<script>` of the page rather than a fetched file
+    pub inline: bool,
+}
+
+/// Fetch the origin's front page and `target`, then the scripts they load and the
+/// same-origin chunks those reference, up to [`MAX_SCRIPTS`]
+pub async fn fetch_client_code(client: &HttpClient, target: &Url, test_name: &str) -> Result<Vec<ClientSource>> {
+    let origin = target.join("/")?;
+    let script_src_regex = Regex::new(r#"(?i)<script[^>]*\ssrc\s*=\s*["']([^"']+)["']"#).unwrap();
+    let inline_script_regex = Regex::new(r"(?is)<script[^>]*>(.*?)</script>").unwrap();
+    let chunk_regex = Regex::new(r#"["']((?:\.{0,2}/)?[\w./-]+\.js)["']"#).unwrap();
+
+    let mut sources = Vec::new();
+    let mut queue: VecDeque<Url> = VecDeque::new();
+    let mut queued = HashSet::new();
+    for page in [origin.clone(), target.clone()] {
+        let response = match client.get_html(page.as_str(), Some(test_name)).await {
+            Ok(r) if r.status < 400 => r,
+            _ => continue,
+        };
+        for cap in script_src_regex.captures_iter(&response.body) {
+            if let Ok(url) = page.join(&cap[1]) {
+                if queued.insert(url.clone()) {
+                    queue.push_back(url);
+                }
+            }
+        }
+        for cap in inline_script_regex.captures_iter(&response.body) {
+            sources.push(ClientSource {
+                url: page.to_string(),
+                body: cap[1].to_string(),
+                inline: true,
+            });
+        }
+    }
+
+    let mut scripts = 0;
+    while let Some(url) = queue.pop_front() {
+        if scripts >= MAX_SCRIPTS {
+            break;
+        }
+        let response = match client.get_html(url.as_str(), Some(test_name)).await {
+            Ok(r) if r.status < 400 => r,
+            _ => continue,
+        };
+        scripts += 1;
+
+        // Lazy-loaded chunks on the same origin hold most of the queries in big apps
+        for cap in chunk_regex.captures_iter(&response.body) {
+            if let Ok(chunk) = url.join(&cap[1]) {
+                if chunk.origin() == origin.origin() && queued.insert(chunk.clone()) {
+                    queue.push_back(chunk);
+                }
+            }
+        }
+        sources.push(ClientSource {
+            url: url.to_string(),
+            body: response.body,
+            inline: false,
+        });
+    }
+    Ok(sources)
+}
+
+/// The `{ ... }` block `source` starts with, or None if it never closes
+fn balanced_braces(source: &str) -> Option<&str> {
+    let mut depth = 0;
+    for (i, c) in source.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(&source[..=i]);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// A URL or absolute path naming GraphQL, and not a static asset or a template
+pub(crate) fn is_endpoint_like(candidate: &str) -> bool {
+    let lower = candidate.to_lowercase();
+    let path = lower.split(['?', '#']).next().unwrap_or_default();
+    (path.contains("graphql") || path.ends_with("/gql") || path.contains("/gql/"))
+        && !candidate.contains('{')
+        && ![".js", ".mjs", ".map", ".css", ".png", ".svg", ".html"]
+            .iter()
+            .any(|ext| path.ends_with(ext))
+}
+
+/// Documents in JS string literals keep their escapes
+fn unescape(document: &str) -> String {
+    document
+        .replace("\\n", "\n")
+        .replace("\\t", "  ")
+        .replace("\\\"", "\"")
+        .replace("\\'", "'")
+}
+
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
@@ -1,3 +1,11 @@
 mod endpoint;
+mod harvest;
+mod passive;
+mod secrets;
+mod subdomain;
 
 pub use endpoint::*;
+pub use harvest::*;
+pub use passive::*;
+pub use secrets::*;
+pub use subdomain::*;
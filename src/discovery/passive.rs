@@ -0,0 +1,136 @@
+use super::harvest::is_endpoint_like;
+use crate::http::HttpClient;
+use anyhow::Result;
+use regex::Regex;
+use url::Url;
+
+/// Sitemaps read per run, index files included
+const MAX_SITEMAPS: usize = 5;
+/// URLs asked of the Wayback Machine
+const WAYBACK_LIMIT: usize = 1000;
+
+/// GraphQL-looking paths the target already admits to, by where they were found
+#[derive(Debug, Default)]
+pub struct PassiveSeeds {
+    pub robots: Vec<String>,
+    pub sitemap: Vec<String>,
+    pub wayback: Vec<String>,
+}
+
+impl PassiveSeeds {
+    /// Every path once, robots.txt first
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths: Vec<String> = Vec::new();
+        for path in self.robots.iter().chain(&self.sitemap).chain(&self.wayback) {
+            if !paths.contains(path) {
+                paths.push(path.clone());
+            }
+        }
+        paths
+    }
+}
+
+/// Read robots.txt and the sitemaps it (or the default location) points at, and, given
+/// `archive`, the Wayback Machine's captures of the host. `archive` should carry none of
+/// the target's auth headers.
+pub async fn passive_seeds(client: &HttpClient, target: &str, archive: Option<&HttpClient>) -> Result<PassiveSeeds> {
+    let origin = Url::parse(target)?.join("/")?;
+    let mut seeds = PassiveSeeds::default();
+
+    let mut sitemaps = vec![origin.join("/sitemap.xml")?];
+    if let Ok(robots) = client.get_html(origin.join("/robots.txt")?.as_str(), Some("passive")).await {
+        if robots.status < 400 {
+            for line in robots.body.lines() {
+                let (key, value) = match line.split_once(':') {
+                    Some((key, value)) => (key.trim().to_lowercase(), value.trim()),
+                    None => continue,
+                };
+                match key.as_str() {
+                    "allow" | "disallow" => {
+                        if let Some(path) = seed_path(&origin, value) {
+                            seeds.robots.push(path);
+                        }
+                    }
+                    "sitemap" => {
+                        if let Ok(url) = origin.join(value) {
+                            if !sitemaps.contains(&url) {
+                                sitemaps.push(url);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    let loc_regex = Regex::new(r"(?i)<loc>\s*([^<\s]+)\s*</loc>").unwrap();
+    let mut read = 0;
+    while read < sitemaps.len() && read < MAX_SITEMAPS {
+        let url = sitemaps[read].clone();
+        read += 1;
+        let response = match client.get_html(url.as_str(), Some("passive")).await {
+            Ok(r) if r.status < 400 => r,
+            _ => continue,
+        };
+        for cap in loc_regex.captures_iter(&response.body) {
+            let loc = cap[1].replace("&amp;", "&");
+            // Sitemap indexes list more sitemaps
+            if loc.ends_with(".xml") {
+                if let Ok(nested) = Url::parse(&loc) {
+                    if !sitemaps.contains(&nested) {
+                        sitemaps.push(nested);
+                    }
+                }
+            } else if let Some(path) = seed_path(&origin, &loc) {
+                seeds.sitemap.push(path);
+            }
+        }
+    }
+
+    if let Some(archive) = archive {
+        if let Some(host) = origin.host_str() {
+            let cdx = format!(
+                "https://web.archive.org/cdx/search/cdx?url={}/*&fl=original&collapse=urlkey&filter=original:.*(graphql|gql).*&limit={}",
+                host, WAYBACK_LIMIT
+            );
+            if let Ok(response) = archive.get_html(&cdx, Some("passive")).await {
+                if response.status < 400 {
+                    for line in response.body.lines() {
+                        if let Some(path) = seed_path(&origin, line.trim()) {
+                            seeds.wayback.push(path);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for list in [&mut seeds.robots, &mut seeds.sitemap, &mut seeds.wayback] {
+        list.sort();
+        list.dedup();
+    }
+    Ok(seeds)
+}
+
+/// The path of a GraphQL-looking URL or path on `origin`'s host, skipping robots.txt
+/// wildcards. The query string is kept only when it's what names GraphQL
+/// (`/index.php?graphql`), not when it's a captured request.
+fn seed_path(origin: &Url, candidate: &str) -> Option<String> {
+    if candidate.is_empty() || candidate.contains(['*', '$']) {
+        return None;
+    }
+    let url = origin.join(candidate).ok()?;
+    if url.host_str() != origin.host_str() {
+        return None;
+    }
+    if is_endpoint_like(url.path()) {
+        return Some(url.path().to_string());
+    }
+    match url.query() {
+        Some(query) if query.to_lowercase().contains("graphql") && query.len() <= 40 => {
+            Some(format!("{}?{}", url.path(), query))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,101 @@
+use super::{is_endpoint_like, ClientSource};
+use regex::Regex;
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use url::Url;
+
+/// Host suffixes that only resolve inside a private network
+const INTERNAL_SUFFIXES: &[&str] = &[".internal", ".local", ".localdomain", ".lan", ".corp", ".intranet", ".svc", ".cluster.local"];
+
+/// Something in client code that shouldn't have shipped to browsers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LeakedSecret {
+    /// What it is, e.g. `Hasura admin secret`
+    pub kind: &'static str,
+    /// Credentials with their middle masked; internal URLs in full
+    pub value: String,
+    /// Page or script it was found in
+    pub source: String,
+}
+
+/// Credential patterns, the secret in group 1 when there's context around it
+fn secret_patterns() -> &'static [(&'static str, Regex)] {
+    static PATTERNS: OnceLock<Vec<(&'static str, Regex)>> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        [
+            ("Hasura admin secret", r#"(?i)x-hasura-(?:admin-secret|access-key)["']?\s*[:=,]\s*["'`]([^"'`\s]{6,})["'`]"#),
+            ("Hasura admin secret", r#"HASURA_GRAPHQL_ADMIN_SECRET["']?\s*[:=]\s*["'`]([^"'`\s]{6,})["'`]"#),
+            ("Apollo Studio key", r"\b((?:service|user):[\w-]+:[\w-]{20,})"),
+            ("Apollo Studio key", r#"APOLLO_KEY["']?\s*[:=]\s*["'`]([^"'`\s]{16,})["'`]"#),
+            ("AWS AppSync API key", r"\b(da2-[a-z0-9]{26})\b"),
+            ("GraphQL admin secret", r#"(?i)\b(?:graphql[_-]?)?admin[_-]?(?:secret|token|key)["']?\s*[:=]\s*["'`]([^"'`\s]{8,})["'`]"#),
+            ("JWT", r"\b(eyJ[A-Za-z0-9_-]{10,}\.eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,})"),
+            ("Bearer token", r#"["'`]Bearer\s+([A-Za-z0-9._~+/-]{20,}=*)["'`]"#),
+            ("AWS access key", r"\b((?:AKIA|ASIA)[0-9A-Z]{16})\b"),
+            ("GitHub token", r"\b(gh[pousr]_[A-Za-z0-9]{36})\b"),
+            ("Stripe secret key", r"\b((?:sk|rk)_live_[0-9A-Za-z]{24,})\b"),
+            ("Slack token", r"\b(xox[abprs]-[0-9A-Za-z-]{10,})\b"),
+        ]
+        .into_iter()
+        .map(|(kind, pattern)| (kind, Regex::new(pattern).unwrap()))
+        .collect()
+    })
+}
+
+/// Credentials and internal GraphQL URLs in the given pages and scripts, each once
+pub fn find_secrets(sources: &[ClientSource]) -> Vec<LeakedSecret> {
+    let url_regex = Regex::new(r#"["'`](https?://[^"'`\s]+)["'`]"#).unwrap();
+
+    let mut seen = HashSet::new();
+    let mut found = Vec::new();
+    for source in sources {
+        for (kind, regex) in secret_patterns() {
+            for cap in regex.captures_iter(&source.body) {
+                if seen.insert(cap[1].to_string()) {
+                    found.push(LeakedSecret {
+                        kind,
+                        value: mask(&cap[1]),
+                        source: source.url.clone(),
+                    });
+                }
+            }
+        }
+        for cap in url_regex.captures_iter(&source.body) {
+            let candidate = &cap[1];
+            if is_endpoint_like(candidate) && is_internal(candidate) && seen.insert(candidate.to_string()) {
+                found.push(LeakedSecret {
+                    kind: "internal GraphQL URL",
+                    value: candidate.to_string(),
+                    source: source.url.clone(),
+                });
+            }
+        }
+    }
+    found
+}
+
+/// A URL on localhost, a private address or an internal-only domain
+fn is_internal(candidate: &str) -> bool {
+    let Some(host) = Url::parse(candidate).ok().and_then(|u| u.host_str().map(|h| h.to_lowercase())) else {
+        return false;
+    };
+    match host.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_private() || ip.is_loopback() || ip.is_link_local(),
+        Ok(IpAddr::V6(ip)) => ip.is_loopback() || (ip.segments()[0] & 0xfe00) == 0xfc00,
+        Err(_) => host == "localhost" || INTERNAL_SUFFIXES.iter().any(|suffix| host.ends_with(suffix)),
+    }
+}
+
+/// Enough of a credential to find it again, not enough to use it
+fn mask(secret: &str) -> String {
+    let chars: Vec<char> = secret.chars().collect();
+    if chars.len() <= 12 {
+        return format!("{}…", chars[..3].iter().collect::<String>());
+    }
+    format!(
+        "{}…{}",
+        chars[..6].iter().collect::<String>(),
+        chars[chars.len() - 4..].iter().collect::<String>()
+    )
+}
@@ -0,0 +1,74 @@
+use anyhow::{anyhow, Result};
+use std::collections::HashSet;
+use std::net::IpAddr;
+use tokio::net::lookup_host;
+use tokio::task::JoinSet;
+use url::Url;
+
+/// DNS lookups in flight at once
+const CONCURRENT_LOOKUPS: usize = 32;
+
+// Resolves only under wildcard DNS
+const WILDCARD_PROBE: &str = "gqlmap-wildcard-check";
+
+/// Prefix every word to the target's host and keep the names that resolve, as base URLs
+/// with the target's scheme and port. Under wildcard DNS, names pointing at the same
+/// addresses as a made-up subdomain are dropped.
+pub async fn resolve_subdomains(target: &str, words: &[String]) -> Result<Vec<Url>> {
+    let base = Url::parse(target)?;
+    let domain = base
+        .host_str()
+        .ok_or_else(|| anyhow!("{} has no host", target))?
+        .to_string();
+    let port = base.port_or_known_default().unwrap_or(443);
+
+    let wildcard = resolve(&format!("{}.{}", WILDCARD_PROBE, domain), port).await;
+
+    let mut hosts: Vec<String> = words
+        .iter()
+        .map(|w| w.trim().trim_end_matches('.').to_lowercase())
+        .filter(|w| !w.is_empty() && !w.starts_with('#'))
+        .map(|w| format!("{}.{}", w, domain))
+        .collect();
+    let mut seen = HashSet::new();
+    hosts.retain(|h| seen.insert(h.clone()));
+
+    let mut live = Vec::new();
+    for chunk in hosts.chunks(CONCURRENT_LOOKUPS) {
+        let mut lookups = JoinSet::new();
+        for host in chunk {
+            let host = host.clone();
+            lookups.spawn(async move {
+                let addrs = resolve(&host, port).await;
+                (host, addrs)
+            });
+        }
+        while let Some(joined) = lookups.join_next().await {
+            // A lookup task that failed (panicked, cancelled) only loses its own host
+            let Ok((host, addrs)) = joined else { continue };
+            if addrs.is_empty() || (!wildcard.is_empty() && addrs.is_subset(&wildcard)) {
+                continue;
+            }
+            live.push(host);
+        }
+    }
+    live.sort();
+
+    Ok(live
+        .into_iter()
+        .filter_map(|host| {
+            let mut url = base.clone();
+            url.set_host(Some(&host)).ok()?;
+            url.set_path("/");
+            url.set_query(None);
+            Some(url)
+        })
+        .collect())
+}
+
+async fn resolve(host: &str, port: u16) -> HashSet<IpAddr> {
+    match lookup_host((host, port)).await {
+        Ok(addrs) => addrs.map(|a| a.ip()).collect(),
+        Err(_) => HashSet::new(),
+    }
+}
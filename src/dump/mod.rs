@@ -0,0 +1,377 @@
+mod template;
+
+pub use template::*;
+
+use crate::exec::VariableOverrides;
+use crate::http::HttpClient;
+use crate::schema::{type_ref_to_string, Field, FullType, Schema, TypeRef};
+use anyhow::{bail, Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Keeps requests at least an interval apart
+#[derive(Debug, Clone, Default)]
+struct Pace {
+    interval: Option<Duration>,
+    last: Option<Instant>,
+}
+
+impl Pace {
+    fn per_minute(per_minute: u32) -> Self {
+        Self {
+            interval: Some(Duration::from_secs(60) / per_minute.max(1)),
+            last: None,
+        }
+    }
+
+    async fn wait(&mut self) {
+        let interval = match self.interval {
+            Some(interval) => interval,
+            None => return,
+        };
+        if let Some(last) = self.last {
+            let wait = interval.saturating_sub(last.elapsed());
+            if !wait.is_zero() {
+                tokio::time::sleep(wait).await;
+            }
+        }
+        self.last = Some(Instant::now());
+    }
+}
+
+/// A root query field that returns a Relay connection
+#[derive(Debug, Clone, Serialize)]
+pub struct Connection {
+    pub field: String,
+    /// Type of the objects the connection pages through
+    pub node_type: String,
+    /// Whether items come as `edges { node }` rather than `nodes`
+    pub edges: bool,
+}
+
+impl Connection {
+    /// `field` as a connection: `first`/`after` arguments, a `pageInfo` with
+    /// `hasNextPage` and `endCursor`, and `edges { node }` or `nodes`
+    pub fn of(schema: &Schema, field: &Field) -> Option<Self> {
+        let takes = |name: &str| field.args.iter().any(|a| a.name == name);
+        if !takes("first") || !takes("after") {
+            return None;
+        }
+        let connection = object_type(schema, &field.field_type)?;
+
+        let page_info = object_type(schema, &child(connection, "pageInfo")?.field_type)?;
+        if child(page_info, "hasNextPage").is_none() || child(page_info, "endCursor").is_none() {
+            return None;
+        }
+
+        let (node, edges) = match child(connection, "edges") {
+            Some(edges) => (child(object_type(schema, &edges.field_type)?, "node")?, true),
+            None => (child(connection, "nodes")?, false),
+        };
+        Some(Self {
+            field: field.name.clone(),
+            node_type: node.field_type.get_base_type_name()?.to_string(),
+            edges,
+        })
+    }
+}
+
+/// Every query field that can be paged through
+pub fn find_connections(schema: &Schema) -> Vec<Connection> {
+    schema
+        .get_query_type()
+        .and_then(|t| t.fields.as_ref())
+        .into_iter()
+        .flatten()
+        .filter_map(|f| Connection::of(schema, f))
+        .collect()
+}
+
+/// One page as it came back
+#[derive(Debug, Clone, Serialize)]
+pub struct DumpPage {
+    pub page: usize,
+    pub status: u16,
+    /// Objects on the page, each written as one line
+    pub records: usize,
+    pub end_cursor: Option<String>,
+    pub has_next_page: bool,
+    pub error: Option<String>,
+}
+
+/// Why a walk ended and what it pulled
+#[derive(Debug, Clone, Serialize)]
+pub struct DumpSummary {
+    pub pages: usize,
+    pub records: usize,
+    /// Whether the server said there was nothing after the last page
+    pub complete: bool,
+}
+
+/// Follows a connection's cursors page by page and writes every object as a JSON line
+pub struct PageWalker {
+    client: HttpClient,
+    url: String,
+    schema: Schema,
+    connection: Connection,
+    page_size: usize,
+    max_pages: usize,
+    selection: Option<String>,
+    variables: VariableOverrides,
+    pace: Pace,
+    requests: usize,
+}
+
+impl PageWalker {
+    /// Walk the query field `field`, which has to be a connection
+    pub fn new(client: HttpClient, url: &str, schema: Schema, field: &str) -> Result<Self> {
+        let root = schema
+            .get_query_type()
+            .and_then(|t| t.fields.as_ref())
+            .and_then(|fields| fields.iter().find(|f| f.name == field))
+            .with_context(|| format!("The query type has no field {}", field))?;
+        let connection = match Connection::of(&schema, root) {
+            Some(connection) => connection,
+            None => {
+                let found: Vec<String> = find_connections(&schema).into_iter().map(|c| c.field).collect();
+                if found.is_empty() {
+                    bail!("{} isn't a Relay connection, and no query field is", field);
+                }
+                bail!("{} isn't a Relay connection; these are: {}", field, found.join(", "));
+            }
+        };
+
+        Ok(Self {
+            client,
+            url: url.to_string(),
+            schema,
+            connection,
+            page_size: 100,
+            max_pages: 50,
+            selection: None,
+            variables: VariableOverrides::default(),
+            pace: Pace::default(),
+            requests: 0,
+        })
+    }
+
+    /// Objects asked for per page (`first`)
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Stop after this many pages even if there are more
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// What to select on each object instead of its scalar fields, e.g. `id email`
+    pub fn with_selection(mut self, selection: Option<String>) -> Self {
+        self.selection = selection;
+        self
+    }
+
+    /// Values for the field's other arguments (filters, an owner ID, ...)
+    pub fn with_variables(mut self, variables: VariableOverrides) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Send at most this many requests per minute
+    pub fn with_rate_limit(mut self, per_minute: u32) -> Self {
+        self.pace = Pace::per_minute(per_minute);
+        self
+    }
+
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    /// The document sent for every page, `$first` and `$after` as variables
+    pub fn query(&self) -> Result<String> {
+        let field = self.field();
+        let mut definitions = Vec::new();
+        let mut arguments = Vec::new();
+        let provided = self.other_variables();
+        for arg in &field.args {
+            let paging = arg.name == "first" || arg.name == "after";
+            if paging || provided.contains_key(&arg.name) {
+                definitions.push(format!("${}: {}", arg.name, type_ref_to_string(&arg.input_type)));
+                arguments.push(format!("{}: ${}", arg.name, arg.name));
+            } else if arg.input_type.is_non_null() && arg.default_value.is_none() {
+                bail!("{} needs a value for ${}, pass it with --variables", field.name, arg.name);
+            }
+        }
+
+        let selection = match &self.selection {
+            Some(selection) => selection.clone(),
+            None => self.scalar_selection(),
+        };
+        let items = if self.connection.edges {
+            format!("edges {{ cursor node {{ {} }} }}", selection)
+        } else {
+            format!("nodes {{ {} }}", selection)
+        };
+        Ok(format!(
+            "query({}) {{ {}({}) {{ {} pageInfo {{ hasNextPage endCursor }} }} }}",
+            definitions.join(", "),
+            field.name,
+            arguments.join(", "),
+            items
+        ))
+    }
+
+    pub async fn run(&mut self, sink: &mut dyn Write, callback: Option<&dyn Fn(&DumpPage)>) -> Result<DumpSummary> {
+        let query = self.query()?;
+        let mut variables = Value::Object(self.other_variables());
+        let mut summary = DumpSummary {
+            pages: 0,
+            records: 0,
+            complete: false,
+        };
+        let mut cursor: Option<String> = None;
+
+        while summary.pages < self.max_pages {
+            variables["first"] = Value::from(self.page_size);
+            variables["after"] = cursor.clone().map(Value::String).unwrap_or(Value::Null);
+
+            self.pace.wait().await;
+            self.requests += 1;
+            let response = self
+                .client
+                .post_graphql(&self.url, &query, Some(variables.clone()), Some("dump"))
+                .await?;
+            summary.pages += 1;
+
+            let connection = response
+                .get_data()
+                .and_then(|d| d.get(&self.connection.field))
+                .filter(|c| c.is_object());
+            let mut page = DumpPage {
+                page: summary.pages,
+                status: response.status,
+                records: 0,
+                end_cursor: None,
+                has_next_page: false,
+                error: response.get_first_error_message(),
+            };
+            if let Some(connection) = connection {
+                for record in self.records(connection) {
+                    writeln!(sink, "{}", record)?;
+                    page.records += 1;
+                }
+                let page_info = connection.get("pageInfo");
+                page.has_next_page = page_info
+                    .and_then(|p| p.get("hasNextPage"))
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                page.end_cursor = page_info
+                    .and_then(|p| p.get("endCursor"))
+                    .and_then(|v| v.as_str())
+                    .map(String::from);
+            } else if page.error.is_none() {
+                page.error = Some(format!("HTTP {} without {} in the data", response.status, self.connection.field));
+            }
+            summary.records += page.records;
+            sink.flush()?;
+            if let Some(cb) = callback {
+                cb(&page);
+            }
+
+            if !page.has_next_page {
+                summary.complete = connection.is_some();
+                break;
+            }
+            // A cursor that doesn't move would walk the same page forever
+            if page.end_cursor.is_none() || page.end_cursor == cursor {
+                break;
+            }
+            cursor = page.end_cursor;
+        }
+
+        Ok(summary)
+    }
+
+    fn field(&self) -> &Field {
+        self.schema
+            .get_query_type()
+            .and_then(|t| t.fields.as_ref())
+            .and_then(|fields| fields.iter().find(|f| f.name == self.connection.field))
+            .expect("connection field checked in new")
+    }
+
+    /// `--variables` values for arguments other than the page ones
+    fn other_variables(&self) -> Map<String, Value> {
+        let field = self.field();
+        let declared: Vec<&str> = field
+            .args
+            .iter()
+            .map(|a| a.name.as_str())
+            .filter(|name| *name != "first" && *name != "after")
+            .collect();
+        let mut variables = Value::Object(Map::new());
+        self.variables.apply(&field.name, &declared, &mut variables);
+        match variables {
+            Value::Object(map) => map,
+            _ => Map::new(),
+        }
+    }
+
+    /// Scalar and enum fields of the node type that need no arguments
+    fn scalar_selection(&self) -> String {
+        let fields: Vec<&str> = self
+            .schema
+            .get_type(&self.connection.node_type)
+            .and_then(|t| t.fields.as_ref())
+            .into_iter()
+            .flatten()
+            .filter(|f| !f.name.starts_with("__") && !f.args.iter().any(|a| a.input_type.is_non_null()))
+            .filter(|f| {
+                f.field_type
+                    .get_base_type_name()
+                    .and_then(|name| self.schema.get_type(name))
+                    .is_some_and(|t| t.kind == "SCALAR" || t.kind == "ENUM")
+            })
+            .map(|f| f.name.as_str())
+            .collect();
+        if fields.is_empty() {
+            "__typename".to_string()
+        } else {
+            fields.join(" ")
+        }
+    }
+
+    fn records<'a>(&self, connection: &'a Value) -> Vec<&'a Value> {
+        let items = if self.connection.edges {
+            connection.get("edges")
+        } else {
+            connection.get("nodes")
+        };
+        items
+            .and_then(|i| i.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|item| if self.connection.edges { item.get("node") } else { Some(item) })
+            .filter(|record| !record.is_null())
+            .collect()
+    }
+}
+
+fn child<'a>(object: &'a FullType, name: &str) -> Option<&'a Field> {
+    object.fields.as_ref()?.iter().find(|f| f.name == name)
+}
+
+fn object_type<'a>(schema: &'a Schema, type_ref: &TypeRef) -> Option<&'a FullType> {
+    schema
+        .get_type(type_ref.get_base_type_name()?)
+        .filter(|t| t.kind == "OBJECT" || t.kind == "INTERFACE")
+}
@@ -0,0 +1,265 @@
+use super::Pace;
+use crate::exec::VariableOverrides;
+use crate::http::HttpClient;
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::io::Write;
+use std::path::Path;
+
+/// Values one variable takes in turn: `id=1..10000`, `email=@emails.txt` or
+/// `slug=admin,root,test`
+#[derive(Debug, Clone)]
+pub struct Iteration {
+    pub variable: String,
+    source: IterationSource,
+}
+
+#[derive(Debug, Clone)]
+enum IterationSource {
+    /// Inclusive
+    Range(i64, i64),
+    Words(Vec<String>),
+}
+
+impl Iteration {
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (variable, values) = spec
+            .split_once('=')
+            .with_context(|| format!("--iterate {} should be name=values", spec))?;
+        let variable = variable.trim().trim_start_matches('$').to_string();
+        if variable.is_empty() {
+            bail!("--iterate {} has no variable name", spec);
+        }
+
+        let range = values
+            .split_once("..")
+            .and_then(|(start, end)| Some((start.trim().parse::<i64>().ok()?, end.trim().parse::<i64>().ok()?)));
+        let source = if let Some((start, end)) = range {
+            if end < start {
+                bail!("--iterate {} counts backwards", spec);
+            }
+            IterationSource::Range(start, end)
+        } else if let Some(path) = values.strip_prefix('@') {
+            let path = Path::new(path);
+            let content =
+                std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+            let words: Vec<String> = content
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(String::from)
+                .collect();
+            IterationSource::Words(words)
+        } else {
+            IterationSource::Words(values.split(',').map(|v| v.trim().to_string()).collect())
+        };
+        Ok(Self { variable, source })
+    }
+
+    pub fn len(&self) -> usize {
+        match &self.source {
+            IterationSource::Range(start, end) => (end - start) as usize + 1,
+            IterationSource::Words(words) => words.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The `index`th value, typed for a variable declared as `base_type`: numbers go to
+    /// Int and Float as numbers and everywhere else (ID, String) as strings
+    fn value(&self, index: usize, base_type: &str) -> Value {
+        let text = match &self.source {
+            IterationSource::Range(start, _) => (start + index as i64).to_string(),
+            IterationSource::Words(words) => words[index].clone(),
+        };
+        let number = match base_type {
+            "Int" => text.parse::<i64>().ok().map(Value::from),
+            "Float" => text.parse::<f64>().ok().map(Value::from),
+            _ => None,
+        };
+        number.unwrap_or(Value::String(text))
+    }
+}
+
+/// One response as written to the JSONL output
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateRecord {
+    /// Position in the iteration, from 0
+    pub index: usize,
+    pub variables: Value,
+    pub status: Option<u16>,
+    pub data: Option<Value>,
+    pub errors: Option<Value>,
+    /// Why there was no response
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl TemplateRecord {
+    /// Whether anything came back under `data`, not just nulls
+    pub fn has_data(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|d| d.as_object())
+            .is_some_and(|d| d.values().any(|v| !v.is_null()))
+    }
+}
+
+/// What a template run sent and got
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateSummary {
+    pub sent: usize,
+    /// Responses with data in them
+    pub with_data: usize,
+}
+
+/// Sends one query document once per combination of `--iterate` values and writes
+/// every response as a JSON line
+pub struct TemplateDumper {
+    client: HttpClient,
+    url: String,
+    query: String,
+    iterations: Vec<Iteration>,
+    variables: VariableOverrides,
+    /// Variables the document declares, with their base type
+    declared: Vec<(String, String)>,
+    operation: Option<String>,
+    only_data: bool,
+    pace: Pace,
+    requests: usize,
+}
+
+impl TemplateDumper {
+    pub fn new(client: HttpClient, url: &str, query: &str, iterations: Vec<Iteration>) -> Result<Self> {
+        let definition = Regex::new(r"\$(\w+)\s*:\s*\[*\s*(\w+)").unwrap();
+        let declared: Vec<(String, String)> = definition
+            .captures_iter(query)
+            .map(|c| (c[1].to_string(), c[2].to_string()))
+            .collect();
+        for iteration in &iterations {
+            if !declared.iter().any(|(name, _)| *name == iteration.variable) {
+                bail!("The query doesn't declare ${}", iteration.variable);
+            }
+            if iteration.is_empty() {
+                bail!("Nothing to put in ${}", iteration.variable);
+            }
+        }
+        let operation = Regex::new(r"\b(?:query|mutation)\s+([A-Za-z_]\w*)")
+            .unwrap()
+            .captures(query)
+            .map(|c| c[1].to_string());
+
+        Ok(Self {
+            client,
+            url: url.to_string(),
+            query: query.to_string(),
+            iterations,
+            variables: VariableOverrides::default(),
+            declared,
+            operation,
+            only_data: false,
+            pace: Pace::default(),
+            requests: 0,
+        })
+    }
+
+    /// Fixed values for the variables that aren't iterated
+    pub fn with_variables(mut self, variables: VariableOverrides) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Only write responses that have data in them
+    pub fn with_only_data(mut self, only_data: bool) -> Self {
+        self.only_data = only_data;
+        self
+    }
+
+    /// Send at most this many requests per minute
+    pub fn with_rate_limit(mut self, per_minute: u32) -> Self {
+        self.pace = Pace::per_minute(per_minute);
+        self
+    }
+
+    /// Requests the run will send: every combination of the iterated values
+    pub fn total(&self) -> usize {
+        self.iterations.iter().map(Iteration::len).product()
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    pub async fn run(
+        &mut self,
+        sink: &mut dyn Write,
+        callback: Option<&dyn Fn(&TemplateRecord)>,
+    ) -> Result<TemplateSummary> {
+        let declared: Vec<&str> = self.declared.iter().map(|(name, _)| name.as_str()).collect();
+        let mut base = Value::Object(Map::new());
+        self.variables
+            .apply(self.operation.as_deref().unwrap_or_default(), &declared, &mut base);
+
+        let mut summary = TemplateSummary { sent: 0, with_data: 0 };
+        for index in 0..self.total() {
+            let mut variables = base.clone();
+            // Odometer over the iterations, the last one turning fastest
+            let mut rest = index;
+            for iteration in self.iterations.iter().rev() {
+                let position = rest % iteration.len();
+                rest /= iteration.len();
+                let base_type = self
+                    .declared
+                    .iter()
+                    .find(|(name, _)| *name == iteration.variable)
+                    .map(|(_, t)| t.as_str())
+                    .unwrap_or_default();
+                variables[&iteration.variable] = iteration.value(position, base_type);
+            }
+
+            self.pace.wait().await;
+            self.requests += 1;
+            let response = self
+                .client
+                .post_graphql(&self.url, &self.query, Some(variables.clone()), Some("dump"))
+                .await;
+            summary.sent += 1;
+
+            let record = match response {
+                Ok(response) => TemplateRecord {
+                    index,
+                    variables,
+                    status: Some(response.status),
+                    data: response.get_data().cloned(),
+                    errors: response.get_errors().cloned(),
+                    error: None,
+                },
+                Err(e) => TemplateRecord {
+                    index,
+                    variables,
+                    status: None,
+                    data: None,
+                    errors: None,
+                    error: Some(e.to_string()),
+                },
+            };
+            if record.has_data() {
+                summary.with_data += 1;
+            }
+            if record.has_data() || !self.only_data {
+                writeln!(sink, "{}", serde_json::to_string(&record)?)?;
+                sink.flush()?;
+            }
+            if let Some(cb) = callback {
+                cb(&record);
+            }
+        }
+
+        Ok(summary)
+    }
+}
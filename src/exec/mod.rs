@@ -0,0 +1,176 @@
+mod variables;
+
+pub use variables::*;
+
+use crate::analysis::{classify_error, ErrorClass, FieldOutcome};
+use crate::export::{CatalogExporter, OperationFilter};
+use crate::guard::{MutationClass, MutationGuard};
+use crate::http::HttpClient;
+use crate::schema::Schema;
+use anyhow::Result;
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+/// What one operation got when run with its example variables
+#[derive(Debug, Clone, Serialize)]
+pub struct ExecResult {
+    pub operation: String,
+    /// query or mutation
+    pub kind: String,
+    pub status: Option<u16>,
+    /// None when the response says nothing either way (no response, a server error)
+    pub outcome: Option<FieldOutcome>,
+    /// Classes of the errors that came back, each once
+    pub error_classes: Vec<ErrorClass>,
+    /// First error message, or why there was no response
+    pub detail: Option<String>,
+    /// Response body length in bytes
+    pub size: usize,
+    pub elapsed_ms: u128,
+    pub curl_command: Option<String>,
+}
+
+impl ExecResult {
+    /// Whether the resolver ran and answered
+    pub fn works(&self) -> bool {
+        self.outcome == Some(FieldOutcome::Success)
+    }
+}
+
+/// Sends every generated operation once, with placeholders or the given variables, and
+/// records what came back
+pub struct Executor {
+    client: HttpClient,
+    url: String,
+    schema: Schema,
+    filter: OperationFilter,
+    mutations: bool,
+    guard: MutationGuard,
+    variables: VariableOverrides,
+    held_back: Vec<(String, MutationClass)>,
+    requests: usize,
+}
+
+impl Executor {
+    pub fn new(client: HttpClient, url: &str, schema: Schema) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            schema,
+            filter: OperationFilter::default(),
+            mutations: false,
+            guard: MutationGuard::default(),
+            variables: VariableOverrides::default(),
+            held_back: Vec::new(),
+            requests: 0,
+        }
+    }
+
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Run mutations too, those `guard` lets through
+    pub fn with_mutations(mut self, mutations: bool, guard: MutationGuard) -> Self {
+        self.mutations = mutations;
+        self.guard = guard;
+        self
+    }
+
+    /// Values to send instead of the generated placeholders
+    pub fn with_variables(mut self, variables: VariableOverrides) -> Self {
+        self.variables = variables;
+        self
+    }
+
+    /// Mutations the guard kept from running, with their class
+    pub fn held_back(&self) -> &[(String, MutationClass)] {
+        &self.held_back
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    pub async fn run(&mut self, callback: Option<&dyn Fn(&ExecResult)>) -> Result<Vec<ExecResult>> {
+        let catalog = CatalogExporter::new(self.schema.clone())
+            .with_filter(self.filter.clone())
+            .export();
+
+        let mut results = Vec::new();
+        for operation in catalog.operations {
+            if operation.kind != "query" && !(self.mutations && operation.kind == "mutation") {
+                continue;
+            }
+            if operation.kind == "mutation" {
+                let field = self
+                    .schema
+                    .get_mutation_type()
+                    .and_then(|t| t.fields.as_ref())
+                    .and_then(|fields| fields.iter().find(|f| f.name == operation.name));
+                if let Some(field) = field {
+                    let (class, allowed) = self.guard.check(field);
+                    if !allowed {
+                        self.held_back.push((operation.name, class));
+                        continue;
+                    }
+                }
+            }
+
+            let declared: Vec<&str> = operation.arguments.iter().map(|a| a.name.as_str()).collect();
+            let mut variables = operation.example.variables.clone();
+            self.variables.apply(&operation.name, &declared, &mut variables);
+
+            self.requests += 1;
+            let started = Instant::now();
+            let response = self
+                .client
+                .post_graphql(&self.url, &operation.example.query, Some(variables), Some("exec"))
+                .await;
+            let elapsed_ms = started.elapsed().as_millis();
+
+            let result = match response {
+                Ok(response) => {
+                    let error_classes: BTreeSet<ErrorClass> = response
+                        .get_errors()
+                        .and_then(|e| e.as_array())
+                        .into_iter()
+                        .flatten()
+                        .map(classify_error)
+                        .collect();
+                    ExecResult {
+                        outcome: FieldOutcome::of_response(&response, &operation.name),
+                        status: Some(response.status),
+                        error_classes: error_classes.into_iter().collect(),
+                        detail: response.get_first_error_message(),
+                        size: response.text.len(),
+                        elapsed_ms,
+                        curl_command: Some(response.curl_command),
+                        operation: operation.name,
+                        kind: operation.kind,
+                    }
+                }
+                Err(e) => ExecResult {
+                    operation: operation.name,
+                    kind: operation.kind,
+                    status: None,
+                    outcome: None,
+                    error_classes: Vec::new(),
+                    detail: Some(e.to_string()),
+                    size: 0,
+                    elapsed_ms,
+                    curl_command: None,
+                },
+            };
+            if let Some(cb) = callback {
+                cb(&result);
+            }
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,93 @@
+use anyhow::{bail, Context, Result};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Real values to send instead of generated placeholders: some for every operation that
+/// declares the variable, some for one operation only
+#[derive(Debug, Clone, Default)]
+pub struct VariableOverrides {
+    shared: Map<String, Value>,
+    operations: HashMap<String, Map<String, Value>>,
+}
+
+impl VariableOverrides {
+    /// A JSON object of variables for every operation, or a directory of
+    /// `<operationName>.json` files, each one operation's variables
+    pub fn load(path: &Path) -> Result<Self> {
+        let mut overrides = Self::default();
+        if !path.is_dir() {
+            overrides.shared = read_object(path)?;
+            return Ok(overrides);
+        }
+
+        let entries = std::fs::read_dir(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        for entry in entries {
+            let file = entry?.path();
+            if file.extension().and_then(|e| e.to_str()) != Some("json") {
+                continue;
+            }
+            let name = match file.file_stem().and_then(|s| s.to_str()) {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            overrides.operations.insert(name, read_object(&file)?);
+        }
+        Ok(overrides)
+    }
+
+    /// Take everything `other` has, its values winning
+    pub fn merge(&mut self, other: Self) {
+        for (name, value) in other.shared {
+            merge_value(self.shared.entry(name).or_insert(Value::Null), value);
+        }
+        for (operation, variables) in other.operations {
+            let existing = self.operations.entry(operation).or_default();
+            for (name, value) in variables {
+                merge_value(existing.entry(name).or_insert(Value::Null), value);
+            }
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shared.is_empty() && self.operations.is_empty()
+    }
+
+    /// Put the values for `operation` into `variables`, shared ones first. Only the
+    /// variables in `declared` are touched; objects are merged key by key, so a partial
+    /// input object keeps the placeholders for the rest.
+    pub fn apply(&self, operation: &str, declared: &[&str], variables: &mut Value) {
+        let variables = match variables.as_object_mut() {
+            Some(v) => v,
+            None => return,
+        };
+        let layers = std::iter::once(&self.shared).chain(self.operations.get(operation));
+        for layer in layers {
+            for (name, value) in layer {
+                if declared.contains(&name.as_str()) {
+                    merge_value(variables.entry(name.clone()).or_insert(Value::Null), value.clone());
+                }
+            }
+        }
+    }
+}
+
+fn read_object(path: &Path) -> Result<Map<String, Value>> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    match serde_json::from_str(&content).with_context(|| format!("{} isn't valid JSON", path.display()))? {
+        Value::Object(map) => Ok(map),
+        _ => bail!("{} should hold a JSON object of variables", path.display()),
+    }
+}
+
+/// Objects merge key by key, anything else replaces
+fn merge_value(base: &mut Value, value: Value) {
+    match (base, value) {
+        (Value::Object(base), Value::Object(value)) => {
+            for (key, item) in value {
+                merge_value(base.entry(key).or_insert(Value::Null), item);
+            }
+        }
+        (base, value) => *base = value,
+    }
+}
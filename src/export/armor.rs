@@ -0,0 +1,288 @@
+use crate::schema::{Schema, TypeRef};
+use crate::tests::{measured_limit, TestResult};
+use std::collections::{HashMap, HashSet};
+
+// graphql-armor defaults, used when nothing suggests tighter limits
+const DEFAULT_MAX_DEPTH: usize = 6;
+const DEFAULT_MAX_ALIASES: usize = 15;
+const DEFAULT_MAX_DIRECTIVES: usize = 50;
+const DEFAULT_MAX_TOKENS: usize = 1000;
+const DEFAULT_MAX_COST: usize = 5000;
+
+/// Recommended protection limits derived from a schema and scan findings
+#[derive(Debug, Clone)]
+pub struct HardeningPlan {
+    pub max_depth: usize,
+    pub max_aliases: usize,
+    pub max_directives: usize,
+    pub max_tokens: usize,
+    pub max_cost: usize,
+    pub max_root_fields: usize,
+    pub block_field_suggestions: bool,
+    pub disable_introspection: bool,
+    pub disable_batching: bool,
+    /// Why each limit was picked, keyed by setting name
+    pub reasons: Vec<(String, String)>,
+}
+
+/// Turns findings into a graphql-armor or Apollo Router configuration
+pub struct ArmorExporter {
+    schema: Option<Schema>,
+    findings: Vec<TestResult>,
+}
+
+impl ArmorExporter {
+    pub fn new(schema: Option<Schema>, findings: Vec<TestResult>) -> Self {
+        Self { schema, findings }
+    }
+
+    pub fn plan(&self) -> HardeningPlan {
+        let mut reasons = Vec::new();
+
+        let vulnerable = |name: &str| {
+            self.findings
+                .iter()
+                .any(|r| r.name == name && r.vulnerable)
+        };
+
+        // Depth: enough for the deepest non-recursive path real clients need
+        let max_depth = match self.schema.as_ref().and_then(schema_depth) {
+            Some(depth) => {
+                let limit = (depth + 1).clamp(4, 15);
+                reasons.push((
+                    "maxDepth".to_string(),
+                    format!("deepest non-recursive path in the schema is {} levels", depth),
+                ));
+                limit
+            }
+            None => DEFAULT_MAX_DEPTH,
+        };
+        // The finding says there's no limit today, not where one should be
+        if vulnerable("depth_limit") || vulnerable("circular_introspection") {
+            reasons.push((
+                "maxDepth".to_string(),
+                "no depth limit on the server today (it accepted deeply nested / recursive queries)".to_string(),
+            ));
+        }
+
+        // A tenth of the aliases the server took in one query
+        let max_aliases = match self.measured("alias_overloading") {
+            Some((accepted, evidence)) => {
+                reasons.push(("maxAliases".to_string(), format!("server {}", evidence)));
+                (accepted / 10).clamp(1, DEFAULT_MAX_ALIASES)
+            }
+            None => DEFAULT_MAX_ALIASES,
+        };
+
+        let max_directives = if vulnerable("directive_overloading") {
+            reasons.push((
+                "maxDirectives".to_string(),
+                "server processed repeated directives on a single field".to_string(),
+            ));
+            10
+        } else {
+            DEFAULT_MAX_DIRECTIVES
+        };
+
+        // Each duplicated field is a token: half of what the server took
+        let max_tokens = match self.measured("field_duplication") {
+            Some((accepted, evidence)) => {
+                reasons.push(("maxTokens".to_string(), format!("server {}", evidence)));
+                (accepted / 2).clamp(1, DEFAULT_MAX_TOKENS)
+            }
+            None => DEFAULT_MAX_TOKENS,
+        };
+
+        let max_cost = if vulnerable("query_complexity") {
+            reasons.push((
+                "costLimit".to_string(),
+                "server executed nested list queries without a cost limit".to_string(),
+            ));
+            DEFAULT_MAX_COST / 5
+        } else {
+            DEFAULT_MAX_COST
+        };
+
+        // Root fields: allow a handful per operation, never more than the schema has
+        let root_count = self
+            .schema
+            .as_ref()
+            .and_then(|s| s.get_query_type())
+            .and_then(|t| t.fields.as_ref())
+            .map(|f| f.len())
+            .unwrap_or(0);
+        let max_root_fields = if root_count == 0 { 10 } else { root_count.clamp(1, 10) };
+
+        let block_field_suggestions = true;
+        if vulnerable("field_suggestions") {
+            reasons.push((
+                "blockFieldSuggestions".to_string(),
+                "error messages leak valid field names".to_string(),
+            ));
+        }
+
+        let disable_introspection = vulnerable("introspection");
+        if disable_introspection {
+            reasons.push((
+                "introspection".to_string(),
+                "full introspection is enabled".to_string(),
+            ));
+        }
+
+        let disable_batching = vulnerable("batch_query");
+        if disable_batching {
+            reasons.push((
+                "batching".to_string(),
+                "array-based batching is accepted".to_string(),
+            ));
+        }
+
+        HardeningPlan {
+            max_depth,
+            max_aliases,
+            max_directives,
+            max_tokens,
+            max_cost,
+            max_root_fields,
+            block_field_suggestions,
+            disable_introspection,
+            disable_batching,
+            reasons,
+        }
+    }
+
+    /// The largest size `test`'s finding saw the server accept, and the evidence saying
+    /// so. None without a finding, or when it measured nothing (rejected even 1).
+    fn measured(&self, test: &str) -> Option<(usize, String)> {
+        let finding = self.findings.iter().find(|r| r.name == test && r.vulnerable)?;
+        measured_limit(finding.evidence.as_deref()?)
+    }
+
+    /// graphql-armor configuration (TypeScript, works for Apollo Server / Yoga / Envelop)
+    pub fn render_armor(&self) -> String {
+        let plan = self.plan();
+        let mut out = String::new();
+
+        out.push_str("// graphql-armor configuration generated by gqlmap\n");
+        push_reasons(&mut out, &plan, "//");
+        out.push_str("import { ApolloArmor } from '@escape.tech/graphql-armor';\n\n");
+        out.push_str("export const armor = new ApolloArmor({\n");
+        out.push_str(&format!("  maxDepth: {{ enabled: true, n: {} }},\n", plan.max_depth));
+        out.push_str(&format!("  maxAliases: {{ enabled: true, n: {} }},\n", plan.max_aliases));
+        out.push_str(&format!(
+            "  maxDirectives: {{ enabled: true, n: {} }},\n",
+            plan.max_directives
+        ));
+        out.push_str(&format!("  maxTokens: {{ enabled: true, n: {} }},\n", plan.max_tokens));
+        out.push_str(&format!(
+            "  costLimit: {{ enabled: true, maxCost: {} }},\n",
+            plan.max_cost
+        ));
+        out.push_str(&format!(
+            "  blockFieldSuggestion: {{ enabled: {} }},\n",
+            plan.block_field_suggestions
+        ));
+        out.push_str("});\n\n");
+        out.push_str("// Spread into your server: new ApolloServer({ ...armor.protect(), ... })\n");
+
+        if plan.disable_introspection {
+            out.push_str("// Also disable introspection in production: introspection: false\n");
+        }
+        if plan.disable_batching {
+            out.push_str("// Also disable batching in production: allowBatchedHttpRequests: false\n");
+        }
+
+        out
+    }
+
+    /// Apollo Router `router.yaml` fragment
+    pub fn render_router(&self) -> String {
+        let plan = self.plan();
+        let mut out = String::new();
+
+        out.push_str("# Apollo Router configuration generated by gqlmap\n");
+        push_reasons(&mut out, &plan, "#");
+        out.push_str("limits:\n");
+        out.push_str(&format!("  max_depth: {}\n", plan.max_depth));
+        out.push_str(&format!("  max_aliases: {}\n", plan.max_aliases));
+        out.push_str(&format!("  max_root_fields: {}\n", plan.max_root_fields));
+        // No max_height: it counts the unique fields of an operation, and neither the
+        // schema nor the findings tell how many a real client selects per level
+        out.push_str("  # max_height (unique fields per operation) left unset: set it from your clients' largest query\n");
+        out.push_str("supergraph:\n");
+        out.push_str(&format!("  introspection: {}\n", !plan.disable_introspection));
+        out.push_str("batching:\n");
+        out.push_str(&format!("  enabled: {}\n", !plan.disable_batching));
+        out.push_str("  mode: batch_http_link\n");
+        out.push_str("demand_control:\n");
+        out.push_str("  enabled: true\n");
+        out.push_str("  mode: enforce\n");
+        out.push_str("  strategy:\n");
+        out.push_str("    static_estimated:\n");
+        out.push_str(&format!("      max: {}\n", plan.max_cost));
+        out.push_str("      list_size: 10\n");
+
+        out
+    }
+}
+
+fn push_reasons(out: &mut String, plan: &HardeningPlan, comment: &str) {
+    if plan.reasons.is_empty() {
+        out.push('\n');
+        return;
+    }
+
+    out.push_str(&format!("{}\n{} Findings behind these limits:\n", comment, comment));
+    for (setting, reason) in &plan.reasons {
+        out.push_str(&format!("{}   {}: {}\n", comment, setting, reason));
+    }
+    out.push('\n');
+}
+
+/// Longest path from the query root, ignoring edges that loop back into types already on the path
+fn schema_depth(schema: &Schema) -> Option<usize> {
+    let root = schema.get_query_type()?.name.clone()?;
+    let mut memo = HashMap::new();
+    Some(type_depth(schema, &root, &mut HashSet::new(), &mut memo))
+}
+
+fn type_depth(
+    schema: &Schema,
+    type_name: &str,
+    on_path: &mut HashSet<String>,
+    memo: &mut HashMap<String, usize>,
+) -> usize {
+    if let Some(depth) = memo.get(type_name) {
+        return *depth;
+    }
+
+    let fields = match schema.get_type(type_name).and_then(|t| t.fields.as_ref()) {
+        Some(f) => f,
+        None => return 0,
+    };
+
+    on_path.insert(type_name.to_string());
+
+    let mut deepest = 0;
+    for field in fields.iter().filter(|f| !f.name.starts_with("__")) {
+        if let Some(child) = object_type_name(schema, &field.field_type) {
+            if !on_path.contains(child) {
+                deepest = deepest.max(type_depth(schema, child, on_path, memo));
+            }
+        }
+    }
+
+    on_path.remove(type_name);
+
+    let depth = deepest + 1;
+    memo.insert(type_name.to_string(), depth);
+    depth
+}
+
+fn object_type_name<'a>(schema: &Schema, type_ref: &'a TypeRef) -> Option<&'a str> {
+    let name = type_ref.get_base_type_name()?;
+    match schema.get_type(name) {
+        Some(t) if t.kind == "OBJECT" || t.kind == "INTERFACE" => Some(name),
+        _ => None,
+    }
+}
@@ -1,17 +1,44 @@
 use crate::schema::{Field, InputValue, Schema, TypeRef};
+use super::{GroupBy, OperationFilter};
 use anyhow::{Context, Result};
 use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 pub struct BrunoExporter {
     schema: Schema,
     base_url: String,
+    filter: OperationFilter,
+    headers: Vec<(String, String)>,
+    group_by: GroupBy,
 }
 
 impl BrunoExporter {
     pub fn new(schema: Schema, base_url: String) -> Self {
-        Self { schema, base_url }
+        Self {
+            schema,
+            base_url,
+            filter: OperationFilter::default(),
+            headers: Vec::new(),
+            group_by: GroupBy::default(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Extra headers (e.g. Authorization) embedded into every exported request
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Split each operation kind into sub-folders
+    pub fn with_group_by(mut self, group_by: GroupBy) -> Self {
+        self.group_by = group_by;
+        self
     }
 
     pub fn export(&self, output_dir: &Path) -> Result<ExportStats> {
@@ -19,6 +46,7 @@ impl BrunoExporter {
 
         let queries_dir = output_dir.join("queries");
         let mutations_dir = output_dir.join("mutations");
+        let subscriptions_dir = output_dir.join("subscriptions");
         fs::create_dir_all(&queries_dir)?;
         fs::create_dir_all(&mutations_dir)?;
 
@@ -40,38 +68,81 @@ impl BrunoExporter {
         let mut stats = ExportStats::default();
 
         // Export queries
-        if let Some(query_type) = self.schema.get_query_type() {
+        if let Some(query_type) = self
+            .schema
+            .get_query_type()
+            .filter(|_| self.filter.allows_kind("query"))
+        {
             if let Some(fields) = &query_type.fields {
                 for (idx, field) in fields.iter().enumerate() {
-                    if field.name.starts_with("__") {
+                    if field.name.starts_with("__") || !self.filter.allows("query", &field.name) {
                         continue;
                     }
                     let content = self.generate_bru_file(field, "query", idx + 1);
                     let filename = format!("{}.bru", field.name);
-                    fs::write(queries_dir.join(&filename), content)?;
+                    let dir = self.group_dir(&queries_dir, field)?;
+                    fs::write(dir.join(&filename), content)?;
                     stats.queries += 1;
                 }
             }
         }
 
         // Export mutations
-        if let Some(mutation_type) = self.schema.get_mutation_type() {
+        if let Some(mutation_type) = self
+            .schema
+            .get_mutation_type()
+            .filter(|_| self.filter.allows_kind("mutation"))
+        {
             if let Some(fields) = &mutation_type.fields {
                 for (idx, field) in fields.iter().enumerate() {
-                    if field.name.starts_with("__") {
+                    if field.name.starts_with("__") || !self.filter.allows("mutation", &field.name) {
                         continue;
                     }
                     let content = self.generate_bru_file(field, "mutation", idx + 1);
                     let filename = format!("{}.bru", field.name);
-                    fs::write(mutations_dir.join(&filename), content)?;
+                    let dir = self.group_dir(&mutations_dir, field)?;
+                    fs::write(dir.join(&filename), content)?;
                     stats.mutations += 1;
                 }
             }
         }
 
+        // Export subscriptions
+        if let Some(subscription_type) = self
+            .schema
+            .get_subscription_type()
+            .filter(|_| self.filter.allows_kind("subscription"))
+        {
+            if let Some(fields) = &subscription_type.fields {
+                fs::create_dir_all(&subscriptions_dir)?;
+                for (idx, field) in fields.iter().enumerate() {
+                    if field.name.starts_with("__") || !self.filter.allows("subscription", &field.name) {
+                        continue;
+                    }
+                    let content = self.generate_bru_file(field, "subscription", idx + 1);
+                    let filename = format!("{}.bru", field.name);
+                    let dir = self.group_dir(&subscriptions_dir, field)?;
+                    fs::write(dir.join(&filename), content)?;
+                    stats.subscriptions += 1;
+                }
+            }
+        }
+
         Ok(stats)
     }
 
+    /// Directory for an operation, creating its group sub-folder when grouping
+    fn group_dir(&self, base: &Path, field: &Field) -> Result<PathBuf> {
+        match self.group_by.key(field) {
+            Some(group) => {
+                let dir = base.join(group);
+                fs::create_dir_all(&dir)?;
+                Ok(dir)
+            }
+            None => Ok(base.to_path_buf()),
+        }
+    }
+
     fn generate_bru_file(&self, field: &Field, operation_type: &str, seq: usize) -> String {
         let args_str = self.build_args_string(&field.args);
         let selection = self.build_field_selection(&field.field_type, 0, &mut HashSet::new());
@@ -85,6 +156,17 @@ impl BrunoExporter {
             )
         };
 
+        let headers_block = if self.headers.is_empty() {
+            String::new()
+        } else {
+            let lines: Vec<String> = self
+                .headers
+                .iter()
+                .map(|(key, value)| format!("  {}: {}", key, value))
+                .collect();
+            format!("headers {{\n{}\n}}\n\n", lines.join("\n"))
+        };
+
         format!(
             r#"meta {{
   name: {}
@@ -98,13 +180,14 @@ post {{
   auth: inherit
 }}
 
-body:graphql {{
+{}body:graphql {{
   {}
 }}
 "#,
             field.name,
             seq,
             self.base_url,
+            headers_block,
             query.replace('\n', "\n  ")
         )
     }
@@ -273,4 +356,5 @@ body:graphql {{
 pub struct ExportStats {
     pub queries: usize,
     pub mutations: usize,
+    pub subscriptions: usize,
 }
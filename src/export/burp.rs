@@ -0,0 +1,253 @@
+use crate::schema::{build_arg_value, build_field_selection, type_ref_to_string, Field, Schema};
+use super::{ExportStats, OperationFilter};
+use anyhow::{Context, Result};
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::Path;
+
+/// Burp Suite items export format
+/// Writes a single XML file in the same layout as Burp's "Save items",
+/// with one full raw HTTP request per operation. Import it from the
+/// Proxy history / Target site map, then send items to Repeater.
+pub struct BurpExporter {
+    schema: Schema,
+    base_url: String,
+    filter: OperationFilter,
+    headers: Vec<(String, String)>,
+}
+
+impl BurpExporter {
+    pub fn new(schema: Schema, base_url: String) -> Self {
+        Self {
+            schema,
+            base_url,
+            filter: OperationFilter::default(),
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Extra headers (e.g. Authorization) embedded into every exported request
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn export(&self, output_path: &Path) -> Result<ExportStats> {
+        let target = url::Url::parse(&self.base_url).context("Invalid base URL")?;
+        let mut stats = ExportStats::default();
+
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\"?>\n");
+        xml.push_str("<!DOCTYPE items [\n");
+        xml.push_str("<!ELEMENT items (item*)>\n");
+        xml.push_str("<!ATTLIST items burpVersion CDATA \"\">\n");
+        xml.push_str("<!ATTLIST items exportTime CDATA \"\">\n");
+        xml.push_str("<!ELEMENT item (time, url, host, port, protocol, method, path, extension, request, status, responselength, mimetype, response, comment)>\n");
+        xml.push_str("<!ELEMENT time (#PCDATA)>\n");
+        xml.push_str("<!ELEMENT url (#PCDATA)>\n");
+        xml.push_str("<!ELEMENT host (#PCDATA)>\n");
+        xml.push_str("<!ATTLIST host ip CDATA \"\">\n");
+        xml.push_str("<!ELEMENT port (#PCDATA)>\n");
+        xml.push_str("<!ELEMENT protocol (#PCDATA)>\n");
+        xml.push_str("<!ELEMENT method (#PCDATA)>\n");
+        xml.push_str("<!ELEMENT path (#PCDATA)>\n");
+        xml.push_str("<!ELEMENT extension (#PCDATA)>\n");
+        xml.push_str("<!ELEMENT request (#PCDATA)>\n");
+        xml.push_str("<!ATTLIST request base64 (true|false) \"false\">\n");
+        xml.push_str("<!ELEMENT status (#PCDATA)>\n");
+        xml.push_str("<!ELEMENT responselength (#PCDATA)>\n");
+        xml.push_str("<!ELEMENT mimetype (#PCDATA)>\n");
+        xml.push_str("<!ELEMENT response (#PCDATA)>\n");
+        xml.push_str("<!ATTLIST response base64 (true|false) \"false\">\n");
+        xml.push_str("<!ELEMENT comment (#PCDATA)>\n");
+        xml.push_str("]>\n");
+        xml.push_str("<items burpVersion=\"\" exportTime=\"\">\n");
+
+        // Export queries
+        if let Some(query_type) = self
+            .schema
+            .get_query_type()
+            .filter(|_| self.filter.allows_kind("query"))
+        {
+            if let Some(fields) = &query_type.fields {
+                for field in fields.iter().filter(|f| !f.name.starts_with("__") && self.filter.allows("query", &f.name)) {
+                    xml.push_str(&self.generate_item(&target, field, "query"));
+                    stats.queries += 1;
+                }
+            }
+        }
+
+        // Export mutations
+        if let Some(mutation_type) = self
+            .schema
+            .get_mutation_type()
+            .filter(|_| self.filter.allows_kind("mutation"))
+        {
+            if let Some(fields) = &mutation_type.fields {
+                for field in fields.iter().filter(|f| !f.name.starts_with("__") && self.filter.allows("mutation", &f.name)) {
+                    xml.push_str(&self.generate_item(&target, field, "mutation"));
+                    stats.mutations += 1;
+                }
+            }
+        }
+
+        // Export subscriptions
+        if let Some(subscription_type) = self
+            .schema
+            .get_subscription_type()
+            .filter(|_| self.filter.allows_kind("subscription"))
+        {
+            if let Some(fields) = &subscription_type.fields {
+                for field in fields.iter().filter(|f| !f.name.starts_with("__") && self.filter.allows("subscription", &f.name)) {
+                    xml.push_str(&self.generate_item(&target, field, "subscription"));
+                    stats.subscriptions += 1;
+                }
+            }
+        }
+
+        xml.push_str("</items>\n");
+
+        fs::write(output_path, xml)?;
+
+        Ok(stats)
+    }
+
+    fn generate_item(&self, target: &url::Url, field: &Field, operation: &str) -> String {
+        let host = target.host_str().unwrap_or("localhost");
+        let port = target.port_or_known_default().unwrap_or(80);
+        let path = match target.query() {
+            Some(q) => format!("{}?{}", target.path(), q),
+            None => target.path().to_string(),
+        };
+
+        let host_header = match target.port() {
+            Some(p) => format!("{}:{}", host, p),
+            None => host.to_string(),
+        };
+
+        let body = serde_json::to_string(&self.build_body(field, operation)).unwrap_or_default();
+
+        let extra_headers: String = self
+            .headers
+            .iter()
+            .map(|(key, value)| format!("{}: {}\r\n", key, value))
+            .collect();
+
+        let request = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nAccept: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            path,
+            host_header,
+            extra_headers,
+            body.len(),
+            body
+        );
+
+        format!(
+            r#"  <item>
+    <time></time>
+    <url>{}</url>
+    <host ip="">{}</host>
+    <port>{}</port>
+    <protocol>{}</protocol>
+    <method>POST</method>
+    <path>{}</path>
+    <extension>null</extension>
+    <request base64="false">{}</request>
+    <status></status>
+    <responselength></responselength>
+    <mimetype></mimetype>
+    <response base64="false"></response>
+    <comment>{} {}</comment>
+  </item>
+"#,
+            cdata(target.as_str()),
+            host,
+            port,
+            target.scheme(),
+            cdata(&path),
+            cdata(&request),
+            operation,
+            field.name
+        )
+    }
+
+    fn build_body(&self, field: &Field, operation: &str) -> Value {
+        let selection = build_field_selection(&self.schema, &field.field_type, 3);
+        let op_name = operation_name(&field.name);
+
+        if field.args.is_empty() {
+            let query = if selection.is_empty() {
+                format!("{} {} {{ {} }}", operation, op_name, field.name)
+            } else {
+                format!("{} {} {{ {} {} }}", operation, op_name, field.name, selection)
+            };
+            return json!({ "operationName": op_name, "query": query });
+        }
+
+        // Build variable definitions
+        let var_defs: Vec<String> = field
+            .args
+            .iter()
+            .map(|arg| {
+                let type_str = type_ref_to_string(&arg.input_type);
+                format!("${}: {}", arg.name, type_str)
+            })
+            .collect();
+
+        // Build argument usage
+        let arg_usage: Vec<String> = field
+            .args
+            .iter()
+            .map(|arg| format!("{}: ${}", arg.name, arg.name))
+            .collect();
+
+        let query = if selection.is_empty() {
+            format!(
+                "{} {}({}) {{ {}({}) }}",
+                operation,
+                op_name,
+                var_defs.join(", "),
+                field.name,
+                arg_usage.join(", ")
+            )
+        } else {
+            format!(
+                "{} {}({}) {{ {}({}) {} }}",
+                operation,
+                op_name,
+                var_defs.join(", "),
+                field.name,
+                arg_usage.join(", "),
+                selection
+            )
+        };
+
+        let mut variables = Map::new();
+        for arg in &field.args {
+            if let Some(value) = build_arg_value(&self.schema, &arg.input_type) {
+                variables.insert(arg.name.clone(), value);
+            }
+        }
+
+        json!({ "operationName": op_name, "query": query, "variables": variables })
+    }
+}
+
+/// Operation names make the requests easy to tell apart in Burp's UI
+fn operation_name(field_name: &str) -> String {
+    let mut chars = field_name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Wrap text in a CDATA section, splitting any embedded `]]>` terminator
+fn cdata(text: &str) -> String {
+    format!("<![CDATA[{}]]>", text.replace("]]>", "]]]]><![CDATA[>"))
+}
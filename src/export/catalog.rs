@@ -0,0 +1,265 @@
+use super::OperationFilter;
+use crate::schema::{build_arg_value, build_field_selection, type_ref_to_string, Field, Schema, TypeRef};
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+
+/// Machine-readable inventory of every operation in a schema
+#[derive(Debug, Serialize)]
+pub struct OperationCatalog {
+    pub generator: String,
+    pub operations: Vec<CatalogOperation>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogOperation {
+    pub name: String,
+    /// query, mutation or subscription
+    pub kind: String,
+    pub description: Option<String>,
+    pub is_deprecated: bool,
+    pub deprecation_reason: Option<String>,
+    pub arguments: Vec<CatalogArgument>,
+    /// Return type in SDL notation, e.g. `[User!]!`
+    pub return_type: String,
+    pub returns: ReturnTypeNode,
+    pub example: CatalogExample,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogArgument {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_string: String,
+    pub required: bool,
+    pub default_value: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Return type tree, expanded a few levels deep
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReturnTypeNode {
+    pub name: String,
+    pub kind: String,
+    /// Set when the type already appears higher up in this branch
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub recursive: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<ReturnFieldNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReturnFieldNode {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub type_string: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub returns: Option<ReturnTypeNode>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CatalogExample {
+    pub query: String,
+    pub variables: Value,
+}
+
+pub struct CatalogExporter {
+    schema: Schema,
+    filter: OperationFilter,
+    max_depth: usize,
+}
+
+impl CatalogExporter {
+    pub fn new(schema: Schema) -> Self {
+        Self {
+            schema,
+            filter: OperationFilter::default(),
+            max_depth: 3,
+        }
+    }
+
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// How many levels of the return type tree to expand
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    pub fn export(&self) -> OperationCatalog {
+        let mut operations = Vec::new();
+
+        let roots = [
+            ("query", self.schema.get_query_type()),
+            ("mutation", self.schema.get_mutation_type()),
+            ("subscription", self.schema.get_subscription_type()),
+        ];
+
+        for (kind, root) in roots {
+            if !self.filter.allows_kind(kind) {
+                continue;
+            }
+            let fields = match root.and_then(|t| t.fields.as_ref()) {
+                Some(f) => f,
+                None => continue,
+            };
+
+            for field in fields
+                .iter()
+                .filter(|f| !f.name.starts_with("__") && self.filter.allows(kind, &f.name))
+            {
+                operations.push(self.catalog_operation(field, kind));
+            }
+        }
+
+        OperationCatalog {
+            generator: concat!("gqlmap/", env!("CARGO_PKG_VERSION")).to_string(),
+            operations,
+        }
+    }
+
+    fn catalog_operation(&self, field: &Field, kind: &str) -> CatalogOperation {
+        let arguments = field
+            .args
+            .iter()
+            .map(|arg| CatalogArgument {
+                name: arg.name.clone(),
+                type_string: type_ref_to_string(&arg.input_type),
+                required: arg.input_type.is_non_null() && arg.default_value.is_none(),
+                default_value: arg.default_value.clone(),
+                description: arg.description.clone(),
+            })
+            .collect();
+
+        CatalogOperation {
+            name: field.name.clone(),
+            kind: kind.to_string(),
+            description: field.description.clone(),
+            is_deprecated: field.is_deprecated,
+            deprecation_reason: field.deprecation_reason.clone(),
+            arguments,
+            return_type: type_ref_to_string(&field.field_type),
+            returns: self.build_type_node(&field.field_type, 0, &mut HashSet::new()),
+            example: self.build_example(field, kind),
+        }
+    }
+
+    fn build_type_node(
+        &self,
+        type_ref: &TypeRef,
+        depth: usize,
+        visited: &mut HashSet<String>,
+    ) -> ReturnTypeNode {
+        let name = type_ref.get_base_type_name().unwrap_or("Unknown").to_string();
+        let full_type = self.schema.get_type(&name);
+        let kind = full_type
+            .map(|t| t.kind.clone())
+            .unwrap_or_else(|| "SCALAR".to_string());
+
+        let mut node = ReturnTypeNode {
+            name: name.clone(),
+            kind,
+            recursive: false,
+            fields: Vec::new(),
+        };
+
+        let fields = match full_type.and_then(|t| t.fields.as_ref()) {
+            Some(f) => f,
+            None => return node,
+        };
+
+        if visited.contains(&name) {
+            node.recursive = true;
+            return node;
+        }
+        if depth >= self.max_depth {
+            return node;
+        }
+
+        visited.insert(name.clone());
+        node.fields = fields
+            .iter()
+            .filter(|f| !f.name.starts_with("__"))
+            .map(|f| {
+                let returns = match f.field_type.get_base_type_name().and_then(|n| self.schema.get_type(n)) {
+                    Some(t) if t.fields.is_some() => {
+                        Some(self.build_type_node(&f.field_type, depth + 1, visited))
+                    }
+                    _ => None,
+                };
+                ReturnFieldNode {
+                    name: f.name.clone(),
+                    type_string: type_ref_to_string(&f.field_type),
+                    returns,
+                }
+            })
+            .collect();
+        visited.remove(&name);
+
+        node
+    }
+
+    fn build_example(&self, field: &Field, kind: &str) -> CatalogExample {
+        let selection = build_field_selection(&self.schema, &field.field_type, 3);
+
+        if field.args.is_empty() {
+            let query = if selection.is_empty() {
+                format!("{} {{ {} }}", kind, field.name)
+            } else {
+                format!("{} {{ {} {} }}", kind, field.name, selection)
+            };
+            return CatalogExample {
+                query,
+                variables: json!({}),
+            };
+        }
+
+        let var_defs: Vec<String> = field
+            .args
+            .iter()
+            .map(|arg| format!("${}: {}", arg.name, type_ref_to_string(&arg.input_type)))
+            .collect();
+        let arg_usage: Vec<String> = field
+            .args
+            .iter()
+            .map(|arg| format!("{}: ${}", arg.name, arg.name))
+            .collect();
+
+        let query = if selection.is_empty() {
+            format!(
+                "{}({}) {{ {}({}) }}",
+                kind,
+                var_defs.join(", "),
+                field.name,
+                arg_usage.join(", ")
+            )
+        } else {
+            format!(
+                "{}({}) {{ {}({}) {} }}",
+                kind,
+                var_defs.join(", "),
+                field.name,
+                arg_usage.join(", "),
+                selection
+            )
+        };
+
+        let mut variables = Map::new();
+        for arg in &field.args {
+            if let Some(value) = build_arg_value(&self.schema, &arg.input_type) {
+                variables.insert(arg.name.clone(), value);
+            }
+        }
+
+        CatalogExample {
+            query,
+            variables: Value::Object(variables),
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+
+/// Selects which operations an exporter writes out.
+///
+/// Patterns are globs (`user*`, `get?ser`) matched case-insensitively against
+/// the operation name, or a regex when prefixed with `re:` (`re:^(get|list)`).
+#[derive(Debug, Clone, Default)]
+pub struct OperationFilter {
+    pattern: Option<Regex>,
+    kinds: Vec<String>,
+}
+
+impl OperationFilter {
+    /// `only` takes plural kind names: queries, mutations, subscriptions
+    pub fn new(pattern: Option<&str>, only: &[String]) -> Result<Self> {
+        let pattern = match pattern {
+            Some(p) => Some(compile_pattern(p)?),
+            None => None,
+        };
+
+        let mut kinds = Vec::new();
+        for kind in only {
+            let operation = match kind.trim() {
+                "queries" | "query" => "query",
+                "mutations" | "mutation" => "mutation",
+                "subscriptions" | "subscription" => "subscription",
+                other => bail!("Unknown operation kind: {}", other),
+            };
+            kinds.push(operation.to_string());
+        }
+
+        Ok(Self { pattern, kinds })
+    }
+
+    /// Whether any operation of this kind (query, mutation, subscription) can be exported
+    pub fn allows_kind(&self, operation: &str) -> bool {
+        self.kinds.is_empty() || self.kinds.iter().any(|k| k == operation)
+    }
+
+    pub fn allows(&self, operation: &str, name: &str) -> bool {
+        if !self.allows_kind(operation) {
+            return false;
+        }
+
+        match &self.pattern {
+            Some(re) => re.is_match(name),
+            None => true,
+        }
+    }
+}
+
+fn compile_pattern(pattern: &str) -> Result<Regex> {
+    if let Some(re) = pattern.strip_prefix("re:") {
+        return Regex::new(re).context("Invalid filter regex");
+    }
+
+    let mut re = String::from("(?i)^");
+    for c in pattern.chars() {
+        match c {
+            '*' => re.push_str(".*"),
+            '?' => re.push('.'),
+            _ => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    re.push('$');
+
+    Regex::new(&re).context("Invalid filter glob")
+}
@@ -0,0 +1,87 @@
+use crate::schema::{print_sdl, Schema};
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+
+/// graphql-config project, read by the GraphQL IDE plugins (VS Code, JetBrains) and
+/// by codegen tools
+///
+/// Structure:
+/// output_dir/
+/// ├── .graphqlrc.yml   schema, documents glob and the endpoint
+/// ├── schema.graphql
+/// └── .env             header values the config refers to, with headers only
+pub struct GraphqlConfigExporter {
+    schema: Schema,
+    base_url: String,
+    headers: Vec<(String, String)>,
+}
+
+impl GraphqlConfigExporter {
+    pub fn new(schema: Schema, base_url: String) -> Self {
+        Self {
+            schema,
+            base_url,
+            headers: Vec::new(),
+        }
+    }
+
+    /// Headers the endpoint is queried with. The config names them as `${VARIABLE}`
+    /// placeholders; the values go into `.env`, not the config.
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn export(&self, output_dir: &Path) -> Result<()> {
+        fs::create_dir_all(output_dir).context("Failed to create output directory")?;
+
+        fs::write(output_dir.join("schema.graphql"), print_sdl(&self.schema))?;
+        fs::write(output_dir.join(".graphqlrc.yml"), self.render_config())?;
+        if !self.headers.is_empty() {
+            let lines: Vec<String> = self
+                .headers
+                .iter()
+                .map(|(name, value)| format!("{}={}", env_variable(name), quoted(value)))
+                .collect();
+            fs::write(output_dir.join(".env"), lines.join("\n") + "\n")?;
+        }
+
+        Ok(())
+    }
+
+    fn render_config(&self) -> String {
+        let mut out = String::new();
+        out.push_str(&format!("# graphql-config for {}, generated by gqlmap\n", self.base_url));
+        out.push_str("schema: schema.graphql\n");
+        out.push_str("documents: \"operations/**/*.graphql\"\n");
+        out.push_str("extensions:\n");
+        out.push_str("  endpoints:\n");
+        out.push_str("    default:\n");
+        out.push_str(&format!("      url: {}\n", quoted(&self.base_url)));
+        if !self.headers.is_empty() {
+            out.push_str("      headers:\n");
+            for (name, _) in &self.headers {
+                out.push_str(&format!(
+                    "        {}: {}\n",
+                    quoted(name),
+                    quoted(&format!("${{{}}}", env_variable(name)))
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// `X-Api-Key` -> `X_API_KEY`
+fn env_variable(header: &str) -> String {
+    header
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect()
+}
+
+/// A double-quoted string, which YAML and dotenv files read the same way
+fn quoted(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
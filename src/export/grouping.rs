@@ -0,0 +1,116 @@
+use crate::schema::Field;
+use anyhow::{bail, Result};
+
+// Verbs stripped from operation names when grouping by tag
+const VERB_PREFIXES: &[&str] = &[
+    "get", "list", "find", "search", "fetch", "all", "create", "update", "upsert", "delete",
+    "remove", "add", "set", "edit", "patch", "save",
+];
+
+/// How collection exporters split operations into sub-folders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GroupBy {
+    /// Flat Queries/Mutations/Subscriptions folders
+    #[default]
+    None,
+    /// Base return type (`user`, `users` and `updateUser` all returning User end up together)
+    Type,
+    /// Entity the operation acts on, with CRUD verbs stripped (`getUser`, `deleteUser` -> User)
+    Tag,
+    /// Leading camelCase word of the operation name (`userById`, `userSearch` -> user)
+    Prefix,
+}
+
+impl GroupBy {
+    pub fn parse(value: &str) -> Result<Self> {
+        Ok(match value {
+            "none" => GroupBy::None,
+            "type" => GroupBy::Type,
+            "tag" => GroupBy::Tag,
+            "prefix" => GroupBy::Prefix,
+            other => bail!("Unknown grouping: {}", other),
+        })
+    }
+
+    /// Sub-folder name for this operation, `None` when not grouping
+    pub fn key(&self, field: &Field) -> Option<String> {
+        match self {
+            GroupBy::None => None,
+            GroupBy::Type => Some(
+                field
+                    .field_type
+                    .get_base_type_name()
+                    .unwrap_or("Other")
+                    .to_string(),
+            ),
+            GroupBy::Tag => Some(tag_for(&field.name)),
+            GroupBy::Prefix => {
+                let first = camel_words(&field.name).into_iter().next().unwrap_or_default();
+                Some(singular(first))
+            }
+        }
+    }
+}
+
+fn tag_for(name: &str) -> String {
+    let words = camel_words(name);
+    let start = match words.first() {
+        Some(first) if words.len() > 1 && VERB_PREFIXES.contains(&first.to_lowercase().as_str()) => 1,
+        _ => 0,
+    };
+
+    // Stop at "By"/"For" qualifiers: userById -> User
+    let entity: Vec<&String> = words[start..]
+        .iter()
+        .take_while(|w| *w != "By" && *w != "For")
+        .collect();
+
+    let mut tag = String::new();
+    for word in entity {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            tag.extend(first.to_uppercase());
+            tag.push_str(chars.as_str());
+        }
+    }
+
+    let tag = singular(tag);
+    if tag.is_empty() {
+        "Other".to_string()
+    } else {
+        tag
+    }
+}
+
+/// Fold plurals into the singular folder (users -> user)
+fn singular(mut word: String) -> String {
+    if word.len() > 3 && word.ends_with('s') && !word.ends_with("ss") {
+        word.pop();
+    }
+    word
+}
+
+/// Split a camelCase / PascalCase / snake_case identifier into words
+pub fn camel_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for c in name.chars() {
+        if c == '_' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_ascii_uppercase() && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c);
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
@@ -0,0 +1,70 @@
+use crate::schema::Schema;
+use super::script::{shell_quote, ShellClient, ShellScript};
+use super::{ExportStats, OperationFilter};
+use anyhow::Result;
+use std::path::Path;
+
+// `key=value` is sent as a JSON string and `key:=value` as raw JSON
+const HTTPIE: ShellClient = ShellClient {
+    name: "HTTPie",
+    header: httpie_header,
+    request_function: r#"gql_request() {
+    local query="$1"
+    local variables="$2"
+    [ -z "$variables" ] && variables='{}'
+    http --ignore-stdin POST "$BASE_URL" \
+        Content-Type:application/json \
+        "${HEADERS[@]}" \
+        query="$query" \
+        variables:="$variables"
+}
+
+"#,
+};
+
+/// HTTPie script export format
+/// Same layout as the cURL script, but every operation is sent with `http POST`
+/// so HTTPie handles the JSON encoding of the query and variables.
+pub struct HttpieExporter {
+    schema: Schema,
+    base_url: String,
+    filter: OperationFilter,
+    headers: Vec<(String, String)>,
+}
+
+impl HttpieExporter {
+    pub fn new(schema: Schema, base_url: String) -> Self {
+        Self {
+            schema,
+            base_url,
+            filter: OperationFilter::default(),
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Extra headers (e.g. Authorization) embedded into every exported request
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    pub fn export(&self, output_path: &Path) -> Result<ExportStats> {
+        let script = ShellScript {
+            schema: &self.schema,
+            base_url: &self.base_url,
+            filter: &self.filter,
+            headers: &self.headers,
+        };
+        script.write(&HTTPIE, output_path)
+    }
+}
+
+/// An HTTPie `Name:value` request item
+fn httpie_header(key: &str, value: &str) -> String {
+    shell_quote(&format!("{}:{}", key, value))
+}
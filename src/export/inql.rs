@@ -1,8 +1,9 @@
 use crate::schema::{Field, Schema, TypeRef};
+use super::{GroupBy, OperationFilter};
 use anyhow::Result;
 use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// InQL-compatible export format
 /// Creates a directory structure compatible with Burp Suite's InQL extension
@@ -12,23 +13,51 @@ use std::path::Path;
 /// ├── queries/
 /// │   ├── query1.graphql
 /// │   └── query2.graphql
-/// └── mutations/
-///     ├── mutation1.graphql
-///     └── mutation2.graphql
-
+/// ├── mutations/
+/// │   ├── mutation1.graphql
+/// │   └── mutation2.graphql
+/// └── subscriptions/
+///     └── subscription1.graphql
 pub struct InqlExporter {
     schema: Schema,
     base_url: String,
+    filter: OperationFilter,
+    headers: Vec<(String, String)>,
+    group_by: GroupBy,
 }
 
 impl InqlExporter {
     pub fn new(schema: Schema, base_url: String) -> Self {
-        Self { schema, base_url }
+        Self {
+            schema,
+            base_url,
+            filter: OperationFilter::default(),
+            headers: Vec::new(),
+            group_by: GroupBy::default(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Extra headers (e.g. Authorization) embedded into every exported request
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Split each operation kind into sub-folders
+    pub fn with_group_by(mut self, group_by: GroupBy) -> Self {
+        self.group_by = group_by;
+        self
     }
 
     pub fn export(&self, output_dir: &Path) -> Result<ExportStats> {
         let queries_dir = output_dir.join("queries");
         let mutations_dir = output_dir.join("mutations");
+        let subscriptions_dir = output_dir.join("subscriptions");
 
         fs::create_dir_all(&queries_dir)?;
         fs::create_dir_all(&mutations_dir)?;
@@ -36,11 +65,16 @@ impl InqlExporter {
         let mut stats = ExportStats::default();
 
         // Export queries
-        if let Some(query_type) = self.schema.get_query_type() {
+        if let Some(query_type) = self
+            .schema
+            .get_query_type()
+            .filter(|_| self.filter.allows_kind("query"))
+        {
             if let Some(fields) = &query_type.fields {
-                for field in fields.iter().filter(|f| !f.name.starts_with("__")) {
+                for field in fields.iter().filter(|f| !f.name.starts_with("__") && self.filter.allows("query", &f.name)) {
                     let content = self.generate_operation(field, "query");
-                    let path = queries_dir.join(format!("{}.graphql", field.name));
+                    let dir = self.group_dir(&queries_dir, field)?;
+                    let path = dir.join(format!("{}.graphql", field.name));
                     fs::write(path, content)?;
                     stats.queries += 1;
                 }
@@ -48,27 +82,65 @@ impl InqlExporter {
         }
 
         // Export mutations
-        if let Some(mutation_type) = self.schema.get_mutation_type() {
+        if let Some(mutation_type) = self
+            .schema
+            .get_mutation_type()
+            .filter(|_| self.filter.allows_kind("mutation"))
+        {
             if let Some(fields) = &mutation_type.fields {
-                for field in fields.iter().filter(|f| !f.name.starts_with("__")) {
+                for field in fields.iter().filter(|f| !f.name.starts_with("__") && self.filter.allows("mutation", &f.name)) {
                     let content = self.generate_operation(field, "mutation");
-                    let path = mutations_dir.join(format!("{}.graphql", field.name));
+                    let dir = self.group_dir(&mutations_dir, field)?;
+                    let path = dir.join(format!("{}.graphql", field.name));
                     fs::write(path, content)?;
                     stats.mutations += 1;
                 }
             }
         }
 
+        // Export subscriptions
+        if let Some(subscription_type) = self
+            .schema
+            .get_subscription_type()
+            .filter(|_| self.filter.allows_kind("subscription"))
+        {
+            if let Some(fields) = &subscription_type.fields {
+                fs::create_dir_all(&subscriptions_dir)?;
+                for field in fields.iter().filter(|f| !f.name.starts_with("__") && self.filter.allows("subscription", &f.name)) {
+                    let content = self.generate_operation(field, "subscription");
+                    let dir = self.group_dir(&subscriptions_dir, field)?;
+                    let path = dir.join(format!("{}.graphql", field.name));
+                    fs::write(path, content)?;
+                    stats.subscriptions += 1;
+                }
+            }
+        }
+
         // Write metadata file
-        let metadata = format!(
-            "# InQL Export\n# URL: {}\n# Queries: {}\n# Mutations: {}\n",
-            self.base_url, stats.queries, stats.mutations
+        let mut metadata = format!(
+            "# InQL Export\n# URL: {}\n# Queries: {}\n# Mutations: {}\n# Subscriptions: {}\n",
+            self.base_url, stats.queries, stats.mutations, stats.subscriptions
         );
+        for (key, value) in &self.headers {
+            metadata.push_str(&format!("# Header: {}: {}\n", key, value));
+        }
         fs::write(output_dir.join("metadata.txt"), metadata)?;
 
         Ok(stats)
     }
 
+    /// Directory for an operation, creating its group sub-folder when grouping
+    fn group_dir(&self, base: &Path, field: &Field) -> Result<PathBuf> {
+        match self.group_by.key(field) {
+            Some(group) => {
+                let dir = base.join(group);
+                fs::create_dir_all(&dir)?;
+                Ok(dir)
+            }
+            None => Ok(base.to_path_buf()),
+        }
+    }
+
     fn generate_operation(&self, field: &Field, operation: &str) -> String {
         let selection = self.build_field_selection(&field.field_type, 0, &mut HashSet::new());
 
@@ -262,4 +334,5 @@ impl InqlExporter {
 pub struct ExportStats {
     pub queries: usize,
     pub mutations: usize,
+    pub subscriptions: usize,
 }
@@ -1,9 +1,29 @@
+mod armor;
 mod bruno;
+mod burp;
+mod catalog;
 mod curl;
+mod filter;
+mod graphql_config;
+mod grouping;
+mod httpie;
 mod inql;
 mod postman;
+mod script;
+mod stats;
 
+pub use armor::{ArmorExporter, HardeningPlan};
 pub use bruno::BrunoExporter;
+pub use burp::BurpExporter;
+pub use catalog::{
+    CatalogArgument, CatalogExample, CatalogExporter, CatalogOperation, OperationCatalog,
+    ReturnFieldNode, ReturnTypeNode,
+};
 pub use curl::CurlExporter;
+pub use filter::OperationFilter;
+pub use graphql_config::GraphqlConfigExporter;
+pub use grouping::{camel_words, GroupBy};
+pub use httpie::HttpieExporter;
 pub use inql::InqlExporter;
-pub use postman::PostmanExporter;
+pub use postman::{PostmanAuthKind, PostmanExporter};
+pub use stats::ExportStats;
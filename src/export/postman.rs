@@ -1,12 +1,15 @@
 use crate::schema::{Field, InputValue, Schema, TypeRef};
+use super::{GroupBy, OperationFilter};
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PostmanCollection {
     pub info: PostmanInfo,
     pub item: Vec<PostmanFolder>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth: Option<PostmanAuth>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,7 +21,28 @@ pub struct PostmanInfo {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PostmanFolder {
     pub name: String,
-    pub item: Vec<PostmanRequest>,
+    pub item: Vec<PostmanItem>,
+}
+
+/// Folder entries are either requests or nested folders (when grouping)
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PostmanItem {
+    Folder(PostmanFolder),
+    Request(PostmanRequest),
+}
+
+impl PostmanFolder {
+    /// Number of requests in this folder, including nested folders
+    pub fn request_count(&self) -> usize {
+        self.item
+            .iter()
+            .map(|i| match i {
+                PostmanItem::Folder(f) => f.request_count(),
+                PostmanItem::Request(_) => 1,
+            })
+            .sum()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -58,55 +82,189 @@ pub struct PostmanGraphQL {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PostmanUrl {
     pub raw: String,
-    pub protocol: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
     pub host: Vec<String>,
     pub path: Vec<String>,
 }
 
+/// Collection-level auth, inherited by every request
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostmanAuth {
+    #[serde(rename = "type")]
+    pub auth_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bearer: Option<Vec<PostmanKeyValue>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub apikey: Option<Vec<PostmanKeyValue>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostmanKeyValue {
+    pub key: String,
+    pub value: String,
+    #[serde(rename = "type")]
+    pub value_type: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostmanEnvironment {
+    pub name: String,
+    pub values: Vec<PostmanEnvironmentValue>,
+    #[serde(rename = "_postman_variable_scope")]
+    pub scope: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PostmanEnvironmentValue {
+    pub key: String,
+    pub value: String,
+    #[serde(rename = "type")]
+    pub value_type: String,
+    pub enabled: bool,
+}
+
+/// Which collection-level auth to attach
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PostmanAuthKind {
+    None,
+    Bearer,
+    ApiKey { header: String },
+}
+
 pub struct PostmanExporter {
     schema: Schema,
     base_url: String,
+    filter: OperationFilter,
+    headers: Vec<(String, String)>,
+    group_by: GroupBy,
+    auth: PostmanAuthKind,
+    token: String,
 }
 
 impl PostmanExporter {
     pub fn new(schema: Schema, base_url: String) -> Self {
-        Self { schema, base_url }
+        Self {
+            schema,
+            base_url,
+            filter: OperationFilter::default(),
+            headers: Vec::new(),
+            group_by: GroupBy::default(),
+            auth: PostmanAuthKind::None,
+            token: String::new(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Extra headers (e.g. Authorization) embedded into every exported request
+    pub fn with_headers(mut self, headers: Vec<(String, String)>) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    /// Split each operation kind into sub-folders
+    pub fn with_group_by(mut self, group_by: GroupBy) -> Self {
+        self.group_by = group_by;
+        self
+    }
+
+    /// Attach collection-level auth that reads its secret from `{{token}}`
+    pub fn with_auth(mut self, auth: PostmanAuthKind, token: Option<String>) -> Self {
+        self.auth = auth;
+        self.token = token.unwrap_or_default();
+        self
+    }
+
+    /// Environment holding `{{baseUrl}}` and `{{token}}` for the collection
+    pub fn environment(&self) -> PostmanEnvironment {
+        PostmanEnvironment {
+            name: "GraphQL API".to_string(),
+            values: vec![
+                PostmanEnvironmentValue {
+                    key: "baseUrl".to_string(),
+                    value: self.base_url.clone(),
+                    value_type: "default".to_string(),
+                    enabled: true,
+                },
+                PostmanEnvironmentValue {
+                    key: "token".to_string(),
+                    value: self.token.clone(),
+                    value_type: "secret".to_string(),
+                    enabled: true,
+                },
+            ],
+            scope: "environment".to_string(),
+        }
     }
 
     pub fn export(&self) -> Result<PostmanCollection> {
         let mut folders = Vec::new();
 
         // Export queries
-        if let Some(query_type) = self.schema.get_query_type() {
+        if let Some(query_type) = self
+            .schema
+            .get_query_type()
+            .filter(|_| self.filter.allows_kind("query"))
+        {
             if let Some(fields) = &query_type.fields {
-                let requests: Vec<PostmanRequest> = fields
+                let requests: Vec<(&Field, PostmanRequest)> = fields
                     .iter()
-                    .filter(|f| !f.name.starts_with("__"))
-                    .map(|f| self.create_request(f, "query"))
+                    .filter(|f| !f.name.starts_with("__") && self.filter.allows("query", &f.name))
+                    .map(|f| (f, self.create_request(f, "query")))
                     .collect();
 
                 if !requests.is_empty() {
                     folders.push(PostmanFolder {
                         name: "Queries".to_string(),
-                        item: requests,
+                        item: self.group_items(requests),
                     });
                 }
             }
         }
 
         // Export mutations
-        if let Some(mutation_type) = self.schema.get_mutation_type() {
+        if let Some(mutation_type) = self
+            .schema
+            .get_mutation_type()
+            .filter(|_| self.filter.allows_kind("mutation"))
+        {
             if let Some(fields) = &mutation_type.fields {
-                let requests: Vec<PostmanRequest> = fields
+                let requests: Vec<(&Field, PostmanRequest)> = fields
                     .iter()
-                    .filter(|f| !f.name.starts_with("__"))
-                    .map(|f| self.create_request(f, "mutation"))
+                    .filter(|f| !f.name.starts_with("__") && self.filter.allows("mutation", &f.name))
+                    .map(|f| (f, self.create_request(f, "mutation")))
                     .collect();
 
                 if !requests.is_empty() {
                     folders.push(PostmanFolder {
                         name: "Mutations".to_string(),
-                        item: requests,
+                        item: self.group_items(requests),
+                    });
+                }
+            }
+        }
+
+        // Export subscriptions
+        if let Some(subscription_type) = self
+            .schema
+            .get_subscription_type()
+            .filter(|_| self.filter.allows_kind("subscription"))
+        {
+            if let Some(fields) = &subscription_type.fields {
+                let requests: Vec<(&Field, PostmanRequest)> = fields
+                    .iter()
+                    .filter(|f| !f.name.starts_with("__") && self.filter.allows("subscription", &f.name))
+                    .map(|f| (f, self.create_request(f, "subscription")))
+                    .collect();
+
+                if !requests.is_empty() {
+                    folders.push(PostmanFolder {
+                        name: "Subscriptions".to_string(),
+                        item: self.group_items(requests),
                     });
                 }
             }
@@ -119,9 +277,56 @@ impl PostmanExporter {
                     .to_string(),
             },
             item: folders,
+            auth: self.build_auth(),
         })
     }
 
+    fn group_items(&self, requests: Vec<(&Field, PostmanRequest)>) -> Vec<PostmanItem> {
+        if self.group_by == GroupBy::None {
+            return requests
+                .into_iter()
+                .map(|(_, r)| PostmanItem::Request(r))
+                .collect();
+        }
+
+        let mut groups: BTreeMap<String, Vec<PostmanItem>> = BTreeMap::new();
+        for (field, request) in requests {
+            let key = self.group_by.key(field).unwrap_or_default();
+            groups.entry(key).or_default().push(PostmanItem::Request(request));
+        }
+
+        groups
+            .into_iter()
+            .map(|(name, item)| PostmanItem::Folder(PostmanFolder { name, item }))
+            .collect()
+    }
+
+    fn build_auth(&self) -> Option<PostmanAuth> {
+        let kv = |key: &str, value: &str| PostmanKeyValue {
+            key: key.to_string(),
+            value: value.to_string(),
+            value_type: "string".to_string(),
+        };
+
+        match &self.auth {
+            PostmanAuthKind::None => None,
+            PostmanAuthKind::Bearer => Some(PostmanAuth {
+                auth_type: "bearer".to_string(),
+                bearer: Some(vec![kv("token", "{{token}}")]),
+                apikey: None,
+            }),
+            PostmanAuthKind::ApiKey { header } => Some(PostmanAuth {
+                auth_type: "apikey".to_string(),
+                bearer: None,
+                apikey: Some(vec![
+                    kv("key", header),
+                    kv("value", "{{token}}"),
+                    kv("in", "header"),
+                ]),
+            }),
+        }
+    }
+
     fn create_request(&self, field: &Field, operation: &str) -> PostmanRequest {
         let args_str = self.build_args_string(&field.args);
         let selection = self.build_field_selection(&field.field_type, 0, &mut HashSet::new());
@@ -136,17 +341,24 @@ impl PostmanExporter {
             )
         };
 
-        let url_parts = parse_url(&self.base_url);
+        let url_parts = env_url();
+
+        let mut header = vec![PostmanHeader {
+            key: "Content-Type".to_string(),
+            value: "application/json".to_string(),
+            header_type: "text".to_string(),
+        }];
+        header.extend(self.headers.iter().map(|(key, value)| PostmanHeader {
+            key: key.clone(),
+            value: value.clone(),
+            header_type: "text".to_string(),
+        }));
 
         PostmanRequest {
             name: field.name.clone(),
             request: PostmanRequestDetails {
                 method: "POST".to_string(),
-                header: vec![PostmanHeader {
-                    key: "Content-Type".to_string(),
-                    value: "application/json".to_string(),
-                    header_type: "text".to_string(),
-                }],
+                header,
                 body: PostmanBody {
                     mode: "graphql".to_string(),
                     graphql: PostmanGraphQL {
@@ -322,27 +534,12 @@ impl PostmanExporter {
     }
 }
 
-fn parse_url(url: &str) -> PostmanUrl {
-    let url_obj = url::Url::parse(url).unwrap_or_else(|_| url::Url::parse("http://localhost").unwrap());
-
-    let protocol = url_obj.scheme().to_string();
-    let host: Vec<String> = url_obj
-        .host_str()
-        .unwrap_or("localhost")
-        .split('.')
-        .map(|s| s.to_string())
-        .collect();
-    let path: Vec<String> = url_obj
-        .path()
-        .split('/')
-        .filter(|s| !s.is_empty())
-        .map(|s| s.to_string())
-        .collect();
-
+/// Requests point at `{{baseUrl}}` so the target lives in the environment file
+fn env_url() -> PostmanUrl {
     PostmanUrl {
-        raw: url.to_string(),
-        protocol,
-        host,
-        path,
+        raw: "{{baseUrl}}".to_string(),
+        protocol: None,
+        host: vec!["{{baseUrl}}".to_string()],
+        path: Vec::new(),
     }
 }
@@ -0,0 +1,310 @@
+use crate::schema::{type_ref_to_string, Field, Schema, TypeRef};
+use super::{ExportStats, OperationFilter};
+use anyhow::Result;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// What an exported shell script changes with the HTTP client it runs
+pub(super) struct ShellClient {
+    /// Client name for the script's header comment
+    pub name: &'static str,
+    /// One `HEADERS` array item for a request header
+    pub header: fn(&str, &str) -> String,
+    /// Bash `gql_request QUERY [VARIABLES]` function posting to `$BASE_URL`
+    pub request_function: &'static str,
+}
+
+/// Executable bash script with a function per operation and a dispatcher running the
+/// one named on the command line, shared by the cURL and HTTPie exporters. Functions
+/// are named after the root too (`query_user`, `subscription_user`), so a field on
+/// two roots doesn't clash and none shadows a builtin like `test` or a script helper.
+pub(super) struct ShellScript<'a> {
+    pub schema: &'a Schema,
+    pub base_url: &'a str,
+    pub filter: &'a OperationFilter,
+    pub headers: &'a [(String, String)],
+}
+
+impl ShellScript<'_> {
+    pub fn write(&self, client: &ShellClient, output_path: &Path) -> Result<ExportStats> {
+        let mut script = String::new();
+        let mut stats = ExportStats::default();
+
+        // Script header
+        script.push_str("#!/bin/bash\n");
+        script.push_str(&format!("# GraphQL API {} commands\n", client.name));
+        script.push_str(&format!("# Generated by gqlmap for: {}\n", self.base_url));
+        script.push_str("#\n");
+        script.push_str("# Usage: ./queries.sh [operation_name]   (e.g. query_user)\n");
+        script.push_str("# Without arguments, lists all available operations\n\n");
+
+        script.push_str(&format!("BASE_URL=\"{}\"\n\n", self.base_url));
+
+        // Extra request headers
+        script.push_str("HEADERS=(\n");
+        for (key, value) in self.headers {
+            script.push_str(&format!("    {}\n", (client.header)(key, value)));
+        }
+        script.push_str(")\n\n");
+
+        script.push_str(client.request_function);
+
+        let mut operations = Vec::new();
+
+        let roots = [
+            ("query", self.schema.get_query_type(), "QUERIES"),
+            ("mutation", self.schema.get_mutation_type(), "MUTATIONS"),
+            ("subscription", self.schema.get_subscription_type(), "SUBSCRIPTIONS"),
+        ];
+        for (operation, root, heading) in roots {
+            let fields = match root.filter(|_| self.filter.allows_kind(operation)).and_then(|t| t.fields.as_ref()) {
+                Some(fields) => fields,
+                None => continue,
+            };
+            script.push_str(&format!("# ========== {} ==========\n\n", heading));
+
+            for field in fields.iter().filter(|f| !f.name.starts_with("__") && self.filter.allows(operation, &f.name)) {
+                let func = self.generate_function(field, operation);
+                script.push_str(&func);
+                script.push('\n');
+                operations.push(function_name(operation, field));
+                match operation {
+                    "query" => stats.queries += 1,
+                    "mutation" => stats.mutations += 1,
+                    _ => stats.subscriptions += 1,
+                }
+            }
+        }
+
+        // Add main dispatcher
+        script.push_str("# ========== MAIN ==========\n\n");
+        script.push_str("list_operations() {\n");
+        script.push_str("    echo \"Available operations:\"\n");
+        for op in &operations {
+            script.push_str(&format!("    echo \"  - {}\"\n", op));
+        }
+        script.push_str("}\n\n");
+
+        script.push_str("case \"$1\" in\n");
+        for op in &operations {
+            script.push_str(&format!("    {}) {};;", op, op));
+            script.push('\n');
+        }
+        script.push_str("    \"\") list_operations;;\n");
+        script.push_str("    *) echo \"Unknown operation: $1\"; list_operations; exit 1;;\n");
+        script.push_str("esac\n");
+
+        fs::write(output_path, script)?;
+
+        // Make executable on Unix
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mut perms = fs::metadata(output_path)?.permissions();
+            perms.set_mode(0o755);
+            fs::set_permissions(output_path, perms)?;
+        }
+
+        Ok(stats)
+    }
+
+    fn generate_function(&self, field: &Field, operation: &str) -> String {
+        let selection = self.build_field_selection(&field.field_type, 0, &mut HashSet::new());
+        let (query, variables) = self.build_query_and_vars(field, operation, &selection);
+
+        let mut func = String::new();
+        func.push_str(&format!("# {} {}\n", operation, field.name));
+        func.push_str(&format!("{}() {{\n", function_name(operation, field)));
+
+        if field.args.is_empty() {
+            func.push_str(&format!("    gql_request {}\n", shell_quote(&query)));
+        } else {
+            func.push_str(&format!("    local vars='{}'\n", variables));
+            func.push_str(&format!("    gql_request {} \"$vars\"\n", shell_quote(&query)));
+        }
+
+        func.push_str("}\n");
+        func
+    }
+
+    fn build_query_and_vars(&self, field: &Field, operation: &str, selection: &str) -> (String, String) {
+        if field.args.is_empty() {
+            let query = if selection.is_empty() {
+                format!("{} {{ {} }}", operation, field.name)
+            } else {
+                format!("{} {{ {} {} }}", operation, field.name, selection)
+            };
+            return (query, "{}".to_string());
+        }
+
+        // Build variable definitions
+        let var_defs: Vec<String> = field
+            .args
+            .iter()
+            .map(|arg| format!("${}: {}", arg.name, type_ref_to_string(&arg.input_type)))
+            .collect();
+
+        // Build argument usage
+        let arg_usage: Vec<String> = field
+            .args
+            .iter()
+            .map(|arg| format!("{}: ${}", arg.name, arg.name))
+            .collect();
+
+        let query = if selection.is_empty() {
+            format!(
+                "{}({}) {{ {}({}) }}",
+                operation,
+                var_defs.join(", "),
+                field.name,
+                arg_usage.join(", ")
+            )
+        } else {
+            format!(
+                "{}({}) {{ {}({}) {} }}",
+                operation,
+                var_defs.join(", "),
+                field.name,
+                arg_usage.join(", "),
+                selection
+            )
+        };
+
+        // Build variables JSON
+        let vars: Vec<String> = field
+            .args
+            .iter()
+            .filter_map(|arg| {
+                let value = self.build_arg_value(&arg.input_type, 0)?;
+                Some(format!("\"{}\": {}", arg.name, value))
+            })
+            .collect();
+
+        let variables = format!("{{ {} }}", vars.join(", "));
+
+        (query, variables)
+    }
+
+    fn build_arg_value(&self, type_ref: &TypeRef, depth: usize) -> Option<String> {
+        if depth > 3 {
+            return None;
+        }
+
+        match type_ref.kind.as_str() {
+            "NON_NULL" | "LIST" => {
+                if let Some(ref of_type) = type_ref.of_type {
+                    self.build_arg_value(of_type, depth)
+                } else {
+                    None
+                }
+            }
+            "SCALAR" => {
+                let name = type_ref.name.as_deref()?;
+                Some(
+                    match name {
+                        "String" | "ID" => "\"\"",
+                        "Int" => "0",
+                        "Float" => "0.0",
+                        "Boolean" => "false",
+                        _ => "\"\"",
+                    }
+                    .to_string(),
+                )
+            }
+            "ENUM" => {
+                let name = type_ref.name.as_deref()?;
+                if let Some(enum_type) = self.schema.get_type(name) {
+                    if let Some(values) = &enum_type.enum_values {
+                        if let Some(first) = values.first() {
+                            return Some(format!("\"{}\"", first.name));
+                        }
+                    }
+                }
+                None
+            }
+            "INPUT_OBJECT" => Some("{}".to_string()),
+            _ => None,
+        }
+    }
+
+    fn build_field_selection(
+        &self,
+        type_ref: &TypeRef,
+        depth: usize,
+        visited: &mut HashSet<String>,
+    ) -> String {
+        if depth > 2 {
+            return String::new();
+        }
+
+        let base_name = match type_ref.get_base_type_name() {
+            Some(name) => name,
+            None => return String::new(),
+        };
+
+        let scalar_types = ["String", "Int", "Float", "Boolean", "ID"];
+        if scalar_types.contains(&base_name) {
+            return String::new();
+        }
+
+        if let Some(t) = self.schema.get_type(base_name) {
+            if t.kind == "ENUM" || t.kind == "SCALAR" {
+                return String::new();
+            }
+        }
+
+        if visited.contains(base_name) {
+            return String::new();
+        }
+        visited.insert(base_name.to_string());
+
+        let object_type = match self.schema.get_type(base_name) {
+            Some(t) if t.kind == "OBJECT" || t.kind == "INTERFACE" => t,
+            _ => {
+                visited.remove(base_name);
+                return String::new();
+            }
+        };
+
+        let fields = match &object_type.fields {
+            Some(f) => f,
+            None => {
+                visited.remove(base_name);
+                return String::new();
+            }
+        };
+
+        let field_strs: Vec<String> = fields
+            .iter()
+            .filter(|f| !f.name.starts_with("__"))
+            .take(10)
+            .map(|f| {
+                let sub = self.build_field_selection(&f.field_type, depth + 1, visited);
+                if sub.is_empty() {
+                    f.name.clone()
+                } else {
+                    format!("{} {}", f.name, sub)
+                }
+            })
+            .collect();
+
+        visited.remove(base_name);
+
+        if field_strs.is_empty() {
+            String::new()
+        } else {
+            format!("{{ {} }}", field_strs.join(" "))
+        }
+    }
+}
+
+/// `query_user` for the `user` query
+fn function_name(operation: &str, field: &Field) -> String {
+    format!("{}_{}", operation, field.name)
+}
+
+/// Single-quote a value for bash
+pub(super) fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\"'\"'"))
+}
@@ -0,0 +1,7 @@
+/// Operations written by an exporter, per root
+#[derive(Default)]
+pub struct ExportStats {
+    pub queries: usize,
+    pub mutations: usize,
+    pub subscriptions: usize,
+}
@@ -0,0 +1,332 @@
+use crate::http::{GraphQLResponse, HttpClient};
+use crate::schema::fetch_schema;
+use anyhow::Result;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Every subgraph answers this; gateways usually don't
+const SERVICE_QUERY: &str = "query { _service { sdl } }";
+/// Only subgraphs have `_entities`; an empty list resolves without touching data
+const ENTITIES_QUERY: &str = "query { _entities(representations: []) { __typename } }";
+
+/// Ports subgraphs tend to sit on next to a gateway on 4000
+pub const SUBGRAPH_PORTS: &[u16] = &[4001, 4002, 4003, 4004, 4005, 4006, 8081, 8082];
+
+/// Types federation adds to a subgraph, and the supergraph types a gateway can leak
+const FEDERATION_TYPES: &[&str] = &["_Service", "_Entity", "_Any", "FieldSet", "link__Import", "join__Graph"];
+
+/// Which side of a federated graph the target is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FederationRole {
+    /// Composes subgraphs; plans queries and forwards them
+    Gateway,
+    /// One service behind a gateway, meant to be reached only by it
+    Subgraph,
+}
+
+impl std::fmt::Display for FederationRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            FederationRole::Gateway => "gateway",
+            FederationRole::Subgraph => "subgraph",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A type other subgraphs can resolve and extend, and the fields that identify it
+#[derive(Debug, Clone, Serialize)]
+pub struct Entity {
+    pub name: String,
+    /// `@key` field sets, e.g. `id` or `sku package`
+    pub keys: Vec<String>,
+}
+
+/// A URL a subgraph might answer on
+#[derive(Debug, Clone, Serialize)]
+pub struct Subgraph {
+    pub name: Option<String>,
+    pub url: String,
+    /// Where the URL came from: `join__graph`, `port` or `name`
+    pub source: String,
+    /// Whether anything GraphQL answered there
+    pub reachable: bool,
+    pub status: Option<u16>,
+    /// Whether it handed out its SDL through `_service`
+    pub sdl_exposed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FederationReport {
+    pub url: String,
+    /// None when nothing points to federation
+    pub role: Option<FederationRole>,
+    /// What gave it away
+    pub signals: Vec<String>,
+    /// Subgraph names from the supergraph or from error extensions
+    pub subgraph_names: Vec<String>,
+    pub entities: Vec<Entity>,
+    /// The target's `_service.sdl`, if it gave it out
+    pub sdl: Option<String>,
+    /// Candidate subgraph URLs and how they answered
+    pub subgraphs: Vec<Subgraph>,
+}
+
+impl FederationReport {
+    pub fn is_federated(&self) -> bool {
+        self.role.is_some()
+    }
+}
+
+/// Tells whether an endpoint is part of an Apollo Federation graph, pulls what the
+/// composition gives away, and tries to reach the subgraphs directly
+pub struct FederationScanner {
+    client: HttpClient,
+    url: String,
+    probe: bool,
+    ports: Vec<u16>,
+    requests: usize,
+}
+
+impl FederationScanner {
+    pub fn new(client: HttpClient, url: &str) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            probe: true,
+            ports: SUBGRAPH_PORTS.to_vec(),
+            requests: 0,
+        }
+    }
+
+    /// Whether to try candidate subgraph URLs, rather than only look at the target
+    pub fn with_probe(mut self, probe: bool) -> Self {
+        self.probe = probe;
+        self
+    }
+
+    /// Ports to look for subgraphs on, on the target's host
+    pub fn with_ports(mut self, ports: Vec<u16>) -> Self {
+        self.ports = ports;
+        self
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    pub async fn run(&mut self, callback: Option<&dyn Fn(&Subgraph)>) -> Result<FederationReport> {
+        let mut report = FederationReport {
+            url: self.url.clone(),
+            role: None,
+            signals: Vec::new(),
+            subgraph_names: Vec::new(),
+            entities: Vec::new(),
+            sdl: None,
+            subgraphs: Vec::new(),
+        };
+        let mut subgraph_like = false;
+        let mut gateway_like = false;
+
+        let url = self.url.clone();
+        if let Some(response) = self.send(&url, SERVICE_QUERY).await {
+            collect_service_names(&response, &mut report.subgraph_names);
+            if let Some(sdl) = service_sdl(&response) {
+                report.signals.push(format!("_service.sdl answers ({} bytes)", sdl.len()));
+                report.sdl = Some(sdl);
+                subgraph_like = true;
+            }
+        }
+        if let Some(response) = self.send(&url, ENTITIES_QUERY).await {
+            collect_service_names(&response, &mut report.subgraph_names);
+            if response.get_data().is_some_and(|d| d.get("_entities").is_some()) {
+                report.signals.push("_entities resolves".to_string());
+                subgraph_like = true;
+            }
+        }
+
+        self.requests += 1;
+        if let Ok(schema) = fetch_schema(&self.client, &self.url).await {
+            let found: Vec<&str> = FEDERATION_TYPES
+                .iter()
+                .copied()
+                .filter(|name| schema.get_type(name).is_some())
+                .collect();
+            if !found.is_empty() {
+                report.signals.push(format!("introspection has {}", found.join(", ")));
+            }
+            if let Some(graphs) = schema.get_type("join__Graph").and_then(|t| t.enum_values.as_ref()) {
+                gateway_like = true;
+                for value in graphs {
+                    push_unique(&mut report.subgraph_names, value.name.to_lowercase());
+                }
+            } else if !found.is_empty() {
+                subgraph_like = true;
+            }
+            // Without SDL, the `_Entity` union still names the entities
+            if report.sdl.is_none() {
+                if let Some(types) = schema.get_type("_Entity").and_then(|t| t.possible_types.as_ref()) {
+                    for name in types.iter().filter_map(|t| t.name.clone()) {
+                        report.entities.push(Entity { name, keys: Vec::new() });
+                    }
+                }
+            }
+        }
+
+        let mut candidates = Vec::new();
+        if let Some(sdl) = &report.sdl {
+            if sdl.contains("specs.apollo.dev/federation/v2") {
+                report.signals.push("federation v2 @link".to_string());
+            }
+            // `ACCOUNTS @join__graph(name: "accounts", url: "http://accounts:4001/graphql")`
+            let join_graph =
+                Regex::new(r#"@join__graph\(\s*name:\s*"([^"]+)"\s*,?\s*url:\s*"([^"]*)"\s*\)"#).unwrap();
+            for cap in join_graph.captures_iter(sdl) {
+                gateway_like = true;
+                push_unique(&mut report.subgraph_names, cap[1].to_string());
+                if !cap[2].is_empty() {
+                    candidates.push((Some(cap[1].to_string()), cap[2].to_string(), "join__graph"));
+                }
+            }
+            merge_entities(&mut report.entities, sdl_entities(sdl));
+        }
+        if !report.subgraph_names.is_empty() && !gateway_like && !subgraph_like {
+            report.signals.push("errors name the subgraph that failed".to_string());
+            gateway_like = true;
+        }
+        report.role = if gateway_like {
+            Some(FederationRole::Gateway)
+        } else if subgraph_like {
+            Some(FederationRole::Subgraph)
+        } else {
+            None
+        };
+
+        if !self.probe || report.role.is_none() {
+            return Ok(report);
+        }
+
+        candidates.extend(self.candidate_urls(&report.subgraph_names));
+        let mut seen = vec![self.url.trim_end_matches('/').to_string()];
+        for (name, url, source) in candidates {
+            if seen.contains(&url.trim_end_matches('/').to_string()) {
+                continue;
+            }
+            seen.push(url.trim_end_matches('/').to_string());
+
+            let response = self.send(&url, SERVICE_QUERY).await;
+            let sdl = response.as_ref().and_then(service_sdl);
+            // The target again, behind a catch-all route
+            if sdl.is_some() && sdl == report.sdl {
+                continue;
+            }
+            let subgraph = Subgraph {
+                name,
+                url,
+                source: source.to_string(),
+                reachable: response.as_ref().is_some_and(|r| r.has_data() || r.has_errors()),
+                status: response.as_ref().map(|r| r.status),
+                sdl_exposed: sdl.is_some(),
+            };
+            if let Some(sdl) = &sdl {
+                merge_entities(&mut report.entities, sdl_entities(sdl));
+            }
+            if let Some(cb) = callback {
+                cb(&subgraph);
+            }
+            report.subgraphs.push(subgraph);
+        }
+
+        Ok(report)
+    }
+
+    /// Subgraph guesses around the target: other ports on its host, then paths and
+    /// hosts named after each known subgraph
+    fn candidate_urls(&self, names: &[String]) -> Vec<(Option<String>, String, &'static str)> {
+        let target = match url::Url::parse(&self.url) {
+            Ok(url) => url,
+            Err(_) => return Vec::new(),
+        };
+        let host = match target.host_str() {
+            Some(host) => host.to_string(),
+            None => return Vec::new(),
+        };
+        let scheme = target.scheme();
+        let origin = target.origin().ascii_serialization();
+
+        let mut candidates = Vec::new();
+        for port in &self.ports {
+            if target.port_or_known_default() == Some(*port) {
+                continue;
+            }
+            candidates.push((None, format!("{}://{}:{}/graphql", scheme, host, port), "port"));
+        }
+        for name in names {
+            let name = name.to_lowercase();
+            candidates.push((Some(name.clone()), format!("{}/{}/graphql", origin, name), "name"));
+            candidates.push((Some(name.clone()), format!("{}/{}", origin, name), "name"));
+            candidates.push((Some(name.clone()), format!("{}://{}.{}/graphql", scheme, name, host), "name"));
+        }
+        candidates
+    }
+
+    async fn send(&mut self, url: &str, query: &str) -> Option<GraphQLResponse> {
+        self.requests += 1;
+        self.client.post_graphql(url, query, None, Some("federation")).await.ok()
+    }
+}
+
+fn service_sdl(response: &GraphQLResponse) -> Option<String> {
+    response
+        .get_data()?
+        .get("_service")?
+        .get("sdl")?
+        .as_str()
+        .filter(|sdl| !sdl.trim().is_empty())
+        .map(String::from)
+}
+
+/// Gateways tag errors from a subgraph with its name (`extensions.serviceName`)
+fn collect_service_names(response: &GraphQLResponse, names: &mut Vec<String>) {
+    for error in response.get_errors().and_then(|e| e.as_array()).into_iter().flatten() {
+        if let Some(Value::String(name)) = error.get("extensions").and_then(|e| e.get("serviceName")) {
+            push_unique(names, name.clone());
+        }
+    }
+}
+
+/// Types with `@key` (or a supergraph's `@join__type(key:)`) and their field sets
+pub fn sdl_entities(sdl: &str) -> Vec<Entity> {
+    // `type User @key(fields: "id")`, `extend type Product @key(fields: "upc") {`
+    let type_header = Regex::new(r"(?m)^\s*(?:extend\s+)?(?:type|interface)\s+(\w+)([^{]*)").unwrap();
+    // `@key(fields: "id")` in a subgraph, `@join__type(graph: ACCOUNTS, key: "id")` in a supergraph
+    let key_directive = Regex::new(r#"@(?:key\(\s*fields:|join__type\([^)]*?key:)\s*"([^"]*)""#).unwrap();
+
+    let mut entities: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for cap in type_header.captures_iter(sdl) {
+        for key in key_directive.captures_iter(&cap[2]) {
+            let keys = entities.entry(cap[1].to_string()).or_default();
+            push_unique(keys, key[1].to_string());
+        }
+    }
+    entities.into_iter().map(|(name, keys)| Entity { name, keys }).collect()
+}
+
+fn merge_entities(entities: &mut Vec<Entity>, found: Vec<Entity>) {
+    for entity in found {
+        match entities.iter_mut().find(|e| e.name == entity.name) {
+            Some(existing) => entity.keys.into_iter().for_each(|k| push_unique(&mut existing.keys, k)),
+            None => entities.push(entity),
+        }
+    }
+}
+
+fn push_unique(items: &mut Vec<String>, item: String) {
+    if !items.contains(&item) {
+        items.push(item);
+    }
+}
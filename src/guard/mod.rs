@@ -0,0 +1,138 @@
+use crate::export::{camel_words, OperationFilter};
+use crate::schema::Field;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+const DELETE_WORDS: &[&str] = &[
+    "delete", "remove", "destroy", "purge", "drop", "erase", "wipe", "truncate", "clear", "reset", "revoke",
+    "cancel", "disable", "deactivate", "archive", "unlink", "detach", "unassign", "unsubscribe", "ban", "block",
+    "terminate", "kill", "expire", "logout", "signout", "invalidate", "rotate",
+];
+const UPDATE_WORDS: &[&str] = &[
+    "update", "edit", "set", "change", "modify", "patch", "replace", "rename", "move", "upsert", "assign",
+    "toggle", "enable", "activate", "grant", "merge", "transfer", "approve", "reject", "publish", "unpublish",
+    "restore", "mark", "link", "attach", "save", "sync", "import", "promote", "demote", "lock", "unlock",
+    "confirm", "complete", "close", "reopen", "increment", "decrement", "pay", "charge", "refund", "send",
+];
+const CREATE_WORDS: &[&str] = &[
+    "create", "add", "insert", "new", "register", "signup", "upload", "submit", "post", "invite", "generate",
+    "clone", "duplicate", "copy", "open", "start", "begin", "request", "subscribe", "follow", "like", "comment",
+    "reply", "report",
+];
+const READ_WORDS: &[&str] = &[
+    "get", "list", "fetch", "find", "search", "query", "read", "check", "validate", "verify", "preview",
+    "calculate", "estimate", "lookup", "resolve", "login", "signin", "authenticate", "refresh", "echo", "ping",
+];
+
+/// What a mutation most likely does to the data behind it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum MutationClass {
+    /// Computes or checks something without writing (`validateCoupon`, `login`)
+    ReadLike,
+    Create,
+    Update,
+    Delete,
+}
+
+impl MutationClass {
+    /// Whether running it can change or lose data that was already there
+    pub fn is_destructive(&self) -> bool {
+        matches!(self, MutationClass::Update | MutationClass::Delete)
+    }
+}
+
+impl std::fmt::Display for MutationClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            MutationClass::ReadLike => "read-like",
+            MutationClass::Create => "create",
+            MutationClass::Update => "update",
+            MutationClass::Delete => "delete",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Class of a mutation from the verbs in its name (`deleteUser`, `user_update`), the
+/// riskiest one winning (`getOrCreateUser` creates). Names without a known verb go by
+/// their arguments: an id alone reads as a delete, an id with more as an update, no id
+/// as a create. Anything left over counts as an update, so unknowns stay guarded.
+pub fn classify_mutation(field: &Field) -> MutationClass {
+    let by_name = camel_words(&field.name)
+        .iter()
+        .filter_map(|word| {
+            let word = word.to_lowercase();
+            if DELETE_WORDS.contains(&word.as_str()) {
+                Some(MutationClass::Delete)
+            } else if UPDATE_WORDS.contains(&word.as_str()) {
+                Some(MutationClass::Update)
+            } else if CREATE_WORDS.contains(&word.as_str()) {
+                Some(MutationClass::Create)
+            } else if READ_WORDS.contains(&word.as_str()) {
+                Some(MutationClass::ReadLike)
+            } else {
+                None
+            }
+        })
+        .max();
+    if let Some(class) = by_name {
+        return class;
+    }
+
+    let is_id = |name: &str| {
+        let lower = name.to_lowercase();
+        lower == "id" || lower == "ids" || lower.ends_with("_id") || name.ends_with("Id") || name.ends_with("Ids")
+    };
+    let has_id = field.args.iter().any(|a| is_id(&a.name));
+    let has_other = field.args.iter().any(|a| !is_id(&a.name));
+    match (has_id, has_other) {
+        (true, false) => MutationClass::Delete,
+        (false, true) => MutationClass::Create,
+        _ => MutationClass::Update,
+    }
+}
+
+/// Decides which mutations a module may run. Read-like and create mutations go through;
+/// updates and deletes need `unsafe` or a matching allowlist entry.
+#[derive(Debug, Clone, Default)]
+pub struct MutationGuard {
+    allow_unsafe: bool,
+    allowlist: Vec<OperationFilter>,
+}
+
+impl MutationGuard {
+    pub fn new(allow_unsafe: bool) -> Self {
+        Self {
+            allow_unsafe,
+            allowlist: Vec::new(),
+        }
+    }
+
+    /// Mutations to run whatever their class: one name, glob or `re:` regex per line,
+    /// `#` comments allowed
+    pub fn with_allowlist(mut self, path: &Path) -> Result<Self> {
+        let content =
+            std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            self.allowlist.push(
+                OperationFilter::new(Some(line), &[])
+                    .with_context(|| format!("{}: bad allowlist entry '{}'", path.display(), line))?,
+            );
+        }
+        Ok(self)
+    }
+
+    /// The mutation's class, and whether it may run
+    pub fn check(&self, field: &Field) -> (MutationClass, bool) {
+        let class = classify_mutation(field);
+        let allowed = !class.is_destructive()
+            || self.allow_unsafe
+            || self.allowlist.iter().any(|f| f.allows("mutation", &field.name));
+        (class, allowed)
+    }
+}
@@ -0,0 +1,84 @@
+use super::evasion::random_below;
+use anyhow::{bail, Context, Result};
+use bytes::Bytes;
+use http_body::{Body, Frame, SizeHint};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::task::Poll;
+
+/// How request bodies are cut up for `Transfer-Encoding: chunked`: chunks of one size,
+/// or of a size picked at random for every chunk
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chunking {
+    min: usize,
+    max: usize,
+}
+
+impl Chunking {
+    /// Chunks between `min` and `max` bytes
+    pub fn new(min: usize, max: usize) -> Self {
+        let min = min.max(1);
+        Self { min, max: max.max(min) }
+    }
+
+    /// `16` for chunks of 16 bytes or `1-32` for a size picked at random each time
+    pub fn parse(spec: &str) -> Result<Self> {
+        let parse = |size: &str| -> Result<usize> {
+            let size = size
+                .trim()
+                .parse::<usize>()
+                .with_context(|| format!("--chunked {} isn't a size in bytes", spec))?;
+            if size == 0 {
+                bail!("--chunked {} has an empty chunk in it", spec);
+            }
+            Ok(size)
+        };
+        let (min, max) = match spec.split_once('-') {
+            Some((min, max)) => (parse(min)?, parse(max)?),
+            None => (parse(spec)?, parse(spec)?),
+        };
+        if max < min {
+            bail!("--chunked {} has its range backwards", spec);
+        }
+        Ok(Self { min, max })
+    }
+
+    /// `body` as a stream of chunks. Its length isn't known up front, so it goes out
+    /// chunked on HTTP/1 with no Content-Length.
+    pub(super) fn body(&self, body: Vec<u8>) -> reqwest::Body {
+        let mut body = Bytes::from(body);
+        let mut chunks = VecDeque::new();
+        while !body.is_empty() {
+            let size = self.min + random_below(self.max - self.min + 1);
+            chunks.push_back(body.split_to(size.min(body.len())));
+        }
+        reqwest::Body::wrap(ChunkedBody { chunks })
+    }
+}
+
+/// Hands its chunks to hyper one frame at a time; each frame is written as one chunk
+struct ChunkedBody {
+    chunks: VecDeque<Bytes>,
+}
+
+impl Body for ChunkedBody {
+    type Data = Bytes;
+    type Error = Infallible;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        _cx: &mut std::task::Context<'_>,
+    ) -> Poll<Option<Result<Frame<Bytes>, Infallible>>> {
+        Poll::Ready(self.chunks.pop_front().map(|chunk| Ok(Frame::data(chunk))))
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.chunks.is_empty()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        // Unknown on purpose: an exact hint would bring the Content-Length back
+        SizeHint::default()
+    }
+}
@@ -1,19 +1,66 @@
 use anyhow::{Context, Result};
 use reqwest::{Client, Proxy, Response};
 use serde_json::{json, Value};
+use super::evasion::random_below;
+use super::log::{log_at, truncate_body, verbosity};
+use super::obfuscate::{obfuscate, Obfuscation};
+use super::pool::{count_request, pool};
+use super::{shuffle, Chunking, Evasion, Stealth};
+use crate::schema::Schema;
 use std::collections::HashMap;
-use std::time::Duration;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 const DEFAULT_TIMEOUT: u64 = 30;
-/// const USER_AGENT: &str = concat!("gqlmap/", env!("CARGO_PKG_VERSION"));
-
-const USER_AGENT: &str = concat!("Mozilla/5.0 (Linux; Android 16) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/143.0.7499.194 Mobile Safari/537.36");
+// const USER_AGENT: &str = concat!("gqlmap/", env!("CARGO_PKG_VERSION"));
+
+const USER_AGENT: &str = "Mozilla/5.0 (Linux; Android 16) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/143.0.7499.194 Mobile Safari/537.36";
+
+/// Accept values browsers and GraphQL clients send, rotated with --random-headers
+const ACCEPT_VALUES: &[&str] = &[
+    "*/*",
+    "application/json",
+    "application/json, text/plain, */*",
+    "application/graphql-response+json, application/json",
+    "application/graphql-response+json, application/graphql+json, application/json",
+    "application/json, multipart/mixed",
+];
+
+const ACCEPT_LANGUAGE_VALUES: &[&str] = &[
+    "en-US,en;q=0.9",
+    "en-GB,en;q=0.9",
+    "en-US,en;q=0.8,de;q=0.6",
+    "de-DE,de;q=0.9,en;q=0.8",
+    "fr-FR,fr;q=0.9,en-US;q=0.8,en;q=0.7",
+    "es-ES,es;q=0.9,en;q=0.8",
+    "nl-NL,nl;q=0.9,en-US;q=0.8,en;q=0.7",
+];
 
 #[derive(Clone)]
 pub struct HttpClient {
     client: Client,
+    /// The same client writing Title-Case header names, set with --random-headers
+    title_case: Option<Client>,
+    proxy: Option<String>,
     headers: HashMap<String, String>,
     debug_mode: bool,
+    /// Every query sent, when recording is on (shared between clones)
+    query_log: Option<Arc<Mutex<Vec<String>>>>,
+    evasion: Evasion,
+    stealth: Stealth,
+    random_headers: bool,
+    obfuscate: bool,
+    chunking: Option<Chunking>,
+    /// Requests sent so far (shared between clones)
+    sent: Arc<AtomicUsize>,
+    /// Requests so far that got no reply or an overload status (shared between clones)
+    failed: Arc<AtomicUsize>,
+    /// Longest wait for a response since the last `take_slowest`, body included where
+    /// it's read, in microseconds (shared between clones)
+    slowest: Arc<AtomicU64>,
+    /// Introspected schemas by URL (shared between clones)
+    schemas: Arc<Mutex<HashMap<String, Arc<Schema>>>>,
 }
 
 impl HttpClient {
@@ -22,32 +69,207 @@ impl HttpClient {
         headers: HashMap<String, String>,
         debug_mode: bool,
     ) -> Result<Self> {
-        let mut builder = Client::builder()
-            .timeout(Duration::from_secs(DEFAULT_TIMEOUT))
-            .danger_accept_invalid_certs(true)
-            .user_agent(USER_AGENT);
-
-        if let Some(proxy_url) = proxy {
-            let proxy = if proxy_url.starts_with("socks") {
-                Proxy::all(proxy_url).context("Invalid SOCKS proxy URL")?
-            } else {
-                Proxy::all(proxy_url).context("Invalid HTTP proxy URL")?
-            };
-            builder = builder.proxy(proxy);
-        }
-
-        let client = builder.build().context("Failed to build HTTP client")?;
-
         Ok(Self {
-            client,
+            client: build_client(proxy, false, true)?,
+            title_case: None,
+            proxy: proxy.map(String::from),
             headers,
             debug_mode,
+            query_log: None,
+            evasion: Evasion::default(),
+            stealth: Stealth::default(),
+            random_headers: false,
+            obfuscate: false,
+            chunking: None,
+            sent: Arc::new(AtomicUsize::new(0)),
+            failed: Arc::new(AtomicUsize::new(0)),
+            slowest: Arc::new(AtomicU64::new(0)),
+            schemas: Arc::new(Mutex::new(HashMap::new())),
         })
     }
 
+    /// Record every query sent through this client, e.g. for schema coverage
+    pub fn with_query_log(mut self) -> Self {
+        self.query_log = Some(Arc::new(Mutex::new(Vec::new())));
+        self
+    }
+
+    /// Rewrite every query sent as a single document with these evasion profiles
+    pub fn with_evasion(mut self, evasion: Evasion) -> Self {
+        self.evasion = evasion;
+        self
+    }
+
+    /// Space requests out the way `stealth` says
+    pub fn with_stealth(mut self, stealth: Stealth) -> Self {
+        self.stealth = stealth;
+        self
+    }
+
+    /// Vary what the requests look like: Accept and Accept-Language picked per request,
+    /// headers in a shuffled order, and header names lowercase or Title-Case (HTTP/1)
+    pub fn with_random_headers(mut self) -> Result<Self> {
+        self.title_case = Some(build_client(self.proxy.as_deref(), true, true)?);
+        self.random_headers = true;
+        Ok(self)
+    }
+
+    /// The same client (sharing its counters and caches), handing redirects back instead
+    /// of following them
+    pub fn without_redirects(&self) -> Result<Self> {
+        let mut client = self.clone();
+        client.client = build_client(self.proxy.as_deref(), false, false)?;
+        if self.title_case.is_some() {
+            client.title_case = Some(build_client(self.proxy.as_deref(), true, false)?);
+        }
+        Ok(client)
+    }
+
+    /// Give every query sent as a single document random aliases, a random operation
+    /// name and an inline fragment around its fields. Responses come back with the
+    /// original aliases.
+    pub fn with_obfuscation(mut self) -> Self {
+        self.obfuscate = true;
+        self
+    }
+
+    /// Send GraphQL request bodies with `Transfer-Encoding: chunked`, cut up the way
+    /// `chunking` says, instead of with a Content-Length
+    pub fn with_chunking(mut self, chunking: Chunking) -> Self {
+        self.chunking = Some(chunking);
+        self
+    }
+
+    /// Queries recorded since the last call
+    pub fn take_logged_queries(&self) -> Vec<String> {
+        match &self.query_log {
+            Some(log) => std::mem::take(&mut *log.lock().unwrap()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Requests this client and its clones have sent
+    pub fn requests_sent(&self) -> usize {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// Requests this client and its clones sent that got no reply, or a 429, 502, 503
+    /// or 504: the server or something in front of it not coping
+    pub fn requests_failed(&self) -> usize {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// The longest any request of this client and its clones waited for a response
+    /// (or a failure) since the last call
+    pub fn take_slowest(&self) -> Option<Duration> {
+        match self.slowest.swap(0, Ordering::Relaxed) {
+            0 => None,
+            micros => Some(Duration::from_micros(micros)),
+        }
+    }
+
+    /// Send `req` once stealth allows, counting it, and log it at `-vv` and up
+    async fn send(&self, req: reqwest::RequestBuilder) -> reqwest::Result<Response> {
+        self.stealth.wait().await;
+        self.sent.fetch_add(1, Ordering::Relaxed);
+        count_request();
+
+        let (client, request) = req.build_split();
+        let request = request?;
+        if verbosity() >= 2 {
+            log_at(2, &format!("> {} {}", request.method(), request.url()));
+            let body = match request.body() {
+                Some(body) => body.as_bytes().map(truncate_body).unwrap_or_else(|| "(chunked)".to_string()),
+                None => String::new(),
+            };
+            if !body.is_empty() {
+                log_at(3, &format!("> {}", body));
+            }
+        }
+
+        let started = Instant::now();
+        let mut response = client.execute(request).await;
+        let elapsed = started.elapsed();
+        self.slowest.fetch_max(elapsed.as_micros() as u64, Ordering::Relaxed);
+        match &mut response {
+            Ok(response) => {
+                log_at(2, &format!("< {} in {}ms", response.status().as_u16(), elapsed.as_millis()));
+                if is_overloaded(response.status().as_u16()) {
+                    self.failed.fetch_add(1, Ordering::Relaxed);
+                }
+                // For GraphQLResponse::elapsed, which also counts reading the body
+                response.extensions_mut().insert(Sent {
+                    at: started,
+                    slowest: self.slowest.clone(),
+                });
+            }
+            Err(e) => {
+                self.failed.fetch_add(1, Ordering::Relaxed);
+                log_at(2, &format!("< failed after {}ms: {}", elapsed.as_millis(), e))
+            }
+        }
+        response
+    }
+
+    pub(crate) fn cached_schema(&self, url: &str) -> Option<Arc<Schema>> {
+        self.schemas.lock().unwrap().get(url).cloned()
+    }
+
+    pub(crate) fn cache_schema(&self, url: &str, schema: Arc<Schema>) {
+        self.schemas.lock().unwrap().insert(url.to_string(), schema);
+    }
+
+    fn record(&self, query: &str) {
+        if let Some(log) = &self.query_log {
+            log.lock().unwrap().push(query.to_string());
+        }
+    }
+
+    /// The query as it goes out: obfuscated, then rewritten for evasion. The operation
+    /// `operation_name` names keeps its name.
+    fn outgoing(&self, query: &str, operation_name: Option<&str>) -> Obfuscation {
+        let mut outgoing = if self.obfuscate { obfuscate(query, operation_name) } else { Obfuscation::plain(query) };
+        outgoing.query = self.evasion.apply(&outgoing.query);
+        outgoing
+    }
+
+    /// The client for the next request: either one, when header casing varies
+    fn http(&self) -> &Client {
+        match &self.title_case {
+            Some(title_case) if random_below(2) == 0 => title_case,
+            _ => &self.client,
+        }
+    }
+
+    /// Attach `body` to `req`, in chunks when chunking is on
+    fn with_body(&self, req: reqwest::RequestBuilder, body: Vec<u8>) -> reqwest::RequestBuilder {
+        match &self.chunking {
+            Some(chunking) => req.body(chunking.body(body)),
+            None => req.body(body),
+        }
+    }
+
     fn apply_headers(&self, mut req: reqwest::RequestBuilder, test_name: Option<&str>) -> reqwest::RequestBuilder {
-        for (key, value) in &self.headers {
-            req = req.header(key, value);
+        if !self.random_headers {
+            for (key, value) in &self.headers {
+                req = req.header(key, value);
+            }
+        } else {
+            let mut headers: Vec<(&str, &str)> = self.headers.iter().map(|(k, v)| (k.as_str(), v.as_str())).collect();
+            let has = |name: &str| self.headers.keys().any(|k| k.eq_ignore_ascii_case(name));
+            if !has("Accept") {
+                headers.push(("Accept", ACCEPT_VALUES[random_below(ACCEPT_VALUES.len())]));
+            }
+            if !has("Accept-Language") {
+                headers.push((
+                    "Accept-Language",
+                    ACCEPT_LANGUAGE_VALUES[random_below(ACCEPT_LANGUAGE_VALUES.len())],
+                ));
+            }
+            shuffle(&mut headers);
+            for (key, value) in headers {
+                req = req.header(key, value);
+            }
         }
 
         if self.debug_mode {
@@ -66,7 +288,22 @@ impl HttpClient {
         variables: Option<Value>,
         test_name: Option<&str>,
     ) -> Result<GraphQLResponse> {
-        let body = match variables {
+        self.post_graphql_operation(url, query, variables, None, test_name).await
+    }
+
+    /// POST a document, naming the operation in it to run when there are several
+    pub async fn post_graphql_operation(
+        &self,
+        url: &str,
+        query: &str,
+        variables: Option<Value>,
+        operation_name: Option<&str>,
+        test_name: Option<&str>,
+    ) -> Result<GraphQLResponse> {
+        self.record(query);
+        let outgoing = self.outgoing(query, operation_name);
+        let query = outgoing.query.as_str();
+        let mut body = match variables {
             Some(vars) => json!({
                 "query": query,
                 "variables": vars
@@ -75,16 +312,21 @@ impl HttpClient {
                 "query": query
             }),
         };
+        if let Some(name) = operation_name {
+            body["operationName"] = json!(name);
+        }
 
-        let req = self.client
+        let req = self.http()
             .post(url)
-            .header("Content-Type", "application/json")
-            .json(&body);
+            .header("Content-Type", "application/json");
+        let req = self.with_body(req, serde_json::to_vec(&body)?);
 
         let req = self.apply_headers(req, test_name);
-        let response = req.send().await.context("Failed to send POST request")?;
+        let response = self.send(req).await.context("Failed to send POST request")?;
 
-        GraphQLResponse::from_response(response, url, "POST", &body).await
+        let mut response = GraphQLResponse::from_response(response, url, "POST", &body).await?;
+        outgoing.restore(&mut response.body);
+        Ok(response)
     }
 
     pub async fn post_graphql_batch(
@@ -93,13 +335,16 @@ impl HttpClient {
         queries: Vec<Value>,
         test_name: Option<&str>,
     ) -> Result<GraphQLResponse> {
-        let req = self.client
+        for query in queries.iter().filter_map(|q| q.get("query").and_then(|v| v.as_str())) {
+            self.record(query);
+        }
+        let req = self.http()
             .post(url)
-            .header("Content-Type", "application/json")
-            .json(&queries);
+            .header("Content-Type", "application/json");
+        let req = self.with_body(req, serde_json::to_vec(&queries)?);
 
         let req = self.apply_headers(req, test_name);
-        let response = req.send().await.context("Failed to send batch POST request")?;
+        let response = self.send(req).await.context("Failed to send batch POST request")?;
 
         GraphQLResponse::from_response(response, url, "POST", &json!(queries)).await
     }
@@ -110,17 +355,65 @@ impl HttpClient {
         query: &str,
         test_name: Option<&str>,
     ) -> Result<GraphQLResponse> {
-        let params = [("query", query)];
+        self.record(query);
+        let outgoing = self.outgoing(query, None);
+        let query = outgoing.query.as_str();
+        let params = url::form_urlencoded::Serializer::new(String::new())
+            .append_pair("query", query)
+            .finish();
+
+        let req = self.http()
+            .post(url)
+            .header("Content-Type", "application/x-www-form-urlencoded");
+        let req = self.with_body(req, params.into_bytes());
+
+        let req = self.apply_headers(req, test_name);
+        let response = self.send(req).await.context("Failed to send URL-encoded POST request")?;
 
-        let req = self.client
+        let mut response = GraphQLResponse::from_response(response, url, "POST", &json!({"query": query})).await?;
+        outgoing.restore(&mut response.body);
+        Ok(response)
+    }
+
+    /// POST the bare document as `application/graphql`, as older express-graphql clients do
+    pub async fn post_graphql_document(
+        &self,
+        url: &str,
+        query: &str,
+        test_name: Option<&str>,
+    ) -> Result<GraphQLResponse> {
+        self.record(query);
+        let outgoing = self.outgoing(query, None);
+        let query = outgoing.query.as_str();
+        let req = self.http()
             .post(url)
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .form(&params);
+            .header("Content-Type", "application/graphql");
+        let req = self.with_body(req, query.as_bytes().to_vec());
 
         let req = self.apply_headers(req, test_name);
-        let response = req.send().await.context("Failed to send URL-encoded POST request")?;
+        let response = self.send(req).await.context("Failed to send application/graphql POST request")?;
 
-        GraphQLResponse::from_response(response, url, "POST", &json!({"query": query})).await
+        let mut response = GraphQLResponse::from_response(response, url, "POST", &json!({"query": query})).await?;
+        outgoing.restore(&mut response.body);
+        Ok(response)
+    }
+
+    /// POST any JSON body, e.g. to a callback server's API
+    pub async fn post_json(
+        &self,
+        url: &str,
+        body: &Value,
+        test_name: Option<&str>,
+    ) -> Result<GraphQLResponse> {
+        let req = self.http()
+            .post(url)
+            .header("Content-Type", "application/json")
+            .json(body);
+
+        let req = self.apply_headers(req, test_name);
+        let response = self.send(req).await.context("Failed to send JSON POST request")?;
+
+        GraphQLResponse::from_response(response, url, "POST", body).await
     }
 
     pub async fn get_graphql(
@@ -129,14 +422,63 @@ impl HttpClient {
         query: &str,
         test_name: Option<&str>,
     ) -> Result<GraphQLResponse> {
-        let req = self.client
+        self.record(query);
+        let outgoing = self.outgoing(query, None);
+        let query = outgoing.query.as_str();
+        let req = self.http()
             .get(url)
             .query(&[("query", query)]);
 
         let req = self.apply_headers(req, test_name);
-        let response = req.send().await.context("Failed to send GET request")?;
+        let response = self.send(req).await.context("Failed to send GET request")?;
 
-        GraphQLResponse::from_response(response, url, "GET", &json!({"query": query})).await
+        let mut response = GraphQLResponse::from_response(response, url, "GET", &json!({"query": query})).await?;
+        outgoing.restore(&mut response.body);
+        Ok(response)
+    }
+
+    /// GET with any query-string parameters, e.g. an APQ `extensions` with no query
+    pub async fn get_params(
+        &self,
+        url: &str,
+        params: &[(&str, String)],
+        test_name: Option<&str>,
+    ) -> Result<GraphQLResponse> {
+        let full_url = if params.is_empty() {
+            url::Url::parse(url)
+        } else {
+            url::Url::parse_with_params(url, params)
+        }
+        .context("Invalid URL")?;
+        let req = self.http().get(full_url.clone());
+
+        let req = self.apply_headers(req, test_name);
+        let response = self.send(req).await.context("Failed to send GET request")?;
+
+        GraphQLResponse::from_response(response, full_url.as_str(), "GET", &Value::Null).await
+    }
+
+    /// Ask for a WebSocket upgrade offering `protocols`, with the usual headers and `key`
+    /// as `Sec-WebSocket-Key`. The response is the raw handshake reply; see
+    /// [`GraphQLSocket`](super::GraphQLSocket).
+    pub async fn websocket_upgrade(
+        &self,
+        url: &str,
+        protocols: &[&str],
+        key: &str,
+        test_name: Option<&str>,
+    ) -> Result<Response> {
+        let req = self.http()
+            .get(url)
+            .version(reqwest::Version::HTTP_11)
+            .header("Connection", "Upgrade")
+            .header("Upgrade", "websocket")
+            .header("Sec-WebSocket-Version", "13")
+            .header("Sec-WebSocket-Key", key)
+            .header("Sec-WebSocket-Protocol", protocols.join(", "));
+
+        let req = self.apply_headers(req, test_name);
+        self.send(req).await.context("Failed to send WebSocket upgrade request")
     }
 
     pub async fn get_html(
@@ -144,37 +486,87 @@ impl HttpClient {
         url: &str,
         test_name: Option<&str>,
     ) -> Result<HtmlResponse> {
-        let req = self.client
+        let req = self.http()
             .get(url)
             .header("Accept", "text/html");
 
         let req = self.apply_headers(req, test_name);
-        let response = req.send().await.context("Failed to send HTML GET request")?;
+        let response = self.send(req).await.context("Failed to send HTML GET request")?;
 
         let status = response.status().as_u16();
+        let headers = header_map(&response);
         let body = response.text().await.unwrap_or_default();
 
         Ok(HtmlResponse {
             status,
             body,
             url: url.to_string(),
+            headers,
         })
     }
 }
 
+fn build_client(proxy: Option<&str>, title_case: bool, follow_redirects: bool) -> Result<Client> {
+    let mut builder = Client::builder()
+        .timeout(Duration::from_secs(DEFAULT_TIMEOUT))
+        .danger_accept_invalid_certs(true)
+        .user_agent(USER_AGENT);
+    if !follow_redirects {
+        builder = builder.redirect(reqwest::redirect::Policy::none());
+    }
+    builder = pool().configure(builder);
+
+    if let Some(proxy_url) = proxy {
+        let proxy = if proxy_url.starts_with("socks") {
+            Proxy::all(proxy_url).context("Invalid SOCKS proxy URL")?
+        } else {
+            Proxy::all(proxy_url).context("Invalid HTTP proxy URL")?
+        };
+        builder = builder.proxy(proxy);
+    }
+    if title_case {
+        builder = builder.http1_title_case_headers();
+    }
+
+    builder.build().context("Failed to build HTTP client")
+}
+
 #[derive(Debug, Clone)]
 pub struct GraphQLResponse {
     pub status: u16,
     pub body: Value,
     pub curl_command: String,
+    /// Response headers, names lowercased
+    pub headers: HashMap<String, String>,
+    /// Raw body, for replies that aren't JSON
+    pub text: String,
+    /// From sending the request to the end of the body
+    pub elapsed: Duration,
+}
+
+/// When a request went out, carried on its response, and the client's slowest time
+/// to update once the body is in
+#[derive(Clone)]
+struct Sent {
+    at: Instant,
+    slowest: Arc<AtomicU64>,
 }
 
 impl GraphQLResponse {
     async fn from_response(response: Response, url: &str, method: &str, body: &Value) -> Result<Self> {
         let status = response.status().as_u16();
-        let response_body: Value = response
-            .json()
-            .await
+        let headers = header_map(&response);
+        let sent = response.extensions().get::<Sent>().cloned();
+        let text = response.text().await.unwrap_or_default();
+        let elapsed = match sent {
+            Some(sent) => {
+                let elapsed = sent.at.elapsed();
+                sent.slowest.fetch_max(elapsed.as_micros() as u64, Ordering::Relaxed);
+                elapsed
+            }
+            None => Duration::ZERO,
+        };
+        let response_body: Value = serde_json::from_str(&text)
             .unwrap_or(json!({"error": "Failed to parse response as JSON"}));
 
         let curl_command = Self::build_curl(url, method, body);
@@ -183,6 +575,9 @@ impl GraphQLResponse {
             status,
             body: response_body,
             curl_command,
+            headers,
+            text,
+            elapsed,
         })
     }
 
@@ -238,4 +633,20 @@ pub struct HtmlResponse {
     pub status: u16,
     pub body: String,
     pub url: String,
+    /// Response headers, names lowercased
+    pub headers: HashMap<String, String>,
+}
+
+fn header_map(response: &Response) -> HashMap<String, String> {
+    response
+        .headers()
+        .iter()
+        .map(|(name, value)| (name.as_str().to_string(), value.to_str().unwrap_or_default().to_string()))
+        .collect()
+}
+
+/// Statuses of a server, or a gateway in front of it, that isn't coping: rate limited,
+/// bad gateway, unavailable, gateway timeout
+pub fn is_overloaded(status: u16) -> bool {
+    matches!(status, 429 | 502 | 503 | 504)
 }
@@ -0,0 +1,253 @@
+use anyhow::{bail, Result};
+
+/// A rewrite of a query document that means the same to a GraphQL server but not to a
+/// filter matching on its text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EvasionProfile {
+    /// Runs of spaces, tabs and commas between tokens (commas are insignificant)
+    Whitespace,
+    /// `#` comments between tokens, GraphQL's only kind
+    Comments,
+    /// Letters and digits in string literals as `\uXXXX` escapes
+    Unicode,
+    /// Every token on a line of its own, so `query` and `__schema` never sit next to
+    /// what follows them
+    Newlines,
+}
+
+impl EvasionProfile {
+    pub const ALL: &'static [EvasionProfile] = &[
+        EvasionProfile::Whitespace,
+        EvasionProfile::Comments,
+        EvasionProfile::Unicode,
+        EvasionProfile::Newlines,
+    ];
+
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "whitespace" => Ok(EvasionProfile::Whitespace),
+            "comments" => Ok(EvasionProfile::Comments),
+            "unicode" => Ok(EvasionProfile::Unicode),
+            "newlines" => Ok(EvasionProfile::Newlines),
+            other => bail!(
+                "Unknown evasion profile {} (whitespace, comments, unicode, newlines or all)",
+                other
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for EvasionProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            EvasionProfile::Whitespace => "whitespace",
+            EvasionProfile::Comments => "comments",
+            EvasionProfile::Unicode => "unicode",
+            EvasionProfile::Newlines => "newlines",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The profiles to rewrite every outgoing query with; none leaves queries as they are
+#[derive(Debug, Clone, Default)]
+pub struct Evasion {
+    profiles: Vec<EvasionProfile>,
+}
+
+impl Evasion {
+    pub fn new(profiles: Vec<EvasionProfile>) -> Self {
+        Self { profiles }
+    }
+
+    /// Profile names, `all` for every one of them
+    pub fn from_names(names: &[String]) -> Result<Self> {
+        let mut profiles = Vec::new();
+        for name in names {
+            if name.trim().eq_ignore_ascii_case("all") {
+                profiles = EvasionProfile::ALL.to_vec();
+                break;
+            }
+            let profile = EvasionProfile::parse(name)?;
+            if !profiles.contains(&profile) {
+                profiles.push(profile);
+            }
+        }
+        Ok(Self { profiles })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.profiles.is_empty()
+    }
+
+    pub fn profiles(&self) -> &[EvasionProfile] {
+        &self.profiles
+    }
+
+    /// `query` rewritten with every profile; as it is when it doesn't lex (the server
+    /// should see the same broken document a test meant to send)
+    pub fn apply(&self, query: &str) -> String {
+        if self.profiles.is_empty() {
+            return query.to_string();
+        }
+        let tokens = match split_tokens(query) {
+            Some(tokens) => tokens,
+            None => return query.to_string(),
+        };
+
+        let mut out = String::with_capacity(query.len() * 2);
+        for (index, token) in tokens.iter().enumerate() {
+            if index > 0 {
+                out.push_str(&self.separator());
+            }
+            match token {
+                RawToken::String(raw) if self.uses(EvasionProfile::Unicode) => out.push_str(&escape_string(raw)),
+                RawToken::String(raw) | RawToken::Other(raw) => out.push_str(raw),
+            }
+        }
+        out
+    }
+
+    fn uses(&self, profile: EvasionProfile) -> bool {
+        self.profiles.contains(&profile)
+    }
+
+    /// What goes between two tokens: a space at least, so names never run together
+    fn separator(&self) -> String {
+        let mut separator = String::new();
+        if self.uses(EvasionProfile::Whitespace) {
+            const PIECES: &[&str] = &[" ", "  ", "\t", ",", " ,", ", "];
+            for _ in 0..=random_below(3) {
+                separator.push_str(PIECES[random_below(PIECES.len())]);
+            }
+        }
+        if self.uses(EvasionProfile::Comments) {
+            separator.push_str(&format!(" #{}\n", crate::oob::random_label(4 + random_below(8))));
+        }
+        if self.uses(EvasionProfile::Newlines) {
+            separator.push('\n');
+            separator.push_str(&" ".repeat(random_below(4)));
+        }
+        if separator.is_empty() {
+            separator.push(' ');
+        }
+        separator
+    }
+}
+
+/// A uniformly random number below `n`
+pub(crate) fn random_below(n: usize) -> usize {
+    let mut bytes = [0u8; 8];
+    openssl::rand::rand_bytes(&mut bytes).expect("no randomness available");
+    (u64::from_le_bytes(bytes) % n.max(1) as u64) as usize
+}
+
+pub(super) enum RawToken<'a> {
+    /// A `"..."` literal, quotes and escapes as written
+    String(&'a str),
+    /// Names, numbers, punctuation and block strings, as written
+    Other(&'a str),
+}
+
+/// The document's tokens as they appear in it, comments and ignored characters
+/// dropped. None for an unterminated string.
+pub(super) fn split_tokens(query: &str) -> Option<Vec<RawToken<'_>>> {
+    let bytes = query.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        match bytes[i] {
+            b' ' | b'\t' | b'\r' | b'\n' | b',' => i += 1,
+            b'#' => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            b'"' if query[i..].starts_with("\"\"\"") => {
+                i += 3;
+                loop {
+                    if i >= bytes.len() {
+                        return None;
+                    }
+                    if query[i..].starts_with("\\\"\"\"") {
+                        i += 4;
+                    } else if query[i..].starts_with("\"\"\"") {
+                        i += 3;
+                        break;
+                    } else {
+                        i += 1;
+                    }
+                }
+                tokens.push(RawToken::Other(&query[start..i]));
+            }
+            b'"' => {
+                i += 1;
+                loop {
+                    match bytes.get(i) {
+                        None | Some(b'\n') => return None,
+                        Some(b'\\') => i += 2,
+                        Some(b'"') => {
+                            i += 1;
+                            break;
+                        }
+                        Some(_) => i += 1,
+                    }
+                }
+                tokens.push(RawToken::String(&query[start..i]));
+            }
+            b'.' if query[i..].starts_with("...") => {
+                i += 3;
+                tokens.push(RawToken::Other(&query[start..i]));
+            }
+            c if c.is_ascii_alphanumeric() || c == b'_' || c == b'-' => {
+                // Names and numbers (with signs, fractions and exponents) run to the next
+                // character that can't be part of either
+                let numeric = c.is_ascii_digit() || c == b'-';
+                i += 1;
+                while let Some(&next) = bytes.get(i) {
+                    let continues = next.is_ascii_alphanumeric()
+                        || next == b'_'
+                        || (numeric && next == b'.')
+                        || (numeric && (next == b'+' || next == b'-') && matches!(bytes[i - 1], b'e' | b'E'));
+                    if !continues {
+                        break;
+                    }
+                    i += 1;
+                }
+                tokens.push(RawToken::Other(&query[start..i]));
+            }
+            _ => {
+                // Punctuators, and anything else one character at a time
+                let width = query[i..].chars().next().map(char::len_utf8).unwrap_or(1);
+                i += width;
+                tokens.push(RawToken::Other(&query[start..i]));
+            }
+        }
+    }
+    Some(tokens)
+}
+
+/// `raw`, a quoted string literal, with its letters and digits as `\u` escapes. Escapes
+/// already there stay as they are.
+fn escape_string(raw: &str) -> String {
+    let inner = &raw[1..raw.len() - 1];
+    let mut out = String::with_capacity(raw.len() * 4);
+    out.push('"');
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            out.push(c);
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else if c.is_ascii_alphanumeric() {
+            out.push_str(&format!("\\u{:04x}", c as u32));
+        } else {
+            out.push(c);
+        }
+    }
+    out.push('"');
+    out
+}
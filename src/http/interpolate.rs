@@ -0,0 +1,35 @@
+use anyhow::{bail, Context, Result};
+use regex::Regex;
+
+/// `value` with its `{{env:NAME}}` placeholders replaced by environment variables and
+/// `{{file:PATH}}` by the file's contents, trimmed, so tokens don't have to be typed
+/// out on the command line or committed in header files. A placeholder that can't be
+/// resolved is an error rather than an empty header.
+pub fn interpolate(value: &str) -> Result<String> {
+    if !value.contains("{{") {
+        return Ok(value.to_string());
+    }
+    let placeholder = Regex::new(r"\{\{\s*(env|file):([^}]+?)\s*\}\}").unwrap();
+
+    let mut out = String::with_capacity(value.len());
+    let mut last = 0;
+    for captures in placeholder.captures_iter(value) {
+        let whole = captures.get(0).unwrap();
+        let name = captures[2].trim();
+        let resolved = match &captures[1] {
+            "env" => match std::env::var(name) {
+                Ok(resolved) => resolved,
+                Err(_) => bail!("{{{{env:{}}}}} isn't set", name),
+            },
+            _ => std::fs::read_to_string(name)
+                .with_context(|| format!("Failed to read {{{{file:{}}}}}", name))?
+                .trim()
+                .to_string(),
+        };
+        out.push_str(&value[last..whole.start()]);
+        out.push_str(&resolved);
+        last = whole.end();
+    }
+    out.push_str(&value[last..]);
+    Ok(out)
+}
@@ -0,0 +1,60 @@
+use anyhow::{Context, Result};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+
+/// Longest request body `-vvv` prints before cutting it off
+const MAX_BODY: usize = 500;
+
+static LOG: OnceLock<RequestLog> = OnceLock::new();
+
+/// Where `-v` output goes and how much of it there is, for the whole process
+struct RequestLog {
+    level: u8,
+    sink: Mutex<Box<dyn Write + Send>>,
+}
+
+/// Turn on logging at `level` (the number of `-v`s): 1 for failures and skipped
+/// steps, 2 adds every request line with its status and latency, 3 adds request
+/// bodies. Lines go to `file` when given, stderr otherwise. Only the first call counts.
+pub fn init_request_log(level: u8, file: Option<&Path>) -> Result<()> {
+    let sink: Box<dyn Write + Send> = match file {
+        Some(path) => Box::new(
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Failed to open {}", path.display()))?,
+        ),
+        None => Box::new(std::io::stderr()),
+    };
+    let _ = LOG.set(RequestLog {
+        level,
+        sink: Mutex::new(sink),
+    });
+    Ok(())
+}
+
+/// The `-v` level, 0 when logging is off
+pub fn verbosity() -> u8 {
+    LOG.get().map(|log| log.level).unwrap_or(0)
+}
+
+/// Write `line` when logging is at `level` or above
+pub fn log_at(level: u8, line: &str) {
+    if let Some(log) = LOG.get().filter(|log| log.level >= level) {
+        let mut sink = log.sink.lock().unwrap();
+        let _ = writeln!(sink, "{}", line);
+        let _ = sink.flush();
+    }
+}
+
+/// `body` as text for the log, cut off after `MAX_BODY` characters
+pub(super) fn truncate_body(body: &[u8]) -> String {
+    let text = String::from_utf8_lossy(body);
+    match text.char_indices().nth(MAX_BODY) {
+        Some((cut, _)) => format!("{}... ({} bytes)", &text[..cut], body.len()),
+        None => text.into_owned(),
+    }
+}
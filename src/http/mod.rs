@@ -1,3 +1,18 @@
+mod chunked;
 mod client;
+mod evasion;
+mod interpolate;
+mod log;
+mod obfuscate;
+mod pool;
+mod stealth;
+mod websocket;
 
+pub use chunked::*;
 pub use client::*;
+pub use evasion::*;
+pub use interpolate::*;
+pub use log::*;
+pub use pool::*;
+pub use stealth::*;
+pub use websocket::*;
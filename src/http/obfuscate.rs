@@ -0,0 +1,187 @@
+use super::evasion::{random_below, split_tokens, RawToken};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+/// A query reshaped so it matches no known payload, and what to undo in its response
+#[derive(Debug, Clone)]
+pub(super) struct Obfuscation {
+    pub query: String,
+    /// Random alias to the alias it stands for
+    aliases: HashMap<String, String>,
+}
+
+impl Obfuscation {
+    /// `query` as it is, nothing to undo
+    pub fn plain(query: &str) -> Self {
+        Self {
+            query: query.to_string(),
+            aliases: HashMap::new(),
+        }
+    }
+
+    /// Put the original aliases back into `body`'s data and error paths, so the
+    /// response reads as if the query had gone out as written
+    pub fn restore(&self, body: &mut Value) {
+        if self.aliases.is_empty() {
+            return;
+        }
+        if let Some(data) = body.get_mut("data") {
+            self.restore_keys(data);
+        }
+        let errors = body.get_mut("errors").and_then(|e| e.as_array_mut());
+        for error in errors.into_iter().flatten() {
+            let path = error.get_mut("path").and_then(|p| p.as_array_mut());
+            for segment in path.into_iter().flatten() {
+                if let Some(original) = segment.as_str().and_then(|s| self.aliases.get(s)) {
+                    *segment = Value::String(original.clone());
+                }
+            }
+        }
+    }
+
+    fn restore_keys(&self, value: &mut Value) {
+        match value {
+            Value::Object(object) => {
+                let renamed: Map<String, Value> = std::mem::take(object)
+                    .into_iter()
+                    .map(|(key, mut item)| {
+                        self.restore_keys(&mut item);
+                        (self.aliases.get(&key).cloned().unwrap_or(key), item)
+                    })
+                    .collect();
+                *object = renamed;
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.restore_keys(item)),
+            _ => {}
+        }
+    }
+}
+
+/// Rewrite `query` with random aliases in place of the ones it has, a random name on
+/// its operation unless the request names it (`operation_name`), and every operation's
+/// fields behind an inline fragment. Documents that don't lex go out as they are.
+pub(super) fn obfuscate(query: &str, operation_name: Option<&str>) -> Obfuscation {
+    let tokens: Vec<String> = match split_tokens(query) {
+        Some(tokens) => tokens
+            .into_iter()
+            .map(|t| match t {
+                RawToken::String(raw) | RawToken::Other(raw) => raw.to_string(),
+            })
+            .collect(),
+        None => return Obfuscation::plain(query),
+    };
+
+    // Where each operation starts (its keyword, or its `{` when it has none)
+    let mut operations = Vec::new();
+    let mut originals: HashMap<String, String> = HashMap::new();
+    let mut out: Vec<String> = Vec::with_capacity(tokens.len() * 2);
+    let (mut braces, mut parens) = (0usize, 0usize);
+    for (index, token) in tokens.iter().enumerate() {
+        match token.as_str() {
+            "{" => {
+                if braces == 0 && parens == 0 && is_operation_start(&tokens, index) {
+                    operations.push(out.len());
+                }
+                braces += 1;
+            }
+            "}" => braces = braces.saturating_sub(1),
+            "(" => parens += 1,
+            ")" => parens = parens.saturating_sub(1),
+            "query" | "mutation" | "subscription" if braces == 0 && parens == 0 => operations.push(out.len()),
+            _ => {}
+        }
+
+        let is_alias = braces > 0
+            && parens == 0
+            && is_name(token)
+            && tokens.get(index + 1).is_some_and(|next| next == ":");
+        if is_alias {
+            let alias = originals.entry(token.clone()).or_insert_with(random_name).clone();
+            out.push(alias);
+        } else {
+            out.push(token.clone());
+        }
+    }
+
+    // Back to front, so the positions of earlier operations stay put
+    let single = operations.len() == 1 && operation_name.is_none();
+    for start in operations.into_iter().rev() {
+        wrap_selection(&mut out, start);
+        if !single {
+            continue;
+        }
+        // Only a lone operation the request doesn't name can be renamed
+        if out[start] == "{" {
+            out.insert(start, random_name());
+            out.insert(start, "query".to_string());
+        } else if out.get(start + 1).is_some_and(|t| is_name(t)) {
+            out[start + 1] = random_name();
+        } else {
+            out.insert(start + 1, random_name());
+        }
+    }
+
+    Obfuscation {
+        query: out.join(" "),
+        aliases: originals.into_iter().map(|(original, alias)| (alias, original)).collect(),
+    }
+}
+
+/// Whether the `{` at `index`, outside any definition, opens an anonymous query rather
+/// than the selection set of a named definition before it
+fn is_operation_start(tokens: &[String], index: usize) -> bool {
+    index == 0 || tokens[index - 1] == "}"
+}
+
+/// Put the top-level selection set of the operation at `start` inside `... { }`
+fn wrap_selection(tokens: &mut Vec<String>, start: usize) {
+    let mut parens = 0usize;
+    let mut open = None;
+    for (index, token) in tokens.iter().enumerate().skip(start) {
+        match token.as_str() {
+            "(" => parens += 1,
+            ")" => parens = parens.saturating_sub(1),
+            "{" if parens == 0 => {
+                open = Some(index);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let open = match open {
+        Some(open) => open,
+        None => return,
+    };
+
+    let mut depth = 0usize;
+    let mut close = None;
+    for (index, token) in tokens.iter().enumerate().skip(open) {
+        match token.as_str() {
+            "{" => depth += 1,
+            "}" => {
+                depth -= 1;
+                if depth == 0 {
+                    close = Some(index);
+                    break;
+                }
+            }
+            _ => {}
+        }
+    }
+    if let Some(close) = close {
+        tokens.insert(close, "}".to_string());
+        tokens.insert(open + 1, "{".to_string());
+        tokens.insert(open + 1, "...".to_string());
+    }
+}
+
+fn is_name(token: &str) -> bool {
+    token.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+}
+
+/// A lowercase letter, then letters and digits
+fn random_name() -> String {
+    const LETTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+    let first = LETTERS[random_below(LETTERS.len())] as char;
+    format!("{}{}", first, crate::oob::random_label(4 + random_below(6)))
+}
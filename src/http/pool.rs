@@ -0,0 +1,114 @@
+use reqwest::ClientBuilder;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use tower_layer::Layer;
+use tower_service::Service;
+
+static POOL: OnceLock<Pool> = OnceLock::new();
+
+/// Requests sent and connections opened by every client in the process
+static REQUESTS: AtomicUsize = AtomicUsize::new(0);
+static CONNECTIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// How clients keep connections around between requests. Unset fields keep reqwest's
+/// defaults (no idle limit, idle connections closed after 90s, no TCP keep-alive).
+#[derive(Debug, Clone)]
+pub struct Pool {
+    /// Idle connections kept per host
+    pub max_idle_per_host: Option<usize>,
+    /// How long an idle connection is kept before it's closed
+    pub idle_timeout: Option<Duration>,
+    /// Interval for TCP keep-alive probes on open connections
+    pub tcp_keepalive: Option<Duration>,
+    /// Send small writes right away instead of batching them (Nagle off)
+    pub tcp_nodelay: bool,
+}
+
+impl Default for Pool {
+    fn default() -> Self {
+        Self {
+            max_idle_per_host: None,
+            idle_timeout: None,
+            tcp_keepalive: None,
+            tcp_nodelay: true,
+        }
+    }
+}
+
+impl Pool {
+    pub(super) fn configure(&self, mut builder: ClientBuilder) -> ClientBuilder {
+        if let Some(max) = self.max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max);
+        }
+        if let Some(timeout) = self.idle_timeout {
+            builder = builder.pool_idle_timeout(timeout);
+        }
+        if let Some(interval) = self.tcp_keepalive {
+            builder = builder.tcp_keepalive(interval);
+        }
+        builder
+            .tcp_nodelay(self.tcp_nodelay)
+            .connector_layer(CountConnections)
+    }
+}
+
+/// Use `pool` for every client built from here on. Only the first call counts.
+pub fn init_pool(pool: Pool) {
+    let _ = POOL.set(pool);
+}
+
+pub(super) fn pool() -> Pool {
+    POOL.get().cloned().unwrap_or_default()
+}
+
+pub(super) fn count_request() {
+    REQUESTS.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Requests sent and connections opened so far, by every client in the process.
+/// The difference is how many requests went over a connection already open.
+pub fn connection_stats() -> (usize, usize) {
+    (REQUESTS.load(Ordering::Relaxed), CONNECTIONS.load(Ordering::Relaxed))
+}
+
+/// Counts every connection the client's connector opens
+#[derive(Clone)]
+struct CountConnections;
+
+impl<S> Layer<S> for CountConnections {
+    type Service = Counted<S>;
+
+    fn layer(&self, inner: S) -> Counted<S> {
+        Counted(inner)
+    }
+}
+
+#[derive(Clone)]
+struct Counted<S>(S);
+
+impl<S, R> Service<R> for Counted<S>
+where
+    S: Service<R>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), S::Error>> {
+        self.0.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        let connecting = self.0.call(request);
+        Box::pin(async move {
+            let conn = connecting.await?;
+            CONNECTIONS.fetch_add(1, Ordering::Relaxed);
+            Ok(conn)
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use super::evasion::random_below;
+use anyhow::{bail, Context, Result};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Spacing that makes a run look less like a scanner: a random wait before every
+/// request, and a longer pause after every burst of them
+#[derive(Debug, Clone, Default)]
+pub struct Stealth {
+    delay: Option<(Duration, Duration)>,
+    /// Requests in a row, and the pause after them
+    burst: Option<(usize, Duration)>,
+    /// Shared between clones, so every copy of a client counts towards the same burst
+    state: Arc<Mutex<PaceState>>,
+}
+
+#[derive(Debug, Default)]
+struct PaceState {
+    /// Requests sent so far
+    sent: usize,
+    in_burst: usize,
+}
+
+impl Stealth {
+    /// Wait between `min` and `max` before each request
+    pub fn with_delay(mut self, min: Duration, max: Duration) -> Self {
+        self.delay = Some((min, max.max(min)));
+        self
+    }
+
+    /// Pause for `pause` after every `size` requests
+    pub fn with_burst(mut self, size: usize, pause: Duration) -> Self {
+        self.burst = Some((size.max(1), pause));
+        self
+    }
+
+    /// `1500` for a fixed delay or `500-3000` for a range, in milliseconds
+    pub fn parse_delay(spec: &str) -> Result<(Duration, Duration)> {
+        let parse = |ms: &str| -> Result<Duration> {
+            let ms = ms.trim().parse::<u64>().with_context(|| format!("--delay {} isn't milliseconds", spec))?;
+            Ok(Duration::from_millis(ms))
+        };
+        let (min, max) = match spec.split_once('-') {
+            Some((min, max)) => (parse(min)?, parse(max)?),
+            None => (parse(spec)?, parse(spec)?),
+        };
+        if max < min {
+            bail!("--delay {} has its range backwards", spec);
+        }
+        Ok((min, max))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.delay.is_none() && self.burst.is_none()
+    }
+
+    /// Hold the next request back as long as the delay and burst say; the first one
+    /// goes straight away
+    pub async fn wait(&self) {
+        if self.is_empty() {
+            return;
+        }
+        let wait = {
+            let mut state = self.state.lock().unwrap();
+            let mut wait = match self.delay {
+                Some((min, max)) if state.sent > 0 => {
+                    let spread = (max - min).as_millis() as usize;
+                    min + Duration::from_millis(random_below(spread + 1) as u64)
+                }
+                _ => Duration::ZERO,
+            };
+            if let Some((size, pause)) = self.burst {
+                if state.in_burst >= size {
+                    state.in_burst = 0;
+                    wait = wait.max(pause);
+                }
+                state.in_burst += 1;
+            }
+            state.sent += 1;
+            wait
+        };
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// Put `items` in a random order
+pub fn shuffle<T>(items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+        items.swap(i, random_below(i + 1));
+    }
+}
@@ -0,0 +1,275 @@
+use super::HttpClient;
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use reqwest::Upgraded;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::time::timeout;
+
+/// Subprotocols offered, current first: graphql-ws, then the older subscriptions-transport-ws
+pub const GRAPHQL_WS_PROTOCOLS: &[&str] = &["graphql-transport-ws", "graphql-ws"];
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+
+/// Largest message accepted, fragments included: a length past it ends the connection
+/// instead of allocating whatever the server announced
+const MAX_MESSAGE: usize = 4 << 20;
+
+/// A WebSocket speaking one of the GraphQL subprotocols. Just enough framing for
+/// handshakes and JSON messages, fragmented or not; no extensions.
+pub struct GraphQLSocket {
+    stream: Upgraded,
+    /// Subprotocol the server picked
+    pub protocol: String,
+    closed: bool,
+}
+
+/// What the server sent for a running subscription
+#[derive(Debug, Clone)]
+pub enum SubscriptionMessage {
+    /// An execution result: `data` and maybe `errors`
+    Next(Value),
+    /// The operation failed; the payload is the error or list of errors
+    Error(Value),
+    /// The server ended the subscription
+    Complete,
+}
+
+impl GraphQLSocket {
+    /// Upgrade `url` (http(s) or ws(s)) offering the GraphQL subprotocols. Fails unless the
+    /// server switches protocols and picks one of them.
+    pub async fn connect(client: &HttpClient, url: &str, test_name: Option<&str>) -> Result<Self> {
+        let url = http_url(url);
+        let key = websocket_key();
+        let response = client.websocket_upgrade(&url, GRAPHQL_WS_PROTOCOLS, &key, test_name).await?;
+        if response.status().as_u16() != 101 {
+            bail!("HTTP {}", response.status().as_u16());
+        }
+        let accept = response.headers().get("sec-websocket-accept").and_then(|v| v.to_str().ok());
+        if accept.map(str::trim) != Some(accept_key(&key).as_str()) {
+            bail!("Sec-WebSocket-Accept doesn't answer the key");
+        }
+        let protocol = response
+            .headers()
+            .get("sec-websocket-protocol")
+            .and_then(|v| v.to_str().ok())
+            .map(|p| p.trim().to_string())
+            .filter(|p| GRAPHQL_WS_PROTOCOLS.contains(&p.as_str()))
+            .context("Server picked no GraphQL subprotocol")?;
+        let stream = response.upgrade().await.context("WebSocket upgrade failed")?;
+        Ok(Self {
+            stream,
+            protocol,
+            closed: false,
+        })
+    }
+
+    /// Send `connection_init` and wait for `connection_ack`
+    pub async fn init(&mut self, payload: Value, wait: Duration) -> Result<bool> {
+        self.send(&json!({"type": "connection_init", "payload": payload})).await?;
+        loop {
+            match self.recv(wait).await? {
+                Some(message) => match message.get("type").and_then(|t| t.as_str()) {
+                    Some("connection_ack") => return Ok(true),
+                    Some("connection_error") => return Ok(false),
+                    // Keep-alives and pings from the older protocol
+                    _ => continue,
+                },
+                None => return Ok(false),
+            }
+        }
+    }
+
+    /// Start operation `id`: `subscribe` under graphql-transport-ws, `start` under the
+    /// older protocol
+    pub async fn subscribe(&mut self, id: &str, query: &str, variables: Option<Value>) -> Result<()> {
+        let kind = if self.is_legacy() { "start" } else { "subscribe" };
+        let mut payload = json!({"query": query});
+        if let Some(variables) = variables {
+            payload["variables"] = variables;
+        }
+        self.send(&json!({"id": id, "type": kind, "payload": payload})).await
+    }
+
+    /// Ask the server to stop operation `id`
+    pub async fn stop(&mut self, id: &str) -> Result<()> {
+        let kind = if self.is_legacy() { "stop" } else { "complete" };
+        self.send(&json!({"id": id, "type": kind})).await
+    }
+
+    /// Next message for operation `id`, or None when the server closes or stays quiet for
+    /// `wait`. Keep-alives and other operations' messages are skipped, pings answered.
+    pub async fn next_message(&mut self, id: &str, wait: Duration) -> Result<Option<SubscriptionMessage>> {
+        loop {
+            let message = match self.recv(wait).await? {
+                Some(message) => message,
+                None => return Ok(None),
+            };
+            let kind = message.get("type").and_then(|t| t.as_str()).unwrap_or_default();
+            if kind == "ping" {
+                self.send(&json!({"type": "pong"})).await?;
+                continue;
+            }
+            if message.get("id").and_then(|i| i.as_str()) != Some(id) {
+                continue;
+            }
+            let payload = message.get("payload").cloned().unwrap_or(Value::Null);
+            match kind {
+                "next" | "data" => return Ok(Some(SubscriptionMessage::Next(payload))),
+                "error" => return Ok(Some(SubscriptionMessage::Error(payload))),
+                "complete" => return Ok(Some(SubscriptionMessage::Complete)),
+                _ => continue,
+            }
+        }
+    }
+
+    /// Whether the server sent a close frame
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// subscriptions-transport-ws, which names its messages differently
+    fn is_legacy(&self) -> bool {
+        self.protocol == "graphql-ws"
+    }
+
+    pub async fn send(&mut self, message: &Value) -> Result<()> {
+        let payload = serde_json::to_vec(message)?;
+        let mut frame = vec![0x80 | OPCODE_TEXT];
+        match payload.len() {
+            len if len < 126 => frame.push(0x80 | len as u8),
+            len if len <= u16::MAX as usize => {
+                frame.push(0x80 | 126);
+                frame.extend_from_slice(&(len as u16).to_be_bytes());
+            }
+            len => {
+                frame.push(0x80 | 127);
+                frame.extend_from_slice(&(len as u64).to_be_bytes());
+            }
+        }
+        // Clients must mask; the key only has to be unpredictable to intermediaries
+        let mut mask = [0u8; 4];
+        openssl::rand::rand_bytes(&mut mask)?;
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        self.stream.write_all(&frame).await?;
+        self.stream.flush().await?;
+        Ok(())
+    }
+
+    /// Next JSON text message, or None when the server closes or stays quiet for `wait`.
+    /// Fragments are put back together; text that isn't JSON is skipped.
+    pub async fn recv(&mut self, wait: Duration) -> Result<Option<Value>> {
+        // Text message being received in fragments
+        let mut partial: Option<Vec<u8>> = None;
+        loop {
+            let (fin, opcode, payload) = match timeout(wait, self.read_frame()).await {
+                Ok(frame) => frame?,
+                Err(_) => return Ok(None),
+            };
+            let message = match (opcode, partial.as_mut()) {
+                (OPCODE_TEXT, _) if fin => payload,
+                (OPCODE_TEXT, _) => {
+                    partial = Some(payload);
+                    continue;
+                }
+                (OPCODE_CONTINUATION, Some(buffer)) => {
+                    if buffer.len() + payload.len() > MAX_MESSAGE {
+                        bail!("WebSocket message over {} bytes", MAX_MESSAGE);
+                    }
+                    buffer.extend_from_slice(&payload);
+                    if !fin {
+                        continue;
+                    }
+                    partial.take().unwrap_or_default()
+                }
+                (OPCODE_CLOSE, _) => {
+                    self.closed = true;
+                    return Ok(None);
+                }
+                // Pings (answered while reading), pongs, binary messages
+                _ => continue,
+            };
+            match serde_json::from_slice(&message) {
+                Ok(message) => return Ok(Some(message)),
+                Err(_) => continue,
+            }
+        }
+    }
+
+    /// FIN bit, opcode and unmasked payload of the next frame
+    async fn read_frame(&mut self) -> Result<(bool, u8, Vec<u8>)> {
+        let mut header = [0u8; 2];
+        self.stream.read_exact(&mut header).await?;
+        let fin = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0f;
+        let masked = header[1] & 0x80 != 0;
+        let len = match header[1] & 0x7f {
+            126 => self.stream.read_u16().await? as u64,
+            127 => self.stream.read_u64().await?,
+            len => len as u64,
+        };
+        if len > MAX_MESSAGE as u64 {
+            bail!("WebSocket frame of {} bytes, over the {} byte limit", len, MAX_MESSAGE);
+        }
+        let len = len as usize;
+        let mut mask = [0u8; 4];
+        if masked {
+            self.stream.read_exact(&mut mask).await?;
+        }
+        let mut payload = vec![0u8; len];
+        self.stream.read_exact(&mut payload).await?;
+        if masked {
+            payload.iter_mut().enumerate().for_each(|(i, b)| *b ^= mask[i % 4]);
+        }
+        if opcode == OPCODE_PING {
+            // Pong with the same payload (masked like any client frame, payloads are tiny)
+            let mut pong = vec![0x8a, 0x80 | payload.len().min(125) as u8, 0, 0, 0, 0];
+            pong.extend_from_slice(&payload[..payload.len().min(125)]);
+            self.stream.write_all(&pong).await?;
+        }
+        Ok((fin, opcode, payload))
+    }
+}
+
+/// The http(s) URL a ws(s) URL upgrades from
+pub fn http_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("ws://") {
+        format!("http://{}", rest)
+    } else if let Some(rest) = url.strip_prefix("wss://") {
+        format!("https://{}", rest)
+    } else {
+        url.to_string()
+    }
+}
+
+/// The ws(s) URL an http(s) URL upgrades to
+pub fn ws_url(url: &str) -> String {
+    if let Some(rest) = url.strip_prefix("http://") {
+        format!("ws://{}", rest)
+    } else if let Some(rest) = url.strip_prefix("https://") {
+        format!("wss://{}", rest)
+    } else {
+        url.to_string()
+    }
+}
+
+/// GUID the server appends to the key before hashing it (RFC 6455)
+const WEBSOCKET_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// A fresh `Sec-WebSocket-Key`: 16 random bytes, base64
+fn websocket_key() -> String {
+    let mut bytes = [0u8; 16];
+    openssl::rand::rand_bytes(&mut bytes).expect("no randomness available");
+    STANDARD.encode(bytes)
+}
+
+/// The `Sec-WebSocket-Accept` a server owes `key`
+fn accept_key(key: &str) -> String {
+    STANDARD.encode(openssl::sha::sha1(format!("{}{}", key, WEBSOCKET_GUID).as_bytes()))
+}
@@ -0,0 +1,236 @@
+mod strategy;
+
+pub use strategy::*;
+
+use crate::export::{CatalogExporter, OperationFilter};
+use crate::http::HttpClient;
+use crate::schema::{Field, Schema};
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+/// An object a neighbor ID fetched that isn't the seed's
+#[derive(Debug, Clone, Serialize)]
+pub struct IdorFinding {
+    pub operation: String,
+    /// The argument the ID went into
+    pub argument: String,
+    pub seed: String,
+    pub candidate: String,
+    pub strategy: IdStrategy,
+    /// The object that came back, cut short
+    pub object: String,
+    pub curl_command: String,
+}
+
+/// Progress while scanning
+pub enum IdorEvent<'a> {
+    /// A seed fetched an object through an operation: operation, seed, neighbors to try
+    Seed(&'a str, &'a str, usize),
+    Finding(&'a IdorFinding),
+}
+
+/// A query field that takes an ID, and the example query to send through it
+struct IdorTarget {
+    name: String,
+    argument: String,
+    /// Whether the argument wants an Int rather than an ID or String
+    numeric: bool,
+    query: String,
+}
+
+/// Feeds IDs derived from known ones into every query field that takes an ID and
+/// reports the objects that come back
+pub struct IdorScanner {
+    client: HttpClient,
+    url: String,
+    schema: Schema,
+    filter: OperationFilter,
+    spread: u64,
+    requests: usize,
+}
+
+impl IdorScanner {
+    pub fn new(client: HttpClient, url: &str, schema: Schema) -> Self {
+        Self {
+            client,
+            url: url.to_string(),
+            schema,
+            filter: OperationFilter::default(),
+            spread: 3,
+            requests: 0,
+        }
+    }
+
+    pub fn with_filter(mut self, filter: OperationFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Neighbors to try on each side of a seed, per strategy
+    pub fn with_spread(mut self, spread: u64) -> Self {
+        self.spread = spread;
+        self
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    pub async fn run(&mut self, seeds: &[String], callback: Option<&dyn Fn(&IdorEvent)>) -> Result<Vec<IdorFinding>> {
+        let types = self.node_types();
+        let mut findings = Vec::new();
+
+        for target in self.targets() {
+            for seed in seeds {
+                let value = match target.value(seed) {
+                    Some(v) => v,
+                    None => continue,
+                };
+                let seed_object = match self.fetch(&target, value).await {
+                    Some((object, _)) => object,
+                    // The seed isn't something this field returns
+                    None => continue,
+                };
+
+                let candidates = neighbor_ids(seed, self.spread, &types);
+                if let Some(cb) = callback {
+                    cb(&IdorEvent::Seed(&target.name, seed, candidates.len()));
+                }
+                for candidate in candidates {
+                    let value = match target.value(&candidate.value) {
+                        Some(v) => v,
+                        None => continue,
+                    };
+                    let (object, curl_command) = match self.fetch(&target, value).await {
+                        Some(v) => v,
+                        None => continue,
+                    };
+                    if object == seed_object {
+                        continue;
+                    }
+
+                    let finding = IdorFinding {
+                        operation: target.name.clone(),
+                        argument: target.argument.clone(),
+                        seed: seed.clone(),
+                        candidate: candidate.value,
+                        strategy: candidate.strategy,
+                        object: excerpt(&object.to_string()),
+                        curl_command,
+                    };
+                    if let Some(cb) = callback {
+                        cb(&IdorEvent::Finding(&finding));
+                    }
+                    findings.push(finding);
+                }
+            }
+        }
+
+        Ok(findings)
+    }
+
+    /// The non-null object `target` returns for `id`, with the request that got it
+    async fn fetch(&mut self, target: &IdorTarget, id: Value) -> Option<(Value, String)> {
+        self.requests += 1;
+        let mut variables = Map::new();
+        variables.insert(target.argument.clone(), id);
+        let response = self
+            .client
+            .post_graphql(&self.url, &target.query, Some(Value::Object(variables)), Some("idor"))
+            .await
+            .ok()?;
+        let object = response.get_data()?.get(&target.name)?.clone();
+        let empty = match &object {
+            Value::Null => true,
+            Value::Array(items) => items.is_empty(),
+            _ => false,
+        };
+        (!empty).then_some((object, response.curl_command))
+    }
+
+    /// Query fields whose only non-null argument is an ID-like one (the example queries
+    /// declare every argument, so a non-null one with a default still needs a value)
+    fn targets(&self) -> Vec<IdorTarget> {
+        let fields: Vec<&Field> = self
+            .schema
+            .get_query_type()
+            .and_then(|t| t.fields.as_ref())
+            .into_iter()
+            .flatten()
+            .collect();
+        let catalog = CatalogExporter::new(self.schema.clone())
+            .with_filter(self.filter.clone())
+            .export();
+
+        let mut targets = Vec::new();
+        for operation in catalog.operations.into_iter().filter(|o| o.kind == "query") {
+            let field = match fields.iter().find(|f| f.name == operation.name) {
+                Some(v) => v,
+                None => continue,
+            };
+            let arg = match field.args.iter().find(|a| is_id_argument(&a.name)) {
+                Some(v) => v,
+                None => continue,
+            };
+            let others_required = field
+                .args
+                .iter()
+                .any(|a| a.name != arg.name && a.input_type.is_non_null());
+            let scalar = arg.input_type.get_base_type_name().unwrap_or_default();
+            if others_required || arg.input_type.is_list() || !matches!(scalar, "ID" | "String" | "Int") {
+                continue;
+            }
+
+            targets.push(IdorTarget {
+                name: operation.name,
+                argument: arg.name.clone(),
+                numeric: scalar == "Int",
+                query: operation.example.query,
+            });
+        }
+        targets
+    }
+
+    /// Object types to swap into Relay IDs: those implementing `Node`, if the schema
+    /// has it
+    fn node_types(&self) -> Vec<String> {
+        self.schema
+            .get_user_types()
+            .into_iter()
+            .filter(|t| t.kind == "OBJECT")
+            .filter(|t| {
+                t.interfaces
+                    .iter()
+                    .flatten()
+                    .any(|i| i.name.as_deref() == Some("Node"))
+            })
+            .filter_map(|t| t.name.clone())
+            .collect()
+    }
+}
+
+impl IdorTarget {
+    /// `id` as this target's argument takes it, if it can
+    fn value(&self, id: &str) -> Option<Value> {
+        if self.numeric {
+            id.parse::<i64>().ok().map(|n| json!(n))
+        } else {
+            Some(json!(id))
+        }
+    }
+}
+
+/// `id`, `userId`, `user_id`, `ID`
+pub(crate) fn is_id_argument(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    lower == "id" || lower.ends_with("_id") || name.ends_with("Id") || name.ends_with("ID") || lower == "uuid"
+}
+
+fn excerpt(text: &str) -> String {
+    if text.len() <= 120 {
+        return text.to_string();
+    }
+    format!("{}...", &text[..text.floor_char_boundary(120)])
+}
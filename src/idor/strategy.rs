@@ -0,0 +1,155 @@
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE_NO_PAD};
+use base64::Engine;
+use serde::Serialize;
+
+/// How a candidate ID was derived from a seed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdStrategy {
+    /// seed ± n
+    Numeric,
+    /// Version 1 UUID with its timestamp moved by a few ticks
+    UuidTimestamp,
+    /// Base64 `Type:id` Relay ID with a different id (or type) inside
+    RelaySwap,
+}
+
+impl std::fmt::Display for IdStrategy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            IdStrategy::Numeric => "numeric",
+            IdStrategy::UuidTimestamp => "uuid v1 timestamp",
+            IdStrategy::RelaySwap => "relay swap",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CandidateId {
+    pub value: String,
+    pub strategy: IdStrategy,
+}
+
+/// IDs likely to sit next to `seed`, `spread` on each side for each strategy that fits
+/// it. `types` are object type names to swap into Relay IDs.
+pub fn neighbor_ids(seed: &str, spread: u64, types: &[String]) -> Vec<CandidateId> {
+    let mut candidates = Vec::new();
+
+    for (values, strategy) in [
+        (numeric_neighbors(seed, spread), IdStrategy::Numeric),
+        (uuid_neighbors(seed, spread), IdStrategy::UuidTimestamp),
+    ] {
+        candidates.extend(values.into_iter().map(|value| CandidateId { value, strategy }));
+    }
+
+    if let Some((type_name, inner, encoding)) = decode_relay_id(seed) {
+        let mut swapped: Vec<String> = numeric_neighbors(&inner, spread)
+            .into_iter()
+            .chain(uuid_neighbors(&inner, spread))
+            .map(|id| format!("{}:{}", type_name, id))
+            .collect();
+        swapped.extend(
+            types
+                .iter()
+                .filter(|t| **t != type_name)
+                .map(|t| format!("{}:{}", t, inner)),
+        );
+        candidates.extend(swapped.into_iter().map(|raw| CandidateId {
+            value: encoding.encode(raw),
+            strategy: IdStrategy::RelaySwap,
+        }));
+    }
+
+    candidates.dedup();
+    candidates.retain(|c| c.value != seed);
+    candidates
+}
+
+fn numeric_neighbors(id: &str, spread: u64) -> Vec<String> {
+    let n: u64 = match id.parse() {
+        Ok(n) if !id.starts_with('+') => n,
+        _ => return Vec::new(),
+    };
+    let mut values = Vec::new();
+    for step in 1..=spread {
+        if let Some(below) = n.checked_sub(step) {
+            values.push(below.to_string());
+        }
+        values.push((n + step).to_string());
+    }
+    values
+}
+
+/// Neighbors of a version 1 UUID: same clock sequence and node, timestamp ± a few
+/// 100ns ticks. Anything else yields nothing.
+fn uuid_neighbors(id: &str, spread: u64) -> Vec<String> {
+    let hex: String = id.chars().filter(|c| *c != '-').collect();
+    let is_uuid = id.len() == 36
+        && id.chars().enumerate().all(|(i, c)| matches!(i, 8 | 13 | 18 | 23) == (c == '-'))
+        && hex.chars().all(|c| c.is_ascii_hexdigit());
+    if !is_uuid || &hex[12..13] != "1" {
+        return Vec::new();
+    }
+
+    let time_low = u64::from_str_radix(&hex[0..8], 16).unwrap();
+    let time_mid = u64::from_str_radix(&hex[8..12], 16).unwrap();
+    let time_hi = u64::from_str_radix(&hex[13..16], 16).unwrap();
+    let timestamp = (time_hi << 48) | (time_mid << 32) | time_low;
+    let upper = id.chars().any(|c| c.is_ascii_uppercase());
+
+    let mut values = Vec::new();
+    for step in 1..=spread {
+        for ts in [timestamp.checked_sub(step), timestamp.checked_add(step)].into_iter().flatten() {
+            if ts >> 60 != 0 {
+                continue;
+            }
+            let value = format!(
+                "{:08x}-{:04x}-1{:03x}-{}",
+                ts & 0xffff_ffff,
+                (ts >> 32) & 0xffff,
+                (ts >> 48) & 0xfff,
+                &id[19..]
+            );
+            values.push(if upper { value.to_uppercase() } else { value });
+        }
+    }
+    values
+}
+
+/// How a Relay ID was encoded, so neighbors come out the same way
+#[derive(Debug, Clone, Copy)]
+enum RelayEncoding {
+    Padded,
+    Unpadded,
+    UrlSafe,
+}
+
+impl RelayEncoding {
+    fn encode(&self, raw: String) -> String {
+        match self {
+            RelayEncoding::Padded => STANDARD.encode(raw),
+            RelayEncoding::Unpadded => STANDARD_NO_PAD.encode(raw),
+            RelayEncoding::UrlSafe => URL_SAFE_NO_PAD.encode(raw),
+        }
+    }
+}
+
+/// `VXNlcjo0Mg==` -> (`User`, `42`)
+fn decode_relay_id(id: &str) -> Option<(String, String, RelayEncoding)> {
+    let (bytes, encoding) = if id.contains(['-', '_']) {
+        (URL_SAFE_NO_PAD.decode(id.trim_end_matches('=')).ok()?, RelayEncoding::UrlSafe)
+    } else if id.ends_with('=') {
+        (STANDARD.decode(id).ok()?, RelayEncoding::Padded)
+    } else {
+        (STANDARD_NO_PAD.decode(id).ok()?, RelayEncoding::Unpadded)
+    };
+    let raw = String::from_utf8(bytes).ok()?;
+    let (type_name, inner) = raw.split_once(':')?;
+    let valid_type = type_name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && type_name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid_type || inner.is_empty() || inner.chars().any(|c| c.is_control()) {
+        return None;
+    }
+    Some((type_name.to_string(), inner.to_string(), encoding))
+}
@@ -1,5 +1,16 @@
+pub mod analysis;
+pub mod apq;
+pub mod authz;
 pub mod discovery;
+pub mod dump;
+pub mod exec;
 pub mod export;
+pub mod federation;
+pub mod fuzz;
+pub mod guard;
 pub mod http;
+pub mod idor;
+pub mod oob;
+pub mod progress;
 pub mod schema;
 pub mod tests;
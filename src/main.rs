@@ -1,17 +1,41 @@
 use anyhow::{bail, Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand};
 use colored::Colorize;
-use gqlmap::discovery::{load_wordlist, EndpointDiscovery};
-use gqlmap::export::{BrunoExporter, CurlExporter, InqlExporter, PostmanExporter};
-use gqlmap::http::HttpClient;
-use gqlmap::schema::{default_wordlist, fetch_schema_raw, load_wordlist as load_inference_wordlist, SchemaInferrer};
-use gqlmap::tests::{all_tests, is_graphql_endpoint, Severity, TestResult};
-use serde_json::Value;
-use std::collections::HashMap;
-use std::path::PathBuf;
+use gqlmap::analysis::{FieldOutcome, OutcomeReport};
+use gqlmap::apq::{apq_extensions, apq_hash, load_hashes, PersistedProbe, PersistedQueryProber, PersistedStatus};
+use gqlmap::authz::{Access, AccessMatrix, AccessRow, AuthContext};
+use gqlmap::discovery::{
+    harvest_words, load_wordlist, passive_seeds, path_pack, path_pack_names, resolve_subdomains, EndpointCandidate, EndpointDiscovery, DEV_PORTS,
+};
+use gqlmap::export::{
+    ArmorExporter, BrunoExporter, BurpExporter, CatalogExporter, CurlExporter, GraphqlConfigExporter, GroupBy, HttpieExporter, InqlExporter,
+    OperationFilter, PostmanAuthKind, PostmanExporter,
+};
+use gqlmap::dump::{DumpPage, Iteration, PageWalker, TemplateDumper, TemplateRecord};
+use gqlmap::exec::{ExecResult, Executor, VariableOverrides};
+use gqlmap::federation::{FederationScanner, Subgraph};
+use gqlmap::fuzz::{select_packs, FuzzEvent, Fuzzer, PayloadPack};
+use gqlmap::guard::MutationGuard;
+use gqlmap::http::{connection_stats, http_url, init_pool, init_request_log, interpolate, log_at, Chunking, Evasion, GraphQLSocket, HttpClient, Pool, Stealth, SubscriptionMessage};
+use gqlmap::idor::{IdorEvent, IdorScanner};
+use gqlmap::oob::OobClient;
+use gqlmap::progress::{Progress, ProgressEvent, ScanMetrics};
+use gqlmap::schema::{
+    audit_schema, default_wordlist, schema_coverage, AttackGenerator, Confidence, CoverageReport, diff_schemas, schema_drift, schema_from_traffic, load_traffic, observed_arguments, parse_schema_json, find_cycles, fetch_schema, fetch_schema_raw, load_schema_file,
+    lint_schema, load_state as load_inference_state, InferenceEvent, load_wordlist as load_inference_wordlist, mangle_wordlist, merge_schemas, LINT_RULES, print_sdl, schema_wordlist, SchemaGraph, ChangeSeverity, EngineProfile, Schema, SchemaInferrer,
+};
+use gqlmap::tests::{all_tests, Baseline, Category, Confidence as FindingConfidence, ImpactCheck, is_graphql_endpoint, merge_results, order_tests, parse_results_json, ScanContext, SecurityTest, Severity, TestInfo, TestResult, Transport, Watchdog, WatchdogEvent};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// How long `subscribe` waits for connection_ack
+const SUBSCRIBE_ACK_WAIT: Duration = Duration::from_secs(10);
+
 fn print_banner() {
     println!("{}", "   __________    __    __  ___          ".bright_magenta());
     println!("{}", "  / ____/ __ \\  / /   /  |/  /___ _____ ".bright_magenta());
@@ -34,6 +58,30 @@ fn print_banner() {
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// More detail on stderr: -v detection and failures, -vv every request with its status and latency, -vvv request bodies too
+    #[arg(short, long, action = clap::ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Write -v output to this file instead of stderr
+    #[arg(long, global = true)]
+    log_file: Option<PathBuf>,
+
+    /// Idle connections to keep open per host (default: no limit)
+    #[arg(long, global = true, value_name = "N")]
+    pool_max_idle: Option<usize>,
+
+    /// Seconds an idle connection stays open before it's closed (default: 90)
+    #[arg(long, global = true, value_name = "SECS")]
+    pool_idle_timeout: Option<u64>,
+
+    /// Send TCP keep-alive probes on open connections every this many seconds
+    #[arg(long, global = true, value_name = "SECS")]
+    tcp_keepalive: Option<u64>,
+
+    /// Let small writes wait to be batched (Nagle's algorithm) instead of sending them right away
+    #[arg(long, global = true)]
+    no_tcp_nodelay: bool,
 }
 
 #[derive(Subcommand)]
@@ -41,8 +89,12 @@ enum Commands {
     /// Run security tests against a GraphQL endpoint
     Scan {
         /// Target GraphQL endpoint URL
-        #[arg(short, long)]
-        target: String,
+        #[arg(short, long, required_unless_present_any = ["targets_file", "list_tests"])]
+        target: Option<String>,
+
+        /// File of endpoint URLs to scan, one per line, each optionally followed by its own -H, -x or --auth (`-` reads stdin, e.g. from `discover -o urls`)
+        #[arg(long, conflicts_with_all = ["target", "discover"])]
+        targets_file: Option<PathBuf>,
 
         /// Custom HTTP headers (can be repeated)
         #[arg(short = 'H', long = "header")]
@@ -52,6 +104,13 @@ enum Commands {
         #[arg(short = 'x', long)]
         proxy: Option<String>,
 
+        /// Run the tests in a random order
+        #[arg(long)]
+        shuffle: bool,
+
+        #[command(flatten)]
+        evasion: EvasionArgs,
+
         /// Output format (text, json)
         #[arg(short, long, default_value = "text")]
         output: String,
@@ -60,6 +119,10 @@ enum Commands {
         #[arg(short, long)]
         exclude: Option<String>,
 
+        /// Also run these opt-in tests (comma-separated), e.g. tls_config
+        #[arg(short, long)]
+        include: Option<String>,
+
         /// Enable debug mode (adds test headers)
         #[arg(short, long)]
         debug: bool,
@@ -76,9 +139,123 @@ enum Commands {
         #[arg(short, long)]
         wordlist: Option<PathBuf>,
 
-        /// List available tests
-        #[arg(short, long)]
+        /// List available tests (same as `gqlmap tests list`)
+        #[arg(short, long, hide = true)]
         list_tests: bool,
+
+        /// Write a schema coverage report of the fields the scan touched (JSON)
+        #[arg(long)]
+        coverage: Option<PathBuf>,
+
+        /// Schema to use instead of introspecting (introspection JSON or SDL), e.g. one saved by
+        /// `gqlmap infer`; schema-based tests and --coverage work from it
+        #[arg(long)]
+        schema: Option<PathBuf>,
+
+        /// Account account_enumeration expects to exist (default: admin, or admin@<host> for email logins)
+        #[arg(long)]
+        known_account: Option<String>,
+
+        /// Measure DoS findings: send each payload at 3 increasing sizes, stopping once the target
+        /// slows down or stops answering, and mark the finding verified when it did
+        #[arg(long)]
+        verify_dos: bool,
+
+        /// Don't watch the target's health during the scan (by default aggressive tests pause
+        /// while it's failing or slow against the baseline, and stop when it doesn't recover)
+        #[arg(long)]
+        no_watchdog: bool,
+
+        /// Emit progress events (phase, test, requests sent, findings so far) as JSON lines on stderr
+        #[arg(long, value_parser = ["json"])]
+        progress: Option<String>,
+
+        /// Write progress events to this file or FIFO instead of stderr
+        #[arg(long, requires = "progress")]
+        progress_file: Option<PathBuf>,
+
+        /// Push findings by severity, duration and errors to this Prometheus Pushgateway after each target
+        #[arg(long)]
+        push_metrics: Option<String>,
+    },
+
+    /// What scan can run
+    Tests {
+        #[command(subcommand)]
+        action: TestsCommand,
+    },
+
+    /// Work with saved scan results
+    Report {
+        #[command(subcommand)]
+        action: ReportCommand,
+    },
+
+    /// Find GraphQL endpoints under a base URL
+    Discover {
+        /// Base URL to try the candidate paths on
+        #[arg(short, long)]
+        target: String,
+
+        /// Custom HTTP headers (can be repeated)
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
+
+        /// HTTP/HTTPS/SOCKS proxy URL
+        #[arg(short = 'x', long)]
+        proxy: Option<String>,
+
+        /// Custom wordlist of paths to try
+        #[arg(short, long)]
+        wordlist: Option<PathBuf>,
+
+        /// Output format (text, json, urls: live endpoints only, one per line)
+        #[arg(short, long, default_value = "text", value_parser = ["text", "json", "urls"])]
+        output: String,
+
+        /// Show every candidate, not only the GraphQL ones
+        #[arg(short, long)]
+        all: bool,
+
+        /// Also read the site's JavaScript bundles for endpoint URLs, queries and persisted-query hashes
+        #[arg(short, long)]
+        bundles: bool,
+
+        /// Save the operations, queries and persisted-query hashes found in bundles (JSON)
+        #[arg(long, requires = "bundles")]
+        operations: Option<PathBuf>,
+
+        /// Subdomain wordlist (api, graphql, gateway, ...): probe every name that resolves too
+        #[arg(long)]
+        subdomains: Option<PathBuf>,
+
+        /// Also try every host on these ports (comma-separated; bare flag for 3000,4000,5000,8000,8080,8443,9000)
+        #[arg(long, value_delimiter = ',', num_args = 0..)]
+        ports: Option<Vec<u16>>,
+
+        /// Platform path packs to add (comma-separated): hasura, wordpress, shopify, saleor, magento, appsync, ...
+        #[arg(long, value_delimiter = ',')]
+        paths: Vec<String>,
+
+        /// Also try graphql-ws upgrades on subscription paths (/graphql, /subscriptions, /socket, ...)
+        #[arg(long)]
+        websocket: bool,
+
+        /// Also try base segments (/api, /internal, /v2, /services/<name>) combined with GraphQL suffixes
+        #[arg(long)]
+        combine: bool,
+
+        /// Cap on the paths --combine generates
+        #[arg(long, default_value = "500", requires = "combine")]
+        max_paths: usize,
+
+        /// Seed paths from robots.txt and sitemap.xml before probing
+        #[arg(long)]
+        passive: bool,
+
+        /// With --passive, also ask the Wayback Machine for archived GraphQL URLs (no target headers are sent)
+        #[arg(long, requires = "passive")]
+        wayback: bool,
     },
 
     /// Fetch and save introspection schema
@@ -121,6 +298,59 @@ enum Commands {
         /// Output file path for inferred schema
         #[arg(short, long)]
         output: Option<PathBuf>,
+
+        /// Candidate fields sent per request (1 probes one word at a time)
+        #[arg(short, long, default_value = "64")]
+        bucket_size: usize,
+
+        /// How many levels of object types below the root to explore (0 = root fields only)
+        #[arg(short, long, default_value = "3")]
+        depth: usize,
+
+        /// Save progress to this file every 30 seconds and after each phase
+        #[arg(long)]
+        checkpoint: Option<PathBuf>,
+
+        /// Continue an interrupted run from its checkpoint file (and keep saving to it)
+        #[arg(long)]
+        resume: Option<PathBuf>,
+
+        /// Partial schema (introspection JSON or SDL) to start from; only what it lacks is probed
+        #[arg(long)]
+        seed: Option<PathBuf>,
+
+        /// Output format: introspection JSON, or SDL annotated with what couldn't be determined
+        #[arg(short, long, default_value = "json", value_parser = ["json", "sdl"])]
+        format: String,
+
+        /// Error message style: graphql-js, graphql-java, hasura, absinthe, or a .toml
+        /// profile of patterns (detected from the first errors when omitted)
+        #[arg(short, long)]
+        engine: Option<String>,
+
+        /// Expand the wordlist with plurals, verb prefixes and suffixes (getUser, allUsers, userById)
+        #[arg(long)]
+        mangle: bool,
+
+        /// Send probes as GET query strings (used on its own when POST gets no GraphQL response)
+        #[arg(long)]
+        get: bool,
+
+        /// Add names from the target site's HTML and JavaScript (embedded queries, operation names)
+        #[arg(long)]
+        harvest: bool,
+
+        /// Add every name in recorded traffic (HAR files, Burp XML exports): fields, arguments, types
+        #[arg(long)]
+        traffic: Vec<PathBuf>,
+
+        /// Stop after this many requests and save what was found (resume with --checkpoint)
+        #[arg(long)]
+        max_requests: Option<usize>,
+
+        /// Send at most this many requests per minute
+        #[arg(long)]
+        per_minute: Option<u32>,
     },
 
     /// Export schema to API client formats
@@ -128,538 +358,4062 @@ enum Commands {
         #[command(subcommand)]
         format: ExportFormat,
     },
-}
 
-#[derive(Subcommand)]
-enum ExportFormat {
-    /// Export to Bruno collection
-    Bruno {
-        /// Path to introspection JSON schema file
+    /// Work with saved schemas
+    Schema {
+        #[command(subcommand)]
+        action: SchemaCommand,
+    },
+
+    /// Generate worst-case queries to demonstrate DoS findings
+    Generate {
+        /// Query shape: deep recursion, nested list explosion or alias flood
+        #[arg(value_parser = ["deep", "wide", "aliases"])]
+        kind: String,
+
+        /// Schema file (introspection JSON or SDL)
         #[arg(short, long)]
         schema: PathBuf,
 
-        /// Output directory for Bruno collection
-        #[arg(short, long)]
-        output: PathBuf,
+        /// Nesting depth for deep and wide queries
+        #[arg(short, long, default_value = "10")]
+        depth: usize,
 
-        /// Base URL for requests
-        #[arg(short, long)]
-        url: String,
-    },
+        /// Number of aliased copies for alias floods
+        #[arg(short, long, default_value = "100")]
+        count: usize,
 
-    /// Export to Postman collection
-    Postman {
-        /// Path to introspection JSON schema file
-        #[arg(short, long)]
-        schema: PathBuf,
+        /// Items requested per level through pagination arguments (first, limit, ...)
+        #[arg(long, default_value = "100")]
+        page_size: usize,
 
-        /// Output JSON file path
-        #[arg(short, long)]
-        output: PathBuf,
+        /// Write a JSON request body instead of a raw query
+        #[arg(long)]
+        json: bool,
 
-        /// Base URL for requests
+        /// Output file (defaults to stdout)
         #[arg(short, long)]
-        url: String,
+        output: Option<PathBuf>,
     },
 
-    /// Export to executable cURL script
-    Curl {
-        /// Path to introspection JSON schema file
+    /// Send payloads through every operation argument and report odd responses
+    Fuzz {
+        /// Target GraphQL endpoint URL
         #[arg(short, long)]
-        schema: PathBuf,
+        target: String,
 
-        /// Output shell script path
+        /// Schema file, introspection JSON or SDL (introspected from the target when omitted)
         #[arg(short, long)]
-        output: PathBuf,
+        schema: Option<PathBuf>,
 
-        /// Base URL for requests
-        #[arg(short, long)]
-        url: String,
-    },
+        /// Custom HTTP headers
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
 
-    /// Export to InQL/Burp format (GraphQL files)
-    Inql {
-        /// Path to introspection JSON schema file
-        #[arg(short, long)]
-        schema: PathBuf,
+        /// HTTP/HTTPS/SOCKS proxy URL
+        #[arg(short = 'x', long)]
+        proxy: Option<String>,
 
-        /// Output directory for GraphQL files
-        #[arg(short, long)]
-        output: PathBuf,
+        /// Only fuzz operations whose name matches this glob (or regex with a `re:` prefix)
+        #[arg(long)]
+        filter: Option<String>,
 
-        /// Base URL for requests
-        #[arg(short, long)]
-        url: String,
-    },
-}
+        #[command(flatten)]
+        guard: GuardArgs,
 
-fn parse_headers(headers: &[String]) -> Result<HashMap<String, String>> {
-    let mut map = HashMap::new();
+        /// Recorded traffic (HAR files, Burp XML exports) whose argument values replace the placeholders
+        #[arg(long)]
+        traffic: Vec<PathBuf>,
 
-    for header in headers {
-        // Try JSON format first: {"Authorization": "Bearer token"}
-        if header.starts_with('{') {
-            let parsed: HashMap<String, String> =
-                serde_json::from_str(header).context("Invalid JSON header format")?;
-            map.extend(parsed);
-        } else if let Some((key, value)) = header.split_once(':') {
-            // Standard format: "Authorization: Bearer token"
-            map.insert(key.trim().to_string(), value.trim().to_string());
-        } else {
-            bail!("Invalid header format: {}", header);
-        }
-    }
+        /// Payload packs to use (comma-separated: sqli, nosqli, xss, ssti, traversal, xxe, oob, or custom ones; all by default)
+        #[arg(short, long, value_delimiter = ',')]
+        packs: Vec<String>,
 
-    Ok(map)
-}
+        /// Directory of custom packs, one .txt file per pack
+        #[arg(long)]
+        payloads: Option<PathBuf>,
 
-fn print_result(result: &TestResult) {
-    if !result.vulnerable {
-        return;
-    }
+        #[command(flatten)]
+        oob: OobArgs,
 
-    let severity = match result.severity {
-        Severity::High => format!("[{}]", result.severity).red().bold(),
-        Severity::Medium => format!("[{}]", result.severity).yellow().bold(),
-        Severity::Low => format!("[{}]", result.severity).blue().bold(),
-        Severity::Info => format!("[{}]", result.severity).green().bold(),
-    };
+        /// Instead of payloads, write markers through create/update mutations and look for them unencoded in every query
+        #[arg(long, requires = "mutations", conflicts_with_all = ["packs", "payloads", "oob"])]
+        stored: bool,
 
-    println!(
-        "{} {} - {}",
-        severity,
-        result.title.bold(),
-        result.description
-    );
-    println!("    Impact: {}", result.impact);
-    println!("    Verify: {}", result.curl_command.dimmed());
-    println!();
-}
+        /// Write the outcome of every query and mutation field (JSON)
+        #[arg(long)]
+        coverage: Option<PathBuf>,
 
-fn print_results_json(results: &[TestResult]) {
-    let output = serde_json::to_string_pretty(results).unwrap_or_default();
-    println!("{}", output);
-}
+        #[command(flatten)]
+        evasion: EvasionArgs,
 
-async fn run_scan(
-    target: String,
-    headers: Vec<String>,
-    proxy: Option<String>,
-    output: String,
-    exclude: Option<String>,
-    debug: bool,
-    force: bool,
-    discover: bool,
-    wordlist: Option<PathBuf>,
-    list_tests: bool,
-) -> Result<()> {
-    let tests = all_tests();
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
 
-    if list_tests {
-        println!("Available security tests:\n");
-        for test in &tests {
-            println!(
-                "  {} [{}] - {}",
-                test.name(),
-                test.severity(),
-                test.description()
-            );
-        }
-        return Ok(());
-    }
+    /// Try IDs next to known ones on every query that takes an ID
+    Idor {
+        /// Target GraphQL endpoint URL
+        #[arg(short, long)]
+        target: String,
 
-    print_banner();
+        /// Known object ID to start from: numeric, UUID or base64 Relay ID (repeat or comma-separate)
+        #[arg(long = "id", required = true, value_delimiter = ',')]
+        ids: Vec<String>,
 
-    let headers_map = parse_headers(&headers)?;
-    let client = HttpClient::new(proxy.as_deref(), headers_map, debug)?;
+        /// Schema file, introspection JSON or SDL (introspected from the target when omitted)
+        #[arg(short, long)]
+        schema: Option<PathBuf>,
 
-    let excluded: Vec<&str> = exclude
-        .as_deref()
-        .map(|e| e.split(',').map(|s| s.trim()).collect())
-        .unwrap_or_default();
+        /// Custom HTTP headers
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
 
-    // Determine target URLs
-    let targets: Vec<String> = if discover {
-        println!("{} Discovering GraphQL endpoints...\n", "[*]".cyan());
+        /// HTTP/HTTPS/SOCKS proxy URL
+        #[arg(short = 'x', long)]
+        proxy: Option<String>,
 
-        let custom_paths = wordlist
-            .map(|p| load_wordlist(p.to_str().unwrap()))
-            .transpose()?;
+        /// Only probe operations whose name matches this glob (or regex with a `re:` prefix)
+        #[arg(long)]
+        filter: Option<String>,
 
-        let discovery = EndpointDiscovery::new(&target, custom_paths)?;
-        let found = discovery.discover(&client).await;
+        /// Neighbors to try on each side of a seed ID
+        #[arg(long, default_value = "3")]
+        spread: u64,
 
-        if found.is_empty() {
-            println!("{} No GraphQL endpoints found", "[-]".red());
-            return Ok(());
-        }
+        #[command(flatten)]
+        evasion: EvasionArgs,
 
-        println!("{} Found {} endpoint(s):\n", "[+]".green(), found.len());
-        for url in &found {
-            println!("    {}", url);
-        }
-        println!();
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
 
-        found
-    } else {
-        vec![target]
-    };
+    /// Run every operation under several auth contexts and show who gets in
+    Authz {
+        /// Target GraphQL endpoint URL
+        #[arg(short, long)]
+        target: String,
 
-    for url in targets {
-        println!("{} Target: {}\n", "[*]".cyan(), url);
+        /// Auth context, most privileged first: a file of `Name: value` header lines, or `anon` (repeat for each)
+        #[arg(short, long = "context", required = true)]
+        contexts: Vec<String>,
 
-        // Check if GraphQL endpoint
-        if !force {
-            match is_graphql_endpoint(&client, &url).await {
-                Ok(true) => {
-                    println!("{} GraphQL endpoint detected\n", "[+]".green());
-                }
-                Ok(false) => {
-                    println!(
-                        "{} GraphQL not detected at this URL (use -f to force)",
+        /// Schema file, introspection JSON or SDL (introspected as the first context when omitted)
+        #[arg(short, long)]
+        schema: Option<PathBuf>,
+
+        /// Headers sent under every context
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
+
+        /// HTTP/HTTPS/SOCKS proxy URL
+        #[arg(short = 'x', long)]
+        proxy: Option<String>,
+
+        /// Only run operations whose name matches this glob (or regex with a `re:` prefix)
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Variables to use instead of placeholders: a JSON file for every operation, or a directory of <operationName>.json files (can be repeated)
+        #[arg(long)]
+        variables: Vec<PathBuf>,
+
+        #[command(flatten)]
+        guard: GuardArgs,
+
+        /// Write the outcome of every query and mutation field (JSON)
+        #[arg(long)]
+        coverage: Option<PathBuf>,
+
+        #[command(flatten)]
+        evasion: EvasionArgs,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+
+    /// Run every generated operation once and record what each got back
+    Exec {
+        /// Target GraphQL endpoint URL
+        #[arg(short, long)]
+        target: String,
+
+        /// Schema file, introspection JSON or SDL (introspected from the target when omitted)
+        #[arg(short, long)]
+        schema: Option<PathBuf>,
+
+        /// Custom HTTP headers
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
+
+        /// HTTP/HTTPS/SOCKS proxy URL
+        #[arg(short = 'x', long)]
+        proxy: Option<String>,
+
+        /// Only run operations whose name matches this glob (or regex with a `re:` prefix)
+        #[arg(long)]
+        filter: Option<String>,
+
+        /// Variables to use instead of placeholders: a JSON file for every operation, or a directory of <operationName>.json files (can be repeated)
+        #[arg(long)]
+        variables: Vec<PathBuf>,
+
+        #[command(flatten)]
+        guard: GuardArgs,
+
+        #[command(flatten)]
+        evasion: EvasionArgs,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+
+    /// Listen on a subscription over WebSocket and print what it streams
+    Subscribe {
+        /// WebSocket endpoint (ws://, wss://, or the http(s) URL it upgrades from)
+        #[arg(short, long)]
+        target: String,
+
+        /// Subscription document, e.g. 'subscription { messageAdded { id body } }'
+        #[arg(short, long)]
+        query: String,
+
+        /// JSON file of variables for the subscription
+        #[arg(long)]
+        variables: Option<PathBuf>,
+
+        /// connection_init payload as JSON, where most servers want auth, e.g. '{"Authorization":"Bearer ..."}'
+        #[arg(long)]
+        init: Option<String>,
+
+        /// Custom HTTP headers for the upgrade request
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
+
+        /// HTTP/HTTPS/SOCKS proxy URL
+        #[arg(short = 'x', long)]
+        proxy: Option<String>,
+
+        /// Stop after this many seconds (default: until the server completes or Ctrl-C)
+        #[arg(long)]
+        duration: Option<u64>,
+
+        /// Stop after this many events
+        #[arg(long)]
+        count: Option<usize>,
+
+        /// Output format (text, json: one event per line)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+
+    /// Detect Apollo Federation, pull entity keys and subgraph names, and try to reach subgraphs directly
+    Federation {
+        /// Target GraphQL endpoint URL (gateway or subgraph)
+        #[arg(short, long)]
+        target: String,
+
+        /// Custom HTTP headers
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
+
+        /// HTTP/HTTPS/SOCKS proxy URL
+        #[arg(short = 'x', long)]
+        proxy: Option<String>,
+
+        /// Ports on the target's host to look for subgraphs on (comma-separated, default 4001-4006,8081,8082)
+        #[arg(long, value_delimiter = ',')]
+        ports: Option<Vec<u16>>,
+
+        /// Only look at the target, don't try candidate subgraph URLs
+        #[arg(long)]
+        no_probe: bool,
+
+        /// Save the target's _service SDL here
+        #[arg(long)]
+        sdl: Option<PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+
+    /// Automatic persisted queries: hash documents, find out which hashes a server knows
+    Apq {
+        #[command(subcommand)]
+        action: ApqCommand,
+    },
+
+    /// Pull data out as JSON lines: page through a Relay connection, or run a query template over ranges of values
+    Dump {
+        /// Target GraphQL endpoint URL
+        #[arg(short, long)]
+        target: String,
+
+        /// Query field returning a connection (edges/pageInfo), e.g. users
+        #[arg(short, long, required_unless_present = "query", conflicts_with = "query")]
+        field: Option<String>,
+
+        /// .graphql file with a query to send once per --iterate value
+        #[arg(short, long)]
+        query: Option<PathBuf>,
+
+        /// Values for a variable of --query: id=1..10000, email=@emails.txt or slug=a,b,c (repeat for every combination)
+        #[arg(long, requires = "query")]
+        iterate: Vec<String>,
+
+        /// With --query, only write responses that have data in them
+        #[arg(long, requires = "query")]
+        only_data: bool,
+
+        /// Schema file, introspection JSON or SDL (introspected from the target when omitted)
+        #[arg(short, long)]
+        schema: Option<PathBuf>,
+
+        /// Custom HTTP headers
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
+
+        /// HTTP/HTTPS/SOCKS proxy URL
+        #[arg(short = 'x', long)]
+        proxy: Option<String>,
+
+        /// Stop after this many pages
+        #[arg(long, default_value = "50")]
+        max_pages: usize,
+
+        /// Objects to ask for per page (`first`)
+        #[arg(long, default_value = "100")]
+        page_size: usize,
+
+        /// Fields to select on each object (default: all of its scalar fields), e.g. 'id email role'
+        #[arg(long)]
+        select: Option<String>,
+
+        /// Values for the other arguments: a JSON file, or a directory of <operationName>.json files (can be repeated)
+        #[arg(long)]
+        variables: Vec<PathBuf>,
+
+        /// Send at most this many requests per minute
+        #[arg(long)]
+        per_minute: Option<u32>,
+
+        #[command(flatten)]
+        evasion: EvasionArgs,
+
+        /// JSONL file to write the objects to (stdout when omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum TestsCommand {
+    /// Every scan test with its category, severity, CWE and whether it sends heavy queries
+    List {
+        /// Output format (table, json)
+        #[arg(short, long, default_value = "table", value_parser = ["table", "json"])]
+        format: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum ReportCommand {
+    /// Combine `scan --output json` results into one, a finding per test and endpoint
+    Merge {
+        /// Result files; when the same finding is in several, the newest file's evidence wins
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+
+        /// File to write the merged results to (stdout when omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ApqCommand {
+    /// Print the APQ sha256 of a query document and the extensions that send it
+    Hash {
+        /// .graphql file with the document, exactly as the client sends it
+        #[arg(required_unless_present = "query")]
+        source: Option<PathBuf>,
+
+        /// The document inline instead of a file
+        #[arg(short, long, conflicts_with = "source")]
+        query: Option<String>,
+    },
+
+    /// Send known hashes without their query and list the ones the server has registered
+    Probe {
+        /// Target GraphQL endpoint URL
+        #[arg(short, long)]
+        target: String,
+
+        /// `discover --operations` output, or one hash per line (optionally followed by a name)
+        #[arg(long)]
+        hashes: PathBuf,
+
+        /// Custom HTTP headers
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
+
+        /// HTTP/HTTPS/SOCKS proxy URL
+        #[arg(short = 'x', long)]
+        proxy: Option<String>,
+
+        /// Send hashes as GET query strings instead of POST bodies
+        #[arg(long)]
+        get: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text")]
+        output: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SchemaCommand {
+    /// Compare two schemas and classify breaking changes
+    Diff {
+        /// Older schema file (introspection JSON or SDL)
+        old: PathBuf,
+
+        /// Newer schema file (introspection JSON or SDL)
+        new: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text", value_parser = ["text", "json"])]
+        output: String,
+    },
+
+    /// Compare what two environments expose: operations, types and fields only one has
+    Drift {
+        /// The two GraphQL endpoints, e.g. `-t https://staging/graphql -t https://prod/graphql`
+        #[arg(short, long = "target", required = true, num_args = 1)]
+        targets: Vec<String>,
+
+        /// Custom HTTP headers, sent to both (can be repeated)
+        #[arg(short = 'H', long = "header")]
+        headers: Vec<String>,
+
+        /// HTTP/HTTPS/SOCKS proxy URL
+        #[arg(short = 'x', long)]
+        proxy: Option<String>,
+
+        /// Fail instead of inferring the schema of an endpoint that won't be introspected
+        #[arg(long)]
+        no_infer: bool,
+
+        /// Requests each inference may send
+        #[arg(long, default_value = "2000")]
+        max_requests: usize,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text", value_parser = ["text", "json"])]
+        output: String,
+    },
+
+    /// Offline review of sensitive fields, PII and auth-looking arguments
+    Audit {
+        /// Schema file (introspection JSON or SDL)
+        #[arg(short, long)]
+        schema: PathBuf,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text", value_parser = ["text", "json"])]
+        output: String,
+    },
+
+    /// Check a schema against best-practice rules (pagination, payload types, docs)
+    Lint {
+        /// Schema file (introspection JSON or SDL)
+        #[arg(short, long, required_unless_present = "list_rules")]
+        schema: Option<PathBuf>,
+
+        /// Skip rules by id (comma-separated, e.g. GQL002,GQL003)
+        #[arg(short, long)]
+        exclude: Option<String>,
+
+        /// List available rules
+        #[arg(short, long)]
+        list_rules: bool,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text", value_parser = ["text", "json"])]
+        output: String,
+    },
+
+    /// Report which types and fields a set of queries touched
+    Coverage {
+        /// Schema file (introspection JSON or SDL)
+        #[arg(short, long)]
+        schema: PathBuf,
+
+        /// Query files: .graphql documents, JSON request bodies or JSONL request logs
+        #[arg(required = true)]
+        queries: Vec<PathBuf>,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text", value_parser = ["text", "json"])]
+        output: String,
+    },
+
+    /// Build an inference wordlist from every name in a known schema
+    Wordlist {
+        /// Schema file (introspection JSON or SDL)
+        #[arg(short, long)]
+        schema: PathBuf,
+
+        /// Output file path (prints to stdout when omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Keep names whole instead of also adding their camelCase words
+        #[arg(long)]
+        no_split: bool,
+    },
+
+    /// Render the type graph as Graphviz DOT or Mermaid
+    Graph {
+        /// Schema file (introspection JSON or SDL)
+        #[arg(short, long)]
+        schema: PathBuf,
+
+        /// Graph flavour (dot, mermaid)
+        #[arg(short, long, default_value = "dot", value_parser = ["dot", "mermaid"])]
+        format: String,
+
+        /// Only show types reachable from this type (or query, mutation, subscription)
+        #[arg(short, long)]
+        root: Option<String>,
+
+        /// Maximum hops from --root
+        #[arg(short, long, requires = "root")]
+        depth: Option<usize>,
+
+        /// Output file path (prints to stdout when omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Combine partial introspection, infer output and SDL fragments into one schema
+    Merge {
+        /// Schema files, most trusted first (introspection JSON or SDL)
+        #[arg(required = true, num_args = 2..)]
+        sources: Vec<PathBuf>,
+
+        /// Output file; `.json` writes introspection JSON, anything else SDL
+        #[arg(short, long)]
+        output: PathBuf,
+    },
+
+    /// List recursive type cycles (A -> B -> A) and how to reach them
+    Cycles {
+        /// Schema file (introspection JSON or SDL)
+        #[arg(short, long)]
+        schema: PathBuf,
+
+        /// Longest cycle to look for, in hops
+        #[arg(long, default_value = "5")]
+        max_length: usize,
+
+        /// Stop after this many cycles
+        #[arg(long, default_value = "500")]
+        limit: usize,
+
+        /// Output format (text, json)
+        #[arg(short, long, default_value = "text", value_parser = ["text", "json"])]
+        output: String,
+    },
+
+    /// Rebuild a partial schema from captured traffic (HAR files, Burp XML exports)
+    FromTraffic {
+        /// HAR files or Burp Suite XML exports of proxy history
+        #[arg(required = true)]
+        captures: Vec<PathBuf>,
+
+        /// Output file; `.json` writes introspection JSON, anything else SDL (prints SDL when omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+
+    /// Convert between introspection JSON and SDL
+    Convert {
+        /// Schema file to read (introspection JSON or SDL)
+        #[arg(long = "in")]
+        input: PathBuf,
+
+        /// File to write (prints to stdout when omitted)
+        #[arg(long = "out")]
+        output: Option<PathBuf>,
+
+        /// Target format (sdl, json); defaults to the --out extension, else the opposite of --in
+        #[arg(long, value_parser = ["sdl", "json"])]
+        to: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ExportFormat {
+    /// Export to Bruno collection
+    Bruno {
+        #[command(flatten)]
+        source: SchemaSourceArgs,
+
+        /// Output directory for Bruno collection
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Base URL for requests (defaults to --target)
+        #[arg(short, long, required_unless_present = "target")]
+        url: Option<String>,
+
+        /// Organize operations into sub-folders (none, type, tag, prefix)
+        #[arg(long, default_value = "none", value_parser = ["none", "type", "tag", "prefix"])]
+        group_by: String,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+
+    /// Export to Postman collection
+    Postman {
+        #[command(flatten)]
+        source: SchemaSourceArgs,
+
+        /// Output JSON file path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Base URL for requests (defaults to --target)
+        #[arg(short, long, required_unless_present = "target")]
+        url: Option<String>,
+
+        /// Organize operations into sub-folders (none, type, tag, prefix)
+        #[arg(long, default_value = "none", value_parser = ["none", "type", "tag", "prefix"])]
+        group_by: String,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+
+        /// Output path for the Postman environment file (defaults next to the collection)
+        #[arg(short, long)]
+        environment: Option<PathBuf>,
+
+        /// Collection-level auth (none, bearer, apikey)
+        #[arg(long, default_value = "none", value_parser = ["none", "bearer", "apikey"])]
+        auth_type: String,
+
+        /// Header name used for apikey auth
+        #[arg(long, default_value = "X-API-Key")]
+        api_key_header: String,
+
+        /// Initial value for the {{token}} environment variable
+        #[arg(long)]
+        token: Option<String>,
+    },
+
+    /// Export to executable cURL script
+    Curl {
+        #[command(flatten)]
+        source: SchemaSourceArgs,
+
+        /// Output shell script path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Base URL for requests (defaults to --target)
+        #[arg(short, long, required_unless_present = "target")]
+        url: Option<String>,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+
+    /// Export to executable HTTPie script
+    Httpie {
+        #[command(flatten)]
+        source: SchemaSourceArgs,
+
+        /// Output shell script path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Base URL for requests (defaults to --target)
+        #[arg(short, long, required_unless_present = "target")]
+        url: Option<String>,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+
+    /// Export to Burp Suite items XML (raw HTTP requests)
+    Burp {
+        #[command(flatten)]
+        source: SchemaSourceArgs,
+
+        /// Output XML file path
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Base URL for requests (defaults to --target)
+        #[arg(short, long, required_unless_present = "target")]
+        url: Option<String>,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+
+    /// Export to InQL/Burp format (GraphQL files)
+    Inql {
+        #[command(flatten)]
+        source: SchemaSourceArgs,
+
+        /// Output directory for GraphQL files
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Base URL for requests (defaults to --target)
+        #[arg(short, long, required_unless_present = "target")]
+        url: Option<String>,
+
+        /// Organize operations into sub-folders (none, type, tag, prefix)
+        #[arg(long, default_value = "none", value_parser = ["none", "type", "tag", "prefix"])]
+        group_by: String,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+
+    /// Export a JSON catalog of every operation (arguments, return types, examples)
+    Catalog {
+        #[command(flatten)]
+        source: SchemaSourceArgs,
+
+        /// Output file path (prints to stdout when omitted)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// How many levels of each return type to expand
+        #[arg(long, default_value = "3")]
+        depth: usize,
+
+        #[command(flatten)]
+        filter: FilterArgs,
+    },
+
+    /// Export a graphql-config project (.graphqlrc.yml and the SDL) for IDE plugins and codegen
+    GraphqlConfig {
+        #[command(flatten)]
+        source: SchemaSourceArgs,
+
+        /// Output directory for the project
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Endpoint URL for the config (defaults to --target)
+        #[arg(short, long, required_unless_present = "target")]
+        url: Option<String>,
+    },
+
+    /// Generate graphql-armor / Apollo Router limits from scan findings and the schema
+    ArmorConfig {
+        /// Path to schema file (introspection JSON or SDL)
+        #[arg(short, long, required_unless_present = "findings")]
+        schema: Option<PathBuf>,
+
+        /// Scan results saved from `scan --output json`
+        #[arg(short, long)]
+        findings: Option<PathBuf>,
+
+        /// Config flavour (armor, router)
+        #[arg(long, default_value = "armor", value_parser = ["armor", "router"])]
+        format: String,
+
+        /// Output file path
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Where an export reads its schema from
+#[derive(Args)]
+struct SchemaSourceArgs {
+    /// Path to schema file, introspection JSON or SDL (with --target, used when introspection fails)
+    #[arg(short, long, required_unless_present = "target")]
+    schema: Option<PathBuf>,
+
+    /// Introspect this GraphQL endpoint instead of reading a schema file
+    #[arg(short, long)]
+    target: Option<String>,
+
+    /// Custom HTTP headers, used for --target and embedded in exported requests (can be repeated)
+    #[arg(short = 'H', long = "header")]
+    headers: Vec<String>,
+
+    /// Authorization header value embedded in exported requests (bare tokens become `Bearer <token>`)
+    #[arg(short, long)]
+    auth: Option<String>,
+
+    /// HTTP/HTTPS/SOCKS proxy URL for --target
+    #[arg(short = 'x', long)]
+    proxy: Option<String>,
+}
+
+/// Operation selection shared by every export format
+#[derive(Args)]
+struct FilterArgs {
+    /// Only export operations whose name matches this glob (or regex with a `re:` prefix)
+    #[arg(long)]
+    filter: Option<String>,
+
+    /// Only export these operation kinds (comma-separated: queries, mutations, subscriptions)
+    #[arg(long, value_delimiter = ',', value_parser = ["queries", "mutations", "subscriptions"])]
+    only: Vec<String>,
+}
+
+/// Which mutations a module that sends them may run
+#[derive(Args)]
+struct GuardArgs {
+    /// Run mutations too: the read-like and create ones, updates and deletes need --unsafe or --allowlist
+    #[arg(long)]
+    mutations: bool,
+
+    /// Also run mutations that look like updates or deletes
+    #[arg(long = "unsafe", requires = "mutations")]
+    allow_unsafe: bool,
+
+    /// File of mutations to run whatever they look like: one name, glob or `re:` regex per line
+    #[arg(long, requires = "mutations")]
+    allowlist: Option<PathBuf>,
+}
+
+/// Rewrites that get requests past filters matching on their text
+#[derive(Args)]
+struct EvasionArgs {
+    /// Rewrite every query with equivalent forms (comma-separated: whitespace, comments, unicode, newlines, or all)
+    #[arg(long, value_delimiter = ',')]
+    evasion: Vec<String>,
+
+    /// Random aliases and operation names, and fields wrapped in an inline fragment, so known payloads match no signature
+    #[arg(long)]
+    obfuscate: bool,
+
+    /// Slow and irregular: --delay 1000-5000, --burst 10 and, for scan, --shuffle
+    #[arg(long)]
+    stealth: bool,
+
+    /// Wait before every request, in milliseconds: 1500, or a range to pick from at random like 500-3000
+    #[arg(long)]
+    delay: Option<String>,
+
+    /// Pause after this many requests in a row
+    #[arg(long)]
+    burst: Option<usize>,
+
+    /// Seconds to pause between bursts
+    #[arg(long, default_value = "30")]
+    burst_pause: u64,
+
+    /// Vary header order and name casing, and rotate Accept and Accept-Language, on every request
+    #[arg(long)]
+    random_headers: bool,
+
+    /// Send bodies with Transfer-Encoding: chunked instead of a Content-Length, in chunks of this many bytes (16, or a random size in a range like 1-32)
+    #[arg(long, value_name = "SIZE")]
+    chunked: Option<String>,
+}
+
+impl EvasionArgs {
+    fn apply(&self, client: HttpClient) -> Result<HttpClient> {
+        let mut stealth = Stealth::default();
+        let delay = match &self.delay {
+            Some(spec) => Some(Stealth::parse_delay(spec)?),
+            None if self.stealth => Some((Duration::from_secs(1), Duration::from_secs(5))),
+            None => None,
+        };
+        if let Some((min, max)) = delay {
+            stealth = stealth.with_delay(min, max);
+        }
+        let burst = self.burst.or(if self.stealth { Some(10) } else { None });
+        if let Some(size) = burst {
+            stealth = stealth.with_burst(size, Duration::from_secs(self.burst_pause));
+        }
+        let mut client = client
+            .with_evasion(Evasion::from_names(&self.evasion)?)
+            .with_stealth(stealth);
+        if self.obfuscate {
+            client = client.with_obfuscation();
+        }
+        if let Some(spec) = &self.chunked {
+            client = client.with_chunking(Chunking::parse(spec)?);
+        }
+        if self.random_headers {
+            return client.with_random_headers();
+        }
+        Ok(client)
+    }
+}
+
+/// Where blind payloads call back to
+#[derive(Args)]
+struct OobArgs {
+    /// Callback server for blind payloads: `interactsh`, an interactsh server URL, or a domain you watch yourself
+    #[arg(long)]
+    oob: Option<String>,
+
+    /// Seconds to wait for late callbacks after the last request
+    #[arg(long, default_value = "10", requires = "oob")]
+    oob_wait: u64,
+
+    /// Authorization token for a private interactsh server
+    #[arg(long, requires = "oob")]
+    oob_token: Option<String>,
+}
+
+impl OobArgs {
+    /// Register with the callback server, if there is one. The target's headers stay out of it.
+    async fn connect(&self, proxy: Option<&str>) -> Result<Option<OobClient>> {
+        let spec = match &self.oob {
+            Some(spec) => spec,
+            None => return Ok(None),
+        };
+        let mut headers = HashMap::new();
+        if let Some(token) = &self.oob_token {
+            headers.insert("Authorization".to_string(), token.clone());
+        }
+        let client = HttpClient::new(proxy, headers, false)?;
+        Ok(Some(OobClient::from_spec(client, spec).await?))
+    }
+}
+
+impl GuardArgs {
+    fn build(&self) -> Result<MutationGuard> {
+        let guard = MutationGuard::new(self.allow_unsafe);
+        match &self.allowlist {
+            Some(path) => guard.with_allowlist(path),
+            None => Ok(guard),
+        }
+    }
+}
+
+impl FilterArgs {
+    fn build(&self) -> Result<OperationFilter> {
+        OperationFilter::new(self.filter.as_deref(), &self.only)
+    }
+}
+
+fn parse_headers(headers: &[String]) -> Result<HashMap<String, String>> {
+    let mut map = HashMap::new();
+
+    for header in headers {
+        // Try JSON format first: {"Authorization": "Bearer token"}
+        if header.starts_with('{') {
+            let parsed: HashMap<String, String> =
+                serde_json::from_str(header).context("Invalid JSON header format")?;
+            for (key, value) in parsed {
+                map.insert(key, interpolate(&value)?);
+            }
+        } else if let Some((key, value)) = header.split_once(':') {
+            // Standard format: "Authorization: Bearer token"
+            map.insert(key.trim().to_string(), interpolate(value.trim())?);
+        } else {
+            bail!("Invalid header format: {}", header);
+        }
+    }
+
+    Ok(map)
+}
+
+fn print_result(result: &TestResult) {
+    if !result.vulnerable {
+        return;
+    }
+
+    let severity = match result.severity {
+        Severity::High => format!("[{}]", result.severity).red().bold(),
+        Severity::Medium => format!("[{}]", result.severity).yellow().bold(),
+        Severity::Low => format!("[{}]", result.severity).blue().bold(),
+        Severity::Info => format!("[{}]", result.severity).green().bold(),
+    };
+
+    println!(
+        "{} {} - {}",
+        severity,
+        result.title.bold(),
+        result.description
+    );
+    println!("    Impact: {}", result.impact);
+    if let Some(evidence) = &result.evidence {
+        println!("    Evidence: {}", evidence);
+    }
+    if result.confidence == FindingConfidence::Verified {
+        println!("    Confidence: {}", result.confidence.to_string().green());
+    }
+    println!("    Verify: {}", result.curl_command.dimmed());
+    println!();
+}
+
+/// Print what the watchdog did, as the progress event to emit for it
+fn watchdog_event(url: &str, event: WatchdogEvent) -> ProgressEvent {
+    let (action, reason) = match event {
+        WatchdogEvent::Paused { reason, wait } => {
+            println!("{} Watchdog: {}, pausing {}s", "[!]".yellow(), reason, wait.as_secs());
+            ("pause", Some(reason))
+        }
+        WatchdogEvent::Resumed => {
+            println!("{} Watchdog: target recovered, resuming", "[*]".cyan());
+            ("resume", None)
+        }
+        WatchdogEvent::Aborted { reason } => {
+            println!("{} Watchdog: {}, no more aggressive tests on {}", "[-]".red(), reason, url);
+            ("abort", Some(reason))
+        }
+    };
+    ProgressEvent::Watchdog {
+        target: url.to_string(),
+        action: action.to_string(),
+        reason,
+    }
+}
+
+/// A result for `test` that didn't run, the reason where the command would go
+fn skipped_result(test: &dyn SecurityTest, url: &str, reason: &str) -> TestResult {
+    TestResult {
+        name: test.name().to_string(),
+        url: url.to_string(),
+        title: test.title().to_string(),
+        description: test.description().to_string(),
+        impact: test.impact().to_string(),
+        severity: test.severity(),
+        vulnerable: false,
+        curl_command: reason.to_string(),
+        evidence: None,
+        confidence: FindingConfidence::Heuristic,
+    }
+}
+
+/// Run `test`'s impact check for `--verify-dos` and add what it measured to `result`
+async fn verify_impact(
+    client: &HttpClient,
+    url: &str,
+    test: &dyn SecurityTest,
+    context: &ScanContext,
+    result: &mut TestResult,
+) {
+    let Some(baseline) = context.baseline() else {
+        log_at(1, &format!("Not verifying {}: no baseline for {}", test.name(), url));
+        return;
+    };
+    let Some(payload) = test.impact_payload(client, url, context).await else {
+        log_at(1, &format!("Not verifying {}: it has no payload to scale", test.name()));
+        return;
+    };
+    println!("{} Verifying {}...", "[*]".cyan(), test.name());
+    let check = ImpactCheck::run(client, url, test.name(), &payload, baseline).await;
+    if check.verified() {
+        result.confidence = FindingConfidence::Verified;
+    }
+    result.evidence = Some(match result.evidence.take() {
+        Some(evidence) => format!("{}; {}", evidence, check.evidence()),
+        None => check.evidence(),
+    });
+}
+
+fn print_results_json(results: &[TestResult]) {
+    let output = serde_json::to_string_pretty(results).unwrap_or_default();
+    println!("{}", output);
+}
+
+async fn run_tests_list(format: String) -> Result<()> {
+    let tests: Vec<TestInfo> = all_tests().iter().map(|t| TestInfo::of(t.as_ref())).collect();
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&tests)?);
+        return Ok(());
+    }
+
+    print_banner();
+    let mut table = tabled::builder::Builder::default();
+    table.push_record(["NAME", "CATEGORY", "SEVERITY", "CWE", "HEAVY", "OPT-IN", "DESCRIPTION"]);
+    for test in &tests {
+        table.push_record([
+            test.name.to_string(),
+            test.category.to_string(),
+            test.severity.to_string(),
+            test.cwe.clone(),
+            if test.heavy { "yes" } else { "" }.to_string(),
+            if test.opt_in { "yes" } else { "" }.to_string(),
+            test.description.to_string(),
+        ]);
+    }
+    println!("{}", table.build().with(tabled::settings::Style::rounded()));
+    Ok(())
+}
+
+async fn run_report_merge(files: Vec<PathBuf>, output: Option<PathBuf>) -> Result<()> {
+    let mut runs = Vec::new();
+    for path in &files {
+        let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let results = parse_results_json(&content).with_context(|| format!("Failed to parse {}", path.display()))?;
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        runs.push((modified, results));
+    }
+    // Oldest first, so the newest evidence is merged in last
+    runs.sort_by_key(|(modified, _)| *modified);
+
+    let total: usize = runs.iter().map(|(_, results)| results.len()).sum();
+    let merged = merge_results(runs.into_iter().map(|(_, results)| results).collect());
+    let json = serde_json::to_string_pretty(&merged)?;
+
+    match output {
+        Some(path) => {
+            print_banner();
+            std::fs::write(&path, json)?;
+            println!(
+                "{} Merged {} results from {} files into {} ({} vulnerable), saved to {}",
+                "[+]".green(),
+                total,
+                files.len(),
+                merged.len(),
+                merged.iter().filter(|r| r.vulnerable).count(),
+                path.display()
+            );
+        }
+        None => println!("{}", json),
+    }
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_scan(
+    target: Option<String>,
+    targets_file: Option<PathBuf>,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    output: String,
+    exclude: Option<String>,
+    include: Option<String>,
+    debug: bool,
+    force: bool,
+    discover: bool,
+    wordlist: Option<PathBuf>,
+    list_tests: bool,
+    coverage: Option<PathBuf>,
+    schema_file: Option<PathBuf>,
+    known_account: Option<String>,
+    verify_dos: bool,
+    no_watchdog: bool,
+    shuffle: bool,
+    evasion: EvasionArgs,
+    progress: Option<Progress>,
+    push_metrics: Option<String>,
+) -> Result<()> {
+    if list_tests {
+        return run_tests_list("table".to_string()).await;
+    }
+    let tests = all_tests();
+
+    print_banner();
+
+    let headers_map = parse_headers(&headers)?;
+    let build_client = |proxy: Option<&str>, headers: HashMap<String, String>| -> Result<HttpClient> {
+        let client = evasion.apply(HttpClient::new(proxy, headers, debug)?)?;
+        Ok(if coverage.is_some() { client.with_query_log() } else { client })
+    };
+    let default_client = build_client(proxy.as_deref(), headers_map.clone())?;
+    let given_schema = schema_file.map(|p| load_schema_file(&p).map(Arc::new)).transpose()?;
+
+    let excluded: Vec<&str> = exclude
+        .as_deref()
+        .map(|e| e.split(',').map(|s| s.trim()).collect())
+        .unwrap_or_default();
+    let included: Vec<&str> = include
+        .as_deref()
+        .map(|i| i.split(',').map(|s| s.trim()).collect())
+        .unwrap_or_default();
+
+    let emit = |event: ProgressEvent, requests: usize, findings: usize| {
+        if let Some(progress) = &progress {
+            progress.emit(&event, requests, findings);
+        }
+    };
+    let mut findings = 0;
+
+    // Determine target URLs
+    let targets: Vec<Target> = if let Some(path) = &targets_file {
+        let targets = read_targets(path)?;
+        if targets.is_empty() {
+            bail!("No targets in {}", path.display());
+        }
+        println!("{} Scanning {} target(s) from {}\n", "[*]".cyan(), targets.len(), path.display());
+        targets
+    } else if discover {
+        println!("{} Discovering GraphQL endpoints...\n", "[*]".cyan());
+        emit(ProgressEvent::Phase { phase: "discovery".to_string(), target: None }, 0, 0);
+
+        let custom_paths = wordlist
+            .map(|p| load_wordlist(p.to_str().unwrap()))
+            .transpose()?;
+
+        let discovery = EndpointDiscovery::new(target.as_deref().unwrap_or_default(), custom_paths)?;
+        let found = discovery.discover(&default_client).await;
+
+        if found.is_empty() {
+            println!("{} No GraphQL endpoints found", "[-]".red());
+            return Ok(());
+        }
+
+        println!("{} Found {} endpoint(s):\n", "[+]".green(), found.len());
+        for url in &found {
+            println!("    {}", url);
+        }
+        println!();
+
+        found.into_iter().map(Target::url).collect()
+    } else {
+        target.into_iter().map(Target::url).collect()
+    };
+    let target_count = targets.len();
+    // Requests sent for targets already done (and discovery)
+    let mut requests_done = default_client.requests_sent();
+
+    for (index, target) in targets.into_iter().enumerate() {
+        let url = target.url.clone();
+        println!("{} Target: {}\n", "[*]".cyan(), url);
+        let client = if target.has_overrides() {
+            let proxy = target.proxy.as_deref().or(proxy.as_deref());
+            build_client(proxy, target.merge_headers(&headers_map))?
+        } else {
+            default_client.clone()
+        };
+        // Discovery and earlier targets don't count towards this target's coverage
+        client.take_logged_queries();
+        let start = client.requests_sent();
+        let requests = || requests_done + client.requests_sent() - start;
+        let started = Instant::now();
+        let mut metrics = ScanMetrics::new(&url);
+
+        // Check if GraphQL endpoint
+        if !force {
+            emit(ProgressEvent::Phase { phase: "detection".to_string(), target: Some(url.clone()) }, requests(), findings);
+            let skipped = match is_graphql_endpoint(&client, &url).await {
+                Ok(true) => {
+                    println!("{} GraphQL endpoint detected\n", "[+]".green());
+                    None
+                }
+                Ok(false) => {
+                    println!(
+                        "{} GraphQL not detected at this URL (use -f to force)",
                         "[-]".red()
                     );
-                    continue;
+                    Some("GraphQL not detected".to_string())
                 }
                 Err(e) => {
                     println!("{} Detection failed: {}", "[-]".red(), e);
+                    Some(format!("Detection failed: {}", e))
+                }
+            };
+            if let Some(reason) = skipped {
+                emit(ProgressEvent::TargetSkipped { target: url.clone(), reason }, requests(), findings);
+                if let Some(gateway) = &push_metrics {
+                    metrics.requests = client.requests_sent() - start;
+                    metrics.duration = started.elapsed();
+                    push_scan_metrics(gateway, &metrics).await;
+                }
+                requests_done = requests();
+                continue;
+            }
+        }
+
+        // Run tests
+        let mut results = Vec::new();
+        let mut active_tests: Vec<&dyn SecurityTest> = tests
+            .iter()
+            .map(|t| t.as_ref())
+            .filter(|t| !excluded.contains(&t.name()))
+            .filter(|t| !t.opt_in() || included.contains(&t.name()))
+            .collect();
+        if shuffle {
+            gqlmap::http::shuffle(&mut active_tests);
+        }
+        // Shuffled or not, tests still run after those whose findings they use
+        let active_tests = order_tests(active_tests);
+        let mut context = match &given_schema {
+            Some(schema) => ScanContext::default().with_schema(schema.clone()),
+            None => ScanContext::default(),
+        };
+        if let Some(account) = &known_account {
+            context = context.with_known_account(account.clone());
+        }
+
+        emit(ProgressEvent::Phase { phase: "baseline".to_string(), target: Some(url.clone()) }, requests(), findings);
+        match Baseline::measure(&client, &url).await {
+            Some(baseline) => {
+                println!(
+                    "{} Baseline: {}ms median, {}ms slowest",
+                    "[*]".cyan(),
+                    baseline.median.as_millis(),
+                    baseline.slowest.as_millis()
+                );
+                context = context.with_baseline(baseline);
+            }
+            None => log_at(1, &format!("No baseline for {}: a warm-up query failed", url)),
+        }
+        let mut watchdog = (!no_watchdog).then(|| Watchdog::new(&client, context.baseline().copied()));
+
+        println!(
+            "{} Running {} security tests...\n",
+            "[*]".cyan(),
+            active_tests.len()
+        );
+
+        emit(ProgressEvent::Phase { phase: "tests".to_string(), target: Some(url.clone()) }, requests(), findings);
+        let total = active_tests.len();
+        for (position, test) in active_tests.into_iter().enumerate() {
+            if let (true, Some(watchdog)) = (test.heavy(), watchdog.as_mut()) {
+                if !watchdog.clear(&client, &url, |event| emit(watchdog_event(&url, event), requests(), findings)).await {
+                    let reason = watchdog.aborted().unwrap_or_default();
+                    log_at(1, &format!("Skipping {}: the watchdog stopped aggressive tests ({})", test.name(), reason));
+                    results.push(skipped_result(test, &url, &format!("Skipped by the watchdog: {}", reason)));
                     continue;
                 }
             }
+            emit(
+                ProgressEvent::TestStarted {
+                    target: url.clone(),
+                    test: test.name().to_string(),
+                    index: position + 1,
+                    total,
+                },
+                requests(),
+                findings,
+            );
+            client.take_slowest();
+            let outcome = test.run(&client, &url, &context).await;
+            let slowest = client.take_slowest();
+            let (vulnerable, error) = match outcome {
+                Ok(mut result) => {
+                    if let (true, Some(slowest)) = (result.vulnerable, slowest) {
+                        let latency = match context.baseline() {
+                            Some(baseline) => format!("slowest response {}", baseline.compare(slowest)),
+                            None => format!("slowest response {}ms", slowest.as_millis()),
+                        };
+                        result.evidence = Some(match result.evidence.take() {
+                            Some(evidence) => format!("{}; {}", evidence, latency),
+                            None => latency,
+                        });
+                    }
+                    if verify_dos && result.vulnerable && test.category() == Category::Dos {
+                        let clear = match watchdog.as_mut() {
+                            Some(watchdog) => {
+                                watchdog
+                                    .clear(&client, &url, |event| emit(watchdog_event(&url, event), requests(), findings))
+                                    .await
+                            }
+                            None => true,
+                        };
+                        if clear {
+                            verify_impact(&client, &url, test, &context, &mut result).await;
+                        }
+                    }
+                    let vulnerable = result.vulnerable;
+                    results.push(result);
+                    (vulnerable, None)
+                }
+                Err(e) => {
+                    if debug {
+                        eprintln!("{} Test {} failed: {}", "[-]".red(), test.name(), e);
+                    } else {
+                        log_at(1, &format!("Test {} failed: {:#}", test.name(), e));
+                    }
+                    (false, Some(e.to_string()))
+                }
+            };
+            if let Some(watchdog) = watchdog.as_mut() {
+                watchdog.observe(&client);
+            }
+            if vulnerable {
+                findings += 1;
+            }
+            if error.is_some() {
+                metrics.test_errors += 1;
+            }
+            emit(
+                ProgressEvent::TestFinished {
+                    target: url.clone(),
+                    test: test.name().to_string(),
+                    vulnerable,
+                    error,
+                },
+                requests(),
+                findings,
+            );
+        }
+
+        log_at(1, &format!("What the tests found out about {}: {}", url, serde_json::to_string(&context.facts())?));
+
+        if let Some(gateway) = &push_metrics {
+            metrics.up = true;
+            metrics.tests = total;
+            metrics.requests = client.requests_sent() - start;
+            metrics.duration = started.elapsed();
+            push_scan_metrics(gateway, &metrics.with_results(&results)).await;
+        }
+
+        if let Some(path) = &coverage {
+            emit(ProgressEvent::Phase { phase: "coverage".to_string(), target: Some(url.clone()) }, requests(), findings);
+            let queries = client.take_logged_queries();
+            match context.schema(&client, &url).await {
+                Some(schema) => {
+                    let report = schema_coverage(&schema, &queries);
+                    let path = if target_count > 1 {
+                        numbered_path(path, index + 1)
+                    } else {
+                        path.clone()
+                    };
+                    std::fs::write(&path, serde_json::to_string_pretty(&report)?)?;
+                    print_coverage_summary(&report);
+                    println!("{} Coverage report saved to {}\n", "[+]".green(), path.display());
+                }
+                None => println!(
+                    "{} No schema for coverage (introspection failed, pass --schema)\n",
+                    "[-]".red()
+                ),
+            }
+        }
+
+        // Sort by severity
+        results.sort_by(|a, b| {
+            let severity_order = |s: &Severity| match s {
+                Severity::High => 0,
+                Severity::Medium => 1,
+                Severity::Low => 2,
+                Severity::Info => 3,
+            };
+            severity_order(&a.severity).cmp(&severity_order(&b.severity))
+        });
+
+        // Output results
+        match output.as_str() {
+            "json" => print_results_json(&results),
+            _ => {
+                let vulnerable_count = results.iter().filter(|r| r.vulnerable).count();
+
+                if vulnerable_count == 0 {
+                    println!("{} No vulnerabilities found", "[+]".green());
+                } else {
+                    println!(
+                        "{} Found {} issue(s):\n",
+                        "[!]".yellow(),
+                        vulnerable_count
+                    );
+                    for result in &results {
+                        print_result(result);
+                    }
+                }
+            }
+        }
+        requests_done = requests();
+    }
+    emit(ProgressEvent::Finished { targets: target_count }, requests_done, findings);
+
+    Ok(())
+}
+
+async fn push_scan_metrics(gateway: &str, metrics: &ScanMetrics) {
+    match metrics.push(gateway).await {
+        Ok(()) => log_at(1, &format!("Metrics for {} pushed to {}", metrics.target, gateway)),
+        Err(e) => println!("{} {:#}", "[!]".yellow(), e),
+    }
+}
+
+/// An endpoint from a targets file, with what it needs that the others don't
+#[derive(Debug, Clone, Default)]
+struct Target {
+    url: String,
+    headers: HashMap<String, String>,
+    proxy: Option<String>,
+}
+
+impl Target {
+    fn url(url: String) -> Self {
+        Self {
+            url,
+            ..Default::default()
+        }
+    }
+
+    /// `url -H 'Name: value' -x proxy --auth file.headers`; everything after the URL is
+    /// optional and quoted the way a shell would
+    fn parse(line: &str) -> Result<Self> {
+        let words = split_words(line).with_context(|| format!("Unbalanced quotes in target: {}", line))?;
+        let mut words = words.into_iter();
+        let mut target = Self::url(words.next().unwrap_or_default());
+        while let Some(flag) = words.next() {
+            let value = words
+                .next()
+                .with_context(|| format!("{} needs a value in target: {}", flag, line))?;
+            match flag.as_str() {
+                "-H" | "--header" => {
+                    target.headers.extend(parse_headers(std::slice::from_ref(&value))?);
+                }
+                "-x" | "--proxy" => target.proxy = Some(interpolate(&value)?),
+                "--auth" => target.headers.extend(AuthContext::load(&value)?.headers),
+                other => bail!("Unknown option {} in target: {} (-H, -x or --auth)", other, line),
+            }
+        }
+        Ok(target)
+    }
+
+    fn has_overrides(&self) -> bool {
+        !self.headers.is_empty() || self.proxy.is_some()
+    }
+
+    /// `headers` with this target's on top, whatever the case of their names
+    fn merge_headers(&self, headers: &HashMap<String, String>) -> HashMap<String, String> {
+        let mut merged: HashMap<String, String> = headers
+            .iter()
+            .filter(|(key, _)| !self.headers.keys().any(|k| k.eq_ignore_ascii_case(key)))
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect();
+        merged.extend(self.headers.clone());
+        merged
+    }
+}
+
+/// Targets one per line, `-` for stdin; blank lines and `#` comments are skipped
+fn read_targets(path: &Path) -> Result<Vec<Target>> {
+    let content = if path == Path::new("-") {
+        std::io::read_to_string(std::io::stdin()).context("Failed to read targets from stdin")?
+    } else {
+        std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?
+    };
+    content
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(Target::parse)
+        .collect()
+}
+
+/// `line` split on whitespace, with single and double quotes holding words together.
+/// None when a quote isn't closed.
+fn split_words(line: &str) -> Option<Vec<String>> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quote: Option<char> = None;
+    for c in line.chars() {
+        match quote {
+            Some(q) if c == q => quote = None,
+            Some(_) => word.get_or_insert_with(String::new).push(c),
+            None if c == '"' || c == '\'' => {
+                quote = Some(c);
+                word.get_or_insert_with(String::new);
+            }
+            None if c.is_whitespace() => words.extend(word.take()),
+            None => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    if quote.is_some() {
+        return None;
+    }
+    words.extend(word);
+    Some(words)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_discover(
+    target: String,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    wordlist: Option<PathBuf>,
+    output: String,
+    all: bool,
+    bundles: bool,
+    operations: Option<PathBuf>,
+    subdomains: Option<PathBuf>,
+    ports: Option<Vec<u16>>,
+    paths: Vec<String>,
+    websocket: bool,
+    combine: Option<usize>,
+    passive: bool,
+    wayback: bool,
+) -> Result<()> {
+    let headers_map = parse_headers(&headers)?;
+    let client = HttpClient::new(proxy.as_deref(), headers_map, false)?;
+    let custom_paths = wordlist
+        .map(|p| load_wordlist(p.to_str().unwrap()))
+        .transpose()?;
+    let mut discovery = EndpointDiscovery::new(&target, custom_paths)?;
+    for name in &paths {
+        let pack = path_pack(name).with_context(|| {
+            format!("Unknown path pack '{}' (known: {})", name, path_pack_names().join(", "))
+        })?;
+        discovery = discovery.with_paths(pack);
+    }
+    discovery = discovery.with_websockets(websocket);
+    if let Some(max_paths) = combine {
+        discovery = discovery.with_combinations(max_paths);
+    }
+
+    if output == "text" {
+        print_banner();
+    }
+    if passive {
+        // The archive gets no auth headers
+        let archive = if wayback {
+            Some(HttpClient::new(proxy.as_deref(), HashMap::new(), false)?)
+        } else {
+            None
+        };
+        let seeds = passive_seeds(&client, &target, archive.as_ref()).await?;
+        if output == "text" {
+            println!(
+                "{} Passive: {} paths from robots.txt, {} from sitemaps{}",
+                "[*]".cyan(),
+                seeds.robots.len(),
+                seeds.sitemap.len(),
+                if wayback { format!(", {} from the Wayback Machine", seeds.wayback.len()) } else { String::new() }
+            );
+        }
+        discovery = discovery.with_paths(&seeds.paths());
+    }
+    if bundles {
+        let harvest = harvest_words(&client, &target).await?;
+        if output == "text" {
+            println!(
+                "{} Read {} scripts: {} endpoint URLs, {} documents, {} operations, {} persisted-query hashes",
+                "[*]".cyan(),
+                harvest.scripts,
+                harvest.endpoints.len(),
+                harvest.queries.len(),
+                harvest.operations.len(),
+                harvest.persisted_hashes.len()
+            );
+        }
+        if let Some(path) = &operations {
+            let saved = serde_json::json!({
+                "endpoints": harvest.endpoints,
+                "operations": harvest.operations,
+                "queries": harvest.queries,
+                "persisted_hashes": harvest.persisted_hashes,
+            });
+            std::fs::write(path, serde_json::to_string_pretty(&saved)?)?;
+            if output == "text" {
+                println!("{} Bundle findings saved to {}", "[+]".green(), path.display());
+            }
+        }
+        discovery = discovery.with_urls(harvest.endpoints);
+    }
+    if let Some(path) = subdomains {
+        let words: Vec<String> = std::fs::read_to_string(&path)?.lines().map(String::from).collect();
+        let hosts = resolve_subdomains(&target, &words).await?;
+        if output == "text" {
+            println!(
+                "{} {} of {} subdomains resolve{}",
+                "[*]".cyan(),
+                hosts.len(),
+                words.len(),
+                if hosts.is_empty() {
+                    String::new()
+                } else {
+                    format!(": {}", hosts.iter().filter_map(|h| h.host_str()).collect::<Vec<_>>().join(", "))
+                }
+            );
+        }
+        discovery = discovery.with_hosts(hosts);
+    }
+    if let Some(ports) = ports {
+        let ports = if ports.is_empty() { DEV_PORTS.to_vec() } else { ports };
+        discovery = discovery.with_ports(ports);
+    }
+
+    if output != "text" {
+        let mut candidates: Vec<EndpointCandidate> = discovery
+            .probe(&client)
+            .await
+            .into_iter()
+            .filter(|c| all || c.graphql)
+            .collect();
+        candidates.sort_by_key(|c| std::cmp::Reverse(c.confidence));
+        if output == "json" {
+            println!("{}", serde_json::to_string_pretty(&candidates)?);
+        } else {
+            // Scans need an HTTP endpoint
+            for candidate in candidates.iter().filter(|c| c.graphql && c.subprotocol.is_none()) {
+                println!("{}", candidate.url);
+            }
+        }
+        return Ok(());
+    }
+
+    println!("{} Discovering GraphQL endpoints on {}...\n", "[*]".cyan(), target);
+
+    // Most certain first, so the list doubles as a scan order
+    let mut candidates = discovery.probe(&client).await;
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.confidence));
+    for candidate in candidates.iter().filter(|c| all || c.graphql) {
+        let status = candidate
+            .status
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| "---".to_string());
+        let mut details = Vec::new();
+        if let Some(root) = &candidate.root_type {
+            details.push(format!("root {}", root));
+        }
+        if let Some(engine) = &candidate.engine {
+            details.push(engine.clone());
+        }
+        if !candidate.transports.is_empty() && candidate.transports.first() != Some(&Transport::Json) {
+            let labels: Vec<&str> = candidate.transports.iter().map(|t| t.label()).collect();
+            details.push(format!("only {}", labels.join(" / ")));
+        }
+        // Only heuristics to go on, say which
+        if candidate.transports.is_empty() && candidate.subprotocol.is_none() {
+            details.extend(candidate.signals.iter().map(|s| s.name.clone()));
+        }
+        if let Some(protocol) = &candidate.subprotocol {
+            details.push(protocol.clone());
+            let http = http_url(&candidate.url);
+            if candidate.graphql && !candidates.iter().any(|c| c.graphql && c.url == http) {
+                details.push("subscriptions only".to_string());
+            }
+        }
+        if candidate.source == "bundle" {
+            details.push("from bundle".to_string());
+        }
+        let marker = if candidate.graphql { "[+]".green() } else { "[-]".red() };
+        let score = format!("{:>3}%", candidate.confidence);
+        let score = match candidate.confidence {
+            90.. => score.green(),
+            50.. => score.yellow(),
+            _ => score.dimmed(),
+        };
+        println!(
+            "{} {} {} {} {}",
+            marker,
+            status.dimmed(),
+            score,
+            candidate.url,
+            if details.is_empty() { String::new() } else { format!("({})", details.join(", ")) }.dimmed()
+        );
+    }
+
+    let found = candidates.iter().filter(|c| c.graphql).count();
+    if found == 0 {
+        println!("{} No GraphQL endpoints found", "[-]".red());
+    } else {
+        println!("\n{} Found {} endpoint(s) out of {} candidates", "[+]".green(), found, candidates.len());
+    }
+
+    Ok(())
+}
+
+async fn run_introspect(
+    target: String,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    print_banner();
+
+    let headers_map = parse_headers(&headers)?;
+    let client = HttpClient::new(proxy.as_deref(), headers_map, false)?;
+
+    println!("{} Fetching introspection from {}...\n", "[*]".cyan(), target);
+
+    let schema = fetch_schema_raw(&client, &target).await?;
+
+    let json_output = serde_json::to_string_pretty(&schema)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &json_output)?;
+            println!("{} Schema saved to {}", "[+]".green(), path.display());
+        }
+        None => {
+            println!("{}", json_output);
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_infer(
+    target: String,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    wordlist: Option<PathBuf>,
+    output: Option<PathBuf>,
+    bucket_size: usize,
+    depth: usize,
+    checkpoint: Option<PathBuf>,
+    resume: Option<PathBuf>,
+    seed: Option<PathBuf>,
+    format: String,
+    engine: Option<String>,
+    mangle: bool,
+    get: bool,
+    harvest: bool,
+    traffic: Vec<PathBuf>,
+    max_requests: Option<usize>,
+    per_minute: Option<u32>,
+) -> Result<()> {
+    print_banner();
+
+    let headers_map = parse_headers(&headers)?;
+    let client = HttpClient::new(proxy.as_deref(), headers_map, false)?;
+
+    println!(
+        "{} Inferring schema from {} (introspection disabled mode)...\n",
+        "[*]".cyan(),
+        target
+    );
+
+    // Load wordlist
+    let mut words = match wordlist {
+        Some(path) => {
+            println!(
+                "{} Loading wordlist from {}...",
+                "[*]".cyan(),
+                path.display()
+            );
+            load_inference_wordlist(path.to_str().unwrap())?
+        }
+        None => {
+            println!("{} Using built-in wordlist ({} words)...", "[*]".cyan(), default_wordlist().len());
+            default_wordlist()
+        }
+    };
+    if mangle {
+        let before = words.len();
+        words = mangle_wordlist(&words);
+        println!("{} Mangled wordlist: {} -> {} words", "[*]".cyan(), before, words.len());
+    }
+    if harvest {
+        println!("{} Harvesting names from the site's HTML and JavaScript...", "[*]".cyan());
+        let harvested = harvest_words(&client, &target).await?;
+        let known: HashSet<String> = words.iter().cloned().collect();
+        let new_words: Vec<String> = harvested.words.into_iter().filter(|w| !known.contains(w)).collect();
+        println!(
+            "{} Harvested {} new words from {} scripts ({} GraphQL documents, {} operations)",
+            "[+]".green(),
+            new_words.len(),
+            harvested.scripts,
+            harvested.documents,
+            harvested.operations.len()
+        );
+        words.extend(new_words);
+    }
+    if !traffic.is_empty() {
+        let mut operations = Vec::new();
+        for path in &traffic {
+            operations.extend(load_traffic(path)?);
+        }
+        let known: HashSet<String> = words.iter().cloned().collect();
+        let new_words: Vec<String> = schema_from_traffic(&operations)
+            .words()
+            .into_iter()
+            .filter(|w| !known.contains(w))
+            .collect();
+        println!(
+            "{} {} new words from {} recorded operations",
+            "[+]".green(),
+            new_words.len(),
+            operations.len()
+        );
+        words.extend(new_words);
+    }
+
+    let mut inferrer = SchemaInferrer::new(client, target.clone(), words)
+        .with_bucket_size(bucket_size)
+        .with_max_depth(depth);
+
+    if let Some(path) = &seed {
+        let seed_schema = load_schema_file(path)?;
+        println!(
+            "{} Seeding with {} types from {}",
+            "[*]".cyan(),
+            seed_schema.get_user_types().len(),
+            path.display()
+        );
+        inferrer = inferrer.with_seed(&seed_schema);
+    }
+    if let Some(path) = &resume {
+        let state = load_inference_state(path)?;
+        if state.url != target {
+            bail!("{} is a run against {}, not {}", path.display(), state.url, target);
+        }
+        println!(
+            "{} Resuming: {} types, {} requests so far, done: {}",
+            "[*]".cyan(),
+            state.types(),
+            state.requests,
+            if state.completed.is_empty() { "nothing yet".to_string() } else { state.completed.join(", ") }
+        );
+        inferrer = inferrer.with_state(state);
+    }
+    if let Some(engine) = &engine {
+        let profile = EngineProfile::resolve(engine)?;
+        println!("{} Reading errors as {}", "[*]".cyan(), profile.name);
+        inferrer = inferrer.with_engine(profile);
+    }
+    if get {
+        inferrer = inferrer.with_get();
+    }
+    if let Some(max) = max_requests {
+        inferrer = inferrer.with_max_requests(max);
+    }
+    if let Some(rate) = per_minute {
+        inferrer = inferrer.with_rate_limit(rate);
+    }
+    let checkpoint = checkpoint.or(resume);
+    if let Some(path) = &checkpoint {
+        inferrer = inferrer.with_checkpoint(path.clone());
+    }
+
+    // Live status line; hidden when stderr isn't a terminal
+    let bar = ProgressBar::new_spinner();
+    bar.set_style(ProgressStyle::with_template("{spinner:.cyan} {msg}").unwrap());
+    bar.enable_steady_tick(Duration::from_millis(120));
+    let started = Instant::now();
+    let requests_before = inferrer.requests();
+
+    let callback = |event: &InferenceEvent| match event {
+        InferenceEvent::Phase(msg) => bar.suspend(|| println!("{} {}", "[*]".cyan(), msg)),
+        InferenceEvent::Notice(msg) => bar.suspend(|| println!("{} {}", "[!]".yellow(), msg)),
+        InferenceEvent::Progress(p) => {
+            // Rate over this session only, a resumed run's earlier requests took unknown time
+            let sent = p.requests.saturating_sub(requests_before);
+            let elapsed = started.elapsed().as_secs_f64();
+            let eta = if sent > 0 && elapsed > 0.0 {
+                let secs = p.remaining as f64 * elapsed / sent as f64;
+                format!(", ~{} left", format_duration(Duration::from_secs_f64(secs)))
+            } else {
+                String::new()
+            };
+            bar.set_message(format!(
+                "{}/{} words, {} found, {} requests{}",
+                p.checked, p.total, p.found, p.requests, eta
+            ));
+        }
+    };
+
+    let result = inferrer.infer(Some(&callback)).await;
+    bar.finish_and_clear();
+    let schema = match result {
+        Ok(schema) => schema,
+        Err(e) => {
+            if let Some(path) = &checkpoint {
+                eprintln!(
+                    "{} Progress is saved in {}, pick it up with --resume {}",
+                    "[!]".yellow(),
+                    path.display(),
+                    path.display()
+                );
+            }
+            return Err(e);
+        }
+    };
+
+    // Count discovered items
+    let query_fields = schema.query_type.as_ref().map(|t| t.fields.len()).unwrap_or(0);
+    let mutation_fields = schema.mutation_type.as_ref().map(|t| t.fields.len()).unwrap_or(0);
+    let total_types = schema.types.len();
+
+    println!();
+    println!(
+        "{} Discovered: {} query fields, {} mutation fields, {} types, {} directives ({} requests)",
+        "[+]".green(),
+        query_fields,
+        mutation_fields,
+        total_types,
+        schema.directives.len(),
+        inferrer.requests()
+    );
+    let confidences: Vec<Confidence> = schema
+        .types
+        .values()
+        .flat_map(|t| t.fields.iter().map(|f| f.confidence))
+        .collect();
+    let count = |confidence: Confidence| confidences.iter().filter(|c| **c == confidence).count();
+    println!(
+        "{} Fields: {} confirmed, {} derived from errors, {} suggested only",
+        "[+]".green(),
+        count(Confidence::Confirmed),
+        count(Confidence::DerivedFromError),
+        count(Confidence::SuggestedOnly)
+    );
+
+    let rendered = if format == "sdl" {
+        schema.to_sdl()
+    } else {
+        serde_json::to_string_pretty(&schema.to_introspection_format())?
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &rendered)?;
+            println!("{} Inferred schema saved to {}", "[+]".green(), path.display());
+        }
+        None => {
+            println!("\n{}", rendered);
+        }
+    }
+
+    Ok(())
+}
+
+/// `1h02m`, `3m40s`, `12s`
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}h{:02}m", secs / 3600, secs % 3600 / 60)
+    } else if secs >= 60 {
+        format!("{}m{:02}s", secs / 60, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Resolve the schema (live introspection or file) and the base URL for an export
+async fn load_export_schema(
+    source: &SchemaSourceArgs,
+    url: Option<String>,
+) -> Result<(Schema, String)> {
+    let url = match url.or_else(|| source.target.clone()) {
+        Some(u) => u,
+        None => bail!("--url is required when exporting from a schema file"),
+    };
+
+    Ok((load_source_schema(source, false).await?, url))
+}
+
+/// Introspect `--target` (falling back to `--schema`) or read the schema file.
+/// With `quiet`, progress goes to stderr so stdout stays machine-readable.
+async fn load_source_schema(source: &SchemaSourceArgs, quiet: bool) -> Result<Schema> {
+    let status = |msg: String| {
+        if quiet {
+            eprintln!("{}", msg);
+        } else {
+            println!("{}", msg);
+        }
+    };
+
+    if let Some(target) = &source.target {
+        let headers_map = export_headers(source)?.into_iter().collect();
+        let client = HttpClient::new(source.proxy.as_deref(), headers_map, false)?;
+
+        status(format!("{} Fetching introspection from {}...", "[*]".cyan(), target));
+
+        match fetch_schema(&client, target).await {
+            Ok(schema) => return Ok(schema),
+            Err(e) => match &source.schema {
+                Some(path) => status(format!(
+                    "{} Introspection failed ({}), falling back to {}",
+                    "[!]".yellow(),
+                    e,
+                    path.display()
+                )),
+                None => return Err(e),
+            },
+        }
+    }
+
+    let schema_path = source
+        .schema
+        .as_ref()
+        .context("No schema file or target given")?;
+
+    status(format!("{} Loading schema from {}...", "[*]".cyan(), schema_path.display()));
+
+    load_schema_file(schema_path)
+}
+
+/// Headers to embed in exported requests, in a stable order
+fn export_headers(source: &SchemaSourceArgs) -> Result<Vec<(String, String)>> {
+    let mut headers: Vec<(String, String)> = parse_headers(&source.headers)?.into_iter().collect();
+    headers.sort();
+
+    if let Some(auth) = &source.auth {
+        let value = if auth.contains(' ') {
+            auth.clone()
+        } else {
+            format!("Bearer {}", auth)
+        };
+        headers.retain(|(key, _)| !key.eq_ignore_ascii_case("authorization"));
+        headers.push(("Authorization".to_string(), value));
+    }
+
+    Ok(headers)
+}
+
+async fn run_export_bruno(
+    source: SchemaSourceArgs,
+    output: PathBuf,
+    url: Option<String>,
+    filter: OperationFilter,
+    group_by: GroupBy,
+) -> Result<()> {
+    print_banner();
+
+    let (schema, url) = load_export_schema(&source, url).await?;
+
+    let headers = export_headers(&source)?;
+    let exporter = BrunoExporter::new(schema, url)
+        .with_filter(filter)
+        .with_headers(headers)
+        .with_group_by(group_by);
+    let stats = exporter.export(&output)?;
+
+    println!(
+        "{} Exported {} queries, {} mutations and {} subscriptions to {}",
+        "[+]".green(),
+        stats.queries,
+        stats.mutations,
+        stats.subscriptions,
+        output.display()
+    );
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_export_postman(
+    source: SchemaSourceArgs,
+    output: PathBuf,
+    url: Option<String>,
+    filter: OperationFilter,
+    group_by: GroupBy,
+    environment: Option<PathBuf>,
+    auth_type: String,
+    api_key_header: String,
+    token: Option<String>,
+) -> Result<()> {
+    print_banner();
+
+    let (schema, url) = load_export_schema(&source, url).await?;
+
+    let auth = match auth_type.as_str() {
+        "bearer" => PostmanAuthKind::Bearer,
+        "apikey" => PostmanAuthKind::ApiKey { header: api_key_header },
+        _ => PostmanAuthKind::None,
+    };
+
+    let headers = export_headers(&source)?;
+    let exporter = PostmanExporter::new(schema, url)
+        .with_filter(filter)
+        .with_headers(headers)
+        .with_group_by(group_by)
+        .with_auth(auth, token);
+    let collection = exporter.export()?;
+
+    let json_output = serde_json::to_string_pretty(&collection)?;
+    std::fs::write(&output, json_output)?;
+
+    let environment_path = environment.unwrap_or_else(|| {
+        let stem = output
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("collection");
+        output.with_file_name(format!("{}.postman_environment.json", stem))
+    });
+    let environment_output = serde_json::to_string_pretty(&exporter.environment())?;
+    std::fs::write(&environment_path, environment_output)?;
+
+    let query_count: usize = collection.item.iter()
+        .filter(|f| f.name == "Queries")
+        .map(|f| f.request_count())
+        .sum();
+    let mutation_count: usize = collection.item.iter()
+        .filter(|f| f.name == "Mutations")
+        .map(|f| f.request_count())
+        .sum();
+    let subscription_count: usize = collection.item.iter()
+        .filter(|f| f.name == "Subscriptions")
+        .map(|f| f.request_count())
+        .sum();
+
+    println!(
+        "{} Exported {} queries, {} mutations and {} subscriptions to {}",
+        "[+]".green(),
+        query_count,
+        mutation_count,
+        subscription_count,
+        output.display()
+    );
+    println!(
+        "{} Environment written to {}",
+        "[+]".green(),
+        environment_path.display()
+    );
+
+    Ok(())
+}
+
+async fn run_export_curl(
+    source: SchemaSourceArgs,
+    output: PathBuf,
+    url: Option<String>,
+    filter: OperationFilter,
+) -> Result<()> {
+    print_banner();
+
+    let (schema, url) = load_export_schema(&source, url).await?;
+
+    let headers = export_headers(&source)?;
+    let exporter = CurlExporter::new(schema, url)
+        .with_filter(filter)
+        .with_headers(headers);
+    let stats = exporter.export(&output)?;
+
+    println!(
+        "{} Exported {} queries, {} mutations and {} subscriptions to {}",
+        "[+]".green(),
+        stats.queries,
+        stats.mutations,
+        stats.subscriptions,
+        output.display()
+    );
+
+    Ok(())
+}
+
+async fn run_export_httpie(
+    source: SchemaSourceArgs,
+    output: PathBuf,
+    url: Option<String>,
+    filter: OperationFilter,
+) -> Result<()> {
+    print_banner();
+
+    let (schema, url) = load_export_schema(&source, url).await?;
+
+    let headers = export_headers(&source)?;
+    let exporter = HttpieExporter::new(schema, url)
+        .with_filter(filter)
+        .with_headers(headers);
+    let stats = exporter.export(&output)?;
+
+    println!(
+        "{} Exported {} queries, {} mutations and {} subscriptions to {}",
+        "[+]".green(),
+        stats.queries,
+        stats.mutations,
+        stats.subscriptions,
+        output.display()
+    );
+
+    Ok(())
+}
+
+async fn run_export_burp(
+    source: SchemaSourceArgs,
+    output: PathBuf,
+    url: Option<String>,
+    filter: OperationFilter,
+) -> Result<()> {
+    print_banner();
+
+    let (schema, url) = load_export_schema(&source, url).await?;
+
+    let headers = export_headers(&source)?;
+    let exporter = BurpExporter::new(schema, url)
+        .with_filter(filter)
+        .with_headers(headers);
+    let stats = exporter.export(&output)?;
+
+    println!(
+        "{} Exported {} queries, {} mutations and {} subscriptions to {}",
+        "[+]".green(),
+        stats.queries,
+        stats.mutations,
+        stats.subscriptions,
+        output.display()
+    );
+
+    Ok(())
+}
+
+async fn run_export_inql(
+    source: SchemaSourceArgs,
+    output: PathBuf,
+    url: Option<String>,
+    filter: OperationFilter,
+    group_by: GroupBy,
+) -> Result<()> {
+    print_banner();
+
+    let (schema, url) = load_export_schema(&source, url).await?;
+
+    let headers = export_headers(&source)?;
+    let exporter = InqlExporter::new(schema, url)
+        .with_filter(filter)
+        .with_headers(headers)
+        .with_group_by(group_by);
+    let stats = exporter.export(&output)?;
+
+    println!(
+        "{} Exported {} queries, {} mutations and {} subscriptions to {}",
+        "[+]".green(),
+        stats.queries,
+        stats.mutations,
+        stats.subscriptions,
+        output.display()
+    );
+
+    Ok(())
+}
+
+async fn run_export_graphql_config(source: SchemaSourceArgs, output: PathBuf, url: Option<String>) -> Result<()> {
+    print_banner();
+
+    let (schema, url) = load_export_schema(&source, url).await?;
+
+    let headers = export_headers(&source)?;
+    GraphqlConfigExporter::new(schema, url)
+        .with_headers(headers.clone())
+        .export(&output)?;
+
+    println!(
+        "{} Wrote .graphqlrc.yml and schema.graphql to {}",
+        "[+]".green(),
+        output.display()
+    );
+    if !headers.is_empty() {
+        println!(
+            "{} Header values are in {}, keep it out of version control",
+            "[!]".yellow(),
+            output.join(".env").display()
+        );
+    }
+
+    Ok(())
+}
+
+async fn run_export_catalog(
+    source: SchemaSourceArgs,
+    output: Option<PathBuf>,
+    depth: usize,
+    filter: OperationFilter,
+) -> Result<()> {
+    // Keep stdout clean for piping into jq when printing the catalog
+    let quiet = output.is_none();
+    if !quiet {
+        print_banner();
+    }
+
+    let schema = load_source_schema(&source, quiet).await?;
+
+    let catalog = CatalogExporter::new(schema)
+        .with_filter(filter)
+        .with_max_depth(depth)
+        .export();
+    let json = serde_json::to_string_pretty(&catalog)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &json)?;
+            println!(
+                "{} Catalogued {} operations to {}",
+                "[+]".green(),
+                catalog.operations.len(),
+                path.display()
+            );
+        }
+        None => println!("{}", json),
+    }
+
+    Ok(())
+}
+
+async fn run_export_armor(
+    schema_path: Option<PathBuf>,
+    findings_path: Option<PathBuf>,
+    format: String,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let schema = match &schema_path {
+        Some(path) => Some(load_schema_file(path)?),
+        None => None,
+    };
+
+    let findings = match &findings_path {
+        Some(path) => {
+            let content = std::fs::read_to_string(path).context("Failed to read findings file")?;
+            parse_results_json(&content)?
+        }
+        None => Vec::new(),
+    };
+
+    let exporter = ArmorExporter::new(schema, findings);
+    let config = match format.as_str() {
+        "router" => exporter.render_router(),
+        _ => exporter.render_armor(),
+    };
+
+    match output {
+        Some(path) => {
+            print_banner();
+            std::fs::write(&path, &config)?;
+            println!("{} Hardening config saved to {}", "[+]".green(), path.display());
+        }
+        None => print!("{}", config),
+    }
+
+    Ok(())
+}
+
+async fn run_schema_diff(old_path: PathBuf, new_path: PathBuf, output: String) -> Result<()> {
+    let old = load_schema_file(&old_path)?;
+    let new = load_schema_file(&new_path)?;
+    let diff = diff_schemas(&old, &new);
+
+    if output == "json" {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+
+    print_banner();
+
+    println!(
+        "{} Comparing {} -> {}\n",
+        "[*]".cyan(),
+        old_path.display(),
+        new_path.display()
+    );
+
+    if diff.changes.is_empty() {
+        println!("{} No differences found", "[+]".green());
+        return Ok(());
+    }
+
+    for change in &diff.changes {
+        let severity = match change.severity {
+            ChangeSeverity::Breaking => format!("[{}]", change.severity).red().bold(),
+            ChangeSeverity::Dangerous => format!("[{}]", change.severity).yellow().bold(),
+            ChangeSeverity::Safe => format!("[{}]", change.severity).green().bold(),
+        };
+        println!("{} {}", severity, change.message);
+    }
+
+    println!(
+        "\n{} {} breaking, {} dangerous, {} safe changes",
+        "[*]".cyan(),
+        diff.summary.breaking,
+        diff.summary.dangerous,
+        diff.summary.safe
+    );
+    println!(
+        "{} Attack surface: {} operations added, {} removed",
+        "[*]".cyan(),
+        diff.summary.operations_added,
+        diff.summary.operations_removed
+    );
+
+    Ok(())
+}
+
+async fn run_schema_drift(
+    targets: Vec<String>,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    no_infer: bool,
+    max_requests: usize,
+    output: String,
+) -> Result<()> {
+    let [first, second]: [String; 2] = match targets.try_into() {
+        Ok(targets) => targets,
+        Err(_) => bail!("Give exactly two --target"),
+    };
+    // With json output, progress goes to stderr so stdout stays machine-readable
+    let quiet = output == "json";
+    let status = |msg: String| {
+        if quiet {
+            eprintln!("{}", msg);
+        } else {
+            println!("{}", msg);
+        }
+    };
+    if !quiet {
+        print_banner();
+    }
+    let client = HttpClient::new(proxy.as_deref(), parse_headers(&headers)?, false)?;
+
+    let mut schemas = Vec::new();
+    for target in [&first, &second] {
+        status(format!("{} Fetching introspection from {}...", "[*]".cyan(), target));
+
+        let fetched = match fetch_schema(&client, target).await {
+            Ok(schema) => (schema, "introspection"),
+            Err(e) if no_infer => return Err(e.context(format!("Introspection failed on {}", target))),
+            Err(e) => {
+                status(format!(
+                    "{} Introspection failed ({}), inferring with the built-in wordlist (up to {} requests)...",
+                    "[!]".yellow(),
+                    e,
+                    max_requests
+                ));
+                let mut inferrer = SchemaInferrer::new(client.clone(), target.to_string(), default_wordlist())
+                    .with_max_requests(max_requests);
+                let inferred = inferrer.infer(None).await?;
+                (parse_schema_json(inferred.to_introspection_format())?, "inference")
+            }
+        };
+        schemas.push(fetched);
+    }
+    let drift = schema_drift(&schemas[0].0, &schemas[1].0);
+
+    if quiet {
+        let side = |url: &str, source: &str| serde_json::json!({ "url": url, "source": source });
+        let report = serde_json::json!({
+            "first": side(&first, schemas[0].1),
+            "second": side(&second, schemas[1].1),
+            "only_first": drift.only_first,
+            "only_second": drift.only_second,
+        });
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!();
+    if drift.is_empty() {
+        println!("{} Both expose the same operations, types and fields", "[+]".green());
+    }
+    for (url, side) in [(&first, &drift.only_first), (&second, &drift.only_second)] {
+        if side.is_empty() {
+            continue;
+        }
+        println!("{} Only on {}:", "[!]".yellow(), url.bold());
+        for operation in &side.operations {
+            let (kind, name) = operation.split_once('.').unwrap_or(("", operation));
+            let line = format!("    {:<12} {}", kind, name);
+            if kind == "mutation" {
+                println!("{}", line.red());
+            } else {
+                println!("{}", line);
+            }
+        }
+        for name in &side.types {
+            println!("    {:<12} {}", "type", name);
+        }
+        for member in &side.members {
+            println!("    {:<12} {}", "member", member);
+        }
+        println!();
+    }
+
+    for (index, (url, (_, source))) in [&first, &second].iter().zip(&schemas).enumerate() {
+        let other = if index == 0 { &drift.only_second } else { &drift.only_first };
+        if *source == "inference" && !other.is_empty() {
+            println!(
+                "{} {} was inferred, so some of what only the other one has may just not have been found there",
+                "[*]".cyan(),
+                url
+            );
+        }
+    }
+    let mutations: Vec<&str> = drift.only_first.mutations().chain(drift.only_second.mutations()).collect();
+    if !mutations.is_empty() {
+        println!(
+            "{} {} mutation(s) exist on one environment only: {}",
+            "[!]".yellow(),
+            mutations.len(),
+            mutations.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+async fn run_schema_audit(schema_path: PathBuf, output: String) -> Result<()> {
+    let schema = load_schema_file(&schema_path)?;
+    let findings = audit_schema(&schema);
+
+    if output == "json" {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+        return Ok(());
+    }
+
+    print_banner();
+
+    println!("{} Auditing {}...\n", "[*]".cyan(), schema_path.display());
+
+    if findings.is_empty() {
+        println!("{} Nothing sensitive-looking found", "[+]".green());
+        return Ok(());
+    }
+
+    for finding in &findings {
+        let severity = match finding.severity {
+            Severity::High => format!("[{}]", finding.severity).red().bold(),
+            Severity::Medium => format!("[{}]", finding.severity).yellow().bold(),
+            Severity::Low => format!("[{}]", finding.severity).blue().bold(),
+            Severity::Info => format!("[{}]", finding.severity).green().bold(),
+        };
+        println!("{} {} - {}", severity, finding.path.bold(), finding.reason);
+    }
+
+    let count = |s: Severity| findings.iter().filter(|f| f.severity == s).count();
+    println!(
+        "\n{} {} fields worth a manual look ({} high, {} medium, {} low)",
+        "[*]".cyan(),
+        findings.len(),
+        count(Severity::High),
+        count(Severity::Medium),
+        count(Severity::Low)
+    );
+
+    Ok(())
+}
+
+async fn run_schema_lint(
+    schema_path: Option<PathBuf>,
+    exclude: Option<String>,
+    list_rules: bool,
+    output: String,
+) -> Result<()> {
+    if list_rules {
+        if output == "json" {
+            println!("{}", serde_json::to_string_pretty(LINT_RULES)?);
+            return Ok(());
+        }
+        print_banner();
+        println!("{}", "Available lint rules:".bold());
+        for rule in LINT_RULES {
+            println!("  {} {} - {}", rule.id.cyan(), rule.name.bold(), rule.description);
+        }
+        return Ok(());
+    }
+
+    let schema_path = match schema_path {
+        Some(p) => p,
+        None => bail!("--schema is required"),
+    };
+    let schema = load_schema_file(&schema_path)?;
+    let exclude: Vec<String> = exclude
+        .map(|e| e.split(',').map(|s| s.trim().to_string()).collect())
+        .unwrap_or_default();
+    let warnings = lint_schema(&schema, &exclude);
+
+    if output == "json" {
+        println!("{}", serde_json::to_string_pretty(&warnings)?);
+        return Ok(());
+    }
+
+    print_banner();
+
+    println!("{} Linting {}...\n", "[*]".cyan(), schema_path.display());
+
+    if warnings.is_empty() {
+        println!("{} No warnings", "[+]".green());
+        return Ok(());
+    }
+
+    for warning in &warnings {
+        let severity = match warning.severity {
+            Severity::High => format!("[{}]", warning.severity).red().bold(),
+            Severity::Medium => format!("[{}]", warning.severity).yellow().bold(),
+            Severity::Low => format!("[{}]", warning.severity).blue().bold(),
+            Severity::Info => format!("[{}]", warning.severity).green().bold(),
+        };
+        println!(
+            "{} {} {} - {} {}",
+            severity,
+            warning.rule.cyan(),
+            warning.path.bold(),
+            warning.message,
+            format!("({})", warning.name).dimmed()
+        );
+    }
+
+    println!("\n{} {} warnings", "[*]".cyan(), warnings.len());
+    for rule in LINT_RULES {
+        let count = warnings.iter().filter(|w| w.rule == rule.id).count();
+        if count > 0 {
+            println!("    {} {}: {}", rule.id, rule.name, count);
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_schema_coverage(schema_path: PathBuf, sources: Vec<PathBuf>, output: String) -> Result<()> {
+    let schema = load_schema_file(&schema_path)?;
+
+    let mut queries = Vec::new();
+    for path in &sources {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        queries.extend(extract_queries(&content));
+    }
+
+    let report = schema_coverage(&schema, &queries);
+
+    if output == "json" {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    print_banner();
+
+    println!(
+        "{} Measuring {} queries against {}...\n",
+        "[*]".cyan(),
+        queries.len(),
+        schema_path.display()
+    );
+
+    for type_coverage in &report.types {
+        let total = type_coverage.covered.len() + type_coverage.uncovered.len();
+        let line = format!(
+            "{} {}/{}",
+            type_coverage.name, type_coverage.covered.len(), total
+        );
+        if type_coverage.covered.is_empty() {
+            println!("{} {}", "[-]".red(), line);
+        } else if type_coverage.uncovered.is_empty() {
+            println!("{} {}", "[+]".green(), line);
+        } else {
+            println!("{} {}", "[*]".cyan(), line);
+            println!("    untested: {}", type_coverage.uncovered.join(", ").dimmed());
+        }
+    }
+
+    println!();
+    if !report.unknown_fields.is_empty() {
+        println!(
+            "{} {} selected fields not in the schema: {}",
+            "[!]".yellow(),
+            report.unknown_fields.len(),
+            report.unknown_fields.join(", ")
+        );
+    }
+    print_coverage_summary(&report);
+
+    Ok(())
+}
+
+fn print_coverage_summary(report: &CoverageReport) {
+    println!(
+        "{} Schema coverage: {}/{} fields ({:.1}%), {}/{} types touched",
+        "[*]".cyan(),
+        report.fields_covered,
+        report.fields_total,
+        report.percent(),
+        report.types_touched,
+        report.types_total
+    );
+    if report.unparsed > 0 {
+        println!("{} {} queries could not be parsed", "[!]".yellow(), report.unparsed);
+    }
+}
+
+/// Outcome counts over the query and mutation fields, and where the full list went
+fn print_outcome_summary(report: &OutcomeReport, saved_to: Option<&Path>) {
+    let counts: Vec<String> = FieldOutcome::ALL
+        .iter()
+        .filter(|o| report.count(**o) > 0)
+        .map(|o| format!("{} {}", report.count(*o), o))
+        .collect();
+    println!(
+        "{} Field coverage: {}/{} exercised ({})",
+        "[*]".cyan(),
+        report.exercised(),
+        report.total,
+        counts.join(", ")
+    );
+    if let Some(path) = saved_to {
+        println!("{} Field outcomes saved to {}", "[+]".green(), path.display());
+    }
+}
+
+/// Queries from a .graphql document, a JSON request body (or array of them) or a JSONL log
+fn extract_queries(content: &str) -> Vec<String> {
+    fn from_value(value: &serde_json::Value, out: &mut Vec<String>) {
+        match value {
+            serde_json::Value::Array(items) => items.iter().for_each(|v| from_value(v, out)),
+            serde_json::Value::Object(map) => {
+                if let Some(query) = map.get("query").and_then(|q| q.as_str()) {
+                    out.push(query.to_string());
+                }
+            }
+            serde_json::Value::String(query) => out.push(query.clone()),
+            _ => {}
+        }
+    }
+
+    let mut queries = Vec::new();
+    if let Ok(value) = serde_json::from_str::<serde_json::Value>(content) {
+        from_value(&value, &mut queries);
+        return queries;
+    }
+
+    let lines: Vec<serde_json::Value> = content
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map_while(|l| serde_json::from_str(l).ok())
+        .collect();
+    if !lines.is_empty() && lines.len() == content.lines().filter(|l| !l.trim().is_empty()).count() {
+        lines.iter().for_each(|v| from_value(v, &mut queries));
+        return queries;
+    }
+
+    vec![content.to_string()]
+}
+
+/// `coverage.json` -> `coverage-2.json`
+fn numbered_path(path: &std::path::Path, number: usize) -> PathBuf {
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("coverage");
+    let name = match path.extension().and_then(|e| e.to_str()) {
+        Some(ext) => format!("{}-{}.{}", stem, number, ext),
+        None => format!("{}-{}", stem, number),
+    };
+    path.with_file_name(name)
+}
+
+async fn run_schema_wordlist(
+    schema_path: PathBuf,
+    output: Option<PathBuf>,
+    no_split: bool,
+) -> Result<()> {
+    let schema = load_schema_file(&schema_path)?;
+    let words = schema_wordlist(&schema, !no_split);
+
+    let mut content = words.join("\n");
+    content.push('\n');
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &content)?;
+            println!(
+                "{} Wrote {} words from {} to {}",
+                "[+]".green(),
+                words.len(),
+                schema_path.display(),
+                path.display()
+            );
+        }
+        None => print!("{}", content),
+    }
+
+    Ok(())
+}
+
+async fn run_schema_graph(
+    schema_path: PathBuf,
+    format: String,
+    root: Option<String>,
+    depth: Option<usize>,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let schema = load_schema_file(&schema_path)?;
+    let graph = SchemaGraph::new(schema).with_root(root).with_max_depth(depth);
+
+    let rendered = match format.as_str() {
+        "mermaid" => graph.render_mermaid()?,
+        _ => graph.render_dot()?,
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &rendered)?;
+            println!("{} Schema graph saved to {}", "[+]".green(), path.display());
+        }
+        None => print!("{}", rendered),
+    }
+
+    Ok(())
+}
+
+async fn run_schema_merge(sources: Vec<PathBuf>, output: PathBuf) -> Result<()> {
+    print_banner();
+
+    let mut loaded = Vec::new();
+    for path in &sources {
+        println!("{} Loading schema from {}...", "[*]".cyan(), path.display());
+        loaded.push((path.display().to_string(), load_schema_file(path)?));
+    }
+
+    let result = merge_schemas(loaded);
+
+    println!();
+    for contribution in &result.contributions {
+        println!(
+            "{} {} contributed {} types, {} fields",
+            "[+]".green(),
+            contribution.source,
+            contribution.types,
+            contribution.fields
+        );
+    }
+
+    if !result.conflicts.is_empty() {
+        println!();
+        for conflict in &result.conflicts {
+            println!(
+                "{} Conflict at {}: kept {} ({}), dropped {} ({})",
+                "[!]".yellow(),
+                conflict.path.bold(),
+                conflict.kept,
+                conflict.kept_from,
+                conflict.dropped,
+                conflict.dropped_from
+            );
+        }
+    }
+
+    let to_json = output.extension().is_some_and(|e| e == "json");
+    std::fs::write(&output, render_schema(&result.schema, to_json)?)?;
+
+    println!(
+        "\n{} Merged schema saved to {} ({} conflicts)",
+        "[+]".green(),
+        output.display(),
+        result.conflicts.len()
+    );
+
+    Ok(())
+}
+
+/// Introspection JSON (in the same envelope `introspect` saves) or SDL
+fn render_schema(schema: &Schema, to_json: bool) -> Result<String> {
+    if to_json {
+        let mut json = serde_json::to_string_pretty(&serde_json::json!({ "data": schema }))?;
+        json.push('\n');
+        Ok(json)
+    } else {
+        Ok(print_sdl(schema))
+    }
+}
+
+async fn run_schema_cycles(
+    schema_path: PathBuf,
+    max_length: usize,
+    limit: usize,
+    output: String,
+) -> Result<()> {
+    let schema = load_schema_file(&schema_path)?;
+    let cycles = find_cycles(&schema, max_length, limit);
+
+    if output == "json" {
+        println!("{}", serde_json::to_string_pretty(&cycles)?);
+        return Ok(());
+    }
+
+    print_banner();
+
+    println!("{} Looking for type cycles in {}...\n", "[*]".cyan(), schema_path.display());
+
+    if cycles.is_empty() {
+        println!("{} No recursive types found", "[+]".green());
+        return Ok(());
+    }
+
+    for cycle in &cycles {
+        println!("{} {}", "[!]".yellow(), cycle.describe().bold());
+        for hop in &cycle.hops {
+            println!("    {}.{{{}}} -> {}", hop.from, hop.fields.join(", "), hop.to);
         }
+        match cycle.deep_query(&schema, cycle.len() * 2) {
+            Some(query) => println!("    Example: {}", query.dimmed()),
+            None => println!("    {}", "Not reachable from Query without arguments".dimmed()),
+        }
+        println!();
+    }
 
-        // Run tests
-        let mut results = Vec::new();
-        let active_tests: Vec<_> = tests
-            .iter()
-            .filter(|t| !excluded.contains(&t.name()))
-            .collect();
+    let reachable = cycles.iter().filter(|c| c.entry.is_some()).count();
+    println!(
+        "{} {} cycles found, {} reachable from the query root",
+        "[*]".cyan(),
+        cycles.len(),
+        reachable
+    );
+    if cycles.len() >= limit {
+        println!("{} Stopped at --limit {}, there may be more", "[!]".yellow(), limit);
+    }
 
-        println!(
-            "{} Running {} security tests...\n",
-            "[*]".cyan(),
-            active_tests.len()
-        );
+    Ok(())
+}
 
-        for test in active_tests {
-            match test.run(&client, &url).await {
-                Ok(result) => results.push(result),
-                Err(e) => {
-                    if debug {
-                        eprintln!("{} Test {} failed: {}", "[-]".red(), test.name(), e);
-                    }
-                }
-            }
+async fn run_schema_from_traffic(captures: Vec<PathBuf>, output: Option<PathBuf>) -> Result<()> {
+    let mut operations = Vec::new();
+    for path in &captures {
+        operations.extend(load_traffic(path)?);
+    }
+    let traffic = schema_from_traffic(&operations);
+    let schema = traffic.to_schema()?;
+
+    let Some(path) = output else {
+        print!("{}", render_schema(&schema, false)?);
+        return Ok(());
+    };
+
+    print_banner();
+
+    println!(
+        "{} {} GraphQL operations in {} captures, {} with a JSON reply",
+        "[*]".cyan(),
+        traffic.operations,
+        captures.len(),
+        traffic.with_response
+    );
+    if traffic.unparsed > 0 {
+        println!("{} {} documents could not be parsed", "[!]".yellow(), traffic.unparsed);
+    }
+    let root_fields = |root: Option<&gqlmap::schema::FullType>| root.and_then(|t| t.fields.as_ref()).map_or(0, |f| f.len());
+    println!(
+        "{} {} queries, {} mutations, {} subscriptions, {} other types",
+        "[+]".green(),
+        root_fields(schema.get_query_type()),
+        root_fields(schema.get_mutation_type()),
+        root_fields(schema.get_subscription_type()),
+        traffic
+            .schema
+            .types
+            .keys()
+            .filter(|name| !["Query", "Mutation", "Subscription"].contains(&name.as_str()))
+            .count()
+    );
+
+    let to_json = path.extension().is_some_and(|e| e == "json");
+    std::fs::write(&path, render_schema(&schema, to_json)?)?;
+    println!("{} Schema saved to {}", "[+]".green(), path.display());
+
+    Ok(())
+}
+
+async fn run_schema_convert(
+    input: PathBuf,
+    output: Option<PathBuf>,
+    to: Option<String>,
+) -> Result<()> {
+    let schema = load_schema_file(&input)?;
+
+    let is_json = |path: &PathBuf| path.extension().is_some_and(|e| e == "json");
+    let to_json = match to.as_deref() {
+        Some(format) => format == "json",
+        None => match &output {
+            Some(path) => is_json(path),
+            None => !is_json(&input),
+        },
+    };
+
+    let converted = render_schema(&schema, to_json)?;
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &converted)?;
+            println!(
+                "{} Converted {} to {} ({})",
+                "[+]".green(),
+                input.display(),
+                path.display(),
+                if to_json { "introspection JSON" } else { "SDL" }
+            );
         }
+        None => print!("{}", converted),
+    }
 
-        // Sort by severity
-        results.sort_by(|a, b| {
-            let severity_order = |s: &Severity| match s {
-                Severity::High => 0,
-                Severity::Medium => 1,
-                Severity::Low => 2,
-                Severity::Info => 3,
-            };
-            severity_order(&a.severity).cmp(&severity_order(&b.severity))
-        });
+    Ok(())
+}
 
-        // Output results
-        match output.as_str() {
-            "json" => print_results_json(&results),
-            _ => {
-                let vulnerable_count = results.iter().filter(|r| r.vulnerable).count();
+async fn run_generate(
+    kind: String,
+    schema_path: PathBuf,
+    depth: usize,
+    count: usize,
+    page_size: usize,
+    json: bool,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let schema = load_schema_file(&schema_path)?;
+    let generator = AttackGenerator::new(&schema);
 
-                if vulnerable_count == 0 {
-                    println!("{} No vulnerabilities found", "[+]".green());
-                } else {
-                    println!(
-                        "{} Found {} issue(s):\n",
-                        "[!]".yellow(),
-                        vulnerable_count
-                    );
-                    for result in &results {
-                        print_result(result);
-                    }
-                }
+    let generated = match kind.as_str() {
+        "deep" => generator.deep(depth)?,
+        "wide" => generator.wide(depth, page_size)?,
+        _ => generator.aliases(count)?,
+    };
+
+    let mut rendered = if json {
+        serde_json::to_string(&serde_json::json!({ "query": generated.query }))?
+    } else {
+        generated.query.clone()
+    };
+    rendered.push('\n');
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &rendered)?;
+            println!("{} {}", "[+]".green(), generated.description);
+            if let Some(nodes) = generated.estimated_nodes {
+                println!("{} Up to {} objects to resolve", "[*]".cyan(), nodes);
             }
+            println!(
+                "{} Query saved to {} ({} bytes)",
+                "[+]".green(),
+                path.display(),
+                rendered.len()
+            );
+        }
+        None => {
+            eprintln!("{} {}", "[+]".green(), generated.description);
+            print!("{}", rendered);
         }
     }
 
     Ok(())
 }
 
-async fn run_introspect(
+#[allow(clippy::too_many_arguments)]
+async fn run_fuzz(
     target: String,
+    schema_path: Option<PathBuf>,
     headers: Vec<String>,
     proxy: Option<String>,
-    output: Option<PathBuf>,
+    filter: Option<String>,
+    mutations: bool,
+    guard: MutationGuard,
+    traffic: Vec<PathBuf>,
+    packs: Vec<String>,
+    payloads: Option<PathBuf>,
+    oob: OobArgs,
+    stored: bool,
+    coverage: Option<PathBuf>,
+    evasion: EvasionArgs,
+    output: String,
 ) -> Result<()> {
-    print_banner();
+    let text = output == "text";
+    let custom = match &payloads {
+        Some(dir) => PayloadPack::load_dir(dir)?,
+        None => Vec::new(),
+    };
+    let packs = select_packs(&packs, custom)?;
+    if text {
+        print_banner();
+    }
 
     let headers_map = parse_headers(&headers)?;
-    let client = HttpClient::new(proxy.as_deref(), headers_map, false)?;
+    let client = evasion.apply(HttpClient::new(proxy.as_deref(), headers_map, false)?)?;
+    let schema = match &schema_path {
+        Some(path) => load_schema_file(path)?,
+        None => {
+            if text {
+                println!("{} Fetching introspection from {}...", "[*]".cyan(), target);
+            }
+            fetch_schema(&client, &target)
+                .await
+                .context("Introspection failed, pass the schema with --schema")?
+        }
+    };
 
-    println!("{} Fetching introspection from {}...\n", "[*]".cyan(), target);
+    let filter = OperationFilter::new(filter.as_deref(), &[])?;
+    let mut fuzzer = Fuzzer::new(client, &target, schema)
+        .with_filter(filter)
+        .with_mutations(mutations, guard)
+        .with_packs(packs);
+    if !traffic.is_empty() {
+        let mut operations = Vec::new();
+        for path in &traffic {
+            operations.extend(load_traffic(path)?);
+        }
+        let observed = observed_arguments(&operations);
+        if text {
+            println!(
+                "{} Real argument values for {} operations from {} recorded requests",
+                "[*]".cyan(),
+                observed.len(),
+                operations.len()
+            );
+        }
+        fuzzer = fuzzer.with_observed(observed);
+    }
+    if let Some(client) = oob.connect(proxy.as_deref()).await? {
+        if text {
+            println!("{} Callbacks go to {}", "[*]".cyan(), client.domain());
+        }
+        fuzzer = fuzzer.with_oob(client, Duration::from_secs(oob.oob_wait));
+    }
 
-    let schema = fetch_schema_raw(&client, &target).await?;
+    let callback = |event: &FuzzEvent| match event {
+        FuzzEvent::Operation(name, kind, requests) => {
+            println!("{} {} {} ({} requests)", "[*]".cyan(), kind, name, requests);
+        }
+        FuzzEvent::Skipped(name, reason) => {
+            println!("{} {} skipped: {}", "[-]".red(), name, reason);
+        }
+        FuzzEvent::Finding(finding) => {
+            println!(
+                "  {} {} via {} = {}: {}",
+                "[!]".yellow(),
+                finding.anomaly.yellow().bold(),
+                finding.argument,
+                finding.payload,
+                finding.detail.dimmed()
+            );
+        }
+    };
+    let callback: Option<&dyn Fn(&FuzzEvent)> = if text { Some(&callback) } else { None };
+    let findings = if stored {
+        fuzzer.run_stored(callback).await?
+    } else {
+        fuzzer.run(callback).await?
+    };
+    if let Some(client) = fuzzer.oob() {
+        client.close().await.ok();
+    }
+    let outcomes = fuzzer.outcomes();
+    if let Some(path) = &coverage {
+        std::fs::write(path, serde_json::to_string_pretty(&outcomes)?)?;
+    }
 
-    let json_output = serde_json::to_string_pretty(&schema)?;
+    if !text {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+        return Ok(());
+    }
 
-    match output {
-        Some(path) => {
-            std::fs::write(&path, &json_output)?;
-            println!("{} Schema saved to {}", "[+]".green(), path.display());
-        }
-        None => {
-            println!("{}", json_output);
+    println!();
+    if let Some(client) = fuzzer.oob().filter(|c| !c.can_poll()) {
+        let planted = fuzzer.planted();
+        if !planted.is_empty() {
+            println!(
+                "{} {} callback hosts planted under {}, watch it for these tokens:",
+                "[*]".cyan(),
+                planted.len(),
+                client.domain()
+            );
+            for plant in planted {
+                println!("    {}  {} {} = {}", plant.token, plant.operation, plant.argument, plant.payload);
+            }
+            println!();
         }
     }
+    print_outcome_summary(&outcomes, coverage.as_deref());
+    if findings.is_empty() {
+        println!("{} Nothing stood out ({} requests)", "[+]".green(), fuzzer.requests());
+    } else {
+        let operations: HashSet<&str> = findings.iter().map(|f| f.operation.as_str()).collect();
+        println!(
+            "{} {} anomalies in {} operations ({} requests)",
+            "[!]".yellow(),
+            findings.len(),
+            operations.len(),
+            fuzzer.requests()
+        );
+    }
 
     Ok(())
 }
 
-async fn run_infer(
+#[allow(clippy::too_many_arguments)]
+async fn run_idor(
     target: String,
+    ids: Vec<String>,
+    schema_path: Option<PathBuf>,
     headers: Vec<String>,
     proxy: Option<String>,
-    wordlist: Option<PathBuf>,
-    output: Option<PathBuf>,
+    filter: Option<String>,
+    spread: u64,
+    evasion: EvasionArgs,
+    output: String,
 ) -> Result<()> {
-    print_banner();
+    let text = output == "text";
+    if text {
+        print_banner();
+    }
 
     let headers_map = parse_headers(&headers)?;
-    let client = HttpClient::new(proxy.as_deref(), headers_map, false)?;
+    let client = evasion.apply(HttpClient::new(proxy.as_deref(), headers_map, false)?)?;
+    let schema = match &schema_path {
+        Some(path) => load_schema_file(path)?,
+        None => {
+            if text {
+                println!("{} Fetching introspection from {}...", "[*]".cyan(), target);
+            }
+            fetch_schema(&client, &target)
+                .await
+                .context("Introspection failed, pass the schema with --schema")?
+        }
+    };
 
-    println!(
-        "{} Inferring schema from {} (introspection disabled mode)...\n",
-        "[*]".cyan(),
-        target
-    );
+    let filter = OperationFilter::new(filter.as_deref(), &[])?;
+    let mut scanner = IdorScanner::new(client, &target, schema)
+        .with_filter(filter)
+        .with_spread(spread);
 
-    // Load wordlist
-    let words = match wordlist {
-        Some(path) => {
+    let callback = |event: &IdorEvent| match event {
+        IdorEvent::Seed(operation, seed, candidates) => {
+            println!("{} {}({}) returns an object, trying {} neighbors", "[*]".cyan(), operation, seed, candidates);
+        }
+        IdorEvent::Finding(finding) => {
             println!(
-                "{} Loading wordlist from {}...",
-                "[*]".cyan(),
-                path.display()
+                "  {} {} ({}): {}",
+                "[!]".yellow(),
+                finding.candidate.bold(),
+                finding.strategy,
+                finding.object.dimmed()
             );
-            load_inference_wordlist(path.to_str().unwrap())?
         }
+    };
+    let findings = scanner.run(&ids, if text { Some(&callback) } else { None }).await?;
+
+    if !text {
+        println!("{}", serde_json::to_string_pretty(&findings)?);
+        return Ok(());
+    }
+
+    println!();
+    if findings.is_empty() {
+        println!("{} No other objects came back ({} requests)", "[+]".green(), scanner.requests());
+    } else {
+        let operations: HashSet<&str> = findings.iter().map(|f| f.operation.as_str()).collect();
+        println!(
+            "{} {} objects besides the seeds through {} operations ({} requests)",
+            "[!]".yellow(),
+            findings.len(),
+            operations.len(),
+            scanner.requests()
+        );
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_authz(
+    target: String,
+    contexts: Vec<String>,
+    schema_path: Option<PathBuf>,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    filter: Option<String>,
+    variables: Vec<PathBuf>,
+    mutations: bool,
+    guard: MutationGuard,
+    coverage: Option<PathBuf>,
+    evasion: EvasionArgs,
+    output: String,
+) -> Result<()> {
+    let text = output == "text";
+    let variables = load_variables(&variables)?;
+    let shared = parse_headers(&headers)?;
+    let mut clients = Vec::new();
+    for spec in &contexts {
+        let context = AuthContext::load(spec)?;
+        if clients.iter().any(|(name, _)| *name == context.name) {
+            bail!("Two contexts named '{}'", context.name);
+        }
+        let mut context_headers = shared.clone();
+        context_headers.extend(context.headers);
+        let client = HttpClient::new(proxy.as_deref(), context_headers, false)?;
+        clients.push((context.name, evasion.apply(client)?));
+    }
+    if text {
+        print_banner();
+    }
+
+    let schema = match &schema_path {
+        Some(path) => load_schema_file(path)?,
         None => {
-            println!("{} Using built-in wordlist ({} words)...", "[*]".cyan(), default_wordlist().len());
-            default_wordlist()
+            let (name, client) = &clients[0];
+            if text {
+                println!("{} Fetching introspection from {} as {}...", "[*]".cyan(), target, name);
+            }
+            fetch_schema(client, &target)
+                .await
+                .context("Introspection failed, pass the schema with --schema")?
+        }
+    };
+
+    let filter = OperationFilter::new(filter.as_deref(), &[])?;
+    let mut matrix = AccessMatrix::new(&target, schema)
+        .with_filter(filter)
+        .with_variables(variables)
+        .with_mutations(mutations, guard);
+    for (name, client) in clients {
+        matrix = matrix.with_context(&name, client);
+    }
+
+    let names: Vec<String> = matrix.context_names().iter().map(|n| n.to_string()).collect();
+    let width = names.iter().map(|n| n.len()).max().unwrap_or(0).max("allowed!".len()) + 2;
+    let callback = |row: &AccessRow| {
+        let mut line = format!("{:<32} ", format!("{} {}", row.kind, row.operation));
+        for cell in &row.cells {
+            let label = if cell.unexpected {
+                format!("{:<width$}", format!("{}!", cell.access)).red().bold().to_string()
+            } else {
+                let padded = format!("{:<width$}", cell.access.to_string());
+                match cell.access {
+                    Access::Allowed => padded.green().to_string(),
+                    Access::Denied => padded.dimmed().to_string(),
+                    Access::Error => padded.yellow().to_string(),
+                }
+            };
+            line.push_str(&label);
+        }
+        println!("{}", line);
+    };
+    if text {
+        let mut header = format!("{:<32} ", "operation");
+        for name in &names {
+            header.push_str(&format!("{:<width$}", name));
+        }
+        println!("{}", header.bold());
+    }
+    let rows = matrix.run(if text { Some(&callback) } else { None }).await?;
+    let outcomes = matrix.outcomes();
+    if let Some(path) = &coverage {
+        std::fs::write(path, serde_json::to_string_pretty(&outcomes)?)?;
+    }
+
+    if !text {
+        println!("{}", serde_json::to_string_pretty(&rows)?);
+        return Ok(());
+    }
+
+    println!();
+    for (i, name) in names.iter().enumerate() {
+        let allowed = rows.iter().filter(|r| r.cells[i].access == Access::Allowed).count();
+        println!("{} {}: {}/{} operations allowed", "[*]".cyan(), name, allowed, rows.len());
+    }
+    if !matrix.held_back().is_empty() {
+        let held: Vec<String> = matrix
+            .held_back()
+            .iter()
+            .map(|(name, class)| format!("{} ({})", name, class))
+            .collect();
+        println!("{} Held back {} mutations: {}", "[-]".red(), held.len(), held.join(", "));
+    }
+    print_outcome_summary(&outcomes, coverage.as_deref());
+    let unexpected: Vec<&AccessRow> = rows.iter().filter(|r| r.has_unexpected()).collect();
+    if unexpected.is_empty() {
+        println!("{} No unexpected access ({} requests)", "[+]".green(), matrix.requests());
+    } else {
+        println!(
+            "{} {} operations let a less privileged context in ({} requests):",
+            "[!]".yellow(),
+            unexpected.len(),
+            matrix.requests()
+        );
+        for row in unexpected {
+            let who: Vec<&str> = row.cells.iter().filter(|c| c.unexpected).map(|c| c.context.as_str()).collect();
+            println!("    {} {} ({})", row.kind, row.operation.bold(), who.join(", "));
+        }
+    }
+
+    Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_exec(
+    target: String,
+    schema_path: Option<PathBuf>,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    filter: Option<String>,
+    variables: Vec<PathBuf>,
+    mutations: bool,
+    guard: MutationGuard,
+    evasion: EvasionArgs,
+    output: String,
+) -> Result<()> {
+    let text = output == "text";
+    if text {
+        print_banner();
+    }
+
+    let headers_map = parse_headers(&headers)?;
+    let client = evasion.apply(HttpClient::new(proxy.as_deref(), headers_map, false)?)?;
+    let schema = match &schema_path {
+        Some(path) => load_schema_file(path)?,
+        None => {
+            if text {
+                println!("{} Fetching introspection from {}...", "[*]".cyan(), target);
+            }
+            fetch_schema(&client, &target)
+                .await
+                .context("Introspection failed, pass the schema with --schema")?
         }
     };
 
-    let mut inferrer = SchemaInferrer::new(client, target.clone(), words);
+    let filter = OperationFilter::new(filter.as_deref(), &[])?;
+    let mut executor = Executor::new(client, &target, schema)
+        .with_filter(filter)
+        .with_variables(load_variables(&variables)?)
+        .with_mutations(mutations, guard);
 
-    let callback = |msg: &str| {
-        println!("{} {}", "[*]".cyan(), msg);
+    let callback = |result: &ExecResult| {
+        let status = result.status.map(|s| s.to_string()).unwrap_or_else(|| "-".to_string());
+        let outcome = match result.outcome {
+            Some(outcome) => outcome.to_string(),
+            None => "error".to_string(),
+        };
+        let padded = format!("{:<18}", outcome);
+        let outcome = match result.outcome {
+            Some(FieldOutcome::Success) => padded.green(),
+            Some(FieldOutcome::AuthDenied) => padded.dimmed(),
+            _ => padded.yellow(),
+        };
+        let classes: Vec<String> = result.error_classes.iter().map(|c| c.to_string()).collect();
+        println!(
+            "{:<32} {:<5} {} {:>8}  {}",
+            format!("{} {}", result.kind, result.operation),
+            status,
+            outcome,
+            format!("{}B", result.size),
+            classes.join(", ").dimmed()
+        );
     };
+    if text {
+        println!("{}", format!("{:<32} {:<5} {:<18} {:>8}  errors", "operation", "http", "outcome", "size").bold());
+    }
+    let results = executor.run(if text { Some(&callback) } else { None }).await?;
 
-    let schema = inferrer.infer(Some(&callback)).await?;
-
-    // Count discovered items
-    let query_fields = schema.query_type.as_ref().map(|t| t.fields.len()).unwrap_or(0);
-    let mutation_fields = schema.mutation_type.as_ref().map(|t| t.fields.len()).unwrap_or(0);
-    let total_types = schema.types.len();
+    if !text {
+        println!("{}", serde_json::to_string_pretty(&results)?);
+        return Ok(());
+    }
 
     println!();
+    if !executor.held_back().is_empty() {
+        let held: Vec<String> = executor
+            .held_back()
+            .iter()
+            .map(|(name, class)| format!("{} ({})", name, class))
+            .collect();
+        println!("{} Held back {} mutations: {}", "[-]".red(), held.len(), held.join(", "));
+    }
+    let working = results.iter().filter(|r| r.works()).count();
     println!(
-        "{} Discovered: {} query fields, {} mutation fields, {} types",
-        "[+]".green(),
-        query_fields,
-        mutation_fields,
-        total_types
+        "{} {}/{} operations returned data ({} requests)",
+        if working > 0 { "[+]".green() } else { "[-]".red() },
+        working,
+        results.len(),
+        executor.requests()
     );
 
-    // Convert to introspection format
-    let introspection_format = inferrer.to_introspection_format(&schema);
-    let json_output = serde_json::to_string_pretty(&introspection_format)?;
+    Ok(())
+}
 
-    match output {
+/// Every `--variables` file or directory, later ones winning
+fn load_variables(paths: &[PathBuf]) -> Result<VariableOverrides> {
+    let mut variables = VariableOverrides::default();
+    for path in paths {
+        variables.merge(VariableOverrides::load(path)?);
+    }
+    Ok(variables)
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_subscribe(
+    target: String,
+    query: String,
+    variables: Option<PathBuf>,
+    init: Option<String>,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    duration: Option<u64>,
+    count: Option<usize>,
+    output: String,
+) -> Result<()> {
+    let text = output == "text";
+    let variables = match &variables {
         Some(path) => {
-            std::fs::write(&path, &json_output)?;
-            println!("{} Inferred schema saved to {}", "[+]".green(), path.display());
+            let content =
+                std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+            Some(serde_json::from_str(&content).with_context(|| format!("{} isn't valid JSON", path.display()))?)
         }
-        None => {
-            println!("\n{}", json_output);
+        None => None,
+    };
+    let init = match &init {
+        Some(raw) => serde_json::from_str(raw).context("--init isn't valid JSON")?,
+        None => serde_json::json!({}),
+    };
+    if text {
+        print_banner();
+    }
+
+    let client = HttpClient::new(proxy.as_deref(), parse_headers(&headers)?, false)?;
+    let mut socket = GraphQLSocket::connect(&client, &target, Some("subscribe"))
+        .await
+        .with_context(|| format!("WebSocket handshake with {} failed", target))?;
+    if !socket.init(init, SUBSCRIBE_ACK_WAIT).await? {
+        bail!("Server didn't acknowledge the connection (does it want auth in --init?)");
+    }
+    if text {
+        println!("{} Connected to {} ({})", "[*]".cyan(), target, socket.protocol);
+    }
+    socket.subscribe("1", &query, variables).await?;
+
+    let started = Instant::now();
+    let deadline = duration.map(|secs| started + Duration::from_secs(secs));
+    let mut events = 0;
+    // Whether the server ended the operation itself, so there's nothing to stop
+    let mut ended = false;
+    loop {
+        if count.is_some_and(|count| events >= count) {
+            break;
+        }
+        let wait = match deadline {
+            Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                Some(wait) if !wait.is_zero() => wait,
+                _ => break,
+            },
+            None => Duration::from_secs(60),
+        };
+        let message = tokio::select! {
+            message = socket.next_message("1", wait) => message?,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let elapsed = started.elapsed();
+
+        match message {
+            Some(SubscriptionMessage::Next(payload)) => {
+                events += 1;
+                if text {
+                    let marker = if payload.get("errors").is_some() { "[!]".yellow() } else { "[+]".green() };
+                    println!("{} #{} +{:.2}s {}", marker, events, elapsed.as_secs_f64(), payload);
+                } else {
+                    let line = serde_json::json!({"type": "next", "elapsed_ms": elapsed.as_millis(), "payload": payload});
+                    println!("{}", line);
+                }
+            }
+            Some(SubscriptionMessage::Error(payload)) => {
+                if text {
+                    println!("{} Subscription failed: {}", "[-]".red(), payload);
+                } else {
+                    let line = serde_json::json!({"type": "error", "elapsed_ms": elapsed.as_millis(), "payload": payload});
+                    println!("{}", line);
+                }
+                ended = true;
+                break;
+            }
+            Some(SubscriptionMessage::Complete) => {
+                if text {
+                    println!("{} Server completed the subscription", "[*]".cyan());
+                }
+                ended = true;
+                break;
+            }
+            None if socket.is_closed() => {
+                if text {
+                    println!("{} Server closed the connection", "[-]".red());
+                }
+                ended = true;
+                break;
+            }
+            None => continue,
         }
     }
+    if !ended {
+        socket.stop("1").await.ok();
+    }
+
+    if text {
+        println!(
+            "\n{} {} events in {:.1}s",
+            "[*]".cyan(),
+            events,
+            started.elapsed().as_secs_f64()
+        );
+    }
 
     Ok(())
 }
 
-async fn run_export_bruno(schema_path: PathBuf, output: PathBuf, url: String) -> Result<()> {
-    print_banner();
-
-    println!("{} Loading schema from {}...", "[*]".cyan(), schema_path.display());
-
-    let schema_content = std::fs::read_to_string(&schema_path)
-        .context("Failed to read schema file")?;
+#[allow(clippy::too_many_arguments)]
+async fn run_federation(
+    target: String,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    ports: Option<Vec<u16>>,
+    no_probe: bool,
+    sdl: Option<PathBuf>,
+    output: String,
+) -> Result<()> {
+    let text = output == "text";
+    if text {
+        print_banner();
+        println!("{} Looking for federation at {}...", "[*]".cyan(), target);
+    }
 
-    let schema_json: Value = serde_json::from_str(&schema_content)
-        .context("Failed to parse schema JSON")?;
+    let client = HttpClient::new(proxy.as_deref(), parse_headers(&headers)?, false)?;
+    let mut scanner = FederationScanner::new(client, &target).with_probe(!no_probe);
+    if let Some(ports) = ports {
+        scanner = scanner.with_ports(ports);
+    }
 
-    // Handle both {"data": {...}} and direct schema format
-    let schema_data = if let Some(data) = schema_json.get("data") {
-        data.clone()
-    } else {
-        schema_json
+    let callback = |subgraph: &Subgraph| {
+        if !subgraph.reachable {
+            return;
+        }
+        let name = subgraph.name.as_deref().map(|n| format!(" ({})", n)).unwrap_or_default();
+        if subgraph.sdl_exposed {
+            println!("{} {}{} answers and hands out its SDL", "[!]".yellow(), subgraph.url.bold(), name);
+        } else {
+            println!("{} {}{} answers GraphQL", "[+]".green(), subgraph.url.bold(), name);
+        }
     };
+    let report = scanner.run(if text { Some(&callback) } else { None }).await?;
+    if let (Some(path), Some(content)) = (&sdl, &report.sdl) {
+        std::fs::write(path, content)?;
+    }
 
-    let schema: gqlmap::schema::Schema = serde_json::from_value(schema_data)
-        .context("Failed to parse introspection schema")?;
-
-    let exporter = BrunoExporter::new(schema, url);
-    let stats = exporter.export(&output)?;
+    if !text {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
 
-    println!(
-        "{} Exported {} queries and {} mutations to {}",
-        "[+]".green(),
-        stats.queries,
-        stats.mutations,
-        output.display()
-    );
+    println!();
+    let role = match report.role {
+        Some(role) => role,
+        None => {
+            println!("{} No sign of federation ({} requests)", "[-]".red(), scanner.requests());
+            return Ok(());
+        }
+    };
+    println!("{} Federated {}", "[+]".green(), role.to_string().bold());
+    for signal in &report.signals {
+        println!("    {}", signal.dimmed());
+    }
+    if !report.subgraph_names.is_empty() {
+        println!("{} Subgraphs: {}", "[*]".cyan(), report.subgraph_names.join(", "));
+    }
+    if !report.entities.is_empty() {
+        println!("{} {} entities:", "[*]".cyan(), report.entities.len());
+        for entity in &report.entities {
+            if entity.keys.is_empty() {
+                println!("    {}", entity.name);
+            } else {
+                println!("    {} @key({})", entity.name, entity.keys.join(" | ").dimmed());
+            }
+        }
+    }
+    if let (Some(path), Some(_)) = (&sdl, &report.sdl) {
+        println!("{} SDL saved to {}", "[+]".green(), path.display());
+    }
+    let reachable = report.subgraphs.iter().filter(|s| s.reachable).count();
+    if !no_probe {
+        println!(
+            "{} {}/{} candidate subgraph URLs answered ({} requests)",
+            if reachable > 0 { "[!]".yellow() } else { "[*]".cyan() },
+            reachable,
+            report.subgraphs.len(),
+            scanner.requests()
+        );
+    }
 
     Ok(())
 }
 
-async fn run_export_postman(schema_path: PathBuf, output: PathBuf, url: String) -> Result<()> {
-    print_banner();
-
-    println!("{} Loading schema from {}...", "[*]".cyan(), schema_path.display());
-
-    let schema_content = std::fs::read_to_string(&schema_path)
-        .context("Failed to read schema file")?;
-
-    let schema_json: Value = serde_json::from_str(&schema_content)
-        .context("Failed to parse schema JSON")?;
-
-    let schema_data = if let Some(data) = schema_json.get("data") {
-        data.clone()
-    } else {
-        schema_json
+fn run_apq_hash(source: Option<PathBuf>, query: Option<String>) -> Result<()> {
+    let document = match (&source, query) {
+        (Some(path), _) => std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?,
+        (None, Some(query)) => query,
+        (None, None) => bail!("Pass a .graphql file or --query"),
     };
+    let hash = apq_hash(&document);
+    println!("{}", hash);
+    println!("{}", serde_json::json!({"extensions": apq_extensions(&hash)}));
+    Ok(())
+}
 
-    let schema: gqlmap::schema::Schema = serde_json::from_value(schema_data)
-        .context("Failed to parse introspection schema")?;
+async fn run_apq_probe(
+    target: String,
+    hashes: PathBuf,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    get: bool,
+    output: String,
+) -> Result<()> {
+    let text = output == "text";
+    let hashes = load_hashes(&hashes)?;
+    if hashes.is_empty() {
+        bail!("No hashes to try");
+    }
+    if text {
+        print_banner();
+    }
 
-    let exporter = PostmanExporter::new(schema, url);
-    let collection = exporter.export()?;
+    let client = HttpClient::new(proxy.as_deref(), parse_headers(&headers)?, false)?;
+    let mut prober = PersistedQueryProber::new(client, &target).with_get(get);
+    match prober.check_support().await {
+        PersistedStatus::NotFound => {
+            if text {
+                println!("{} APQ is on: a made-up hash gets PersistedQueryNotFound", "[+]".green());
+            }
+        }
+        PersistedStatus::Unsupported => bail!("The server doesn't do automatic persisted queries"),
+        status => bail!(
+            "Can't tell registered hashes apart: a made-up hash came back {}{}",
+            status,
+            if get { "" } else { " (try --get)" }
+        ),
+    }
 
-    let json_output = serde_json::to_string_pretty(&collection)?;
-    std::fs::write(&output, json_output)?;
+    let callback = |probe: &PersistedProbe| {
+        if probe.status != PersistedStatus::Registered {
+            return;
+        }
+        let label = probe.label.as_deref().map(|l| format!(" ({})", l)).unwrap_or_default();
+        println!(
+            "{} {}{}: {}",
+            "[+]".green(),
+            probe.hash.bold(),
+            label,
+            probe.detail.as_deref().unwrap_or_default().dimmed()
+        );
+    };
+    let probes = prober.run(&hashes, if text { Some(&callback) } else { None }).await;
 
-    let query_count: usize = collection.item.iter()
-        .filter(|f| f.name == "Queries")
-        .map(|f| f.item.len())
-        .sum();
-    let mutation_count: usize = collection.item.iter()
-        .filter(|f| f.name == "Mutations")
-        .map(|f| f.item.len())
-        .sum();
+    if !text {
+        println!("{}", serde_json::to_string_pretty(&probes)?);
+        return Ok(());
+    }
 
+    let registered = probes.iter().filter(|p| p.status == PersistedStatus::Registered).count();
     println!(
-        "{} Exported {} queries and {} mutations to {}",
-        "[+]".green(),
-        query_count,
-        mutation_count,
-        output.display()
+        "\n{} {}/{} hashes are registered ({} requests)",
+        if registered > 0 { "[+]".green() } else { "[-]".red() },
+        registered,
+        probes.len(),
+        prober.requests()
     );
 
     Ok(())
 }
 
-async fn run_export_curl(schema_path: PathBuf, output: PathBuf, url: String) -> Result<()> {
-    print_banner();
-
-    println!("{} Loading schema from {}...", "[*]".cyan(), schema_path.display());
-
-    let schema_content = std::fs::read_to_string(&schema_path)
-        .context("Failed to read schema file")?;
-
-    let schema_json: Value = serde_json::from_str(&schema_content)
-        .context("Failed to parse schema JSON")?;
+#[allow(clippy::too_many_arguments)]
+async fn run_dump(
+    target: String,
+    field: String,
+    schema_path: Option<PathBuf>,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    max_pages: usize,
+    page_size: usize,
+    select: Option<String>,
+    variables: Vec<PathBuf>,
+    per_minute: Option<u32>,
+    evasion: EvasionArgs,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    // With no file the objects go to stdout, so nothing else does
+    let text = output.is_some();
+    if text {
+        print_banner();
+    }
 
-    let schema_data = if let Some(data) = schema_json.get("data") {
-        data.clone()
-    } else {
-        schema_json
+    let client = evasion.apply(HttpClient::new(proxy.as_deref(), parse_headers(&headers)?, false)?)?;
+    let schema = match &schema_path {
+        Some(path) => load_schema_file(path)?,
+        None => {
+            if text {
+                println!("{} Fetching introspection from {}...", "[*]".cyan(), target);
+            }
+            fetch_schema(&client, &target)
+                .await
+                .context("Introspection failed, pass the schema with --schema")?
+        }
     };
 
-    let schema: gqlmap::schema::Schema = serde_json::from_value(schema_data)
-        .context("Failed to parse introspection schema")?;
+    let mut walker = PageWalker::new(client, &target, schema, &field)?
+        .with_page_size(page_size)
+        .with_max_pages(max_pages)
+        .with_selection(select)
+        .with_variables(load_variables(&variables)?);
+    if let Some(rate) = per_minute {
+        walker = walker.with_rate_limit(rate);
+    }
+    let mut sink = dump_sink(output.as_deref())?;
+    if text {
+        let connection = walker.connection();
+        println!(
+            "{} Walking {} ({} objects, {} per page, up to {} pages)",
+            "[*]".cyan(),
+            connection.field.bold(),
+            connection.node_type,
+            page_size,
+            max_pages
+        );
+    }
 
-    let exporter = CurlExporter::new(schema, url);
-    let stats = exporter.export(&output)?;
+    let callback = |page: &DumpPage| match &page.error {
+        Some(error) => println!("{} page {}: {} records, {}", "[!]".yellow(), page.page, page.records, error),
+        None => println!("{} page {}: {} records", "[+]".green(), page.page, page.records),
+    };
+    let summary = walker.run(&mut sink, if text { Some(&callback) } else { None }).await?;
 
-    println!(
-        "{} Exported {} queries and {} mutations to {}",
-        "[+]".green(),
-        stats.queries,
-        stats.mutations,
-        output.display()
-    );
+    if let Some(path) = &output {
+        println!(
+            "\n{} {} records from {} pages saved to {}{} ({} requests)",
+            if summary.records > 0 { "[+]".green() } else { "[-]".red() },
+            summary.records,
+            summary.pages,
+            path.display(),
+            if summary.complete { "" } else { ", more left" },
+            walker.requests()
+        );
+    }
 
     Ok(())
 }
 
-async fn run_export_inql(schema_path: PathBuf, output: PathBuf, url: String) -> Result<()> {
-    print_banner();
-
-    println!("{} Loading schema from {}...", "[*]".cyan(), schema_path.display());
-
-    let schema_content = std::fs::read_to_string(&schema_path)
-        .context("Failed to read schema file")?;
+#[allow(clippy::too_many_arguments)]
+async fn run_dump_template(
+    target: String,
+    query: PathBuf,
+    iterate: Vec<String>,
+    only_data: bool,
+    headers: Vec<String>,
+    proxy: Option<String>,
+    variables: Vec<PathBuf>,
+    per_minute: Option<u32>,
+    evasion: EvasionArgs,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let text = output.is_some();
+    let document = std::fs::read_to_string(&query).with_context(|| format!("Failed to read {}", query.display()))?;
+    let iterations = iterate.iter().map(|spec| Iteration::parse(spec)).collect::<Result<Vec<_>>>()?;
 
-    let schema_json: Value = serde_json::from_str(&schema_content)
-        .context("Failed to parse schema JSON")?;
+    let client = evasion.apply(HttpClient::new(proxy.as_deref(), parse_headers(&headers)?, false)?)?;
+    let mut dumper = TemplateDumper::new(client, &target, &document, iterations)?
+        .with_variables(load_variables(&variables)?)
+        .with_only_data(only_data);
+    if let Some(rate) = per_minute {
+        dumper = dumper.with_rate_limit(rate);
+    }
+    let mut sink = dump_sink(output.as_deref())?;
+    if text {
+        print_banner();
+        println!("{} Sending {} {} times", "[*]".cyan(), query.display(), dumper.total());
+    }
 
-    let schema_data = if let Some(data) = schema_json.get("data") {
-        data.clone()
-    } else {
-        schema_json
+    // Live status line; hidden when stderr isn't a terminal or the lines go to stdout
+    let bar = if text { ProgressBar::new(dumper.total() as u64) } else { ProgressBar::hidden() };
+    bar.set_style(ProgressStyle::with_template("{spinner:.cyan} {pos}/{len} {msg} ({eta} left)").unwrap());
+    bar.enable_steady_tick(Duration::from_millis(120));
+    let found = std::cell::Cell::new(0);
+    let callback = |record: &TemplateRecord| {
+        if record.has_data() {
+            found.set(found.get() + 1);
+        }
+        bar.set_message(format!("{} with data", found.get()));
+        bar.inc(1);
     };
+    let result = dumper.run(&mut sink, Some(&callback)).await;
+    bar.finish_and_clear();
+    let summary = result?;
 
-    let schema: gqlmap::schema::Schema = serde_json::from_value(schema_data)
-        .context("Failed to parse introspection schema")?;
-
-    let exporter = InqlExporter::new(schema, url);
-    let stats = exporter.export(&output)?;
-
-    println!(
-        "{} Exported {} queries and {} mutations to {}",
-        "[+]".green(),
-        stats.queries,
-        stats.mutations,
-        output.display()
-    );
+    if let Some(path) = &output {
+        println!(
+            "\n{} {}/{} responses had data, saved to {} ({} requests)",
+            if summary.with_data > 0 { "[+]".green() } else { "[-]".red() },
+            summary.with_data,
+            summary.sent,
+            path.display(),
+            dumper.requests()
+        );
+    }
 
     Ok(())
 }
 
+/// Where `dump` writes its JSON lines
+fn dump_sink(output: Option<&Path>) -> Result<Box<dyn std::io::Write>> {
+    Ok(match output {
+        Some(path) => Box::new(std::io::BufWriter::new(
+            std::fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?,
+        )),
+        None => Box::new(std::io::stdout().lock()),
+    })
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let cli = Cli::parse();
+    if cli.verbose > 0 {
+        init_request_log(cli.verbose, cli.log_file.as_deref())?;
+    }
+    init_pool(Pool {
+        max_idle_per_host: cli.pool_max_idle,
+        idle_timeout: cli.pool_idle_timeout.map(Duration::from_secs),
+        tcp_keepalive: cli.tcp_keepalive.map(Duration::from_secs),
+        tcp_nodelay: !cli.no_tcp_nodelay,
+    });
 
-    match cli.command {
+    let result = match cli.command {
         Commands::Scan {
             target,
+            targets_file,
             headers,
             proxy,
             output,
             exclude,
+            include,
             debug,
             force,
             discover,
             wordlist,
             list_tests,
+            coverage,
+            schema,
+            known_account,
+            verify_dos,
+            no_watchdog,
+            shuffle,
+            evasion,
+            progress,
+            progress_file,
+            push_metrics,
         } => {
+            let shuffle = shuffle || evasion.stealth;
+            let progress = match (progress, progress_file) {
+                (None, _) => None,
+                (Some(_), Some(path)) => Some(Progress::to_file(&path)?),
+                (Some(_), None) => Some(Progress::stderr()),
+            };
             run_scan(
-                target, headers, proxy, output, exclude, debug, force, discover, wordlist,
-                list_tests,
+                target, targets_file, headers, proxy, output, exclude, include, debug, force, discover, wordlist,
+                list_tests, coverage, schema, known_account, verify_dos, no_watchdog, shuffle, evasion, progress, push_metrics,
+            )
+            .await
+        }
+        Commands::Report { action } => match action {
+            ReportCommand::Merge { files, output } => run_report_merge(files, output).await,
+        },
+        Commands::Tests { action } => match action {
+            TestsCommand::List { format } => run_tests_list(format).await,
+        },
+        Commands::Discover {
+            target,
+            headers,
+            proxy,
+            wordlist,
+            output,
+            all,
+            bundles,
+            operations,
+            subdomains,
+            ports,
+            paths,
+            websocket,
+            combine,
+            max_paths,
+            passive,
+            wayback,
+        } => {
+            let max_paths = combine.then_some(max_paths);
+            run_discover(
+                target, headers, proxy, wordlist, output, all, bundles, operations, subdomains, ports, paths,
+                websocket, max_paths, passive, wayback,
             )
             .await
         }
@@ -675,20 +4429,305 @@ async fn main() -> Result<()> {
             proxy,
             wordlist,
             output,
-        } => run_infer(target, headers, proxy, wordlist, output).await,
-        Commands::Export { format } => match format {
-            ExportFormat::Bruno { schema, output, url } => {
-                run_export_bruno(schema, output, url).await
+            bucket_size,
+            depth,
+            checkpoint,
+            resume,
+            seed,
+            format,
+            engine,
+            mangle,
+            get,
+            harvest,
+            traffic,
+            max_requests,
+            per_minute,
+        } => {
+            run_infer(
+                target, headers, proxy, wordlist, output, bucket_size, depth, checkpoint, resume, seed, format, engine,
+                mangle, get, harvest, traffic, max_requests, per_minute,
+            )
+            .await
+        }
+        Commands::Schema { action } => match action {
+            SchemaCommand::Diff { old, new, output } => run_schema_diff(old, new, output).await,
+            SchemaCommand::Drift {
+                targets,
+                headers,
+                proxy,
+                no_infer,
+                max_requests,
+                output,
+            } => run_schema_drift(targets, headers, proxy, no_infer, max_requests, output).await,
+            SchemaCommand::Audit { schema, output } => run_schema_audit(schema, output).await,
+            SchemaCommand::Lint {
+                schema,
+                exclude,
+                list_rules,
+                output,
+            } => run_schema_lint(schema, exclude, list_rules, output).await,
+            SchemaCommand::Coverage {
+                schema,
+                queries,
+                output,
+            } => run_schema_coverage(schema, queries, output).await,
+            SchemaCommand::Wordlist {
+                schema,
+                output,
+                no_split,
+            } => run_schema_wordlist(schema, output, no_split).await,
+            SchemaCommand::Graph {
+                schema,
+                format,
+                root,
+                depth,
+                output,
+            } => run_schema_graph(schema, format, root, depth, output).await,
+            SchemaCommand::Merge { sources, output } => run_schema_merge(sources, output).await,
+            SchemaCommand::Cycles {
+                schema,
+                max_length,
+                limit,
+                output,
+            } => run_schema_cycles(schema, max_length, limit, output).await,
+            SchemaCommand::FromTraffic { captures, output } => run_schema_from_traffic(captures, output).await,
+            SchemaCommand::Convert { input, output, to } => {
+                run_schema_convert(input, output, to).await
+            }
+        },
+        Commands::Generate {
+            kind,
+            schema,
+            depth,
+            count,
+            page_size,
+            json,
+            output,
+        } => run_generate(kind, schema, depth, count, page_size, json, output).await,
+        Commands::Fuzz {
+            target,
+            schema,
+            headers,
+            proxy,
+            filter,
+            guard,
+            traffic,
+            packs,
+            payloads,
+            oob,
+            stored,
+            coverage,
+            evasion,
+            output,
+        } => {
+            let mutations = guard.mutations;
+            run_fuzz(
+                target, schema, headers, proxy, filter, mutations, guard.build()?, traffic, packs, payloads, oob,
+                stored, coverage, evasion, output,
+            )
+            .await
+        }
+        Commands::Idor {
+            target,
+            ids,
+            schema,
+            headers,
+            proxy,
+            filter,
+            spread,
+            evasion,
+            output,
+        } => run_idor(target, ids, schema, headers, proxy, filter, spread, evasion, output).await,
+        Commands::Authz {
+            target,
+            contexts,
+            schema,
+            headers,
+            proxy,
+            filter,
+            variables,
+            guard,
+            coverage,
+            evasion,
+            output,
+        } => {
+            let mutations = guard.mutations;
+            run_authz(
+                target, contexts, schema, headers, proxy, filter, variables, mutations, guard.build()?, coverage,
+                evasion, output,
+            )
+            .await
+        }
+        Commands::Exec {
+            target,
+            schema,
+            headers,
+            proxy,
+            filter,
+            variables,
+            guard,
+            evasion,
+            output,
+        } => {
+            let mutations = guard.mutations;
+            run_exec(
+                target, schema, headers, proxy, filter, variables, mutations, guard.build()?, evasion, output,
+            )
+            .await
+        }
+        Commands::Subscribe {
+            target,
+            query,
+            variables,
+            init,
+            headers,
+            proxy,
+            duration,
+            count,
+            output,
+        } => run_subscribe(target, query, variables, init, headers, proxy, duration, count, output).await,
+        Commands::Federation {
+            target,
+            headers,
+            proxy,
+            ports,
+            no_probe,
+            sdl,
+            output,
+        } => run_federation(target, headers, proxy, ports, no_probe, sdl, output).await,
+        Commands::Apq { action } => match action {
+            ApqCommand::Hash { source, query } => run_apq_hash(source, query),
+            ApqCommand::Probe {
+                target,
+                hashes,
+                headers,
+                proxy,
+                get,
+                output,
+            } => run_apq_probe(target, hashes, headers, proxy, get, output).await,
+        },
+        Commands::Dump {
+            target,
+            field,
+            query,
+            iterate,
+            only_data,
+            schema,
+            headers,
+            proxy,
+            max_pages,
+            page_size,
+            select,
+            variables,
+            per_minute,
+            evasion,
+            output,
+        } => match (field, query) {
+            (Some(field), _) => {
+                run_dump(
+                    target, field, schema, headers, proxy, max_pages, page_size, select, variables, per_minute,
+                    evasion, output,
+                )
+                .await
             }
-            ExportFormat::Postman { schema, output, url } => {
-                run_export_postman(schema, output, url).await
+            (None, Some(query)) => {
+                run_dump_template(
+                    target, query, iterate, only_data, headers, proxy, variables, per_minute, evasion, output,
+                )
+                .await
+            }
+            (None, None) => bail!("Pass --field or --query"),
+        },
+        Commands::Export { format } => match format {
+            ExportFormat::Bruno {
+                source,
+                output,
+                url,
+                group_by,
+                filter,
+            } => {
+                run_export_bruno(source, output, url, filter.build()?, GroupBy::parse(&group_by)?)
+                    .await
             }
-            ExportFormat::Curl { schema, output, url } => {
-                run_export_curl(schema, output, url).await
+            ExportFormat::Postman {
+                source,
+                output,
+                url,
+                group_by,
+                filter,
+                environment,
+                auth_type,
+                api_key_header,
+                token,
+            } => {
+                run_export_postman(
+                    source,
+                    output,
+                    url,
+                    filter.build()?,
+                    GroupBy::parse(&group_by)?,
+                    environment,
+                    auth_type,
+                    api_key_header,
+                    token,
+                )
+                .await
             }
-            ExportFormat::Inql { schema, output, url } => {
-                run_export_inql(schema, output, url).await
+            ExportFormat::Curl {
+                source,
+                output,
+                url,
+                filter,
+            } => run_export_curl(source, output, url, filter.build()?).await,
+            ExportFormat::Httpie {
+                source,
+                output,
+                url,
+                filter,
+            } => run_export_httpie(source, output, url, filter.build()?).await,
+            ExportFormat::Burp {
+                source,
+                output,
+                url,
+                filter,
+            } => run_export_burp(source, output, url, filter.build()?).await,
+            ExportFormat::Inql {
+                source,
+                output,
+                url,
+                group_by,
+                filter,
+            } => {
+                run_export_inql(source, output, url, filter.build()?, GroupBy::parse(&group_by)?)
+                    .await
             }
+            ExportFormat::Catalog {
+                source,
+                output,
+                depth,
+                filter,
+            } => run_export_catalog(source, output, depth, filter.build()?).await,
+            ExportFormat::GraphqlConfig { source, output, url } => run_export_graphql_config(source, output, url).await,
+            ExportFormat::ArmorConfig {
+                schema,
+                findings,
+                format,
+                output,
+            } => run_export_armor(schema, findings, format, output).await,
         },
+    };
+
+    let (requests, connections) = connection_stats();
+    if requests > 0 {
+        log_at(
+            1,
+            &format!(
+                "{} requests over {} connections ({} reused)",
+                requests,
+                connections,
+                requests.saturating_sub(connections)
+            ),
+        );
     }
+    result
 }
@@ -0,0 +1,146 @@
+use super::{random_label, Interaction};
+use crate::http::HttpClient;
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use openssl::encrypt::Decrypter;
+use openssl::hash::MessageDigest;
+use openssl::pkey::{PKey, Private};
+use openssl::rsa::{Padding, Rsa};
+use openssl::symm::{decrypt, Cipher};
+use serde_json::{json, Value};
+
+/// Interactsh ties interactions to a session by this prefix of the first label
+const CORRELATION_ID_LENGTH: usize = 20;
+
+/// A registration with an interactsh server: interactions come back encrypted with a
+/// key only this session can unwrap
+pub struct InteractshSession {
+    client: HttpClient,
+    server: String,
+    domain: String,
+    correlation_id: String,
+    secret: String,
+    key: PKey<Private>,
+}
+
+impl InteractshSession {
+    pub async fn register(client: HttpClient, server: &str) -> Result<Self> {
+        let server = server.trim_end_matches('/').to_string();
+        let domain = url::Url::parse(&server)
+            .ok()
+            .and_then(|u| u.host_str().map(String::from))
+            .with_context(|| format!("Invalid interactsh server URL: {}", server))?;
+
+        let key = PKey::from_rsa(Rsa::generate(2048)?)?;
+        let public_pem = key.public_key_to_pem()?;
+        let session = Self {
+            client,
+            server,
+            domain,
+            correlation_id: random_label(CORRELATION_ID_LENGTH),
+            secret: uuid_v4(),
+            key,
+        };
+
+        let body = json!({
+            "public-key": STANDARD.encode(public_pem),
+            "secret-key": session.secret,
+            "correlation-id": session.correlation_id,
+        });
+        let response = session
+            .client
+            .post_json(&format!("{}/register", session.server), &body, None)
+            .await
+            .context("Failed to register with the interactsh server")?;
+        if response.status != 200 {
+            bail!(
+                "Interactsh server refused registration (HTTP {}): {}",
+                response.status,
+                response.text.trim()
+            );
+        }
+
+        Ok(session)
+    }
+
+    pub fn domain(&self) -> &str {
+        &self.domain
+    }
+
+    /// `<correlation id><token>.<domain>`, the shape the server routes back to us
+    pub fn host(&self, token: &str) -> String {
+        format!("{}{}.{}", self.correlation_id, token, self.domain)
+    }
+
+    pub async fn poll(&self) -> Result<Vec<Interaction>> {
+        let url = format!("{}/poll?id={}&secret={}", self.server, self.correlation_id, self.secret);
+        let response = self.client.get_html(&url, None).await.context("Failed to poll the interactsh server")?;
+        if response.status != 200 {
+            bail!("Interactsh poll failed (HTTP {}): {}", response.status, response.body.trim());
+        }
+        let body: Value = serde_json::from_str(&response.body).context("Interactsh poll reply isn't JSON")?;
+
+        let data: Vec<&str> = body
+            .get("data")
+            .and_then(|d| d.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|d| d.as_str())
+            .collect();
+        if data.is_empty() {
+            return Ok(Vec::new());
+        }
+        let wrapped = body
+            .get("aes_key")
+            .and_then(|k| k.as_str())
+            .context("Interactsh poll reply has no aes_key")?;
+        let aes_key = self.unwrap_key(wrapped)?;
+
+        let mut interactions = Vec::new();
+        for item in data {
+            let bytes = STANDARD.decode(item).context("Interaction isn't base64")?;
+            if bytes.len() <= 16 {
+                continue;
+            }
+            let plain = decrypt(Cipher::aes_256_cfb128(), &aes_key, Some(&bytes[..16]), &bytes[16..])?;
+            if let Ok(interaction) = serde_json::from_slice::<Interaction>(&plain) {
+                interactions.push(interaction);
+            }
+        }
+        Ok(interactions)
+    }
+
+    pub async fn deregister(&self) -> Result<()> {
+        let body = json!({
+            "correlation-id": self.correlation_id,
+            "secret-key": self.secret,
+        });
+        self.client
+            .post_json(&format!("{}/deregister", self.server), &body, None)
+            .await?;
+        Ok(())
+    }
+
+    /// The AES key, RSA-OAEP (SHA-256) encrypted to our public key
+    fn unwrap_key(&self, wrapped: &str) -> Result<Vec<u8>> {
+        let wrapped = STANDARD.decode(wrapped).context("aes_key isn't base64")?;
+        let mut decrypter = Decrypter::new(&self.key)?;
+        decrypter.set_rsa_padding(Padding::PKCS1_OAEP)?;
+        decrypter.set_rsa_oaep_md(MessageDigest::sha256())?;
+        decrypter.set_rsa_mgf1_md(MessageDigest::sha256())?;
+        let mut key = vec![0u8; decrypter.decrypt_len(&wrapped)?];
+        let len = decrypter.decrypt(&wrapped, &mut key)?;
+        key.truncate(len);
+        Ok(key)
+    }
+}
+
+fn uuid_v4() -> String {
+    let mut bytes = [0u8; 16];
+    openssl::rand::rand_bytes(&mut bytes).expect("no randomness available");
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..])
+}
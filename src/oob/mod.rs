@@ -0,0 +1,131 @@
+mod interactsh;
+
+pub use interactsh::*;
+
+use crate::http::HttpClient;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Stands for a unique callback host in a payload, e.g. `http://{oob}/`
+pub const OOB_PLACEHOLDER: &str = "{oob}";
+
+/// Public interactsh server used for `--oob interactsh`
+pub const DEFAULT_INTERACTSH_SERVER: &str = "https://oast.pro";
+
+/// Length of the random label that tells planted hosts apart
+const TOKEN_LENGTH: usize = 13;
+
+/// A DNS lookup or request that reached the callback server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Interaction {
+    /// dns, http, smtp, ...
+    pub protocol: String,
+    #[serde(default)]
+    pub unique_id: String,
+    /// Every label before the server's domain
+    #[serde(default)]
+    pub full_id: String,
+    #[serde(default)]
+    pub remote_address: String,
+    #[serde(default)]
+    pub timestamp: String,
+    #[serde(default)]
+    pub raw_request: String,
+}
+
+enum Provider {
+    Interactsh(Box<InteractshSession>),
+    /// A domain whose DNS you watch yourself (Burp Collaborator, your own server)
+    Collaborator(String),
+}
+
+/// Hands out unique callback hosts and reports which of them got hit
+pub struct OobClient {
+    provider: Provider,
+}
+
+impl OobClient {
+    /// Register with an interactsh server. `client` should not carry the target's
+    /// headers; give it the server's token header if it wants one.
+    pub async fn interactsh(client: HttpClient, server: &str) -> Result<Self> {
+        Ok(Self {
+            provider: Provider::Interactsh(Box::new(InteractshSession::register(client, server).await?)),
+        })
+    }
+
+    /// Plant hosts under a domain you watch yourself; nothing can be polled
+    pub fn collaborator(domain: &str) -> Self {
+        Self {
+            provider: Provider::Collaborator(domain.trim_start_matches("*.").trim_end_matches('.').to_string()),
+        }
+    }
+
+    /// `--oob` value: `interactsh`, an interactsh server URL, or a bare collaborator domain
+    pub async fn from_spec(client: HttpClient, spec: &str) -> Result<Self> {
+        if spec == "interactsh" {
+            Self::interactsh(client, DEFAULT_INTERACTSH_SERVER).await
+        } else if spec.starts_with("http://") || spec.starts_with("https://") {
+            Self::interactsh(client, spec).await
+        } else {
+            Ok(Self::collaborator(spec))
+        }
+    }
+
+    /// Domain the callback hosts live under
+    pub fn domain(&self) -> &str {
+        match &self.provider {
+            Provider::Interactsh(session) => session.domain(),
+            Provider::Collaborator(domain) => domain,
+        }
+    }
+
+    /// Whether interactions can be fetched, rather than watched for by hand
+    pub fn can_poll(&self) -> bool {
+        matches!(self.provider, Provider::Interactsh(_))
+    }
+
+    /// A fresh callback host and the token that identifies it
+    pub fn new_host(&self) -> (String, String) {
+        let token = random_label(TOKEN_LENGTH);
+        let host = match &self.provider {
+            Provider::Interactsh(session) => session.host(&token),
+            Provider::Collaborator(domain) => format!("{}.{}", token, domain),
+        };
+        (token, host)
+    }
+
+    /// Interactions since the last poll
+    pub async fn poll(&self) -> Result<Vec<Interaction>> {
+        match &self.provider {
+            Provider::Interactsh(session) => session.poll().await,
+            Provider::Collaborator(_) => Ok(Vec::new()),
+        }
+    }
+
+    /// Let the server forget this session
+    pub async fn close(&self) -> Result<()> {
+        match &self.provider {
+            Provider::Interactsh(session) => session.deregister().await,
+            Provider::Collaborator(_) => Ok(()),
+        }
+    }
+}
+
+impl Interaction {
+    /// Whether this interaction hit the host planted with `token`
+    pub fn matches(&self, token: &str) -> bool {
+        self.unique_id.contains(token) || self.full_id.to_lowercase().contains(token)
+    }
+}
+
+/// Lowercase letters and digits, valid in any DNS label
+pub(crate) fn random_label(length: usize) -> String {
+    const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+    let mut bytes = vec![0u8; length];
+    openssl::rand::rand_bytes(&mut bytes).expect("no randomness available");
+    bytes
+        .iter()
+        .map(|b| ALPHABET[*b as usize % ALPHABET.len()] as char)
+        .collect()
+}
@@ -0,0 +1,115 @@
+use crate::tests::{Severity, TestResult};
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use std::fmt::Write;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SEVERITIES: &[Severity] = &[Severity::High, Severity::Medium, Severity::Low, Severity::Info];
+
+/// How one target's scan went, as Prometheus gauges
+#[derive(Debug, Clone)]
+pub struct ScanMetrics {
+    pub target: String,
+    /// Whether the target was scanned at all (it looked like GraphQL, or `-f`)
+    pub up: bool,
+    /// Vulnerable results by severity, in [`SEVERITIES`] order
+    findings: [usize; 4],
+    pub tests: usize,
+    /// Tests that failed to run, mostly because a request failed
+    pub test_errors: usize,
+    pub requests: usize,
+    pub duration: Duration,
+}
+
+impl ScanMetrics {
+    pub fn new(target: &str) -> Self {
+        Self {
+            target: target.to_string(),
+            up: false,
+            findings: [0; 4],
+            tests: 0,
+            test_errors: 0,
+            requests: 0,
+            duration: Duration::ZERO,
+        }
+    }
+
+    pub fn with_results(mut self, results: &[TestResult]) -> Self {
+        for result in results.iter().filter(|r| r.vulnerable) {
+            if let Some(i) = SEVERITIES.iter().position(|s| *s == result.severity) {
+                self.findings[i] += 1;
+            }
+        }
+        self
+    }
+
+    /// The text exposition format, every severity included so a fixed finding drops to 0
+    pub fn render(&self) -> String {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        let mut out = String::new();
+        let mut gauge = |name: &str, help: &str, samples: Vec<(String, String)>| {
+            let _ = writeln!(out, "# HELP gqlmap_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE gqlmap_{} gauge", name);
+            for (labels, value) in samples {
+                let _ = writeln!(out, "gqlmap_{}{} {}", name, labels, value);
+            }
+        };
+
+        gauge("up", "Whether the last scan reached a GraphQL endpoint", vec![(String::new(), (self.up as u8).to_string())]);
+        gauge(
+            "findings",
+            "Vulnerable results of the last scan, by severity",
+            SEVERITIES
+                .iter()
+                .zip(self.findings)
+                .map(|(severity, count)| {
+                    (format!("{{severity=\"{}\"}}", severity.to_string().to_lowercase()), count.to_string())
+                })
+                .collect(),
+        );
+        gauge("tests", "Tests run by the last scan", vec![(String::new(), self.tests.to_string())]);
+        gauge(
+            "test_errors",
+            "Tests of the last scan that failed to run, mostly on request errors",
+            vec![(String::new(), self.test_errors.to_string())],
+        );
+        gauge("requests", "Requests sent by the last scan", vec![(String::new(), self.requests.to_string())]);
+        gauge(
+            "scan_duration_seconds",
+            "How long the last scan took",
+            vec![(String::new(), format!("{:.3}", self.duration.as_secs_f64()))],
+        );
+        gauge(
+            "last_scan_timestamp_seconds",
+            "When the last scan finished",
+            vec![(String::new(), timestamp.to_string())],
+        );
+        out
+    }
+
+    /// Replace this target's group on a Prometheus Pushgateway (`job="gqlmap"`,
+    /// `instance` = the target URL). Sent directly, not through the scan's proxy.
+    pub async fn push(&self, gateway: &str) -> Result<()> {
+        let url = format!(
+            "{}/metrics/job/gqlmap/instance@base64/{}",
+            gateway.trim_end_matches('/'),
+            URL_SAFE_NO_PAD.encode(&self.target)
+        );
+        let response = reqwest::Client::new()
+            .put(&url)
+            .header("Content-Type", "text/plain; version=0.0.4")
+            .body(self.render())
+            .send()
+            .await
+            .with_context(|| format!("Failed to push metrics to {}", gateway))?;
+        if !response.status().is_success() {
+            bail!("Pushgateway answered {}", response.status());
+        }
+        Ok(())
+    }
+}
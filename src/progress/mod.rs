@@ -0,0 +1,104 @@
+mod metrics;
+
+pub use metrics::*;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Something a run did, for wrappers to show as it happens
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ProgressEvent {
+    /// A new stage: `discovery`, `detection`, `baseline`, `tests`, `coverage`
+    Phase {
+        phase: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        target: Option<String>,
+    },
+    TestStarted {
+        target: String,
+        test: String,
+        /// Position among the tests run on this target, from 1
+        index: usize,
+        total: usize,
+    },
+    TestFinished {
+        target: String,
+        test: String,
+        vulnerable: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// A target was skipped, e.g. because it didn't look like GraphQL
+    TargetSkipped { target: String, reason: String },
+    /// The watchdog paused for a degraded target (`pause`), saw it recover (`resume`)
+    /// or stopped aggressive tests on it (`abort`)
+    Watchdog {
+        target: String,
+        action: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    Finished { targets: usize },
+}
+
+/// Every line carries the time and the running totals next to the event
+#[derive(Serialize)]
+struct ProgressLine<'a> {
+    /// Milliseconds since the Unix epoch
+    time: u128,
+    requests: usize,
+    findings: usize,
+    #[serde(flatten)]
+    event: &'a ProgressEvent,
+}
+
+/// Writes progress events as JSON lines to stderr or a file (a FIFO works too)
+pub struct Progress {
+    sink: Mutex<Box<dyn Write + Send>>,
+}
+
+impl Progress {
+    pub fn stderr() -> Self {
+        Self {
+            sink: Mutex::new(Box::new(std::io::stderr())),
+        }
+    }
+
+    /// Append to `path`. Opening a FIFO waits until something reads from it.
+    pub fn to_file(path: &Path) -> Result<Self> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        Ok(Self {
+            sink: Mutex::new(Box::new(file)),
+        })
+    }
+
+    /// Write one event. A reader that went away doesn't stop the run, so write errors
+    /// are dropped.
+    pub fn emit(&self, event: &ProgressEvent, requests: usize, findings: usize) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let line = ProgressLine {
+            time,
+            requests,
+            findings,
+            event,
+        };
+        if let Ok(json) = serde_json::to_string(&line) {
+            let mut sink = self.sink.lock().unwrap();
+            let _ = writeln!(sink, "{}", json);
+            let _ = sink.flush();
+        }
+    }
+}
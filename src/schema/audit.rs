@@ -0,0 +1,332 @@
+use super::{InputValue, Schema};
+use crate::tests::Severity;
+use serde::Serialize;
+
+/// What kind of data a schema name appears to expose
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataCategory {
+    Credential,
+    Secret,
+    Financial,
+    GovernmentId,
+    Pii,
+    Authorization,
+    Internal,
+}
+
+impl DataCategory {
+    pub fn severity(&self) -> Severity {
+        match self {
+            DataCategory::Credential
+            | DataCategory::Secret
+            | DataCategory::Financial
+            | DataCategory::GovernmentId => Severity::High,
+            DataCategory::Pii | DataCategory::Authorization => Severity::Medium,
+            DataCategory::Internal => Severity::Low,
+        }
+    }
+}
+
+impl std::fmt::Display for DataCategory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DataCategory::Credential => write!(f, "credential"),
+            DataCategory::Secret => write!(f, "secret"),
+            DataCategory::Financial => write!(f, "financial"),
+            DataCategory::GovernmentId => write!(f, "government id"),
+            DataCategory::Pii => write!(f, "pii"),
+            DataCategory::Authorization => write!(f, "authorization"),
+            DataCategory::Internal => write!(f, "internal"),
+        }
+    }
+}
+
+// Matched against the whole lowercased name with separators removed
+const COMPACT_RULES: &[(&str, DataCategory)] = &[
+    ("password", DataCategory::Credential),
+    ("passwd", DataCategory::Credential),
+    ("passphrase", DataCategory::Credential),
+    ("passcode", DataCategory::Credential),
+    ("recoverycode", DataCategory::Credential),
+    ("backupcode", DataCategory::Credential),
+    ("securityanswer", DataCategory::Credential),
+    ("apikey", DataCategory::Secret),
+    ("accesskey", DataCategory::Secret),
+    ("privatekey", DataCategory::Secret),
+    ("secretkey", DataCategory::Secret),
+    ("clientsecret", DataCategory::Secret),
+    ("sessionid", DataCategory::Secret),
+    ("creditcard", DataCategory::Financial),
+    ("cardnumber", DataCategory::Financial),
+    ("accountnumber", DataCategory::Financial),
+    ("routingnumber", DataCategory::Financial),
+    ("bankaccount", DataCategory::Financial),
+    ("socialsecurity", DataCategory::GovernmentId),
+    ("nationalid", DataCategory::GovernmentId),
+    ("taxid", DataCategory::GovernmentId),
+    ("driverslicense", DataCategory::GovernmentId),
+    ("driverlicense", DataCategory::GovernmentId),
+    ("dateofbirth", DataCategory::Pii),
+    ("birthdate", DataCategory::Pii),
+    ("birthday", DataCategory::Pii),
+    ("ipaddress", DataCategory::Pii),
+    ("firstname", DataCategory::Pii),
+    ("lastname", DataCategory::Pii),
+    ("fullname", DataCategory::Pii),
+    ("isadmin", DataCategory::Authorization),
+    ("issuperuser", DataCategory::Authorization),
+    ("isstaff", DataCategory::Authorization),
+    ("stacktrace", DataCategory::Internal),
+];
+
+// Matched against individual camelCase / snake_case words
+const WORD_RULES: &[(&str, DataCategory)] = &[
+    ("pwd", DataCategory::Credential),
+    ("pin", DataCategory::Credential),
+    ("otp", DataCategory::Credential),
+    ("totp", DataCategory::Credential),
+    ("mfa", DataCategory::Credential),
+    ("hash", DataCategory::Credential),
+    ("salt", DataCategory::Credential),
+    ("secret", DataCategory::Secret),
+    ("secrets", DataCategory::Secret),
+    ("token", DataCategory::Secret),
+    ("tokens", DataCategory::Secret),
+    ("jwt", DataCategory::Secret),
+    ("cookie", DataCategory::Secret),
+    ("session", DataCategory::Secret),
+    ("credential", DataCategory::Secret),
+    ("credentials", DataCategory::Secret),
+    ("cvv", DataCategory::Financial),
+    ("cvc", DataCategory::Financial),
+    ("iban", DataCategory::Financial),
+    ("swift", DataCategory::Financial),
+    ("ssn", DataCategory::GovernmentId),
+    ("passport", DataCategory::GovernmentId),
+    ("email", DataCategory::Pii),
+    ("emails", DataCategory::Pii),
+    ("phone", DataCategory::Pii),
+    ("mobile", DataCategory::Pii),
+    ("address", DataCategory::Pii),
+    ("street", DataCategory::Pii),
+    ("zip", DataCategory::Pii),
+    ("postcode", DataCategory::Pii),
+    ("dob", DataCategory::Pii),
+    ("gender", DataCategory::Pii),
+    ("ip", DataCategory::Pii),
+    ("latitude", DataCategory::Pii),
+    ("longitude", DataCategory::Pii),
+    ("geolocation", DataCategory::Pii),
+    ("role", DataCategory::Authorization),
+    ("roles", DataCategory::Authorization),
+    ("permission", DataCategory::Authorization),
+    ("permissions", DataCategory::Authorization),
+    ("privilege", DataCategory::Authorization),
+    ("privileges", DataCategory::Authorization),
+    ("admin", DataCategory::Authorization),
+    ("superuser", DataCategory::Authorization),
+    ("scope", DataCategory::Authorization),
+    ("scopes", DataCategory::Authorization),
+    ("acl", DataCategory::Authorization),
+    ("internal", DataCategory::Internal),
+    ("debug", DataCategory::Internal),
+    ("config", DataCategory::Internal),
+    ("env", DataCategory::Internal),
+    ("environment", DataCategory::Internal),
+    ("hidden", DataCategory::Internal),
+    ("private", DataCategory::Internal),
+    ("system", DataCategory::Internal),
+    ("logs", DataCategory::Internal),
+];
+
+// Arguments naming another object: the usual IDOR entry points
+const OBJECT_REFERENCE_ARGS: &[&str] = &[
+    "userid", "ownerid", "accountid", "customerid", "tenantid", "orgid", "organizationid",
+    "memberid", "profileid", "authorid", "uid",
+];
+
+/// Classify a field, argument or type name. Returns the most severe match.
+pub fn classify_name(name: &str) -> Option<DataCategory> {
+    let compact: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase();
+    let words = name_words(name);
+
+    let mut matches: Vec<DataCategory> = COMPACT_RULES
+        .iter()
+        .filter(|(needle, _)| compact.contains(needle))
+        .map(|(_, category)| *category)
+        .collect();
+    matches.extend(
+        WORD_RULES
+            .iter()
+            .filter(|(word, _)| words.iter().any(|w| w == word))
+            .map(|(_, category)| *category),
+    );
+
+    matches.into_iter().min_by_key(|c| severity_rank(c.severity()))
+}
+
+/// Where in the schema a finding was made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditLocation {
+    Field,
+    Argument,
+    InputField,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditFinding {
+    /// Schema coordinate, e.g. `User.password` or `Mutation.updateUser(role:)`
+    pub path: String,
+    pub location: AuditLocation,
+    pub category: DataCategory,
+    pub severity: Severity,
+    pub reason: String,
+}
+
+/// Offline review of a schema: sensitive fields, PII and auth-looking arguments,
+/// most interesting first
+pub fn audit_schema(schema: &Schema) -> Vec<AuditFinding> {
+    let mut findings = Vec::new();
+    let mutation_root = schema
+        .schema
+        .mutation_type
+        .as_ref()
+        .map(|t| t.name.as_str());
+
+    for full_type in schema.get_user_types() {
+        let type_name = full_type.name.as_deref().unwrap_or_default();
+        let is_mutation = Some(type_name) == mutation_root;
+
+        for field in full_type.fields.iter().flatten() {
+            let path = format!("{}.{}", type_name, field.name);
+
+            // Mutation names describe actions (resetPassword), not data they return
+            if !is_mutation {
+                if let Some(category) = classify_name(&field.name) {
+                    findings.push(AuditFinding {
+                        reason: format!("{} field readable in query results", category),
+                        path: path.clone(),
+                        location: AuditLocation::Field,
+                        category,
+                        severity: category.severity(),
+                    });
+                }
+            }
+
+            for arg in &field.args {
+                if let Some(finding) = audit_argument(&path, arg, is_mutation) {
+                    findings.push(finding);
+                }
+            }
+        }
+
+        for input in full_type.input_fields.iter().flatten() {
+            let category = match classify_name(&input.name) {
+                Some(c) => c,
+                None => continue,
+            };
+
+            // Clients setting their own role or admin flag is the classic mass-assignment bug
+            let (severity, reason) = if category == DataCategory::Authorization {
+                (Severity::High, "privilege attribute accepted as input (mass assignment)".to_string())
+            } else {
+                (category.severity(), format!("{} value accepted as input", category))
+            };
+
+            findings.push(AuditFinding {
+                path: format!("{}.{}", type_name, input.name),
+                location: AuditLocation::InputField,
+                category,
+                severity,
+                reason,
+            });
+        }
+    }
+
+    findings.sort_by(|a, b| {
+        severity_rank(a.severity)
+            .cmp(&severity_rank(b.severity))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    findings
+}
+
+fn audit_argument(owner: &str, arg: &InputValue, is_mutation: bool) -> Option<AuditFinding> {
+    let path = format!("{}({}:)", owner, arg.name);
+    let compact = arg.name.to_lowercase().replace('_', "");
+
+    if OBJECT_REFERENCE_ARGS.contains(&compact.as_str()) {
+        return Some(AuditFinding {
+            path,
+            location: AuditLocation::Argument,
+            category: DataCategory::Authorization,
+            severity: if is_mutation { Severity::High } else { Severity::Medium },
+            reason: "references another user's object directly (IDOR candidate)".to_string(),
+        });
+    }
+
+    let category = classify_name(&arg.name)?;
+    let (severity, reason) = match category {
+        DataCategory::Credential | DataCategory::Secret => (
+            Severity::High,
+            "credential passed as an argument (ends up in logs, GET URLs and caches)".to_string(),
+        ),
+        DataCategory::Authorization if is_mutation => (
+            Severity::High,
+            "mutation lets the caller choose a privilege value".to_string(),
+        ),
+        DataCategory::Internal => (
+            Severity::Medium,
+            "debug / internal switch exposed as an argument".to_string(),
+        ),
+        other => (other.severity(), format!("{} used as a lookup argument", other)),
+    };
+
+    Some(AuditFinding {
+        path,
+        location: AuditLocation::Argument,
+        category,
+        severity,
+        reason,
+    })
+}
+
+fn severity_rank(severity: Severity) -> u8 {
+    match severity {
+        Severity::High => 0,
+        Severity::Medium => 1,
+        Severity::Low => 2,
+        Severity::Info => 3,
+    }
+}
+
+/// Lowercased camelCase / snake_case words
+fn name_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for c in name.chars() {
+        if !c.is_ascii_alphanumeric() {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_ascii_uppercase() && current.chars().last().is_some_and(|p| p.is_ascii_lowercase()) {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c.to_ascii_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
@@ -0,0 +1,197 @@
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// An inference wordlist, stored once for every pass of a run: the words back to back
+/// in one string, with their positions sorted by word for lookups
+#[derive(Debug, Clone, Default)]
+pub(super) struct Words {
+    text: String,
+    /// Where each word ends in `text`
+    ends: Vec<usize>,
+    /// Positions in word order
+    sorted: Vec<usize>,
+}
+
+impl Words {
+    pub fn new(words: impl IntoIterator<Item = String>) -> Self {
+        let mut list = Self::default();
+        list.extend(words);
+        list
+    }
+
+    /// Add the words it doesn't have yet, at the end
+    pub fn extend(&mut self, words: impl IntoIterator<Item = String>) {
+        let before = self.len();
+        for word in words {
+            if self.position(&word).is_none() {
+                self.text.push_str(&word);
+                self.ends.push(self.text.len());
+            }
+        }
+        if self.len() == before {
+            return;
+        }
+
+        // Sorting puts repeats among the new words next to each other; the first one stays
+        self.sort();
+        let mut repeats = vec![false; self.len()];
+        for pair in self.sorted.windows(2) {
+            if self.get(pair[0]) == self.get(pair[1]) {
+                repeats[pair[0].max(pair[1])] = true;
+            }
+        }
+        if repeats.contains(&true) {
+            let (text, ends) = (std::mem::take(&mut self.text), std::mem::take(&mut self.ends));
+            let mut start = 0;
+            for (end, repeat) in ends.into_iter().zip(repeats) {
+                if !repeat {
+                    self.text.push_str(&text[start..end]);
+                    self.ends.push(self.text.len());
+                }
+                start = end;
+            }
+            self.sort();
+        }
+    }
+
+    fn sort(&mut self) {
+        let mut sorted: Vec<usize> = (0..self.len()).collect();
+        sorted.sort_by(|a, b| self.get(*a).cmp(self.get(*b)));
+        self.sorted = sorted;
+    }
+
+    pub fn len(&self) -> usize {
+        self.ends.len()
+    }
+
+    pub fn get(&self, position: usize) -> &str {
+        let start = if position == 0 { 0 } else { self.ends[position - 1] };
+        &self.text[start..self.ends[position]]
+    }
+
+    fn position(&self, word: &str) -> Option<usize> {
+        self.sorted
+            .binary_search_by(|p| self.get(*p).cmp(word))
+            .ok()
+            .map(|i| self.sorted[i])
+    }
+}
+
+/// One wordlist pass: the words left to try and those already tried. The wordlist is
+/// walked by position from its end rather than copied; words from elsewhere (built-in
+/// extras, suggestions) are tried before it and are the only ones kept by name.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(from = "SavedPass")]
+pub(super) struct WordPass {
+    words: Arc<Words>,
+    /// Words before `left` haven't been reached yet
+    left: usize,
+    /// Words to try before the wordlist, last one first
+    extra: Vec<String>,
+    /// Words tried or known, besides the part of the wordlist already walked
+    tried: HashSet<String>,
+    /// Words handed out or marked tried
+    count: usize,
+}
+
+/// How a pass is saved in a checkpoint: every word tried and every word left, by name
+#[derive(Deserialize)]
+struct SavedPass {
+    checked: HashSet<String>,
+    remaining: Vec<String>,
+}
+
+impl From<SavedPass> for WordPass {
+    fn from(saved: SavedPass) -> Self {
+        Self {
+            words: Arc::default(),
+            left: 0,
+            count: saved.checked.len(),
+            extra: saved.remaining,
+            tried: saved.checked,
+        }
+    }
+}
+
+impl Serialize for WordPass {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let remaining: Vec<&str> = (0..self.left)
+            .map(|p| self.words.get(p))
+            .chain(self.extra.iter().map(String::as_str))
+            .collect();
+        let mut state = serializer.serialize_struct("WordPass", 2)?;
+        state.serialize_field("checked", &self.tried().collect::<Vec<_>>())?;
+        state.serialize_field("remaining", &remaining)?;
+        state.end()
+    }
+}
+
+impl WordPass {
+    pub fn new(words: Arc<Words>) -> Self {
+        Self {
+            left: words.len(),
+            words,
+            extra: Vec::new(),
+            tried: HashSet::new(),
+            count: 0,
+        }
+    }
+
+    /// Try `words` before the rest of the wordlist, last one first
+    pub fn extend(&mut self, words: impl IntoIterator<Item = String>) {
+        self.extra.extend(words);
+    }
+
+    /// Count `words` as tried, so they're never handed out
+    pub fn mark_tried<'a>(&mut self, words: impl IntoIterator<Item = &'a String>) {
+        for word in words {
+            if !self.is_tried(word) {
+                self.tried.insert(word.clone());
+                self.count += 1;
+            }
+        }
+    }
+
+    /// The next word not tried yet, counted as tried from here on
+    pub fn next_word(&mut self) -> Option<String> {
+        while let Some(word) = self.extra.pop() {
+            if !self.is_tried(&word) {
+                self.tried.insert(word.clone());
+                self.count += 1;
+                return Some(word);
+            }
+        }
+        while self.left > 0 {
+            self.left -= 1;
+            let word = self.words.get(self.left);
+            if !self.tried.contains(word) {
+                self.count += 1;
+                return Some(word.to_string());
+            }
+        }
+        None
+    }
+
+    /// Words handed out or marked tried so far
+    pub fn tried_count(&self) -> usize {
+        self.count
+    }
+
+    /// Words left, some of which may turn out to be tried already
+    pub fn remaining(&self) -> usize {
+        self.left + self.extra.len()
+    }
+
+    /// Every word handed out or marked tried
+    pub fn tried(&self) -> impl Iterator<Item = &str> {
+        (self.left..self.words.len())
+            .map(|p| self.words.get(p))
+            .chain(self.tried.iter().map(String::as_str))
+    }
+
+    fn is_tried(&self, word: &str) -> bool {
+        self.tried.contains(word) || self.words.position(word).is_some_and(|p| p >= self.left)
+    }
+}
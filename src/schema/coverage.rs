@@ -0,0 +1,471 @@
+use super::sdl::{tokenize, Token};
+use super::Schema;
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+// Fragment spreads can reference each other; stop following them past this depth
+const MAX_SELECTION_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeCoverage {
+    pub name: String,
+    pub kind: String,
+    pub covered: Vec<String>,
+    pub uncovered: Vec<String>,
+}
+
+/// Which object and interface fields a set of queries touched
+#[derive(Debug, Clone, Serialize)]
+pub struct CoverageReport {
+    pub queries: usize,
+    /// Queries that couldn't be parsed (payloads built to break parsers, mostly)
+    pub unparsed: usize,
+    pub fields_total: usize,
+    pub fields_covered: usize,
+    pub types_total: usize,
+    pub types_touched: usize,
+    pub types: Vec<TypeCoverage>,
+    /// Fields selected in queries that the schema doesn't define
+    pub unknown_fields: Vec<String>,
+}
+
+impl CoverageReport {
+    pub fn percent(&self) -> f64 {
+        if self.fields_total == 0 {
+            return 0.0;
+        }
+        self.fields_covered as f64 * 100.0 / self.fields_total as f64
+    }
+
+    /// Types none of the queries reached at all
+    pub fn untouched_types(&self) -> Vec<&TypeCoverage> {
+        self.types.iter().filter(|t| t.covered.is_empty()).collect()
+    }
+}
+
+/// Map every field selected by `queries` onto the schema
+pub fn schema_coverage(schema: &Schema, queries: &[String]) -> CoverageReport {
+    let mut walker = Walker {
+        schema,
+        touched: HashSet::new(),
+        unknown: BTreeSet::new(),
+        fragments: HashMap::new(),
+    };
+
+    let mut unparsed = 0;
+    for query in queries {
+        match parse_document(query) {
+            Ok(document) => walker.document(document),
+            Err(_) => unparsed += 1,
+        }
+    }
+
+    let mut types = Vec::new();
+    for full_type in schema.get_user_types() {
+        if full_type.kind != "OBJECT" && full_type.kind != "INTERFACE" {
+            continue;
+        }
+        let name = full_type.name.clone().unwrap_or_default();
+        let (covered, uncovered): (Vec<String>, Vec<String>) = full_type
+            .fields
+            .iter()
+            .flatten()
+            .map(|f| f.name.clone())
+            .partition(|f| walker.touched.contains(&(name.clone(), f.clone())));
+
+        types.push(TypeCoverage {
+            name,
+            kind: full_type.kind.clone(),
+            covered,
+            uncovered,
+        });
+    }
+    types.sort_by(|a, b| a.name.cmp(&b.name));
+
+    CoverageReport {
+        queries: queries.len(),
+        unparsed,
+        fields_total: types.iter().map(|t| t.covered.len() + t.uncovered.len()).sum(),
+        fields_covered: types.iter().map(|t| t.covered.len()).sum(),
+        types_total: types.len(),
+        types_touched: types.iter().filter(|t| !t.covered.is_empty()).count(),
+        types,
+        unknown_fields: walker.unknown.into_iter().collect(),
+    }
+}
+
+pub(super) enum Selection {
+    Field {
+        /// Key the field comes back under, when it isn't the name
+        alias: Option<String>,
+        name: String,
+        arguments: Vec<(String, ArgumentValue)>,
+        selections: Vec<Selection>,
+    },
+    Inline { type_condition: Option<String>, selections: Vec<Selection> },
+    Spread(String),
+}
+
+/// An argument as written in a document, numbers as their source text
+pub(super) enum ArgumentValue {
+    Variable(String),
+    Int(String),
+    Float(String),
+    String(String),
+    Boolean(bool),
+    Null,
+    Enum(String),
+    List(Vec<ArgumentValue>),
+    Object(Vec<(String, ArgumentValue)>),
+}
+
+/// `$name: [Type!]!`, with the named type and the outermost markers
+pub(super) struct VariableDefinition {
+    pub name: String,
+    pub type_name: String,
+    pub is_list: bool,
+    pub is_non_null: bool,
+}
+
+pub(super) struct Operation {
+    pub kind: String,
+    pub variables: Vec<VariableDefinition>,
+    pub selections: Vec<Selection>,
+}
+
+pub(super) struct Document {
+    pub operations: Vec<Operation>,
+    pub fragments: HashMap<String, (String, Vec<Selection>)>,
+}
+
+struct Walker<'a> {
+    schema: &'a Schema,
+    touched: HashSet<(String, String)>,
+    unknown: BTreeSet<String>,
+    /// Fragments of the document being walked
+    fragments: HashMap<String, (String, Vec<Selection>)>,
+}
+
+impl Walker<'_> {
+    fn document(&mut self, document: Document) {
+        self.fragments = document.fragments;
+
+        for operation in &document.operations {
+            let root = match operation.kind.as_str() {
+                "mutation" => self.schema.get_mutation_type(),
+                "subscription" => self.schema.get_subscription_type(),
+                _ => self.schema.get_query_type(),
+            };
+            if let Some(name) = root.and_then(|t| t.name.clone()) {
+                self.selections(&name, &operation.selections, 0);
+            }
+        }
+    }
+
+    fn selections(&mut self, type_name: &str, selections: &[Selection], depth: usize) {
+        if depth > MAX_SELECTION_DEPTH {
+            return;
+        }
+
+        for selection in selections {
+            match selection {
+                Selection::Field { name, selections, .. } => {
+                    if name.starts_with("__") {
+                        continue;
+                    }
+                    let field = self
+                        .schema
+                        .get_type(type_name)
+                        .and_then(|t| t.fields.as_ref())
+                        .and_then(|fields| fields.iter().find(|f| &f.name == name));
+
+                    match field {
+                        Some(field) => {
+                            self.touched.insert((type_name.to_string(), name.clone()));
+                            if let Some(target) = field.field_type.get_base_type_name() {
+                                let target = target.to_string();
+                                self.selections(&target, selections, depth + 1);
+                            }
+                        }
+                        None => {
+                            self.unknown.insert(format!("{}.{}", type_name, name));
+                        }
+                    }
+                }
+                Selection::Inline {
+                    type_condition,
+                    selections,
+                } => {
+                    let target = type_condition.as_deref().unwrap_or(type_name).to_string();
+                    self.selections(&target, selections, depth + 1);
+                }
+                Selection::Spread(name) => {
+                    // Taken out while walking so the borrow doesn't outlive the recursion
+                    if let Some((condition, selections)) = self.fragments.remove(name) {
+                        self.selections(&condition, &selections, depth + 1);
+                        self.fragments.insert(name.clone(), (condition, selections));
+                    }
+                }
+            }
+        }
+    }
+}
+
+pub(super) fn parse_document(source: &str) -> Result<Document> {
+    let tokens: Vec<Token> = tokenize(source)?.into_iter().map(|s| s.token).collect();
+    let mut parser = QueryParser { tokens, pos: 0 };
+
+    let mut document = Document {
+        operations: Vec::new(),
+        fragments: HashMap::new(),
+    };
+
+    while parser.pos < parser.tokens.len() {
+        if parser.eat_punct('{') {
+            document.operations.push(Operation {
+                kind: "query".to_string(),
+                variables: Vec::new(),
+                selections: parser.selection_set()?,
+            });
+            continue;
+        }
+
+        let keyword = parser.name()?;
+        match keyword.as_str() {
+            "query" | "mutation" | "subscription" => {
+                // Optional name, variable definitions and directives before the selection set
+                if let Some(Token::Name(_)) = parser.tokens.get(parser.pos) {
+                    parser.pos += 1;
+                }
+                let variables = if parser.eat_punct('(') {
+                    parser.variable_definitions()?
+                } else {
+                    Vec::new()
+                };
+                parser.directives()?;
+                parser.expect_punct('{')?;
+                document.operations.push(Operation {
+                    kind: keyword,
+                    variables,
+                    selections: parser.selection_set()?,
+                });
+            }
+            "fragment" => {
+                let name = parser.name()?;
+                if parser.name()? != "on" {
+                    bail!("Expected 'on' in fragment {}", name);
+                }
+                let condition = parser.name()?;
+                parser.directives()?;
+                parser.expect_punct('{')?;
+                let selections = parser.selection_set()?;
+                document.fragments.insert(name, (condition, selections));
+            }
+            other => bail!("Unexpected '{}' at document level", other),
+        }
+    }
+
+    Ok(document)
+}
+
+struct QueryParser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl QueryParser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.tokens.get(self.pos) == Some(&Token::Punct(c))
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        if self.is_punct(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<()> {
+        if !self.eat_punct(c) {
+            bail!("Expected '{}'", c);
+        }
+        Ok(())
+    }
+
+    fn name(&mut self) -> Result<String> {
+        match self.next() {
+            Some(Token::Name(name)) => Ok(name),
+            other => bail!("Expected a name, found {:?}", other),
+        }
+    }
+
+    fn skip_balanced(&mut self, open: char, close: char) -> Result<()> {
+        let mut depth = 0;
+        loop {
+            match self.next() {
+                Some(Token::Punct(c)) if c == open => depth += 1,
+                Some(Token::Punct(c)) if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+                Some(_) => {}
+                None => bail!("Unbalanced '{}'", open),
+            }
+        }
+    }
+
+    /// Variable definitions after the `(`, up to and including the closing `)`
+    fn variable_definitions(&mut self) -> Result<Vec<VariableDefinition>> {
+        let mut variables = Vec::new();
+
+        while !self.eat_punct(')') {
+            self.expect_punct('$')?;
+            let name = self.name()?;
+            self.expect_punct(':')?;
+            let mut lists = 0;
+            while self.eat_punct('[') {
+                lists += 1;
+            }
+            let type_name = self.name()?;
+            for _ in 0..lists {
+                self.eat_punct('!');
+                self.expect_punct(']')?;
+            }
+            let is_non_null = self.eat_punct('!');
+            if self.eat_punct('=') {
+                self.value()?;
+            }
+            self.directives()?;
+
+            variables.push(VariableDefinition {
+                name,
+                type_name,
+                is_list: lists > 0,
+                is_non_null,
+            });
+        }
+
+        Ok(variables)
+    }
+
+    fn arguments(&mut self) -> Result<Vec<(String, ArgumentValue)>> {
+        let mut arguments = Vec::new();
+        if self.eat_punct('(') {
+            while !self.eat_punct(')') {
+                let name = self.name()?;
+                self.expect_punct(':')?;
+                arguments.push((name, self.value()?));
+            }
+        }
+        Ok(arguments)
+    }
+
+    fn value(&mut self) -> Result<ArgumentValue> {
+        Ok(match self.next() {
+            Some(Token::Punct('$')) => ArgumentValue::Variable(self.name()?),
+            Some(Token::Number(number)) if number.contains(['.', 'e', 'E']) => ArgumentValue::Float(number),
+            Some(Token::Number(number)) => ArgumentValue::Int(number),
+            Some(Token::Str { value, .. }) => ArgumentValue::String(value),
+            Some(Token::Name(name)) => match name.as_str() {
+                "true" | "false" => ArgumentValue::Boolean(name == "true"),
+                "null" => ArgumentValue::Null,
+                _ => ArgumentValue::Enum(name),
+            },
+            Some(Token::Punct('[')) => {
+                let mut items = Vec::new();
+                while !self.eat_punct(']') {
+                    items.push(self.value()?);
+                }
+                ArgumentValue::List(items)
+            }
+            Some(Token::Punct('{')) => {
+                let mut fields = Vec::new();
+                while !self.eat_punct('}') {
+                    let name = self.name()?;
+                    self.expect_punct(':')?;
+                    fields.push((name, self.value()?));
+                }
+                ArgumentValue::Object(fields)
+            }
+            other => bail!("Expected a value, found {:?}", other),
+        })
+    }
+
+    fn directives(&mut self) -> Result<()> {
+        while self.eat_punct('@') {
+            self.name()?;
+            if self.is_punct('(') {
+                self.skip_balanced('(', ')')?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Selections up to and including the closing `}`
+    fn selection_set(&mut self) -> Result<Vec<Selection>> {
+        let mut selections = Vec::new();
+
+        while !self.eat_punct('}') {
+            if self.tokens.get(self.pos) == Some(&Token::Spread) {
+                self.pos += 1;
+                match self.tokens.get(self.pos).cloned() {
+                    Some(Token::Name(name)) if name != "on" => {
+                        self.pos += 1;
+                        self.directives()?;
+                        selections.push(Selection::Spread(name));
+                    }
+                    _ => {
+                        let type_condition = match self.tokens.get(self.pos) {
+                            Some(Token::Name(n)) if n == "on" => {
+                                self.pos += 1;
+                                Some(self.name()?)
+                            }
+                            _ => None,
+                        };
+                        self.directives()?;
+                        self.expect_punct('{')?;
+                        selections.push(Selection::Inline {
+                            type_condition,
+                            selections: self.selection_set()?,
+                        });
+                    }
+                }
+                continue;
+            }
+
+            // alias: name, or just name
+            let mut alias = None;
+            let mut name = self.name()?;
+            if self.eat_punct(':') {
+                alias = Some(std::mem::replace(&mut name, self.name()?));
+            }
+            let arguments = self.arguments()?;
+            self.directives()?;
+            let selections_below = if self.eat_punct('{') {
+                self.selection_set()?
+            } else {
+                Vec::new()
+            };
+
+            selections.push(Selection::Field {
+                alias,
+                name,
+                arguments,
+                selections: selections_below,
+            });
+        }
+
+        Ok(selections)
+    }
+}
@@ -0,0 +1,241 @@
+use super::{Field, Schema};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// One step of a cycle and every field that makes it
+#[derive(Debug, Clone, Serialize)]
+pub struct CycleHop {
+    pub from: String,
+    pub to: String,
+    pub fields: Vec<String>,
+}
+
+/// A loop in the type graph, e.g. User -> Post -> User
+#[derive(Debug, Clone, Serialize)]
+pub struct TypeCycle {
+    pub hops: Vec<CycleHop>,
+    /// Root fields that lead into the cycle without required arguments, if any
+    pub entry: Option<Vec<String>>,
+}
+
+impl TypeCycle {
+    pub fn types(&self) -> Vec<&str> {
+        self.hops.iter().map(|h| h.from.as_str()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.hops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hops.is_empty()
+    }
+
+    /// `User -> Post -> User`
+    pub fn describe(&self) -> String {
+        let mut names = self.types();
+        if let Some(first) = self.hops.first() {
+            names.push(first.from.as_str());
+        }
+        names.join(" -> ")
+    }
+
+    /// Nest the cycle `depth` times below its entry point:
+    /// `query { me { posts { author { posts { ... __typename } } } } }`
+    pub fn deep_query(&self, schema: &Schema, depth: usize) -> Option<String> {
+        let entry = self.entry.as_ref()?;
+
+        // Every hop needs a field we can select without arguments
+        let steps: Vec<&str> = self
+            .hops
+            .iter()
+            .map(|hop| {
+                let fields = schema.get_type(&hop.from)?.fields.as_ref()?;
+                hop.fields
+                    .iter()
+                    .filter_map(|name| fields.iter().find(|f| &f.name == name))
+                    .find(|f| !has_required_args(f))
+                    .map(|f| f.name.as_str())
+            })
+            .collect::<Option<_>>()?;
+
+        let mut selection = String::from("__typename");
+        for i in (0..depth).rev() {
+            selection = format!("{} {{ {} }}", steps[i % steps.len()], selection);
+        }
+        for field in entry.iter().rev() {
+            selection = format!("{} {{ {} }}", field, selection);
+        }
+
+        Some(format!("query {{ {} }}", selection))
+    }
+}
+
+/// Enumerate simple cycles between object and interface types, shortest first.
+///
+/// Each cycle is reported once (starting from its alphabetically first type), up to
+/// `max_length` hops and `limit` cycles so dense schemas stay tractable.
+pub fn find_cycles(schema: &Schema, max_length: usize, limit: usize) -> Vec<TypeCycle> {
+    let names: Vec<&str> = {
+        let mut names: Vec<&str> = schema
+            .get_user_types()
+            .into_iter()
+            .filter(|t| is_composite(&t.kind))
+            .filter_map(|t| t.name.as_deref())
+            .collect();
+        names.sort();
+        names
+    };
+    let index: HashMap<&str, usize> = names.iter().enumerate().map(|(i, n)| (*n, i)).collect();
+
+    // Adjacency by index, remembering which fields make each edge
+    let mut edges: BTreeMap<(usize, usize), Vec<String>> = BTreeMap::new();
+    for (from, name) in names.iter().enumerate() {
+        let fields = match schema.get_type(name).and_then(|t| t.fields.as_ref()) {
+            Some(f) => f,
+            None => continue,
+        };
+        for field in fields.iter().filter(|f| !f.name.starts_with("__")) {
+            if let Some(to) = field.field_type.get_base_type_name().and_then(|n| index.get(n)) {
+                edges.entry((from, *to)).or_default().push(field.name.clone());
+            }
+        }
+    }
+    let mut adjacency: Vec<Vec<usize>> = vec![Vec::new(); names.len()];
+    for (from, to) in edges.keys() {
+        adjacency[*from].push(*to);
+    }
+
+    let mut found: Vec<Vec<usize>> = Vec::new();
+    for start in 0..names.len() {
+        if found.len() >= limit {
+            break;
+        }
+        let mut path = vec![start];
+        walk(start, start, &adjacency, &mut path, max_length, limit, &mut found);
+    }
+
+    let entries = entry_paths(schema);
+
+    let mut cycles: Vec<TypeCycle> = found
+        .into_iter()
+        .map(|path| {
+            let hops: Vec<CycleHop> = path
+                .iter()
+                .enumerate()
+                .map(|(i, from)| {
+                    let to = path[(i + 1) % path.len()];
+                    CycleHop {
+                        from: names[*from].to_string(),
+                        to: names[to].to_string(),
+                        fields: edges.get(&(*from, to)).cloned().unwrap_or_default(),
+                    }
+                })
+                .collect();
+            rotate_to_entry(hops, &entries)
+        })
+        .collect();
+
+    cycles.sort_by(|a, b| {
+        a.len()
+            .cmp(&b.len())
+            .then_with(|| b.entry.is_some().cmp(&a.entry.is_some()))
+            .then_with(|| a.describe().cmp(&b.describe()))
+    });
+    cycles
+}
+
+fn walk(
+    start: usize,
+    node: usize,
+    adjacency: &[Vec<usize>],
+    path: &mut Vec<usize>,
+    max_length: usize,
+    limit: usize,
+    found: &mut Vec<Vec<usize>>,
+) {
+    for &next in &adjacency[node] {
+        if found.len() >= limit {
+            return;
+        }
+        if next == start {
+            found.push(path.clone());
+        } else if next > start && path.len() < max_length && !path.contains(&next) {
+            path.push(next);
+            walk(start, next, adjacency, path, max_length, limit, found);
+            path.pop();
+        }
+    }
+}
+
+/// Shortest argument-free field path from the query root to every reachable type
+fn entry_paths(schema: &Schema) -> HashMap<String, Vec<String>> {
+    let mut paths: HashMap<String, Vec<String>> = HashMap::new();
+    let root = match schema.get_query_type().and_then(|t| t.name.clone()) {
+        Some(r) => r,
+        None => return paths,
+    };
+
+    let mut queue = VecDeque::new();
+    paths.insert(root.clone(), Vec::new());
+    queue.push_back(root);
+
+    while let Some(name) = queue.pop_front() {
+        let fields = match schema.get_type(&name).and_then(|t| t.fields.as_ref()) {
+            Some(f) => f,
+            None => continue,
+        };
+        let prefix = paths[&name].clone();
+
+        for field in fields
+            .iter()
+            .filter(|f| !f.name.starts_with("__") && !has_required_args(f))
+        {
+            let target = match field.field_type.get_base_type_name() {
+                Some(t) => t,
+                None => continue,
+            };
+            let composite = schema.get_type(target).is_some_and(|t| is_composite(&t.kind));
+            if composite && !paths.contains_key(target) {
+                let mut path = prefix.clone();
+                path.push(field.name.clone());
+                paths.insert(target.to_string(), path);
+                queue.push_back(target.to_string());
+            }
+        }
+    }
+
+    paths
+}
+
+/// Start the cycle at whichever member is cheapest to reach from the query root
+fn rotate_to_entry(mut hops: Vec<CycleHop>, entries: &HashMap<String, Vec<String>>) -> TypeCycle {
+    let best = hops
+        .iter()
+        .enumerate()
+        .filter_map(|(i, hop)| Some((i, entries.get(&hop.from)?)))
+        .min_by_key(|(_, path)| path.len());
+
+    match best {
+        Some((i, path)) => {
+            let entry = path.clone();
+            hops.rotate_left(i);
+            TypeCycle {
+                hops,
+                entry: Some(entry),
+            }
+        }
+        None => TypeCycle { hops, entry: None },
+    }
+}
+
+fn has_required_args(field: &Field) -> bool {
+    field
+        .args
+        .iter()
+        .any(|a| a.input_type.is_non_null() && a.default_value.is_none())
+}
+
+fn is_composite(kind: &str) -> bool {
+    kind == "OBJECT" || kind == "INTERFACE"
+}
@@ -0,0 +1,600 @@
+use super::{Directive, EnumValue, Field, FullType, InputValue, Schema, TypeRef};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// How a change affects existing clients
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeSeverity {
+    /// Existing operations can stop working
+    Breaking,
+    /// Valid operations keep working but may behave differently
+    Dangerous,
+    Safe,
+}
+
+impl std::fmt::Display for ChangeSeverity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChangeSeverity::Breaking => write!(f, "BREAKING"),
+            ChangeSeverity::Dangerous => write!(f, "DANGEROUS"),
+            ChangeSeverity::Safe => write!(f, "SAFE"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SchemaChange {
+    /// Machine-friendly change code, e.g. `FIELD_REMOVED`
+    pub kind: String,
+    pub severity: ChangeSeverity,
+    /// Schema coordinate the change applies to (`User.email`, `Query.user(id:)`, `@auth`)
+    pub path: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct DiffSummary {
+    pub breaking: usize,
+    pub dangerous: usize,
+    pub safe: usize,
+    /// Root operations (queries, mutations, subscriptions) that did not exist before
+    pub operations_added: usize,
+    pub operations_removed: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SchemaDiff {
+    pub summary: DiffSummary,
+    pub changes: Vec<SchemaChange>,
+}
+
+impl SchemaDiff {
+    pub fn has_breaking(&self) -> bool {
+        self.summary.breaking > 0
+    }
+}
+
+/// Compare two schemas and classify every difference
+pub fn diff_schemas(old: &Schema, new: &Schema) -> SchemaDiff {
+    let mut differ = Differ::default();
+
+    differ.root_types(old, new);
+    differ.types(old, new);
+    differ.directives(&old.schema.directives, &new.schema.directives);
+
+    let mut changes = differ.changes;
+    changes.sort_by(|a, b| a.severity.cmp(&b.severity).then_with(|| a.path.cmp(&b.path)));
+
+    let mut summary = DiffSummary::default();
+    for change in &changes {
+        match change.severity {
+            ChangeSeverity::Breaking => summary.breaking += 1,
+            ChangeSeverity::Dangerous => summary.dangerous += 1,
+            ChangeSeverity::Safe => summary.safe += 1,
+        }
+    }
+
+    let (added, removed) = operation_delta(old, new);
+    summary.operations_added = added;
+    summary.operations_removed = removed;
+
+    SchemaDiff { summary, changes }
+}
+
+#[derive(Default)]
+struct Differ {
+    changes: Vec<SchemaChange>,
+}
+
+impl Differ {
+    fn push(&mut self, kind: &str, severity: ChangeSeverity, path: String, message: String) {
+        self.changes.push(SchemaChange {
+            kind: kind.to_string(),
+            severity,
+            path,
+            message,
+        });
+    }
+
+    fn root_types(&mut self, old: &Schema, new: &Schema) {
+        let roots = [
+            (
+                "query",
+                old.schema.query_type.as_ref().map(|t| t.name.as_str()),
+                new.schema.query_type.as_ref().map(|t| t.name.as_str()),
+            ),
+            (
+                "mutation",
+                old.schema.mutation_type.as_ref().map(|t| t.name.as_str()),
+                new.schema.mutation_type.as_ref().map(|t| t.name.as_str()),
+            ),
+            (
+                "subscription",
+                old.schema.subscription_type.as_ref().map(|t| t.name.as_str()),
+                new.schema.subscription_type.as_ref().map(|t| t.name.as_str()),
+            ),
+        ];
+
+        for (operation, before, after) in roots {
+            match (before, after) {
+                (Some(b), Some(a)) if b != a => self.push(
+                    "ROOT_TYPE_CHANGED",
+                    ChangeSeverity::Breaking,
+                    format!("schema.{}", operation),
+                    format!("{} root type changed from {} to {}", operation, b, a),
+                ),
+                (Some(b), None) => self.push(
+                    "ROOT_TYPE_REMOVED",
+                    ChangeSeverity::Breaking,
+                    format!("schema.{}", operation),
+                    format!("{} root type {} was removed", operation, b),
+                ),
+                (None, Some(a)) => self.push(
+                    "ROOT_TYPE_ADDED",
+                    ChangeSeverity::Safe,
+                    format!("schema.{}", operation),
+                    format!("{} root type {} was added", operation, a),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    fn types(&mut self, old: &Schema, new: &Schema) {
+        let old_types = user_types(old);
+        let new_types = user_types(new);
+
+        for (name, old_type) in &old_types {
+            match new_types.get(name) {
+                None => self.push(
+                    "TYPE_REMOVED",
+                    ChangeSeverity::Breaking,
+                    name.to_string(),
+                    format!("Type {} was removed", name),
+                ),
+                Some(new_type) if new_type.kind != old_type.kind => self.push(
+                    "TYPE_KIND_CHANGED",
+                    ChangeSeverity::Breaking,
+                    name.to_string(),
+                    format!(
+                        "{} changed from {} to {}",
+                        name, old_type.kind, new_type.kind
+                    ),
+                ),
+                Some(new_type) => self.type_members(name, old_type, new_type),
+            }
+        }
+
+        for name in new_types.keys().filter(|n| !old_types.contains_key(*n)) {
+            self.push(
+                "TYPE_ADDED",
+                ChangeSeverity::Safe,
+                name.to_string(),
+                format!("Type {} was added", name),
+            );
+        }
+    }
+
+    fn type_members(&mut self, name: &str, old: &FullType, new: &FullType) {
+        self.fields(name, old.fields.as_deref(), new.fields.as_deref());
+        self.input_fields(name, old.input_fields.as_deref(), new.input_fields.as_deref());
+        self.enum_values(name, old.enum_values.as_deref(), new.enum_values.as_deref());
+
+        let (removed, added) = name_delta(
+            old.possible_types.as_deref().unwrap_or_default(),
+            new.possible_types.as_deref().unwrap_or_default(),
+        );
+        if old.kind == "UNION" {
+            for member in removed {
+                self.push(
+                    "UNION_MEMBER_REMOVED",
+                    ChangeSeverity::Breaking,
+                    name.to_string(),
+                    format!("{} was removed from union {}", member, name),
+                );
+            }
+            for member in added {
+                self.push(
+                    "UNION_MEMBER_ADDED",
+                    ChangeSeverity::Dangerous,
+                    name.to_string(),
+                    format!("{} was added to union {}", member, name),
+                );
+            }
+        }
+
+        let (removed, added) = name_delta(
+            old.interfaces.as_deref().unwrap_or_default(),
+            new.interfaces.as_deref().unwrap_or_default(),
+        );
+        for interface in removed {
+            self.push(
+                "INTERFACE_REMOVED",
+                ChangeSeverity::Breaking,
+                name.to_string(),
+                format!("{} no longer implements {}", name, interface),
+            );
+        }
+        for interface in added {
+            self.push(
+                "INTERFACE_ADDED",
+                ChangeSeverity::Dangerous,
+                name.to_string(),
+                format!("{} now implements {}", name, interface),
+            );
+        }
+    }
+
+    fn fields(&mut self, type_name: &str, old: Option<&[Field]>, new: Option<&[Field]>) {
+        let old: BTreeMap<&str, &Field> = old
+            .unwrap_or_default()
+            .iter()
+            .map(|f| (f.name.as_str(), f))
+            .collect();
+        let new: BTreeMap<&str, &Field> = new
+            .unwrap_or_default()
+            .iter()
+            .map(|f| (f.name.as_str(), f))
+            .collect();
+
+        for (name, old_field) in &old {
+            let path = format!("{}.{}", type_name, name);
+            let new_field = match new.get(name) {
+                Some(f) => f,
+                None => {
+                    let severity = if old_field.is_deprecated {
+                        // Clients were warned, but it still breaks anything that kept using it
+                        ChangeSeverity::Dangerous
+                    } else {
+                        ChangeSeverity::Breaking
+                    };
+                    self.push(
+                        "FIELD_REMOVED",
+                        severity,
+                        path.clone(),
+                        format!("Field {} was removed", path),
+                    );
+                    continue;
+                }
+            };
+
+            let before = type_string(&old_field.field_type);
+            let after = type_string(&new_field.field_type);
+            if before != after {
+                let severity = if safe_output_change(&old_field.field_type, &new_field.field_type) {
+                    ChangeSeverity::Safe
+                } else {
+                    ChangeSeverity::Breaking
+                };
+                self.push(
+                    "FIELD_TYPE_CHANGED",
+                    severity,
+                    path.clone(),
+                    format!("{} changed type from {} to {}", path, before, after),
+                );
+            }
+
+            match (old_field.is_deprecated, new_field.is_deprecated) {
+                (false, true) => self.push(
+                    "FIELD_DEPRECATED",
+                    ChangeSeverity::Safe,
+                    path.clone(),
+                    match &new_field.deprecation_reason {
+                        Some(reason) => format!("{} was deprecated: {}", path, reason),
+                        None => format!("{} was deprecated", path),
+                    },
+                ),
+                (true, false) => self.push(
+                    "FIELD_UNDEPRECATED",
+                    ChangeSeverity::Safe,
+                    path.clone(),
+                    format!("{} is no longer deprecated", path),
+                ),
+                _ => {}
+            }
+
+            self.arguments(&path, &old_field.args, &new_field.args);
+        }
+
+        for name in new.keys().filter(|n| !old.contains_key(*n)) {
+            let path = format!("{}.{}", type_name, name);
+            self.push(
+                "FIELD_ADDED",
+                ChangeSeverity::Safe,
+                path.clone(),
+                format!("Field {} was added", path),
+            );
+        }
+    }
+
+    fn arguments(&mut self, owner: &str, old: &[InputValue], new: &[InputValue]) {
+        let old: BTreeMap<&str, &InputValue> = old.iter().map(|a| (a.name.as_str(), a)).collect();
+        let new: BTreeMap<&str, &InputValue> = new.iter().map(|a| (a.name.as_str(), a)).collect();
+
+        for (name, old_arg) in &old {
+            let path = format!("{}({}:)", owner, name);
+            let new_arg = match new.get(name) {
+                Some(a) => a,
+                None => {
+                    self.push(
+                        "ARG_REMOVED",
+                        ChangeSeverity::Breaking,
+                        path.clone(),
+                        format!("Argument {} was removed", path),
+                    );
+                    continue;
+                }
+            };
+
+            self.input_value_change("ARG", &path, old_arg, new_arg);
+        }
+
+        for (name, new_arg) in new.iter().filter(|(n, _)| !old.contains_key(*n)) {
+            let path = format!("{}({}:)", owner, name);
+            let (severity, message) = if is_required(new_arg) {
+                (ChangeSeverity::Breaking, format!("Required argument {} was added", path))
+            } else {
+                (ChangeSeverity::Safe, format!("Optional argument {} was added", path))
+            };
+            self.push("ARG_ADDED", severity, path, message);
+        }
+    }
+
+    fn input_fields(
+        &mut self,
+        type_name: &str,
+        old: Option<&[InputValue]>,
+        new: Option<&[InputValue]>,
+    ) {
+        let old: BTreeMap<&str, &InputValue> = old
+            .unwrap_or_default()
+            .iter()
+            .map(|f| (f.name.as_str(), f))
+            .collect();
+        let new: BTreeMap<&str, &InputValue> = new
+            .unwrap_or_default()
+            .iter()
+            .map(|f| (f.name.as_str(), f))
+            .collect();
+
+        for (name, old_field) in &old {
+            let path = format!("{}.{}", type_name, name);
+            match new.get(name) {
+                Some(new_field) => self.input_value_change("INPUT_FIELD", &path, old_field, new_field),
+                None => self.push(
+                    "INPUT_FIELD_REMOVED",
+                    ChangeSeverity::Breaking,
+                    path.clone(),
+                    format!("Input field {} was removed", path),
+                ),
+            }
+        }
+
+        for (name, new_field) in new.iter().filter(|(n, _)| !old.contains_key(*n)) {
+            let path = format!("{}.{}", type_name, name);
+            let (severity, message) = if is_required(new_field) {
+                (ChangeSeverity::Breaking, format!("Required input field {} was added", path))
+            } else {
+                (ChangeSeverity::Safe, format!("Optional input field {} was added", path))
+            };
+            self.push("INPUT_FIELD_ADDED", severity, path, message);
+        }
+    }
+
+    /// Type and default value changes shared by arguments and input fields
+    fn input_value_change(&mut self, prefix: &str, path: &str, old: &InputValue, new: &InputValue) {
+        let before = type_string(&old.input_type);
+        let after = type_string(&new.input_type);
+        if before != after {
+            let severity = if safe_input_change(&old.input_type, &new.input_type) {
+                ChangeSeverity::Safe
+            } else {
+                ChangeSeverity::Breaking
+            };
+            self.push(
+                &format!("{}_TYPE_CHANGED", prefix),
+                severity,
+                path.to_string(),
+                format!("{} changed type from {} to {}", path, before, after),
+            );
+        }
+
+        if old.default_value != new.default_value {
+            self.push(
+                &format!("{}_DEFAULT_CHANGED", prefix),
+                ChangeSeverity::Dangerous,
+                path.to_string(),
+                format!(
+                    "{} default changed from {} to {}",
+                    path,
+                    old.default_value.as_deref().unwrap_or("none"),
+                    new.default_value.as_deref().unwrap_or("none")
+                ),
+            );
+        }
+    }
+
+    fn enum_values(&mut self, type_name: &str, old: Option<&[EnumValue]>, new: Option<&[EnumValue]>) {
+        let old: BTreeSet<&str> = old
+            .unwrap_or_default()
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect();
+        let new: BTreeSet<&str> = new
+            .unwrap_or_default()
+            .iter()
+            .map(|v| v.name.as_str())
+            .collect();
+
+        for value in old.difference(&new) {
+            let path = format!("{}.{}", type_name, value);
+            self.push(
+                "ENUM_VALUE_REMOVED",
+                ChangeSeverity::Breaking,
+                path.clone(),
+                format!("Enum value {} was removed", path),
+            );
+        }
+        for value in new.difference(&old) {
+            let path = format!("{}.{}", type_name, value);
+            self.push(
+                "ENUM_VALUE_ADDED",
+                ChangeSeverity::Dangerous,
+                path.clone(),
+                format!("Enum value {} was added", path),
+            );
+        }
+    }
+
+    fn directives(&mut self, old: &[Directive], new: &[Directive]) {
+        let old: BTreeMap<&str, &Directive> = old.iter().map(|d| (d.name.as_str(), d)).collect();
+        let new: BTreeMap<&str, &Directive> = new.iter().map(|d| (d.name.as_str(), d)).collect();
+
+        for (name, old_directive) in &old {
+            let path = format!("@{}", name);
+            let new_directive = match new.get(name) {
+                Some(d) => d,
+                None => {
+                    self.push(
+                        "DIRECTIVE_REMOVED",
+                        ChangeSeverity::Breaking,
+                        path.clone(),
+                        format!("Directive {} was removed", path),
+                    );
+                    continue;
+                }
+            };
+
+            for location in old_directive
+                .locations
+                .iter()
+                .filter(|l| !new_directive.locations.contains(l))
+            {
+                self.push(
+                    "DIRECTIVE_LOCATION_REMOVED",
+                    ChangeSeverity::Breaking,
+                    path.clone(),
+                    format!("{} can no longer be used on {}", path, location),
+                );
+            }
+            for location in new_directive
+                .locations
+                .iter()
+                .filter(|l| !old_directive.locations.contains(l))
+            {
+                self.push(
+                    "DIRECTIVE_LOCATION_ADDED",
+                    ChangeSeverity::Safe,
+                    path.clone(),
+                    format!("{} can now be used on {}", path, location),
+                );
+            }
+
+            self.arguments(&path, &old_directive.args, &new_directive.args);
+        }
+
+        for name in new.keys().filter(|n| !old.contains_key(*n)) {
+            let path = format!("@{}", name);
+            self.push(
+                "DIRECTIVE_ADDED",
+                ChangeSeverity::Safe,
+                path.clone(),
+                format!("Directive {} was added", path),
+            );
+        }
+    }
+}
+
+pub(super) fn user_types(schema: &Schema) -> BTreeMap<&str, &FullType> {
+    schema
+        .get_user_types()
+        .into_iter()
+        .filter_map(|t| Some((t.name.as_deref()?, t)))
+        .collect()
+}
+
+fn name_delta<'a>(old: &'a [TypeRef], new: &'a [TypeRef]) -> (Vec<&'a str>, Vec<&'a str>) {
+    let old: BTreeSet<&str> = old.iter().filter_map(|t| t.get_base_type_name()).collect();
+    let new: BTreeSet<&str> = new.iter().filter_map(|t| t.get_base_type_name()).collect();
+    (
+        old.difference(&new).copied().collect(),
+        new.difference(&old).copied().collect(),
+    )
+}
+
+fn operation_delta(old: &Schema, new: &Schema) -> (usize, usize) {
+    let before = operations(old);
+    let after = operations(new);
+    (after.difference(&before).count(), before.difference(&after).count())
+}
+
+/// Root fields as `query.user`, `mutation.deleteUser`
+pub(super) fn operations(schema: &Schema) -> BTreeSet<String> {
+    let roots = [
+        ("query", schema.get_query_type()),
+        ("mutation", schema.get_mutation_type()),
+        ("subscription", schema.get_subscription_type()),
+    ];
+    roots
+        .iter()
+        .filter_map(|(kind, root)| Some((kind, root.as_ref()?.fields.as_ref()?)))
+        .flat_map(|(kind, fields)| fields.iter().map(move |f| format!("{}.{}", kind, f.name)))
+        .collect()
+}
+
+fn is_required(value: &InputValue) -> bool {
+    value.input_type.is_non_null() && value.default_value.is_none()
+}
+
+/// Output positions may only get stricter: `String` -> `String!` is fine, the reverse is not
+fn safe_output_change(old: &TypeRef, new: &TypeRef) -> bool {
+    match old.kind.as_str() {
+        "NON_NULL" => match (&old.of_type, &new.of_type) {
+            (Some(o), Some(n)) if new.kind == "NON_NULL" => safe_output_change(o, n),
+            _ => false,
+        },
+        "LIST" => match (&old.of_type, &new.of_type) {
+            (Some(o), Some(n)) if new.kind == "LIST" => safe_output_change(o, n),
+            (_, Some(n)) if new.kind == "NON_NULL" => safe_output_change(old, n),
+            _ => false,
+        },
+        _ => match new.kind.as_str() {
+            "NON_NULL" => new.of_type.as_ref().is_some_and(|n| safe_output_change(old, n)),
+            "LIST" => false,
+            _ => old.name == new.name,
+        },
+    }
+}
+
+/// Input positions may only get looser: `ID!` -> `ID` is fine, the reverse is not
+fn safe_input_change(old: &TypeRef, new: &TypeRef) -> bool {
+    match old.kind.as_str() {
+        "NON_NULL" => match &old.of_type {
+            Some(o) if new.kind == "NON_NULL" => {
+                new.of_type.as_ref().is_some_and(|n| safe_input_change(o, n))
+            }
+            Some(o) => safe_input_change(o, new),
+            None => false,
+        },
+        "LIST" => match (&old.of_type, &new.of_type) {
+            (Some(o), Some(n)) if new.kind == "LIST" => safe_input_change(o, n),
+            _ => false,
+        },
+        _ => new.kind != "NON_NULL" && new.kind != "LIST" && old.name == new.name,
+    }
+}
+
+fn type_string(type_ref: &TypeRef) -> String {
+    match type_ref.kind.as_str() {
+        "NON_NULL" => match &type_ref.of_type {
+            Some(of_type) => format!("{}!", type_string(of_type)),
+            None => "String!".to_string(),
+        },
+        "LIST" => match &type_ref.of_type {
+            Some(of_type) => format!("[{}]", type_string(of_type)),
+            None => "[String]".to_string(),
+        },
+        _ => type_ref.name.clone().unwrap_or_else(|| "String".to_string()),
+    }
+}
@@ -0,0 +1,92 @@
+use super::diff::{operations, user_types};
+use super::{FullType, Schema};
+use serde::Serialize;
+use std::collections::BTreeSet;
+
+const BUILTIN_SCALARS: &[&str] = &["Int", "Float", "String", "Boolean", "ID"];
+
+/// What one of two schemas has and the other doesn't
+#[derive(Debug, Default, Serialize)]
+pub struct DriftSide {
+    /// Root fields as `mutation.resetDatabase`
+    pub operations: Vec<String>,
+    pub types: Vec<String>,
+    /// Fields, input fields and enum values of types both have, as `User.internalNotes`
+    pub members: Vec<String>,
+}
+
+impl DriftSide {
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty() && self.types.is_empty() && self.members.is_empty()
+    }
+
+    /// Mutations, the operations most worth a look when only one environment has them
+    pub fn mutations(&self) -> impl Iterator<Item = &str> {
+        self.operations
+            .iter()
+            .filter_map(|o| o.strip_prefix("mutation."))
+    }
+}
+
+/// How two environments' schemas differ in what they expose. Unlike `diff_schemas`,
+/// neither one is older: everything only one of them has is reported on its side.
+#[derive(Debug, Serialize)]
+pub struct SchemaDrift {
+    pub only_first: DriftSide,
+    pub only_second: DriftSide,
+}
+
+impl SchemaDrift {
+    pub fn is_empty(&self) -> bool {
+        self.only_first.is_empty() && self.only_second.is_empty()
+    }
+}
+
+/// Compare the operations, types and type members of two schemas both ways
+pub fn schema_drift(first: &Schema, second: &Schema) -> SchemaDrift {
+    SchemaDrift {
+        only_first: one_side(first, second),
+        only_second: one_side(second, first),
+    }
+}
+
+/// What `schema` has that `other` doesn't
+fn one_side(schema: &Schema, other: &Schema) -> DriftSide {
+    let other_operations = operations(other);
+    let other_types = user_types(other);
+    // Root fields are compared as operations already
+    let roots: BTreeSet<&str> = [schema, other]
+        .iter()
+        .flat_map(|s| [&s.schema.query_type, &s.schema.mutation_type, &s.schema.subscription_type])
+        .filter_map(|t| Some(t.as_ref()?.name.as_str()))
+        .collect();
+
+    let mut side = DriftSide {
+        operations: operations(schema).difference(&other_operations).cloned().collect(),
+        ..DriftSide::default()
+    };
+    // Introspection leaves out built-in scalars nothing uses, inference doesn't
+    let types = user_types(schema).into_iter().filter(|(name, _)| !BUILTIN_SCALARS.contains(name));
+    for (name, full_type) in types {
+        match other_types.get(name) {
+            None => side.types.push(name.to_string()),
+            Some(_) if roots.contains(name) => {}
+            Some(other_type) => {
+                let theirs = members(other_type);
+                side.members.extend(
+                    members(full_type)
+                        .difference(&theirs)
+                        .map(|member| format!("{}.{}", name, member)),
+                );
+            }
+        }
+    }
+    side
+}
+
+fn members(full_type: &FullType) -> BTreeSet<&str> {
+    let fields = full_type.fields.iter().flatten().map(|f| f.name.as_str());
+    let inputs = full_type.input_fields.iter().flatten().map(|f| f.name.as_str());
+    let values = full_type.enum_values.iter().flatten().map(|v| v.name.as_str());
+    fields.chain(inputs).chain(values).collect()
+}
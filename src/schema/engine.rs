@@ -0,0 +1,523 @@
+use anyhow::{bail, Context, Result};
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Built-in engines, in the order detection tries them. graphql-js goes last: its
+/// messages are the common denominator and it's the fallback.
+pub const ENGINES: &[&str] = &["graphql-java", "hasura", "absinthe", "graphql-js"];
+
+/// Pattern keys a profile sets, with the named groups each one must have. Groups not
+/// listed (`type` on `required_arg`, `found` on `expected_value`...) are optional.
+const PATTERNS: &[(&str, &[&str])] = &[
+    ("suggestions", &["list"]),
+    ("field_error", &["field", "type"]),
+    ("type_error", &["type"]),
+    ("arg_error", &["arg"]),
+    ("subselection", &["field", "type"]),
+    ("must_have_selection", &["field", "type"]),
+    ("must_not_have_selection", &["field", "type"]),
+    ("required_arg", &["arg"]),
+    ("input_field_error", &["field"]),
+    ("required_input_field", &["field"]),
+    ("expected_value", &["expected"]),
+    ("enum_value", &["expected"]),
+    ("fragment_spread", &["type", "fragment"]),
+    ("inline_fragment", &["list"]),
+    ("directive_error", &["directive"]),
+    ("misplaced_directive", &["directive"]),
+    ("directive_arg_error", &["arg"]),
+    ("required_directive_arg", &["arg"]),
+    // A message only this engine produces, for detection
+    ("signature", &[]),
+];
+
+/// Stands in for a message an engine never produces
+const NEVER: &str = r"[^\s\S]";
+
+const GRAPHQL_JS: &[(&str, &str)] = &[
+    // Did you mean "user" or "users"?
+    ("suggestions", r#"Did you mean (?P<list>.+)""#),
+    // Cannot query field "foo" on type "Query"
+    ("field_error", r#"Cannot query field ["']?(?P<field>\w+)["']? on type ["']?(?P<type>\w+)["']?"#),
+    // Unknown type "Foo"
+    ("type_error", r#"Unknown type ["']?(?P<type>\w+)["']?"#),
+    // Unknown argument "foo" on field "Query.user"
+    ("arg_error", r#"Unknown argument ["']?(?P<arg>\w+)["']? on field ["']?(?:\w+\.)?(?P<field>\w+)["']?"#),
+    // Subselection required for type 'now_query' of field 'now'
+    ("subselection", r#"Subselection required for type ["']?(?P<type>\w+)["']? of field ["']?(?P<field>\w+)["']?"#),
+    // Field "user" of type "User" must have a selection of subfields
+    ("must_have_selection", r#"Field ["']?(?P<field>\w+)["']? of type ["']?(?P<type>[\w\[\]!]+)["']? must have a selection of subfields"#),
+    // Field "name" must not have a selection since type "String" has no subfields
+    ("must_not_have_selection", r#"Field ["']?(?P<field>\w+)["']? must not have a selection since type ["']?(?P<type>[\w\[\]!]+)["']? has no subfields"#),
+    // Field "user" argument "id" of type "ID!" is required, but it was not provided
+    ("required_arg", r#"Field ["']?(?P<field>\w+)["']? argument ["']?(?P<arg>\w+)["']? of type ["']?(?P<type>[\w\[\]!]+)["']? is required"#),
+    // Field "foo" is not defined by type "UserInput"
+    ("input_field_error", r#"Field ["']?(?P<field>\w+)["']? is not defined by type ["']?(?P<type>\w+)["']?"#),
+    // Field "UserInput.name" of required type "String!" was not provided
+    ("required_input_field", r#"Field ["']?(?P<type>\w+)\.(?P<field>\w+)["']? of required type ["']?(?P<expected>[\w\[\]!]+)["']? was not provided"#),
+    // Expected value of type "Int!", found {...}
+    ("expected_value", r#"Expected value of type ["']?(?P<expected>[\w\[\]!]+)["']?, found\s*(?P<found>\S)?"#),
+    // Enum "Role" cannot represent non-enum value: {...}
+    ("enum_value", r#"Enum ["']?(?P<expected>\w+)["']? cannot represent[^:]*:?\s*(?P<found>\S)?"#),
+    // Fragment cannot be spread here as objects of type "User" can never be of type "Post"
+    ("fragment_spread", r#"Fragment cannot be spread here as objects of type ["']?(?P<type>\w+)["']? can never be of type ["']?(?P<fragment>\w+)["']?"#),
+    // Did you mean to use an inline fragment on "User" or "Post"?
+    ("inline_fragment", r#"Did you mean to use an inline fragment on (?P<list>.+)\?"#),
+    // Unknown directive "@foo".
+    ("directive_error", r#"Unknown directive ["'`]?@?(?P<directive>\w+)"#),
+    // Directive "@deprecated" may not be used on FIELD.
+    ("misplaced_directive", r#"Directive ["']?@?(?P<directive>\w+)["']? may not be used on"#),
+    // Unknown argument "foo" on directive "@include".
+    ("directive_arg_error", r#"Unknown argument ["']?(?P<arg>\w+)["']? on directive ["']?@?(?P<directive>\w+)"#),
+    // Directive "@include" argument "if" of type "Boolean!" is required, but it was not provided.
+    ("required_directive_arg", r#"Directive ["']?@?(?P<directive>\w+)["']? argument ["']?(?P<arg>\w+)["']? of type ["']?(?P<type>[\w\[\]!]+)["']? is required"#),
+    ("signature", NEVER),
+];
+
+/// Differences from graphql-js; validation errors come as `Validation error (Rule@[path]) : ...`
+const GRAPHQL_JAVA: &[(&str, &str)] = &[
+    ("suggestions", NEVER),
+    ("field_error", r#"Field '(?P<field>\w+)' in type '(?P<type>\w+)' is undefined"#),
+    ("type_error", r#"Unknown type '(?P<type>\w+)'"#),
+    ("arg_error", r#"UnknownArgument@\[(?:[^\]]*/)?(?P<field>\w+)\]\) : Unknown field argument '(?P<arg>\w+)'"#),
+    ("subselection", r#"Subselection required for type '(?P<type>[\w\[\]!]+)' of field '(?P<field>\w+)'"#),
+    ("must_have_selection", r#"Subselection required for type '(?P<type>[\w\[\]!]+)' of field '(?P<field>\w+)'"#),
+    ("must_not_have_selection", r#"Subselection not allowed on leaf type '(?P<type>[\w\[\]!]+)' of field '(?P<field>\w+)'"#),
+    ("required_arg", r#"MissingFieldArgument@\[(?:[^\]]*/)?(?P<field>\w+)\]\) : Missing field argument '(?P<arg>\w+)'"#),
+    ("input_field_error", r#"contains a field not in '(?P<type>\w+)': '(?P<field>\w+)'"#),
+    ("required_input_field", r#"is missing required fields '\[(?P<field>\w+)"#),
+    ("expected_value", r#"with value '(?P<found>[A-Za-z]*).*is not a valid '(?P<expected>[\w\[\]!]+)'"#),
+    ("enum_value", r#"with value '(?P<found>[A-Za-z]*).*is not a valid '(?P<expected>\w+)' - .*allowable values"#),
+    ("fragment_spread", r#"Fragment cannot be spread here as objects of type '(?P<type>\w+)' can never be of type '(?P<fragment>\w+)'"#),
+    ("inline_fragment", NEVER),
+    ("directive_error", r#"Unknown directive '(?P<directive>\w+)'"#),
+    ("misplaced_directive", r#"Directive '(?P<directive>\w+)' not allowed here"#),
+    ("directive_arg_error", r#"Unknown directive argument '(?P<arg>\w+)'"#),
+    ("required_directive_arg", r#"Missing directive argument '(?P<arg>\w+)'"#),
+    ("signature", r#"Validation error \(\w+@"#),
+];
+
+/// Differences from graphql-js; Hasura names fields and types in single quotes
+const HASURA: &[(&str, &str)] = &[
+    ("suggestions", NEVER),
+    ("field_error", r#"field '(?P<field>\w+)' not found in type: '(?P<type>\w+)'"#),
+    ("arg_error", r#"'(?P<field>\w+)' has no argument named '(?P<arg>\w+)'"#),
+    ("subselection", r#"missing selection set for field '(?P<field>\w+)' of type '(?P<type>[\w\[\]!]+)'"#),
+    ("must_have_selection", r#"missing selection set for field '(?P<field>\w+)' of type '(?P<type>[\w\[\]!]+)'"#),
+    ("must_not_have_selection", r#"unexpected subselection set for (?:non-object )?field '(?P<field>\w+)' of type '(?P<type>[\w\[\]!]+)'"#),
+    ("required_arg", r#"missing required field '(?P<arg>\w+)'"#),
+    ("input_field_error", r#"field '(?P<field>\w+)' not found in type: '(?P<type>\w+)'"#),
+    ("required_input_field", r#"missing required field '(?P<field>\w+)'"#),
+    ("expected_value", r#"expected [^']* for type '(?P<expected>[\w\[\]!]+)', but found (?:an? )?(?P<found>\w+)"#),
+    ("enum_value", r#"expected one of the values .* for type '(?P<expected>\w+)'"#),
+    ("inline_fragment", NEVER),
+    ("directive_error", r#"directive '(?P<directive>\w+)' is not defined"#),
+    ("misplaced_directive", r#"directive '(?P<directive>\w+)' is not allowed on"#),
+    ("directive_arg_error", r#"'(?P<directive>\w+)' has no argument named '(?P<arg>\w+)'"#),
+    ("required_directive_arg", r#"missing required field '(?P<arg>\w+)'"#),
+    ("signature", r#"not found in type: '"#),
+];
+
+/// Differences from graphql-js; Absinthe reports bad values per argument and field
+const ABSINTHE: &[(&str, &str)] = &[
+    ("arg_error", r#"Unknown argument "(?P<arg>\w+)" on field "(?P<field>\w+)" of type"#),
+    ("required_arg", r#"In argument "(?P<arg>\w+)": Expected type "(?P<type>[\w\[\]!]+)", found null"#),
+    ("input_field_error", r#"In field "(?P<field>\w+)": Unknown field"#),
+    ("expected_value", r#"Expected type "(?P<expected>[\w\[\]!]+)", found (?P<found>\S)"#),
+    ("signature", r#"on field "\w+" of type "\w+""#),
+];
+
+/// Error message patterns inference reads, one set per server implementation.
+/// Patterns capture what they need through named groups (`field`, `type`, `arg`...).
+#[derive(Debug, Clone)]
+pub struct EngineProfile {
+    pub name: String,
+    pub suggestions: Regex,
+    pub field_error: Regex,
+    pub type_error: Regex,
+    pub arg_error: Regex,
+    pub subselection: Regex,
+    pub must_have_selection: Regex,
+    pub must_not_have_selection: Regex,
+    pub required_arg: Regex,
+    pub input_field_error: Regex,
+    pub required_input_field: Regex,
+    pub expected_value: Regex,
+    pub enum_value: Regex,
+    pub fragment_spread: Regex,
+    pub inline_fragment: Regex,
+    pub directive_error: Regex,
+    pub misplaced_directive: Regex,
+    pub directive_arg_error: Regex,
+    pub required_directive_arg: Regex,
+    pub signature: Regex,
+}
+
+impl Default for EngineProfile {
+    fn default() -> Self {
+        Self::builtin("graphql-js").unwrap()
+    }
+}
+
+impl EngineProfile {
+    /// A built-in profile by name, see [`ENGINES`]
+    pub fn builtin(name: &str) -> Option<Self> {
+        let overrides: Vec<(String, String)> = builtin_overrides(name)?
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Some(Self::build(name, &overrides).expect("built-in engine patterns are valid"))
+    }
+
+    /// Load a profile from a TOML file of patterns, set at the top level or under a
+    /// `[patterns]` table. Unset keys come from `base` (a built-in engine, graphql-js
+    /// by default); an empty pattern turns one off.
+    ///
+    /// ```toml
+    /// name = "my-engine"
+    /// base = "graphql-java"
+    ///
+    /// [patterns]
+    /// field_error = 'Champ "(?P<field>\w+)" inconnu sur le type "(?P<type>\w+)"'
+    /// type_error = '''Type "(?P<type>\w+)" inconnu, vérifiez l'orthographe'''
+    /// suggestions = ''
+    /// ```
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read engine profile {}", path.display()))?;
+        let values = parse_toml(&content)
+            .with_context(|| format!("Failed to parse engine profile {}", path.display()))?;
+
+        let mut name = path.file_stem().and_then(|s| s.to_str()).unwrap_or("custom").to_string();
+        let mut base = "graphql-js".to_string();
+        let mut overrides = Vec::new();
+        for (key, value) in values {
+            let pattern = key.strip_prefix("patterns.").unwrap_or(&key);
+            match key.as_str() {
+                "name" => name = value,
+                "base" => base = value,
+                _ if PATTERNS.iter().any(|(k, _)| *k == pattern) => overrides.push((pattern.to_string(), value)),
+                _ => bail!(
+                    "{}: unknown key '{}', expected name, base or one of: {}",
+                    path.display(),
+                    key,
+                    PATTERNS.iter().map(|(k, _)| *k).collect::<Vec<_>>().join(", ")
+                ),
+            }
+        }
+
+        let base_overrides = match builtin_overrides(&base) {
+            Some(o) => o,
+            None => bail!("{}: unknown base engine '{}' (known: {})", path.display(), base, ENGINES.join(", ")),
+        };
+        let mut merged: Vec<(String, String)> =
+            base_overrides.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+        merged.extend(overrides);
+
+        Self::build(&name, &merged).with_context(|| format!("Invalid engine profile {}", path.display()))
+    }
+
+    /// A built-in name, or a path to a TOML profile
+    pub fn resolve(name_or_path: &str) -> Result<Self> {
+        if let Some(profile) = Self::builtin(name_or_path) {
+            return Ok(profile);
+        }
+        let path = Path::new(name_or_path);
+        if path.is_file() {
+            return Self::load(path);
+        }
+        bail!("Unknown engine '{}' (known: {}, or a .toml profile)", name_or_path, ENGINES.join(", "))
+    }
+
+    /// The built-in engine whose messages these are: a matching signature first, then
+    /// the graphql-js field error as the common case
+    pub fn detect(messages: &[String]) -> Option<Self> {
+        for name in ENGINES {
+            let profile = Self::builtin(name)?;
+            if messages.iter().any(|m| profile.signature.is_match(m)) {
+                return Some(profile);
+            }
+        }
+        let fallback = Self::default();
+        messages
+            .iter()
+            .any(|m| fallback.field_error.is_match(m))
+            .then_some(fallback)
+    }
+
+    /// graphql-js patterns with `overrides` applied
+    fn build(name: &str, overrides: &[(String, String)]) -> Result<Self> {
+        let mut patterns: HashMap<&str, String> = GRAPHQL_JS.iter().map(|(k, v)| (*k, v.to_string())).collect();
+        for (key, value) in overrides {
+            let key = PATTERNS
+                .iter()
+                .map(|(k, _)| *k)
+                .find(|k| *k == key.as_str())
+                .with_context(|| format!("Unknown pattern '{}'", key))?;
+            patterns.insert(key, if value.is_empty() { NEVER.to_string() } else { value.clone() });
+        }
+
+        let mut compiled: HashMap<&str, Regex> = HashMap::new();
+        for (key, groups) in PATTERNS {
+            let pattern = &patterns[key];
+            let regex = Regex::new(pattern).with_context(|| format!("Invalid '{}' pattern", key))?;
+            if pattern != NEVER {
+                for group in *groups {
+                    if !regex.capture_names().flatten().any(|n| n == *group) {
+                        bail!("'{}' pattern needs a (?P<{}>...) group", key, group);
+                    }
+                }
+            }
+            compiled.insert(key, regex);
+        }
+        let mut take = |key: &str| compiled.remove(key).unwrap();
+
+        Ok(Self {
+            name: name.to_string(),
+            suggestions: take("suggestions"),
+            field_error: take("field_error"),
+            type_error: take("type_error"),
+            arg_error: take("arg_error"),
+            subselection: take("subselection"),
+            must_have_selection: take("must_have_selection"),
+            must_not_have_selection: take("must_not_have_selection"),
+            required_arg: take("required_arg"),
+            input_field_error: take("input_field_error"),
+            required_input_field: take("required_input_field"),
+            expected_value: take("expected_value"),
+            enum_value: take("enum_value"),
+            fragment_spread: take("fragment_spread"),
+            inline_fragment: take("inline_fragment"),
+            directive_error: take("directive_error"),
+            misplaced_directive: take("misplaced_directive"),
+            directive_arg_error: take("directive_arg_error"),
+            required_directive_arg: take("required_directive_arg"),
+            signature: take("signature"),
+        })
+    }
+}
+
+/// What a built-in engine changes from graphql-js
+fn builtin_overrides(name: &str) -> Option<&'static [(&'static str, &'static str)]> {
+    match name {
+        "graphql-js" => Some(&[]),
+        "graphql-java" => Some(GRAPHQL_JAVA),
+        "hasura" => Some(HASURA),
+        "absinthe" => Some(ABSINTHE),
+        _ => None,
+    }
+}
+
+/// Text of a named group, empty when the pattern doesn't have it or it didn't match
+pub fn group<'h>(cap: &Captures<'h>, name: &str) -> &'h str {
+    cap.name(name).map_or("", |m| m.as_str())
+}
+
+/// Whether a named group says `expected`, or the engine doesn't say at all
+pub fn group_is(cap: &Captures, name: &str, expected: &str) -> bool {
+    cap.name(name).is_none_or(|m| m.as_str() == expected)
+}
+
+/// The TOML a profile can hold: `key = value` pairs of strings, at the top level or
+/// under `[table]` headers (returned as `table.key`), and comments. All four string
+/// kinds work: basic `"..."` with TOML escapes, literal `'...'` taken as written, and
+/// their multi-line `"""..."""` / `'''...'''` forms. Numbers, booleans, arrays and
+/// inline tables are rejected, no pattern needs them.
+fn parse_toml(content: &str) -> Result<Vec<(String, String)>> {
+    let mut toml = Toml { rest: content, line: 1 };
+    let mut values: Vec<(String, String)> = Vec::new();
+    let mut table = String::new();
+
+    loop {
+        toml.skip_blank();
+        if toml.rest.is_empty() {
+            break;
+        }
+        let line = toml.line;
+        if toml.eat("[") {
+            toml.skip_spaces();
+            table = toml.key()?;
+            toml.skip_spaces();
+            if !toml.eat("]") {
+                bail!("line {}: expected ']' after table name", line);
+            }
+        } else {
+            let key = toml.key()?;
+            toml.skip_spaces();
+            if !toml.eat("=") {
+                bail!("line {}: expected '=' after key '{}'", line, key);
+            }
+            toml.skip_spaces();
+            let value = toml.string()?;
+            let key = if table.is_empty() { key } else { format!("{}.{}", table, key) };
+            if values.iter().any(|(k, _)| *k == key) {
+                bail!("line {}: '{}' is defined twice", line, key);
+            }
+            values.push((key, value));
+        }
+        toml.end_of_line()?;
+    }
+
+    Ok(values)
+}
+
+/// Cursor over a TOML document, see [`parse_toml`]
+struct Toml<'a> {
+    rest: &'a str,
+    line: usize,
+}
+
+impl Toml<'_> {
+    fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.rest = &self.rest[c.len_utf8()..];
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+
+    fn eat(&mut self, token: &str) -> bool {
+        if !self.rest.starts_with(token) {
+            return false;
+        }
+        self.line += token.matches('\n').count();
+        self.rest = &self.rest[token.len()..];
+        true
+    }
+
+    fn skip_spaces(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.bump();
+        }
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Some('#') {
+            let end = self.rest.find('\n').unwrap_or(self.rest.len());
+            self.rest = &self.rest[end..];
+        }
+    }
+
+    /// Whitespace, empty lines and comments
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            if !(self.eat("\n") || self.eat("\r\n")) {
+                break;
+            }
+        }
+    }
+
+    /// Nothing but a comment may follow a value or table header on its line
+    fn end_of_line(&mut self) -> Result<()> {
+        self.skip_spaces();
+        self.skip_comment();
+        if self.rest.is_empty() || self.rest.starts_with('\n') || self.rest.starts_with("\r\n") {
+            return Ok(());
+        }
+        bail!("line {}: unexpected '{}'", self.line, self.rest.lines().next().unwrap_or_default())
+    }
+
+    /// A bare (`A-Za-z0-9_-`) or quoted key
+    fn key(&mut self) -> Result<String> {
+        if matches!(self.peek(), Some('"' | '\'')) {
+            return self.string();
+        }
+        let len = self
+            .rest
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+            .unwrap_or(self.rest.len());
+        if len == 0 {
+            bail!("line {}: expected a key", self.line);
+        }
+        let key = self.rest[..len].to_string();
+        self.rest = &self.rest[len..];
+        Ok(key)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let start = self.line;
+        if self.eat("'''") {
+            // A newline right after the opening delimiter isn't part of the string
+            let _ = self.eat("\n") || self.eat("\r\n");
+            Ok(self.until("'''", start)?.to_string())
+        } else if self.eat("'") {
+            let value = self.until("'", start)?;
+            if value.contains('\n') {
+                bail!("line {}: unterminated string", start);
+            }
+            Ok(value.to_string())
+        } else if self.eat("\"\"\"") {
+            let _ = self.eat("\n") || self.eat("\r\n");
+            self.basic("\"\"\"", start)
+        } else if self.eat("\"") {
+            self.basic("\"", start)
+        } else {
+            bail!("line {}: only string values are supported", start)
+        }
+    }
+
+    /// Raw text up to `delimiter`, which is consumed
+    fn until(&mut self, delimiter: &str, start: usize) -> Result<&str> {
+        let end = self
+            .rest
+            .find(delimiter)
+            .with_context(|| format!("line {}: unterminated string", start))?;
+        let value = &self.rest[..end];
+        self.line += value.matches('\n').count();
+        self.rest = &self.rest[end + delimiter.len()..];
+        Ok(value)
+    }
+
+    /// A basic string's body up to `delimiter`, escapes decoded
+    fn basic(&mut self, delimiter: &str, start: usize) -> Result<String> {
+        let multiline = delimiter.len() == 3;
+        let mut value = String::new();
+        loop {
+            if self.eat(delimiter) {
+                return Ok(value);
+            }
+            match self.bump() {
+                None => bail!("line {}: unterminated string", start),
+                Some('\n') if !multiline => bail!("line {}: unterminated string", start),
+                Some('\\') => match self.bump() {
+                    Some('b') => value.push('\u{8}'),
+                    Some('t') => value.push('\t'),
+                    Some('n') => value.push('\n'),
+                    Some('f') => value.push('\u{c}'),
+                    Some('r') => value.push('\r'),
+                    Some('"') => value.push('"'),
+                    Some('\\') => value.push('\\'),
+                    Some(u @ ('u' | 'U')) => {
+                        let digits = if u == 'u' { 4 } else { 8 };
+                        let hex: String = (0..digits).filter_map(|_| self.bump()).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .with_context(|| format!("line {}: bad \\{} escape", self.line, u))?;
+                        value.push(c);
+                    }
+                    // A backslash ending a line drops the newline and the indentation after it
+                    Some(c) if multiline && c.is_whitespace() => {
+                        while self.peek().is_some_and(char::is_whitespace) {
+                            self.bump();
+                        }
+                    }
+                    other => bail!(
+                        "line {}: unsupported escape \\{} (use a 'literal string' for regexes)",
+                        self.line,
+                        other.map(String::from).unwrap_or_default()
+                    ),
+                },
+                Some(c) => value.push(c),
+            }
+        }
+    }
+}
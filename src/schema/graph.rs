@@ -0,0 +1,389 @@
+use super::{classify_name, Schema};
+use crate::tests::Severity;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+
+/// One edge per (from, to) pair, labelled with every field that links them
+#[derive(Debug, Clone)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+    pub label: String,
+    /// Edge stays inside a recursive cluster
+    pub recursive: bool,
+    /// Interface implementation or union membership rather than a field
+    pub structural: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct GraphNode {
+    pub name: String,
+    pub kind: String,
+    /// Fields the audit classifier rates HIGH
+    pub sensitive_fields: usize,
+    pub recursive: bool,
+}
+
+/// Type graph of a schema, for rendering as Graphviz DOT or Mermaid
+pub struct SchemaGraph {
+    schema: Schema,
+    root: Option<String>,
+    max_depth: Option<usize>,
+}
+
+impl SchemaGraph {
+    pub fn new(schema: Schema) -> Self {
+        Self {
+            schema,
+            root: None,
+            max_depth: None,
+        }
+    }
+
+    /// Only include types reachable from this type (or `query`, `mutation`, `subscription`)
+    pub fn with_root(mut self, root: Option<String>) -> Self {
+        self.root = root;
+        self
+    }
+
+    /// Stop expanding this many hops away from the root
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    fn resolve_root(&self, root: &str) -> Option<String> {
+        let root_type = match root {
+            "query" => self.schema.get_query_type(),
+            "mutation" => self.schema.get_mutation_type(),
+            "subscription" => self.schema.get_subscription_type(),
+            name => self.schema.get_type(name),
+        };
+        root_type.and_then(|t| t.name.clone())
+    }
+
+    /// Nodes and edges after scoping, with recursive clusters marked
+    pub fn build(&self) -> anyhow::Result<(Vec<GraphNode>, Vec<GraphEdge>)> {
+        let mut edges: BTreeMap<(String, String), (Vec<String>, bool)> = BTreeMap::new();
+        let mut kinds: BTreeMap<String, String> = BTreeMap::new();
+
+        for full_type in self.schema.get_user_types() {
+            let name = match &full_type.name {
+                Some(n) => n.clone(),
+                None => continue,
+            };
+            if !matches!(full_type.kind.as_str(), "OBJECT" | "INTERFACE" | "UNION") {
+                continue;
+            }
+            kinds.insert(name.clone(), full_type.kind.clone());
+
+            for field in full_type.fields.iter().flatten() {
+                let target = match field.field_type.get_base_type_name() {
+                    Some(t) => t,
+                    None => continue,
+                };
+                let is_composite = self
+                    .schema
+                    .get_type(target)
+                    .is_some_and(|t| matches!(t.kind.as_str(), "OBJECT" | "INTERFACE" | "UNION"));
+                if !is_composite {
+                    continue;
+                }
+
+                let label = if field.field_type.is_list() {
+                    format!("{}[]", field.name)
+                } else {
+                    field.name.clone()
+                };
+                edges
+                    .entry((name.clone(), target.to_string()))
+                    .or_insert_with(|| (Vec::new(), false))
+                    .0
+                    .push(label);
+            }
+
+            // Union members and interface implementations
+            let structural = full_type
+                .possible_types
+                .iter()
+                .flatten()
+                .filter_map(|t| t.get_base_type_name());
+            for member in structural {
+                edges
+                    .entry((name.clone(), member.to_string()))
+                    .or_insert_with(|| (Vec::new(), true));
+            }
+        }
+
+        let pairs: Vec<(String, String)> = edges.keys().cloned().collect();
+        let mut adjacency: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (from, to) in &pairs {
+            adjacency.entry(from.as_str()).or_default().push(to.as_str());
+        }
+
+        let scope: BTreeSet<String> = match &self.root {
+            Some(root) => {
+                let start = self
+                    .resolve_root(root)
+                    .ok_or_else(|| anyhow::anyhow!("Root type {} not found in schema", root))?;
+                reachable(&adjacency, &start, self.max_depth)
+            }
+            None => kinds.keys().cloned().collect(),
+        };
+
+        let clusters = strongly_connected(&adjacency);
+
+        let nodes = scope
+            .iter()
+            .filter_map(|name| {
+                let kind = kinds.get(name)?.clone();
+                let sensitive_fields = self
+                    .schema
+                    .get_type(name)
+                    .and_then(|t| t.fields.as_ref())
+                    .map(|fields| {
+                        fields
+                            .iter()
+                            .filter(|f| classify_name(&f.name).is_some_and(|c| c.severity() == Severity::High))
+                            .count()
+                    })
+                    .unwrap_or(0);
+                Some(GraphNode {
+                    name: name.clone(),
+                    kind,
+                    sensitive_fields,
+                    recursive: clusters.contains_key(name.as_str()),
+                })
+            })
+            .collect();
+
+        let edges = edges
+            .into_iter()
+            .filter(|((from, to), _)| scope.contains(from) && scope.contains(to))
+            .map(|((from, to), (labels, structural))| {
+                let recursive = match (clusters.get(from.as_str()), clusters.get(to.as_str())) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => false,
+                };
+                GraphEdge {
+                    label: labels.join(", "),
+                    from,
+                    to,
+                    recursive,
+                    structural,
+                }
+            })
+            .collect();
+
+        Ok((nodes, edges))
+    }
+
+    pub fn render_dot(&self) -> anyhow::Result<String> {
+        let (nodes, edges) = self.build()?;
+        let mut out = String::new();
+
+        out.push_str("digraph schema {\n");
+        out.push_str("  rankdir=LR;\n");
+        out.push_str("  node [shape=box, style=\"rounded,filled\", fillcolor=\"#eeeeee\", fontname=\"Helvetica\"];\n");
+        out.push_str("  edge [fontname=\"Helvetica\", fontsize=10];\n\n");
+
+        for node in &nodes {
+            let mut attrs = vec![format!("label=\"{}\"", node_label(node))];
+            if node.sensitive_fields > 0 {
+                attrs.push("fillcolor=\"#f4a6a6\"".to_string());
+            }
+            if node.recursive {
+                attrs.push("color=\"#cc0000\", penwidth=2".to_string());
+            }
+            match node.kind.as_str() {
+                "INTERFACE" => attrs.push("shape=component".to_string()),
+                "UNION" => attrs.push("shape=hexagon".to_string()),
+                _ => {}
+            }
+            out.push_str(&format!("  \"{}\" [{}];\n", node.name, attrs.join(", ")));
+        }
+
+        out.push('\n');
+        for edge in &edges {
+            let mut attrs = Vec::new();
+            if !edge.label.is_empty() {
+                attrs.push(format!("label=\"{}\"", edge.label));
+            }
+            if edge.structural {
+                attrs.push("style=dashed, arrowhead=empty".to_string());
+            }
+            if edge.recursive {
+                attrs.push("color=\"#cc0000\"".to_string());
+            }
+            out.push_str(&format!(
+                "  \"{}\" -> \"{}\" [{}];\n",
+                edge.from,
+                edge.to,
+                attrs.join(", ")
+            ));
+        }
+
+        out.push_str("}\n");
+        Ok(out)
+    }
+
+    pub fn render_mermaid(&self) -> anyhow::Result<String> {
+        let (nodes, edges) = self.build()?;
+        let mut out = String::new();
+
+        out.push_str("graph LR\n");
+        out.push_str("  classDef sensitive fill:#f4a6a6,stroke:#333\n");
+        out.push_str("  classDef recursive stroke:#cc0000,stroke-width:2px\n");
+
+        for node in &nodes {
+            let label = node_label(node).replace("\\n", "<br/>");
+            let shape = match node.kind.as_str() {
+                "UNION" => format!("{{{{\"{}\"}}}}", label),
+                "INTERFACE" => format!("([\"{}\"])", label),
+                _ => format!("[\"{}\"]", label),
+            };
+            out.push_str(&format!("  {}{}\n", node.name, shape));
+        }
+
+        let mut recursive_links = Vec::new();
+        for (index, edge) in edges.iter().enumerate() {
+            let arrow = if edge.structural { "-.->" } else { "-->" };
+            if edge.label.is_empty() {
+                out.push_str(&format!("  {} {} {}\n", edge.from, arrow, edge.to));
+            } else {
+                out.push_str(&format!(
+                    "  {} {}|\"{}\"| {}\n",
+                    edge.from, arrow, edge.label, edge.to
+                ));
+            }
+            if edge.recursive {
+                recursive_links.push(index.to_string());
+            }
+        }
+
+        let sensitive: Vec<&str> = nodes
+            .iter()
+            .filter(|n| n.sensitive_fields > 0)
+            .map(|n| n.name.as_str())
+            .collect();
+        if !sensitive.is_empty() {
+            out.push_str(&format!("  class {} sensitive\n", sensitive.join(",")));
+        }
+        let recursive: Vec<&str> = nodes
+            .iter()
+            .filter(|n| n.recursive)
+            .map(|n| n.name.as_str())
+            .collect();
+        if !recursive.is_empty() {
+            out.push_str(&format!("  class {} recursive\n", recursive.join(",")));
+        }
+        if !recursive_links.is_empty() {
+            out.push_str(&format!(
+                "  linkStyle {} stroke:#cc0000\n",
+                recursive_links.join(",")
+            ));
+        }
+
+        Ok(out)
+    }
+}
+
+fn node_label(node: &GraphNode) -> String {
+    if node.sensitive_fields > 0 {
+        format!("{}\\n({} sensitive)", node.name, node.sensitive_fields)
+    } else {
+        node.name.clone()
+    }
+}
+
+/// Breadth-first walk from `start`, limited to `max_depth` hops
+fn reachable(adjacency: &HashMap<&str, Vec<&str>>, start: &str, max_depth: Option<usize>) -> BTreeSet<String> {
+    let mut seen = BTreeSet::new();
+    let mut queue = VecDeque::new();
+    seen.insert(start.to_string());
+    queue.push_back((start, 0));
+
+    while let Some((name, depth)) = queue.pop_front() {
+        if max_depth.is_some_and(|max| depth >= max) {
+            continue;
+        }
+        for next in adjacency.get(name).into_iter().flatten() {
+            if seen.insert(next.to_string()) {
+                queue.push_back((next, depth + 1));
+            }
+        }
+    }
+
+    seen
+}
+
+/// Types that sit on a cycle, mapped to a cluster id (Tarjan's algorithm).
+/// Single types only count when they reference themselves.
+fn strongly_connected<'a>(adjacency: &HashMap<&'a str, Vec<&'a str>>) -> HashMap<&'a str, usize> {
+    struct State<'a> {
+        index: usize,
+        indices: HashMap<&'a str, usize>,
+        lowlink: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        on_stack: BTreeSet<&'a str>,
+        clusters: HashMap<&'a str, usize>,
+        next_cluster: usize,
+    }
+
+    fn visit<'a>(node: &'a str, adjacency: &HashMap<&'a str, Vec<&'a str>>, state: &mut State<'a>) {
+        state.indices.insert(node, state.index);
+        state.lowlink.insert(node, state.index);
+        state.index += 1;
+        state.stack.push(node);
+        state.on_stack.insert(node);
+
+        for &next in adjacency.get(node).into_iter().flatten() {
+            if !state.indices.contains_key(next) {
+                visit(next, adjacency, state);
+                let low = state.lowlink[node].min(state.lowlink[next]);
+                state.lowlink.insert(node, low);
+            } else if state.on_stack.contains(next) {
+                let low = state.lowlink[node].min(state.indices[next]);
+                state.lowlink.insert(node, low);
+            }
+        }
+
+        if state.lowlink[node] == state.indices[node] {
+            let mut members = Vec::new();
+            while let Some(member) = state.stack.pop() {
+                state.on_stack.remove(member);
+                members.push(member);
+                if member == node {
+                    break;
+                }
+            }
+
+            let self_loop = adjacency.get(node).is_some_and(|n| n.contains(&node));
+            if members.len() > 1 || self_loop {
+                for member in members {
+                    state.clusters.insert(member, state.next_cluster);
+                }
+                state.next_cluster += 1;
+            }
+        }
+    }
+
+    let mut state = State {
+        index: 0,
+        indices: HashMap::new(),
+        lowlink: HashMap::new(),
+        stack: Vec::new(),
+        on_stack: BTreeSet::new(),
+        clusters: HashMap::new(),
+        next_cluster: 0,
+    };
+
+    let mut nodes: Vec<&str> = adjacency.keys().copied().collect();
+    nodes.sort();
+    for node in nodes {
+        if !state.indices.contains_key(node) {
+            visit(node, adjacency, &mut state);
+        }
+    }
+
+    state.clusters
+}
@@ -1,10 +1,72 @@
-use crate::http::HttpClient;
+use super::candidates::{WordPass, Words};
+use super::sdl::BUILTIN_DIRECTIVES;
+use super::{group, group_is, schema_wordlist, EngineProfile, InputValue, Schema, TypeRef};
+use crate::http::{GraphQLResponse, HttpClient};
 use anyhow::{Context, Result};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 const SCALAR_TYPES: &[&str] = &["String", "Int", "Float", "Boolean", "ID"];
+const DEFAULT_BUCKET_SIZE: usize = 64;
+// Argument name no schema should have, to check how unknown arguments are reported
+const CANARY_ARG: &str = "gqlmapCanaryArg";
+// Unknown field added to every nested probe: keeps the document invalid so nothing
+// executes, and tells where a server that only reports the first error stopped
+const CANARY_FIELD: &str = "gqlmapCanaryField";
+// Unknown field closing every mutation and subscription document and every value probe,
+// so none of them passes validation and runs with whatever placeholders filled in. Not
+// CANARY_FIELD, which value probes put in input objects: Hasura words both the same.
+const CANARY_GUARD: &str = "gqlmapCanaryGuard";
+const DEFAULT_MAX_DEPTH: usize = 3;
+const CHECKPOINT_INTERVAL: Duration = Duration::from_secs(30);
+
+// Common object field names, tried on nested types on top of the wordlist
+const FIELD_WORDS: &[&str] = &[
+    "id", "uuid", "name", "title", "description", "email", "username", "firstName", "lastName",
+    "fullName", "displayName", "avatar", "bio", "url", "image", "createdAt", "updatedAt",
+    "deletedAt", "status", "type", "kind", "role", "roles", "price", "total", "amount", "quantity",
+    "currency", "count", "totalCount", "text", "body", "content", "message", "author", "owner",
+    "user", "creator", "sender", "items", "edges", "node", "nodes", "cursor", "pageInfo",
+    "hasNextPage", "hasPreviousPage", "startCursor", "endCursor", "address", "street", "city",
+    "country", "zip", "phone", "company", "profile", "settings", "token", "expiresAt", "slug",
+    "tags", "category", "parent", "children", "comments", "posts", "orders", "product",
+    "products", "friends", "followers", "following", "members", "width", "height", "size", "path",
+    "version", "enabled", "active", "isActive", "verified", "password", "secret", "theme",
+];
+
+// Names common in input objects (filters, payloads), tried on top of FIELD_WORDS
+const INPUT_WORDS: &[&str] = &[
+    "ids", "userId", "customerId", "ownerId", "productId", "orderId", "limit", "offset", "first",
+    "after", "before", "search", "query", "filter", "where", "orderBy", "sort", "direction", "from",
+    "to", "min", "max", "isAdmin", "admin", "permissions", "oldPassword", "newPassword",
+    "confirmPassword", "input", "data",
+];
+
+// Directive names tried on top of the wordlist: the spec's, then caching, auth,
+// federation and formatting directives common in schema-first servers
+const DIRECTIVE_WORDS: &[&str] = &[
+    "include", "skip", "deprecated", "specifiedBy", "oneOf", "defer", "stream", "live", "export",
+    "client", "connection", "cacheControl", "cached", "cache", "auth", "authenticated", "authorized",
+    "requiresScopes", "policy", "hasRole", "hasPermission", "isAuthenticated", "requireAuth", "private",
+    "public", "admin", "cost", "complexity", "listSize", "rateLimit", "key", "external", "requires",
+    "provides", "shareable", "inaccessible", "override", "tag", "lowercase", "uppercase", "formatDate",
+    "date", "trim", "debug", "log", "mock", "experimental", "beta", "internal", "featureFlag",
+];
+
+// Argument names common on directives
+const DIRECTIVE_ARGS: &[&str] = &[
+    "if", "reason", "url", "by", "label", "initialCount", "maxAge", "scope", "scopes", "inheritMaxAge",
+    "requires", "role", "roles", "permission", "permissions", "policy", "policies", "name", "fields",
+    "from", "format", "weight", "limit", "max", "window", "duration", "key", "value", "as", "flag",
+];
+
+// Directive no schema should have, closing every directive probe the way CANARY_FIELD
+// closes nested ones
+const CANARY_DIRECTIVE: &str = "gqlmapCanaryDirective";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InferredSchema {
@@ -12,6 +74,8 @@ pub struct InferredSchema {
     pub mutation_type: Option<InferredType>,
     pub subscription_type: Option<InferredType>,
     pub types: HashMap<String, InferredType>,
+    #[serde(default)]
+    pub directives: Vec<InferredDirective>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,6 +83,15 @@ pub struct InferredType {
     pub name: String,
     pub kind: String,
     pub fields: Vec<InferredField>,
+    /// Fields of an INPUT_OBJECT
+    #[serde(default)]
+    pub input_fields: Vec<InferredArg>,
+    /// Interfaces an OBJECT implements
+    #[serde(default)]
+    pub interfaces: Vec<String>,
+    /// Object types an INTERFACE or UNION covers
+    #[serde(default)]
+    pub possible_types: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,275 +101,2389 @@ pub struct InferredField {
     pub is_list: bool,
     pub is_non_null: bool,
     pub args: Vec<InferredArg>,
+    #[serde(default)]
+    pub confidence: Confidence,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InferredArg {
     pub name: String,
     pub type_name: Option<String>,
+    /// Known when the server complained the argument was missing or null
+    #[serde(default)]
+    pub is_non_null: bool,
+    #[serde(default)]
+    pub is_list: bool,
+    #[serde(default)]
+    pub confidence: Confidence,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferredDirective {
+    pub name: String,
+    pub args: Vec<InferredArg>,
+    /// `FIELD` when it was accepted on one; empty when it exists but goes elsewhere
+    pub locations: Vec<String>,
+}
+
+/// How sure inference is that a field or argument exists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Confidence {
+    /// Selected again in a query that passed validation (or came from a seed schema)
+    Confirmed,
+    /// Only validation errors gave it away
+    #[default]
+    DerivedFromError,
+    /// Only ever named in a "Did you mean" suggestion
+    SuggestedOnly,
+}
+
+impl Confidence {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Confidence::Confirmed => "confirmed",
+            Confidence::DerivedFromError => "derived-from-error",
+            Confidence::SuggestedOnly => "suggested-only",
+        }
+    }
+}
+
+/// What an inference run reports as it goes
+#[derive(Debug, Clone)]
+pub enum InferenceEvent {
+    /// A phase or a type started
+    Phase(String),
+    /// Something went differently than planned
+    Notice(String),
+    Progress(InferenceProgress),
+}
+
+/// Where the running wordlist pass stands
+#[derive(Debug, Clone)]
+pub struct InferenceProgress {
+    /// Candidates tried so far, out of `total` (suggestions can grow it)
+    pub checked: usize,
+    pub total: usize,
+    /// Fields found by this pass
+    pub found: usize,
+    /// Requests sent in the whole run
+    pub requests: usize,
+    /// Estimated requests left in the running phase
+    pub remaining: usize,
+}
+
+/// Everything needed to pick an interrupted run back up
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceState {
+    /// Endpoint the run was against
+    pub url: String,
+    pub requests: usize,
+    /// Phases already finished: query, mutation, subscription, nested, arguments, abstract,
+    /// directives, validate
+    pub completed: Vec<String>,
+    discovered_types: HashMap<String, InferredType>,
+    discovered_fields: HashSet<String>,
+    #[serde(default)]
+    seeded: HashSet<String>,
+    /// Work left in the phase that was running
+    progress: Option<Progress>,
+    /// Candidates already sent at each probe path, so wordlist passes pick up where they stopped
+    #[serde(default)]
+    probed_words: HashMap<String, HashSet<String>>,
+    #[serde(default)]
+    suggested: HashMap<String, HashSet<String>>,
+    #[serde(default)]
+    directives: Vec<InferredDirective>,
+}
+
+impl InferenceState {
+    pub fn types(&self) -> usize {
+        self.discovered_types.len()
+    }
+}
+
+/// [`InferenceState`] borrowed from a running inferrer, so a checkpoint doesn't copy
+/// everything found so far
+#[derive(Serialize)]
+struct StateRef<'a> {
+    url: &'a str,
+    requests: usize,
+    completed: &'a [String],
+    discovered_types: &'a HashMap<String, InferredType>,
+    discovered_fields: &'a HashSet<String>,
+    seeded: &'a HashSet<String>,
+    progress: Option<Progress>,
+    probed_words: &'a HashMap<String, HashSet<String>>,
+    suggested: &'a HashMap<String, HashSet<String>>,
+    directives: &'a [InferredDirective],
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum Progress {
+    Root(RootProgress),
+    Nested(NestedProgress),
+}
+
+/// Where a root type's wordlist run stands
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RootProgress {
+    operation: String,
+    fields: Vec<InferredField>,
+    #[serde(flatten)]
+    words: WordPass,
+    /// Buckets split up after an ambiguous response, probed before new words
+    pending: Vec<Vec<String>>,
+    single_mode: bool,
+}
+
+/// Types still to visit in the nested walk
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NestedProgress {
+    queue: VecDeque<(String, ProbePath, usize)>,
+    seen: HashSet<String>,
 }
 
 pub struct SchemaInferrer {
     client: HttpClient,
     url: String,
-    wordlist: Vec<String>,
+    wordlist: Arc<Words>,
     discovered_types: HashMap<String, InferredType>,
     discovered_fields: HashSet<String>,
-    // Regex patterns
-    suggestions_regex: Regex,
-    field_error_regex: Regex,
-    _type_error_regex: Regex,
-    _arg_error_regex: Regex,
-    // New regexes for robust detection (Clairvoyance logic)
-    subselection_regex: Regex,
-    must_have_selection_regex: Regex,
-    must_not_have_selection_regex: Regex,
+    /// Candidate fields sent per request; 1 probes word by word
+    bucket_size: usize,
+    requests: usize,
+    /// Whether unknown arguments are named in errors, once known
+    args_reported: Option<bool>,
+    /// How many levels below the root types to explore
+    max_depth: usize,
+    checkpoint: Option<PathBuf>,
+    last_checkpoint: Instant,
+    completed: Vec<String>,
+    /// Unfinished phase from a resumed state
+    resumed: Option<Progress>,
+    /// `Type.field` coordinates a seed schema already describes
+    seeded: HashSet<String>,
+    /// Types still waiting in the running phase, for estimates
+    queued: usize,
+    /// Candidates put in a bucket and requests sent since `infer` started, for estimates
+    words_sent: usize,
+    requests_at_start: usize,
+    /// Error message patterns of the server's engine
+    engine: EngineProfile,
+    /// Whether `engine` was picked by hand rather than detected
+    engine_chosen: bool,
+    /// Send probes as GET query strings instead of JSON POSTs
+    use_get: bool,
+    /// Whether the transport was picked by hand rather than checked
+    transport_chosen: bool,
+    /// Requests this run may send before stopping with what it has
+    max_requests: Option<usize>,
+    /// Smallest gap between two requests, from a per-minute cap
+    min_interval: Option<Duration>,
+    last_request: Option<Instant>,
+    /// Candidates already sent at each probe path (keyed by the wrapped empty selection)
+    probed_words: HashMap<String, HashSet<String>>,
+    /// Field names "Did you mean" suggestions gave for each type
+    suggested: HashMap<String, HashSet<String>>,
+    directives: Vec<InferredDirective>,
     quoted_word_regex: Regex,
 }
 
+/// What one multi-field probe said about each candidate
+#[derive(Default)]
+struct BucketOutcome {
+    has_data: bool,
+    errors: usize,
+    /// Errors that named one of the candidates
+    attributed: usize,
+    /// Rejected with "Cannot query field"
+    missing: HashSet<String>,
+    /// Exist and need a selection set: field -> type
+    objects: HashMap<String, String>,
+    /// Exist, type unknown (returned data or complained about arguments)
+    present: HashSet<String>,
+    required_args: Vec<(String, InferredArg)>,
+    suggestions: Vec<String>,
+}
+
+/// How the server rejected a value probe
+enum ValueError {
+    /// An input object named the unknown canary field
+    UnknownField(String),
+    /// Type string it expected, and whether it rejected a list as a whole
+    Expected(String, bool),
+}
+
+/// Where a probed selection goes in the document: `prefix` + selection + `suffix`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ProbePath {
+    prefix: String,
+    suffix: String,
+}
+
+impl ProbePath {
+    /// The root of `operation`, closed by [`CANARY_GUARD`] unless it's a query
+    fn root(operation: &str) -> Self {
+        let guard = if operation == "query" { String::new() } else { format!(" {}", CANARY_GUARD) };
+        Self {
+            prefix: format!("{} {{ ", operation),
+            suffix: format!("{} }}", guard),
+        }
+    }
+
+    /// The value of argument `arg` on a root field, [`CANARY_GUARD`] after it: a value
+    /// of the right type would otherwise run the field
+    fn argument(operation: &str, field: &InferredField, arg: &str, selection: &str) -> Self {
+        Self {
+            prefix: format!("{} {{ {}({}: ", operation, field.name, arg),
+            suffix: format!("){} {} }}", selection, CANARY_GUARD),
+        }
+    }
+
+    /// The value of field `name` inside the input object at this path
+    fn input_field(&self, name: &str) -> Self {
+        Self {
+            prefix: format!("{}{{{}: ", self.prefix, name),
+            suffix: format!("}}{}", self.suffix),
+        }
+    }
+
+    fn wrap(&self, selection: &str) -> String {
+        format!("{}{}{}", self.prefix, selection, self.suffix)
+    }
+
+    /// The value of argument `arg` on a directive attached to `__typename`
+    fn directive_argument(directive: &str, arg: &str) -> Self {
+        Self {
+            prefix: format!("query {{ __typename @{}({}: ", directive, arg),
+            suffix: ") }".to_string(),
+        }
+    }
+
+    /// Inside an inline fragment on `type_name` at this path
+    fn fragment(&self, type_name: &str) -> Self {
+        Self {
+            prefix: format!("{}... on {} {{ ", self.prefix, type_name),
+            suffix: format!(" }}{}", self.suffix),
+        }
+    }
+
+    /// One level further down, through `field`. `None` when a required argument can't
+    /// be filled with a placeholder (input objects, enums we don't know the values of).
+    fn nested(&self, field: &InferredField) -> Option<Self> {
+        let arguments = placeholder_arguments(&field.args)?;
+        Some(Self {
+            prefix: format!("{}{}{} {{ ", self.prefix, field.name, arguments),
+            suffix: format!(" }}{}", self.suffix),
+        })
+    }
+}
+
+impl InferredSchema {
+    /// Render as SDL, with comments marking what the probes couldn't pin down
+    pub fn to_sdl(&self) -> String {
+        let mut blocks = vec![[
+            "# Inferred by probing, not introspection: this is what the server's errors gave away.",
+            "# List and non-null markers only appear where an error spelled out the full type,",
+            "# and `# ?` marks a type that couldn't be determined (shown as String).",
+            "# Fields a second query couldn't confirm say how they were found: derived-from-error",
+            "# or suggested-only.",
+        ]
+        .join("\n")];
+
+        for directive in self.directives.iter().filter(|d| !BUILTIN_DIRECTIVES.contains(&d.name.as_str())) {
+            let args: Vec<String> = directive
+                .args
+                .iter()
+                .map(|a| format!("{}: {}", a.name, sdl_arg_type(a).0))
+                .collect();
+            let args = if args.is_empty() {
+                String::new()
+            } else {
+                format!("({})", args.join(", "))
+            };
+            let unknown = if directive.args.iter().any(|a| a.type_name.is_none()) { " # ?" } else { "" };
+            blocks.push(if directive.locations.is_empty() {
+                format!("# Not allowed on fields, locations unknown\n# directive @{}{}", directive.name, args)
+            } else {
+                format!("directive @{}{} on {}{}", directive.name, args, directive.locations.join(" | "), unknown)
+            });
+        }
+
+        let roots = ["Query", "Mutation", "Subscription"];
+        let mut types: Vec<&InferredType> = self.types.values().collect();
+        types.sort_by_key(|t| {
+            let rank = roots.iter().position(|r| *r == t.name).unwrap_or(roots.len());
+            (rank, t.name.clone())
+        });
+
+        for inferred_type in types {
+            let block = match inferred_type.kind.as_str() {
+                "SCALAR" => format!("# Enum or custom scalar, values unknown\nscalar {}", inferred_type.name),
+                "UNION" if inferred_type.possible_types.is_empty() => {
+                    format!("# Members unknown\nunion {}", inferred_type.name)
+                }
+                "UNION" => format!("union {} = {}", inferred_type.name, inferred_type.possible_types.join(" | ")),
+                "INPUT_OBJECT" if inferred_type.input_fields.is_empty() => {
+                    format!("# No fields found\ninput {}", inferred_type.name)
+                }
+                "INPUT_OBJECT" => {
+                    let lines: Vec<String> = inferred_type
+                        .input_fields
+                        .iter()
+                        .map(|f| {
+                            let (type_string, known) = sdl_arg_type(f);
+                            format!("  {}: {}{}", f.name, type_string, if known { "" } else { " # ?" })
+                        })
+                        .collect();
+                    format!("input {} {{\n{}\n}}", inferred_type.name, lines.join("\n"))
+                }
+                _ if inferred_type.fields.is_empty() => format!(
+                    "# No fields found (deeper than --depth, or errors didn't name them)\ntype {}{}",
+                    inferred_type.name,
+                    sdl_implements(inferred_type)
+                ),
+                _ => {
+                    let lines: Vec<String> = inferred_type
+                        .fields
+                        .iter()
+                        .map(|f| {
+                            let args: Vec<String> = f
+                                .args
+                                .iter()
+                                .map(|a| format!("{}: {}", a.name, sdl_arg_type(a).0))
+                                .collect();
+                            let args = if args.is_empty() {
+                                String::new()
+                            } else {
+                                format!("({})", args.join(", "))
+                            };
+                            let mut notes = Vec::new();
+                            if f.type_name.is_none() || f.args.iter().any(|a| a.type_name.is_none()) {
+                                notes.push("?");
+                            }
+                            if f.confidence != Confidence::Confirmed {
+                                notes.push(f.confidence.label());
+                            }
+                            format!(
+                                "  {}{}: {}{}",
+                                f.name,
+                                args,
+                                sdl_type(f.type_name.as_deref(), f.is_list, f.is_non_null),
+                                if notes.is_empty() { String::new() } else { format!(" # {}", notes.join(" ")) }
+                            )
+                        })
+                        .collect();
+                    let keyword = if inferred_type.kind == "INTERFACE" { "interface" } else { "type" };
+                    let implemented_by = if inferred_type.possible_types.is_empty() {
+                        String::new()
+                    } else {
+                        format!("# Implemented by {}\n", inferred_type.possible_types.join(", "))
+                    };
+                    format!(
+                        "{}{} {}{} {{\n{}\n}}",
+                        implemented_by,
+                        keyword,
+                        inferred_type.name,
+                        sdl_implements(inferred_type),
+                        lines.join("\n")
+                    )
+                }
+            };
+            blocks.push(block);
+        }
+
+        blocks.join("\n\n") + "\n"
+    }
+}
+
 impl SchemaInferrer {
     pub fn new(client: HttpClient, url: String, wordlist: Vec<String>) -> Self {
         Self {
             client,
             url,
-            wordlist,
+            wordlist: Arc::new(Words::new(wordlist)),
             discovered_types: HashMap::new(),
             discovered_fields: HashSet::new(),
-            // Regex patterns to extract info from GraphQL error messages
-            suggestions_regex: Regex::new(r#"Did you mean (.+)""#).unwrap(),
-            field_error_regex: Regex::new(
-                r#"Cannot query field ["\']?(\w+)["\']? on type ["\']?(\w+)["\']?"#,
-            )
-            .unwrap(),
-            _type_error_regex: Regex::new(r#"Unknown type ["\']?(\w+)["\']?"#).unwrap(),
-            _arg_error_regex: Regex::new(
-                r#"Unknown argument ["\']?(\w+)["\']? on field ["\']?(\w+)["\']?"#,
-            )
-            .unwrap(),
-            // Matches: Subselection required for type 'now_query' of field 'now'
-            subselection_regex: Regex::new(r#"Subselection required for type ["\']?(\w+)["\']? of field ["\']?(\w+)["\']?"#).unwrap(),
-            // Matches: Field "user" of type "User" must have a selection of subfields
-            must_have_selection_regex: Regex::new(r#"Field ["\']?(\w+)["\']? of type ["\']?(\w+)["\']? must have a selection of subfields"#).unwrap(),
-            // Matches: Field "name" must not have a selection since type "String" has no subfields
-            must_not_have_selection_regex: Regex::new(r#"Field ["\']?(\w+)["\']? must not have a selection since type ["\']?(\w+)["\']? has no subfields"#).unwrap(),
-             // Matches quoted words for suggestion extraction: "word" or 'word'
+            bucket_size: DEFAULT_BUCKET_SIZE,
+            requests: 0,
+            args_reported: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            checkpoint: None,
+            last_checkpoint: Instant::now(),
+            completed: Vec::new(),
+            resumed: None,
+            seeded: HashSet::new(),
+            queued: 0,
+            words_sent: 0,
+            requests_at_start: 0,
+            engine: EngineProfile::default(),
+            engine_chosen: false,
+            use_get: false,
+            transport_chosen: false,
+            max_requests: None,
+            min_interval: None,
+            last_request: None,
+            probed_words: HashMap::new(),
+            suggested: HashMap::new(),
+            directives: Vec::new(),
+            // Matches quoted words for suggestion extraction: "word" or 'word'
             quoted_word_regex: Regex::new(r#"["\'](\w+)["\']"#).unwrap(),
         }
     }
 
-    pub async fn infer(&mut self, callback: Option<&dyn Fn(&str)>) -> Result<InferredSchema> {
-        // Try to discover Query type fields
-        if let Some(cb) = callback {
-            cb("Probing Query type...");
-        }
-        let query_fields = self.probe_root_type("query").await?;
-        if !query_fields.is_empty() {
-            self.discovered_types.insert(
-                "Query".to_string(),
-                InferredType {
-                    name: "Query".to_string(),
-                    kind: "OBJECT".to_string(),
-                    fields: query_fields,
-                },
-            );
-        }
+    /// Send up to `size` candidate fields per request (1 disables bucketing)
+    pub fn with_bucket_size(mut self, size: usize) -> Self {
+        self.bucket_size = size.max(1);
+        self
+    }
+
+    /// Explore object types up to this many fields below the root (0 = root fields only)
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Read errors with this engine's patterns instead of detecting them
+    pub fn with_engine(mut self, engine: EngineProfile) -> Self {
+        self.engine = engine;
+        self.engine_chosen = true;
+        self
+    }
+
+    /// Send every probe as a GET query string instead of checking whether POST works
+    pub fn with_get(mut self) -> Self {
+        self.use_get = true;
+        self.transport_chosen = true;
+        self
+    }
+
+    /// Stop once this many requests are sent and return what was found by then. The
+    /// bucket or value probe in flight is finished first, so the count can run a little
+    /// over, but nothing is left half-probed for a resumed run.
+    pub fn with_max_requests(mut self, max: usize) -> Self {
+        self.max_requests = Some(max);
+        self
+    }
+
+    /// Send at most this many requests per minute
+    pub fn with_rate_limit(mut self, per_minute: u32) -> Self {
+        self.min_interval = Some(Duration::from_secs(60) / per_minute.max(1));
+        self
+    }
+
+    /// Save progress to `path` every 30 seconds and after each phase
+    pub fn with_checkpoint(mut self, path: PathBuf) -> Self {
+        self.checkpoint = Some(path);
+        self
+    }
+
+    /// Continue from a saved state instead of starting over
+    pub fn with_state(mut self, state: InferenceState) -> Self {
+        self.discovered_types = state.discovered_types;
+        self.discovered_fields = state.discovered_fields;
+        self.requests = state.requests;
+        self.completed = state.completed;
+        self.seeded = state.seeded;
+        self.resumed = state.progress;
+        self.probed_words = state.probed_words;
+        self.suggested = state.suggested;
+        self.directives = state.directives;
+        self
+    }
+
+    /// Start from a partial schema: its types and fields count as discovered and its
+    /// names join the wordlist, so requests go to what it doesn't cover
+    pub fn with_seed(mut self, schema: &Schema) -> Self {
+        let roots = [
+            (schema.get_query_type(), "Query"),
+            (schema.get_mutation_type(), "Mutation"),
+            (schema.get_subscription_type(), "Subscription"),
+        ];
+
+        for full_type in schema.get_user_types() {
+            let type_name = full_type.name.clone().unwrap_or_default();
+            if SCALAR_TYPES.contains(&type_name.as_str()) {
+                continue;
+            }
+            // Inferred root types go by their default names
+            let name = roots
+                .iter()
+                .find(|(root, _)| root.and_then(|t| t.name.as_deref()) == Some(type_name.as_str()))
+                .map(|(_, default)| default.to_string())
+                .unwrap_or(type_name);
+            let kind = match full_type.kind.as_str() {
+                "INPUT_OBJECT" => "INPUT_OBJECT",
+                "SCALAR" | "ENUM" => "SCALAR",
+                "INTERFACE" => "INTERFACE",
+                "UNION" => "UNION",
+                _ => "OBJECT",
+            };
+            let type_names = |refs: &Option<Vec<TypeRef>>| -> Vec<String> {
+                refs.iter().flatten().filter_map(|t| t.name.clone()).collect()
+            };
+
+            let fields: Vec<InferredField> = full_type
+                .fields
+                .iter()
+                .flatten()
+                .map(|f| {
+                    let (type_name, is_list, is_non_null) = seed_type(&f.field_type);
+                    InferredField {
+                        name: f.name.clone(),
+                        type_name,
+                        is_list,
+                        is_non_null,
+                        args: f.args.iter().map(seed_input_value).collect(),
+                        confidence: Confidence::Confirmed,
+                    }
+                })
+                .collect();
+            for field in &fields {
+                self.seeded.insert(format!("{}.{}", name, field.name));
+                if roots.iter().any(|(_, root)| *root == name) {
+                    self.discovered_fields.insert(field.name.clone());
+                }
+            }
+            for input in full_type.input_fields.iter().flatten() {
+                self.seeded.insert(format!("{}.{}", name, input.name));
+            }
+
+            self.discovered_types.insert(
+                name.clone(),
+                InferredType {
+                    name,
+                    kind: kind.to_string(),
+                    fields,
+                    input_fields: full_type.input_fields.iter().flatten().map(seed_input_value).collect(),
+                    interfaces: type_names(&full_type.interfaces),
+                    possible_types: type_names(&full_type.possible_types),
+                },
+            );
+        }
+
+        for directive in &schema.schema.directives {
+            self.directives.push(InferredDirective {
+                name: directive.name.clone(),
+                args: directive.args.iter().map(seed_input_value).collect(),
+                locations: directive.locations.clone(),
+            });
+        }
+
+        Arc::make_mut(&mut self.wordlist).extend(schema_wordlist(schema, false));
+        self
+    }
+
+    /// Requests sent so far
+    pub fn requests(&self) -> usize {
+        self.requests
+    }
+
+    /// Some servers refuse JSON POSTs from unknown origins or only speak GraphQL over
+    /// GET. If a POST doesn't get a GraphQL answer and a GET does, probe over GET.
+    async fn detect_transport(&mut self, callback: Option<&dyn Fn(&InferenceEvent)>) {
+        let query = "query { __typename }";
+        let post = self.send(query).await;
+        if post.as_ref().is_ok_and(is_graphql_response) {
+            return;
+        }
+
+        self.use_get = true;
+        let get = self.send(query).await;
+        if !get.as_ref().is_ok_and(is_graphql_response) {
+            self.use_get = false;
+            return;
+        }
+
+        let reason = match post {
+            Ok(response) => format!("HTTP {}", response.status),
+            Err(_) => "request failed".to_string(),
+        };
+        if let Some(cb) = callback {
+            cb(&InferenceEvent::Notice(format!(
+                "POST got no GraphQL response ({}), probing over GET instead",
+                reason
+            )));
+        }
+    }
+
+    /// Pick the error patterns from how an unknown field and an unknown argument get
+    /// rejected, staying with graphql-js when nothing matches
+    async fn detect_engine(&mut self, callback: Option<&dyn Fn(&InferenceEvent)>) {
+        let query = format!("query {{ {} __typename({}: 0) }}", CANARY_FIELD, CANARY_ARG);
+        let messages = match self.send(&query).await {
+            Ok(response) => error_messages(&response),
+            Err(_) => return,
+        };
+
+        let event = match EngineProfile::detect(&messages) {
+            Some(engine) => {
+                let event = InferenceEvent::Phase(format!("Errors look like {}", engine.name));
+                self.engine = engine;
+                event
+            }
+            None => InferenceEvent::Notice(
+                "Errors don't match any known engine, reading them as graphql-js (see --engine)".to_string(),
+            ),
+        };
+        if let Some(cb) = callback {
+            cb(&event);
+        }
+    }
+
+    /// Report where the running wordlist pass stands. Types queued behind it count as a
+    /// full pass each, and words cost as many requests as they have so far this run
+    /// (bisecting on one-error-at-a-time servers costs far more than a bucket each).
+    fn report(&self, callback: Option<&dyn Fn(&InferenceEvent)>, checked: usize, total: usize, found: usize) {
+        if let Some(cb) = callback {
+            let words_left = total.saturating_sub(checked) + self.queued * (self.wordlist.len() + FIELD_WORDS.len());
+            let sent = self.requests - self.requests_at_start;
+            let remaining = if self.words_sent == 0 {
+                words_left.div_ceil(self.bucket_size)
+            } else {
+                (words_left as f64 * sent as f64 / self.words_sent as f64).ceil() as usize
+            };
+            cb(&InferenceEvent::Progress(InferenceProgress {
+                checked,
+                total,
+                found,
+                requests: self.requests,
+                remaining,
+            }));
+        }
+    }
+
+    pub async fn infer(&mut self, callback: Option<&dyn Fn(&InferenceEvent)>) -> Result<InferredSchema> {
+        self.requests_at_start = self.requests;
+        if !self.transport_chosen {
+            self.detect_transport(callback).await;
+        }
+        if !self.engine_chosen {
+            self.detect_engine(callback).await;
+        }
+        for (operation, root) in [("query", "Query"), ("mutation", "Mutation"), ("subscription", "Subscription")] {
+            if self.is_completed(operation) {
+                continue;
+            }
+            if let Some(cb) = callback {
+                cb(&InferenceEvent::Phase(format!("Probing {} type...", root)));
+            }
+
+            let fields = self.probe_root_type(operation, callback).await?;
+            if !fields.is_empty() {
+                self.discovered_types.insert(
+                    root.to_string(),
+                    InferredType {
+                        name: root.to_string(),
+                        kind: "OBJECT".to_string(),
+                        fields,
+                        input_fields: Vec::new(),
+                        interfaces: Vec::new(),
+                        possible_types: Vec::new(),
+                    },
+                );
+            }
+            if self.budget_spent() {
+                return Ok(self.stop_on_budget(callback));
+            }
+            self.finish_phase(operation)?;
+        }
+
+        if self.max_depth > 0 && !self.is_completed("nested") {
+            self.probe_nested_types(callback).await?;
+            if self.budget_spent() {
+                return Ok(self.stop_on_budget(callback));
+            }
+            self.finish_phase("nested")?;
+        }
+        if !self.is_completed("arguments") {
+            self.probe_arguments(callback).await?;
+            if self.budget_spent() {
+                // No progress of its own to save, the phase reruns on what it found
+                self.save_checkpoint(None)?;
+                return Ok(self.stop_on_budget(callback));
+            }
+            self.finish_phase("arguments")?;
+        }
+        if !self.is_completed("abstract") {
+            self.probe_abstract_types(callback).await?;
+            if self.budget_spent() {
+                // No progress of its own to save, the phase reruns on what it found
+                self.save_checkpoint(None)?;
+                return Ok(self.stop_on_budget(callback));
+            }
+            self.finish_phase("abstract")?;
+        }
+        if !self.is_completed("directives") {
+            self.probe_directives(callback).await?;
+            if self.budget_spent() {
+                // No progress of its own to save, the phase reruns on what it found
+                self.save_checkpoint(None)?;
+                return Ok(self.stop_on_budget(callback));
+            }
+            self.finish_phase("directives")?;
+        }
+        if !self.is_completed("validate") {
+            self.validate(callback).await?;
+            if self.budget_spent() {
+                // What was confirmed is saved with the types, the rest is checked again
+                self.save_checkpoint(None)?;
+                return Ok(self.stop_on_budget(callback));
+            }
+            self.finish_phase("validate")?;
+        }
+
+        Ok(self.inferred_schema())
+    }
+
+    /// The unfinished phase isn't marked done, so resuming the checkpoint picks it up
+    fn stop_on_budget(&mut self, callback: Option<&dyn Fn(&InferenceEvent)>) -> InferredSchema {
+        if let Some(cb) = callback {
+            cb(&InferenceEvent::Notice(format!(
+                "Request budget of {} spent, stopping with what was found so far",
+                self.max_requests.unwrap_or_default()
+            )));
+        }
+        self.inferred_schema()
+    }
+
+    /// Candidates already sent at `path`, less those still waiting in a bisect, so a
+    /// resumed run doesn't send them again
+    fn remember_probed(&mut self, path: &ProbePath, words: &WordPass, pending: &[Vec<String>]) {
+        let waiting: HashSet<&str> = pending.iter().flatten().map(String::as_str).collect();
+        let probed = words.tried().filter(|w| !waiting.contains(w)).map(String::from).collect();
+        self.probed_words.insert(path.wrap(""), probed);
+    }
+
+    fn budget_spent(&self) -> bool {
+        self.max_requests
+            .is_some_and(|max| self.requests - self.requests_at_start >= max)
+    }
+
+    /// The run ends here, so what was found moves into the schema instead of being copied
+    fn inferred_schema(&mut self) -> InferredSchema {
+        let types = std::mem::take(&mut self.discovered_types);
+        InferredSchema {
+            query_type: types.get("Query").cloned(),
+            mutation_type: types.get("Mutation").cloned(),
+            subscription_type: types.get("Subscription").cloned(),
+            types,
+            directives: std::mem::take(&mut self.directives),
+        }
+    }
+
+    async fn probe_root_type(
+        &mut self,
+        operation: &str,
+        callback: Option<&dyn Fn(&InferenceEvent)>,
+    ) -> Result<Vec<InferredField>> {
+        let mut progress = match self.resumed.take() {
+            Some(Progress::Root(p)) if p.operation == operation => p,
+            other => {
+                self.resumed = other;
+                // Fields a seed already gave aren't probed again
+                let fields = self
+                    .discovered_types
+                    .get(root_name(operation))
+                    .map(|t| t.fields.clone())
+                    .unwrap_or_default();
+                let mut words = WordPass::new(self.wordlist.clone());
+                words.mark_tried(fields.iter().map(|f| &f.name));
+                RootProgress {
+                    operation: operation.to_string(),
+                    fields,
+                    words,
+                    pending: Vec::new(),
+                    single_mode: self.bucket_size <= 1,
+                }
+            }
+        };
+
+        loop {
+            if self.budget_spent() {
+                self.save_checkpoint(Some(Progress::Root(progress.clone())))?;
+                break;
+            }
+            if self.checkpoint_due() {
+                self.save_checkpoint(Some(Progress::Root(progress.clone())))?;
+            }
+            self.report(
+                callback,
+                progress.words.tried_count(),
+                progress.words.tried_count() + progress.words.remaining(),
+                progress.fields.len(),
+            );
+
+            let bucket = match progress.pending.pop() {
+                Some(bucket) => bucket,
+                None => {
+                    let size = if progress.single_mode { 1 } else { self.bucket_size };
+                    let mut bucket = Vec::new();
+                    while bucket.len() < size {
+                        let word = match progress.words.next_word() {
+                            Some(w) => w,
+                            None => break,
+                        };
+                        // Validate field name format
+                        if is_valid_graphql_name(&word) {
+                            bucket.push(word);
+                        }
+                    }
+                    if bucket.is_empty() {
+                        break;
+                    }
+                    self.words_sent += bucket.len();
+                    bucket
+                }
+            };
+
+            if progress.single_mode || bucket.len() == 1 {
+                for word in bucket {
+                    if let Some(field) = self.probe_word(&word, operation, &mut progress.words).await? {
+                        self.discovered_fields.insert(word.clone());
+                        progress.fields.push(field);
+                    }
+                }
+                continue;
+            }
+
+            let outcome = match self.probe_bucket(&bucket, operation).await {
+                Some(outcome) => outcome,
+                None => {
+                    // Transport error or non-GraphQL reply (413, WAF page): try smaller documents
+                    let half = bucket.len() / 2;
+                    progress.pending.push(bucket[half..].to_vec());
+                    progress.pending.push(bucket[..half].to_vec());
+                    continue;
+                }
+            };
+            progress.words.extend(outcome.suggestions.iter().cloned());
+            self.note_suggestions(root_name(operation), &outcome.suggestions);
+
+            if outcome.attributed == 0 && !outcome.has_data {
+                // Errors don't name the fields (masked or generic), so buckets can't be split up
+                progress.single_mode = true;
+                progress.pending.push(bucket);
+                continue;
+            }
+
+            for word in &bucket {
+                let field = if let Some(type_name) = outcome.objects.get(word) {
+                    Some(self.object_field(word, type_name, operation).await?)
+                } else if outcome.present.contains(word) {
+                    Some(self.probe_field(word, operation).await?)
+                } else {
+                    None
+                };
+
+                if let Some(mut field) = field {
+                    for arg in outcome.required_args.iter().filter(|(w, _)| w == word) {
+                        if !field.args.iter().any(|a| a.name == arg.1.name) {
+                            field.args.push(arg.1.clone());
+                        }
+                    }
+                    self.discovered_fields.insert(word.clone());
+                    progress.fields.push(field);
+                }
+            }
+
+            let unresolved: Vec<String> = bucket
+                .into_iter()
+                .filter(|w| {
+                    !outcome.missing.contains(w) && !outcome.objects.contains_key(w) && !outcome.present.contains(w)
+                })
+                .collect();
+
+            if unresolved.len() > 1 && outcome.errors == 1 {
+                // One error for a whole bucket: the server may stop at the first one, so bisect
+                let half = unresolved.len() / 2;
+                progress.pending.push(unresolved[half..].to_vec());
+                progress.pending.push(unresolved[..half].to_vec());
+            } else {
+                // Full error list and no complaint: most likely valid scalar fields, confirm each
+                progress.pending.extend(unresolved.into_iter().map(|w| vec![w]));
+            }
+        }
+
+        Ok(progress.fields)
+    }
+
+    /// Send many candidate fields in one document and sort them by what the errors say.
+    /// `None` when the reply isn't a GraphQL response at all.
+    async fn probe_bucket(&mut self, bucket: &[String], operation: &str) -> Option<BucketOutcome> {
+        let query = ProbePath::root(operation).wrap(&bucket.join(" "));
+        let response = self.send(&query).await.ok()?;
+
+        if !response.has_data() && !response.has_errors() {
+            return None;
+        }
+
+        let mut outcome = BucketOutcome {
+            has_data: response.has_data(),
+            ..Default::default()
+        };
+        let in_bucket = |word: &str| bucket.iter().any(|w| w == word);
+
+        if let Some(data) = response.get_data().and_then(|d| d.as_object()) {
+            for key in data.keys().filter(|k| in_bucket(k)) {
+                outcome.present.insert(key.clone());
+            }
+        }
+
+        let messages: Vec<String> = response
+            .get_errors()
+            .and_then(|e| e.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        outcome.errors = messages.iter().filter(|msg| !self.is_guard_error(msg)).count();
+
+        for msg in &messages {
+            // Turning down the guard names fields as well as any other unknown one does
+            let mut attributed = self.is_guard_error(msg);
+
+            if let Some(cap) = self.engine.field_error.captures(msg) {
+                if in_bucket(&cap["field"]) {
+                    outcome.missing.insert(cap["field"].to_string());
+                    attributed = true;
+                }
+                self.register_type(&cap["type"]);
+            }
+            if let Some(cap) = self.engine.must_have_selection.captures(msg) {
+                if in_bucket(&cap["field"]) {
+                    outcome.objects.insert(cap["field"].to_string(), cap["type"].to_string());
+                    attributed = true;
+                }
+            }
+            if let Some(cap) = self.engine.subselection.captures(msg) {
+                if in_bucket(&cap["field"]) {
+                    outcome.objects.insert(cap["field"].to_string(), cap["type"].to_string());
+                    attributed = true;
+                }
+            }
+            if let Some(cap) = self.engine.required_arg.captures(msg) {
+                let field = group(&cap, "field");
+                if in_bucket(field) {
+                    outcome.present.insert(field.to_string());
+                    outcome.required_args.push((
+                        field.to_string(),
+                        required_arg(&cap["arg"], group(&cap, "type")),
+                    ));
+                    attributed = true;
+                }
+            }
+
+            if attributed {
+                outcome.attributed += 1;
+            }
+
+            if let Some(cap) = self.engine.suggestions.captures(msg) {
+                for word_match in self.quoted_word_regex.captures_iter(&cap["list"]) {
+                    outcome.suggestions.push(word_match[1].to_string());
+                }
+            }
+        }
+
+        // A field needing a selection can also complain about its arguments first
+        for word in outcome.objects.keys() {
+            outcome.present.remove(word);
+        }
+
+        Some(outcome)
+    }
+
+    /// Probe a single candidate field, one request per word
+    async fn probe_word(
+        &mut self,
+        word: &str,
+        operation: &str,
+        words_to_check: &mut WordPass,
+    ) -> Result<Option<InferredField>> {
+        let query = ProbePath::root(operation).wrap(word);
+        let response = match self.send(&query).await {
+            Ok(r) => r,
+            Err(_) => return Ok(None),
+        };
+
+        let mut found_field: Option<InferredField> = None;
+
+        // Check if field exists (has data, or only the guard was turned down)
+        if response.get_data().is_some_and(|data| data.get(word).is_some())
+            || self.passed_validation_but_guard(&response)
+        {
+            // Field exists! Try to determine its type
+            let field = self.probe_field(word, operation).await?;
+            found_field = Some(field);
+        }
+
+        let messages: Vec<String> = response
+            .get_errors()
+            .and_then(|e| e.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        // Check if field exists via specific error messages
+        for msg in &messages {
+            if found_field.is_some() {
+                break;
+            }
+
+            // 1. Subselection required (It's an Object)
+            let object_type = match self.engine.subselection.captures(msg) {
+                Some(cap) if &cap["field"] == word => Some(cap["type"].to_string()),
+                _ => None,
+            };
+            // 2. Must have selection (It's an Object)
+            let object_type = object_type.or_else(|| match self.engine.must_have_selection.captures(msg) {
+                Some(cap) if &cap["field"] == word => Some(cap["type"].to_string()),
+                _ => None,
+            });
+
+            if let Some(type_name) = object_type {
+                found_field = Some(self.object_field(word, &type_name, operation).await?);
+                continue;
+            }
+
+            // 3. Required argument missing (exists, type still unknown)
+            if let Some(cap) = self.engine.required_arg.captures(msg) {
+                if group_is(&cap, "field", word) {
+                    found_field = Some(self.probe_field(word, operation).await?);
+                }
+            }
+        }
+
+        // Extract suggestions and type names from error messages
+        let mut suggestions = Vec::new();
+        for msg in &messages {
+            // Extract "Did you mean X, Y, Z?"
+            if let Some(cap) = self.engine.suggestions.captures(msg) {
+                for word_match in self.quoted_word_regex.captures_iter(&cap["list"]) {
+                    suggestions.push(word_match[1].to_string());
+                }
+            }
+
+            let type_names: Vec<String> = self
+                .engine
+                .field_error
+                .captures_iter(msg)
+                .map(|cap| cap["type"].to_string())
+                .collect();
+            for type_name in type_names {
+                self.register_type(&type_name);
+            }
+        }
+        self.note_suggestions(root_name(operation), &suggestions);
+        words_to_check.extend(suggestions);
+
+        Ok(found_field)
+    }
+
+    /// A field known to return an object type; only its arguments are left to find
+    async fn object_field(&mut self, word: &str, type_string: &str, operation: &str) -> Result<InferredField> {
+        let (type_name, is_list, is_non_null) = parse_type_string(type_string);
+        self.register_type(&type_name);
+
+        let mut field = InferredField {
+            name: word.to_string(),
+            type_name: Some(type_name),
+            is_list,
+            is_non_null,
+            args: Vec::new(),
+            confidence: Confidence::DerivedFromError,
+        };
+        field.args = self.probe_field_args(word, operation, true).await?;
+        Ok(field)
+    }
+
+    /// Breadth-first walk from the root fields, filling in each object type's fields by
+    /// probing them underneath a path that reaches it, e.g. `query { user(id: "1") { <candidates> } }`
+    async fn probe_nested_types(&mut self, callback: Option<&dyn Fn(&InferenceEvent)>) -> Result<()> {
+        let NestedProgress { mut queue, mut seen } = match self.resumed.take() {
+            Some(Progress::Nested(p)) => p,
+            _ => self.nested_start(),
+        };
+
+        loop {
+            if self.budget_spent() || self.checkpoint_due() {
+                let progress = NestedProgress {
+                    queue: queue.clone(),
+                    seen: seen.clone(),
+                };
+                self.save_checkpoint(Some(Progress::Nested(progress)))?;
+                if self.budget_spent() {
+                    break;
+                }
+            }
+
+            let (type_name, path, depth) = match queue.pop_front() {
+                Some(next) => next,
+                None => break,
+            };
+            self.queued = queue.len();
+            if SCALAR_TYPES.contains(&type_name.as_str()) || !seen.insert(type_name.clone()) {
+                continue;
+            }
+
+            if let Some(cb) = callback {
+                cb(&InferenceEvent::Phase(format!("Probing {} fields...", type_name)));
+            }
+
+            // Fields a seed already gave aren't probed again
+            let mut fields = self
+                .discovered_types
+                .get(&type_name)
+                .map(|t| t.fields.clone())
+                .unwrap_or_default();
+            let known: Vec<String> = fields.iter().map(|f| f.name.clone()).collect();
+            let probed = self.probe_type_fields(&type_name, &path, &known, callback).await?;
+            if self.budget_spent() {
+                // Probed halfway: keep what it found, and the type for the next run
+                seen.remove(&type_name);
+                queue.push_front((type_name.clone(), path.clone(), depth));
+            }
+            match probed {
+                Some(found) => fields.extend(found),
+                None => {
+                    if let Some(cb) = callback {
+                        cb(&InferenceEvent::Notice(format!(
+                            "Errors under {} don't name fields, skipping it",
+                            type_name
+                        )));
+                    }
+                    if fields.is_empty() {
+                        continue;
+                    }
+                }
+            }
+
+            if depth < self.max_depth {
+                for field in &fields {
+                    let target = match &field.type_name {
+                        Some(t) if self.discovered_types.get(t).is_some_and(|t| matches!(t.kind.as_str(), "OBJECT" | "INTERFACE")) => {
+                            t.clone()
+                        }
+                        _ => continue,
+                    };
+                    if let Some(next) = path.nested(field) {
+                        queue.push_back((target, next, depth + 1));
+                    }
+                }
+            }
+
+            self.discovered_types
+                .entry(type_name.clone())
+                .or_insert_with(|| InferredType {
+                    name: type_name.clone(),
+                    kind: "OBJECT".to_string(),
+                    fields: Vec::new(),
+                    input_fields: Vec::new(),
+                    interfaces: Vec::new(),
+                    possible_types: Vec::new(),
+                })
+                .fields = fields;
+        }
+
+        Ok(())
+    }
+
+    /// Root fields to walk down from, Query paths first so each type is reached the cheapest way
+    fn nested_start(&self) -> NestedProgress {
+        let mut queue = VecDeque::new();
+        let mut seen = HashSet::new();
+
+        for (operation, root) in [("query", "Query"), ("mutation", "Mutation"), ("subscription", "Subscription")] {
+            seen.insert(root.to_string());
+            let fields = match self.discovered_types.get(root) {
+                Some(t) => &t.fields,
+                None => continue,
+            };
+            for field in fields {
+                if let (Some(type_name), Some(path)) = (&field.type_name, ProbePath::root(operation).nested(field)) {
+                    queue.push_back((type_name.clone(), path, 1));
+                }
+            }
+        }
+
+        NestedProgress { queue, seen }
+    }
+
+    fn is_completed(&self, phase: &str) -> bool {
+        self.completed.iter().any(|p| p == phase)
+    }
+
+    fn finish_phase(&mut self, phase: &str) -> Result<()> {
+        self.completed.push(phase.to_string());
+        self.queued = 0;
+        self.save_checkpoint(None)
+    }
+
+    fn checkpoint_due(&self) -> bool {
+        self.checkpoint.is_some() && self.last_checkpoint.elapsed() >= CHECKPOINT_INTERVAL
+    }
+
+    /// Write the state, through a temporary file so an interruption mid-write can't
+    /// leave a truncated checkpoint behind
+    fn save_checkpoint(&mut self, progress: Option<Progress>) -> Result<()> {
+        let path = match &self.checkpoint {
+            Some(p) => p.clone(),
+            None => return Ok(()),
+        };
+
+        let state = StateRef {
+            url: &self.url,
+            requests: self.requests,
+            completed: &self.completed,
+            discovered_types: &self.discovered_types,
+            discovered_fields: &self.discovered_fields,
+            seeded: &self.seeded,
+            progress,
+            probed_words: &self.probed_words,
+            suggested: &self.suggested,
+            directives: &self.directives,
+        };
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, serde_json::to_string(&state)?)
+            .with_context(|| format!("Failed to write checkpoint {}", tmp.display()))?;
+        std::fs::rename(&tmp, &path)
+            .with_context(|| format!("Failed to write checkpoint {}", path.display()))?;
+
+        self.last_checkpoint = Instant::now();
+        Ok(())
+    }
+
+    /// Fields of `type_name`, at the end of `path`, besides the `known` ones. `None` when
+    /// errors never name the candidates, so nothing can be told apart.
+    async fn probe_type_fields(
+        &mut self,
+        type_name: &str,
+        path: &ProbePath,
+        known: &[String],
+        callback: Option<&dyn Fn(&InferenceEvent)>,
+    ) -> Result<Option<Vec<InferredField>>> {
+        let mut candidates = WordPass::new(self.wordlist.clone());
+        candidates.extend(FIELD_WORDS.iter().rev().map(|w| w.to_string()));
+        candidates.mark_tried(known);
+        candidates.mark_tried(self.probed_words.get(&path.wrap("")).into_iter().flatten());
+        let mut pending: Vec<Vec<String>> = Vec::new();
+
+        // field -> type string, for fields that need a selection
+        let mut objects: HashMap<String, String> = HashMap::new();
+        let mut scalars: Vec<String> = Vec::new();
+        let mut required: Vec<(String, InferredArg)> = Vec::new();
+        let mut any_attributed = false;
+
+        loop {
+            if self.budget_spent() {
+                self.remember_probed(path, &candidates, &pending);
+                break;
+            }
+            self.report(
+                callback,
+                candidates.tried_count(),
+                candidates.tried_count() + candidates.remaining(),
+                objects.len() + scalars.len(),
+            );
+            let bucket = match pending.pop() {
+                Some(bucket) => bucket,
+                None => {
+                    let mut bucket = Vec::new();
+                    while bucket.len() < self.bucket_size {
+                        match candidates.next_word() {
+                            Some(word) if is_valid_graphql_name(&word) => {
+                                bucket.push(word)
+                            }
+                            Some(_) => {}
+                            None => break,
+                        }
+                    }
+                    if bucket.is_empty() {
+                        self.remember_probed(path, &candidates, &pending);
+                        break;
+                    }
+                    self.words_sent += bucket.len();
+                    bucket
+                }
+            };
+
+            let query = path.wrap(&format!("{} {}", bucket.join(" "), CANARY_FIELD));
+            let response = match self.send(&query).await {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+
+            let in_bucket = |word: &str| bucket.iter().any(|w| w == word);
+            let mut missing = HashSet::new();
+            let mut present = HashSet::new();
+            let mut canary_named = false;
+
+            for msg in error_messages(&response) {
+                if let Some(cap) = self.engine.field_error.captures(&msg) {
+                    if &cap["field"] == CANARY_FIELD {
+                        canary_named = true;
+                    } else if in_bucket(&cap["field"]) {
+                        missing.insert(cap["field"].to_string());
+                    }
+                }
+                if let Some(cap) = self.engine.must_have_selection.captures(&msg) {
+                    if in_bucket(&cap["field"]) {
+                        objects.insert(cap["field"].to_string(), cap["type"].to_string());
+                        present.insert(cap["field"].to_string());
+                    }
+                }
+                if let Some(cap) = self.engine.required_arg.captures(&msg) {
+                    let field = group(&cap, "field");
+                    if in_bucket(field) {
+                        present.insert(field.to_string());
+                        required.push((field.to_string(), required_arg(&cap["arg"], group(&cap, "type"))));
+                    }
+                }
+                if let Some(cap) = self.engine.suggestions.captures(&msg) {
+                    let suggestions: Vec<String> = self
+                        .quoted_word_regex
+                        .captures_iter(&cap["list"])
+                        .map(|m| m[1].to_string())
+                        .collect();
+                    self.note_suggestions(type_name, &suggestions);
+                    candidates.extend(suggestions);
+                }
+            }
+
+            if !missing.is_empty() || !present.is_empty() || canary_named {
+                any_attributed = true;
+            } else if !any_attributed {
+                return Ok(None);
+            }
+
+            let probed = bucket.len();
+            let unresolved: Vec<String> = bucket
+                .into_iter()
+                .filter(|w| !missing.contains(w) && !present.contains(w))
+                .collect();
+
+            for word in present.into_iter().filter(|w| !objects.contains_key(w)) {
+                scalars.push(word);
+            }
+
+            if canary_named {
+                // Everything before the canary passed validation
+                scalars.extend(unresolved);
+            } else if unresolved.len() > 1 {
+                // Reporting stopped before the canary: bisect
+                let half = unresolved.len() / 2;
+                pending.push(unresolved[half..].to_vec());
+                pending.push(unresolved[..half].to_vec());
+            } else if probed > 1 {
+                pending.extend(unresolved.into_iter().map(|w| vec![w]));
+            }
+            // A lone word whose error names neither it nor the canary tells us nothing
+        }
+
+        // Selecting subfields on a leaf names its type
+        let mut leaf_types: HashMap<String, String> = HashMap::new();
+        let mut untyped: Vec<String> = scalars.clone();
+        while !untyped.is_empty() {
+            let selections: Vec<String> = untyped.iter().map(|w| format!("{} {{ __typename }}", w)).collect();
+            let query = path.wrap(&format!("{} {}", selections.join(" "), CANARY_FIELD));
+            let response = match self.send(&query).await {
+                Ok(r) => r,
+                Err(_) => break,
+            };
+
+            let mut typed = Vec::new();
+            for msg in error_messages(&response) {
+                if let Some(cap) = self.engine.must_not_have_selection.captures(&msg) {
+                    if untyped.iter().any(|w| w == &cap["field"]) {
+                        leaf_types.insert(cap["field"].to_string(), cap["type"].to_string());
+                        typed.push(cap["field"].to_string());
+                    }
+                }
+            }
+            if typed.is_empty() {
+                break;
+            }
+            untyped.retain(|w| !typed.contains(w));
+        }
+
+        let mut fields = Vec::new();
+        for (name, type_string) in objects.into_iter().chain(
+            scalars
+                .into_iter()
+                .map(|w| {
+                    let type_string = leaf_types.get(&w).cloned().unwrap_or_default();
+                    (w, type_string)
+                }),
+        ) {
+            let (type_name, is_list, is_non_null) = parse_type_string(&type_string);
+            let type_name = if type_name.is_empty() { None } else { Some(type_name) };
+            if let Some(t) = &type_name {
+                if leaf_types.contains_key(&name) {
+                    self.register_leaf_type(t);
+                } else {
+                    self.register_type(t);
+                }
+            }
+
+            let args = required
+                .iter()
+                .filter(|(field, _)| field == &name)
+                .map(|(_, arg)| arg.clone())
+                .collect();
+            fields.push(InferredField {
+                name,
+                type_name,
+                is_list,
+                is_non_null,
+                args,
+                confidence: Confidence::DerivedFromError,
+            });
+        }
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(Some(fields))
+    }
+
+    /// Tell interfaces and unions apart from plain objects. Spreading `... on C` where
+    /// the type can never be a `C` fails validation, so the spreads that pass are the
+    /// types it overlaps with. Two objects never overlap, so a type overlapping two
+    /// types that don't overlap each other is abstract. `__typename` in returned data
+    /// and "inline fragment" suggestions name object types behind an abstract one.
+    async fn probe_abstract_types(&mut self, callback: Option<&dyn Fn(&InferenceEvent)>) -> Result<()> {
+        let paths = self.type_paths();
+        let mut candidates: Vec<String> = paths.iter().map(|(t, _)| t.clone()).collect();
+        // type -> object types seen behind it
+        let mut members: HashMap<String, HashSet<String>> = HashMap::new();
+
+        for (type_name, path) in &paths {
+            let mut found = Vec::new();
+            if let Ok(response) = self.send(&path.wrap("__typename")).await {
+                if let Some(data) = response.get_data() {
+                    collect_typenames(data, &mut found);
+                }
+            }
+
+            // Fields other types have, selected where they don't exist, get pointed at
+            // the object types that do have them
+            if self.discovered_types.get(type_name).is_some_and(|t| t.fields.is_empty()) {
+                let mut words: Vec<String> = self
+                    .discovered_types
+                    .values()
+                    .flat_map(|t| t.fields.iter().map(|f| f.name.clone()))
+                    .collect();
+                words.sort();
+                words.dedup();
+                for chunk in words.chunks(self.bucket_size) {
+                    let response = match self.send(&path.wrap(&chunk.join(" "))).await {
+                        Ok(r) => r,
+                        Err(_) => continue,
+                    };
+                    for msg in error_messages(&response) {
+                        if let Some(cap) = self.engine.inline_fragment.captures(&msg) {
+                            for word_match in self.quoted_word_regex.captures_iter(&cap["list"]) {
+                                found.push(word_match[1].to_string());
+                            }
+                        }
+                    }
+                }
+            }
+
+            for name in found.into_iter().filter(|n| n != type_name && is_valid_graphql_name(n)) {
+                self.register_type(&name);
+                if !candidates.contains(&name) {
+                    candidates.push(name.clone());
+                }
+                members.entry(type_name.clone()).or_default().insert(name);
+            }
+        }
+
+        let mut overlaps: HashMap<String, HashSet<String>> = HashMap::new();
+        let mut disjoint: HashSet<(String, String)> = HashSet::new();
+        for (type_name, path) in &paths {
+            if let Some(cb) = callback {
+                cb(&InferenceEvent::Phase(format!("Spreading fragments on {}...", type_name)));
+            }
+
+            let others: Vec<String> = candidates.iter().filter(|c| *c != type_name).cloned().collect();
+            for chunk in others.chunks(self.bucket_size) {
+                // Servers reporting one error at a time reject one spread per round
+                let mut remaining = chunk.to_vec();
+                while !remaining.is_empty() {
+                    // The caller saves and stops once the budget is spent
+                    if self.budget_spent() {
+                        return Ok(());
+                    }
+                    let spreads: Vec<String> = remaining
+                        .iter()
+                        .map(|c| format!("... on {} {{ __typename }}", c))
+                        .collect();
+                    let response = match self.send(&path.wrap(&spreads.join(" "))).await {
+                        Ok(r) => r,
+                        Err(_) => break,
+                    };
+
+                    let mut rejected = HashSet::new();
+                    for msg in error_messages(&response) {
+                        if let Some(cap) = self.engine.fragment_spread.captures(&msg) {
+                            if &cap["type"] == type_name {
+                                disjoint.insert((type_name.clone(), cap["fragment"].to_string()));
+                                disjoint.insert((cap["fragment"].to_string(), type_name.clone()));
+                                rejected.insert(cap["fragment"].to_string());
+                            }
+                        } else if let Some(cap) = self.engine.type_error.captures(&msg) {
+                            rejected.insert(cap["type"].to_string());
+                        }
+                    }
+                    if rejected.is_empty() {
+                        for other in remaining {
+                            overlaps.entry(type_name.clone()).or_default().insert(other.clone());
+                            overlaps.entry(other).or_default().insert(type_name.clone());
+                        }
+                        break;
+                    }
+                    // Rejected names outside the spreads (a type the error message
+                    // names differently) would resend the same document forever
+                    let before = remaining.len();
+                    remaining.retain(|c| !rejected.contains(c));
+                    if remaining.len() == before {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if disjoint.is_empty() {
+            if let Some(cb) = callback {
+                cb(&InferenceEvent::Notice(
+                    "No fragment spread was ever rejected, can't tell interfaces and unions apart".to_string(),
+                ));
+            }
+            return Ok(());
+        }
+
+        let abstract_types: HashSet<String> = overlaps
+            .iter()
+            .filter(|(_, others)| {
+                others
+                    .iter()
+                    .any(|a| others.iter().any(|b| disjoint.contains(&(a.clone(), b.clone()))))
+            })
+            .map(|(t, _)| t.clone())
+            .chain(members.keys().cloned())
+            .collect();
+
+        for type_name in &abstract_types {
+            let mut possible: Vec<String> = overlaps
+                .get(type_name)
+                .into_iter()
+                .flatten()
+                .chain(members.get(type_name).into_iter().flatten())
+                .filter(|t| !abstract_types.contains(*t))
+                .cloned()
+                .collect();
+            possible.sort();
+            possible.dedup();
+
+            if let Some(t) = self.discovered_types.get_mut(type_name) {
+                // Unions have nothing to select but __typename
+                t.kind = if t.fields.is_empty() { "UNION" } else { "INTERFACE" }.to_string();
+                t.possible_types = possible;
+            }
+        }
+
+        let objects: Vec<String> = overlaps.keys().filter(|t| !abstract_types.contains(*t)).cloned().collect();
+        for type_name in objects {
+            let mut interfaces: Vec<String> = overlaps[&type_name]
+                .iter()
+                .filter(|t| self.discovered_types.get(*t).is_some_and(|t| t.kind == "INTERFACE"))
+                .cloned()
+                .collect();
+            interfaces.sort();
+            if let Some(t) = self.discovered_types.get_mut(&type_name) {
+                t.interfaces = interfaces;
+            }
+        }
+
+        // Object types only ever seen behind an abstract one get their fields probed
+        // through a fragment on it
+        for (type_name, path) in &paths {
+            let possible = match self.discovered_types.get(type_name) {
+                Some(t) if t.kind != "OBJECT" => t.possible_types.clone(),
+                _ => continue,
+            };
+            for member in possible {
+                let unprobed = paths.iter().all(|(t, _)| *t != member)
+                    && self.discovered_types.get(&member).is_some_and(|t| t.fields.is_empty());
+                if !unprobed {
+                    continue;
+                }
+                if let Some(cb) = callback {
+                    cb(&InferenceEvent::Phase(format!("Probing {} fields...", member)));
+                }
+                if let Some(fields) = self.probe_type_fields(&member, &path.fragment(&member), &[], callback).await? {
+                    if let Some(t) = self.discovered_types.get_mut(&member) {
+                        t.fields = fields;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The cheapest path to every output type reachable from the root fields
+    fn type_paths(&self) -> Vec<(String, ProbePath)> {
+        let NestedProgress { mut queue, mut seen } = self.nested_start();
+        let mut paths = Vec::new();
+
+        while let Some((type_name, path, _)) = queue.pop_front() {
+            let inferred_type = match self.discovered_types.get(&type_name) {
+                Some(t) if is_composite(t) => t,
+                _ => continue,
+            };
+            if !seen.insert(type_name.clone()) {
+                continue;
+            }
+            for field in &inferred_type.fields {
+                if let (Some(next_type), Some(next)) = (&field.type_name, path.nested(field)) {
+                    queue.push_back((next_type.clone(), next, 0));
+                }
+            }
+            paths.push((type_name, path));
+        }
+
+        paths
+    }
+
+    /// Attach candidate directives to `__typename` and keep those the server doesn't call
+    /// unknown: a directive that exists passes, or complains about its arguments or
+    /// where it was used instead. Names already known (seeded, or found before a
+    /// resume) aren't sent again.
+    async fn probe_directives(&mut self, callback: Option<&dyn Fn(&InferenceEvent)>) -> Result<()> {
+        if let Some(cb) = callback {
+            cb(&InferenceEvent::Phase("Probing directives...".to_string()));
+        }
+        let mut candidates = WordPass::new(self.wordlist.clone());
+        candidates.extend(DIRECTIVE_WORDS.iter().rev().map(|w| w.to_string()));
+        candidates.mark_tried(self.directives.iter().map(|d| &d.name));
+        let mut pending: Vec<Vec<String>> = Vec::new();
+
+        // directive -> whether it may go on a field
+        let mut found: HashMap<String, bool> = HashMap::new();
+        let mut required: Vec<(String, InferredArg)> = Vec::new();
+        let mut any_attributed = false;
+
+        loop {
+            if self.budget_spent() {
+                break;
+            }
+            self.report(
+                callback,
+                candidates.tried_count(),
+                candidates.tried_count() + candidates.remaining(),
+                found.len(),
+            );
+            let bucket = match pending.pop() {
+                Some(bucket) => bucket,
+                None => {
+                    let mut bucket = Vec::new();
+                    while bucket.len() < self.bucket_size {
+                        match candidates.next_word() {
+                            Some(word) if is_valid_graphql_name(&word) => {
+                                bucket.push(word)
+                            }
+                            Some(_) => {}
+                            None => break,
+                        }
+                    }
+                    if bucket.is_empty() {
+                        break;
+                    }
+                    self.words_sent += bucket.len();
+                    bucket
+                }
+            };
+
+            let directives: Vec<String> = bucket.iter().map(|d| format!("@{}", d)).collect();
+            let query = format!("query {{ __typename {} @{} }}", directives.join(" "), CANARY_DIRECTIVE);
+            let response = match self.send(&query).await {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+
+            let in_bucket = |name: &str| bucket.iter().any(|d| d == name);
+            let mut unknown = HashSet::new();
+            let mut present = HashSet::new();
+            let mut canary_named = false;
+
+            for msg in error_messages(&response) {
+                if let Some(cap) = self.engine.directive_error.captures(&msg) {
+                    if &cap["directive"] == CANARY_DIRECTIVE {
+                        canary_named = true;
+                    } else if in_bucket(&cap["directive"]) {
+                        unknown.insert(cap["directive"].to_string());
+                    }
+                }
+                if let Some(cap) = self.engine.misplaced_directive.captures(&msg) {
+                    if in_bucket(&cap["directive"]) {
+                        present.insert(cap["directive"].to_string());
+                        found.insert(cap["directive"].to_string(), false);
+                    }
+                }
+                if let Some(cap) = self.engine.required_directive_arg.captures(&msg) {
+                    // Engines that don't name the directive only get a lone one attributed
+                    let name = match cap.name("directive") {
+                        Some(m) => m.as_str().to_string(),
+                        None if bucket.len() == 1 => bucket[0].clone(),
+                        None => continue,
+                    };
+                    if in_bucket(&name) {
+                        present.insert(name.clone());
+                        required.push((name, required_arg(&cap["arg"], group(&cap, "type"))));
+                    }
+                }
+            }
+
+            if !unknown.is_empty() || !present.is_empty() || canary_named {
+                any_attributed = true;
+            } else if !any_attributed {
+                if let Some(cb) = callback {
+                    cb(&InferenceEvent::Notice(
+                        "Errors don't name directives, skipping them".to_string(),
+                    ));
+                }
+                return Ok(());
+            }
+
+            let probed = bucket.len();
+            let unresolved: Vec<String> = bucket
+                .into_iter()
+                .filter(|d| !unknown.contains(d) && !present.contains(d))
+                .collect();
+            for name in present {
+                found.entry(name).or_insert(true);
+            }
+
+            if canary_named {
+                // Everything before the canary passed validation
+                found.extend(unresolved.into_iter().map(|d| (d, true)));
+            } else if unresolved.len() > 1 {
+                let half = unresolved.len() / 2;
+                pending.push(unresolved[half..].to_vec());
+                pending.push(unresolved[..half].to_vec());
+            } else if probed > 1 {
+                pending.extend(unresolved.into_iter().map(|d| vec![d]));
+            }
+            // A lone directive whose errors name neither it nor the canary tells us nothing
+        }
+
+        let mut found: Vec<(String, bool)> = found.into_iter().collect();
+        found.sort();
+        for (name, on_field) in found {
+            let mut args: Vec<InferredArg> = Vec::new();
+            for (_, arg) in required.iter().filter(|(d, _)| *d == name) {
+                if !args.iter().any(|a| a.name == arg.name) {
+                    args.push(arg.clone());
+                }
+            }
+            if on_field && !self.budget_spent() {
+                self.probe_directive_args(&name, &mut args).await;
+            }
+            self.directives.push(InferredDirective {
+                name,
+                args,
+                locations: if on_field { vec!["FIELD".to_string()] } else { Vec::new() },
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Arguments of a directive allowed on fields, found like field arguments: names the
+    /// server doesn't call unknown exist, and value probes type them
+    async fn probe_directive_args(&mut self, directive: &str, args: &mut Vec<InferredArg>) {
+        let mut candidates: Vec<String> = DIRECTIVE_ARGS
+            .iter()
+            .filter(|a| !args.iter().any(|known| known.name == **a))
+            .map(|a| a.to_string())
+            .collect();
+        candidates.push(CANARY_ARG.to_string());
+        let mut canary_rejected = false;
+
+        // Servers reporting one error at a time name one unknown argument per round
+        loop {
+            if self.budget_spent() {
+                return;
+            }
+            let arguments: Vec<String> = candidates.iter().map(|a| format!("{}: null", a)).collect();
+            let query = format!("query {{ __typename @{}({}) }}", directive, arguments.join(", "));
+            let response = match self.send(&query).await {
+                Ok(r) => r,
+                Err(_) => return,
+            };
+
+            let mut unknown = HashSet::new();
+            for msg in error_messages(&response) {
+                if let Some(cap) = self.engine.directive_arg_error.captures(&msg) {
+                    if group_is(&cap, "directive", directive) && candidates.iter().any(|a| a == &cap["arg"]) {
+                        unknown.insert(cap["arg"].to_string());
+                    }
+                }
+            }
+            if unknown.is_empty() {
+                break;
+            }
+            canary_rejected |= unknown.contains(CANARY_ARG);
+            candidates.retain(|a| !unknown.contains(a));
+        }
+
+        // Unknown arguments aren't named, so the silence says nothing about the rest
+        if canary_rejected {
+            args.extend(candidates.into_iter().map(|name| InferredArg {
+                name,
+                type_name: None,
+                is_non_null: false,
+                is_list: false,
+                confidence: Confidence::DerivedFromError,
+            }));
+        }
+
+        for arg in args.iter_mut() {
+            if self.budget_spent() {
+                break;
+            }
+            if !is_known_scalar(arg) {
+                let path = ProbePath::directive_argument(directive, &arg.name);
+                self.probe_value_type(&path, arg).await;
+            }
+        }
+    }
+
+    /// Select every discovered field again, required arguments filled in, and grade how
+    /// sure we are it exists: passing validation (but for the guard mutations carry)
+    /// confirms it, "Cannot query field" drops it. Names only ever suggested get the same check and join their type unless the
+    /// server rules them out. Confirmed fields aren't sent again on a resumed run.
+    async fn validate(&mut self, callback: Option<&dyn Fn(&InferenceEvent)>) -> Result<()> {
+        let mut paths: Vec<(String, ProbePath)> =
+            [("query", "Query"), ("mutation", "Mutation"), ("subscription", "Subscription")]
+                .into_iter()
+                .filter(|(_, root)| self.discovered_types.contains_key(*root))
+                .map(|(operation, root)| (root.to_string(), ProbePath::root(operation)))
+                .collect();
+        paths.extend(self.type_paths());
+
+        for (type_name, path) in paths {
+            if self.budget_spent() {
+                break;
+            }
+            let mut fields = match self.discovered_types.get(&type_name) {
+                Some(t) => t.fields.clone(),
+                None => continue,
+            };
+            let mut suggested: Vec<String> = self
+                .suggested
+                .get(&type_name)
+                .into_iter()
+                .flatten()
+                .filter(|name| !fields.iter().any(|f| &f.name == *name))
+                .cloned()
+                .collect();
+            suggested.sort();
+            if suggested.is_empty() && fields.iter().all(|f| f.confidence == Confidence::Confirmed) {
+                continue;
+            }
+            if let Some(cb) = callback {
+                cb(&InferenceEvent::Phase(format!("Confirming {} fields...", type_name)));
+            }
+
+            let mut rejected = Vec::new();
+            for field in fields.iter_mut().filter(|f| f.confidence != Confidence::Confirmed) {
+                if self.budget_spent() {
+                    break;
+                }
+                match self.confirm_field(&type_name, &path, field).await {
+                    Some(confidence) => field.confidence = confidence,
+                    None => rejected.push(field.name.clone()),
+                }
+            }
+            for name in suggested {
+                if self.budget_spent() {
+                    break;
+                }
+                let mut field = InferredField {
+                    name,
+                    type_name: None,
+                    is_list: false,
+                    is_non_null: false,
+                    args: Vec::new(),
+                    confidence: Confidence::SuggestedOnly,
+                };
+                if let Some(confidence) = self.confirm_field(&type_name, &path, &mut field).await {
+                    field.confidence = confidence;
+                    fields.push(field);
+                }
+            }
+
+            if !rejected.is_empty() {
+                if let Some(cb) = callback {
+                    cb(&InferenceEvent::Notice(format!(
+                        "{} rejected on a second look, dropping {}",
+                        type_name,
+                        rejected.join(", ")
+                    )));
+                }
+                fields.retain(|f| !rejected.contains(&f.name));
+            }
+            if let Some(t) = self.discovered_types.get_mut(&type_name) {
+                t.fields = fields;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Select `field` of `type_name` at `path` on its own. `None` when the server says
+    /// the type has no such field, otherwise how sure the answer makes us.
+    async fn confirm_field(&mut self, type_name: &str, path: &ProbePath, field: &mut InferredField) -> Option<Confidence> {
+        // A required input object or enum can't be filled in
+        let arguments = match placeholder_arguments(&field.args) {
+            Some(arguments) => arguments,
+            None => return Some(field.confidence),
+        };
+        let selection = match &field.type_name {
+            Some(t) if self.discovered_types.get(t).is_some_and(is_composite) => " { __typename }",
+            _ => "",
+        };
+        let query = path.wrap(&format!("{}{}{}", field.name, arguments, selection));
+        let response = match self.send(&query).await {
+            Ok(r) => r,
+            Err(_) => return Some(field.confidence),
+        };
+
+        // Below a root, a field on the path could have the same name
+        let is_root = ["Query", "Mutation", "Subscription"].contains(&type_name);
+        for msg in error_messages(&response) {
+            if let Some(cap) = self.engine.field_error.captures(&msg) {
+                if cap["field"] == field.name && (is_root || &cap["type"] == type_name) {
+                    return None;
+                }
+            }
+        }
+        if !self.passed_validation_but_guard(&response) {
+            return Some(field.confidence);
+        }
+
+        // Required arguments were filled in and accepted with it
+        for arg in field.args.iter_mut().filter(|a| a.is_non_null) {
+            arg.confidence = Confidence::Confirmed;
+        }
+        self.confirm_optional_args(path, field, selection).await;
+        Some(Confidence::Confirmed)
+    }
+
+    /// Send the optional arguments of a confirmed field as `null`, which any nullable
+    /// type accepts: passing validation (but for the guard) confirms them, "Unknown
+    /// argument" drops them
+    async fn confirm_optional_args(&mut self, path: &ProbePath, field: &mut InferredField, selection: &str) {
+        let required = placeholder_values(&field.args).unwrap_or_default();
+
+        loop {
+            let optional: Vec<String> = field
+                .args
+                .iter()
+                .filter(|a| !a.is_non_null && a.confidence != Confidence::Confirmed)
+                .map(|a| a.name.clone())
+                .collect();
+            if optional.is_empty() || self.budget_spent() {
+                return;
+            }
+
+            let arguments: Vec<String> = required
+                .iter()
+                .cloned()
+                .chain(optional.iter().map(|a| format!("{}: null", a)))
+                .collect();
+            let query = path.wrap(&format!("{}({}){}", field.name, arguments.join(", "), selection));
+            let response = match self.send(&query).await {
+                Ok(r) => r,
+                Err(_) => return,
+            };
+
+            let mut unknown = HashSet::new();
+            for msg in error_messages(&response) {
+                if let Some(cap) = self.engine.arg_error.captures(&msg) {
+                    if optional.iter().any(|a| a == &cap["arg"]) {
+                        unknown.insert(cap["arg"].to_string());
+                    }
+                }
+            }
+            if unknown.is_empty() {
+                if self.passed_validation_but_guard(&response) {
+                    for arg in field.args.iter_mut().filter(|a| !a.is_non_null) {
+                        arg.confidence = Confidence::Confirmed;
+                    }
+                }
+                return;
+            }
+            // Servers reporting one error at a time name one per round
+            field.args.retain(|a| !unknown.contains(&a.name));
+        }
+    }
+
+    /// Whether the document validated, or would have without [`CANARY_GUARD`]: that's
+    /// the only field the server turned down
+    fn passed_validation_but_guard(&self, response: &GraphQLResponse) -> bool {
+        let messages = error_messages(response);
+        passed_validation(response) || (!messages.is_empty() && messages.iter().all(|msg| self.is_guard_error(msg)))
+    }
 
-        // Try to discover Mutation type fields
-        if let Some(cb) = callback {
-            cb("Probing Mutation type...");
-        }
-        let mutation_fields = self.probe_root_type("mutation").await?;
-        if !mutation_fields.is_empty() {
-            self.discovered_types.insert(
-                "Mutation".to_string(),
-                InferredType {
-                    name: "Mutation".to_string(),
-                    kind: "OBJECT".to_string(),
-                    fields: mutation_fields,
-                },
-            );
-        }
+    /// Whether `msg` is the server turning down [`CANARY_GUARD`]
+    fn is_guard_error(&self, msg: &str) -> bool {
+        self.engine
+            .field_error
+            .captures(msg)
+            .is_some_and(|cap| &cap["field"] == CANARY_GUARD)
+    }
 
-        // Try to discover Subscription type fields
-        if let Some(cb) = callback {
-            cb("Probing Subscription type...");
+    /// Type every root field argument and discover the fields of the input objects they
+    /// take, following input objects nested inside them
+    async fn probe_arguments(&mut self, callback: Option<&dyn Fn(&InferenceEvent)>) -> Result<()> {
+        let mut queue: VecDeque<(String, ProbePath)> = VecDeque::new();
+
+        for (operation, root) in [("query", "Query"), ("mutation", "Mutation"), ("subscription", "Subscription")] {
+            let mut fields = match self.discovered_types.get(root) {
+                Some(t) => t.fields.clone(),
+                None => continue,
+            };
+
+            for field in fields.iter_mut() {
+                if self.budget_spent() {
+                    break;
+                }
+                if self.seeded.contains(&format!("{}.{}", root, field.name)) {
+                    continue;
+                }
+                let selection = match &field.type_name {
+                    Some(t) if self.discovered_types.get(t).is_some_and(is_composite) => " { __typename }",
+                    _ => "",
+                };
+                for i in 0..field.args.len() {
+                    if is_known_scalar(&field.args[i]) {
+                        continue;
+                    }
+                    let path = ProbePath::argument(operation, field, &field.args[i].name, selection);
+                    if let Some(input_type) = self.probe_value_type(&path, &mut field.args[i]).await {
+                        queue.push_back((input_type, path));
+                    }
+                }
+            }
+
+            if let Some(t) = self.discovered_types.get_mut(root) {
+                t.fields = fields;
+            }
         }
-        let subscription_fields = self.probe_root_type("subscription").await?;
-        if !subscription_fields.is_empty() {
+
+        let mut seen = HashSet::new();
+        while let Some((type_name, path)) = queue.pop_front() {
+            if self.budget_spent() {
+                break;
+            }
+            if !seen.insert(type_name.clone()) {
+                continue;
+            }
+            self.queued = queue.len();
+            if let Some(cb) = callback {
+                cb(&InferenceEvent::Phase(format!("Probing {} input fields...", type_name)));
+            }
+
+            let known = self
+                .discovered_types
+                .get(&type_name)
+                .map(|t| t.input_fields.clone())
+                .unwrap_or_default();
+            let mut input_fields = self.probe_input_fields(&type_name, &path, known, callback).await?;
+            for input_field in input_fields.iter_mut() {
+                let field_path = path.input_field(&input_field.name);
+                if self.seeded.contains(&format!("{}.{}", type_name, input_field.name)) {
+                    // Typed by the seed, but an input object may still have fields it lacks
+                    let nested = input_field
+                        .type_name
+                        .clone()
+                        .filter(|t| self.discovered_types.get(t).is_some_and(|t| t.kind == "INPUT_OBJECT"));
+                    if let Some(nested) = nested {
+                        queue.push_back((nested, field_path));
+                    }
+                    continue;
+                }
+                if is_known_scalar(input_field) {
+                    continue;
+                }
+                if let Some(nested) = self.probe_value_type(&field_path, input_field).await {
+                    queue.push_back((nested, field_path));
+                }
+            }
+
             self.discovered_types.insert(
-                "Subscription".to_string(),
+                type_name.clone(),
                 InferredType {
-                    name: "Subscription".to_string(),
-                    kind: "OBJECT".to_string(),
-                    fields: subscription_fields,
+                    name: type_name,
+                    kind: "INPUT_OBJECT".to_string(),
+                    fields: Vec::new(),
+                    input_fields,
+                    interfaces: Vec::new(),
+                    possible_types: Vec::new(),
                 },
             );
         }
 
-        // Build the schema
-        Ok(InferredSchema {
-            query_type: self.discovered_types.get("Query").cloned(),
-            mutation_type: self.discovered_types.get("Mutation").cloned(),
-            subscription_type: self.discovered_types.get("Subscription").cloned(),
-            types: self.discovered_types.clone(),
-        })
+        Ok(())
     }
 
-    async fn probe_root_type(&mut self, operation: &str) -> Result<Vec<InferredField>> {
-        let mut fields = Vec::new();
-        let mut checked_words = HashSet::new();
-        let mut words_to_check: Vec<String> = self.wordlist.clone();
+    /// Work out the type expected at `path` from how wrongly shaped values get rejected:
+    /// `null` (non-null types say so), an object with an unknown field (input objects
+    /// name the field, everything else names the type it expected), plain literals for
+    /// custom scalars that take objects, and the object inside a list to tell list types
+    /// apart. Fills in `arg` and returns the input object's name when it is one.
+    async fn probe_value_type(&mut self, path: &ProbePath, arg: &mut InferredArg) -> Option<String> {
+        let fully_typed = is_fully_typed(arg);
 
-        while let Some(word) = words_to_check.pop() {
-            if checked_words.contains(&word) {
-                continue;
+        if !arg.is_non_null {
+            if let Some(ValueError::Expected(type_string, _)) = self.value_error(path, "null").await {
+                let (type_name, is_list, is_non_null) = parse_type_string(&type_string);
+                arg.type_name = Some(type_name);
+                arg.is_list |= is_list;
+                arg.is_non_null |= is_non_null;
             }
-            checked_words.insert(word.clone());
+        }
 
-            // Validate field name format
-            if !is_valid_graphql_name(&word) {
-                continue;
+        let object = format!("{{{}: null}}", CANARY_FIELD);
+        let mut input_object = None;
+        match self.value_error(path, &object).await {
+            Some(ValueError::UnknownField(type_name)) => {
+                arg.type_name = Some(type_name.clone());
+                input_object = Some(type_name);
+            }
+            Some(ValueError::Expected(type_string, _)) => self.set_leaf_type(arg, &type_string),
+            None => {
+                for literal in ["0", "\"gqlmap\"", "true"] {
+                    if let Some(ValueError::Expected(type_string, _)) = self.value_error(path, literal).await {
+                        self.set_leaf_type(arg, &type_string);
+                        break;
+                    }
+                }
             }
+        }
 
-            let query = format!("{} {{ {} }}", operation, word);
-            let response = self
-                .client
-                .post_graphql(&self.url, &query, None, Some("inference"))
-                .await;
+        // A list type checks each item, anything else rejects the list as a whole
+        if !fully_typed && !arg.is_list && arg.type_name.is_some() {
+            match self.value_error(path, &format!("[{}]", object)).await {
+                Some(ValueError::UnknownField(_)) => arg.is_list = true,
+                Some(ValueError::Expected(_, found_list)) => arg.is_list = !found_list,
+                None => {}
+            }
+        }
 
-            let response = match response {
-                Ok(r) => r,
-                Err(_) => continue,
-            };
+        input_object
+    }
 
-            let mut found_field: Option<InferredField> = None;
+    fn set_leaf_type(&mut self, arg: &mut InferredArg, type_string: &str) {
+        let (type_name, is_list, is_non_null) = parse_type_string(type_string);
+        self.register_leaf_type(&type_name);
+        arg.type_name = Some(type_name);
+        arg.is_list |= is_list;
+        arg.is_non_null |= is_non_null;
+    }
 
-            // Check if field exists (has data)
-            if response.has_data() {
-                if let Some(data) = response.get_data() {
-                    if data.get(&word).is_some() {
-                        // Field exists! Try to determine its type
-                        let field = self.probe_field(&word, operation).await?;
-                        found_field = Some(field);
-                    }
-                }
-            }
-
-            // Check if field exists via specific error messages
-            if found_field.is_none() {
-                if let Some(errors) = response.get_errors() {
-                    if let Some(arr) = errors.as_array() {
-                        for error in arr {
-                            if let Some(msg) = error.get("message").and_then(|m| m.as_str()) {
-                                // 1. Subselection required (It's an Object)
-                                if let Some(cap) = self.subselection_regex.captures(msg) {
-                                    if let (Some(type_name), Some(field_name_cap)) = (cap.get(1), cap.get(2)) {
-                                        if field_name_cap.as_str() == word {
-                                            let type_str = type_name.as_str().to_string();
-                                            self.register_type(&type_str);
-                                            
-                                            let mut field = InferredField {
-                                                name: word.clone(),
-                                                type_name: Some(type_str),
-                                                is_list: false,
-                                                is_non_null: false,
-                                                args: Vec::new(),
-                                            };
-                                            field.args = self.probe_field_args(&word, operation).await?;
-                                            found_field = Some(field);
-                                        }
-                                    }
-                                }
+    /// How the server rejected `value` at `path`, if it did in a way we understand
+    async fn value_error(&mut self, path: &ProbePath, value: &str) -> Option<ValueError> {
+        let response = self.send(&path.wrap(value)).await.ok()?;
 
-                                // 2. Must have selection (It's an Object)
-                                if found_field.is_none() {
-                                    if let Some(cap) = self.must_have_selection_regex.captures(msg) {
-                                        if let (Some(field_name_cap), Some(type_name)) = (cap.get(1), cap.get(2)) {
-                                            if field_name_cap.as_str() == word {
-                                                let type_str = type_name.as_str().to_string();
-                                                self.register_type(&type_str);
-
-                                                let mut field = InferredField {
-                                                    name: word.clone(),
-                                                    type_name: Some(type_str),
-                                                    is_list: false,
-                                                    is_non_null: false,
-                                                    args: Vec::new(),
-                                                };
-                                                field.args = self.probe_field_args(&word, operation).await?;
-                                                found_field = Some(field);
-                                            }
-                                        }
-                                    }
-                                }
+        for msg in value_messages(&response, path, value) {
+            if let Some(cap) = self.engine.input_field_error.captures(&msg) {
+                // Without the input object's name there's nothing to walk into
+                if &cap["field"] == CANARY_FIELD && !group(&cap, "type").is_empty() {
+                    return Some(ValueError::UnknownField(cap["type"].to_string()));
+                }
+            }
+            if let Some(cap) = self
+                .engine
+                .expected_value
+                .captures(&msg)
+                .or_else(|| self.engine.enum_value.captures(&msg))
+            {
+                let found = group(&cap, "found").to_lowercase();
+                let found_list = found.starts_with('[') || found.starts_with("list") || found.starts_with("array");
+                return Some(ValueError::Expected(cap["expected"].to_string(), found_list));
+            }
+        }
+        None
+    }
+
+    /// Fields of input object `type_name`, probed with `{candidate: null, ...}` at `path`,
+    /// on top of the `known` ones
+    async fn probe_input_fields(
+        &mut self,
+        type_name: &str,
+        path: &ProbePath,
+        known: Vec<InferredArg>,
+        callback: Option<&dyn Fn(&InferenceEvent)>,
+    ) -> Result<Vec<InferredArg>> {
+        let mut candidates = WordPass::new(self.wordlist.clone());
+        candidates.extend(INPUT_WORDS.iter().chain(FIELD_WORDS).rev().map(|w| w.to_string()));
+        candidates.mark_tried(known.iter().map(|f| &f.name));
+        candidates.mark_tried(self.probed_words.get(&path.wrap("")).into_iter().flatten());
+        let mut pending: Vec<Vec<String>> = Vec::new();
+        let mut fields = known;
+        let mut any_attributed = false;
 
-                                // 3. Must NOT have selection (It's a Scalar, but we know it exists)
-                                // We need to re-query as a scalar to confirm, or trust the error.
-                                // If we sent `query { word }` and got "Must NOT have selection", 
-                                // it implies we sent a selection `word { ... }`.
-                                // Wait, `probe_root_type` sends `query { word }`.
-                                // If it's a scalar, `query { word }` is correct, and we should get DATA, not an error.
-                                // The "Must not have selection" error only happens if we send `query { word { sub } }`.
-                                // BUT: If we are here, we might have received a generic error or no data.
-                                // Let's check `probe_field` logic.
+        loop {
+            if self.budget_spent() {
+                self.remember_probed(path, &candidates, &pending);
+                break;
+            }
+            self.report(
+                callback,
+                candidates.tried_count(),
+                candidates.tried_count() + candidates.remaining(),
+                fields.len(),
+            );
+            let bucket = match pending.pop() {
+                Some(bucket) => bucket,
+                None => {
+                    let mut bucket = Vec::new();
+                    while bucket.len() < self.bucket_size {
+                        match candidates.next_word() {
+                            Some(word) if is_valid_graphql_name(&word) => {
+                                bucket.push(word)
                             }
+                            Some(_) => {}
+                            None => break,
                         }
                     }
+                    if bucket.is_empty() {
+                        self.remember_probed(path, &candidates, &pending);
+                        break;
+                    }
+                    self.words_sent += bucket.len();
+                    bucket
+                }
+            };
+
+            let values: Vec<String> = bucket
+                .iter()
+                .map(String::as_str)
+                .chain(std::iter::once(CANARY_FIELD))
+                .map(|w| format!("{}: null", w))
+                .collect();
+            let response = match self.send(&path.wrap(&format!("{{{}}}", values.join(", ")))).await {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+
+            let mut missing = HashSet::new();
+            let mut canary_named = false;
+            for msg in error_messages(&response) {
+                if let Some(cap) = self.engine.input_field_error.captures(&msg) {
+                    if !group_is(&cap, "type", type_name) {
+                        continue;
+                    }
+                    if &cap["field"] == CANARY_FIELD {
+                        canary_named = true;
+                    } else if bucket.iter().any(|w| w == &cap["field"]) {
+                        missing.insert(cap["field"].to_string());
+                    }
+                    if let Some(cap) = self.engine.suggestions.captures(&msg) {
+                        candidates.extend(self.quoted_word_regex.captures_iter(&cap["list"]).map(|m| m[1].to_string()));
+                    }
                 }
             }
 
-            if let Some(field) = found_field {
-                fields.push(field);
-                self.discovered_fields.insert(word.clone());
+            if !missing.is_empty() || canary_named {
+                any_attributed = true;
+            } else if !any_attributed {
+                return Ok(fields);
             }
 
-            // Extract suggestions from error messages
-            if let Some(errors) = response.get_errors() {
-                if let Some(arr) = errors.as_array() {
-                    for error in arr {
-                        if let Some(msg) = error.get("message").and_then(|m| m.as_str()) {
-                            // Extract "Did you mean X, Y, Z?"
-                            if let Some(cap) = self.suggestions_regex.captures(msg) {
-                                if let Some(suggestion_part) = cap.get(1) {
-                                    // suggestion_part is like: "user", "users" or "me"
-                                    for word_match in self.quoted_word_regex.captures_iter(suggestion_part.as_str()) {
-                                        if let Some(w) = word_match.get(1) {
-                                            let suggested = w.as_str().to_string();
-                                            if !checked_words.contains(&suggested) {
-                                                words_to_check.push(suggested);
-                                            }
-                                        }
-                                    }
-                                }
-                            }
+            let probed = bucket.len();
+            let unresolved: Vec<String> = bucket.into_iter().filter(|w| !missing.contains(w)).collect();
 
-                            // Extract type names from error messages
-                            for cap in self.field_error_regex.captures_iter(msg) {
-                                if let Some(type_name) = cap.get(2) {
-                                    let type_str = type_name.as_str().to_string();
-                                    if !self.discovered_types.contains_key(&type_str)
-                                        && !SCALAR_TYPES.contains(&type_str.as_str())
-                                    {
-                                        self.discovered_types.insert(
-                                            type_str.clone(),
-                                            InferredType {
-                                                name: type_str,
-                                                kind: "OBJECT".to_string(),
-                                                fields: Vec::new(),
-                                            },
-                                        );
-                                    }
-                                }
-                            }
-                        }
+            if canary_named {
+                // Everything before the canary was accepted
+                fields.extend(unresolved.into_iter().map(|name| InferredArg {
+                    name,
+                    type_name: None,
+                    is_non_null: false,
+                    is_list: false,
+                    confidence: Confidence::DerivedFromError,
+                }));
+            } else if unresolved.len() > 1 {
+                let half = unresolved.len() / 2;
+                pending.push(unresolved[half..].to_vec());
+                pending.push(unresolved[..half].to_vec());
+            } else if probed > 1 {
+                pending.extend(unresolved.into_iter().map(|w| vec![w]));
+            }
+        }
+
+        // Required fields left out are named, one per round on some servers
+        while let Some(values) = placeholder_values(&fields) {
+            let response = match self.send(&path.wrap(&format!("{{{}}}", values.join(", ")))).await {
+                Ok(r) => r,
+                Err(_) => break,
+            };
+
+            let mut found = false;
+            for msg in error_messages(&response) {
+                if let Some(cap) = self.engine.required_input_field.captures(&msg) {
+                    if !group_is(&cap, "type", type_name) {
+                        continue;
+                    }
+                    let required = required_arg(&cap["field"], group(&cap, "expected"));
+                    match fields.iter_mut().find(|f| f.name == required.name) {
+                        Some(f) if f.is_non_null => continue,
+                        Some(f) => *f = required,
+                        None => fields.push(required),
                     }
+                    found = true;
                 }
             }
+            if !found {
+                break;
+            }
         }
 
+        fields.sort_by(|a, b| a.name.cmp(&b.name));
         Ok(fields)
     }
 
+    async fn send(&mut self, query: &str) -> Result<GraphQLResponse> {
+        if let Some(interval) = self.min_interval {
+            if let Some(last) = self.last_request {
+                let wait = interval.saturating_sub(last.elapsed());
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait).await;
+                }
+            }
+            self.last_request = Some(Instant::now());
+        }
+        self.requests += 1;
+        if self.use_get {
+            self.client.get_graphql(&self.url, query, Some("inference")).await
+        } else {
+            self.client
+                .post_graphql(&self.url, query, None, Some("inference"))
+                .await
+        }
+    }
+
     async fn probe_field(&mut self, field_name: &str, operation: &str) -> Result<InferredField> {
         let mut field = InferredField {
             name: field_name.to_string(),
@@ -304,15 +2491,13 @@ impl SchemaInferrer {
             is_list: false,
             is_non_null: false,
             args: Vec::new(),
+            confidence: Confidence::DerivedFromError,
         };
 
         // Try to determine if it's a scalar or object type
         // by requesting a subfield
-        let query = format!("{} {{ {} {{ __typename }} }}", operation, field_name);
-        let response = self
-            .client
-            .post_graphql(&self.url, &query, None, Some("inference"))
-            .await?;
+        let query = ProbePath::root(operation).wrap(&format!("{} {{ __typename }}", field_name));
+        let response = self.send(&query).await?;
 
         if response.has_data() {
             if let Some(data) = response.get_data() {
@@ -339,10 +2524,13 @@ impl SchemaInferrer {
              if let Some(arr) = errors.as_array() {
                 for error in arr {
                     if let Some(msg) = error.get("message").and_then(|m| m.as_str()) {
-                         if let Some(cap) = self.must_not_have_selection_regex.captures(msg) {
-                            if let (Some(field_cap), Some(type_name)) = (cap.get(1), cap.get(2)) {
+                         if let Some(cap) = self.engine.must_not_have_selection.captures(msg) {
+                            if let (Some(field_cap), Some(type_name)) = (cap.name("field"), cap.name("type")) {
                                 if field_cap.as_str() == field_name {
-                                    field.type_name = Some(type_name.as_str().to_string());
+                                    let (type_name, is_list, is_non_null) = parse_type_string(type_name.as_str());
+                                    field.type_name = Some(type_name);
+                                    field.is_list = is_list;
+                                    field.is_non_null = is_non_null;
                                 }
                             }
                          }
@@ -353,11 +2541,8 @@ impl SchemaInferrer {
         
         // If we still don't know the type, try querying as scalar
         if field.type_name.is_none() {
-            let query = format!("{} {{ {} }}", operation, field_name);
-            let response = self
-                .client
-                .post_graphql(&self.url, &query, None, Some("inference"))
-                .await?;
+            let query = ProbePath::root(operation).wrap(field_name);
+            let response = self.send(&query).await?;
 
             if response.has_data() {
                 if let Some(data) = response.get_data() {
@@ -368,22 +2553,67 @@ impl SchemaInferrer {
                         }
                     }
                 }
+            } else {
+                // Null object with a required argument: the error still names the type. The
+                // guard's may come first.
+                for msg in error_messages(&response) {
+                    if let Some(cap) = self.engine.must_have_selection.captures(&msg) {
+                        if &cap["field"] == field_name {
+                            let (type_name, is_list, is_non_null) = parse_type_string(&cap["type"]);
+                            self.register_type(&type_name);
+                            field.type_name = Some(type_name);
+                            field.is_list = is_list;
+                            field.is_non_null = is_non_null;
+                        }
+                    }
+                }
             }
         }
 
         // Probe for arguments
-        field.args = self.probe_field_args(field_name, operation).await?;
+        let is_object = field
+            .type_name
+            .as_deref()
+            .is_some_and(|t| !SCALAR_TYPES.contains(&t));
+        field.args = self.probe_field_args(field_name, operation, is_object).await?;
+
+        // A missing required argument may be the only error reported; retry with it filled in
+        if field.type_name.is_none() && field.args.iter().any(|a| a.is_non_null) {
+            if let Some(arguments) = placeholder_arguments(&field.args) {
+                let query = ProbePath::root(operation).wrap(&format!("{}{}", field_name, arguments));
+                if let Ok(response) = self.send(&query).await {
+                    for msg in error_messages(&response) {
+                        if let Some(cap) = self.engine.must_have_selection.captures(&msg) {
+                            if &cap["field"] == field_name {
+                                let (type_name, is_list, is_non_null) = parse_type_string(&cap["type"]);
+                                self.register_type(&type_name);
+                                field.type_name = Some(type_name);
+                                field.is_list = is_list;
+                                field.is_non_null = is_non_null;
+                            }
+                        }
+                    }
+                }
+            }
+        }
 
         Ok(field)
     }
 
     async fn probe_field_args(
-        &self,
+        &mut self,
         field_name: &str,
         operation: &str,
+        is_object: bool,
     ) -> Result<Vec<InferredArg>> {
         let mut args = Vec::new();
         let mut checked_args = HashSet::new();
+        let selection = if is_object { " { __typename }" } else { "" };
+
+        // Every answer below relies on unknown arguments being named in errors
+        if !self.unknown_args_reported(field_name, operation, selection).await {
+            return Ok(args);
+        }
 
         // Common argument names to probe
         let mut common_args: Vec<String> = vec![
@@ -391,62 +2621,158 @@ impl SchemaInferrer {
             "after", "before", "orderBy", "order", "sort", "skip", "take", "page",
             "pageSize", "cursor", "data", "name", "email", "query", "search",
         ].into_iter().map(String::from).collect();
+        let mut pending: Vec<Vec<String>> = Vec::new();
 
-        while let Some(arg_name) = common_args.pop() {
-            if checked_args.contains(&arg_name) { continue; }
-            checked_args.insert(arg_name.clone());
-
-            let query = format!("{} {{ {}({}: null) }}", operation, field_name, arg_name);
-            let response = self
-                .client
-                .post_graphql(&self.url, &query, None, Some("inference"))
-                .await;
-
-            if let Ok(resp) = response {
-                if let Some(errors) = resp.get_errors() {
-                    if let Some(arr) = errors.as_array() {
-                        for error in arr {
-                            if let Some(msg) = error.get("message").and_then(|m| m.as_str()) {
-                                
-                                // Check for argument suggestions "Did you mean..."
-                                if let Some(cap) = self.suggestions_regex.captures(msg) {
-                                    if let Some(suggestion_part) = cap.get(1) {
-                                        for word_match in self.quoted_word_regex.captures_iter(suggestion_part.as_str()) {
-                                            if let Some(w) = word_match.get(1) {
-                                                let suggested = w.as_str().to_string();
-                                                if !checked_args.contains(&suggested) {
-                                                    common_args.push(suggested);
-                                                }
-                                            }
-                                        }
-                                    }
-                                }
-
-                                // If error is about type mismatch, not unknown arg, it exists
-                                let is_unknown = msg.to_lowercase().contains("unknown argument")
-                                    || msg.to_lowercase().contains("no argument");
-
-                                if !is_unknown
-                                    && (msg.contains(&arg_name)
-                                        || msg.contains("expected")
-                                        || msg.contains("type"))
-                                {
-                                    args.push(InferredArg {
-                                        name: arg_name.clone(),
-                                        type_name: extract_type_from_error(msg),
-                                    });
-                                    break;
-                                }
+        loop {
+            let bucket = match pending.pop() {
+                Some(bucket) => bucket,
+                None => {
+                    let mut bucket = Vec::new();
+                    while bucket.len() < self.bucket_size {
+                        match common_args.pop() {
+                            Some(arg) if is_valid_graphql_name(&arg) && checked_args.insert(arg.clone()) => {
+                                bucket.push(arg)
                             }
+                            Some(_) => {}
+                            None => break,
+                        }
+                    }
+                    if bucket.is_empty() {
+                        break;
+                    }
+                    bucket
+                }
+            };
+
+            let arguments: Vec<String> = bucket.iter().map(|a| format!("{}: null", a)).collect();
+            let query = ProbePath::root(operation)
+                .wrap(&format!("{}({}){}", field_name, arguments.join(", "), selection));
+            let response = match self.send(&query).await {
+                Ok(r) => r,
+                Err(_) => continue,
+            };
+
+            let mut messages = error_messages(&response);
+            messages.retain(|msg| !self.is_guard_error(msg));
+
+            let mut unknown = HashSet::new();
+            for msg in &messages {
+                if let Some(cap) = self.engine.arg_error.captures(msg) {
+                    unknown.insert(cap["arg"].to_string());
+
+                    // Check for argument suggestions "Did you mean..."
+                    if let Some(cap) = self.engine.suggestions.captures(msg) {
+                        for word_match in self.quoted_word_regex.captures_iter(&cap["list"]) {
+                            common_args.push(word_match[1].to_string());
                         }
                     }
                 }
+
+                // Required arguments we didn't guess name themselves
+                if let Some(cap) = self.engine.required_arg.captures(msg) {
+                    if group_is(&cap, "field", field_name) && !args.iter().any(|a: &InferredArg| a.name == cap["arg"]) {
+                        checked_args.insert(cap["arg"].to_string());
+                        args.push(required_arg(&cap["arg"], group(&cap, "type")));
+                    }
+                }
+            }
+
+            let probed = bucket.len();
+            let unresolved: Vec<String> = bucket.into_iter().filter(|a| !unknown.contains(a)).collect();
+
+            if probed > 1 && messages.len() == 1 && !unresolved.is_empty() {
+                // Possibly only the first error reported: bisect down to single arguments
+                let half = unresolved.len() / 2;
+                pending.push(unresolved[half..].to_vec());
+                if half > 0 {
+                    pending.push(unresolved[..half].to_vec());
+                }
+                continue;
+            }
+
+            // Types come later, from value probes
+            for name in unresolved {
+                if !args.iter().any(|a| a.name == name) {
+                    args.push(InferredArg {
+                        name,
+                        type_name: None,
+                        is_non_null: false,
+                        is_list: false,
+                        confidence: Confidence::DerivedFromError,
+                    });
+                }
+            }
+        }
+
+        // Nulls for non-null arguments aren't named in errors; leaving them out is. Known
+        // ones are filled in on each round for servers that report one error at a time.
+        while let Some(arguments) = placeholder_arguments(&args) {
+            let query = ProbePath::root(operation).wrap(&format!("{}{}{}", field_name, arguments, selection));
+            let response = match self.send(&query).await {
+                Ok(r) => r,
+                Err(_) => break,
+            };
+
+            let mut found = false;
+            for msg in error_messages(&response) {
+                if let Some(cap) = self.engine.required_arg.captures(&msg) {
+                    if !group_is(&cap, "field", field_name) {
+                        continue;
+                    }
+                    let required = required_arg(&cap["arg"], group(&cap, "type"));
+                    match args.iter_mut().find(|a| a.name == required.name) {
+                        Some(arg) if arg.is_non_null => continue,
+                        Some(arg) => *arg = required,
+                        None => args.push(required),
+                    }
+                    found = true;
+                }
+            }
+            if !found {
+                break;
             }
         }
 
         Ok(args)
     }
 
+    /// Whether the server names unknown arguments in its errors, checked once with a
+    /// made-up argument
+    async fn unknown_args_reported(&mut self, field_name: &str, operation: &str, selection: &str) -> bool {
+        if let Some(reported) = self.args_reported {
+            return reported;
+        }
+
+        let query = ProbePath::root(operation).wrap(&format!("{}({}: null){}", field_name, CANARY_ARG, selection));
+        let reported = match self.send(&query).await {
+            Ok(response) => response
+                .get_errors()
+                .and_then(|e| e.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+                .any(|msg| {
+                    self.engine
+                        .arg_error
+                        .captures(msg)
+                        .is_some_and(|cap| &cap["arg"] == CANARY_ARG)
+                }),
+            Err(_) => return false,
+        };
+
+        self.args_reported = Some(reported);
+        reported
+    }
+
+    /// Remember names the server suggested on `type_name`, for the validation pass to
+    /// try the ones probing didn't turn into fields
+    fn note_suggestions(&mut self, type_name: &str, names: &[String]) {
+        let names: Vec<String> = names.iter().filter(|n| is_valid_graphql_name(n)).cloned().collect();
+        if !names.is_empty() {
+            self.suggested.entry(type_name.to_string()).or_default().extend(names);
+        }
+    }
+
     fn register_type(&mut self, type_name: &str) {
         if !self.discovered_types.contains_key(type_name)
             && !SCALAR_TYPES.contains(&type_name)
@@ -458,12 +2784,37 @@ impl SchemaInferrer {
                     name: type_name.to_string(),
                     kind: "OBJECT".to_string(),
                     fields: Vec::new(),
+                    input_fields: Vec::new(),
+                    interfaces: Vec::new(),
+                    possible_types: Vec::new(),
                 },
             );
         }
     }
 
-    pub fn to_introspection_format(&self, schema: &InferredSchema) -> serde_json::Value {
+    /// Enums and custom scalars: no subfields, and we can't tell which of the two it is
+    fn register_leaf_type(&mut self, type_name: &str) {
+        if SCALAR_TYPES.contains(&type_name) {
+            return;
+        }
+        self.discovered_types
+            .entry(type_name.to_string())
+            .or_insert_with(|| InferredType {
+                name: type_name.to_string(),
+                kind: "SCALAR".to_string(),
+                fields: Vec::new(),
+                input_fields: Vec::new(),
+                interfaces: Vec::new(),
+                possible_types: Vec::new(),
+            })
+            .kind = "SCALAR".to_string();
+    }
+}
+
+impl InferredSchema {
+    /// In the shape of an introspection response, which every schema tool reads
+    pub fn to_introspection_format(&self) -> serde_json::Value {
+        let schema = self;
         let mut types = Vec::new();
 
         // Add scalar types
@@ -480,50 +2831,78 @@ impl SchemaInferrer {
             }));
         }
 
+        let kind_of = |type_name: Option<&str>| {
+            let type_name = type_name.unwrap_or("String");
+            let is_leaf = SCALAR_TYPES.contains(&type_name)
+                || schema.types.get(type_name).is_some_and(|t| t.kind == "SCALAR");
+            if is_leaf {
+                "SCALAR"
+            } else {
+                match schema.types.get(type_name).map(|t| t.kind.as_str()) {
+                    Some("INTERFACE") => "INTERFACE",
+                    Some("UNION") => "UNION",
+                    _ => "OBJECT",
+                }
+            }
+        };
+        let type_refs = |kind: &str, names: &[String]| -> Vec<serde_json::Value> {
+            names
+                .iter()
+                .map(|name| serde_json::json!({ "kind": kind, "name": name, "ofType": null }))
+                .collect()
+        };
+        // Arguments and input fields are input objects or leaves, never output objects
+        let input_value = |a: &InferredArg| {
+            let type_name = a.type_name.as_deref().unwrap_or("String");
+            let is_input = schema.types.get(type_name).is_some_and(|t| t.kind == "INPUT_OBJECT");
+            let named = serde_json::json!({
+                "kind": if is_input { "INPUT_OBJECT" } else { "SCALAR" },
+                "name": type_name,
+                "ofType": null
+            });
+            let type_ref = if a.is_list {
+                serde_json::json!({ "kind": "LIST", "name": null, "ofType": named })
+            } else {
+                named
+            };
+            serde_json::json!({
+                "name": a.name,
+                "description": confidence_description(a.confidence),
+                "type": if a.is_non_null { non_null(type_ref) } else { type_ref },
+                "defaultValue": null
+            })
+        };
+
         // Add discovered types
-        for (_, inferred_type) in &schema.types {
+        for inferred_type in schema.types.values() {
             let fields: Vec<serde_json::Value> = inferred_type
                 .fields
                 .iter()
                 .map(|f| {
-                    let args: Vec<serde_json::Value> = f
-                        .args
-                        .iter()
-                        .map(|a| {
-                            serde_json::json!({
-                                "name": a.name,
-                                "description": null,
-                                "type": {
-                                    "kind": "SCALAR",
-                                    "name": a.type_name.as_deref().unwrap_or("String"),
-                                    "ofType": null
-                                },
-                                "defaultValue": null
-                            })
-                        })
-                        .collect();
+                    let args: Vec<serde_json::Value> = f.args.iter().map(input_value).collect();
 
                     let type_ref = if f.is_list {
                         serde_json::json!({
                             "kind": "LIST",
                             "name": null,
                             "ofType": {
-                                "kind": if SCALAR_TYPES.contains(&f.type_name.as_deref().unwrap_or("")) { "SCALAR" } else { "OBJECT" },
+                                "kind": kind_of(f.type_name.as_deref()),
                                 "name": f.type_name.as_deref().unwrap_or("String"),
                                 "ofType": null
                             }
                         })
                     } else {
                         serde_json::json!({
-                            "kind": if SCALAR_TYPES.contains(&f.type_name.as_deref().unwrap_or("")) { "SCALAR" } else { "OBJECT" },
+                            "kind": kind_of(f.type_name.as_deref()),
                             "name": f.type_name.as_deref().unwrap_or("String"),
                             "ofType": null
                         })
                     };
+                    let type_ref = if f.is_non_null { non_null(type_ref) } else { type_ref };
 
                     serde_json::json!({
                         "name": f.name,
-                        "description": null,
+                        "description": confidence_description(f.confidence),
                         "args": args,
                         "type": type_ref,
                         "isDeprecated": false,
@@ -537,13 +2916,38 @@ impl SchemaInferrer {
                 "name": inferred_type.name,
                 "description": null,
                 "fields": if fields.is_empty() { serde_json::Value::Null } else { serde_json::json!(fields) },
-                "inputFields": null,
-                "interfaces": [],
+                "inputFields": if inferred_type.kind == "INPUT_OBJECT" {
+                    serde_json::json!(inferred_type.input_fields.iter().map(input_value).collect::<Vec<_>>())
+                } else {
+                    serde_json::Value::Null
+                },
+                "interfaces": if inferred_type.kind == "OBJECT" {
+                    serde_json::json!(type_refs("INTERFACE", &inferred_type.interfaces))
+                } else {
+                    serde_json::Value::Null
+                },
                 "enumValues": null,
-                "possibleTypes": null
+                "possibleTypes": if matches!(inferred_type.kind.as_str(), "INTERFACE" | "UNION") {
+                    serde_json::json!(type_refs("OBJECT", &inferred_type.possible_types))
+                } else {
+                    serde_json::Value::Null
+                }
             }));
         }
 
+        let directives: Vec<serde_json::Value> = schema
+            .directives
+            .iter()
+            .map(|d| {
+                serde_json::json!({
+                    "name": d.name,
+                    "description": null,
+                    "locations": d.locations,
+                    "args": d.args.iter().map(input_value).collect::<Vec<_>>()
+                })
+            })
+            .collect();
+
         serde_json::json!({
             "data": {
                 "__schema": {
@@ -551,10 +2955,202 @@ impl SchemaInferrer {
                     "mutationType": schema.mutation_type.as_ref().map(|t| serde_json::json!({"name": t.name})),
                     "subscriptionType": schema.subscription_type.as_ref().map(|t| serde_json::json!({"name": t.name})),
                     "types": types,
-                    "directives": []
+                    "directives": directives
+                }
+            }
+        })
+    }
+}
+
+/// Required arguments filled with placeholder literals, `None` when one can't be
+fn placeholder_arguments(args: &[InferredArg]) -> Option<String> {
+    let arguments = placeholder_values(args)?;
+    if arguments.is_empty() {
+        Some(String::new())
+    } else {
+        Some(format!("({})", arguments.join(", ")))
+    }
+}
+
+/// `name: literal` for each non-null argument or input field
+fn placeholder_values(args: &[InferredArg]) -> Option<Vec<String>> {
+    let mut values = Vec::new();
+    for arg in args.iter().filter(|a| a.is_non_null) {
+        let value = match arg.type_name.as_deref() {
+            Some("Int") => "1",
+            Some("Float") => "1.0",
+            Some("Boolean") => "true",
+            Some("ID") | Some("String") | None => "\"1\"",
+            Some(_) => return None,
+        };
+        values.push(format!("{}: {}", arg.name, value));
+    }
+    Some(values)
+}
+
+/// Fields and arguments carry their confidence in the description, where exporters
+/// and schema viewers show it
+fn confidence_description(confidence: Confidence) -> String {
+    format!("Inferred ({})", confidence.label())
+}
+
+fn sdl_implements(inferred_type: &InferredType) -> String {
+    if inferred_type.interfaces.is_empty() {
+        String::new()
+    } else {
+        format!(" implements {}", inferred_type.interfaces.join(" & "))
+    }
+}
+
+fn sdl_type(type_name: Option<&str>, is_list: bool, is_non_null: bool) -> String {
+    let mut type_string = type_name.unwrap_or("String").to_string();
+    if is_list {
+        type_string = format!("[{}]", type_string);
+    }
+    if is_non_null {
+        type_string.push('!');
+    }
+    type_string
+}
+
+/// SDL type of an argument or input field, and whether it is actually known
+fn sdl_arg_type(arg: &InferredArg) -> (String, bool) {
+    (
+        sdl_type(arg.type_name.as_deref(), arg.is_list, arg.is_non_null),
+        arg.type_name.is_some(),
+    )
+}
+
+fn root_name(operation: &str) -> &'static str {
+    match operation {
+        "mutation" => "Mutation",
+        "subscription" => "Subscription",
+        _ => "Query",
+    }
+}
+
+/// Named type, list and non-null of a seed schema type reference
+fn seed_type(type_ref: &TypeRef) -> (Option<String>, bool, bool) {
+    (
+        type_ref.get_base_type_name().map(String::from),
+        type_ref.is_list(),
+        type_ref.is_non_null(),
+    )
+}
+
+fn seed_input_value(value: &InputValue) -> InferredArg {
+    let (type_name, is_list, is_non_null) = seed_type(&value.input_type);
+    InferredArg {
+        name: value.name.clone(),
+        type_name,
+        is_non_null,
+        is_list,
+        confidence: Confidence::Confirmed,
+    }
+}
+
+/// Required with a type from a "not provided" error, which spells out the whole type
+fn is_composite(inferred_type: &InferredType) -> bool {
+    matches!(inferred_type.kind.as_str(), "OBJECT" | "INTERFACE" | "UNION")
+}
+
+/// Every `__typename` value in response data
+fn collect_typenames(value: &serde_json::Value, names: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map {
+                match value {
+                    serde_json::Value::String(name) if key == "__typename" => names.push(name.clone()),
+                    _ => collect_typenames(value, names),
                 }
             }
+        }
+        serde_json::Value::Array(items) => items.iter().for_each(|item| collect_typenames(item, names)),
+        _ => {}
+    }
+}
+
+fn is_fully_typed(arg: &InferredArg) -> bool {
+    arg.is_non_null && arg.type_name.is_some()
+}
+
+/// Nothing left to probe: a fully typed built-in scalar
+fn is_known_scalar(arg: &InferredArg) -> bool {
+    is_fully_typed(arg) && arg.type_name.as_deref().is_some_and(|t| SCALAR_TYPES.contains(&t))
+}
+
+/// Got past validation: data came back, or every error happened during execution
+/// (those carry a `path`, validation errors don't)
+fn passed_validation(response: &GraphQLResponse) -> bool {
+    match response.get_errors().and_then(|e| e.as_array()) {
+        Some(errors) if !errors.is_empty() => errors.iter().all(|e| e.get("path").is_some()),
+        _ => response.get_data().is_some_and(|d| !d.is_null()),
+    }
+}
+
+/// A body with `data` or `errors`, rather than a WAF page or a bare 405
+fn is_graphql_response(response: &GraphQLResponse) -> bool {
+    response.has_data() || response.has_errors()
+}
+
+/// Messages of the errors about `value` at `path`, leaving out the ones located past it
+/// like the guard's. Some servers locate value errors at the argument, before the value.
+/// Probes are one line, so the column is enough; errors without a location are kept.
+fn value_messages(response: &GraphQLResponse, path: &ProbePath, value: &str) -> Vec<String> {
+    let end = path.prefix.chars().count() + 1 + value.chars().count();
+    response
+        .get_errors()
+        .and_then(|e| e.as_array())
+        .into_iter()
+        .flatten()
+        .filter(|e| {
+            e.get("locations")
+                .and_then(|l| l.get(0))
+                .and_then(|l| l.get("column"))
+                .and_then(|c| c.as_u64())
+                .is_none_or(|column| (column as usize) < end)
+        })
+        .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+        .map(String::from)
+        .collect()
+}
+
+fn error_messages(response: &GraphQLResponse) -> Vec<String> {
+    response
+        .get_errors()
+        .and_then(|e| e.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+                .map(String::from)
+                .collect()
         })
+        .unwrap_or_default()
+}
+
+fn non_null(of_type: serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "kind": "NON_NULL",
+        "name": null,
+        "ofType": of_type
+    })
+}
+
+/// `[User!]!` -> (`User`, list, non-null)
+fn parse_type_string(type_string: &str) -> (String, bool, bool) {
+    let name = type_string.trim_matches(|c| c == '[' || c == ']' || c == '!').to_string();
+    (name, type_string.contains('['), type_string.ends_with('!'))
+}
+
+/// A required argument or input field; engines that don't name the type leave it empty
+fn required_arg(name: &str, type_string: &str) -> InferredArg {
+    let (type_name, is_list, _) = parse_type_string(type_string);
+    InferredArg {
+        name: name.to_string(),
+        type_name: Some(type_name).filter(|t| !t.is_empty()),
+        is_non_null: true,
+        is_list,
+        confidence: Confidence::DerivedFromError,
     }
 }
 
@@ -594,22 +3190,9 @@ fn infer_scalar_type(value: &serde_json::Value) -> String {
     }
 }
 
-fn extract_type_from_error(msg: &str) -> Option<String> {
-    // Try to extract type from error messages like "expected type X"
-    let patterns = [
-        Regex::new(r#"expected type ["\']?(\w+)["\']?"#).ok()?,
-        Regex::new(r#"type ["\']?(\w+)["\']?"#).ok()?,
-    ];
-
-    for pattern in patterns {
-        if let Some(cap) = pattern.captures(msg) {
-            if let Some(m) = cap.get(1) {
-                return Some(m.as_str().to_string());
-            }
-        }
-    }
-
-    None
+pub fn load_state(path: &Path) -> Result<InferenceState> {
+    let content = std::fs::read_to_string(path).context("Failed to read state file")?;
+    serde_json::from_str(&content).context("Failed to parse state file")
 }
 
 pub fn load_wordlist(path: &str) -> Result<Vec<String>> {
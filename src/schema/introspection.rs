@@ -1,7 +1,10 @@
-use crate::http::HttpClient;
-use anyhow::{Context, Result};
+use super::{looks_like_sdl, parse_sdl};
+use crate::http::{log_at, HttpClient};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
+use std::path::Path;
+use std::sync::Arc;
 
 pub const FULL_INTROSPECTION_QUERY: &str = r#"
 query IntrospectionQuery {
@@ -239,27 +242,254 @@ impl TypeRef {
     }
 }
 
+/// Types asked for per request when introspecting one `__type` at a time
+const TYPES_PER_QUERY: usize = 10;
+
+/// The full introspection, or when the server refuses it (depth limits, arguments it
+/// won't take, a response too big), smaller and smaller versions of it: without
+/// descriptions and `includeDeprecated`, with shallower type references, and last
+/// the type names first and then every type through `__type`, stitched back together
 pub async fn fetch_schema(client: &HttpClient, url: &str) -> Result<Schema> {
+    let error = match introspect(client, url, FULL_INTROSPECTION_QUERY).await {
+        Ok(schema) => return Ok(schema),
+        Err(e) => e,
+    };
+    match fetch_schema_reduced(client, url).await {
+        Some(schema) => Ok(schema),
+        None => Err(error),
+    }
+}
+
+async fn introspect(client: &HttpClient, url: &str, query: &str) -> Result<Schema> {
     let response = client
-        .post_graphql(url, FULL_INTROSPECTION_QUERY, None, Some("introspection"))
+        .post_graphql(url, query, None, Some("introspection"))
         .await
         .context("Failed to fetch introspection")?;
 
-    let data = response
+    response
         .get_data()
         .context("No data in introspection response")?;
 
-    let schema: Schema = serde_json::from_value(data.clone())
-        .context("Failed to parse introspection response")?;
+    parse_schema_str(&response.text).context("Failed to parse introspection response")
+}
+
+/// The fallbacks of [`fetch_schema`], after the full query failed
+async fn fetch_schema_reduced(client: &HttpClient, url: &str) -> Option<Schema> {
+    for (label, depth) in [("no descriptions or deprecated members", 7), ("shallow type references", 3)] {
+        log_at(1, &format!("Introspection on {} failed, retrying with {}", url, label));
+        if let Ok(schema) = introspect(client, url, &introspection_query(depth)).await {
+            return Some(schema);
+        }
+    }
+    log_at(1, &format!("Introspection on {} failed, retrying type by type", url));
+    match introspect_per_type(client, url).await {
+        Ok(schema) => Some(schema),
+        Err(e) => {
+            log_at(1, &format!("Type by type introspection on {} failed: {:#}", url, e));
+            None
+        }
+    }
+}
+
+/// [`FULL_INTROSPECTION_QUERY`] without descriptions or `includeDeprecated`, following
+/// `ofType` `depth` levels deep
+fn introspection_query(depth: usize) -> String {
+    format!(
+        "query IntrospectionQuery {{ __schema {{ queryType {{ name }} mutationType {{ name }} \
+         subscriptionType {{ name }} types {{ ...FullType }} directives {{ name locations args {{ ...InputValue }} }} }} }} {}",
+        type_fragments(depth)
+    )
+}
+
+/// `FullType`, `InputValue` and `TypeRef` fragments for [`introspection_query`]
+fn type_fragments(depth: usize) -> String {
+    format!(
+        "fragment FullType on __Type {{ kind name \
+         fields {{ name args {{ ...InputValue }} type {{ ...TypeRef }} isDeprecated deprecationReason }} \
+         inputFields {{ ...InputValue }} interfaces {{ ...TypeRef }} \
+         enumValues {{ name isDeprecated deprecationReason }} possibleTypes {{ ...TypeRef }} }} {}",
+        input_value_fragments(depth)
+    )
+}
+
+/// `InputValue` and the `TypeRef` it uses, without `FullType` (servers reject
+/// documents with fragments they don't use)
+fn input_value_fragments(depth: usize) -> String {
+    let mut type_ref = "kind name".to_string();
+    for _ in 0..depth {
+        type_ref = format!("kind name ofType {{ {} }}", type_ref);
+    }
+    format!(
+        "fragment InputValue on __InputValue {{ name type {{ ...TypeRef }} defaultValue }} \
+         fragment TypeRef on __Type {{ {} }}",
+        type_ref
+    )
+}
+
+/// Root types, type names and directives from `__schema`, then the types themselves a
+/// few at a time through aliased `__type` fields, one at a time for a batch that fails
+async fn introspect_per_type(client: &HttpClient, url: &str) -> Result<Schema> {
+    let query = format!(
+        "query {{ __schema {{ queryType {{ name }} mutationType {{ name }} subscriptionType {{ name }} \
+         types {{ name }} directives {{ name locations args {{ ...InputValue }} }} }} }} {}",
+        input_value_fragments(3)
+    );
+    let response = client
+        .post_graphql(url, &query, None, Some("introspection"))
+        .await
+        .context("Failed to fetch type names")?;
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Roots {
+        query_type: Option<TypeName>,
+        mutation_type: Option<TypeName>,
+        subscription_type: Option<TypeName>,
+        types: Vec<NamedType>,
+        directives: Vec<Directive>,
+    }
+    #[derive(Deserialize)]
+    struct NamedType {
+        name: Option<String>,
+    }
+
+    let roots = response
+        .get_data()
+        .and_then(|d| d.get("__schema"))
+        .context("No __schema in the type name response")?;
+    let roots: Roots = serde_json::from_value(roots.clone()).context("Failed to parse type names")?;
+    let names: Vec<String> = roots.types.into_iter().filter_map(|t| t.name).collect();
+    let fragments = type_fragments(3);
+
+    let mut types = Vec::with_capacity(names.len());
+    for batch in names.chunks(TYPES_PER_QUERY) {
+        match fetch_types(client, url, batch, &fragments).await {
+            Ok(fetched) => types.extend(fetched),
+            Err(_) => {
+                for name in batch {
+                    match fetch_types(client, url, std::slice::from_ref(name), &fragments).await {
+                        Ok(fetched) => types.extend(fetched),
+                        Err(e) => log_at(1, &format!("Introspecting {} on {} failed: {:#}", name, url, e)),
+                    }
+                }
+            }
+        }
+    }
+    if types.is_empty() {
+        bail!("No type could be introspected");
+    }
+
+    Ok(Schema {
+        schema: SchemaInner {
+            query_type: roots.query_type,
+            mutation_type: roots.mutation_type,
+            subscription_type: roots.subscription_type,
+            types,
+            directives: roots.directives,
+        },
+    })
+}
 
+/// `names` through one `__type` alias each
+async fn fetch_types(client: &HttpClient, url: &str, names: &[String], fragments: &str) -> Result<Vec<FullType>> {
+    let fields: Vec<String> = names
+        .iter()
+        .enumerate()
+        .map(|(i, name)| format!("t{}: __type(name: \"{}\") {{ ...FullType }}", i, name))
+        .collect();
+    let query = format!("query {{ {} }} {}", fields.join(" "), fragments);
+    let response = client
+        .post_graphql(url, &query, None, Some("introspection"))
+        .await
+        .context("Failed to fetch types")?;
+    let data = response.get_data().context("No data in __type response")?;
+
+    let mut types = Vec::with_capacity(names.len());
+    for (i, name) in names.iter().enumerate() {
+        let full_type = data
+            .get(format!("t{}", i))
+            .filter(|t| !t.is_null())
+            .with_context(|| format!("No {} in __type response", name))?;
+        types.push(serde_json::from_value(full_type.clone()).context("Failed to parse __type response")?);
+    }
+    Ok(types)
+}
+
+/// [`fetch_schema`] once per URL for `client` and its clones; later calls share the
+/// schema parsed the first time instead of introspecting again
+pub async fn fetch_schema_cached(client: &HttpClient, url: &str) -> Result<Arc<Schema>> {
+    if let Some(schema) = client.cached_schema(url) {
+        return Ok(schema);
+    }
+    let schema = Arc::new(fetch_schema(client, url).await?);
+    client.cache_schema(url, schema.clone());
     Ok(schema)
 }
 
+/// The reply to the full introspection query as it came, or when that has no schema
+/// in it, a reduced introspection (see [`fetch_schema`]) as `{"data": ...}`. When
+/// everything fails, the full query's reply with its errors.
 pub async fn fetch_schema_raw(client: &HttpClient, url: &str) -> Result<Value> {
     let response = client
         .post_graphql(url, FULL_INTROSPECTION_QUERY, None, Some("introspection"))
         .await
         .context("Failed to fetch introspection")?;
 
-    Ok(response.body)
+    let has_schema = response
+        .get_data()
+        .and_then(|d| d.get("__schema"))
+        .is_some_and(|s| s.is_object());
+    if has_schema {
+        return Ok(response.body);
+    }
+    match fetch_schema_reduced(client, url).await {
+        Some(schema) => Ok(serde_json::json!({ "data": schema })),
+        None => Ok(response.body),
+    }
+}
+
+/// Parse an introspection result, with or without the `{"data": ...}` envelope
+pub fn parse_schema_json(value: Value) -> Result<Schema> {
+    let schema_data = match value.get("data") {
+        Some(data) => data.clone(),
+        None => value,
+    };
+
+    serde_json::from_value(schema_data).context("Failed to parse introspection schema")
+}
+
+/// Parse introspection JSON text, with or without the `{"data": ...}` envelope, in
+/// one pass straight into a [`Schema`]. Much cheaper on multi-megabyte schemas than
+/// going through a `Value` first.
+pub fn parse_schema_str(content: &str) -> Result<Schema> {
+    #[derive(Deserialize)]
+    struct Document {
+        data: Option<Schema>,
+        #[serde(rename = "__schema")]
+        schema: Option<SchemaInner>,
+    }
+
+    let document: Document = serde_json::from_str(content).context("Failed to parse introspection schema")?;
+    match (document.data, document.schema) {
+        (Some(schema), _) => Ok(schema),
+        (None, Some(schema)) => Ok(Schema { schema }),
+        (None, None) => bail!("No __schema in introspection JSON"),
+    }
+}
+
+/// Load a schema from introspection JSON or an SDL file (`.graphql`, `.gql`, `.graphqls`)
+pub fn load_schema_file(path: &Path) -> Result<Schema> {
+    let content = std::fs::read_to_string(path).context("Failed to read schema file")?;
+
+    let is_sdl = match path.extension().and_then(|e| e.to_str()) {
+        Some("graphql" | "gql" | "graphqls" | "sdl") => true,
+        Some("json") => false,
+        _ => looks_like_sdl(&content),
+    };
+    if is_sdl {
+        return parse_sdl(&content)
+            .with_context(|| format!("Failed to parse SDL schema {}", path.display()));
+    }
+
+    parse_schema_str(&content)
 }
@@ -0,0 +1,219 @@
+use super::generate::PAGE_ARGS;
+use super::{classify_name, DataCategory, FullType, Schema};
+use crate::tests::Severity;
+use serde::Serialize;
+
+/// A lint rule: stable id, short name and what it checks
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct LintRule {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+}
+
+pub const LINT_RULES: &[LintRule] = &[
+    LintRule {
+        id: "GQL001",
+        name: "mutation-returns-scalar",
+        description: "Mutation returns a raw scalar instead of a payload type, hiding what changed and making errors hard to report",
+    },
+    LintRule {
+        id: "GQL002",
+        name: "unbounded-list",
+        description: "List of objects without pagination arguments; the server decides how much a single request can pull",
+    },
+    LintRule {
+        id: "GQL003",
+        name: "undocumented-auth-field",
+        description: "Credential, secret or authorization field without a description of who may read or set it",
+    },
+    LintRule {
+        id: "GQL004",
+        name: "input-mixes-id-and-privilege",
+        description: "Input object takes an object ID alongside privilege flags, a common mass-assignment shape",
+    },
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LintWarning {
+    pub rule: &'static str,
+    pub name: &'static str,
+    pub severity: Severity,
+    /// Schema coordinate, e.g. `Mutation.deleteUser` or `User.password`
+    pub path: String,
+    pub message: String,
+}
+
+/// Check a schema against the best-practice rules, skipping any rule id in `exclude`
+pub fn lint_schema(schema: &Schema, exclude: &[String]) -> Vec<LintWarning> {
+    let enabled = |id: &str| !exclude.iter().any(|e| e.eq_ignore_ascii_case(id));
+    let mut warnings = Vec::new();
+
+    let mutation_root = schema
+        .schema
+        .mutation_type
+        .as_ref()
+        .map(|t| t.name.as_str());
+
+    for full_type in schema.get_user_types() {
+        let type_name = full_type.name.as_deref().unwrap_or_default();
+        let is_mutation = Some(type_name) == mutation_root;
+
+        if is_mutation && enabled("GQL001") {
+            mutation_scalars(schema, full_type, &mut warnings);
+        }
+        if !is_mutation && enabled("GQL002") {
+            unbounded_lists(schema, full_type, &mut warnings);
+        }
+        if enabled("GQL003") {
+            undocumented_auth(full_type, &mut warnings);
+        }
+        if full_type.kind == "INPUT_OBJECT" && enabled("GQL004") {
+            mixed_input(full_type, &mut warnings);
+        }
+    }
+
+    warnings.sort_by(|a, b| {
+        severity_rank(a.severity)
+            .cmp(&severity_rank(b.severity))
+            .then_with(|| a.rule.cmp(b.rule))
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    warnings
+}
+
+fn warning(index: usize, severity: Severity, path: String, message: String) -> LintWarning {
+    let rule = &LINT_RULES[index];
+    LintWarning {
+        rule: rule.id,
+        name: rule.name,
+        severity,
+        path,
+        message,
+    }
+}
+
+fn mutation_scalars(schema: &Schema, mutation: &FullType, warnings: &mut Vec<LintWarning>) {
+    for field in mutation.fields.iter().flatten() {
+        let base = match field.field_type.get_base_type_name() {
+            Some(b) => b,
+            None => continue,
+        };
+        let is_leaf = schema
+            .get_type(base)
+            .is_some_and(|t| t.kind == "SCALAR" || t.kind == "ENUM");
+        if is_leaf {
+            warnings.push(warning(
+                0,
+                Severity::Low,
+                format!("{}.{}", mutation.name.as_deref().unwrap_or_default(), field.name),
+                format!("returns {} instead of a payload type", base),
+            ));
+        }
+    }
+}
+
+fn unbounded_lists(schema: &Schema, full_type: &FullType, warnings: &mut Vec<LintWarning>) {
+    for field in full_type.fields.iter().flatten() {
+        if !field.field_type.is_list() {
+            continue;
+        }
+        let base = match field.field_type.get_base_type_name() {
+            Some(b) => b,
+            None => continue,
+        };
+        let is_composite = schema
+            .get_type(base)
+            .is_some_and(|t| matches!(t.kind.as_str(), "OBJECT" | "INTERFACE" | "UNION"));
+        let paginated = field.args.iter().any(|a| PAGE_ARGS.contains(&a.name.as_str()));
+
+        if is_composite && !paginated {
+            warnings.push(warning(
+                1,
+                Severity::Medium,
+                format!("{}.{}", full_type.name.as_deref().unwrap_or_default(), field.name),
+                format!("returns [{}] with no way to limit the result size", base),
+            ));
+        }
+    }
+}
+
+fn undocumented_auth(full_type: &FullType, warnings: &mut Vec<LintWarning>) {
+    let type_name = full_type.name.as_deref().unwrap_or_default();
+    let undocumented = |description: &Option<String>| description.as_deref().is_none_or(|d| d.trim().is_empty());
+
+    let mut check = |name: &str, path: String, description: &Option<String>| {
+        let category = match classify_name(name) {
+            Some(c) => c,
+            None => return,
+        };
+        let auth_relevant = matches!(
+            category,
+            DataCategory::Credential | DataCategory::Secret | DataCategory::Authorization
+        );
+        if auth_relevant && undocumented(description) {
+            warnings.push(warning(
+                2,
+                Severity::Low,
+                path,
+                format!("{} value with no description", category),
+            ));
+        }
+    };
+
+    for field in full_type.fields.iter().flatten() {
+        let path = format!("{}.{}", type_name, field.name);
+        check(&field.name, path.clone(), &field.description);
+        for arg in &field.args {
+            check(&arg.name, format!("{}({}:)", path, arg.name), &arg.description);
+        }
+    }
+    for input in full_type.input_fields.iter().flatten() {
+        check(&input.name, format!("{}.{}", type_name, input.name), &input.description);
+    }
+}
+
+fn mixed_input(full_type: &FullType, warnings: &mut Vec<LintWarning>) {
+    let inputs = match &full_type.input_fields {
+        Some(i) => i,
+        None => return,
+    };
+
+    let ids: Vec<&str> = inputs
+        .iter()
+        .filter(|i| {
+            i.input_type.get_base_type_name() == Some("ID")
+                || i.name == "id"
+                || i.name.ends_with("Id")
+                || i.name.ends_with("_id")
+        })
+        .map(|i| i.name.as_str())
+        .collect();
+    let privileges: Vec<&str> = inputs
+        .iter()
+        .filter(|i| classify_name(&i.name) == Some(DataCategory::Authorization))
+        .map(|i| i.name.as_str())
+        .collect();
+
+    if !ids.is_empty() && !privileges.is_empty() {
+        warnings.push(warning(
+            3,
+            Severity::Medium,
+            full_type.name.clone().unwrap_or_default(),
+            format!(
+                "takes {} together with privilege fields {}",
+                ids.join(", "),
+                privileges.join(", ")
+            ),
+        ));
+    }
+}
+
+fn severity_rank(severity: Severity) -> u8 {
+    match severity {
+        Severity::High => 0,
+        Severity::Medium => 1,
+        Severity::Low => 2,
+        Severity::Info => 3,
+    }
+}
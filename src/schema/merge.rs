@@ -0,0 +1,402 @@
+use super::sdl::resolve_kind;
+use super::{Directive, Field, FullType, InputValue, Schema, SchemaInner, TypeRef};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// Two sources disagreed; the earlier (higher priority) source won
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeConflict {
+    pub path: String,
+    pub kept: String,
+    pub kept_from: String,
+    pub dropped: String,
+    pub dropped_from: String,
+}
+
+/// What each source added on top of the ones before it
+#[derive(Debug, Clone, Serialize)]
+pub struct MergeContribution {
+    pub source: String,
+    pub types: usize,
+    pub fields: usize,
+}
+
+#[derive(Debug)]
+pub struct MergeResult {
+    pub schema: Schema,
+    pub conflicts: Vec<MergeConflict>,
+    pub contributions: Vec<MergeContribution>,
+}
+
+/// Combine schemas from several sources (partial introspection, `infer` output, SDL
+/// fragments) into one best-known schema.
+///
+/// Sources are given most trusted first. Anything only one source knows about is kept.
+/// When two sources describe the same field differently, a type that only adds
+/// wrappers (`User` vs `User!`) is treated as more precise rather than conflicting,
+/// since inference can't see non-null markers; otherwise the earlier source wins.
+pub fn merge_schemas(sources: Vec<(String, Schema)>) -> MergeResult {
+    let mut merger = Merger::default();
+    let mut merged: Option<SchemaInner> = None;
+
+    for (label, schema) in sources {
+        let inner = schema.schema;
+        let base = match merged.as_mut() {
+            Some(base) => base,
+            None => {
+                let fields = inner
+                    .types
+                    .iter()
+                    .filter(|t| !is_introspection_type(t))
+                    .map(|t| t.fields.as_ref().map_or(0, |f| f.len()))
+                    .sum();
+                merger.contributions.push(MergeContribution {
+                    source: label.clone(),
+                    types: inner.types.iter().filter(|t| is_user_type(t)).count(),
+                    fields,
+                });
+                merger.origins.insert(String::new(), label);
+                merged = Some(inner);
+                continue;
+            }
+        };
+
+        let mut contribution = MergeContribution {
+            source: label.clone(),
+            types: 0,
+            fields: 0,
+        };
+
+        merger.root(&mut base.query_type, inner.query_type, "schema.query", &label);
+        merger.root(&mut base.mutation_type, inner.mutation_type, "schema.mutation", &label);
+        merger.root(
+            &mut base.subscription_type,
+            inner.subscription_type,
+            "schema.subscription",
+            &label,
+        );
+
+        for incoming in inner.types {
+            if is_introspection_type(&incoming) {
+                continue;
+            }
+            match base.types.iter_mut().find(|t| t.name == incoming.name) {
+                Some(existing) => {
+                    contribution.fields += merger.full_type(existing, incoming, &label);
+                }
+                None => {
+                    if is_user_type(&incoming) {
+                        contribution.types += 1;
+                    }
+                    contribution.fields += incoming.fields.as_ref().map_or(0, |f| f.len());
+                    merger
+                        .origins
+                        .insert(incoming.name.clone().unwrap_or_default(), label.clone());
+                    base.types.push(incoming);
+                }
+            }
+        }
+
+        for directive in inner.directives {
+            merger.directive(&mut base.directives, directive, &label);
+        }
+
+        merger.contributions.push(contribution);
+    }
+
+    let mut inner = merged.unwrap_or(SchemaInner {
+        query_type: None,
+        mutation_type: None,
+        subscription_type: None,
+        types: Vec::new(),
+        directives: Vec::new(),
+    });
+
+    // Inference guesses OBJECT for every non-scalar; use what the merged schema knows
+    let kinds: HashMap<String, String> = inner
+        .types
+        .iter()
+        .filter_map(|t| Some((t.name.clone()?, t.kind.clone())))
+        .collect();
+    for t in &mut inner.types {
+        for field in t.fields.iter_mut().flatten() {
+            resolve_kind(&mut field.field_type, &kinds);
+            for arg in &mut field.args {
+                resolve_kind(&mut arg.input_type, &kinds);
+            }
+        }
+        for input in t.input_fields.iter_mut().flatten() {
+            resolve_kind(&mut input.input_type, &kinds);
+        }
+    }
+
+    MergeResult {
+        schema: Schema { schema: inner },
+        conflicts: merger.conflicts,
+        contributions: merger.contributions,
+    }
+}
+
+#[derive(Default)]
+struct Merger {
+    conflicts: Vec<MergeConflict>,
+    contributions: Vec<MergeContribution>,
+    /// Which source first defined each type, for conflict messages
+    origins: HashMap<String, String>,
+}
+
+impl Merger {
+    fn origin(&self, type_name: &str) -> String {
+        self.origins
+            .get(type_name)
+            .or_else(|| self.origins.get(""))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    fn conflict(&mut self, path: String, kept: String, dropped: String, owner: &str, source: &str) {
+        self.conflicts.push(MergeConflict {
+            path,
+            kept,
+            kept_from: self.origin(owner),
+            dropped,
+            dropped_from: source.to_string(),
+        });
+    }
+
+    fn root(
+        &mut self,
+        existing: &mut Option<super::TypeName>,
+        incoming: Option<super::TypeName>,
+        path: &str,
+        source: &str,
+    ) {
+        match (existing.as_ref(), incoming) {
+            (None, Some(incoming)) => *existing = Some(incoming),
+            (Some(current), Some(incoming)) if current.name != incoming.name => {
+                let kept = current.name.clone();
+                self.conflict(path.to_string(), kept, incoming.name, "", source);
+            }
+            _ => {}
+        }
+    }
+
+    /// Merge `incoming` into `existing`, returning how many fields were new
+    fn full_type(&mut self, existing: &mut FullType, incoming: FullType, source: &str) -> usize {
+        let type_name = existing.name.clone().unwrap_or_default();
+
+        if existing.kind != incoming.kind {
+            let kept = existing.kind.clone();
+            self.conflict(type_name.clone(), kept, incoming.kind, &type_name, source);
+            return 0;
+        }
+
+        if existing.description.is_none() {
+            existing.description = incoming.description;
+        }
+
+        let mut added = 0;
+        if let Some(fields) = incoming.fields {
+            let current = existing.fields.get_or_insert_with(Vec::new);
+            for field in fields {
+                match current.iter_mut().find(|f| f.name == field.name) {
+                    Some(slot) => {
+                        let path = format!("{}.{}", type_name, field.name);
+                        let (conflict, args) = merge_field(slot, field);
+                        if let Some((kept, dropped)) = conflict {
+                            self.conflict(path.clone(), kept, dropped, &type_name, source);
+                        }
+                        for (arg, kept, dropped) in args {
+                            self.conflict(format!("{}({}:)", path, arg), kept, dropped, &type_name, source);
+                        }
+                    }
+                    None => {
+                        added += 1;
+                        current.push(field);
+                    }
+                }
+            }
+        }
+
+        if let Some(inputs) = incoming.input_fields {
+            let current = existing.input_fields.get_or_insert_with(Vec::new);
+            let conflicts = merge_input_values(current, inputs);
+            for (name, kept, dropped) in conflicts {
+                self.conflict(format!("{}.{}", type_name, name), kept, dropped, &type_name, source);
+            }
+        }
+
+        if let Some(values) = incoming.enum_values {
+            let current = existing.enum_values.get_or_insert_with(Vec::new);
+            for value in values {
+                if !current.iter().any(|v| v.name == value.name) {
+                    current.push(value);
+                }
+            }
+        }
+
+        for (current, incoming) in [
+            (&mut existing.interfaces, incoming.interfaces),
+            (&mut existing.possible_types, incoming.possible_types),
+        ] {
+            if let Some(refs) = incoming {
+                let current = current.get_or_insert_with(Vec::new);
+                for type_ref in refs {
+                    if !current.iter().any(|t| t.name == type_ref.name) {
+                        current.push(type_ref);
+                    }
+                }
+            }
+        }
+
+        added
+    }
+
+    fn directive(&mut self, existing: &mut Vec<Directive>, incoming: Directive, source: &str) {
+        let current = match existing.iter_mut().find(|d| d.name == incoming.name) {
+            Some(d) => d,
+            None => {
+                existing.push(incoming);
+                return;
+            }
+        };
+
+        for location in incoming.locations {
+            if !current.locations.contains(&location) {
+                current.locations.push(location);
+            }
+        }
+
+        let path = format!("@{}", current.name);
+        for (arg, kept, dropped) in merge_input_values(&mut current.args, incoming.args) {
+            self.conflict(format!("{}({}:)", path, arg), kept, dropped, "", source);
+        }
+    }
+}
+
+type ArgConflict = (String, String, String);
+
+/// Returns a (kept, dropped) type conflict for the field itself plus any argument conflicts
+fn merge_field(existing: &mut Field, incoming: Field) -> (Option<(String, String)>, Vec<ArgConflict>) {
+    let conflict = match pick_type(&existing.field_type, &incoming.field_type) {
+        Pick::Same | Pick::Existing => None,
+        Pick::Incoming => {
+            existing.field_type = incoming.field_type;
+            None
+        }
+        Pick::Conflict => Some((
+            type_string(&existing.field_type),
+            type_string(&incoming.field_type),
+        )),
+    };
+
+    if existing.description.is_none() {
+        existing.description = incoming.description;
+    }
+    if incoming.is_deprecated && !existing.is_deprecated {
+        existing.is_deprecated = true;
+        existing.deprecation_reason = incoming.deprecation_reason;
+    }
+
+    let args = merge_input_values(&mut existing.args, incoming.args);
+    (conflict, args)
+}
+
+fn merge_input_values(existing: &mut Vec<InputValue>, incoming: Vec<InputValue>) -> Vec<ArgConflict> {
+    let mut conflicts = Vec::new();
+
+    for value in incoming {
+        let slot = match existing.iter_mut().find(|v| v.name == value.name) {
+            Some(slot) => slot,
+            None => {
+                existing.push(value);
+                continue;
+            }
+        };
+
+        match pick_type(&slot.input_type, &value.input_type) {
+            Pick::Same | Pick::Existing => {}
+            Pick::Incoming => slot.input_type = value.input_type.clone(),
+            Pick::Conflict => conflicts.push((
+                value.name.clone(),
+                type_string(&slot.input_type),
+                type_string(&value.input_type),
+            )),
+        }
+        if slot.description.is_none() {
+            slot.description = value.description;
+        }
+        if slot.default_value.is_none() {
+            slot.default_value = value.default_value;
+        }
+    }
+
+    conflicts
+}
+
+enum Pick {
+    Same,
+    Existing,
+    Incoming,
+    Conflict,
+}
+
+fn pick_type(existing: &TypeRef, incoming: &TypeRef) -> Pick {
+    if type_string(existing) == type_string(incoming) {
+        return Pick::Same;
+    }
+    if existing.get_base_type_name() != incoming.get_base_type_name() {
+        return Pick::Conflict;
+    }
+
+    // Same named type: whichever has more wrappers saw more of the real signature
+    let (a, b) = (wrapper_count(existing), wrapper_count(incoming));
+    if b > a && strip_non_null(incoming) == strip_non_null(existing) {
+        Pick::Incoming
+    } else if a > b && strip_non_null(existing) == strip_non_null(incoming) {
+        Pick::Existing
+    } else if b > a && !existing.is_list() {
+        // Inference sometimes misses list wrappers entirely
+        Pick::Incoming
+    } else if a > b && !incoming.is_list() {
+        Pick::Existing
+    } else {
+        Pick::Conflict
+    }
+}
+
+fn wrapper_count(type_ref: &TypeRef) -> usize {
+    match &type_ref.of_type {
+        Some(of_type) => 1 + wrapper_count(of_type),
+        None => 0,
+    }
+}
+
+fn strip_non_null(type_ref: &TypeRef) -> String {
+    type_string(type_ref).replace('!', "")
+}
+
+fn is_introspection_type(t: &FullType) -> bool {
+    t.name.as_deref().is_some_and(|n| n.starts_with("__"))
+}
+
+/// Types worth counting in contributions: not introspection types or built-in scalars
+fn is_user_type(t: &FullType) -> bool {
+    let builtin_scalar = t.kind == "SCALAR"
+        && matches!(t.name.as_deref(), Some("Int" | "Float" | "String" | "Boolean" | "ID"));
+    !builtin_scalar && !is_introspection_type(t)
+}
+
+fn type_string(type_ref: &TypeRef) -> String {
+    match type_ref.kind.as_str() {
+        "NON_NULL" => match &type_ref.of_type {
+            Some(of_type) => format!("{}!", type_string(of_type)),
+            None => "String!".to_string(),
+        },
+        "LIST" => match &type_ref.of_type {
+            Some(of_type) => format!("[{}]", type_string(of_type)),
+            None => "[String]".to_string(),
+        },
+        _ => type_ref.name.clone().unwrap_or_else(|| "String".to_string()),
+    }
+}
@@ -1,5 +1,34 @@
+mod audit;
+mod candidates;
+mod coverage;
+mod cycles;
+mod diff;
+mod drift;
+mod engine;
+mod example;
+mod generate;
+mod graph;
 mod introspection;
+mod lint;
 mod inference;
+mod merge;
+mod sdl;
+mod traffic;
+mod wordlist;
 
 pub use introspection::*;
 pub use inference::*;
+pub use diff::*;
+pub use drift::*;
+pub use engine::*;
+pub use example::*;
+pub use sdl::*;
+pub use audit::*;
+pub use wordlist::*;
+pub use graph::*;
+pub use merge::*;
+pub use cycles::*;
+pub use generate::*;
+pub use coverage::*;
+pub use lint::*;
+pub use traffic::*;
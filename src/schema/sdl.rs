@@ -0,0 +1,1013 @@
+use super::{Directive, EnumValue, Field, FullType, InputValue, Schema, SchemaInner, TypeName, TypeRef};
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+
+const BUILTIN_SCALARS: &[&str] = &["Int", "Float", "String", "Boolean", "ID"];
+
+/// Whether a schema file looks like SDL rather than introspection JSON
+pub fn looks_like_sdl(content: &str) -> bool {
+    !matches!(content.trim_start().chars().next(), Some('{') | Some('['))
+}
+
+/// Parse a GraphQL SDL document into the same shape introspection returns
+pub fn parse_sdl(source: &str) -> Result<Schema> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        types: Vec::new(),
+        directives: Vec::new(),
+        roots: HashMap::new(),
+    };
+    parser.document()?;
+    Ok(parser.finish())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(super) enum Token {
+    Name(String),
+    /// String literal, already unescaped; `block` for `"""` strings
+    Str { value: String, block: bool },
+    Number(String),
+    Punct(char),
+    Spread,
+}
+
+pub(super) struct Spanned {
+    pub(super) token: Token,
+    line: usize,
+    col: usize,
+}
+
+pub(super) fn tokenize(source: &str) -> Result<Vec<Spanned>> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    let mut line = 1;
+    let mut line_start = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let col = i - line_start + 1;
+
+        match c {
+            '\n' => {
+                line += 1;
+                line_start = i + 1;
+                i += 1;
+            }
+            // Commas are insignificant in GraphQL
+            ' ' | '\t' | '\r' | ',' | '\u{feff}' => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '.' => {
+                if chars.get(i + 1) == Some(&'.') && chars.get(i + 2) == Some(&'.') {
+                    tokens.push(Spanned { token: Token::Spread, line, col });
+                    i += 3;
+                } else {
+                    bail!("Unexpected '.' at line {}:{}", line, col);
+                }
+            }
+            '!' | '$' | '&' | '(' | ')' | ':' | '=' | '@' | '[' | ']' | '{' | '|' | '}' => {
+                tokens.push(Spanned { token: Token::Punct(c), line, col });
+                i += 1;
+            }
+            '"' => {
+                let block = chars.get(i + 1) == Some(&'"') && chars.get(i + 2) == Some(&'"');
+                let mut value = String::new();
+
+                if block {
+                    i += 3;
+                    loop {
+                        if i >= chars.len() {
+                            bail!("Unterminated block string starting at line {}:{}", line, col);
+                        }
+                        if chars[i] == '"' && chars.get(i + 1) == Some(&'"') && chars.get(i + 2) == Some(&'"') {
+                            i += 3;
+                            break;
+                        }
+                        if chars[i] == '\\' && chars[i + 1..].starts_with(&['"', '"', '"']) {
+                            value.push_str("\"\"\"");
+                            i += 4;
+                            continue;
+                        }
+                        if chars[i] == '\n' {
+                            line += 1;
+                            line_start = i + 1;
+                        }
+                        value.push(chars[i]);
+                        i += 1;
+                    }
+                    value = dedent_block_string(&value);
+                } else {
+                    i += 1;
+                    loop {
+                        match chars.get(i) {
+                            None | Some('\n') => {
+                                bail!("Unterminated string at line {}:{}", line, col)
+                            }
+                            Some('"') => {
+                                i += 1;
+                                break;
+                            }
+                            Some('\\') => {
+                                let escaped = match chars.get(i + 1) {
+                                    Some('n') => '\n',
+                                    Some('t') => '\t',
+                                    Some('r') => '\r',
+                                    Some('b') => '\u{8}',
+                                    Some('f') => '\u{c}',
+                                    Some('u') => {
+                                        let hex: String = chars.iter().skip(i + 2).take(4).collect();
+                                        let code = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32);
+                                        match code {
+                                            Some(ch) => {
+                                                value.push(ch);
+                                                i += 6;
+                                                continue;
+                                            }
+                                            None => bail!("Invalid unicode escape at line {}:{}", line, col),
+                                        }
+                                    }
+                                    Some(other) => *other,
+                                    None => bail!("Unterminated string at line {}:{}", line, col),
+                                };
+                                value.push(escaped);
+                                i += 2;
+                            }
+                            Some(ch) => {
+                                value.push(*ch);
+                                i += 1;
+                            }
+                        }
+                    }
+                }
+
+                tokens.push(Spanned { token: Token::Str { value, block }, line, col });
+            }
+            c if c == '-' || c.is_ascii_digit() => {
+                let start = i;
+                i += 1;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric() || matches!(chars[i], '.' | '+' | '-'))
+                {
+                    i += 1;
+                }
+                let number: String = chars[start..i].iter().collect();
+                tokens.push(Spanned { token: Token::Number(number), line, col });
+            }
+            c if c == '_' || c.is_ascii_alphabetic() => {
+                let start = i;
+                while i < chars.len() && (chars[i] == '_' || chars[i].is_ascii_alphanumeric()) {
+                    i += 1;
+                }
+                let name: String = chars[start..i].iter().collect();
+                tokens.push(Spanned { token: Token::Name(name), line, col });
+            }
+            other => bail!("Unexpected character '{}' at line {}:{}", other, line, col),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Strip the common indentation and blank edge lines from a `"""` string
+fn dedent_block_string(raw: &str) -> String {
+    let lines: Vec<&str> = raw.lines().collect();
+
+    let indent = lines
+        .iter()
+        .skip(1)
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+
+    let mut out: Vec<String> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, l)| {
+            if i == 0 {
+                l.to_string()
+            } else {
+                l.chars().skip(indent).collect()
+            }
+        })
+        .collect();
+
+    while out.first().is_some_and(|l| l.trim().is_empty()) {
+        out.remove(0);
+    }
+    while out.last().is_some_and(|l| l.trim().is_empty()) {
+        out.pop();
+    }
+
+    out.join("\n")
+}
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    types: Vec<FullType>,
+    directives: Vec<Directive>,
+    roots: HashMap<String, String>,
+}
+
+/// Directives we care about on a definition
+#[derive(Default)]
+struct Applied {
+    deprecated: bool,
+    deprecation_reason: Option<String>,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|t| &t.token)
+    }
+
+    fn location(&self) -> String {
+        match self.tokens.get(self.pos).or_else(|| self.tokens.last()) {
+            Some(t) => format!("line {}:{}", t.line, t.col),
+            None => "end of input".to_string(),
+        }
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).map(|t| t.token.clone());
+        self.pos += 1;
+        token
+    }
+
+    fn is_punct(&self, c: char) -> bool {
+        self.peek() == Some(&Token::Punct(c))
+    }
+
+    fn eat_punct(&mut self, c: char) -> bool {
+        if self.is_punct(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect_punct(&mut self, c: char) -> Result<()> {
+        if self.eat_punct(c) {
+            Ok(())
+        } else {
+            bail!("Expected '{}' at {}", c, self.location())
+        }
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Name(n)) if n == keyword)
+    }
+
+    fn eat_keyword(&mut self, keyword: &str) -> bool {
+        if self.is_keyword(keyword) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let location = self.location();
+        match self.next() {
+            Some(Token::Name(n)) => Ok(n),
+            _ => bail!("Expected a name at {}", location),
+        }
+    }
+
+    fn description(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Str { value, .. }) => {
+                let value = value.clone();
+                self.pos += 1;
+                Some(value)
+            }
+            _ => None,
+        }
+    }
+
+    fn document(&mut self) -> Result<()> {
+        while self.peek().is_some() {
+            self.definition()?;
+        }
+        Ok(())
+    }
+
+    fn definition(&mut self) -> Result<()> {
+        let description = self.description();
+        let extend = self.eat_keyword("extend");
+        let location = self.location();
+
+        let keyword = match self.peek() {
+            Some(Token::Name(n)) => n.clone(),
+            Some(Token::Punct('{')) => bail!(
+                "Found an executable document at {}, expected SDL type definitions",
+                location
+            ),
+            _ => bail!("Expected a definition at {}", location),
+        };
+
+        match keyword.as_str() {
+            "schema" => {
+                self.pos += 1;
+                self.directives_applied()?;
+                self.expect_punct('{')?;
+                while !self.eat_punct('}') {
+                    let operation = self.name()?;
+                    self.expect_punct(':')?;
+                    let type_name = self.name()?;
+                    self.roots.insert(operation, type_name);
+                }
+            }
+            "scalar" => {
+                self.pos += 1;
+                let name = self.name()?;
+                self.directives_applied()?;
+                let full_type = self.new_type("SCALAR", name, description);
+                self.add_type(full_type, extend);
+            }
+            "type" | "interface" => {
+                self.pos += 1;
+                let kind = if keyword == "type" { "OBJECT" } else { "INTERFACE" };
+                let name = self.name()?;
+                let interfaces = self.implements()?;
+                self.directives_applied()?;
+                let fields = if self.is_punct('{') { self.fields_definition()? } else { Vec::new() };
+
+                let mut full_type = self.new_type(kind, name, description);
+                full_type.fields = Some(fields);
+                full_type.interfaces = Some(interfaces);
+                self.add_type(full_type, extend);
+            }
+            "union" => {
+                self.pos += 1;
+                let name = self.name()?;
+                self.directives_applied()?;
+                let mut members = Vec::new();
+                if self.eat_punct('=') {
+                    self.eat_punct('|');
+                    loop {
+                        members.push(named_ref("OBJECT", self.name()?));
+                        if !self.eat_punct('|') {
+                            break;
+                        }
+                    }
+                }
+
+                let mut full_type = self.new_type("UNION", name, description);
+                full_type.possible_types = Some(members);
+                self.add_type(full_type, extend);
+            }
+            "enum" => {
+                self.pos += 1;
+                let name = self.name()?;
+                self.directives_applied()?;
+                let mut values = Vec::new();
+                if self.eat_punct('{') {
+                    while !self.eat_punct('}') {
+                        let description = self.description();
+                        let value_name = self.name()?;
+                        let applied = self.directives_applied()?;
+                        values.push(EnumValue {
+                            name: value_name,
+                            description,
+                            is_deprecated: applied.deprecated,
+                            deprecation_reason: applied.deprecation_reason,
+                        });
+                    }
+                }
+
+                let mut full_type = self.new_type("ENUM", name, description);
+                full_type.enum_values = Some(values);
+                self.add_type(full_type, extend);
+            }
+            "input" => {
+                self.pos += 1;
+                let name = self.name()?;
+                self.directives_applied()?;
+                let mut input_fields = Vec::new();
+                if self.eat_punct('{') {
+                    while !self.eat_punct('}') {
+                        input_fields.push(self.input_value()?);
+                    }
+                }
+
+                let mut full_type = self.new_type("INPUT_OBJECT", name, description);
+                full_type.input_fields = Some(input_fields);
+                self.add_type(full_type, extend);
+            }
+            "directive" => {
+                self.pos += 1;
+                self.expect_punct('@')?;
+                let name = self.name()?;
+                let args = self.arguments_definition()?;
+                self.eat_keyword("repeatable");
+                if !self.eat_keyword("on") {
+                    bail!("Expected 'on' in directive @{} at {}", name, self.location());
+                }
+                self.eat_punct('|');
+                let mut locations = Vec::new();
+                loop {
+                    locations.push(self.name()?);
+                    if !self.eat_punct('|') {
+                        break;
+                    }
+                }
+
+                self.directives.retain(|d| d.name != name);
+                self.directives.push(Directive {
+                    name,
+                    description,
+                    locations,
+                    args,
+                });
+            }
+            "query" | "mutation" | "subscription" | "fragment" => bail!(
+                "Found an executable '{}' definition at {}, expected SDL type definitions",
+                keyword,
+                location
+            ),
+            other => bail!("Unknown definition '{}' at {}", other, location),
+        }
+
+        Ok(())
+    }
+
+    fn new_type(&self, kind: &str, name: String, description: Option<String>) -> FullType {
+        FullType {
+            kind: kind.to_string(),
+            name: Some(name),
+            description,
+            fields: None,
+            input_fields: None,
+            interfaces: None,
+            enum_values: None,
+            possible_types: None,
+        }
+    }
+
+    /// Add a type, merging `extend` definitions (and duplicates) into what is already there
+    fn add_type(&mut self, full_type: FullType, extend: bool) {
+        let existing = self
+            .types
+            .iter_mut()
+            .find(|t| t.name == full_type.name);
+
+        let existing = match existing {
+            Some(t) => t,
+            None => {
+                self.types.push(full_type);
+                return;
+            }
+        };
+
+        if !extend && existing.description.is_none() {
+            existing.description = full_type.description;
+        }
+        merge_list(&mut existing.fields, full_type.fields, |f| f.name.clone());
+        merge_list(&mut existing.input_fields, full_type.input_fields, |f| f.name.clone());
+        merge_list(&mut existing.interfaces, full_type.interfaces, |t| t.name.clone().unwrap_or_default());
+        merge_list(&mut existing.enum_values, full_type.enum_values, |v| v.name.clone());
+        merge_list(&mut existing.possible_types, full_type.possible_types, |t| {
+            t.name.clone().unwrap_or_default()
+        });
+    }
+
+    fn implements(&mut self) -> Result<Vec<TypeRef>> {
+        let mut interfaces = Vec::new();
+        if !self.eat_keyword("implements") {
+            return Ok(interfaces);
+        }
+
+        self.eat_punct('&');
+        loop {
+            interfaces.push(named_ref("INTERFACE", self.name()?));
+            if !self.eat_punct('&') {
+                break;
+            }
+        }
+
+        Ok(interfaces)
+    }
+
+    fn fields_definition(&mut self) -> Result<Vec<Field>> {
+        self.expect_punct('{')?;
+        let mut fields = Vec::new();
+
+        while !self.eat_punct('}') {
+            let description = self.description();
+            let name = self.name()?;
+            let args = self.arguments_definition()?;
+            self.expect_punct(':')?;
+            let field_type = self.type_ref()?;
+            let applied = self.directives_applied()?;
+
+            fields.push(Field {
+                name,
+                description,
+                args,
+                field_type,
+                is_deprecated: applied.deprecated,
+                deprecation_reason: applied.deprecation_reason,
+            });
+        }
+
+        Ok(fields)
+    }
+
+    fn arguments_definition(&mut self) -> Result<Vec<InputValue>> {
+        let mut args = Vec::new();
+        if !self.eat_punct('(') {
+            return Ok(args);
+        }
+
+        while !self.eat_punct(')') {
+            args.push(self.input_value()?);
+        }
+
+        Ok(args)
+    }
+
+    fn input_value(&mut self) -> Result<InputValue> {
+        let description = self.description();
+        let name = self.name()?;
+        self.expect_punct(':')?;
+        let input_type = self.type_ref()?;
+        let default_value = if self.eat_punct('=') {
+            Some(self.value()?)
+        } else {
+            None
+        };
+        self.directives_applied()?;
+
+        Ok(InputValue {
+            name,
+            description,
+            input_type,
+            default_value,
+        })
+    }
+
+    fn type_ref(&mut self) -> Result<TypeRef> {
+        let inner = if self.eat_punct('[') {
+            let of_type = self.type_ref()?;
+            self.expect_punct(']')?;
+            TypeRef {
+                kind: "LIST".to_string(),
+                name: None,
+                of_type: Some(Box::new(of_type)),
+            }
+        } else {
+            // Kind is fixed up once every type has been seen
+            named_ref("SCALAR", self.name()?)
+        };
+
+        if self.eat_punct('!') {
+            Ok(TypeRef {
+                kind: "NON_NULL".to_string(),
+                name: None,
+                of_type: Some(Box::new(inner)),
+            })
+        } else {
+            Ok(inner)
+        }
+    }
+
+    fn directives_applied(&mut self) -> Result<Applied> {
+        let mut applied = Applied::default();
+
+        while self.eat_punct('@') {
+            let name = self.name()?;
+            let mut args = HashMap::new();
+            if self.eat_punct('(') {
+                while !self.eat_punct(')') {
+                    let arg = self.name()?;
+                    self.expect_punct(':')?;
+                    let value = match self.peek() {
+                        Some(Token::Str { value, .. }) => {
+                            let value = value.clone();
+                            self.pos += 1;
+                            value
+                        }
+                        _ => self.value()?,
+                    };
+                    args.insert(arg, value);
+                }
+            }
+
+            if name == "deprecated" {
+                applied.deprecated = true;
+                applied.deprecation_reason =
+                    Some(args.remove("reason").unwrap_or_else(|| "No longer supported".to_string()));
+            }
+        }
+
+        Ok(applied)
+    }
+
+    /// Read a literal value back out as GraphQL source, the way introspection reports defaults
+    fn value(&mut self) -> Result<String> {
+        let location = self.location();
+        match self.next() {
+            Some(Token::Name(n)) => Ok(n),
+            Some(Token::Number(n)) => Ok(n),
+            Some(Token::Str { value, block }) => {
+                if block {
+                    Ok(format!("\"\"\"{}\"\"\"", value))
+                } else {
+                    Ok(serde_json::to_string(&value)?)
+                }
+            }
+            Some(Token::Punct('$')) => Ok(format!("${}", self.name()?)),
+            Some(Token::Punct('[')) => {
+                let mut items = Vec::new();
+                while !self.eat_punct(']') {
+                    items.push(self.value()?);
+                }
+                Ok(format!("[{}]", items.join(", ")))
+            }
+            Some(Token::Punct('{')) => {
+                let mut entries = Vec::new();
+                while !self.eat_punct('}') {
+                    let key = self.name()?;
+                    self.expect_punct(':')?;
+                    entries.push(format!("{}: {}", key, self.value()?));
+                }
+                Ok(format!("{{{}}}", entries.join(", ")))
+            }
+            _ => bail!("Expected a value at {}", location),
+        }
+    }
+
+    fn finish(mut self) -> Schema {
+        for scalar in BUILTIN_SCALARS {
+            if !self.types.iter().any(|t| t.name.as_deref() == Some(scalar)) {
+                let full_type = self.new_type("SCALAR", scalar.to_string(), None);
+                self.types.push(full_type);
+            }
+        }
+
+        for (name, locations, args) in builtin_directives() {
+            if !self.directives.iter().any(|d| d.name == name) {
+                self.directives.push(Directive {
+                    name: name.to_string(),
+                    description: None,
+                    locations,
+                    args,
+                });
+            }
+        }
+
+        let kinds: HashMap<String, String> = self
+            .types
+            .iter()
+            .filter_map(|t| Some((t.name.clone()?, t.kind.clone())))
+            .collect();
+
+        // Interfaces list every object that implements them
+        let mut implementors: HashMap<String, Vec<TypeRef>> = HashMap::new();
+        for t in &self.types {
+            for interface in t.interfaces.iter().flatten() {
+                if let (Some(interface_name), Some(name)) = (&interface.name, &t.name) {
+                    implementors
+                        .entry(interface_name.clone())
+                        .or_default()
+                        .push(named_ref(&t.kind, name.clone()));
+                }
+            }
+        }
+
+        for t in &mut self.types {
+            if t.kind == "INTERFACE" {
+                t.possible_types = Some(
+                    t.name
+                        .as_ref()
+                        .and_then(|n| implementors.remove(n))
+                        .unwrap_or_default(),
+                );
+            }
+
+            for field in t.fields.iter_mut().flatten() {
+                resolve_kind(&mut field.field_type, &kinds);
+                for arg in &mut field.args {
+                    resolve_kind(&mut arg.input_type, &kinds);
+                }
+            }
+            for input in t.input_fields.iter_mut().flatten() {
+                resolve_kind(&mut input.input_type, &kinds);
+            }
+        }
+        for directive in &mut self.directives {
+            for arg in &mut directive.args {
+                resolve_kind(&mut arg.input_type, &kinds);
+            }
+        }
+
+        // Without a schema block the conventional root names apply
+        let root = |operation: &str, default: &str| -> Option<TypeName> {
+            let name = match self.roots.get(operation) {
+                Some(n) => n.clone(),
+                None if kinds.contains_key(default) => default.to_string(),
+                None => return None,
+            };
+            Some(TypeName { name })
+        };
+
+        Schema {
+            schema: SchemaInner {
+                query_type: root("query", "Query"),
+                mutation_type: root("mutation", "Mutation"),
+                subscription_type: root("subscription", "Subscription"),
+                types: self.types,
+                directives: self.directives,
+            },
+        }
+    }
+}
+
+fn named_ref(kind: &str, name: String) -> TypeRef {
+    TypeRef {
+        kind: kind.to_string(),
+        name: Some(name),
+        of_type: None,
+    }
+}
+
+pub(super) fn resolve_kind(type_ref: &mut TypeRef, kinds: &HashMap<String, String>) {
+    match &mut type_ref.of_type {
+        Some(of_type) => resolve_kind(of_type, kinds),
+        None => {
+            if let Some(kind) = type_ref.name.as_ref().and_then(|n| kinds.get(n)) {
+                type_ref.kind = kind.clone();
+            }
+        }
+    }
+}
+
+fn merge_list<T>(existing: &mut Option<Vec<T>>, incoming: Option<Vec<T>>, key: impl Fn(&T) -> String) {
+    let incoming = match incoming {
+        Some(items) => items,
+        None => return,
+    };
+
+    let list = existing.get_or_insert_with(Vec::new);
+    for item in incoming {
+        let k = key(&item);
+        match list.iter_mut().find(|existing| key(existing) == k) {
+            Some(slot) => *slot = item,
+            None => list.push(item),
+        }
+    }
+}
+
+fn builtin_directives() -> Vec<(&'static str, Vec<String>, Vec<InputValue>)> {
+    let arg = |name: &str, type_ref: TypeRef, default_value: Option<&str>| InputValue {
+        name: name.to_string(),
+        description: None,
+        input_type: type_ref,
+        default_value: default_value.map(|d| d.to_string()),
+    };
+    let non_null = |name: &str| TypeRef {
+        kind: "NON_NULL".to_string(),
+        name: None,
+        of_type: Some(Box::new(named_ref("SCALAR", name.to_string()))),
+    };
+    let locations = |l: &[&str]| l.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+
+    vec![
+        (
+            "skip",
+            locations(&["FIELD", "FRAGMENT_SPREAD", "INLINE_FRAGMENT"]),
+            vec![arg("if", non_null("Boolean"), None)],
+        ),
+        (
+            "include",
+            locations(&["FIELD", "FRAGMENT_SPREAD", "INLINE_FRAGMENT"]),
+            vec![arg("if", non_null("Boolean"), None)],
+        ),
+        (
+            "deprecated",
+            locations(&["FIELD_DEFINITION", "ARGUMENT_DEFINITION", "INPUT_FIELD_DEFINITION", "ENUM_VALUE"]),
+            vec![arg(
+                "reason",
+                named_ref("SCALAR", "String".to_string()),
+                Some("\"No longer supported\""),
+            )],
+        ),
+        (
+            "specifiedBy",
+            locations(&["SCALAR"]),
+            vec![arg("url", non_null("String"), None)],
+        ),
+    ]
+}
+
+pub(crate) const BUILTIN_DIRECTIVES: &[&str] = &["skip", "include", "deprecated", "specifiedBy", "oneOf"];
+
+/// Render a schema as SDL, leaving out built-in scalars, directives and introspection types
+pub fn print_sdl(schema: &Schema) -> String {
+    let mut blocks = Vec::new();
+
+    let roots = [
+        ("query", schema.schema.query_type.as_ref(), "Query"),
+        ("mutation", schema.schema.mutation_type.as_ref(), "Mutation"),
+        ("subscription", schema.schema.subscription_type.as_ref(), "Subscription"),
+    ];
+    if roots
+        .iter()
+        .any(|(_, root, default)| root.is_some_and(|r| r.name != *default))
+    {
+        let mut block = String::from("schema {\n");
+        for (operation, root, _) in roots {
+            if let Some(root) = root {
+                block.push_str(&format!("  {}: {}\n", operation, root.name));
+            }
+        }
+        block.push('}');
+        blocks.push(block);
+    }
+
+    for directive in schema
+        .schema
+        .directives
+        .iter()
+        .filter(|d| !BUILTIN_DIRECTIVES.contains(&d.name.as_str()))
+    {
+        let mut block = print_description(directive.description.as_deref(), "");
+        block.push_str(&format!(
+            "directive @{}{} on {}",
+            directive.name,
+            print_arguments(&directive.args, ""),
+            directive.locations.join(" | ")
+        ));
+        blocks.push(block);
+    }
+
+    for full_type in schema.get_user_types() {
+        let name = full_type.name.as_deref().unwrap_or_default();
+        if full_type.kind == "SCALAR" && BUILTIN_SCALARS.contains(&name) {
+            continue;
+        }
+
+        let mut block = print_description(full_type.description.as_deref(), "");
+        match full_type.kind.as_str() {
+            "SCALAR" => block.push_str(&format!("scalar {}", name)),
+            "OBJECT" | "INTERFACE" => {
+                let keyword = if full_type.kind == "OBJECT" { "type" } else { "interface" };
+                block.push_str(&format!("{} {}", keyword, name));
+
+                let interfaces: Vec<&str> = full_type
+                    .interfaces
+                    .iter()
+                    .flatten()
+                    .filter_map(|i| i.get_base_type_name())
+                    .collect();
+                if !interfaces.is_empty() {
+                    block.push_str(&format!(" implements {}", interfaces.join(" & ")));
+                }
+
+                let fields = full_type.fields.as_deref().unwrap_or_default();
+                if !fields.is_empty() {
+                    block.push_str(" {\n");
+                    for field in fields {
+                        block.push_str(&print_description(field.description.as_deref(), "  "));
+                        block.push_str(&format!(
+                            "  {}{}: {}{}\n",
+                            field.name,
+                            print_arguments(&field.args, "  "),
+                            print_type(&field.field_type),
+                            print_deprecated(field.is_deprecated, field.deprecation_reason.as_deref())
+                        ));
+                    }
+                    block.push('}');
+                }
+            }
+            "UNION" => {
+                block.push_str(&format!("union {}", name));
+                let members: Vec<&str> = full_type
+                    .possible_types
+                    .iter()
+                    .flatten()
+                    .filter_map(|t| t.get_base_type_name())
+                    .collect();
+                if !members.is_empty() {
+                    block.push_str(&format!(" = {}", members.join(" | ")));
+                }
+            }
+            "ENUM" => {
+                block.push_str(&format!("enum {} {{\n", name));
+                for value in full_type.enum_values.iter().flatten() {
+                    block.push_str(&print_description(value.description.as_deref(), "  "));
+                    block.push_str(&format!(
+                        "  {}{}\n",
+                        value.name,
+                        print_deprecated(value.is_deprecated, value.deprecation_reason.as_deref())
+                    ));
+                }
+                block.push('}');
+            }
+            "INPUT_OBJECT" => {
+                block.push_str(&format!("input {} {{\n", name));
+                for input in full_type.input_fields.iter().flatten() {
+                    block.push_str(&print_description(input.description.as_deref(), "  "));
+                    block.push_str(&format!("  {}\n", print_input_value(input)));
+                }
+                block.push('}');
+            }
+            _ => continue,
+        }
+        blocks.push(block);
+    }
+
+    let mut out = blocks.join("\n\n");
+    out.push('\n');
+    out
+}
+
+fn print_type(type_ref: &TypeRef) -> String {
+    match type_ref.kind.as_str() {
+        "NON_NULL" => match &type_ref.of_type {
+            Some(of_type) => format!("{}!", print_type(of_type)),
+            None => "String!".to_string(),
+        },
+        "LIST" => match &type_ref.of_type {
+            Some(of_type) => format!("[{}]", print_type(of_type)),
+            None => "[String]".to_string(),
+        },
+        _ => type_ref.name.clone().unwrap_or_else(|| "String".to_string()),
+    }
+}
+
+fn print_input_value(value: &InputValue) -> String {
+    match &value.default_value {
+        Some(default) => format!("{}: {} = {}", value.name, print_type(&value.input_type), default),
+        None => format!("{}: {}", value.name, print_type(&value.input_type)),
+    }
+}
+
+fn print_arguments(args: &[InputValue], indent: &str) -> String {
+    if args.is_empty() {
+        return String::new();
+    }
+
+    // Arguments with descriptions need a line each
+    if args.iter().all(|a| a.description.is_none()) {
+        let args: Vec<String> = args.iter().map(print_input_value).collect();
+        return format!("({})", args.join(", "));
+    }
+
+    let inner = format!("{}  ", indent);
+    let mut out = String::from("(\n");
+    for arg in args {
+        out.push_str(&print_description(arg.description.as_deref(), &inner));
+        out.push_str(&format!("{}{}\n", inner, print_input_value(arg)));
+    }
+    out.push_str(indent);
+    out.push(')');
+    out
+}
+
+fn print_deprecated(deprecated: bool, reason: Option<&str>) -> String {
+    match (deprecated, reason) {
+        (false, _) => String::new(),
+        (true, None) | (true, Some("No longer supported")) => " @deprecated".to_string(),
+        (true, Some(reason)) => format!(
+            " @deprecated(reason: {})",
+            serde_json::to_string(reason).unwrap_or_default()
+        ),
+    }
+}
+
+fn print_description(description: Option<&str>, indent: &str) -> String {
+    let description = match description {
+        Some(d) if !d.is_empty() => d,
+        _ => return String::new(),
+    };
+
+    if !description.contains('\n') {
+        return format!(
+            "{}{}\n",
+            indent,
+            serde_json::to_string(description).unwrap_or_default()
+        );
+    }
+
+    let mut out = format!("{}\"\"\"\n", indent);
+    for line in description.replace("\"\"\"", "\\\"\"\"").lines() {
+        if line.is_empty() {
+            out.push('\n');
+        } else {
+            out.push_str(&format!("{}{}\n", indent, line));
+        }
+    }
+    out.push_str(&format!("{}\"\"\"\n", indent));
+    out
+}
@@ -0,0 +1,621 @@
+use super::coverage::{parse_document, ArgumentValue, Selection, VariableDefinition};
+use super::{parse_schema_json, Confidence, InferredArg, InferredField, InferredSchema, InferredType, Schema};
+use anyhow::{bail, Context, Result};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use regex::Regex;
+use serde_json::{Map, Value};
+use std::collections::{BTreeSet, HashMap};
+use std::path::Path;
+
+const BUILTIN_SCALARS: &[&str] = &["Int", "Float", "String", "Boolean", "ID"];
+/// Objects of one field looked at per response, from the start of lists
+const SAMPLES_PER_FIELD: usize = 50;
+// Fragment spreads can reference each other; stop following them past this depth
+const MAX_SELECTION_DEPTH: usize = 64;
+
+/// One GraphQL request seen in captured traffic, with the reply that came back
+#[derive(Debug, Clone)]
+pub struct CapturedOperation {
+    pub url: String,
+    pub query: String,
+    pub variables: Value,
+    /// The reply's JSON body, when there was one
+    pub response: Option<Value>,
+}
+
+/// GraphQL operations in a HAR file (browser dev tools, Burp, ZAP, mitmproxy) or a
+/// Burp Suite XML export of proxy history. Batched requests come out one operation each,
+/// paired with their part of the reply; persisted queries sent without a document are
+/// left out.
+pub fn load_traffic(path: &Path) -> Result<Vec<CapturedOperation>> {
+    let content = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let exchanges = if content.trim_start().starts_with('<') {
+        burp_exchanges(&content)
+    } else {
+        let har: Value = serde_json::from_str(&content)
+            .with_context(|| format!("{} is neither a HAR file nor a Burp XML export", path.display()))?;
+        har_exchanges(&har)?
+    };
+
+    Ok(exchanges.into_iter().flat_map(|exchange| exchange.operations()).collect())
+}
+
+/// A request as captured, before it's known to be GraphQL
+struct Exchange {
+    url: String,
+    /// Request body, or the URL's query parameters as a JSON object for GET
+    request: Option<Value>,
+    response: Option<Value>,
+}
+
+impl Exchange {
+    fn operations(self) -> Vec<CapturedOperation> {
+        let operation = |request: &Value, response: Option<&Value>| {
+            let query = request.get("query")?.as_str()?.to_string();
+            let variables = match request.get("variables") {
+                // GET requests carry variables as a JSON string
+                Some(Value::String(encoded)) => serde_json::from_str(encoded).unwrap_or(Value::Null),
+                Some(variables) => variables.clone(),
+                None => Value::Null,
+            };
+            Some(CapturedOperation {
+                url: self.url.clone(),
+                query,
+                variables,
+                response: response.filter(|r| r.is_object()).cloned(),
+            })
+        };
+
+        match &self.request {
+            Some(Value::Array(batch)) => batch
+                .iter()
+                .enumerate()
+                .filter_map(|(i, request)| operation(request, self.response.as_ref().and_then(|r| r.get(i))))
+                .collect(),
+            Some(request) => operation(request, self.response.as_ref()).into_iter().collect(),
+            None => Vec::new(),
+        }
+    }
+}
+
+fn har_exchanges(har: &Value) -> Result<Vec<Exchange>> {
+    let Some(entries) = har.pointer("/log/entries").and_then(|e| e.as_array()) else {
+        bail!("No log.entries in the HAR file");
+    };
+
+    Ok(entries
+        .iter()
+        .map(|entry| {
+            let request = &entry["request"];
+            let body = request
+                .pointer("/postData/text")
+                .and_then(|t| t.as_str())
+                .and_then(|t| serde_json::from_str(t).ok());
+            let parameters = || {
+                let parameters: Map<String, Value> = request["queryString"]
+                    .as_array()?
+                    .iter()
+                    .filter_map(|p| Some((p["name"].as_str()?.to_string(), p["value"].clone())))
+                    .collect();
+                (!parameters.is_empty()).then_some(Value::Object(parameters))
+            };
+
+            let content = &entry["response"]["content"];
+            let response = content["text"].as_str().and_then(|text| {
+                if content["encoding"] == "base64" {
+                    serde_json::from_slice(&STANDARD.decode(text).ok()?).ok()
+                } else {
+                    serde_json::from_str(text).ok()
+                }
+            });
+
+            Exchange {
+                url: request["url"].as_str().unwrap_or_default().to_string(),
+                request: body.or_else(parameters),
+                response,
+            }
+        })
+        .collect())
+}
+
+fn burp_exchanges(xml: &str) -> Vec<Exchange> {
+    let item_regex = Regex::new(r"(?s)<item>(.*?)</item>").unwrap();
+    let url_regex = Regex::new(r"(?s)<url><!\[CDATA\[(.*?)\]\]></url>").unwrap();
+    let message_regex =
+        Regex::new(r#"(?s)<(request|response) base64="(true|false)"><!\[CDATA\[(.*?)\]\]></(?:request|response)>"#)
+            .unwrap();
+
+    item_regex
+        .captures_iter(xml)
+        .map(|item| {
+            let url = url_regex.captures(&item[1]).map(|c| c[1].to_string()).unwrap_or_default();
+            let mut request = None;
+            let mut response = None;
+            for message in message_regex.captures_iter(&item[1]) {
+                let raw = if &message[2] == "true" {
+                    STANDARD.decode(message[3].trim()).unwrap_or_default()
+                } else {
+                    message[3].as_bytes().to_vec()
+                };
+                let raw = String::from_utf8_lossy(&raw).into_owned();
+                if &message[1] == "request" {
+                    request = request_body(&raw, &url);
+                } else {
+                    response = http_body(&raw).and_then(|body| serde_json::from_str(&body).ok());
+                }
+            }
+            Exchange { url, request, response }
+        })
+        .collect()
+}
+
+/// The JSON body of a raw HTTP request, or its query parameters when it has none
+fn request_body(raw: &str, url: &str) -> Option<Value> {
+    if let Some(body) = http_body(raw).and_then(|body| serde_json::from_str(&body).ok()) {
+        return Some(body);
+    }
+    let parameters: Map<String, Value> = url::Url::parse(url)
+        .ok()?
+        .query_pairs()
+        .map(|(name, value)| (name.into_owned(), Value::String(value.into_owned())))
+        .collect();
+    (!parameters.is_empty()).then_some(Value::Object(parameters))
+}
+
+/// What follows the headers of a raw HTTP message, de-chunked
+fn http_body(raw: &str) -> Option<String> {
+    let (head, body) = raw.split_once("\r\n\r\n").or_else(|| raw.split_once("\n\n"))?;
+    let chunked = head
+        .lines()
+        .any(|l| l.to_ascii_lowercase().starts_with("transfer-encoding:") && l.to_ascii_lowercase().contains("chunked"));
+    if !chunked {
+        return Some(body.to_string());
+    }
+
+    let mut rest = body;
+    let mut joined = String::new();
+    while let Some((size, after)) = rest.split_once("\r\n") {
+        let size = usize::from_str_radix(size.split(';').next()?.trim(), 16).ok()?;
+        if size == 0 || after.len() < size {
+            break;
+        }
+        joined.push_str(after.get(..size)?);
+        rest = after[size..].trim_start_matches("\r\n");
+    }
+    Some(joined)
+}
+
+/// What captured traffic shows of a schema
+#[derive(Debug)]
+pub struct TrafficSchema {
+    pub schema: InferredSchema,
+    pub operations: usize,
+    /// Operations that came with a JSON reply, the only ones leaf types can be read from
+    pub with_response: usize,
+    /// Documents that couldn't be parsed
+    pub unparsed: usize,
+}
+
+impl TrafficSchema {
+    /// As a schema every schema tool reads. Everything in it was seen in use, so the
+    /// confidence notes inferred schemas carry are left out.
+    pub fn to_schema(&self) -> Result<Schema> {
+        let mut schema = parse_schema_json(self.schema.to_introspection_format())?;
+        for full_type in &mut schema.schema.types {
+            for field in full_type.fields.iter_mut().flatten() {
+                field.description = None;
+                field.args.iter_mut().for_each(|a| a.description = None);
+            }
+            full_type.input_fields.iter_mut().flatten().for_each(|f| f.description = None);
+        }
+        Ok(schema)
+    }
+
+    /// Every type, field, argument and input field name seen, for an inference wordlist
+    pub fn words(&self) -> Vec<String> {
+        let mut words = BTreeSet::new();
+        for inferred_type in self.schema.types.values() {
+            if !["Query", "Mutation", "Subscription"].contains(&inferred_type.name.as_str()) {
+                words.insert(inferred_type.name.clone());
+            }
+            for field in &inferred_type.fields {
+                words.insert(field.name.clone());
+                words.extend(field.args.iter().map(|a| a.name.clone()));
+            }
+            words.extend(inferred_type.input_fields.iter().map(|f| f.name.clone()));
+        }
+        words.into_iter().collect()
+    }
+}
+
+/// Argument values clients really sent, by root field: `("query", "user")` ->
+/// `{"id": "42"}`. The first non-null value seen for each argument is kept, with
+/// variables filled in.
+pub fn observed_arguments(operations: &[CapturedOperation]) -> HashMap<(String, String), Map<String, Value>> {
+    let mut observed: HashMap<(String, String), Map<String, Value>> = HashMap::new();
+
+    for captured in operations {
+        let Ok(document) = parse_document(&captured.query) else {
+            continue;
+        };
+        for operation in &document.operations {
+            for selection in &operation.selections {
+                let Selection::Field { name, arguments, .. } = selection else {
+                    continue;
+                };
+                let values = observed.entry((operation.kind.clone(), name.clone())).or_default();
+                for (argument, value) in arguments {
+                    if let Some(value) = argument_json(value, captured.variables.as_object()).filter(|v| !v.is_null()) {
+                        values.entry(argument.clone()).or_insert(value);
+                    }
+                }
+            }
+        }
+    }
+
+    observed.retain(|_, values| !values.is_empty());
+    observed
+}
+
+/// Rebuild the part of a schema `operations` used: each root field, argument and
+/// selected field, typed from the variable definitions, the variables sent and the
+/// replies. Object types are named by `__typename` when the replies have it, else
+/// after the field (`posts` -> `Post`); enums and custom scalars show up as scalars,
+/// and nullability is only known for arguments with a declared variable type.
+pub fn schema_from_traffic(operations: &[CapturedOperation]) -> TrafficSchema {
+    let mut builder = Builder { types: HashMap::new() };
+    let mut unparsed = 0;
+
+    for captured in operations {
+        let document = match parse_document(&captured.query) {
+            Ok(document) => document,
+            Err(_) => {
+                unparsed += 1;
+                continue;
+            }
+        };
+        let data = captured.response.as_ref().and_then(|r| r.get("data")).filter(|d| d.is_object());
+
+        for operation in &document.operations {
+            let root = match operation.kind.as_str() {
+                "mutation" => "Mutation",
+                "subscription" => "Subscription",
+                _ => "Query",
+            };
+            let walk = Walk {
+                fragments: &document.fragments,
+                variables: &operation.variables,
+                values: captured.variables.as_object(),
+            };
+            walk.selections(&mut builder, root, &operation.selections, &data.into_iter().collect::<Vec<_>>(), 0);
+        }
+    }
+
+    TrafficSchema {
+        schema: builder.finish(),
+        operations: operations.len(),
+        with_response: operations.iter().filter(|o| o.response.is_some()).count(),
+        unparsed,
+    }
+}
+
+struct Builder {
+    types: HashMap<String, InferredType>,
+}
+
+impl Builder {
+    fn get(&mut self, name: &str, kind: &str) -> &mut InferredType {
+        self.types.entry(name.to_string()).or_insert_with(|| InferredType {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            fields: Vec::new(),
+            input_fields: Vec::new(),
+            interfaces: Vec::new(),
+            possible_types: Vec::new(),
+        })
+    }
+
+    fn field(&mut self, type_name: &str, name: &str) -> &mut InferredField {
+        let fields = &mut self.get(type_name, "OBJECT").fields;
+        let position = match fields.iter().position(|f| f.name == name) {
+            Some(position) => position,
+            None => {
+                fields.push(InferredField {
+                    name: name.to_string(),
+                    type_name: None,
+                    is_list: false,
+                    is_non_null: false,
+                    args: Vec::new(),
+                    confidence: Confidence::Confirmed,
+                });
+                fields.len() - 1
+            }
+        };
+        &mut fields[position]
+    }
+
+    /// A named input type, with whatever a value sent for it shows
+    fn input_type(&mut self, type_name: &str, value: Option<&Value>) {
+        if BUILTIN_SCALARS.contains(&type_name) {
+            return;
+        }
+        let value = match value {
+            Some(Value::Array(items)) => items.first(),
+            other => other,
+        };
+        match value {
+            Some(Value::Object(fields)) => {
+                let input = self.get(type_name, "INPUT_OBJECT");
+                for (name, value) in fields {
+                    let (type_name, is_list) = json_type(name, value);
+                    merge_arg(
+                        &mut input.input_fields,
+                        InferredArg {
+                            name: name.clone(),
+                            type_name,
+                            is_non_null: false,
+                            is_list,
+                            confidence: Confidence::Confirmed,
+                        },
+                    );
+                }
+            }
+            // Enum values and custom scalars look alike on the wire
+            _ => {
+                self.get(type_name, "SCALAR");
+            }
+        }
+    }
+
+    fn finish(mut self) -> InferredSchema {
+        // A type inline fragments narrowed is abstract: an interface when fields were
+        // selected on it directly, else a union
+        let mut implementations = Vec::new();
+        for inferred_type in self.types.values_mut().filter(|t| !t.possible_types.is_empty()) {
+            if inferred_type.fields.is_empty() {
+                inferred_type.kind = "UNION".to_string();
+            } else {
+                inferred_type.kind = "INTERFACE".to_string();
+                for member in &inferred_type.possible_types {
+                    implementations.push((member.clone(), inferred_type.name.clone()));
+                }
+            }
+        }
+        for (member, interface) in implementations {
+            self.get(&member, "OBJECT").interfaces.push(interface);
+        }
+
+        InferredSchema {
+            query_type: self.types.get("Query").cloned(),
+            mutation_type: self.types.get("Mutation").cloned(),
+            subscription_type: self.types.get("Subscription").cloned(),
+            types: self.types,
+            directives: Vec::new(),
+        }
+    }
+}
+
+/// One operation being walked alongside its reply
+struct Walk<'a> {
+    fragments: &'a HashMap<String, (String, Vec<Selection>)>,
+    variables: &'a [VariableDefinition],
+    values: Option<&'a Map<String, Value>>,
+}
+
+impl Walk<'_> {
+    /// Record `selections` on `type_name`, reading types off `objects`, the values
+    /// of that type the reply holds
+    fn selections(
+        &self,
+        builder: &mut Builder,
+        type_name: &str,
+        selections: &[Selection],
+        objects: &[&Value],
+        depth: usize,
+    ) {
+        if depth > MAX_SELECTION_DEPTH {
+            return;
+        }
+        builder.get(type_name, "OBJECT");
+
+        for selection in selections {
+            match selection {
+                Selection::Field {
+                    alias,
+                    name,
+                    arguments,
+                    selections,
+                } => {
+                    if name.starts_with("__") {
+                        continue;
+                    }
+                    let key = alias.as_deref().unwrap_or(name);
+                    let values: Vec<&Value> = objects.iter().filter_map(|o| o.get(key)).collect();
+                    let is_list = values.iter().any(|v| v.is_array());
+                    let items: Vec<&Value> = values
+                        .iter()
+                        .flat_map(|v| match v {
+                            Value::Array(items) => items.iter().collect(),
+                            other => vec![*other],
+                        })
+                        .filter(|v| !v.is_null())
+                        .take(SAMPLES_PER_FIELD)
+                        .collect();
+
+                    for (argument, value) in arguments {
+                        let arg = self.argument(builder, argument, value);
+                        merge_arg(&mut builder.field(type_name, name).args, arg);
+                    }
+
+                    let known = builder.field(type_name, name).type_name.clone();
+                    let field_type = if selections.is_empty() {
+                        items.iter().find_map(|v| json_type(name, v).0)
+                    } else {
+                        let typenames: Vec<&str> =
+                            items.iter().filter_map(|v| v.get("__typename")?.as_str()).collect();
+                        let observed = match typenames.first() {
+                            Some(first) if typenames.iter().all(|t| t == first) => Some(first.to_string()),
+                            _ => None,
+                        };
+                        let field_type = known.clone().or(observed).unwrap_or_else(|| type_name_for(name, is_list));
+                        self.selections(builder, &field_type, selections, &items, depth + 1);
+                        Some(field_type)
+                    };
+
+                    let field = builder.field(type_name, name);
+                    field.is_list |= is_list;
+                    field.type_name = match (known.as_deref(), field_type) {
+                        // Whole numbers in one reply, fractions in another
+                        (Some("Int"), Some(observed)) if observed == "Float" => Some(observed),
+                        (Some(known), _) => Some(known.to_string()),
+                        (None, observed) => observed,
+                    };
+                }
+                Selection::Inline {
+                    type_condition,
+                    selections,
+                } => match type_condition {
+                    Some(condition) => self.narrowed(builder, type_name, condition, selections, objects, depth),
+                    None => self.selections(builder, type_name, selections, objects, depth + 1),
+                },
+                Selection::Spread(name) => {
+                    if let Some((condition, selections)) = self.fragments.get(name) {
+                        self.narrowed(builder, type_name, condition, selections, objects, depth);
+                    }
+                }
+            }
+        }
+    }
+
+    /// `... on condition` inside `type_name`, walked over the objects whose
+    /// `__typename` says they're `condition`
+    fn narrowed(
+        &self,
+        builder: &mut Builder,
+        type_name: &str,
+        condition: &str,
+        selections: &[Selection],
+        objects: &[&Value],
+        depth: usize,
+    ) {
+        if condition == type_name {
+            self.selections(builder, type_name, selections, objects, depth + 1);
+            return;
+        }
+        let possible_types = &mut builder.get(type_name, "OBJECT").possible_types;
+        if !possible_types.iter().any(|t| t == condition) {
+            possible_types.push(condition.to_string());
+        }
+        let matching: Vec<&Value> = objects
+            .iter()
+            .copied()
+            .filter(|o| o.get("__typename").and_then(|t| t.as_str()) == Some(condition))
+            .collect();
+        self.selections(builder, condition, selections, &matching, depth + 1);
+    }
+
+    fn argument(&self, builder: &mut Builder, name: &str, value: &ArgumentValue) -> InferredArg {
+        let (type_name, is_list, is_non_null) = match value {
+            ArgumentValue::Variable(variable) => match self.variables.iter().find(|v| &v.name == variable) {
+                Some(definition) => {
+                    builder.input_type(&definition.type_name, self.values.and_then(|v| v.get(variable)));
+                    (Some(definition.type_name.clone()), definition.is_list, definition.is_non_null)
+                }
+                None => (None, false, false),
+            },
+            ArgumentValue::List(items) => (items.first().and_then(literal_type), true, false),
+            other => (literal_type(other), false, false),
+        };
+        InferredArg {
+            name: name.to_string(),
+            type_name,
+            is_non_null,
+            is_list,
+            confidence: Confidence::Confirmed,
+        }
+    }
+}
+
+/// The JSON an argument stood for, with variables looked up in `variables`
+fn argument_json(value: &ArgumentValue, variables: Option<&Map<String, Value>>) -> Option<Value> {
+    Some(match value {
+        ArgumentValue::Variable(name) => variables?.get(name)?.clone(),
+        ArgumentValue::Int(number) | ArgumentValue::Float(number) => serde_json::from_str(number).ok()?,
+        ArgumentValue::String(text) | ArgumentValue::Enum(text) => Value::String(text.clone()),
+        ArgumentValue::Boolean(value) => Value::Bool(*value),
+        ArgumentValue::Null => Value::Null,
+        ArgumentValue::List(items) => Value::Array(items.iter().filter_map(|i| argument_json(i, variables)).collect()),
+        ArgumentValue::Object(fields) => Value::Object(
+            fields
+                .iter()
+                .filter_map(|(name, value)| Some((name.clone(), argument_json(value, variables)?)))
+                .collect(),
+        ),
+    })
+}
+
+/// The built-in scalar a literal has to be, if any
+fn literal_type(value: &ArgumentValue) -> Option<String> {
+    let type_name = match value {
+        ArgumentValue::Int(_) => "Int",
+        ArgumentValue::Float(_) => "Float",
+        ArgumentValue::String(_) => "String",
+        ArgumentValue::Boolean(_) => "Boolean",
+        _ => return None,
+    };
+    Some(type_name.to_string())
+}
+
+/// The scalar a JSON value reads as, and whether it's a list. IDs come back as strings,
+/// so `id` is taken for an ID; objects have no name to go by.
+fn json_type(name: &str, value: &Value) -> (Option<String>, bool) {
+    let type_name = match value {
+        Value::Array(items) => {
+            let item = items.iter().find(|v| !v.is_null());
+            return (item.and_then(|v| json_type(name, v).0), true);
+        }
+        Value::Bool(_) => "Boolean",
+        Value::Number(number) if number.is_f64() => "Float",
+        Value::Number(_) => "Int",
+        Value::String(_) if name == "id" => "ID",
+        Value::String(_) => "String",
+        Value::Object(_) | Value::Null => return (None, false),
+    };
+    (Some(type_name.to_string()), false)
+}
+
+/// Add `arg` by name, or fill in what an earlier sighting didn't know
+fn merge_arg(args: &mut Vec<InferredArg>, arg: InferredArg) {
+    match args.iter_mut().find(|a| a.name == arg.name) {
+        Some(existing) => {
+            if existing.type_name.is_none() {
+                existing.type_name = arg.type_name;
+            }
+            existing.is_list |= arg.is_list;
+            existing.is_non_null |= arg.is_non_null;
+        }
+        None => args.push(arg),
+    }
+}
+
+/// `posts` -> `Post`, `user` -> `User`
+fn type_name_for(field: &str, is_list: bool) -> String {
+    let singular = match field {
+        _ if !is_list => field,
+        _ if field.ends_with("ies") => return pascal_case(&format!("{}y", &field[..field.len() - 3])),
+        _ if field.ends_with("sses") || field.ends_with("xes") => &field[..field.len() - 2],
+        _ if field.ends_with('s') && !field.ends_with("ss") => &field[..field.len() - 1],
+        _ => field,
+    };
+    pascal_case(singular)
+}
+
+fn pascal_case(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
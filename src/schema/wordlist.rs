@@ -0,0 +1,183 @@
+use super::{InputValue, Schema};
+use std::collections::{BTreeSet, HashSet};
+
+const BUILTIN_SCALARS: &[&str] = &["Int", "Float", "String", "Boolean", "ID"];
+const BUILTIN_DIRECTIVES: &[&str] = &["skip", "include", "deprecated", "specifiedBy", "oneOf"];
+
+/// Collect every name in a schema as a wordlist for `infer` against sibling APIs.
+///
+/// With `split`, camelCase / PascalCase / snake_case names also contribute their
+/// individual words, and type names their camelCase form (`UserProfile` -> `userProfile`).
+pub fn schema_wordlist(schema: &Schema, split: bool) -> Vec<String> {
+    let mut names = BTreeSet::new();
+
+    let mut add = |name: &str| {
+        if name.is_empty() || name.starts_with("__") {
+            return;
+        }
+        names.insert(name.to_string());
+        if split {
+            for word in split_words(name) {
+                if word.len() > 1 {
+                    names.insert(word);
+                }
+            }
+        }
+    };
+
+    for full_type in schema.get_user_types() {
+        let type_name = full_type.name.as_deref().unwrap_or_default();
+        if BUILTIN_SCALARS.contains(&type_name) {
+            continue;
+        }
+
+        add(type_name);
+        if split {
+            add(&lower_first(type_name));
+        }
+
+        for field in full_type.fields.iter().flatten() {
+            add(&field.name);
+            add_args(&field.args, &mut add);
+        }
+        add_args(full_type.input_fields.as_deref().unwrap_or_default(), &mut add);
+        for value in full_type.enum_values.iter().flatten() {
+            add(&value.name);
+        }
+    }
+
+    for directive in schema
+        .schema
+        .directives
+        .iter()
+        .filter(|d| !BUILTIN_DIRECTIVES.contains(&d.name.as_str()))
+    {
+        add(&directive.name);
+        add_args(&directive.args, &mut add);
+    }
+
+    names.into_iter().collect()
+}
+
+fn add_args(args: &[InputValue], add: &mut dyn FnMut(&str)) {
+    for arg in args {
+        add(&arg.name);
+    }
+}
+
+fn lower_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Lowercased words of a camelCase, PascalCase or SNAKE_CASE name; empty for single words
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for c in name.chars() {
+        if c == '_' || c == '-' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_ascii_uppercase() && current.chars().last().is_some_and(|p| p.is_ascii_lowercase()) {
+            words.push(std::mem::take(&mut current));
+        }
+        current.push(c.to_ascii_lowercase());
+    }
+
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    if words.len() < 2 {
+        return Vec::new();
+    }
+    words
+}
+
+const MANGLE_PREFIXES: &[&str] = &[
+    "get", "list", "all", "find", "search", "fetch", "create", "update", "delete", "add", "remove", "set",
+];
+const MANGLE_SUFFIXES: &[&str] = &["ById", "ByEmail", "ByName", "Connection", "Input", "Count", "List", "Payload"];
+
+/// Expand a wordlist with the usual GraphQL naming conventions: singular and plural
+/// forms, verb prefixes (`getUser`, `allUsers`) and suffixes (`userById`,
+/// `usersConnection`). The original words come first and nothing is repeated.
+pub fn mangle_wordlist(words: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut mangled = Vec::new();
+    let mut push = |word: String| {
+        if seen.insert(word.clone()) {
+            mangled.push(word);
+        }
+    };
+
+    let bases: Vec<&String> = words
+        .iter()
+        .filter(|w| w.len() > 1 && w.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'))
+        .collect();
+
+    for word in words {
+        push(word.clone());
+    }
+    for word in &bases {
+        let single = singular(word);
+        push(plural(&single));
+        push(single);
+    }
+    for word in &bases {
+        let single = singular(word);
+        let many = plural(&single);
+        for prefix in MANGLE_PREFIXES {
+            push(format!("{}{}", prefix, upper_first(&single)));
+            push(format!("{}{}", prefix, upper_first(&many)));
+        }
+        for suffix in MANGLE_SUFFIXES {
+            push(format!("{}{}", single, suffix));
+            push(format!("{}{}", many, suffix));
+        }
+    }
+
+    mangled
+}
+
+fn upper_first(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn plural(word: &str) -> String {
+    let vowel_before_y = word
+        .chars()
+        .rev()
+        .nth(1)
+        .is_some_and(|c| "aeiou".contains(c.to_ascii_lowercase()));
+    if word.ends_with('y') && !vowel_before_y {
+        format!("{}ies", &word[..word.len() - 1])
+    } else if ["s", "x", "z", "ch", "sh"].iter().any(|end| word.ends_with(end)) {
+        format!("{}es", word)
+    } else {
+        format!("{}s", word)
+    }
+}
+
+fn singular(word: &str) -> String {
+    if word.len() > 3 && word.ends_with("ies") {
+        format!("{}y", &word[..word.len() - 3])
+    } else if ["sses", "xes", "zes", "ches", "shes"].iter().any(|end| word.ends_with(end)) {
+        word[..word.len() - 2].to_string()
+    } else if word.ends_with('s') && !["ss", "us", "is"].iter().any(|end| word.ends_with(end)) {
+        word[..word.len() - 1].to_string()
+    } else {
+        word.to_string()
+    }
+}
@@ -0,0 +1,311 @@
+use super::{Category, Confidence, ScanContext, SecurityTest, Severity, TestResult};
+use crate::analysis::{classify_error, ErrorClass};
+use crate::http::{GraphQLResponse, HttpClient};
+use crate::oob::random_label;
+use crate::schema::{Field, InputValue, Schema, TypeRef};
+use async_trait::async_trait;
+use regex::Regex;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Mutation names that take credentials and say whether they were right
+const LOGIN_NAMES: &[&str] = &[
+    "login", "signin", "logIn", "signIn", "authenticate", "auth", "tokenAuth", "obtainToken", "obtainJSONWebToken",
+    "createSession", "createToken", "generateToken", "userLogin", "loginUser", "signInUser", "authenticateUser",
+];
+
+/// Attempts per account; the accounts alternate so drift hits both
+const ATTEMPTS: usize = 3;
+
+/// Gap between the slowest reply for the unknown account and the fastest for the known
+/// one that counts as a timing difference
+const TIMING_MARGIN: Duration = Duration::from_millis(150);
+
+// Account Enumeration Test
+pub struct AccountEnumeration;
+
+#[async_trait]
+impl SecurityTest for AccountEnumeration {
+    fn name(&self) -> &'static str { "account_enumeration" }
+    fn title(&self) -> &'static str { "Account Enumeration via Login" }
+    fn description(&self) -> &'static str { "Login mutation answers differently for existing and unknown accounts" }
+    fn impact(&self) -> &'static str { "Valid usernames or emails can be harvested for password spraying and phishing" }
+    fn severity(&self) -> Severity { Severity::Medium }
+    fn category(&self) -> Category { Category::Auth }
+    fn cwe(&self) -> u32 { 204 }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
+    // Wrong passwords for a real account can lock it out
+    fn opt_in(&self) -> bool { true }
+
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let result = |vulnerable: bool, curl_command: String, evidence: Option<String>| TestResult {
+            name: self.name().to_string(),
+            url: url.to_string(),
+            title: self.title().to_string(),
+            description: self.description().to_string(),
+            impact: self.impact().to_string(),
+            severity: self.severity(),
+            vulnerable,
+            curl_command,
+            evidence,
+            confidence: Confidence::Heuristic,
+        };
+
+        let Some(schema) = context.schema(client, url).await else {
+            return Ok(result(false, "Introspection failed".to_string(), None));
+        };
+        let Some((field, identity)) = login_mutation(&schema) else {
+            return Ok(result(false, "No login-like mutation".to_string(), None));
+        };
+
+        let host = url::Url::parse(url)?.host_str().unwrap_or("example.com").trim_start_matches("www.").to_string();
+        let known = match context.known_account() {
+            Some(account) => account.to_string(),
+            None if identity.is_email => format!("admin@{}", host),
+            None => "admin".to_string(),
+        };
+        let unknown = if identity.is_email {
+            format!("gqlmap-{}@{}", random_label(10), host)
+        } else {
+            format!("gqlmap-{}", random_label(10))
+        };
+        let password = format!("Gqlmap-{}!", random_label(12));
+
+        let mut known_replies = Vec::new();
+        let mut unknown_replies = Vec::new();
+        for _ in 0..ATTEMPTS {
+            for (account, replies) in [(&unknown, &mut unknown_replies), (&known, &mut known_replies)] {
+                let query = login_query(&schema, field, account, &password);
+                let response = client.post_graphql(url, &query, None, Some(self.name())).await?;
+                let reply = Reply::new(&response, &[&known, &unknown]);
+                if let Some(class) = reply.blocking() {
+                    return Ok(result(
+                        false,
+                        response.curl_command,
+                        Some(format!("{} answered with a {} error: {}", field.name, class, reply.messages.join(" | "))),
+                    ));
+                }
+                replies.push((reply, response.curl_command));
+            }
+        }
+
+        let differences = compare(&unknown_replies, &known_replies);
+        let evidence = if differences.is_empty() {
+            format!("{} answers {} and {} alike", field.name, unknown, known)
+        } else {
+            format!("{} vs {}: {}", unknown, known, differences.join("; "))
+        };
+        let curl_command = known_replies.pop().map(|(_, curl)| curl).unwrap_or_default();
+        Ok(result(!differences.is_empty(), curl_command, Some(evidence)))
+    }
+}
+
+/// The argument (or input field) a login mutation takes the account in
+struct Identity {
+    is_email: bool,
+}
+
+/// What one login attempt got back, with the accounts taken out of the messages
+struct Reply {
+    status: u16,
+    messages: Vec<String>,
+    classes: Vec<ErrorClass>,
+    /// The body with every value replaced by its JSON type
+    shape: String,
+    elapsed: Duration,
+}
+
+impl Reply {
+    fn new(response: &GraphQLResponse, accounts: &[&str]) -> Self {
+        let volatile = Regex::new(r"[0-9a-fA-F]{8}-[0-9a-fA-F-]{27}|\d+").unwrap();
+        let errors: Vec<&Value> = response.get_errors().and_then(|e| e.as_array()).into_iter().flatten().collect();
+        let messages = errors
+            .iter()
+            .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+            .map(|message| {
+                let message = accounts.iter().fold(message.to_string(), |m, account| m.replace(account, "<account>"));
+                volatile.replace_all(&message, "<n>").into_owned()
+            })
+            .collect();
+        Self {
+            status: response.status,
+            messages,
+            classes: errors.iter().map(|e| classify_error(e)).collect(),
+            shape: shape(&response.body),
+            elapsed: response.elapsed,
+        }
+    }
+
+    /// Why the attempts can't be compared: the mutation didn't validate, or attempts
+    /// are being throttled
+    fn blocking(&self) -> Option<ErrorClass> {
+        self.classes
+            .iter()
+            .find(|c| matches!(c, ErrorClass::Validation | ErrorClass::RateLimit))
+            .copied()
+    }
+}
+
+/// Differences that hold across every attempt: each account's replies agree among
+/// themselves and disagree with the other's
+fn compare(unknown: &[(Reply, String)], known: &[(Reply, String)]) -> Vec<String> {
+    let mut differences = Vec::new();
+    let consistent = |get: &dyn Fn(&Reply) -> String| -> Option<(String, String)> {
+        let a = get(&unknown.first()?.0);
+        let b = get(&known.first()?.0);
+        let same_a = unknown.iter().all(|(r, _)| get(r) == a);
+        let same_b = known.iter().all(|(r, _)| get(r) == b);
+        (same_a && same_b && a != b).then_some((a, b))
+    };
+
+    if let Some((a, b)) = consistent(&|r| r.status.to_string()) {
+        differences.push(format!("status {} vs {}", a, b));
+    }
+    if let Some((a, b)) = consistent(&|r| r.messages.join(" | ")) {
+        differences.push(format!("error \"{}\" vs \"{}\"", a, b));
+    }
+    if let Some((a, b)) = consistent(&|r| r.shape.clone()) {
+        differences.push(format!("response shape {} vs {}", a, b));
+    }
+
+    let slowest_unknown = unknown.iter().map(|(r, _)| r.elapsed).max();
+    let fastest_known = known.iter().map(|(r, _)| r.elapsed).min();
+    let fastest_unknown = unknown.iter().map(|(r, _)| r.elapsed).min();
+    let slowest_known = known.iter().map(|(r, _)| r.elapsed).max();
+    if let (Some(slowest_unknown), Some(fastest_known)) = (slowest_unknown, fastest_known) {
+        if fastest_known > slowest_unknown + TIMING_MARGIN {
+            differences.push(format!(
+                "timing {}-{}ms vs {}-{}ms",
+                fastest_unknown.unwrap_or_default().as_millis(),
+                slowest_unknown.as_millis(),
+                fastest_known.as_millis(),
+                slowest_known.unwrap_or_default().as_millis()
+            ));
+        }
+    }
+    differences
+}
+
+/// `{"data":{"login":null},"errors":[{"message":string}]}`-like skeleton
+fn shape(value: &Value) -> String {
+    fn walk(value: &Value) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(map.iter().map(|(k, v)| (k.clone(), walk(v))).collect()),
+            Value::Array(items) => Value::Array(items.first().map(walk).into_iter().collect()),
+            Value::String(_) => Value::String("string".to_string()),
+            Value::Number(_) => Value::String("number".to_string()),
+            Value::Bool(_) => Value::String("bool".to_string()),
+            Value::Null => Value::Null,
+        }
+    }
+    walk(value).to_string()
+}
+
+/// The first login-like mutation whose arguments (or input object) name the account
+fn login_mutation(schema: &Schema) -> Option<(&Field, Identity)> {
+    let fields = schema.get_mutation_type()?.fields.as_ref()?;
+    fields
+        .iter()
+        .filter(|f| LOGIN_NAMES.iter().any(|name| name.eq_ignore_ascii_case(&f.name)))
+        .find_map(|f| Some((f, identity(schema, &f.args, 0)?)))
+}
+
+fn identity(schema: &Schema, args: &[InputValue], depth: usize) -> Option<Identity> {
+    if let Some(arg) = args.iter().find(|a| is_identity(&a.name)) {
+        return Some(Identity {
+            is_email: arg.name.to_lowercase().contains("email"),
+        });
+    }
+    if depth > 0 {
+        return None;
+    }
+    args.iter().find_map(|a| {
+        let input = schema.get_type(a.input_type.get_base_type_name()?)?;
+        identity(schema, input.input_fields.as_ref()?, depth + 1)
+    })
+}
+
+fn is_identity(name: &str) -> bool {
+    let name = name.to_lowercase();
+    ["email", "username", "login", "identifier", "user"].iter().any(|n| name == *n)
+        || name.contains("email")
+        || name.contains("username")
+}
+
+fn is_password(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.contains("password") || name.contains("passwd") || name == "pass" || name == "secret"
+}
+
+/// `mutation { login(email: "...", password: "...") { __typename } }`, other required
+/// arguments filled with placeholders
+fn login_query(schema: &Schema, field: &Field, account: &str, password: &str) -> String {
+    let arguments = arguments(schema, &field.args, account, password, 0);
+    let selection = match field
+        .field_type
+        .get_base_type_name()
+        .and_then(|name| schema.get_type(name))
+        .map(|t| t.kind.as_str())
+    {
+        Some("OBJECT" | "INTERFACE" | "UNION") => " { __typename }",
+        _ => "",
+    };
+    format!("mutation {{ {}{}{} }}", field.name, arguments, selection)
+}
+
+fn arguments(schema: &Schema, args: &[InputValue], account: &str, password: &str, depth: usize) -> String {
+    let parts = argument_list(schema, args, account, password, depth);
+    if parts.is_empty() {
+        String::new()
+    } else {
+        format!("({})", parts.join(", "))
+    }
+}
+
+/// `name: value` for the account, the password, an input object holding them, and
+/// any other required argument
+fn argument_list(schema: &Schema, args: &[InputValue], account: &str, password: &str, depth: usize) -> Vec<String> {
+    args.iter()
+        .filter_map(|arg| {
+            let value = if is_identity(&arg.name) {
+                quote(account)
+            } else if is_password(&arg.name) {
+                quote(password)
+            } else if let Some(input) = arg
+                .input_type
+                .get_base_type_name()
+                .and_then(|name| schema.get_type(name))
+                .filter(|t| t.kind == "INPUT_OBJECT" && depth == 0)
+            {
+                let fields = input.input_fields.as_deref().unwrap_or_default();
+                format!("{{ {} }}", argument_list(schema, fields, account, password, depth + 1).join(", "))
+            } else if arg.input_type.is_non_null() && arg.default_value.is_none() {
+                placeholder(schema, &arg.input_type)
+            } else {
+                return None;
+            };
+            Some(format!("{}: {}", arg.name, value))
+        })
+        .collect()
+}
+
+fn placeholder(schema: &Schema, type_ref: &TypeRef) -> String {
+    match type_ref.kind.as_str() {
+        "NON_NULL" => type_ref.of_type.as_deref().map_or("null".to_string(), |t| placeholder(schema, t)),
+        "LIST" => "[]".to_string(),
+        _ => match type_ref.name.as_deref().unwrap_or_default() {
+            "Int" => "1".to_string(),
+            "Float" => "1.0".to_string(),
+            "Boolean" => "false".to_string(),
+            name => match schema.get_type(name).and_then(|t| t.enum_values.as_ref()) {
+                Some(values) => values.first().map_or("null".to_string(), |v| v.name.clone()),
+                None => "\"1\"".to_string(),
+            },
+        },
+    }
+}
+
+/// A GraphQL string literal, which JSON's escaping also is
+fn quote(value: &str) -> String {
+    serde_json::to_string(value).unwrap_or_default()
+}
@@ -0,0 +1,191 @@
+use super::SecurityTest;
+use crate::http::HttpClient;
+use crate::schema::{cheap_root_field, fetch_schema_cached, Schema};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Timed requests of the baseline, after one that warms the connection up
+const BASELINE_SAMPLES: usize = 5;
+
+/// How many times the baseline median a response has to take to count as slow
+pub const SLOWDOWN_FACTOR: u32 = 10;
+
+/// And how much longer than the median in absolute terms, so a 2ms baseline doesn't
+/// make 20ms slow
+const SLOWDOWN_FLOOR: Duration = Duration::from_millis(500);
+
+/// What the tests run so far found out about a target. Unknown until a test says.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Facts {
+    /// Whether the schema can be introspected
+    pub introspection: Option<bool>,
+    /// Whether errors suggest field names
+    pub suggestions: Option<bool>,
+    /// Whether an array of operations gets an array of replies
+    pub batching: Option<bool>,
+    /// Engine the error messages point at
+    pub engine: Option<String>,
+}
+
+/// Shared by the tests of one target, so a test can use what an earlier one found out
+/// instead of probing for it again
+#[derive(Debug, Default)]
+pub struct ScanContext {
+    facts: Mutex<Facts>,
+    /// Schema given up front, used instead of introspecting
+    schema: Option<Arc<Schema>>,
+    /// Account the enumeration test expects to exist
+    known_account: Option<String>,
+    /// Response times of the target before any test ran
+    baseline: Option<Baseline>,
+}
+
+impl ScanContext {
+    pub fn with_schema(mut self, schema: Arc<Schema>) -> Self {
+        self.schema = Some(schema);
+        self
+    }
+
+    pub fn with_known_account(mut self, account: String) -> Self {
+        self.known_account = Some(account);
+        self
+    }
+
+    pub fn known_account(&self) -> Option<&str> {
+        self.known_account.as_deref()
+    }
+
+    pub fn with_baseline(mut self, baseline: Baseline) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    pub fn baseline(&self) -> Option<&Baseline> {
+        self.baseline.as_ref()
+    }
+
+    pub fn facts(&self) -> Facts {
+        self.facts.lock().unwrap().clone()
+    }
+
+    pub fn record(&self, update: impl FnOnce(&mut Facts)) {
+        update(&mut self.facts.lock().unwrap());
+    }
+
+    /// The schema given up front, else the target's, or None without asking when
+    /// introspection is known to be off. A fetch settles whether introspection works
+    /// when no test has yet.
+    pub async fn schema(&self, client: &HttpClient, url: &str) -> Option<Arc<Schema>> {
+        if let Some(schema) = &self.schema {
+            return Some(schema.clone());
+        }
+        if self.facts().introspection == Some(false) {
+            return None;
+        }
+        let schema = fetch_schema_cached(client, url).await.ok();
+        self.record(|facts| {
+            facts.introspection.get_or_insert(schema.is_some());
+        });
+        schema
+    }
+
+    /// What to query when any query that runs will do, see [`Probe`]
+    pub async fn probe(&self, client: &HttpClient, url: &str) -> Probe {
+        let schema = self.schema(client, url).await;
+        schema
+            .as_deref()
+            .and_then(cheap_root_field)
+            .map(|field| Probe { field: field.to_string() })
+            .unwrap_or_default()
+    }
+}
+
+/// How fast the target answers `{ __typename }` when nothing is loading it, to judge
+/// the response times of heavy queries against
+#[derive(Debug, Clone, Copy)]
+pub struct Baseline {
+    pub median: Duration,
+    pub slowest: Duration,
+}
+
+impl Baseline {
+    /// Time [`BASELINE_SAMPLES`] queries after a warm-up one. None when any of them fails.
+    pub async fn measure(client: &HttpClient, url: &str) -> Option<Self> {
+        let mut samples = Vec::with_capacity(BASELINE_SAMPLES);
+        for i in 0..=BASELINE_SAMPLES {
+            let response = client.post_graphql(url, "query { __typename }", None, Some("baseline")).await.ok()?;
+            if i > 0 {
+                samples.push(response.elapsed);
+            }
+        }
+        samples.sort();
+        Some(Self {
+            median: samples[samples.len() / 2],
+            slowest: samples[samples.len() - 1],
+        })
+    }
+
+    /// Whether `elapsed` is well past anything the baseline saw
+    pub fn is_slow(&self, elapsed: Duration) -> bool {
+        elapsed >= self.median * SLOWDOWN_FACTOR && elapsed >= self.slowest + SLOWDOWN_FLOOR
+    }
+
+    /// E.g. `1840ms, 46.0x the 40ms baseline`
+    pub fn compare(&self, elapsed: Duration) -> String {
+        let factor = elapsed.as_secs_f64() / self.median.as_secs_f64().max(0.001);
+        format!("{}ms, {:.1}x the {}ms baseline", elapsed.as_millis(), factor, self.median.as_millis())
+    }
+}
+
+/// A harmless field for tests that only need a query to run. Some gateways answer or
+/// block `__typename`-only documents on their own, so a cheap field of the target's
+/// schema is used when the schema is known, to see how real operations are treated.
+#[derive(Debug, Clone)]
+pub struct Probe {
+    field: String,
+}
+
+impl Default for Probe {
+    fn default() -> Self {
+        Self {
+            field: "__typename".to_string(),
+        }
+    }
+}
+
+impl Probe {
+    pub fn field(&self) -> &str {
+        &self.field
+    }
+
+    /// `query { <field> }`
+    pub fn query(&self) -> String {
+        format!("query {{ {} }}", self.field)
+    }
+
+    /// Whether `data` has the field, null or not
+    pub fn answered(&self, data: &Value) -> bool {
+        data.get(&self.field).is_some()
+    }
+}
+
+/// `tests` in the order they run: each one after the tests it depends on (when they're
+/// in the list at all), otherwise as given
+pub fn order_tests(tests: Vec<&dyn SecurityTest>) -> Vec<&dyn SecurityTest> {
+    let names: Vec<&str> = tests.iter().map(|t| t.name()).collect();
+    let mut ordered: Vec<&dyn SecurityTest> = Vec::with_capacity(tests.len());
+    let mut waiting = tests;
+    while !waiting.is_empty() {
+        let ready = waiting.iter().position(|test| {
+            test.depends_on()
+                .iter()
+                .all(|dep| !names.contains(dep) || ordered.iter().any(|t| t.name() == *dep))
+        });
+        // A dependency cycle runs the rest as given rather than not at all
+        let next = waiting.remove(ready.unwrap_or(0));
+        ordered.push(next);
+    }
+    ordered
+}
@@ -1,4 +1,5 @@
-use super::{SecurityTest, Severity, TestResult};
+use super::{Category, Confidence, ScanContext, SecurityTest, Severity, TestResult};
+use crate::analysis::{classify_error, ErrorClass};
 use crate::http::HttpClient;
 use async_trait::async_trait;
 
@@ -12,26 +13,33 @@ impl SecurityTest for GetQuerySupport {
     fn description(&self) -> &'static str { "GraphQL queries accepted via GET parameters" }
     fn impact(&self) -> &'static str { "CSRF vulnerability - queries triggerable from external sites" }
     fn severity(&self) -> Severity { Severity::Medium }
+    fn category(&self) -> Category { Category::Csrf }
+    fn cwe(&self) -> u32 { 352 }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
-        let query = "query { __typename }";
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let probe = context.probe(client, url).await;
+        let query = probe.query();
 
-        let response = client.get_graphql(url, query, Some(self.name())).await?;
+        let response = client.get_graphql(url, &query, Some(self.name())).await?;
 
         let vulnerable = if let Some(data) = response.get_data() {
-            data.get("__typename").is_some()
+            probe.answered(data)
         } else {
             false
         };
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
             curl_command: format!("curl -G '{}' --data-urlencode 'query={}'", url, query),
+            evidence: None,
+            confidence: Confidence::Heuristic,
         })
     }
 }
@@ -46,8 +54,10 @@ impl SecurityTest for GetMutation {
     fn description(&self) -> &'static str { "GraphQL mutations accepted via GET parameters" }
     fn impact(&self) -> &'static str { "CSRF vulnerability - state changes triggerable from external sites" }
     fn severity(&self) -> Severity { Severity::Medium }
+    fn category(&self) -> Category { Category::Csrf }
+    fn cwe(&self) -> u32 { 352 }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
+    async fn run(&self, client: &HttpClient, url: &str, _context: &ScanContext) -> anyhow::Result<TestResult> {
         let query = "mutation { __typename }";
 
         let response = client.get_graphql(url, query, Some(self.name())).await?;
@@ -55,23 +65,24 @@ impl SecurityTest for GetMutation {
         // Check if mutation was processed (returns data or specific error about mutation not existing)
         let vulnerable = if let Some(data) = response.get_data() {
             data.get("__typename").is_some()
-        } else if let Some(msg) = response.get_first_error_message() {
+        } else if let Some(error) = response.get_errors().and_then(|e| e.get(0)) {
             // If error mentions the mutation doesn't exist, it means mutations ARE processed via GET
-            !msg.to_lowercase().contains("get") &&
-            !msg.to_lowercase().contains("not allowed") &&
-            !msg.to_lowercase().contains("only")
+            classify_error(error) != ErrorClass::Transport
         } else {
             false
         };
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
             curl_command: format!("curl -G '{}' --data-urlencode 'query={}'", url, query),
+            evidence: None,
+            confidence: Confidence::Heuristic,
         })
     }
 }
@@ -86,20 +97,25 @@ impl SecurityTest for PostUrlencoded {
     fn description(&self) -> &'static str { "GraphQL accepts form-encoded POST requests" }
     fn impact(&self) -> &'static str { "CSRF vulnerability - simple form POST without CORS preflight" }
     fn severity(&self) -> Severity { Severity::Medium }
+    fn category(&self) -> Category { Category::Csrf }
+    fn cwe(&self) -> u32 { 352 }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
-        let query = "query { __typename }";
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let probe = context.probe(client, url).await;
+        let query = probe.query();
 
-        let response = client.post_urlencoded(url, query, Some(self.name())).await?;
+        let response = client.post_urlencoded(url, &query, Some(self.name())).await?;
 
         let vulnerable = if let Some(data) = response.get_data() {
-            data.get("__typename").is_some()
+            probe.answered(data)
         } else {
             false
         };
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
@@ -109,6 +125,94 @@ impl SecurityTest for PostUrlencoded {
                 "curl -X POST '{}' -H 'Content-Type: application/x-www-form-urlencoded' -d 'query={}'",
                 url, query
             ),
+            evidence: None,
+            confidence: Confidence::Heuristic,
         })
     }
 }
+
+/// Function name asked for in the callback parameters, unlikely to be in any reply by chance
+const JSONP_CALLBACK: &str = "gqlmapJsonp";
+
+/// Query-string parameters JSONP middleware commonly reads the function name from
+const JSONP_PARAMETERS: &[&str] = &["callback", "jsonp"];
+
+// JSONP Callback Test
+pub struct JsonpCallback;
+
+#[async_trait]
+impl SecurityTest for JsonpCallback {
+    fn name(&self) -> &'static str { "jsonp_callback" }
+    fn title(&self) -> &'static str { "JSONP Callback Support" }
+    fn description(&self) -> &'static str { "GET responses wrapped in a caller-chosen JavaScript callback" }
+    fn impact(&self) -> &'static str { "Any site can load the endpoint as a script and read query results with the victim's cookies" }
+    fn severity(&self) -> Severity { Severity::High }
+    fn category(&self) -> Category { Category::Csrf }
+    fn cwe(&self) -> u32 { 346 }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
+
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let probe = context.probe(client, url).await;
+        let query = probe.query();
+
+        let mut last = None;
+        for parameter in JSONP_PARAMETERS {
+            let params = [("query", query.clone()), (*parameter, JSONP_CALLBACK.to_string())];
+            let response = client.get_params(url, &params, Some(self.name())).await?;
+
+            if let Some(wrapped) = unwrap_jsonp(&response.text) {
+                let answered = wrapped.get("data").is_some_and(|data| probe.answered(data));
+                if answered || wrapped.get("errors").is_some() {
+                    let content_type = response.headers.get("content-type").map_or("no content type", |c| c.as_str());
+                    return Ok(TestResult {
+                        name: self.name().to_string(),
+                        url: url.to_string(),
+                        title: self.title().to_string(),
+                        description: self.description().to_string(),
+                        impact: self.impact().to_string(),
+                        severity: self.severity(),
+                        vulnerable: true,
+                        curl_command: response.curl_command,
+                        evidence: Some(format!(
+                            "`{}=` wraps the {} in {}(...), served as {}",
+                            parameter,
+                            if answered { "query result" } else { "GraphQL errors" },
+                            JSONP_CALLBACK,
+                            content_type
+                        )),
+                        confidence: Confidence::Heuristic,
+                    });
+                }
+            }
+            last = Some(response.curl_command);
+        }
+
+        Ok(TestResult {
+            name: self.name().to_string(),
+            url: url.to_string(),
+            title: self.title().to_string(),
+            description: self.description().to_string(),
+            impact: self.impact().to_string(),
+            severity: self.severity(),
+            vulnerable: false,
+            curl_command: last.unwrap_or_default(),
+            evidence: None,
+            confidence: Confidence::Heuristic,
+        })
+    }
+}
+
+/// The JSON passed to the callback, for bodies like `gqlmapJsonp({...});`, including the
+/// `/**/` and `typeof gqlmapJsonp === 'function' &&` guards some frameworks prepend
+fn unwrap_jsonp(body: &str) -> Option<serde_json::Value> {
+    let call = format!("{}(", JSONP_CALLBACK);
+    let start = body.find(&call)?;
+    // Only guards before the call; a callback name echoed inside JSON isn't a wrapper
+    let prefix = body[..start].trim();
+    if prefix.contains('{') || (prefix.contains('"') && !prefix.contains("typeof")) {
+        return None;
+    }
+    let inner = &body[start + call.len()..];
+    let end = inner.rfind(')')?;
+    serde_json::from_str(inner[..end].trim()).ok()
+}
@@ -1,17 +1,263 @@
-use crate::http::HttpClient;
+use crate::http::{log_at, GraphQLResponse, HtmlResponse, HttpClient};
 use anyhow::Result;
+use serde::Serialize;
 
-const DETECTION_QUERY: &str = "query { __typename }";
+pub const DETECTION_QUERY: &str = "query { __typename }";
 
+/// Confidence from which a URL counts as a GraphQL endpoint
+pub const GRAPHQL_THRESHOLD: u8 = 50;
+
+/// What servers say when a request reaches GraphQL without a document
+const MISSING_QUERY_MESSAGES: &[&str] = &[
+    "must provide query string",
+    "must provide a query",
+    "post body missing",
+    "get query missing",
+    "no operations provided",
+    "query is missing",
+    "missing query parameter",
+    "request must include at least one of",
+    "unexpected <eof>",
+];
+
+/// Strings GraphQL IDEs leave in the page they serve
+const IDE_MARKERS: &[(&str, &str)] = &[
+    ("graphiql", "GraphiQL"),
+    ("graphql playground", "GraphQL Playground"),
+    ("graphql-playground", "GraphQL Playground"),
+    ("embeddable-sandbox", "Apollo Sandbox"),
+    ("apollo sandbox", "Apollo Sandbox"),
+    ("altair", "Altair"),
+    ("graphql-voyager", "GraphQL Voyager"),
+];
+
+/// Something about a URL's replies that points at GraphQL, weighted 0-100
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Signal {
+    pub name: String,
+    pub weight: u8,
+}
+
+impl Signal {
+    pub fn new(name: impl Into<String>, weight: u8) -> Self {
+        Self {
+            name: name.into(),
+            weight,
+        }
+    }
+}
+
+/// Combined confidence of independent signals, 0-100: each one closes its share of the
+/// remaining doubt, so two weak hints beat one but never reach certainty
+pub fn confidence(signals: &[Signal]) -> u8 {
+    let mut names = std::collections::HashSet::new();
+    let doubt = signals
+        .iter()
+        .filter(|s| names.insert(s.name.as_str()))
+        .fold(1.0, |doubt, s| doubt * (1.0 - s.weight.min(100) as f64 / 100.0));
+    ((1.0 - doubt) * 100.0).round() as u8
+}
+
+/// Ways of sending a query. Gateways and CSRF protections often reject one and let
+/// another through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Transport {
+    /// POST with a JSON body
+    Json,
+    /// GET with `?query=`
+    Get,
+    /// POST with the bare document as `application/graphql`
+    #[serde(rename = "graphql")]
+    Document,
+    /// POST with an `application/x-www-form-urlencoded` body
+    Form,
+}
+
+impl Transport {
+    /// In the order detection tries them
+    pub const ALL: [Transport; 4] = [Transport::Json, Transport::Get, Transport::Document, Transport::Form];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Transport::Json => "POST json",
+            Transport::Get => "GET",
+            Transport::Document => "POST application/graphql",
+            Transport::Form => "POST form",
+        }
+    }
+
+    pub async fn send(
+        &self,
+        client: &HttpClient,
+        url: &str,
+        query: &str,
+        test_name: Option<&str>,
+    ) -> Result<GraphQLResponse> {
+        match self {
+            Transport::Json => client.post_graphql(url, query, None, test_name).await,
+            Transport::Get => client.get_graphql(url, query, test_name).await,
+            Transport::Document => client.post_graphql_document(url, query, test_name).await,
+            Transport::Form => client.post_urlencoded(url, query, test_name).await,
+        }
+    }
+}
+
+/// Whether `url` serves GraphQL: a GraphQL answer on any transport, or enough weaker
+/// signals (headers, an IDE page, characteristic errors, batching) to pass [`GRAPHQL_THRESHOLD`]
 pub async fn is_graphql_endpoint(client: &HttpClient, url: &str) -> Result<bool> {
-    let response = client.post_graphql(url, DETECTION_QUERY, None, Some("detection")).await?;
+    let signals = fingerprint_signals(client, url).await?;
+    let score = confidence(&signals);
+    let names: Vec<&str> = signals.iter().map(|s| s.name.as_str()).collect();
+    log_at(
+        1,
+        &format!(
+            "Detection on {}: {}% ({})",
+            url,
+            score,
+            if names.is_empty() { "no signals".to_string() } else { names.join(", ") }
+        ),
+    );
+    Ok(score >= GRAPHQL_THRESHOLD)
+}
+
+/// Signals in a reply to [`DETECTION_QUERY`]: the reply itself, characteristic error
+/// bodies and headers GraphQL servers add
+pub fn response_signals(response: &GraphQLResponse) -> Vec<Signal> {
+    let mut signals = Vec::new();
+
+    let root_type = response
+        .get_data()
+        .and_then(|d| d.get("__typename"))
+        .and_then(|t| t.as_str());
+    if let Some(name) = root_type {
+        let weight = if ["Query", "QueryRoot", "query_root", "Root"].contains(&name) { 100 } else { 90 };
+        signals.push(Signal::new(format!("__typename answered {}", name), weight));
+    }
+
+    match response.get_errors().and_then(|e| e.as_array()) {
+        Some(errors) if errors.iter().any(|e| e.get("locations").is_some() || e.get("extensions").is_some()) => {
+            signals.push(Signal::new("GraphQL-shaped errors", 90));
+        }
+        Some(errors) if errors.iter().any(|e| e.get("message").is_some()) => {
+            signals.push(Signal::new("errors array with messages", 40));
+        }
+        _ => {}
+    }
+
+    let text = response.text.to_lowercase();
+    if let Some(message) = MISSING_QUERY_MESSAGES.iter().find(|m| text.contains(*m)) {
+        signals.push(Signal::new(format!("\"{}\" reply", message), 60));
+    }
+
+    signals.extend(header_signals(&response.headers));
+    signals
+}
+
+/// Signals in a page fetched as HTML: an IDE served on the endpoint
+pub fn page_signals(page: &HtmlResponse) -> Vec<Signal> {
+    let body = page.body.to_lowercase();
+    let mut signals: Vec<Signal> = IDE_MARKERS
+        .iter()
+        .filter(|(marker, _)| body.contains(marker))
+        .map(|(_, ide)| Signal::new(format!("{} page", ide), 50))
+        .collect();
+    signals.dedup();
+    signals.extend(header_signals(&page.headers));
+    signals
+}
+
+/// Headers only GraphQL servers and gateways send
+fn header_signals(headers: &std::collections::HashMap<String, String>) -> Vec<Signal> {
+    let mut signals = Vec::new();
+    for (name, value) in headers {
+        if ["x-apollo", "apollo-", "x-hasura", "x-graphql", "graphql-"]
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+        {
+            signals.push(Signal::new(format!("{} header", name), 40));
+        }
+        if name == "content-type" && value.contains("application/graphql-response+json") {
+            signals.push(Signal::new("application/graphql-response+json reply", 80));
+        }
+    }
+    signals.sort_by(|a, b| a.name.cmp(&b.name));
+    signals
+}
+
+/// Engine a server's headers give away, named like the built-in engine profiles
+pub fn engine_hint(headers: &std::collections::HashMap<String, String>) -> Option<&'static str> {
+    let header = |name: &str| headers.get(name).map(|v| v.to_lowercase()).unwrap_or_default();
+    if headers.keys().any(|k| k.starts_with("x-hasura")) || header("server").contains("hasura") {
+        return Some("hasura");
+    }
+    if header("server").contains("cowboy") || header("x-powered-by").contains("phoenix") {
+        return Some("absinthe");
+    }
+    if headers.contains_key("x-application-context") || header("x-powered-by").contains("spring") {
+        return Some("graphql-java");
+    }
+    if headers.keys().any(|k| k.starts_with("x-apollo") || k.starts_with("apollo-"))
+        || header("x-powered-by").contains("express")
+    {
+        return Some("graphql-js");
+    }
+    None
+}
+
+/// Send two detection queries as a batch: an array of replies back is GraphQL batching
+pub async fn batch_signal(client: &HttpClient, url: &str) -> Option<Signal> {
+    let query = serde_json::json!({ "query": DETECTION_QUERY });
+    let response = client
+        .post_graphql_batch(url, vec![query.clone(), query], Some("detection"))
+        .await
+        .ok()?;
+    let replies = response.body.as_array()?;
+    let answered = !replies.is_empty()
+        && replies
+            .iter()
+            .all(|r| r.get("data").is_some() || r.get("errors").is_some());
+    answered.then(|| Signal::new("batched queries answered", 80))
+}
+
+/// Every signal `url` gives: the detection query over each transport, then, when that
+/// isn't conclusive, the page it serves and a batch
+pub async fn fingerprint_signals(client: &HttpClient, url: &str) -> Result<Vec<Signal>> {
+    let mut signals = Vec::new();
+    let mut last_error = None;
+    let mut answered = false;
+    for transport in Transport::ALL {
+        match transport.send(client, url, DETECTION_QUERY, Some("detection")).await {
+            Ok(response) => {
+                answered = true;
+                signals.extend(response_signals(&response));
+                if looks_like_graphql(&response) {
+                    return Ok(signals);
+                }
+            }
+            Err(e) => last_error = Some(e),
+        }
+    }
+    if !answered {
+        return Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No response from {}", url)));
+    }
+
+    if let Ok(page) = client.get_html(url, Some("detection")).await {
+        signals.extend(page_signals(&page));
+    }
+    signals.extend(batch_signal(client, url).await);
+    Ok(signals)
+}
 
+/// Whether a reply to [`DETECTION_QUERY`] came from a GraphQL server: a known root type
+/// name, or errors shaped like GraphQL errors
+pub fn looks_like_graphql(response: &GraphQLResponse) -> bool {
     if let Some(data) = response.get_data() {
         if let Some(typename) = data.get("__typename") {
             if let Some(name) = typename.as_str() {
                 let valid_roots = ["Query", "QueryRoot", "query_root", "Root"];
                 if valid_roots.contains(&name) {
-                    return Ok(true);
+                    return true;
                 }
             }
         }
@@ -21,11 +267,11 @@ pub async fn is_graphql_endpoint(client: &HttpClient, url: &str) -> Result<bool>
         if let Some(arr) = errors.as_array() {
             for error in arr {
                 if error.get("locations").is_some() || error.get("extensions").is_some() {
-                    return Ok(true);
+                    return true;
                 }
             }
         }
     }
 
-    Ok(false)
+    false
 }
@@ -1,8 +1,19 @@
-use super::{SecurityTest, Severity, TestResult};
-use crate::http::HttpClient;
-use crate::schema::fetch_schema;
+use super::{Category, Confidence, ImpactPayload, ScanContext, SecurityTest, Severity, TestResult, SLOWDOWN_FACTOR};
+use crate::analysis::{classify_error, ErrorClass};
+use crate::http::{GraphQLResponse, HttpClient};
+use crate::schema::{find_cycles, FullType, Schema};
 use async_trait::async_trait;
-use serde_json::json;
+use serde_json::{json, Value};
+use std::time::Duration;
+
+/// Most aliases the alias overloading test puts in one query
+const ALIAS_CAP: usize = 10_000;
+
+/// Most duplicated fields the field duplication test sends in one query
+const FIELD_DUPLICATION_CAP: usize = 10_000;
+
+/// Most named operations the operation multiplexing test puts in one document
+const OPERATION_CAP: usize = 5_000;
 
 // Alias Overloading Test
 pub struct AliasOverloading;
@@ -14,31 +25,57 @@ impl SecurityTest for AliasOverloading {
     fn description(&self) -> &'static str { "Multiple field aliases allowed in single query" }
     fn impact(&self) -> &'static str { "Denial of Service via resource exhaustion" }
     fn severity(&self) -> Severity { Severity::High }
-
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
-        let aliases: Vec<String> = (0..=100)
-            .map(|i| format!("alias{}:__typename", i))
-            .collect();
-        let query = format!("query {{ {} }}", aliases.join(" "));
-
-        let response = client.post_graphql(url, &query, None, Some(self.name())).await?;
-
-        let vulnerable = if let Some(data) = response.get_data() {
-            data.get("alias100").is_some()
-        } else {
-            false
-        };
+    fn category(&self) -> Category { Category::Dos }
+    fn cwe(&self) -> u32 { 770 }
+    fn heavy(&self) -> bool { true }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
+
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let probe = context.probe(client, url).await;
+        let limit = measure_limit(
+            client,
+            url,
+            self.name(),
+            101,
+            ALIAS_CAP,
+            |count| {
+                let aliases: Vec<String> = (0..count)
+                    .map(|i| format!("alias{}:{}", i, probe.field()))
+                    .collect();
+                format!("query {{ {} }}", aliases.join(" "))
+            },
+            |response, count| {
+                response
+                    .get_data()
+                    .is_some_and(|data| data.get(format!("alias{}", count - 1)).is_some())
+            },
+        )
+        .await?;
+
+        let slowdown = limit.slowest.and_then(|(_, elapsed)| slowdown(context, elapsed));
+        let vulnerable = limit.reached() || slowdown.is_some();
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
-            curl_command: response.curl_command,
+            evidence: Some(limit.evidence("aliases", slowdown)),
+            curl_command: limit.curl_command,
+            confidence: Confidence::Heuristic,
         })
     }
+
+    async fn impact_payload(&self, client: &HttpClient, url: &str, context: &ScanContext) -> Option<ImpactPayload> {
+        let field = context.probe(client, url).await.field().to_string();
+        Some(ImpactPayload::new([100, 1_000, ALIAS_CAP / 2], "aliases", move |count| {
+            let aliases: Vec<String> = (0..count).map(|i| format!("alias{}:{}", i, field)).collect();
+            format!("query {{ {} }}", aliases.join(" "))
+        }))
+    }
 }
 
 // Batch Query Test
@@ -51,12 +88,16 @@ impl SecurityTest for BatchQuery {
     fn description(&self) -> &'static str { "Multiple queries accepted in single request" }
     fn impact(&self) -> &'static str { "Denial of Service via batch resource exhaustion" }
     fn severity(&self) -> Severity { Severity::High }
+    fn category(&self) -> Category { Category::Dos }
+    fn cwe(&self) -> u32 { 770 }
+    fn heavy(&self) -> bool { true }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
         let single_query = json!({ "query": "query { __typename }" });
         let batch: Vec<_> = (0..10).map(|_| single_query.clone()).collect();
 
         let response = client.post_graphql_batch(url, batch, Some(self.name())).await?;
+        context.record(|facts| facts.batching = Some(response.body.is_array()));
 
         let vulnerable = if let Some(arr) = response.body.as_array() {
             arr.len() >= 10
@@ -66,12 +107,15 @@ impl SecurityTest for BatchQuery {
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
             curl_command: response.curl_command,
+            evidence: None,
+            confidence: Confidence::Heuristic,
         })
     }
 }
@@ -86,12 +130,15 @@ impl SecurityTest for DirectiveOverloading {
     fn description(&self) -> &'static str { "Multiple duplicate directives accepted on field" }
     fn impact(&self) -> &'static str { "Denial of Service via parser resource exhaustion" }
     fn severity(&self) -> Severity { Severity::High }
+    fn category(&self) -> Category { Category::Dos }
+    fn cwe(&self) -> u32 { 400 }
+    fn heavy(&self) -> bool { true }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
-        let directives = "@aa".repeat(10);
-        let query = format!("query {{ __typename {} }}", directives);
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let query = directive_query(10);
 
         let response = client.post_graphql(url, &query, None, Some(self.name())).await?;
+        let slowdown = slowdown(context, response.elapsed);
 
         let vulnerable = if let Some(errors) = response.get_errors() {
             if let Some(arr) = errors.as_array() {
@@ -101,18 +148,30 @@ impl SecurityTest for DirectiveOverloading {
             }
         } else {
             false
-        };
+        } || slowdown.is_some();
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
             curl_command: response.curl_command,
+            evidence: slowdown,
+            confidence: Confidence::Heuristic,
         })
     }
+
+    async fn impact_payload(&self, _client: &HttpClient, _url: &str, _context: &ScanContext) -> Option<ImpactPayload> {
+        Some(ImpactPayload::new([10, 100, 1_000], "directives", directive_query))
+    }
+}
+
+/// `{ __typename }` with `count` copies of an unknown directive
+fn directive_query(count: usize) -> String {
+    format!("query {{ __typename {} }}", "@aa".repeat(count))
 }
 
 // Circular Introspection Test
@@ -125,33 +184,33 @@ impl SecurityTest for CircularIntrospection {
     fn description(&self) -> &'static str { "Deep nested introspection queries allowed" }
     fn impact(&self) -> &'static str { "Denial of Service via recursive resource exhaustion" }
     fn severity(&self) -> Severity { Severity::High }
+    fn category(&self) -> Category { Category::Dos }
+    fn cwe(&self) -> u32 { 674 }
+    fn heavy(&self) -> bool { true }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
-        let query = r#"query {
-            __schema {
-                types {
-                    fields {
-                        type {
-                            fields {
-                                type {
-                                    fields {
-                                        type {
-                                            fields {
-                                                type { name }
-                                            }
-                                        }
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-        }"#;
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        if context.facts().introspection == Some(false) {
+            return Ok(TestResult {
+                name: self.name().to_string(),
+                url: url.to_string(),
+                title: self.title().to_string(),
+                description: self.description().to_string(),
+                impact: self.impact().to_string(),
+                severity: self.severity(),
+                vulnerable: false,
+                curl_command: "Introspection is disabled".to_string(),
+                evidence: None,
+                confidence: Confidence::Heuristic,
+            });
+        }
+
+        let query = &circular_query(4);
 
         let response = client.post_graphql(url, query, None, Some(self.name())).await?;
+        let slowdown = slowdown(context, response.elapsed);
 
-        let vulnerable = if let Some(data) = response.get_data() {
+        let vulnerable = slowdown.is_some() || if let Some(data) = response.get_data() {
             if let Some(schema) = data.get("__schema") {
                 if let Some(types) = schema.get("types") {
                     if let Some(arr) = types.as_array() {
@@ -171,14 +230,33 @@ impl SecurityTest for CircularIntrospection {
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
             curl_command: response.curl_command,
+            evidence: slowdown,
+            confidence: Confidence::Heuristic,
         })
     }
+
+    async fn impact_payload(&self, _client: &HttpClient, _url: &str, context: &ScanContext) -> Option<ImpactPayload> {
+        if context.facts().introspection == Some(false) {
+            return None;
+        }
+        Some(ImpactPayload::new([2, 3, 4], "levels", circular_query))
+    }
+}
+
+/// Every type's fields' types' fields, `levels` times over
+fn circular_query(levels: usize) -> String {
+    format!(
+        "query {{ __schema {{ types {{ {}name{} }} }} }}",
+        "fields { type { ".repeat(levels),
+        " } }".repeat(levels)
+    )
 }
 
 // Field Duplication Test
@@ -191,25 +269,47 @@ impl SecurityTest for FieldDuplication {
     fn description(&self) -> &'static str { "Repeated fields accepted in query" }
     fn impact(&self) -> &'static str { "Denial of Service via memory exhaustion" }
     fn severity(&self) -> Severity { Severity::High }
-
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
-        let fields = "__typename ".repeat(500);
-        let query = format!("query {{ {} }}", fields.trim());
-
-        let response = client.post_graphql(url, &query, None, Some(self.name())).await?;
-
-        let vulnerable = response.has_data() && !response.has_errors();
+    fn category(&self) -> Category { Category::Dos }
+    fn cwe(&self) -> u32 { 400 }
+    fn heavy(&self) -> bool { true }
+
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let limit = measure_limit(
+            client,
+            url,
+            self.name(),
+            500,
+            FIELD_DUPLICATION_CAP,
+            duplicated_query,
+            |response, _| response.has_data() && !response.has_errors(),
+        )
+        .await?;
+
+        let slowdown = limit.slowest.and_then(|(_, elapsed)| slowdown(context, elapsed));
+        let vulnerable = limit.reached() || slowdown.is_some();
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
-            curl_command: response.curl_command,
+            evidence: Some(limit.evidence("duplicated fields", slowdown)),
+            curl_command: limit.curl_command,
+            confidence: Confidence::Heuristic,
         })
     }
+
+    async fn impact_payload(&self, _client: &HttpClient, _url: &str, _context: &ScanContext) -> Option<ImpactPayload> {
+        Some(ImpactPayload::new([500, 2_500, FIELD_DUPLICATION_CAP], "duplicated fields", duplicated_query))
+    }
+}
+
+/// `__typename` `count` times over
+fn duplicated_query(count: usize) -> String {
+    format!("query {{ {} }}", vec!["__typename"; count].join(" "))
 }
 
 // Depth Limit Test
@@ -222,89 +322,55 @@ impl SecurityTest for DepthLimit {
     fn description(&self) -> &'static str { "Server accepts deeply nested queries" }
     fn impact(&self) -> &'static str { "Denial of Service via stack overflow or resource exhaustion" }
     fn severity(&self) -> Severity { Severity::High }
+    fn category(&self) -> Category { Category::Dos }
+    fn cwe(&self) -> u32 { 674 }
+    fn heavy(&self) -> bool { true }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
         // Try to fetch schema to construct a valid deep query
-        let schema = match fetch_schema(client, url).await {
-            Ok(s) => s,
-            Err(_) => {
+        let schema = match context.schema(client, url).await {
+            Some(s) => s,
+            None => {
                 // If we can't fetch schema, we can't easily construct a deep query without guessing.
                 // We'll return not vulnerable (or inconclusive) for now.
                 return Ok(TestResult {
                     name: self.name().to_string(),
+                    url: url.to_string(),
                     title: self.title().to_string(),
                     description: self.description().to_string(),
                     impact: self.impact().to_string(),
                     severity: self.severity(),
                     vulnerable: false,
-                    curl_command: "Introspection failed, cannot build deep query".to_string(),
+                    curl_command: "Introspection failed, cannot build deep query (pass an inferred schema with --schema)".to_string(),
+                    evidence: None,
+                    confidence: Confidence::Heuristic,
                 });
             }
         };
 
-        // Find a recursive field loop: T -> ... -> T
-        let start_type = if let Some(q) = schema.get_query_type() {
-            q
-        } else {
-             return Ok(TestResult {
-                name: self.name().to_string(),
-                title: self.title().to_string(),
-                description: self.description().to_string(),
-                impact: self.impact().to_string(),
-                severity: self.severity(),
-                vulnerable: false,
-                curl_command: "No Query type found".to_string(),
-            });
-        };
-
-        // Simple strategy: Find a field in Query type that returns a type that has a field returning itself.
-        // Or Query -> TypeA -> TypeA
-        let mut recursive_chain: Option<(String, String)> = None; // (FieldName, FieldName)
-
-        // 1. Check for immediate recursion on Query root fields: Query.me -> User, User.friends -> [User]
-        if let Some(fields) = &start_type.fields {
-            for field in fields {
-                if let Some(base_type_name) = field.field_type.get_base_type_name() {
-                    if let Some(base_type) = schema.get_type(base_type_name) {
-                        // Check if base_type has a field that returns base_type
-                        if let Some(inner_fields) = &base_type.fields {
-                            for inner_field in inner_fields {
-                                if let Some(inner_base_name) = inner_field.field_type.get_base_type_name() {
-                                    if inner_base_name == base_type_name {
-                                        // Found recursion: Query.field -> Type, Type.inner_field -> Type
-                                        recursive_chain = Some((field.name.clone(), inner_field.name.clone()));
-                                        break;
-                                    }
-                                }
-                            }
-                        }
-                    }
-                }
-                if recursive_chain.is_some() { break; }
-            }
-        }
+        // Walk any reachable type cycle (User -> Post -> User, not just self-references)
+        // 64 levels deep from the cheapest argument-free entry point
+        let deep_query = find_cycles(&schema, 4, 200)
+            .iter()
+            .find_map(|cycle| cycle.deep_query(&schema, 64));
 
-        let query_string = if let Some((root_field, recursive_field)) = recursive_chain {
-            // Build deep query: root { recursive { recursive { ... } } }
-            // Depth 100
-            let depth = 64;
-            let mut part = String::from("__typename");
-            for _ in 0..depth {
-                part = format!("{} {{ {} }}", recursive_field, part);
+        let query_string = match deep_query {
+            Some(q) => q,
+            None => {
+                return Ok(TestResult {
+                    name: self.name().to_string(),
+                    url: url.to_string(),
+                    title: self.title().to_string(),
+                    description: self.description().to_string(),
+                    impact: self.impact().to_string(),
+                    severity: self.severity(),
+                    vulnerable: false,
+                    curl_command: "No reachable recursive path found in schema".to_string(),
+                    evidence: None,
+                    confidence: Confidence::Heuristic,
+                });
             }
-            format!("query {{ {} {{ {} }} }}", root_field, part)
-        } else {
-             // Fallback: try to find any self-referencing type and access it if we can guess an entry point
-             // For now, if no simple recursion found from root, skip.
-             return Ok(TestResult {
-                name: self.name().to_string(),
-                title: self.title().to_string(),
-                description: self.description().to_string(),
-                impact: self.impact().to_string(),
-                severity: self.severity(),
-                vulnerable: false,
-                curl_command: "No simple recursive path found in schema".to_string(),
-            });
         };
 
         let response = client.post_graphql(url, &query_string, None, Some(self.name())).await?;
@@ -314,25 +380,38 @@ impl SecurityTest for DepthLimit {
         // If we get timeout or crash, vulnerable.
         // If we get data with correct depth, vulnerable.
 
+        let invalid = response.get_errors().and_then(validation_error);
+        let slowdown = slowdown(context, response.elapsed);
         let vulnerable = if let Some(errors) = response.get_errors() {
-            // Check if errors mention depth
-            let error_str = errors.to_string().to_lowercase();
-            !error_str.contains("depth") && !error_str.contains("complexity")
+            !hit_query_limit(errors) && invalid.is_none()
         } else {
             // No errors means it executed
              response.has_data()
-        };
+        } || slowdown.is_some();
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
+            evidence: invalid.map(|message| format!("Query doesn't validate: {}", message)).or(slowdown),
             curl_command: response.curl_command,
+            confidence: Confidence::Heuristic,
         })
     }
+
+    async fn impact_payload(&self, client: &HttpClient, url: &str, context: &ScanContext) -> Option<ImpactPayload> {
+        let schema = context.schema(client, url).await?;
+        let cycle = find_cycles(&schema, 4, 200)
+            .into_iter()
+            .find(|cycle| cycle.deep_query(&schema, 64).is_some())?;
+        Some(ImpactPayload::new([16, 32, 64], "levels", move |depth| {
+            cycle.deep_query(&schema, depth).unwrap_or_default()
+        }))
+    }
 }
 
 // Query Complexity Test
@@ -345,18 +424,25 @@ impl SecurityTest for QueryComplexity {
     fn description(&self) -> &'static str { "Server accepts complex queries (nested lists)" }
     fn impact(&self) -> &'static str { "Denial of Service via CPU/Memory exhaustion" }
     fn severity(&self) -> Severity { Severity::High }
-
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
-        let schema = match fetch_schema(client, url).await {
-            Ok(s) => s,
-            Err(_) => return Ok(TestResult {
+    fn category(&self) -> Category { Category::Dos }
+    fn cwe(&self) -> u32 { 400 }
+    fn heavy(&self) -> bool { true }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
+
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let schema = match context.schema(client, url).await {
+            Some(s) => s,
+            None => return Ok(TestResult {
                 name: self.name().to_string(),
+                url: url.to_string(),
                 title: self.title().to_string(),
                 description: self.description().to_string(),
                 impact: self.impact().to_string(),
                 severity: self.severity(),
                 vulnerable: false,
-                curl_command: "Introspection failed".to_string(),
+                curl_command: "Introspection failed (pass an inferred schema with --schema)".to_string(),
+                evidence: None,
+                confidence: Confidence::Heuristic,
             }),
         };
 
@@ -365,83 +451,375 @@ impl SecurityTest for QueryComplexity {
         let query_type = if let Some(q) = schema.get_query_type() { q } else {
              return Ok(TestResult {
                 name: self.name().to_string(),
+                url: url.to_string(),
                 title: self.title().to_string(),
                 description: self.description().to_string(),
                 impact: self.impact().to_string(),
                 severity: self.severity(),
                 vulnerable: false,
                 curl_command: "No Query type".to_string(),
+                evidence: None,
+                confidence: Confidence::Heuristic,
             });
         };
 
-        let mut query_struct: Option<(String, String, String)> = None; // RootField, Level1Field, Level2Field
-
-        if let Some(fields) = &query_type.fields {
-            for field in fields {
-                if field.field_type.is_list() {
-                    if let Some(base_name) = field.field_type.get_base_type_name() {
-                        if let Some(type_obj) = schema.get_type(base_name) {
-                            if let Some(inner_fields) = &type_obj.fields {
-                                for inner in inner_fields {
-                                    if inner.field_type.is_list() {
-                                         // Found double nesting: Root -> List -> List
-                                         // Try one more level
-                                         if let Some(inner_base) = inner.field_type.get_base_type_name() {
-                                             if let Some(inner_type) = schema.get_type(inner_base) {
-                                                 if let Some(level2_fields) = &inner_type.fields {
-                                                     for l2 in level2_fields {
-                                                          // Just take the first scalar or object, doesn't need to be list for 3rd level to still be expensive
-                                                          query_struct = Some((field.name.clone(), inner.name.clone(), l2.name.clone()));
-                                                          break;
-                                                     }
-                                                 }
-                                             }
-                                         }
-                                    }
-                                    if query_struct.is_some() { break; }
-                                }
-                            }
-                        }
-                    }
-                }
-                if query_struct.is_some() { break; }
-            }
-        }
-
-        let query = if let Some((f1, f2, f3)) = query_struct {
+        let query = if let Some(selection) = nested_lists(&schema, query_type) {
              // Construct expensive query
-             format!("query {{ {} {{ {} {{ {} }} }} }}", f1, f2, f3)
+             format!("query {{ {} }}", selection)
         } else {
             // Fallback: alias overloading is already a test, so if we can't find nested lists, we skip
             return Ok(TestResult {
                 name: self.name().to_string(),
+                url: url.to_string(),
                 title: self.title().to_string(),
                 description: self.description().to_string(),
                 impact: self.impact().to_string(),
                 severity: self.severity(),
                 vulnerable: false,
                 curl_command: "No nested lists found for complexity test".to_string(),
+                evidence: None,
+                confidence: Confidence::Heuristic,
             });
         };
 
         let response = client.post_graphql(url, &query, None, Some(self.name())).await?;
 
-        // Vulnerable if it executes without error "complexity" or "cost"
+        // Vulnerable if it executes without a complexity or cost limit stopping it
+        let invalid = response.get_errors().and_then(validation_error);
+        let slowdown = slowdown(context, response.elapsed);
         let vulnerable = if let Some(errors) = response.get_errors() {
-            let error_str = errors.to_string().to_lowercase();
-            !error_str.contains("complexity") && !error_str.contains("cost") && !error_str.contains("score")
+            !hit_query_limit(errors) && invalid.is_none()
         } else {
             response.has_data()
-        };
+        } || slowdown.is_some();
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
+            evidence: invalid.map(|message| format!("Query doesn't validate: {}", message)).or(slowdown),
             curl_command: response.curl_command,
+            confidence: Confidence::Heuristic,
         })
     }
-}
\ No newline at end of file
+
+    async fn impact_payload(&self, client: &HttpClient, url: &str, context: &ScanContext) -> Option<ImpactPayload> {
+        let schema = context.schema(client, url).await?;
+        let selection = nested_lists(&schema, schema.get_query_type()?)?;
+        // Copies of the nested query under aliases, each resolving the lists again
+        Some(ImpactPayload::new([1, 5, 25], "copies", move |count| {
+            let copies: Vec<String> = (0..count).map(|i| format!("copy{}: {}", i, selection)).collect();
+            format!("query {{ {} }}", copies.join(" "))
+        }))
+    }
+}
+
+/// `root { inner { leaf } }` through two nested lists from the query root, or None
+fn nested_lists(schema: &Schema, query_type: &FullType) -> Option<String> {
+    let mut query_struct: Option<(String, String, String)> = None; // RootField, Level1Field, Level2Field
+
+    if let Some(fields) = &query_type.fields {
+        for field in fields {
+            if field.field_type.is_list() {
+                if let Some(base_name) = field.field_type.get_base_type_name() {
+                    if let Some(type_obj) = schema.get_type(base_name) {
+                        if let Some(inner_fields) = &type_obj.fields {
+                            for inner in inner_fields {
+                                if inner.field_type.is_list() {
+                                     // Found double nesting: Root -> List -> List
+                                     // Try one more level
+                                     if let Some(inner_base) = inner.field_type.get_base_type_name() {
+                                         if let Some(inner_type) = schema.get_type(inner_base) {
+                                             if let Some(level2_fields) = &inner_type.fields {
+                                                 // Just take the first scalar or object, doesn't need to be list for 3rd level to still be expensive
+                                                 if let Some(l2) = level2_fields.first() {
+                                                      // An object at the bottom still needs a selection to validate
+                                                      let leaf = match l2.field_type.get_base_type_name().and_then(|n| schema.get_type(n)) {
+                                                          Some(t) if matches!(t.kind.as_str(), "OBJECT" | "INTERFACE" | "UNION") => format!("{} {{ __typename }}", l2.name),
+                                                          _ => l2.name.clone(),
+                                                      };
+                                                      query_struct = Some((field.name.clone(), inner.name.clone(), leaf));
+                                                 }
+                                             }
+                                         }
+                                     }
+                                }
+                                if query_struct.is_some() { break; }
+                            }
+                        }
+                    }
+                }
+            }
+            if query_struct.is_some() { break; }
+        }
+    }
+
+    query_struct.map(|(f1, f2, f3)| format!("{} {{ {} {{ {} }} }}", f1, f2, f3))
+}
+
+// Operation Multiplexing Test
+pub struct OperationMultiplexing;
+
+#[async_trait]
+impl SecurityTest for OperationMultiplexing {
+    fn name(&self) -> &'static str { "operation_multiplexing" }
+    fn title(&self) -> &'static str { "Multi-operation Documents" }
+    fn description(&self) -> &'static str { "Documents with many named operations parsed and validated to run one of them" }
+    fn impact(&self) -> &'static str { "Denial of Service via operations that are parsed but never run, past batching limits" }
+    fn severity(&self) -> Severity { Severity::Medium }
+    fn category(&self) -> Category { Category::Dos }
+    fn cwe(&self) -> u32 { 770 }
+    fn heavy(&self) -> bool { true }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
+
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let probe = context.probe(client, url).await;
+        let document = |count: usize| operations_document(probe.field(), count);
+
+        // Names the last operation, so a server that only reads the first few can't pass
+        let limit = measure_operation_limit(
+            client,
+            url,
+            self.name(),
+            101,
+            OPERATION_CAP,
+            |count| (document(count), Some(last_operation(count))),
+            |response, count| {
+                response
+                    .get_data()
+                    .is_some_and(|data| data.get(format!("op{}", count - 1)).is_some())
+            },
+        )
+        .await?;
+
+        // Without operationName the spec wants an error; some servers run one anyway,
+        // or all of them, which turns every document into a batch
+        let unnamed = client.post_graphql(url, &document(3), None, Some(self.name())).await?;
+        let ran: Vec<&str> = unnamed
+            .get_data()
+            .and_then(|data| data.as_object())
+            .map(|data| data.keys().map(|k| k.as_str()).collect())
+            .unwrap_or_default();
+
+        let slowdown = limit.slowest.and_then(|(_, elapsed)| slowdown(context, elapsed));
+        let mut evidence = limit.evidence("operations", slowdown.clone());
+        match ran.as_slice() {
+            [] => evidence.push_str("; no operationName is an error"),
+            [only] => evidence.push_str(&format!("; no operationName runs {} of op0..op2", only)),
+            all => evidence.push_str(&format!("; no operationName runs all of {}", all.join(", "))),
+        }
+
+        Ok(TestResult {
+            name: self.name().to_string(),
+            url: url.to_string(),
+            title: self.title().to_string(),
+            description: self.description().to_string(),
+            impact: self.impact().to_string(),
+            severity: self.severity(),
+            vulnerable: limit.reached() || ran.len() > 1 || slowdown.is_some(),
+            curl_command: if ran.len() > 1 { unnamed.curl_command } else { limit.curl_command },
+            evidence: Some(evidence),
+            confidence: Confidence::Heuristic,
+        })
+    }
+
+    async fn impact_payload(&self, client: &HttpClient, url: &str, context: &ScanContext) -> Option<ImpactPayload> {
+        let field = context.probe(client, url).await.field().to_string();
+        Some(ImpactPayload::with_operation_name([100, 1_000, OPERATION_CAP], "operations", move |count| {
+            (operations_document(&field, count), Some(last_operation(count)))
+        }))
+    }
+}
+
+/// `count` named operations querying `field`, `query op0 { op0: field } query op1 ...`
+fn operations_document(field: &str, count: usize) -> String {
+    let operations: Vec<String> = (0..count)
+        .map(|i| format!("query op{} {{ op{}: {} }}", i, i, field))
+        .collect();
+    operations.join(" ")
+}
+
+/// Name of the last operation of [`operations_document`]
+fn last_operation(count: usize) -> String {
+    format!("op{}", count - 1)
+}
+
+/// Whether any of `errors` is a depth, complexity or cost limit turning the query down
+fn hit_query_limit(errors: &Value) -> bool {
+    errors
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|e| classify_error(e) == ErrorClass::QueryLimit)
+}
+
+/// A note when `elapsed` is slow enough against the target's baseline to be a finding
+/// whatever the response says. The numbers come with every finding's evidence.
+fn slowdown(context: &ScanContext, elapsed: Duration) -> Option<String> {
+    let baseline = context.baseline()?;
+    baseline
+        .is_slow(elapsed)
+        .then(|| format!("slowed down past {}x the baseline", SLOWDOWN_FACTOR))
+}
+
+/// The message of the first error saying the query doesn't validate, which happens
+/// when a schema given with `--schema` (an inferred one, say) is off. Such a query
+/// never ran, so it says nothing about limits.
+fn validation_error(errors: &Value) -> Option<&str> {
+    errors
+        .as_array()?
+        .iter()
+        .find(|e| classify_error(e) == ErrorClass::Validation)
+        .map(|e| e.get("message").and_then(|m| m.as_str()).unwrap_or_default())
+}
+
+/// The largest query size a server accepted, found by bisection between 1 and a cap
+struct Limit {
+    /// None when even a size of 1 was turned down
+    accepted: Option<usize>,
+    /// Size from which acceptance is a finding
+    threshold: usize,
+    cap: usize,
+    /// Size and response time of the smallest and the largest accepted probe
+    fastest: Option<(usize, Duration)>,
+    slowest: Option<(usize, Duration)>,
+    /// Size whose request got no reply (timed out, connection dropped); bisection
+    /// stops there rather than keep loading a server that's struggling
+    no_reply: Option<usize>,
+    /// Reproduces the probe at the threshold, or the first one when it never got there
+    curl_command: String,
+}
+
+impl Limit {
+    /// Whether the server accepted a query of the threshold size
+    fn reached(&self) -> bool {
+        self.accepted.is_some_and(|size| size >= self.threshold)
+    }
+
+    /// E.g. `accepted 1500 duplicated fields (1501 rejected); 4ms at 1 -> 38ms at 1500`,
+    /// then `slowdown` when the largest accepted probe was slow against the baseline
+    fn evidence(&self, what: &str, slowdown: Option<String>) -> String {
+        let mut evidence = match self.accepted {
+            None => format!("rejected even 1 of {}", what),
+            Some(size) if size >= self.cap => {
+                format!("accepted {} {} (the cap): unlimited", size, what)
+            }
+            Some(size) => match self.no_reply {
+                Some(failed) => format!("accepted {} {}, no reply at {}", size, what, failed),
+                None => format!("accepted {} {} ({} rejected)", size, what, size + 1),
+            },
+        };
+        if let (Some((small, fast)), Some((large, slow))) = (self.fastest, self.slowest) {
+            if large > small {
+                evidence.push_str(&format!(
+                    "; {}ms at {} -> {}ms at {}",
+                    fast.as_millis(),
+                    small,
+                    slow.as_millis(),
+                    large
+                ));
+            }
+        }
+        if let Some(slowdown) = slowdown {
+            evidence.push_str(&format!("; {}", slowdown));
+        }
+        evidence
+    }
+}
+
+/// What a [`Limit`] finding measured, read back from its evidence: the largest size
+/// the server accepted, and the evidence up to the timings (the slowdown note left
+/// out), e.g. `accepted 1500 aliases (1501 rejected); 4ms at 1 -> 38ms at 1500`. None
+/// when it accepted nothing. Parses what [`Limit::evidence`] writes, keep them in step.
+pub fn measured_limit(evidence: &str) -> Option<(usize, String)> {
+    let mut clauses = evidence.split("; ");
+    let accepted = clauses.next()?;
+    let size = accepted.strip_prefix("accepted ")?.split_whitespace().next()?.parse().ok()?;
+    Some(match clauses.next().filter(|timing| timing.contains("ms at ")) {
+        Some(timing) => (size, format!("{}; {}", accepted, timing)),
+        None => (size, accepted.to_string()),
+    })
+}
+
+/// Bisect for the largest size up to `cap` whose query (built by `query`) the server
+/// accepts, as judged by `accepted` from the response and the size. Sizes are assumed
+/// to be accepted up to some limit and turned down past it. Sizes 1, `threshold` and
+/// `cap` go first, then about log2(cap) more requests.
+async fn measure_limit(
+    client: &HttpClient,
+    url: &str,
+    test: &str,
+    threshold: usize,
+    cap: usize,
+    query: impl Fn(usize) -> String,
+    accepted: impl Fn(&GraphQLResponse, usize) -> bool,
+) -> anyhow::Result<Limit> {
+    measure_operation_limit(client, url, test, threshold, cap, |size| (query(size), None), accepted).await
+}
+
+/// [`measure_limit`] for documents that name the operation to run, built by `query`
+/// along with that name
+async fn measure_operation_limit(
+    client: &HttpClient,
+    url: &str,
+    test: &str,
+    threshold: usize,
+    cap: usize,
+    query: impl Fn(usize) -> (String, Option<String>),
+    accepted: impl Fn(&GraphQLResponse, usize) -> bool,
+) -> anyhow::Result<Limit> {
+    let probe = |size: usize| {
+        let (query, operation_name) = query(size);
+        async move {
+            let response = client
+                .post_graphql_operation(url, &query, None, operation_name.as_deref(), Some(test))
+                .await?;
+            let elapsed = response.elapsed;
+            anyhow::Ok((response, elapsed))
+        }
+    };
+
+    let (response, elapsed) = probe(1).await?;
+    let mut limit = Limit {
+        accepted: None,
+        threshold,
+        cap,
+        fastest: None,
+        slowest: None,
+        no_reply: None,
+        curl_command: response.curl_command.clone(),
+    };
+    if !accepted(&response, 1) {
+        return Ok(limit);
+    }
+    limit.accepted = Some(1);
+    limit.fastest = Some((1, elapsed));
+    limit.slowest = Some((1, elapsed));
+
+    // `low` is accepted, `high` is the smallest size known to be turned down
+    let (mut low, mut high) = (1, cap + 1);
+    let mut size = threshold.clamp(2, cap);
+    while low + 1 < high {
+        let Ok((response, elapsed)) = probe(size).await else {
+            limit.no_reply = Some(size);
+            break;
+        };
+        if size == threshold {
+            limit.curl_command = response.curl_command.clone();
+        }
+        if accepted(&response, size) {
+            low = size;
+            limit.accepted = Some(size);
+            limit.slowest = Some((size, elapsed));
+        } else {
+            high = size;
+        }
+        // Straight to the cap while nothing was turned down yet
+        size = if high > cap { cap } else { low + (high - low) / 2 };
+    }
+    Ok(limit)
+}
@@ -1,6 +1,29 @@
-use super::{SecurityTest, Severity, TestResult};
+use super::{Category, Confidence, ScanContext, SecurityTest, Severity, TestResult};
+use crate::discovery::{fetch_client_code, find_secrets};
 use crate::http::HttpClient;
+use crate::schema::{looks_like_sdl, parse_schema_str, parse_sdl, EngineProfile, Schema};
 use async_trait::async_trait;
+use regex::Regex;
+use url::Url;
+
+const BUILTIN_SCALARS: &[&str] = &["Int", "Float", "String", "Boolean", "ID"];
+
+/// Schema dumps some servers and build setups leave next to the endpoint, appended to
+/// its path
+const ENDPOINT_ARTIFACTS: &[&str] = &["?sdl", "/schema.graphql", "/schema.json", "/schema", "/v1/schema"];
+
+/// Schema dumps and graphql-config files commonly found at the site root
+const ROOT_ARTIFACTS: &[&str] = &[
+    "/schema.json",
+    "/schema.graphql",
+    "/graphql/schema.graphql",
+    "/graphql/schema.json",
+    "/graphql/v1/schema",
+    "/.graphqlconfig",
+    "/.graphqlrc",
+    "/.graphqlrc.json",
+    "/.graphqlrc.yml",
+];
 
 // Introspection Test
 pub struct Introspection;
@@ -12,8 +35,10 @@ impl SecurityTest for Introspection {
     fn description(&self) -> &'static str { "Full schema introspection query allowed" }
     fn impact(&self) -> &'static str { "Information disclosure - complete API schema exposed" }
     fn severity(&self) -> Severity { Severity::High }
+    fn category(&self) -> Category { Category::Info }
+    fn cwe(&self) -> u32 { 200 }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
         let query = r#"query {
             __schema {
                 types {
@@ -40,15 +65,19 @@ impl SecurityTest for Introspection {
         } else {
             false
         };
+        context.record(|facts| facts.introspection = Some(vulnerable));
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
             curl_command: response.curl_command,
+            evidence: None,
+            confidence: Confidence::Heuristic,
         })
     }
 }
@@ -63,8 +92,10 @@ impl SecurityTest for GraphiQL {
     fn description(&self) -> &'static str { "GraphQL development IDE accessible in production" }
     fn impact(&self) -> &'static str { "Information disclosure - interactive query interface exposed" }
     fn severity(&self) -> Severity { Severity::Low }
+    fn category(&self) -> Category { Category::Info }
+    fn cwe(&self) -> u32 { 489 }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
+    async fn run(&self, client: &HttpClient, url: &str, _context: &ScanContext) -> anyhow::Result<TestResult> {
         let response = client.get_html(url, Some(self.name())).await?;
 
         let indicators = [
@@ -81,12 +112,15 @@ impl SecurityTest for GraphiQL {
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
             curl_command: format!("curl -H 'Accept: text/html' '{}'", url),
+            evidence: None,
+            confidence: Confidence::Heuristic,
         })
     }
 }
@@ -101,8 +135,10 @@ impl SecurityTest for FieldSuggestions {
     fn description(&self) -> &'static str { "Error messages suggest valid field names" }
     fn impact(&self) -> &'static str { "Information disclosure - schema hints in errors" }
     fn severity(&self) -> Severity { Severity::Low }
+    fn category(&self) -> Category { Category::Info }
+    fn cwe(&self) -> u32 { 209 }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
         // Intentionally misspelled field to trigger suggestion
         let query = r#"query { __schema { directive } }"#;
 
@@ -113,15 +149,33 @@ impl SecurityTest for FieldSuggestions {
         } else {
             false
         };
+        let messages: Vec<String> = response
+            .get_errors()
+            .and_then(|e| e.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|e| e.get("message").and_then(|m| m.as_str()))
+            .map(String::from)
+            .collect();
+        let engine = EngineProfile::detect(&messages).map(|e| e.name);
+        context.record(|facts| {
+            facts.suggestions = Some(vulnerable);
+            if engine.is_some() {
+                facts.engine = engine;
+            }
+        });
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
             curl_command: response.curl_command,
+            evidence: None,
+            confidence: Confidence::Heuristic,
         })
     }
 }
@@ -136,8 +190,10 @@ impl SecurityTest for TraceMode {
     fn description(&self) -> &'static str { "Debug tracing information in responses" }
     fn impact(&self) -> &'static str { "Information disclosure - execution traces exposed" }
     fn severity(&self) -> Severity { Severity::Info }
+    fn category(&self) -> Category { Category::Info }
+    fn cwe(&self) -> u32 { 215 }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
+    async fn run(&self, client: &HttpClient, url: &str, _context: &ScanContext) -> anyhow::Result<TestResult> {
         let query = "query { __typename }";
 
         let response = client.post_graphql(url, query, None, Some(self.name())).await?;
@@ -150,12 +206,15 @@ impl SecurityTest for TraceMode {
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
             curl_command: response.curl_command,
+            evidence: None,
+            confidence: Confidence::Heuristic,
         })
     }
 }
@@ -170,8 +229,10 @@ impl SecurityTest for UnhandledErrors {
     fn description(&self) -> &'static str { "Exception details visible in error responses" }
     fn impact(&self) -> &'static str { "Information disclosure - stack traces or internal details" }
     fn severity(&self) -> Severity { Severity::Info }
+    fn category(&self) -> Category { Category::Info }
+    fn cwe(&self) -> u32 { 209 }
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult> {
+    async fn run(&self, client: &HttpClient, url: &str, _context: &ScanContext) -> anyhow::Result<TestResult> {
         // Malformed query to trigger exception
         let query = "qwerty { abc }";
 
@@ -185,12 +246,174 @@ impl SecurityTest for UnhandledErrors {
 
         Ok(TestResult {
             name: self.name().to_string(),
+            url: url.to_string(),
             title: self.title().to_string(),
             description: self.description().to_string(),
             impact: self.impact().to_string(),
             severity: self.severity(),
             vulnerable,
             curl_command: response.curl_command,
+            evidence: None,
+            confidence: Confidence::Heuristic,
+        })
+    }
+}
+
+// Schema Artifacts Test
+pub struct SchemaArtifacts;
+
+#[async_trait]
+impl SecurityTest for SchemaArtifacts {
+    fn name(&self) -> &'static str { "schema_artifacts" }
+    fn title(&self) -> &'static str { "Schema Dump Exposed" }
+    fn description(&self) -> &'static str { "Full SDL or introspection JSON served at a well-known location" }
+    fn impact(&self) -> &'static str { "Information disclosure - complete API schema exposed whether or not introspection is off" }
+    fn severity(&self) -> Severity { Severity::Medium }
+    fn category(&self) -> Category { Category::Info }
+    fn cwe(&self) -> u32 { 538 }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
+
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let endpoint = Url::parse(url)?;
+        let mut candidates: Vec<Url> = Vec::new();
+        let mut add = |candidate: Url| {
+            if !candidates.contains(&candidate) {
+                candidates.push(candidate);
+            }
+        };
+        let base = format!("{}{}", endpoint.origin().ascii_serialization(), endpoint.path().trim_end_matches('/'));
+        for suffix in ENDPOINT_ARTIFACTS {
+            add(Url::parse(&format!("{}{}", base, suffix))?);
+        }
+        for path in ROOT_ARTIFACTS {
+            add(endpoint.join(path)?);
+        }
+
+        let mut found = Vec::new();
+        let mut index = 0;
+        while index < candidates.len() {
+            let candidate = candidates[index].clone();
+            index += 1;
+            let Ok(response) = client.get_params(candidate.as_str(), &[], Some(self.name())).await else {
+                continue;
+            };
+            if !(200..300).contains(&response.status) {
+                continue;
+            }
+
+            if let Some((kind, schema)) = schema_dump(&response.text) {
+                let types = schema
+                    .get_user_types()
+                    .iter()
+                    .filter(|t| !t.name.as_deref().is_some_and(|name| BUILTIN_SCALARS.contains(&name)))
+                    .count();
+                found.push((candidate, kind, types, response.curl_command));
+                continue;
+            }
+            // A graphql-config file only points at the schema; the file it names is tried too
+            for path in config_schema_paths(&response.text) {
+                if let Ok(schema_url) = candidate.join(&path) {
+                    if !candidates.contains(&schema_url) {
+                        candidates.push(schema_url);
+                    }
+                }
+            }
+        }
+
+        let introspection_off = context.facts().introspection == Some(false);
+        let evidence = (!found.is_empty()).then(|| {
+            let mut parts: Vec<String> = found
+                .iter()
+                .map(|(artifact, kind, types, _)| format!("{} serves {} ({} types)", artifact, kind, types))
+                .collect();
+            if introspection_off {
+                parts.push("introspection itself is off".to_string());
+            }
+            parts.join("; ")
+        });
+
+        Ok(TestResult {
+            name: self.name().to_string(),
+            url: url.to_string(),
+            title: self.title().to_string(),
+            description: self.description().to_string(),
+            impact: self.impact().to_string(),
+            severity: self.severity(),
+            vulnerable: !found.is_empty(),
+            curl_command: found
+                .first()
+                .map_or_else(|| format!("{} schema locations tried", candidates.len()), |(.., curl)| curl.clone()),
+            evidence,
+            confidence: Confidence::Heuristic,
+        })
+    }
+}
+
+/// The schema in `body` when it is one, introspection JSON or SDL, with which it was.
+/// HTML error pages and catch-all routes fail to parse or have no query type.
+fn schema_dump(body: &str) -> Option<(&'static str, Schema)> {
+    let body = body.trim();
+    if body.is_empty() || body.starts_with('<') {
+        return None;
+    }
+    let (kind, schema) = if looks_like_sdl(body) {
+        ("SDL", parse_sdl(body).ok()?)
+    } else {
+        ("introspection JSON", parse_schema_str(body).ok()?)
+    };
+    schema.get_query_type().is_some().then_some((kind, schema))
+}
+
+/// Schema files a graphql-config file (`schema:`, or the legacy `schemaPath`) names by
+/// relative path
+fn config_schema_paths(body: &str) -> Vec<String> {
+    let regex = Regex::new(r#"(?m)["']?(?:schemaPath|schema)["']?\s*:\s*["']?([\w./-]+\.(?:graphqls?|gql|json))"#).unwrap();
+    regex
+        .captures_iter(body)
+        .map(|c| c[1].to_string())
+        .collect()
+}
+
+// Client-side Secrets Test
+pub struct ClientSecrets;
+
+#[async_trait]
+impl SecurityTest for ClientSecrets {
+    fn name(&self) -> &'static str { "client_secrets" }
+    fn title(&self) -> &'static str { "Secrets in Client Code" }
+    fn description(&self) -> &'static str { "API keys, admin secrets, tokens or internal GraphQL URLs in the site's HTML and JavaScript" }
+    fn impact(&self) -> &'static str { "Information disclosure - credentials and internal endpoints readable by every visitor" }
+    fn severity(&self) -> Severity { Severity::Medium }
+    fn category(&self) -> Category { Category::Info }
+    fn cwe(&self) -> u32 { 540 }
+
+    async fn run(&self, client: &HttpClient, url: &str, _context: &ScanContext) -> anyhow::Result<TestResult> {
+        let sources = fetch_client_code(client, &Url::parse(url)?, self.name()).await?;
+        let secrets = find_secrets(&sources);
+
+        let evidence = (!secrets.is_empty()).then(|| {
+            secrets
+                .iter()
+                .map(|s| format!("{} {} in {}", s.kind, s.value, s.source))
+                .collect::<Vec<_>>()
+                .join("; ")
+        });
+        let curl_command = match secrets.first() {
+            Some(secret) => format!("curl '{}'", secret.source),
+            None => format!("{} pages and scripts searched", sources.len()),
+        };
+
+        Ok(TestResult {
+            name: self.name().to_string(),
+            url: url.to_string(),
+            title: self.title().to_string(),
+            description: self.description().to_string(),
+            impact: self.impact().to_string(),
+            severity: self.severity(),
+            vulnerable: !secrets.is_empty(),
+            curl_command,
+            evidence,
+            confidence: Confidence::Heuristic,
         })
     }
 }
@@ -1,18 +1,30 @@
+mod auth;
+mod context;
 mod detection;
 mod dos;
 mod info;
 mod csrf;
+mod transport;
+mod verify;
+mod watchdog;
 
+pub use auth::*;
+pub use context::*;
 pub use detection::*;
 pub use dos::*;
 pub use info::*;
 pub use csrf::*;
+pub use transport::*;
+pub use verify::*;
+pub use watchdog::*;
 
 use crate::http::HttpClient;
 use async_trait::async_trait;
-use serde::Serialize;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+/// Ordered worst first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Severity {
     High,
@@ -43,15 +55,67 @@ impl std::fmt::Display for Severity {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+/// What kind of weakness a test looks for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Category {
+    Dos,
+    Info,
+    Csrf,
+    /// How the endpoint is reached rather than what it answers
+    Transport,
+    Auth,
+}
+
+impl std::fmt::Display for Category {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Category::Dos => write!(f, "dos"),
+            Category::Info => write!(f, "info"),
+            Category::Csrf => write!(f, "csrf"),
+            Category::Transport => write!(f, "transport"),
+            Category::Auth => write!(f, "auth"),
+        }
+    }
+}
+
+/// How a finding was established
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Confidence {
+    /// Judged from what the responses said and how long they took
+    #[default]
+    Heuristic,
+    /// Degradation measured by `--verify-dos`
+    Verified,
+}
+
+impl std::fmt::Display for Confidence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Confidence::Heuristic => write!(f, "heuristic"),
+            Confidence::Verified => write!(f, "verified"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TestResult {
     pub name: String,
+    /// Endpoint the test ran against; empty in results saved before it was recorded
+    #[serde(default)]
+    pub url: String,
     pub title: String,
     pub description: String,
     pub impact: String,
     pub severity: Severity,
     pub vulnerable: bool,
     pub curl_command: String,
+    /// What the test measured, for findings that come with numbers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub evidence: Option<String>,
+    #[serde(default)]
+    pub confidence: Confidence,
 }
 
 #[async_trait]
@@ -61,8 +125,29 @@ pub trait SecurityTest: Send + Sync {
     fn description(&self) -> &'static str;
     fn impact(&self) -> &'static str;
     fn severity(&self) -> Severity;
+    fn category(&self) -> Category;
+    /// CWE number the finding maps to
+    fn cwe(&self) -> u32;
+    /// Whether the test sends queries big enough to load the server
+    fn heavy(&self) -> bool {
+        false
+    }
+    /// Left out of scans unless named with `--include`
+    fn opt_in(&self) -> bool {
+        false
+    }
+    /// Tests whose findings this one reads from the [`ScanContext`], run before it
+    fn depends_on(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult>;
 
-    async fn run(&self, client: &HttpClient, url: &str) -> anyhow::Result<TestResult>;
+    /// What the test's finding is made of, at sizes `--verify-dos` sends to measure the
+    /// impact. None for tests with nothing to scale.
+    async fn impact_payload(&self, _client: &HttpClient, _url: &str, _context: &ScanContext) -> Option<ImpactPayload> {
+        None
+    }
 }
 
 pub fn all_tests() -> Vec<Box<dyn SecurityTest>> {
@@ -75,15 +160,112 @@ pub fn all_tests() -> Vec<Box<dyn SecurityTest>> {
         Box::new(dos::FieldDuplication),
         Box::new(dos::DepthLimit),
         Box::new(dos::QueryComplexity),
+        Box::new(dos::OperationMultiplexing),
         // Info tests
         Box::new(info::Introspection),
         Box::new(info::GraphiQL),
         Box::new(info::FieldSuggestions),
         Box::new(info::TraceMode),
         Box::new(info::UnhandledErrors),
+        Box::new(info::SchemaArtifacts),
+        Box::new(info::ClientSecrets),
         // CSRF tests
         Box::new(csrf::GetQuerySupport),
         Box::new(csrf::GetMutation),
         Box::new(csrf::PostUrlencoded),
+        Box::new(csrf::JsonpCallback),
+        // Transport tests
+        Box::new(transport::CleartextHttp),
+        Box::new(transport::TlsConfig),
+        // Auth tests
+        Box::new(auth::AccountEnumeration),
     ]
 }
+
+/// What `tests list` shows about a test
+#[derive(Debug, Clone, Serialize)]
+pub struct TestInfo {
+    pub name: &'static str,
+    pub title: &'static str,
+    pub category: Category,
+    pub severity: Severity,
+    pub cwe: String,
+    pub heavy: bool,
+    pub opt_in: bool,
+    pub description: &'static str,
+}
+
+impl TestInfo {
+    pub fn of(test: &dyn SecurityTest) -> Self {
+        Self {
+            name: test.name(),
+            title: test.title(),
+            category: test.category(),
+            severity: test.severity(),
+            cwe: format!("CWE-{}", test.cwe()),
+            heavy: test.heavy(),
+            opt_in: test.opt_in(),
+            description: test.description(),
+        }
+    }
+}
+
+/// Parse findings written by `scan --output json`.
+///
+/// The scan prints its banner and progress lines on stdout too, so when the
+/// content isn't a bare JSON array every pretty-printed `[ ... ]` block is read.
+pub fn parse_results_json(content: &str) -> anyhow::Result<Vec<TestResult>> {
+    if let Ok(results) = serde_json::from_str::<Vec<TestResult>>(content) {
+        return Ok(results);
+    }
+
+    let mut results = Vec::new();
+    let mut block: Option<String> = None;
+
+    for line in content.lines() {
+        if line == "[" {
+            block = Some(String::from("["));
+        } else if let Some(buf) = block.as_mut() {
+            buf.push('\n');
+            buf.push_str(line);
+            if line == "]" {
+                let parsed: Vec<TestResult> =
+                    serde_json::from_str(buf).context("Failed to parse scan results")?;
+                results.extend(parsed);
+                block = None;
+            }
+        }
+    }
+
+    Ok(results)
+}
+
+/// Findings from several runs as one list: one result per test and endpoint, with the
+/// worst severity any run gave it and the evidence of the most recent run. `runs` go
+/// oldest first. A vulnerable result isn't replaced by a later clean one, so a finding
+/// stays in the report until it's gone from every run.
+pub fn merge_results(runs: Vec<Vec<TestResult>>) -> Vec<TestResult> {
+    let mut merged: Vec<TestResult> = Vec::new();
+    for result in runs.into_iter().flatten() {
+        let existing = merged
+            .iter_mut()
+            .find(|r| r.name == result.name && r.url == result.url);
+        match existing {
+            Some(existing) => {
+                let severity = existing.severity.min(result.severity);
+                if result.vulnerable || !existing.vulnerable {
+                    *existing = result;
+                }
+                existing.severity = severity;
+            }
+            None => merged.push(result),
+        }
+    }
+    merged.sort_by(|a, b| {
+        a.severity
+            .cmp(&b.severity)
+            .then_with(|| a.url.cmp(&b.url))
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    merged
+}
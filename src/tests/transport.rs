@@ -0,0 +1,254 @@
+use super::{Category, Confidence, ScanContext, SecurityTest, Severity, TestResult};
+use crate::http::HttpClient;
+use anyhow::Context;
+use async_trait::async_trait;
+use openssl::ssl::{SslConnector, SslConnectorBuilder, SslMethod, SslStream, SslVerifyMode, SslVersion};
+use openssl::x509::X509VerifyResult;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::Duration;
+use url::Url;
+
+/// How long each TLS handshake of the TLS audit may take, connecting included
+const TLS_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Protocol versions a server shouldn't accept any more, with the `openssl s_client`
+/// flag that asks for them
+const OLD_PROTOCOLS: &[(&str, SslVersion, &str)] = &[
+    ("TLS 1.0", SslVersion::TLS1, "-tls1"),
+    ("TLS 1.1", SslVersion::TLS1_1, "-tls1_1"),
+];
+
+/// Weak cipher classes, as OpenSSL cipher strings. Classes the local OpenSSL was built
+/// without can't be offered, so they go unchecked.
+const WEAK_CIPHERS: &[(&str, &str)] = &[
+    ("RC4", "RC4"),
+    ("3DES", "3DES"),
+    ("NULL", "eNULL"),
+    ("anonymous", "aNULL"),
+    ("export", "EXP"),
+];
+
+// Cleartext HTTP Test
+pub struct CleartextHttp;
+
+#[async_trait]
+impl SecurityTest for CleartextHttp {
+    fn name(&self) -> &'static str { "cleartext_http" }
+    fn title(&self) -> &'static str { "GraphQL over Cleartext HTTP" }
+    fn description(&self) -> &'static str { "HTTPS endpoint also served over plain http" }
+    fn impact(&self) -> &'static str { "Credentials and tokens sent to the http listener can be read on the network" }
+    fn severity(&self) -> Severity { Severity::Low }
+    fn category(&self) -> Category { Category::Transport }
+    fn cwe(&self) -> u32 { 319 }
+    fn depends_on(&self) -> &'static [&'static str] { &["introspection"] }
+
+    async fn run(&self, client: &HttpClient, url: &str, context: &ScanContext) -> anyhow::Result<TestResult> {
+        let skipped = |reason: &str| TestResult {
+            name: self.name().to_string(),
+            url: url.to_string(),
+            title: self.title().to_string(),
+            description: self.description().to_string(),
+            impact: self.impact().to_string(),
+            severity: self.severity(),
+            vulnerable: false,
+            curl_command: reason.to_string(),
+            evidence: None,
+            confidence: Confidence::Heuristic,
+        };
+
+        // Same host, path and port; an implicit 443 becomes an implicit 80
+        let mut plain = Url::parse(url)?;
+        if plain.scheme() != "https" || plain.set_scheme("http").is_err() {
+            return Ok(skipped("Target is not https"));
+        }
+
+        let probe = context.probe(client, url).await;
+        // A redirect to https is the fix, so it's looked at rather than followed
+        let response = match client
+            .without_redirects()?
+            .post_graphql(plain.as_str(), &probe.query(), None, Some(self.name()))
+            .await
+        {
+            Ok(response) => response,
+            Err(_) => return Ok(skipped("Nothing answers over plain http")),
+        };
+
+        let location = response.headers.get("location").filter(|_| (300..400).contains(&response.status));
+        let vulnerable = location.is_none() && (response.has_data() || response.has_errors());
+        let evidence = match location {
+            Some(location) => format!("{} redirects to {}", plain, location),
+            None if vulnerable => format!("{} answers GraphQL (status {})", plain, response.status),
+            None => format!("{} answers with status {}, not GraphQL", plain, response.status),
+        };
+
+        Ok(TestResult {
+            name: self.name().to_string(),
+            url: url.to_string(),
+            title: self.title().to_string(),
+            description: self.description().to_string(),
+            impact: self.impact().to_string(),
+            severity: self.severity(),
+            vulnerable,
+            curl_command: response.curl_command,
+            evidence: Some(evidence),
+            confidence: Confidence::Heuristic,
+        })
+    }
+}
+
+// TLS Configuration Test
+pub struct TlsConfig;
+
+#[async_trait]
+impl SecurityTest for TlsConfig {
+    fn name(&self) -> &'static str { "tls_config" }
+    fn title(&self) -> &'static str { "Weak TLS Configuration" }
+    fn description(&self) -> &'static str { "Outdated TLS versions, weak ciphers or an invalid certificate accepted" }
+    fn impact(&self) -> &'static str { "Traffic to the API can be downgraded, decrypted or intercepted" }
+    fn severity(&self) -> Severity { Severity::Medium }
+    fn category(&self) -> Category { Category::Transport }
+    fn cwe(&self) -> u32 { 326 }
+    fn opt_in(&self) -> bool { true }
+
+    async fn run(&self, _client: &HttpClient, url: &str, _context: &ScanContext) -> anyhow::Result<TestResult> {
+        let target = Url::parse(url)?;
+        let (Some(host), Some(port)) = (target.host_str(), target.port_or_known_default()) else {
+            anyhow::bail!("No host in {}", url);
+        };
+        let host = host.trim_matches(|c| c == '[' || c == ']').to_string();
+
+        let audit = if target.scheme() == "https" {
+            let host = host.clone();
+            Some(tokio::task::spawn_blocking(move || audit_tls(&host, port)).await??)
+        } else {
+            None
+        };
+
+        // Each weakness can be seen again with s_client; the first one is the one to show
+        let verify = |flags: &str| format!("openssl s_client -connect {}:{} {}", host, port, flags).trim_end().to_string();
+        let (vulnerable, curl_command, evidence) = match audit {
+            None => (false, "Target is not https".to_string(), None),
+            Some(audit) => (
+                !audit.weaknesses.is_empty(),
+                verify(audit.weaknesses.first().map_or("", |(_, flags)| flags.as_str())),
+                Some(audit.evidence()),
+            ),
+        };
+
+        Ok(TestResult {
+            name: self.name().to_string(),
+            url: url.to_string(),
+            title: self.title().to_string(),
+            description: self.description().to_string(),
+            impact: self.impact().to_string(),
+            severity: self.severity(),
+            vulnerable,
+            curl_command,
+            evidence,
+            confidence: Confidence::Heuristic,
+        })
+    }
+}
+
+/// What the TLS audit found on a host
+struct TlsAudit {
+    /// Each weakness with the `openssl s_client` flags that show it
+    weaknesses: Vec<(String, String)>,
+    /// Protocol and cipher a current client gets
+    negotiated: String,
+    /// End of the certificate's validity
+    expires: Option<String>,
+}
+
+impl TlsAudit {
+    fn evidence(&self) -> String {
+        let mut parts: Vec<String> = self.weaknesses.iter().map(|(weakness, _)| weakness.clone()).collect();
+        parts.push(format!("negotiates {}", self.negotiated));
+        if let Some(expires) = &self.expires {
+            parts.push(format!("certificate valid until {}", expires));
+        }
+        parts.join("; ")
+    }
+}
+
+/// Handshake with `host` a few times over: as a current client, to look at the
+/// certificate, then offering only each old protocol and each weak cipher class.
+/// Connects directly, not through the scan's proxy.
+fn audit_tls(host: &str, port: u16) -> anyhow::Result<TlsAudit> {
+    let stream = handshake(host, port, |_| Ok(()))?
+        .with_context(|| format!("TLS handshake with {}:{} failed", host, port))?;
+    let ssl = stream.ssl();
+    let negotiated = format!(
+        "{} {}",
+        ssl.version_str(),
+        ssl.current_cipher().map_or("", |c| c.name())
+    );
+    let expires = ssl.peer_certificate().map(|cert| cert.not_after().to_string());
+
+    let mut weaknesses = Vec::new();
+    // Checked against the system's trust store and the host name, expiry included
+    let verified = ssl.verify_result();
+    if verified != X509VerifyResult::OK {
+        weaknesses.push((format!("certificate: {}", verified.error_string()), String::new()));
+    }
+
+    for (name, version, flag) in OLD_PROTOCOLS {
+        let accepted = handshake(host, port, |builder| {
+            builder.set_security_level(0);
+            builder.set_cipher_list("ALL:@SECLEVEL=0")?;
+            builder.set_min_proto_version(Some(*version))?;
+            builder.set_max_proto_version(Some(*version))
+        });
+        if let Ok(Some(_)) = accepted {
+            weaknesses.push((format!("accepts {}", name), flag.to_string()));
+        }
+    }
+
+    for (name, ciphers) in WEAK_CIPHERS {
+        let cipher_list = format!("{}:@SECLEVEL=0", ciphers);
+        let accepted = handshake(host, port, |builder| {
+            builder.set_security_level(0);
+            builder.set_cipher_list(&cipher_list)?;
+            // TLS 1.3 has no weak suites, so offering it could only hide them
+            builder.set_max_proto_version(Some(SslVersion::TLS1_2))
+        });
+        if let Ok(Some(stream)) = accepted {
+            let cipher = stream.ssl().current_cipher().map_or("", |c| c.name());
+            weaknesses.push((
+                format!("accepts {} cipher {}", name, cipher),
+                format!("-tls1_2 -cipher '{}'", cipher_list),
+            ));
+        }
+    }
+
+    Ok(TlsAudit {
+        weaknesses,
+        negotiated,
+        expires,
+    })
+}
+
+/// One handshake with the client set up by `configure`. None when the server turns it
+/// down; an error when the host can't be reached or the local OpenSSL can't offer
+/// what `configure` asks for.
+fn handshake(
+    host: &str,
+    port: u16,
+    configure: impl FnOnce(&mut SslConnectorBuilder) -> Result<(), openssl::error::ErrorStack>,
+) -> anyhow::Result<Option<SslStream<TcpStream>>> {
+    let mut builder = SslConnector::builder(SslMethod::tls_client())?;
+    // The certificate is judged from the verify result, so a bad one doesn't stop the handshake
+    builder.set_verify(SslVerifyMode::NONE);
+    configure(&mut builder)?;
+    let connector = builder.build();
+
+    let address = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .with_context(|| format!("{} doesn't resolve", host))?;
+    let tcp = TcpStream::connect_timeout(&address, TLS_TIMEOUT)?;
+    tcp.set_read_timeout(Some(TLS_TIMEOUT))?;
+    tcp.set_write_timeout(Some(TLS_TIMEOUT))?;
+
+    Ok(connector.connect(host, tcp).ok())
+}
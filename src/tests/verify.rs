@@ -0,0 +1,118 @@
+use super::Baseline;
+use crate::http::HttpClient;
+use std::time::Duration;
+
+/// A payload response this slow ends the impact check, whatever the baseline
+const IMPACT_ABORT: Duration = Duration::from_secs(10);
+
+/// Query a DoS finding is made of, at three increasing sizes (aliases, fields, depth...)
+/// for `--verify-dos`
+pub struct ImpactPayload {
+    sizes: [usize; 3],
+    /// What a size counts, e.g. `aliases`
+    unit: &'static str,
+    query: Box<dyn Fn(usize) -> (String, Option<String>) + Send + Sync>,
+}
+
+impl ImpactPayload {
+    /// `query` builds the payload at a size; `sizes` go smallest first
+    pub fn new(sizes: [usize; 3], unit: &'static str, query: impl Fn(usize) -> String + Send + Sync + 'static) -> Self {
+        Self::with_operation_name(sizes, unit, move |size| (query(size), None))
+    }
+
+    /// [`ImpactPayload::new`] for documents that name the operation to run
+    pub fn with_operation_name(
+        sizes: [usize; 3],
+        unit: &'static str,
+        query: impl Fn(usize) -> (String, Option<String>) + Send + Sync + 'static,
+    ) -> Self {
+        Self {
+            sizes,
+            unit,
+            query: Box::new(query),
+        }
+    }
+}
+
+/// What sending an [`ImpactPayload`] at each size did to the target
+#[derive(Debug, Clone)]
+pub struct ImpactCheck {
+    /// Size and response time of each payload sent, None when it got no reply
+    steps: Vec<(usize, Option<Duration>)>,
+    unit: &'static str,
+    /// What showed the degradation, when something did
+    degraded: Option<String>,
+}
+
+impl ImpactCheck {
+    /// Send `payload` at its sizes, smallest first, each followed by `{ __typename }`
+    /// to see whether the target is still answering normally. Stops at the first size
+    /// that degrades it (past the baseline's slowdown rule, or no reply) and at any
+    /// payload taking over [`IMPACT_ABORT`], so a check never loads the target further
+    /// than needed to show the impact.
+    pub async fn run(client: &HttpClient, url: &str, test: &str, payload: &ImpactPayload, baseline: &Baseline) -> Self {
+        let mut check = Self {
+            steps: Vec::new(),
+            unit: payload.unit,
+            degraded: None,
+        };
+        for size in payload.sizes {
+            let (query, operation_name) = (payload.query)(size);
+            let elapsed = client
+                .post_graphql_operation(url, &query, None, operation_name.as_deref(), Some(test))
+                .await
+                .ok()
+                .map(|response| response.elapsed);
+            check.steps.push((size, elapsed));
+
+            match elapsed {
+                None => check.degraded = Some(format!("no reply at {}", size)),
+                Some(elapsed) if baseline.is_slow(elapsed) => {
+                    check.degraded = Some(format!("{} at {}", baseline.compare(elapsed), size))
+                }
+                Some(_) => {
+                    // Collateral damage: a trivial query stuck behind the payload
+                    match client.post_graphql(url, "query { __typename }", None, Some(test)).await {
+                        Err(_) => check.degraded = Some(format!("__typename got no reply after {}", size)),
+                        Ok(health) if baseline.is_slow(health.elapsed) => {
+                            check.degraded =
+                                Some(format!("__typename took {} after {}", baseline.compare(health.elapsed), size))
+                        }
+                        Ok(_) => {}
+                    }
+                }
+            }
+            if check.degraded.is_some() || elapsed.is_some_and(|elapsed| elapsed >= IMPACT_ABORT) {
+                break;
+            }
+        }
+        check
+    }
+
+    /// Whether the target measurably degraded
+    pub fn verified(&self) -> bool {
+        self.degraded.is_some()
+    }
+
+    /// E.g. `impact check: 3ms at 100 -> 41ms at 1000 -> 1840ms at 5000 aliases,
+    /// degraded: 1840ms, 46.0x the 40ms baseline at 5000`
+    pub fn evidence(&self) -> String {
+        let steps: Vec<String> = self
+            .steps
+            .iter()
+            .map(|(size, elapsed)| match elapsed {
+                Some(elapsed) => format!("{}ms at {}", elapsed.as_millis(), size),
+                None => format!("no reply at {}", size),
+            })
+            .collect();
+        format!(
+            "impact check: {} {}, {}",
+            steps.join(" -> "),
+            self.unit,
+            match &self.degraded {
+                Some(how) => format!("degraded: {}", how),
+                None => "no degradation".to_string(),
+            }
+        )
+    }
+}
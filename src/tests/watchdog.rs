@@ -0,0 +1,127 @@
+use super::Baseline;
+use crate::http::{is_overloaded, HttpClient};
+use std::time::Duration;
+
+/// Share of a test's requests failing (no reply, 429, 502-504) that has the watchdog
+/// look at the target before the next aggressive test
+const ERROR_RATE_LIMIT: f64 = 0.5;
+
+/// Tests with fewer requests than this don't make an error rate
+const ERROR_RATE_SAMPLE: usize = 4;
+
+/// First wait for a degraded target to recover; each next one is twice as long
+const PAUSE: Duration = Duration::from_secs(5);
+
+/// Waits before aggressive tests are given up on the target (5s, 10s, 20s)
+const MAX_PAUSES: u32 = 3;
+
+/// Something the watchdog did, for the scan to log
+#[derive(Debug, Clone)]
+pub enum WatchdogEvent {
+    /// Waiting `wait` for the target to recover before the next aggressive test
+    Paused { reason: String, wait: Duration },
+    /// The target answers normally again
+    Resumed,
+    /// No more aggressive tests on this target
+    Aborted { reason: String },
+}
+
+/// Keeps an eye on the target during a scan: the error rate of each test's requests,
+/// and before every aggressive (heavy) test, how fast `{ __typename }` comes back
+/// against the baseline. A degraded target gets time to recover; one that doesn't
+/// gets no more aggressive tests.
+pub struct Watchdog {
+    baseline: Option<Baseline>,
+    /// Request counters of the client when last looked at
+    sent: usize,
+    failed: usize,
+    /// Why the target looked unwell after the last test, from its error rate
+    struggling: Option<String>,
+    /// Why aggressive tests were stopped, once they were
+    aborted: Option<String>,
+}
+
+impl Watchdog {
+    /// Without a baseline only failures and overload statuses count
+    pub fn new(client: &HttpClient, baseline: Option<Baseline>) -> Self {
+        Self {
+            baseline,
+            sent: client.requests_sent(),
+            failed: client.requests_failed(),
+            struggling: None,
+            aborted: None,
+        }
+    }
+
+    /// Take in how the requests since the last look went, after a test ran
+    pub fn observe(&mut self, client: &HttpClient) {
+        let sent = client.requests_sent() - self.sent;
+        let failed = client.requests_failed() - self.failed;
+        self.sent = client.requests_sent();
+        self.failed = client.requests_failed();
+        if sent >= ERROR_RATE_SAMPLE && failed as f64 / sent as f64 >= ERROR_RATE_LIMIT {
+            self.struggling = Some(format!("{} of {} requests failed or were turned away", failed, sent));
+        }
+    }
+
+    /// Why aggressive tests were stopped, once they were
+    pub fn aborted(&self) -> Option<&str> {
+        self.aborted.as_deref()
+    }
+
+    /// Whether an aggressive test may run now. A target that's struggling or slow gets
+    /// up to [`MAX_PAUSES`] growing waits to recover, after which aggressive tests stop
+    /// for good.
+    pub async fn clear(&mut self, client: &HttpClient, url: &str, mut log: impl FnMut(WatchdogEvent)) -> bool {
+        if self.aborted.is_some() {
+            return false;
+        }
+        let mut reason = match self.struggling.take() {
+            Some(reason) => Some(reason),
+            None => self.health(client, url).await,
+        };
+        let mut paused = false;
+        for pause in 0..MAX_PAUSES {
+            let Some(why) = reason else {
+                break;
+            };
+            let wait = PAUSE * 2u32.pow(pause);
+            log(WatchdogEvent::Paused { reason: why, wait });
+            paused = true;
+            tokio::time::sleep(wait).await;
+            reason = self.health(client, url).await;
+        }
+        // The health checks aren't the next test's requests
+        self.sent = client.requests_sent();
+        self.failed = client.requests_failed();
+
+        match reason {
+            Some(reason) => {
+                self.aborted = Some(reason.clone());
+                log(WatchdogEvent::Aborted { reason });
+                false
+            }
+            None => {
+                if paused {
+                    log(WatchdogEvent::Resumed);
+                }
+                true
+            }
+        }
+    }
+
+    /// Why the target looks degraded, if it does
+    async fn health(&self, client: &HttpClient, url: &str) -> Option<String> {
+        let response = match client.post_graphql(url, "query { __typename }", None, Some("watchdog")).await {
+            Ok(response) => response,
+            Err(_) => return Some("__typename got no reply".to_string()),
+        };
+        if is_overloaded(response.status) {
+            return Some(format!("__typename answered {}", response.status));
+        }
+        let baseline = self.baseline.as_ref()?;
+        baseline
+            .is_slow(response.elapsed)
+            .then(|| format!("__typename took {}", baseline.compare(response.elapsed)))
+    }
+}